@@ -0,0 +1,84 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::{sync::Arc, thread};
+use thingbuf::{SlotLayout, ThingBuf};
+
+/// A message of `SIZE` bytes.
+#[derive(Clone)]
+struct Msg<const SIZE: usize>([u8; SIZE]);
+
+impl<const SIZE: usize> Default for Msg<SIZE> {
+    fn default() -> Self {
+        Self([0; SIZE])
+    }
+}
+
+const LAYOUTS: [(&str, SlotLayout); 2] = [
+    ("Interleaved", SlotLayout::Interleaved),
+    ("Split", SlotLayout::Split),
+];
+
+/// This benchmark compares the interleaved and split slot layouts, by sending
+/// elements of a given size from one thread to another through a `ThingBuf`.
+///
+/// With small elements, the interleaved layout touches a single cache line
+/// per operation. With large elements, the split layout keeps the slot states
+/// that producers and consumers poll on out of the elements' cache lines.
+fn bench_spsc<const SIZE: usize>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("sync/slot_layout/spsc/{}_bytes", SIZE));
+    for size in [1_000, 10_000] {
+        group.throughput(Throughput::Elements(size));
+        for (name, layout) in LAYOUTS {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &i| {
+                b.iter(|| {
+                    let q = Arc::new(ThingBuf::<Msg<SIZE>>::builder(64).layout(layout).build());
+                    let producer = {
+                        let q = q.clone();
+                        thread::spawn(move || {
+                            for n in 0..i {
+                                loop {
+                                    if let Ok(mut slot) = q.push_ref() {
+                                        slot.0[0] = n as u8;
+                                        break;
+                                    }
+                                    thread::yield_now();
+                                }
+                            }
+                        })
+                    };
+                    for _ in 0..i {
+                        loop {
+                            if let Some(val) = q.pop_ref() {
+                                criterion::black_box(val.0[0]);
+                                break;
+                            }
+                            thread::yield_now();
+                        }
+                    }
+                    producer.join().unwrap();
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+/// This benchmark compares the slot layouts when consumers poll a queue that
+/// is almost always empty, which only reads the slot states.
+fn bench_poll_empty(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sync/slot_layout/poll_empty/512_bytes");
+    for (name, layout) in LAYOUTS {
+        group.bench_function(name, |b| {
+            let q = ThingBuf::<Msg<512>>::builder(64).layout(layout).build();
+            b.iter(|| criterion::black_box(q.pop_ref().is_none()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_spsc::<8>,
+    bench_spsc::<512>,
+    bench_poll_empty
+);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::{sync::Arc, thread};
+use thingbuf::ThingBuf;
+
+/// This benchmark measures the cost of pushing and popping an element on a
+/// single thread, with no contention at all.
+///
+/// No thread ever blocks on the queue, so this is dominated by the cost of the
+/// lock-free operations themselves, including any bookkeeping for the
+/// blocking methods that every push and pop pays.
+fn bench_push_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sync/thingbuf/push_pop");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("ThingBuf", |b| {
+        let q = ThingBuf::<usize>::new(64);
+        b.iter(|| {
+            q.push(criterion::black_box(1)).unwrap();
+            criterion::black_box(q.pop())
+        })
+    });
+    group.bench_function("ThingBuf::split", |b| {
+        let (tx, rx) = ThingBuf::<usize>::new(64).split();
+        b.iter(|| {
+            tx.push(criterion::black_box(1)).unwrap();
+            criterion::black_box(rx.pop())
+        })
+    });
+    group.finish();
+}
+
+/// This benchmark sends elements from one thread to another through a
+/// `ThingBuf`, with both threads polling rather than blocking, so that the
+/// producer and consumer contend on the same cache lines.
+///
+/// It compares sharing the `ThingBuf` by reference, where every push and pop
+/// advances the index with a CAS, against the `Producer` and `Consumer`
+/// halves returned by `ThingBuf::split`, which advance it with a plain store.
+fn bench_spsc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sync/thingbuf/spsc_try");
+    for size in [1_000, 10_000] {
+        group.throughput(Throughput::Elements(size));
+        group.bench_with_input(BenchmarkId::new("ThingBuf", size), &size, |b, &i| {
+            b.iter(|| {
+                let q = Arc::new(ThingBuf::<usize>::new(64));
+                let producer = {
+                    let q = q.clone();
+                    thread::spawn(move || {
+                        for n in 0..i {
+                            while q.push(n as usize).is_err() {
+                                thread::yield_now();
+                            }
+                        }
+                    })
+                };
+                for _ in 0..i {
+                    loop {
+                        if let Some(val) = q.pop() {
+                            criterion::black_box(val);
+                            break;
+                        }
+                        thread::yield_now();
+                    }
+                }
+                producer.join().unwrap();
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("ThingBuf::split", size), &size, |b, &i| {
+            b.iter(|| {
+                let (tx, rx) = ThingBuf::<usize>::new(64).split();
+                let producer = thread::spawn(move || {
+                    for n in 0..i {
+                        while tx.push(n as usize).is_err() {
+                            thread::yield_now();
+                        }
+                    }
+                });
+                for _ in 0..i {
+                    loop {
+                        if let Some(val) = rx.pop() {
+                            criterion::black_box(val);
+                            break;
+                        }
+                        thread::yield_now();
+                    }
+                }
+                producer.join().unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_push_pop, bench_spsc);
+criterion_main!(benches);
@@ -0,0 +1,6 @@
+/* Memory layout of an STM32F411, a typical thumbv7em-none-eabihf part. */
+MEMORY
+{
+  FLASH : ORIGIN = 0x08000000, LENGTH = 512K
+  RAM : ORIGIN = 0x20000000, LENGTH = 128K
+}
@@ -0,0 +1,72 @@
+//! Sends messages from an interrupt handler to the main thread over a
+//! [`StaticChannel`], on a Cortex-M microcontroller.
+//!
+//! With the "embassy" feature flag, the channel's internal locks are held
+//! inside a critical section, so the `SysTick` handler below can never preempt
+//! `main` while it holds a lock, and then spin on that lock forever.
+//!
+//! This example is built for `thumbv7em-none-eabihf` in CI:
+//!
+//! ```sh
+//! cd examples/embassy-static
+//! cargo build
+//! ```
+#![no_std]
+#![no_main]
+
+use cortex_m::peripheral::SCB;
+use cortex_m_rt::{entry, exception};
+use panic_halt as _;
+use thingbuf::mpsc::{errors::TryRecvError, StaticChannel, StaticReceiver, StaticSender};
+
+static CHANNEL: StaticChannel<u32, 8> = StaticChannel::new();
+
+/// The sender used by the `SysTick` handler.
+///
+/// A `StaticSender` can be shared by reference, since sending only requires
+/// `&self`. It is stored in a `critical_section::Mutex` so that `main` can hand
+/// it to the interrupt handler after splitting the channel.
+static TX: critical_section::Mutex<core::cell::RefCell<Option<StaticSender<u32>>>> =
+    critical_section::Mutex::new(core::cell::RefCell::new(None));
+
+#[entry]
+fn main() -> ! {
+    let (tx, rx) = CHANNEL.split();
+    critical_section::with(|cs| *TX.borrow_ref_mut(cs) = Some(tx));
+
+    for i in 0..4 {
+        // Pend the `SysTick` exception, which preempts `main` and sends `i`.
+        SCB::set_pendst();
+        cortex_m::asm::isb();
+        assert_eq!(recv(&rx), i);
+    }
+
+    // Dropping the sender closes the channel.
+    critical_section::with(|cs| TX.borrow_ref_mut(cs).take());
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
+
+fn recv(rx: &StaticReceiver<u32>) -> u32 {
+    loop {
+        match rx.try_recv() {
+            Ok(msg) => return msg,
+            Err(TryRecvError::Empty) => cortex_m::asm::nop(),
+            Err(e) => panic!("channel error: {:?}", e),
+        }
+    }
+}
+
+#[exception]
+fn SysTick() {
+    static mut NEXT: u32 = 0;
+    critical_section::with(|cs| {
+        if let Some(tx) = TX.borrow_ref(cs).as_ref() {
+            tx.try_send(*NEXT).expect("channel should have capacity");
+            *NEXT += 1;
+        }
+    });
+}
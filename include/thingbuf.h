@@ -0,0 +1,170 @@
+#ifndef THINGBUF_H
+#define THINGBUF_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of a `thingbuf` FFI operation.
+ */
+typedef enum ThingbufStatus {
+  /**
+   * The operation completed successfully.
+   */
+  THINGBUF_STATUS_OK = 0,
+  /**
+   * The frame could not be sent because the channel is at capacity.
+   */
+  THINGBUF_STATUS_FULL = 1,
+  /**
+   * No frame could be received because the channel is empty.
+   */
+  THINGBUF_STATUS_EMPTY = 2,
+  /**
+   * The channel is closed.
+   */
+  THINGBUF_STATUS_CLOSED = 3,
+  /**
+   * The next frame is larger than the provided buffer.
+   *
+   * The frame's length is written to the `out_len` argument, and the frame
+   * remains in the receiver, so that the receive can be retried with a
+   * larger buffer.
+   */
+  THINGBUF_STATUS_BUFFER_TOO_SMALL = 4,
+  /**
+   * A required pointer argument was null.
+   */
+  THINGBUF_STATUS_NULL_POINTER = 5,
+  /**
+   * The requested channel capacity was zero, or greater than
+   * [`MAX_CAPACITY`](crate::MAX_CAPACITY).
+   */
+  THINGBUF_STATUS_INVALID_CAPACITY = 6,
+} ThingbufStatus;
+
+/**
+ * An opaque handle to the receiving side of a byte frame channel.
+ */
+typedef struct ThingbufReceiver ThingbufReceiver;
+
+/**
+ * An opaque handle to the sending side of a byte frame channel.
+ */
+typedef struct ThingbufSender ThingbufSender;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a new byte frame channel with space for `capacity` frames.
+ *
+ * On success, the sender and receiver handles are written to `tx_out` and
+ * `rx_out`, and `ThingbufStatus::Ok` is returned. If `capacity` is zero or
+ * greater than `MAX_CAPACITY`, `ThingbufStatus::InvalidCapacity` is returned.
+ *
+ * # Safety
+ *
+ * `tx_out` and `rx_out` must be valid for writes.
+ */
+ThingbufStatus thingbuf_channel_new(size_t capacity,
+                                    ThingbufSender **tx_out,
+                                    ThingbufReceiver **rx_out);
+
+/**
+ * Returns a new handle to the same channel as `tx`, or null if `tx` is null.
+ *
+ * # Safety
+ *
+ * `tx` must be null or a live sender handle.
+ */
+ThingbufSender *thingbuf_sender_clone(const ThingbufSender *tx);
+
+/**
+ * Releases a sender handle. When every sender handle for a channel has been
+ * released, the channel is closed.
+ *
+ * # Safety
+ *
+ * `tx` must be null or a live sender handle, and must not be used again.
+ */
+void thingbuf_sender_free(ThingbufSender *tx);
+
+/**
+ * Releases a receiver handle, closing the channel.
+ *
+ * # Safety
+ *
+ * `rx` must be null or a live receiver handle, and must not be used again.
+ */
+void thingbuf_receiver_free(ThingbufReceiver *rx);
+
+/**
+ * Sends the `len` bytes at `data` as a single frame, blocking the current
+ * thread until there is capacity in the channel.
+ *
+ * Returns `ThingbufStatus::Closed` if the receiver has been released.
+ *
+ * # Safety
+ *
+ * `tx` must be a live sender handle, and `data` must be valid for reads of
+ * `len` bytes. `data` may be null if `len` is zero.
+ */
+ThingbufStatus thingbuf_send(const ThingbufSender *tx, const uint8_t *data, size_t len);
+
+/**
+ * Sends the `len` bytes at `data` as a single frame, if there is capacity
+ * in the channel.
+ *
+ * Returns `ThingbufStatus::Full` if the channel is at capacity, or
+ * `ThingbufStatus::Closed` if the receiver has been released.
+ *
+ * # Safety
+ *
+ * `tx` must be a live sender handle, and `data` must be valid for reads of
+ * `len` bytes. `data` may be null if `len` is zero.
+ */
+ThingbufStatus thingbuf_try_send(const ThingbufSender *tx, const uint8_t *data, size_t len);
+
+/**
+ * Receives the next frame into the `cap` bytes at `buf`, blocking the
+ * current thread until a frame is sent.
+ *
+ * On success, the frame's length is written to `out_len`. If the frame is
+ * larger than `cap`, its length is written to `out_len`, the frame is kept,
+ * and `ThingbufStatus::BufferTooSmall` is returned. Returns
+ * `ThingbufStatus::Closed` once every sender has been released and all
+ * frames have been received.
+ *
+ * # Safety
+ *
+ * `rx` must be a live receiver handle that is not being used by any other
+ * thread, `buf` must be valid for writes of `cap` bytes (or null if `cap`
+ * is zero), and `out_len` must be valid for writes.
+ */
+ThingbufStatus thingbuf_recv(ThingbufReceiver *rx, uint8_t *buf, size_t cap, size_t *out_len);
+
+/**
+ * Receives the next frame into the `cap` bytes at `buf`, if one is
+ * available.
+ *
+ * Behaves like [`thingbuf_recv`], but returns `ThingbufStatus::Empty`
+ * rather than blocking if no frame is available.
+ *
+ * # Safety
+ *
+ * See [`thingbuf_recv`].
+ */
+ThingbufStatus thingbuf_try_recv(ThingbufReceiver *rx, uint8_t *buf, size_t cap, size_t *out_len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* THINGBUF_H */
@@ -0,0 +1,460 @@
+//! A C-compatible interface for sending byte frames through a channel.
+//!
+//! This module provides `extern "C"` functions for creating a [blocking]
+//! channel of byte frames, and for sending and receiving frames on it, so that
+//! producer threads written in C or C++ can feed a Rust consumer (or vice
+//! versa) without a hand-written shim. A matching header, `include/thingbuf.h`,
+//! is distributed with the crate; it can be regenerated with [`cbindgen`] using
+//! the `cbindgen.toml` file in the crate's root.
+//!
+//! Each frame is stored in a `Vec<u8>` that is reused by the channel, so
+//! sending a frame does not allocate once the channel has warmed up.
+//!
+//! # Handles
+//!
+//! A channel is represented by two opaque handles: a [`ThingbufSender`] and a
+//! [`ThingbufReceiver`]. Both may be created by [`thingbuf_channel_new`], or
+//! by Rust code from an existing [`blocking::Sender`] or
+//! [`blocking::Receiver`], and passed across the FFI boundary with
+//! [`ThingbufSender::into_raw`] and [`ThingbufReceiver::into_raw`].
+//!
+//! A sender handle may be used by any number of threads concurrently, and may
+//! be duplicated with [`thingbuf_sender_clone`]. A receiver handle must only be
+//! used by one thread at a time. Every handle must be released exactly once,
+//! with [`thingbuf_sender_free`] or [`thingbuf_receiver_free`].
+//!
+//! # Examples
+//!
+//! A Rust consumer receiving frames sent by a C producer:
+//!
+//! ```
+//! use thingbuf::{ffi::{self, ThingbufSender, ThingbufStatus}, mpsc::blocking};
+//!
+//! let (tx, rx) = blocking::channel::<Vec<u8>>(8);
+//!
+//! // This pointer may be handed to C code...
+//! let tx = ThingbufSender::new(tx).into_raw();
+//!
+//! // ...which sends frames using the `extern "C"` functions.
+//! let frame = b"hello from C";
+//! unsafe {
+//!     assert_eq!(ffi::thingbuf_send(tx, frame.as_ptr(), frame.len()), ThingbufStatus::Ok);
+//!     ffi::thingbuf_sender_free(tx);
+//! }
+//!
+//! assert_eq!(rx.recv().as_deref(), Some(&frame[..]));
+//! assert_eq!(rx.recv(), None);
+//! ```
+//!
+//! [blocking]: crate::mpsc::blocking
+//! [`cbindgen`]: https://github.com/eqrion/cbindgen
+use crate::{
+    mpsc::{
+        blocking::{self, RecvRef, SendRef},
+        errors::{Closed, TryRecvError, TrySendError},
+    },
+    MAX_CAPACITY,
+};
+use std::{fmt, ptr, slice};
+
+/// The result of a `thingbuf` FFI operation.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ThingbufStatus {
+    /// The operation completed successfully.
+    Ok = 0,
+    /// The frame could not be sent because the channel is at capacity.
+    Full = 1,
+    /// No frame could be received because the channel is empty.
+    Empty = 2,
+    /// The channel is closed.
+    Closed = 3,
+    /// The next frame is larger than the provided buffer.
+    ///
+    /// The frame's length is written to the `out_len` argument, and the frame
+    /// remains in the receiver, so that the receive can be retried with a
+    /// larger buffer.
+    BufferTooSmall = 4,
+    /// A required pointer argument was null.
+    NullPointer = 5,
+    /// The requested channel capacity was zero, or greater than
+    /// [`MAX_CAPACITY`].
+    InvalidCapacity = 6,
+}
+
+/// An opaque handle to the sending side of a byte frame channel.
+pub struct ThingbufSender {
+    tx: blocking::Sender<Vec<u8>>,
+}
+
+/// An opaque handle to the receiving side of a byte frame channel.
+pub struct ThingbufReceiver {
+    rx: blocking::Receiver<Vec<u8>>,
+    /// A frame that was received but did not fit in the caller's buffer.
+    pending: Option<Vec<u8>>,
+}
+
+// === impl ThingbufSender ===
+
+impl ThingbufSender {
+    /// Wraps a [`blocking::Sender`] in a handle that may be passed to C.
+    #[must_use]
+    pub fn new(tx: blocking::Sender<Vec<u8>>) -> Self {
+        Self { tx }
+    }
+
+    /// Moves this handle to the heap, returning a pointer that may be passed
+    /// to the `extern "C"` functions in this module.
+    ///
+    /// The returned pointer must eventually be released with
+    /// [`thingbuf_sender_free`].
+    #[must_use]
+    pub fn into_raw(self) -> *mut Self {
+        Box::into_raw(Box::new(self))
+    }
+
+    /// Takes back ownership of a handle returned by [`into_raw`] or by one
+    /// of the `extern "C"` functions in this module.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by one of those functions, and must not
+    /// be used again after this function is called.
+    ///
+    /// [`into_raw`]: Self::into_raw
+    pub unsafe fn from_raw(ptr: *mut Self) -> Self {
+        *Box::from_raw(ptr)
+    }
+
+    /// Returns the wrapped [`blocking::Sender`].
+    #[must_use]
+    pub fn into_inner(self) -> blocking::Sender<Vec<u8>> {
+        self.tx
+    }
+}
+
+impl fmt::Debug for ThingbufSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThingbufSender")
+            .field("tx", &self.tx)
+            .finish()
+    }
+}
+
+// === impl ThingbufReceiver ===
+
+impl ThingbufReceiver {
+    /// Wraps a [`blocking::Receiver`] in a handle that may be passed to C.
+    #[must_use]
+    pub fn new(rx: blocking::Receiver<Vec<u8>>) -> Self {
+        Self { rx, pending: None }
+    }
+
+    /// Moves this handle to the heap, returning a pointer that may be passed
+    /// to the `extern "C"` functions in this module.
+    ///
+    /// The returned pointer must eventually be released with
+    /// [`thingbuf_receiver_free`].
+    #[must_use]
+    pub fn into_raw(self) -> *mut Self {
+        Box::into_raw(Box::new(self))
+    }
+
+    /// Takes back ownership of a handle returned by [`into_raw`] or by one
+    /// of the `extern "C"` functions in this module.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by one of those functions, and must not
+    /// be used again after this function is called.
+    ///
+    /// [`into_raw`]: Self::into_raw
+    pub unsafe fn from_raw(ptr: *mut Self) -> Self {
+        *Box::from_raw(ptr)
+    }
+
+    /// Returns the wrapped [`blocking::Receiver`].
+    ///
+    /// If a frame was received but was too large for the caller's buffer, it
+    /// is discarded.
+    #[must_use]
+    pub fn into_inner(self) -> blocking::Receiver<Vec<u8>> {
+        self.rx
+    }
+}
+
+impl fmt::Debug for ThingbufReceiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThingbufReceiver")
+            .field("rx", &self.rx)
+            .field("pending", &self.pending.as_ref().map(Vec::len))
+            .finish()
+    }
+}
+
+// === extern "C" functions ===
+
+/// Creates a new byte frame channel with space for `capacity` frames.
+///
+/// On success, the sender and receiver handles are written to `tx_out` and
+/// `rx_out`, and `ThingbufStatus::Ok` is returned. If `capacity` is zero or
+/// greater than `MAX_CAPACITY`, `ThingbufStatus::InvalidCapacity` is returned.
+///
+/// # Safety
+///
+/// `tx_out` and `rx_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn thingbuf_channel_new(
+    capacity: usize,
+    tx_out: *mut *mut ThingbufSender,
+    rx_out: *mut *mut ThingbufReceiver,
+) -> ThingbufStatus {
+    if tx_out.is_null() || rx_out.is_null() {
+        return ThingbufStatus::NullPointer;
+    }
+    if capacity == 0 || capacity > MAX_CAPACITY {
+        return ThingbufStatus::InvalidCapacity;
+    }
+    let (tx, rx) = blocking::channel(capacity);
+    *tx_out = ThingbufSender::new(tx).into_raw();
+    *rx_out = ThingbufReceiver::new(rx).into_raw();
+    ThingbufStatus::Ok
+}
+
+/// Returns a new handle to the same channel as `tx`, or null if `tx` is null.
+///
+/// # Safety
+///
+/// `tx` must be null or a live sender handle.
+#[no_mangle]
+pub unsafe extern "C" fn thingbuf_sender_clone(tx: *const ThingbufSender) -> *mut ThingbufSender {
+    match tx.as_ref() {
+        Some(tx) => ThingbufSender::new(tx.tx.clone()).into_raw(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Releases a sender handle. When every sender handle for a channel has been
+/// released, the channel is closed.
+///
+/// # Safety
+///
+/// `tx` must be null or a live sender handle, and must not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn thingbuf_sender_free(tx: *mut ThingbufSender) {
+    if !tx.is_null() {
+        drop(ThingbufSender::from_raw(tx));
+    }
+}
+
+/// Releases a receiver handle, closing the channel.
+///
+/// # Safety
+///
+/// `rx` must be null or a live receiver handle, and must not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn thingbuf_receiver_free(rx: *mut ThingbufReceiver) {
+    if !rx.is_null() {
+        drop(ThingbufReceiver::from_raw(rx));
+    }
+}
+
+/// Sends the `len` bytes at `data` as a single frame, blocking the current
+/// thread until there is capacity in the channel.
+///
+/// Returns `ThingbufStatus::Closed` if the receiver has been released.
+///
+/// # Safety
+///
+/// `tx` must be a live sender handle, and `data` must be valid for reads of
+/// `len` bytes. `data` may be null if `len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn thingbuf_send(
+    tx: *const ThingbufSender,
+    data: *const u8,
+    len: usize,
+) -> ThingbufStatus {
+    let (tx, data) = match sender_args(tx, data, len) {
+        Ok(args) => args,
+        Err(status) => return status,
+    };
+    match tx.tx.send_ref() {
+        Ok(frame) => fill_frame(frame, data),
+        Err(Closed(())) => ThingbufStatus::Closed,
+    }
+}
+
+/// Sends the `len` bytes at `data` as a single frame, if there is capacity
+/// in the channel.
+///
+/// Returns `ThingbufStatus::Full` if the channel is at capacity, or
+/// `ThingbufStatus::Closed` if the receiver has been released.
+///
+/// # Safety
+///
+/// `tx` must be a live sender handle, and `data` must be valid for reads of
+/// `len` bytes. `data` may be null if `len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn thingbuf_try_send(
+    tx: *const ThingbufSender,
+    data: *const u8,
+    len: usize,
+) -> ThingbufStatus {
+    let (tx, data) = match sender_args(tx, data, len) {
+        Ok(args) => args,
+        Err(status) => return status,
+    };
+    match tx.tx.try_send_ref() {
+        Ok(frame) => fill_frame(frame, data),
+        Err(TrySendError::Full(())) => ThingbufStatus::Full,
+        Err(_) => ThingbufStatus::Closed,
+    }
+}
+
+/// Receives the next frame into the `cap` bytes at `buf`, blocking the
+/// current thread until a frame is sent.
+///
+/// On success, the frame's length is written to `out_len`. If the frame is
+/// larger than `cap`, its length is written to `out_len`, the frame is kept,
+/// and `ThingbufStatus::BufferTooSmall` is returned. Returns
+/// `ThingbufStatus::Closed` once every sender has been released and all
+/// frames have been received.
+///
+/// # Safety
+///
+/// `rx` must be a live receiver handle that is not being used by any other
+/// thread, `buf` must be valid for writes of `cap` bytes (or null if `cap`
+/// is zero), and `out_len` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn thingbuf_recv(
+    rx: *mut ThingbufReceiver,
+    buf: *mut u8,
+    cap: usize,
+    out_len: *mut usize,
+) -> ThingbufStatus {
+    let ThingbufReceiver { rx, pending } = match receiver_args(rx, buf, cap, out_len) {
+        Ok(rx) => rx,
+        Err(status) => return status,
+    };
+    if pending.is_some() {
+        return copy_pending(pending, buf, cap, out_len);
+    }
+    match rx.recv_ref() {
+        Some(frame) => copy_frame(pending, frame, buf, cap, out_len),
+        None => ThingbufStatus::Closed,
+    }
+}
+
+/// Receives the next frame into the `cap` bytes at `buf`, if one is
+/// available.
+///
+/// Behaves like [`thingbuf_recv`], but returns `ThingbufStatus::Empty`
+/// rather than blocking if no frame is available.
+///
+/// # Safety
+///
+/// See [`thingbuf_recv`].
+#[no_mangle]
+pub unsafe extern "C" fn thingbuf_try_recv(
+    rx: *mut ThingbufReceiver,
+    buf: *mut u8,
+    cap: usize,
+    out_len: *mut usize,
+) -> ThingbufStatus {
+    let ThingbufReceiver { rx, pending } = match receiver_args(rx, buf, cap, out_len) {
+        Ok(rx) => rx,
+        Err(status) => return status,
+    };
+    if pending.is_some() {
+        return copy_pending(pending, buf, cap, out_len);
+    }
+    match rx.try_recv_ref() {
+        Ok(frame) => copy_frame(pending, frame, buf, cap, out_len),
+        Err(TryRecvError::Empty) => ThingbufStatus::Empty,
+        Err(_) => ThingbufStatus::Closed,
+    }
+}
+
+// === helpers ===
+
+unsafe fn sender_args<'a>(
+    tx: *const ThingbufSender,
+    data: *const u8,
+    len: usize,
+) -> Result<(&'a ThingbufSender, &'a [u8]), ThingbufStatus> {
+    let tx = tx.as_ref().ok_or(ThingbufStatus::NullPointer)?;
+    if len == 0 {
+        return Ok((tx, &[]));
+    }
+    if data.is_null() {
+        return Err(ThingbufStatus::NullPointer);
+    }
+    Ok((tx, slice::from_raw_parts(data, len)))
+}
+
+unsafe fn receiver_args<'a>(
+    rx: *mut ThingbufReceiver,
+    buf: *mut u8,
+    cap: usize,
+    out_len: *mut usize,
+) -> Result<&'a mut ThingbufReceiver, ThingbufStatus> {
+    if out_len.is_null() || (buf.is_null() && cap > 0) {
+        return Err(ThingbufStatus::NullPointer);
+    }
+    rx.as_mut().ok_or(ThingbufStatus::NullPointer)
+}
+
+fn fill_frame(mut frame: SendRef<'_, Vec<u8>>, data: &[u8]) -> ThingbufStatus {
+    frame.clear();
+    frame.extend_from_slice(data);
+    ThingbufStatus::Ok
+}
+
+/// Copies `frame` into `buf`, or stashes it in `pending` if it doesn't fit.
+unsafe fn copy_frame(
+    pending: &mut Option<Vec<u8>>,
+    frame: RecvRef<'_, Vec<u8>>,
+    buf: *mut u8,
+    cap: usize,
+    out_len: *mut usize,
+) -> ThingbufStatus {
+    let status = copy_bytes(&frame, buf, cap, out_len);
+    if status == ThingbufStatus::BufferTooSmall {
+        *pending = Some(frame.to_vec());
+    }
+    status
+}
+
+/// Copies a frame previously stashed by `copy_frame` into `buf`, if it now
+/// fits.
+unsafe fn copy_pending(
+    pending: &mut Option<Vec<u8>>,
+    buf: *mut u8,
+    cap: usize,
+    out_len: *mut usize,
+) -> ThingbufStatus {
+    let status = match pending {
+        Some(frame) => copy_bytes(frame, buf, cap, out_len),
+        None => ThingbufStatus::Empty,
+    };
+    if status == ThingbufStatus::Ok {
+        *pending = None;
+    }
+    status
+}
+
+unsafe fn copy_bytes(
+    frame: &[u8],
+    buf: *mut u8,
+    cap: usize,
+    out_len: *mut usize,
+) -> ThingbufStatus {
+    *out_len = frame.len();
+    if frame.len() > cap {
+        return ThingbufStatus::BufferTooSmall;
+    }
+    if !frame.is_empty() {
+        ptr::copy_nonoverlapping(frame.as_ptr(), buf, frame.len());
+    }
+    ThingbufStatus::Ok
+}
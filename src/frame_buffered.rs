@@ -0,0 +1,300 @@
+use crate::{
+    loom::atomic::{AtomicBool, AtomicUsize, Ordering::*},
+    recycling::{self, Recycle},
+    util::Backoff,
+    Full, Ref, ThingBuf,
+};
+use core::fmt;
+
+/// A double-buffered command queue, for game loops and simulations that
+/// collect commands during one frame and apply them during the next.
+///
+/// A `FrameBuffered` queue has two internal [`ThingBuf`]s. Any number of
+/// threads may [`push`] commands into the *current* frame's buffer. Once per
+/// frame, the consumer calls [`swap`], which atomically flips the buffers:
+/// commands pushed after the swap go into the other buffer, while the
+/// returned [`Frame`] iterates over the commands pushed before it.
+///
+/// A push that is in progress when the buffers are flipped always lands in
+/// the frame that is being swapped out, so a command is never split across
+/// frames or pushed into a frame that is being read.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::FrameBuffered;
+///
+/// // Each command moves an entity by some distance.
+/// let commands = FrameBuffered::<(&str, i32)>::new(16);
+/// commands.push(("player", 1)).unwrap();
+/// commands.push(("enemy", -2)).unwrap();
+///
+/// // Flip the buffers. Commands pushed from now on belong to the next frame.
+/// let frame = commands.swap();
+/// commands.push(("player", 3)).unwrap();
+///
+/// let applied = frame.collect::<Vec<_>>();
+/// assert_eq!(applied, vec![("player", 1), ("enemy", -2)]);
+///
+/// let applied = commands.swap().collect::<Vec<_>>();
+/// assert_eq!(applied, vec![("player", 3)]);
+/// ```
+///
+/// [`push`]: Self::push
+/// [`swap`]: Self::swap
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct FrameBuffered<T, R = recycling::DefaultRecycle> {
+    bufs: [ThingBuf<T, R>; 2],
+    /// The index of the buffer that commands are pushed into.
+    current: AtomicUsize,
+    /// The number of pushes in progress into each buffer.
+    writers: [AtomicUsize; 2],
+    /// Set while a [`Frame`] is being read.
+    reading: AtomicBool,
+    /// The number of times the buffers have been swapped.
+    frames: AtomicUsize,
+}
+
+/// The commands pushed into a [`FrameBuffered`] queue during one frame.
+///
+/// A `Frame` is an [`Iterator`] over the frame's commands, in the order they
+/// were pushed. Commands that are not consumed are discarded when the `Frame`
+/// is dropped.
+///
+/// This type is returned by the [`FrameBuffered::swap`] method.
+pub struct Frame<'a, T, R = recycling::DefaultRecycle> {
+    buf: &'a ThingBuf<T, R>,
+    reading: &'a AtomicBool,
+    number: usize,
+}
+
+// === impl FrameBuffered ===
+
+impl<T: Default + Clone> FrameBuffered<T> {
+    /// Returns a new `FrameBuffered` queue with space for `capacity` commands
+    /// in each frame.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_recycle(capacity, recycling::DefaultRecycle::new())
+    }
+}
+
+impl<T, R> FrameBuffered<T, R>
+where
+    R: Recycle<T> + Clone,
+{
+    /// Returns a new `FrameBuffered` queue with space for `capacity` commands
+    /// in each frame, and the provided [recycling policy].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`. This value
+    /// represents the highest power of two that can be expressed by a `usize`, excluding the most
+    /// significant bit.
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    pub fn with_recycle(capacity: usize, recycle: R) -> Self {
+        Self {
+            bufs: [
+                ThingBuf::with_recycle(capacity, recycle.clone()),
+                ThingBuf::with_recycle(capacity, recycle),
+            ],
+            current: AtomicUsize::new(0),
+            writers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            reading: AtomicBool::new(false),
+            frames: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<T, R> FrameBuffered<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Pushes a command into the current frame.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the command was enqueued
+    /// - `Err(`[`Full`]`)` containing the command if the current frame is at
+    ///   capacity
+    pub fn push(&self, val: T) -> Result<(), Full<T>> {
+        self.with_current(|buf| buf.push(val))
+    }
+
+    /// Reserves a slot in the current frame, and calls `f` to write the
+    /// command in place, reusing the slot's existing allocations.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(U)` with the value returned by `f`, if the command was enqueued
+    /// - `Err(`[`Full`]`)` if the current frame is at capacity, in which case
+    ///   `f` is not called
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::FrameBuffered;
+    ///
+    /// let log = FrameBuffered::<String>::new(4);
+    /// log.push_with(|line| line.push_str("player spawned")).unwrap();
+    ///
+    /// let mut frame = log.swap();
+    /// assert_eq!(frame.next().as_deref(), Some("player spawned"));
+    /// ```
+    pub fn push_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Result<U, Full> {
+        self.with_current(|buf| buf.push_with(f))
+    }
+
+    /// Flips the buffers, returning a [`Frame`] over the commands that were
+    /// pushed since the last swap.
+    ///
+    /// Commands pushed after the buffers are flipped belong to the next
+    /// frame. If another thread is pushing a command into the frame being
+    /// swapped out, this waits for that push to complete.
+    ///
+    /// # Panics
+    ///
+    /// If the [`Frame`] returned by a previous call to `swap` has not been
+    /// dropped yet. A `FrameBuffered` queue has a single consumer, which must
+    /// finish reading one frame before starting the next.
+    pub fn swap(&self) -> Frame<'_, T, R> {
+        assert!(
+            !self.reading.swap(true, Acquire),
+            "`FrameBuffered::swap` called while the previous frame is still being read"
+        );
+
+        let prev = self.current.load(Relaxed);
+        self.current.store(prev ^ 1, SeqCst);
+
+        // Wait for pushes that started before the flip to finish, so that the
+        // frame does not change while it is being read.
+        let mut backoff = Backoff::new();
+        while test_dbg!(self.writers[prev].load(SeqCst)) != 0 {
+            backoff.spin_yield();
+        }
+
+        let number = self.frames.fetch_add(1, Relaxed);
+        Frame {
+            buf: &self.bufs[prev],
+            reading: &self.reading,
+            number,
+        }
+    }
+
+    /// Calls `f` with the buffer for the current frame, ensuring that a
+    /// concurrent [`swap`](Self::swap) waits for it to return.
+    fn with_current<U>(&self, f: impl FnOnce(&ThingBuf<T, R>) -> U) -> U {
+        loop {
+            let idx = self.current.load(SeqCst);
+            self.writers[idx].fetch_add(1, SeqCst);
+            // If the buffers were flipped before this push was registered,
+            // the swap may not have waited for it, so retry with the new
+            // buffer.
+            if test_dbg!(self.current.load(SeqCst)) == idx {
+                let res = f(&self.bufs[idx]);
+                self.writers[idx].fetch_sub(1, Release);
+                return res;
+            }
+            self.writers[idx].fetch_sub(1, Release);
+        }
+    }
+}
+
+impl<T, R> FrameBuffered<T, R> {
+    /// Returns the number of commands each frame has space for.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.bufs[0].capacity()
+    }
+
+    /// Returns the number of commands that have been pushed into the current
+    /// frame.
+    pub fn len(&self) -> usize {
+        self.bufs[self.current.load(Acquire)].len()
+    }
+
+    /// Returns `true` if no commands have been pushed into the current
+    /// frame.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of times the buffers have been
+    /// [swapped](Self::swap).
+    pub fn frames(&self) -> usize {
+        self.frames.load(Relaxed)
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for FrameBuffered<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameBuffered")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .field("frames", &self.frames())
+            .field("recycle", &self.bufs[0].recycle)
+            .finish()
+    }
+}
+
+// === impl Frame ===
+
+impl<'a, T, R> Frame<'a, T, R> {
+    /// Returns the number of this frame, counting from 0 for the frame
+    /// returned by the first [`swap`](FrameBuffered::swap).
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Returns the number of commands remaining in this frame.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if no commands remain in this frame.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Dequeues the next command in this frame *by reference*, so that it
+    /// may be read without moving it out of its slot.
+    ///
+    /// Returns `None` once every command in the frame has been read.
+    pub fn pop_ref(&mut self) -> Option<Ref<'a, T>> {
+        self.buf.core.pop_ref(&self.buf.slots).ok()
+    }
+}
+
+impl<T, R> Iterator for Frame<'_, T, R>
+where
+    R: Recycle<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.buf.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<T, R> Drop for Frame<'_, T, R> {
+    fn drop(&mut self) {
+        // Discard the rest of the frame, so that the buffer is empty when it
+        // becomes the current frame again.
+        while self.pop_ref().is_some() {}
+        self.reading.store(false, Release);
+    }
+}
+
+impl<T, R> fmt::Debug for Frame<'_, T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Frame")
+            .field("number", &self.number)
+            .field("len", &self.len())
+            .finish()
+    }
+}
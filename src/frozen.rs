@@ -0,0 +1,128 @@
+#[cfg(all(feature = "std", not(all(loom, test))))]
+use crate::{mpsc::blocking::park::Unparker, wait::WaitQueue};
+use crate::{Core, Slots};
+use core::fmt;
+
+/// A read-only view of the elements in a queue, for inspecting it from a
+/// panic hook or other fatal-error path.
+///
+/// A `Frozen` view is an [`Iterator`] over copies of the elements that were
+/// in the queue when it was created, in first-in, first-out order. Unlike
+/// popping elements, iterating over a `Frozen` view never waits for another
+/// thread and never allocates, so it never holds up a push or pop on another
+/// thread for longer than it takes to copy one element. This makes it safe to
+/// use when the program is in an unknown state, such as when dumping the last
+/// events that were queued before a crash.
+///
+/// Other threads may keep pushing and popping elements while the view is
+/// iterated. Each element is pinned in its slot while it is copied out, so
+/// that it cannot be popped or overwritten while it is being read, and a pop
+/// that reaches it in the meantime treats it as not yet pushed. The element at
+/// the head of the queue may be popped at any moment, so it cannot be pinned,
+/// and is skipped, as are elements that are popped, or that are being pushed
+/// or popped, when the iterator reaches them. Elements pushed after the view
+/// was created are not included. Elements are copied, so iterating requires
+/// `T: Copy`.
+///
+/// This type is returned by the [`ThingBuf::freeze`] and
+/// [`StaticThingBuf::freeze`] methods.
+///
+/// [`ThingBuf::freeze`]: crate::ThingBuf::freeze
+/// [`StaticThingBuf::freeze`]: crate::StaticThingBuf::freeze
+pub struct Frozen<'a, T> {
+    core: &'a Core,
+    slots: &'a (dyn Slots<T> + 'a),
+    pos: usize,
+    end: usize,
+    /// Threads waiting to pop from the queue, which may have seen an element
+    /// while it was pinned.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    pop_wait: Option<&'a WaitQueue<Unparker>>,
+}
+
+// === impl Frozen ===
+
+impl<'a, T> Frozen<'a, T> {
+    pub(crate) fn new(core: &'a Core, slots: &'a (dyn Slots<T> + 'a)) -> Self {
+        let (pos, end) = core.snapshot_bounds();
+        Self {
+            core,
+            slots,
+            pos,
+            end,
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            pop_wait: None,
+        }
+    }
+
+    /// Wakes the threads waiting on `pop_wait` after each element is copied.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    pub(crate) fn with_wake(self, pop_wait: &'a WaitQueue<Unparker>) -> Self {
+        Self {
+            pop_wait: Some(pop_wait),
+            ..self
+        }
+    }
+
+    /// Restricts this view to the `n` most recently pushed of its remaining
+    /// elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    ///
+    /// let events = ThingBuf::new(8);
+    /// for event in 0..6 {
+    ///     events.push(event).unwrap();
+    /// }
+    ///
+    /// let last = events.freeze().newest(2).collect::<Vec<_>>();
+    /// assert_eq!(last, vec![4, 5]);
+    /// ```
+    #[must_use]
+    pub fn newest(mut self, n: usize) -> Self {
+        let len = self.core.distance(self.pos, self.end);
+        for _ in n..len {
+            self.pos = self.core.next_pos(self.pos);
+        }
+        self
+    }
+}
+
+impl<T: Copy> Iterator for Frozen<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos != self.end {
+            let pos = self.pos;
+            self.pos = self.core.next_pos(pos);
+            if let Some(val) = self.core.read_at(self.slots, pos) {
+                // A pop may have seen the element while it was pinned.
+                #[cfg(all(feature = "std", not(all(loom, test))))]
+                if let Some(pop_wait) = self.pop_wait {
+                    pop_wait.notify_waiting();
+                }
+                return Some(val);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.core.distance(self.pos, self.end)))
+    }
+}
+
+impl<T> fmt::Debug for Frozen<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Frozen")
+            .field("pos", &self.pos)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+// A `Frozen` view only reads the slots, which are `Sync` when `T` is.
+unsafe impl<T: Sync> Send for Frozen<'_, T> {}
+unsafe impl<T: Sync> Sync for Frozen<'_, T> {}
@@ -0,0 +1,190 @@
+use crate::{
+    recycling::{self, Recycle},
+    Full, ThingBuf,
+};
+use core::fmt;
+
+/// A bounded, lock-free queue that many threads push tasks into and worker
+/// threads steal batches of tasks out of.
+///
+/// This is intended as a building block for task schedulers: any thread may
+/// [`push`] a task into the shared `Injector`, and each worker periodically
+/// calls [`steal_batch_into`] to move a run of tasks into its own local run
+/// queue. Stealing a batch claims every element in the run with a single
+/// synchronized operation on the queue's head, rather than one operation per
+/// element, so a worker refilling its local queue contends with other workers
+/// once per batch.
+///
+/// An `Injector` is backed by a [`ThingBuf`], and has the same capacity and
+/// [recycling] behavior.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::Injector;
+///
+/// let injector = Injector::new(8);
+/// for task in 0..5 {
+///     injector.push(task).unwrap();
+/// }
+///
+/// // A worker steals up to three tasks into its local queue.
+/// let mut local = Vec::new();
+/// assert_eq!(injector.steal_batch_into(&mut local, 3), 3);
+/// assert_eq!(local, vec![0, 1, 2]);
+///
+/// // Another worker takes the rest.
+/// let mut local = Vec::new();
+/// assert_eq!(injector.steal_batch_into(&mut local, 3), 2);
+/// assert_eq!(local, vec![3, 4]);
+/// ```
+///
+/// [`push`]: Self::push
+/// [`steal_batch_into`]: Self::steal_batch_into
+/// [recycling]: crate::recycling
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct Injector<T, R = recycling::DefaultRecycle> {
+    buf: ThingBuf<T, R>,
+}
+
+// === impl Injector ===
+
+impl<T: Default + Clone> Injector<T> {
+    /// Returns a new `Injector` with space for `capacity` elements.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_recycle(capacity, recycling::DefaultRecycle::new())
+    }
+}
+
+impl<T, R> Injector<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Returns a new `Injector` with space for `capacity` elements and
+    /// the provided [recycling policy].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`. This value
+    /// represents the highest power of two that can be expressed by a `usize`, excluding the most
+    /// significant bit.
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    pub fn with_recycle(capacity: usize, recycle: R) -> Self {
+        Self {
+            buf: ThingBuf::with_recycle(capacity, recycle),
+        }
+    }
+
+    /// Pushes an element into the queue.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the element was enqueued
+    /// - `Err(`[`Full`]`)` containing the value if the queue was at capacity
+    pub fn push(&self, val: T) -> Result<(), Full<T>> {
+        self.buf.push(val)
+    }
+
+    /// Steals a single element from the head of the queue.
+    ///
+    /// Returns `None` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::Injector;
+    ///
+    /// let injector = Injector::new(4);
+    /// injector.push("task").unwrap();
+    ///
+    /// assert_eq!(injector.steal(), Some("task"));
+    /// assert_eq!(injector.steal(), None);
+    /// ```
+    pub fn steal(&self) -> Option<T> {
+        self.buf.pop()
+    }
+
+    /// Steals up to `max` elements from the head of the queue, moving them
+    /// into `dest` in the order they were pushed.
+    ///
+    /// All of the stolen elements are claimed in one synchronized operation,
+    /// so no other thread can steal an element from the middle of the batch.
+    /// Only the elements that have been completely written when the batch is
+    /// claimed are stolen, so this may steal fewer than `max` elements while
+    /// other threads are still pushing.
+    ///
+    /// Returns the number of elements moved into `dest`, which is 0 if the
+    /// queue was empty.
+    ///
+    /// # Panics
+    ///
+    /// If `max` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::Injector;
+    /// use std::collections::VecDeque;
+    ///
+    /// let injector = Injector::new(16);
+    /// for task in 0..10 {
+    ///     injector.push(task).unwrap();
+    /// }
+    ///
+    /// let mut local = VecDeque::new();
+    /// while injector.steal_batch_into(&mut local, 4) > 0 {
+    ///     while let Some(task) = local.pop_front() {
+    ///         // run the task...
+    ///         # let _ = task;
+    ///     }
+    /// }
+    /// assert!(injector.is_empty());
+    /// ```
+    pub fn steal_batch_into<E>(&self, dest: &mut E, max: usize) -> usize
+    where
+        E: Extend<T>,
+    {
+        assert!(max > 0, "a batch must steal at least one element");
+        let max = core::cmp::min(max, self.capacity());
+        let refs = match self.buf.core.pop_n_ref(&self.buf.slots, max) {
+            Ok(refs) => refs,
+            Err(_) => return 0,
+        };
+        let n = refs.len();
+        let recycle = &self.buf.recycle;
+        dest.extend(
+            refs.into_iter()
+                .map(|mut slot| recycling::take(&mut *slot, recycle)),
+        );
+        n
+    }
+}
+
+impl<T, R> Injector<T, R> {
+    /// Returns the *total* capacity of this queue.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if there are currently no elements in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for Injector<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Injector")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .field("recycle", &self.buf.recycle)
+            .finish()
+    }
+}
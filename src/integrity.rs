@@ -0,0 +1,71 @@
+//! Integrity checking of queued elements.
+//!
+//! When the `high-integrity` feature is enabled, a [`ThingBuf`] may be built
+//! with a checksum function, using [`ThingBufBuilder::checksum`]. The
+//! checksum of each element is computed when the element is published (when
+//! the [`Ref`] returned by `push_ref` is dropped), and stored in the
+//! element's slot. When the element is popped, its checksum is computed
+//! again and compared with the stored one. If they differ, the element was
+//! corrupted while it was in the queue (for instance, by a stray write
+//! through a raw pointer, or by a fault in memory shared with another
+//! process), and the pop panics rather than returning it.
+//!
+//! Any function from `&T` to a `u64` may be used as the checksum function.
+//! For elements that can be viewed as bytes, such as `Vec<u8>`, `String`, or
+//! `[u8; N]`, the [`bytes`] function hashes their contents.
+//!
+//! Checksums are meant to detect accidental corruption, not tampering: the
+//! [`bytes`] checksum is not a cryptographic hash. Computing a checksum on
+//! every push and pop also adds overhead proportional to the size of each
+//! element.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::{integrity, ThingBuf};
+//!
+//! // A user-provided checksum function.
+//! fn sum(readings: &[u16; 4]) -> u64 {
+//!     readings.iter().map(|&r| u64::from(r)).sum()
+//! }
+//!
+//! let q = ThingBuf::<[u16; 4]>::builder(8).checksum(sum).build();
+//! q.push([1, 2, 3, 4]).unwrap();
+//! assert_eq!(q.pop(), Some([1, 2, 3, 4]));
+//!
+//! // The provided checksum for byte buffers.
+//! let frames = ThingBuf::<String>::builder(8)
+//!     .checksum(integrity::bytes)
+//!     .build();
+//! frames.push(String::from("hello")).unwrap();
+//! assert_eq!(frames.pop().as_deref(), Some("hello"));
+//! ```
+//!
+//! [`ThingBuf`]: crate::ThingBuf
+//! [`ThingBufBuilder::checksum`]: crate::ThingBufBuilder::checksum
+//! [`Ref`]: crate::Ref
+//! [`bytes`]: bytes()
+
+/// Computes a checksum of the bytes of `val`, for use as a
+/// [`ThingBuf`](crate::ThingBuf)'s checksum function.
+///
+/// This is the 64-bit FNV-1a hash of the bytes, which is fast to compute,
+/// and changes if any single byte changes. It is not a cryptographic hash.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::integrity;
+///
+/// assert_eq!(integrity::bytes(b"hello"), integrity::bytes(&b"hello".to_vec()));
+/// assert_ne!(integrity::bytes(b"hello"), integrity::bytes(b"hellp"));
+/// ```
+#[must_use]
+pub fn bytes<T: AsRef<[u8]> + ?Sized>(val: &T) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    val.as_ref().iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
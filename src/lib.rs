@@ -9,11 +9,14 @@ mod macros;
 
 mod loom;
 pub mod mpsc;
+pub mod raw;
 pub mod recycling;
+pub mod time;
 mod util;
 mod wait;
 
 pub use self::recycling::Recycle;
+pub use self::util::CachePadded;
 
 // TODO(eliza): finish writing this
 // #[doc = include_str!("../mpsc_perf_comparison.md")]
@@ -21,10 +24,48 @@ pub use self::recycling::Recycle;
 //     // Empty module, used only for documentation.
 // }
 
+mod frozen;
+pub use self::frozen::Frozen;
+
 feature! {
     #![all(feature = "static", not(all(loom, test)))]
     mod static_thingbuf;
-    pub use self::static_thingbuf::StaticThingBuf;
+    pub use self::static_thingbuf::{StaticIntoIter, StaticThingBuf};
+}
+
+feature! {
+    #![all(feature = "static", not(all(loom, test)))]
+    pub mod timer;
+}
+
+feature! {
+    #![feature = "ffi"]
+    pub mod ffi;
+}
+
+feature! {
+    #![feature = "stats"]
+    pub mod stats;
+}
+
+feature! {
+    #![feature = "high-integrity"]
+    pub mod integrity;
+}
+
+feature! {
+    #![all(feature = "watchdog", not(all(loom, test)))]
+    pub mod watchdog;
+}
+
+feature! {
+    #![all(feature = "std", not(all(loom, test)))]
+    pub mod telemetry;
+}
+
+feature! {
+    #![all(feature = "test-util", not(all(loom, test)))]
+    pub mod test_util;
 }
 
 feature! {
@@ -32,7 +73,20 @@ feature! {
     extern crate alloc;
 
     mod thingbuf;
-    pub use self::thingbuf::ThingBuf;
+    pub use self::thingbuf::{
+        Consumer, IntoIter, Producer, SlotLayout, SnapshotIter, ThingBuf, ThingBufBuilder,
+    };
+
+    mod thingstack;
+    pub use self::thingstack::{PoolRef, StackRef, ThingStack};
+
+    mod injector;
+    pub use self::injector::Injector;
+
+    mod frame_buffered;
+    pub use self::frame_buffered::{Frame, FrameBuffered};
+
+    pub mod work_queue;
 }
 
 use crate::{
@@ -41,7 +95,7 @@ use crate::{
         cell::{MutPtr, UnsafeCell},
     },
     mpsc::errors::{TryRecvError, TrySendError},
-    util::{Backoff, CachePadded},
+    util::{ref_token::RefToken, Backoff},
 };
 
 const HAS_READER: usize = 1 << (usize::BITS - 1);
@@ -68,9 +122,49 @@ pub const MAX_CAPACITY: usize = usize::MAX & !HAS_READER;
 /// [implements `DerefMut<T>`]: #impl-DerefMut
 pub struct Ref<'slot, T> {
     ptr: MutPtr<MaybeUninit<T>>,
-    slot: &'slot Slot<T>,
+    slot: SlotRef<'slot, T>,
     new_state: usize,
     is_pop: bool,
+    /// Checks that the slot is still claimed by this `Ref`, when the
+    /// "debug-refs" feature is enabled or in debug builds.
+    token: RefToken,
+    /// The sequence number of a popped element.
+    #[cfg(feature = "seq")]
+    seq: usize,
+    /// Computes the checksum of a pushed element when it is published, if
+    /// the queue checks the integrity of its elements.
+    #[cfg(feature = "high-integrity")]
+    checksum: Option<fn(&T) -> u64>,
+    /// Wakes a thread that is blocked on the queue once the slot is released,
+    /// once a thread has blocked on the queue.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    wake: Option<&'slot wait::WaitQueue<mpsc::blocking::park::Unparker>>,
+}
+
+/// A guard for an element that was pinned in place by `Core::pin_at`.
+///
+/// While the guard exists, the element's slot looks like a push that is still
+/// in progress, so the element may be accessed through the guard. Dropping
+/// the guard makes the element readable again.
+struct Pinned<'slot, T> {
+    ptr: MutPtr<MaybeUninit<T>>,
+    state: &'slot AtomicUsize,
+    readable: usize,
+}
+
+/// Releases the slots claimed by `Core::pop_n_with` that have not been passed
+/// to its callback yet, if the callback panics.
+///
+/// The head index has already been advanced past these slots, so their
+/// elements can't be put back. Instead, they are discarded, and the slots are
+/// freed to be pushed to again, rather than staying claimed forever.
+#[cfg(feature = "alloc")]
+struct ClaimedBatch<'a, T, S: Slots<T> + ?Sized> {
+    core: &'a Core,
+    slots: &'a S,
+    head: usize,
+    remaining: usize,
+    _elem: core::marker::PhantomData<fn(T)>,
 }
 
 /// Error indicating that a `push` operation failed because a queue was at
@@ -105,55 +199,129 @@ struct Core {
     capacity: usize,
     /// Set when dropping the slots in the ring buffer, to avoid potential double-frees.
     has_dropped_slots: bool,
+    /// Set when every slot has been initialized ahead of time, so that slots
+    /// in the first lap are recycled rather than initialized when pushed to.
+    prefilled: bool,
+    #[cfg(feature = "stats")]
+    occupancy: stats::OccupancySampler,
+    #[cfg(all(feature = "stats", feature = "timestamps"))]
+    latency: stats::LatencySampler,
+    #[cfg(feature = "stats")]
+    recycling: stats::RecyclingCounter,
+    /// The number of slots skipped by pushes, and by pops, respectively.
+    ///
+    /// Skipped slots do not contain an element, so they are not assigned
+    /// sequence numbers.
+    #[cfg(feature = "seq")]
+    tx_skipped: AtomicUsize,
+    #[cfg(feature = "seq")]
+    rx_skipped: AtomicUsize,
 }
 
-struct Slot<T> {
+/// A single entry in the storage array of a ring buffer.
+///
+/// `Slot`s are only useful together with a [`raw::Core`]; see the [`raw`]
+/// module for details.
+pub struct Slot<T> {
     value: UnsafeCell<MaybeUninit<T>>,
     /// Each slot's state has two components: a flag indicated by the most significant bit (MSB), and the rest of the state.
     /// The MSB is set when a reader is reading from this slot.
     /// The rest of the state helps determine the availability of the slot for reading or writing:
     /// - A slot is available for reading when the state (excluding the MSB) equals head + 1.
     /// - A slot is available for writing when the state (excluding the MSB) equals tail.
+    ///
     /// At initialization, each slot's state is set to its ordinal index.
     state: AtomicUsize,
+    /// When the element in this slot was pushed. This is written by the
+    /// pushing thread before it publishes the element.
+    #[cfg(feature = "timestamps")]
+    enqueued_at: UnsafeCell<Option<std::time::Instant>>,
+    /// The checksum of the element in this slot, computed when it was
+    /// pushed. This is written by the pushing thread before it publishes the
+    /// element.
+    #[cfg(feature = "high-integrity")]
+    checksum: UnsafeCell<u64>,
+}
+
+/// References to the parts of a single slot.
+///
+/// The parts of a slot are either stored together, in a [`Slot`], or in
+/// separate arrays, depending on the layout of the slot array (see
+/// [`Slots`]).
+struct SlotRef<'a, T> {
+    value: &'a UnsafeCell<MaybeUninit<T>>,
+    state: &'a AtomicUsize,
+    #[cfg(feature = "timestamps")]
+    enqueued_at: &'a UnsafeCell<Option<std::time::Instant>>,
+    #[cfg(feature = "high-integrity")]
+    checksum: &'a UnsafeCell<u64>,
+}
+
+/// The storage array of a ring buffer.
+///
+/// A `Core` performs the ring buffer operations on any `Slots`
+/// implementation. This is implemented by `[Slot<T>]`, which stores each
+/// slot's state next to its value, and (with the "alloc" feature) by
+/// `thingbuf::SplitSlots`, which stores the states and values in separate
+/// arrays.
+trait Slots<T> {
+    /// Returns the number of slots.
+    fn len(&self) -> usize;
+
+    /// Returns the slot at `idx`, without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// `idx` must be less than `self.len()`.
+    unsafe fn get_unchecked(&self, idx: usize) -> SlotRef<'_, T>;
+
+    /// Returns the slot at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is out of bounds.
+    #[inline]
+    fn get(&self, idx: usize) -> SlotRef<'_, T> {
+        assert!(
+            idx < self.len(),
+            "index out of bounds (index was {} but the length was {})",
+            idx,
+            self.len()
+        );
+        // Safety: we just checked that `idx` is in bounds.
+        unsafe { self.get_unchecked(idx) }
+    }
 }
 
 impl Core {
-    #[cfg(not(all(loom, test)))]
-    const fn new(capacity: usize) -> Self {
-        assert!(capacity <= MAX_CAPACITY);
-        let closed = (capacity + 1).next_power_of_two();
-        let idx_mask = closed - 1;
-        let gen = closed << 1;
-        let gen_mask = !(closed | idx_mask);
-        Self {
-            head: CachePadded(AtomicUsize::new(0)),
-            tail: CachePadded(AtomicUsize::new(0)),
-            gen,
-            gen_mask,
-            closed,
-            idx_mask,
-            capacity,
-            has_dropped_slots: false,
-        }
-    }
-
-    #[cfg(all(loom, test))]
-    fn new(capacity: usize) -> Self {
-        let closed = (capacity + 1).next_power_of_two();
-        let idx_mask = closed - 1;
-        let gen = closed << 1;
-        let gen_mask = !(closed | idx_mask);
-        Self {
-            head: CachePadded(AtomicUsize::new(0)),
-            tail: CachePadded(AtomicUsize::new(0)),
-            gen,
-            closed,
-            gen_mask,
-            idx_mask,
-            capacity,
-            #[cfg(debug_assertions)]
-            has_dropped_slots: false,
+    loom_const_fn! {
+        fn new(capacity: usize) -> Self {
+            assert!(capacity <= MAX_CAPACITY);
+            let closed = (capacity + 1).next_power_of_two();
+            let idx_mask = closed - 1;
+            let gen = closed << 1;
+            let gen_mask = !(closed | idx_mask);
+            Self {
+                head: CachePadded(AtomicUsize::new(0)),
+                tail: CachePadded(AtomicUsize::new(0)),
+                gen,
+                gen_mask,
+                closed,
+                idx_mask,
+                capacity,
+                has_dropped_slots: false,
+                prefilled: false,
+                #[cfg(feature = "stats")]
+                occupancy: stats::OccupancySampler::new(),
+                #[cfg(all(feature = "stats", feature = "timestamps"))]
+                latency: stats::LatencySampler::new(),
+                #[cfg(feature = "stats")]
+                recycling: stats::RecyclingCounter::new(),
+                #[cfg(feature = "seq")]
+                tx_skipped: AtomicUsize::new(0),
+                #[cfg(feature = "seq")]
+                rx_skipped: AtomicUsize::new(0),
+            }
         }
     }
 
@@ -181,6 +349,17 @@ impl Core {
         self.capacity
     }
 
+    /// Clears the closed bit, so that the queue may be pushed to again.
+    #[cfg(feature = "alloc")]
+    fn reopen(&mut self) {
+        self.tail.fetch_and(!self.closed, SeqCst);
+    }
+
+    #[inline]
+    fn is_closed(&self) -> bool {
+        test_dbg!(self.tail.load(SeqCst) & self.closed) == self.closed
+    }
+
     fn close(&self) -> bool {
         test_println!("Core::close");
         if crate::util::panic::panicking() {
@@ -190,17 +369,83 @@ impl Core {
     }
 
     #[inline(always)]
-    fn push_ref<'slots, T, R>(
+    fn push_ref<'slots, T, S: Slots<T> + ?Sized, R>(
+        &self,
+        slots: &'slots S,
+        recycle: &R,
+    ) -> Result<Ref<'slots, T>, TrySendError<()>>
+    where
+        R: Recycle<T>,
+    {
+        self.push_ref_from(slots, recycle, None)
+    }
+
+    /// Like `push_ref`, for a queue with a single producer, which caches the
+    /// tail index in `tail`.
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    fn push_ref_spsc<'slots, T, S: Slots<T> + ?Sized, R>(
+        &self,
+        slots: &'slots S,
+        recycle: &R,
+        tail: &mut usize,
+    ) -> Result<Ref<'slots, T>, TrySendError<()>>
+    where
+        R: Recycle<T>,
+    {
+        self.push_ref_from(slots, recycle, Some(tail))
+    }
+
+    /// Advances `index` from `current` to `next`, returning the actual
+    /// value of `index` if it was not `current`.
+    ///
+    /// If the calling thread is the only one that advances `index`, it
+    /// passes its `cached` copy of the index. The index can then be advanced
+    /// with a plain `Release` store, rather than with a CAS loop. The other
+    /// side only reads the index (with the `fetch_or(0)`s below), and an RMW
+    /// always reads the latest value, so it still sees the store. `pin_at`,
+    /// which relies on the head only being advanced by RMWs, is never called
+    /// on a queue with a single owner of each index.
+    ///
+    /// Under loom, this is still a swap: loom only partially orders stores,
+    /// so it lets one of those `fetch_or(0)`s overwrite a concurrent plain
+    /// store with the stale value it read, which a real RMW never does.
+    #[inline(always)]
+    fn advance(
+        index: &AtomicUsize,
+        current: usize,
+        next: usize,
+        cached: Option<&mut usize>,
+    ) -> Result<usize, usize> {
+        match cached {
+            Some(cached) => {
+                #[cfg(not(all(loom, test)))]
+                test_dbg!(index.store(next, Release));
+                #[cfg(all(loom, test))]
+                test_dbg!(index.swap(next, SeqCst));
+                *cached = next;
+                Ok(current)
+            }
+            None => index.compare_exchange_weak(current, next, SeqCst, Acquire),
+        }
+    }
+
+    #[inline(always)]
+    fn push_ref_from<'slots, T, S: Slots<T> + ?Sized, R>(
         &self,
-        slots: &'slots [Slot<T>],
+        slots: &'slots S,
         recycle: &R,
+        mut cached_tail: Option<&mut usize>,
     ) -> Result<Ref<'slots, T>, TrySendError<()>>
     where
         R: Recycle<T>,
     {
         test_println!("push_ref");
         let mut backoff = Backoff::new();
-        let mut tail = test_dbg!(self.tail.load(Relaxed));
+        let mut tail = match cached_tail {
+            Some(ref tail) => **tail,
+            None => test_dbg!(self.tail.load(Relaxed)),
+        };
         loop {
             if test_dbg!(tail & self.closed != 0) {
                 return Err(TrySendError::Closed(()));
@@ -231,10 +476,12 @@ impl Core {
             if test_dbg!(state == tail) {
                 let next_tail = self.next(idx, gen);
                 // try to advance the tail
-                match test_dbg!(self
-                    .tail
-                    .compare_exchange_weak(tail, next_tail, SeqCst, Acquire))
-                {
+                match test_dbg!(Self::advance(
+                    &self.tail,
+                    tail,
+                    next_tail,
+                    cached_tail.as_deref_mut()
+                )) {
                     Ok(_) if test_dbg!(check_has_reader(raw_state)) => {
                         test_println!(
                             "advanced tail {} to {}; has an active reader, skipping slot [{}]",
@@ -242,6 +489,8 @@ impl Core {
                             next_tail,
                             idx
                         );
+                        #[cfg(feature = "seq")]
+                        self.tx_skipped.fetch_add(1, Relaxed);
                         let next_state = wrapping_add(tail, self.gen);
                         test_dbg!(slot
                             .state
@@ -268,21 +517,33 @@ impl Core {
                             // Safety: we have just claimed exclusive ownership over
                             // this slot.
                             let ptr = ptr.deref();
-                            if gen == 0 {
+                            if gen == 0 && !self.prefilled {
                                 ptr.write(recycle.new_element());
+                                #[cfg(feature = "stats")]
+                                self.recycling.record_fresh();
                                 test_println!("-> initialized");
                             } else {
-                                // Safety: if the generation is > 0, then the
-                                // slot has already been initialized.
-                                recycle.recycle(ptr.assume_init_mut());
+                                // Safety: if the generation is > 0, or the
+                                // slots were pre-filled, then the slot has
+                                // already been initialized.
+                                self.recycle_element(recycle, ptr.assume_init_mut());
                                 test_println!("-> recycled");
                             }
                         }
+                        #[cfg(feature = "stats")]
+                        self.record_occupancy();
                         return Ok(Ref {
                             ptr,
                             new_state: tail + 1,
                             slot,
                             is_pop: false,
+                            token: RefToken::push(tail, self.idx_mask),
+                            #[cfg(feature = "seq")]
+                            seq: 0,
+                            #[cfg(feature = "high-integrity")]
+                            checksum: None,
+                            #[cfg(all(feature = "std", not(all(loom, test))))]
+                            wake: None,
                         });
                     }
                     Err(actual) => {
@@ -325,11 +586,306 @@ impl Core {
         }
     }
 
+    /// Claims `n` consecutive slots for writing in a single operation, so that
+    /// no other writer's elements can be interleaved with them.
+    ///
+    /// Unlike `push_ref`, this does not skip slots that still have an active
+    /// reader: if any of the next `n` slots is not yet writable, this returns
+    /// `Full`.
+    #[cfg(feature = "std")]
+    fn push_n_ref<'slots, T, S: Slots<T> + ?Sized, R>(
+        &self,
+        slots: &'slots S,
+        recycle: &R,
+        n: usize,
+    ) -> Result<alloc::vec::Vec<Ref<'slots, T>>, TrySendError<()>>
+    where
+        R: Recycle<T>,
+    {
+        test_println!("push_n_ref({})", n);
+        debug_assert!(
+            n <= self.capacity,
+            "cannot claim more slots than the capacity"
+        );
+        let mut backoff = Backoff::new();
+        let mut tail = test_dbg!(self.tail.load(Relaxed));
+        'claim: loop {
+            if test_dbg!(tail & self.closed != 0) {
+                return Err(TrySendError::Closed(()));
+            }
+
+            // Every slot in the range must be writable at its position. This
+            // compares the raw state, so a slot with an active reader is not
+            // writable.
+            let mut next_tail = tail;
+            for _ in 0..n {
+                let (idx, gen) = self.idx_gen(next_tail);
+                if test_dbg!(slots.get(idx).state.load(SeqCst)) != next_tail {
+                    let actual = test_dbg!(self.tail.load(SeqCst));
+                    if actual == tail {
+                        test_println!("not enough free slots");
+                        return Err(TrySendError::Full(()));
+                    }
+                    // our tail is stale, try again with the new one.
+                    tail = actual;
+                    backoff.spin();
+                    continue 'claim;
+                }
+                next_tail = self.next(idx, gen);
+            }
+
+            match test_dbg!(self
+                .tail
+                .compare_exchange_weak(tail, next_tail, SeqCst, Acquire))
+            {
+                Ok(_) => break,
+                Err(actual) => {
+                    test_println!("failed to advance tail {} to {}", tail, next_tail);
+                    tail = actual;
+                    backoff.spin();
+                }
+            }
+        }
+
+        #[cfg(feature = "stats")]
+        self.record_occupancy();
+
+        // We now have exclusive ownership over every slot in the range.
+        let mut refs = alloc::vec::Vec::with_capacity(n);
+        for _ in 0..n {
+            let (idx, gen) = self.idx_gen(tail);
+            let slot = slots.get(idx);
+            let ptr = slot.value.get_mut();
+            unsafe {
+                // Safety: we have claimed exclusive ownership over this slot.
+                let ptr = ptr.deref();
+                if gen == 0 && !self.prefilled {
+                    ptr.write(recycle.new_element());
+                    #[cfg(feature = "stats")]
+                    self.recycling.record_fresh();
+                } else {
+                    // Safety: if the generation is > 0, or the slots were
+                    // pre-filled, then the slot has already been initialized.
+                    self.recycle_element(recycle, ptr.assume_init_mut());
+                }
+            }
+            refs.push(Ref {
+                ptr,
+                new_state: tail + 1,
+                slot,
+                is_pop: false,
+                token: RefToken::push(tail, self.idx_mask),
+                #[cfg(feature = "seq")]
+                seq: 0,
+                #[cfg(feature = "high-integrity")]
+                checksum: None,
+                #[cfg(all(feature = "std", not(all(loom, test))))]
+                wake: None,
+            });
+            tail = self.next(idx, gen);
+        }
+        Ok(refs)
+    }
+
+    /// Releases a slot claimed by `push_n_ref` without publishing it.
+    ///
+    /// The slot is marked as writable in the next generation, exactly like a
+    /// slot that `push_ref` skipped, so the reader skips it rather than
+    /// receiving its stale element.
+    #[cfg(any(feature = "std", feature = "serbuf", feature = "futures-sink"))]
+    fn abandon_ref<T>(&self, mut slot: Ref<'_, T>) {
+        debug_assert!(!slot.is_pop, "only pushed slots can be abandoned");
+        let tail = slot.new_state.wrapping_sub(1);
+        test_println!("abandoning claimed slot at {}", tail);
+        #[cfg(feature = "seq")]
+        self.tx_skipped.fetch_add(1, Relaxed);
+        slot.new_state = wrapping_add(tail, self.gen);
+    }
+
+    /// Claims up to `max` consecutive readable slots in a single operation.
+    ///
+    /// Only the run of slots at the head that are already readable is
+    /// claimed, so this may return fewer than `max` slots even if more
+    /// elements are being written. If the head slot itself is not readable,
+    /// this falls back to `pop_ref`, which handles skipped slots, an empty
+    /// queue, and a closed queue.
+    #[cfg(feature = "alloc")]
+    fn pop_n_ref<'slots, T, S: Slots<T> + ?Sized>(
+        &self,
+        slots: &'slots S,
+        max: usize,
+    ) -> Result<alloc::vec::Vec<Ref<'slots, T>>, TryRecvError> {
+        test_println!("pop_n_ref({})", max);
+        let mut refs = alloc::vec::Vec::new();
+        self.pop_n_with(slots, max, |slot| refs.push(slot))?;
+        Ok(refs)
+    }
+
+    /// Copies as many consecutive readable elements as fit into `out`,
+    /// returning the number of elements copied.
+    ///
+    /// Like `pop_n_ref`, this claims the whole run of readable slots with a
+    /// single update to the head index, but it copies each element out of
+    /// its slot and releases the slot immediately, without allocating.
+    #[cfg(feature = "alloc")]
+    fn pop_into<T: Copy, S: Slots<T> + ?Sized>(
+        &self,
+        slots: &S,
+        out: &mut [T],
+    ) -> Result<usize, TryRecvError> {
+        test_println!("pop_into({})", out.len());
+        if out.is_empty() {
+            return Ok(0);
+        }
+        let mut dst = out.iter_mut();
+        self.pop_n_with(slots, dst.len(), |slot| {
+            // `pop_n_with` never claims more than `dst.len()` slots.
+            if let Some(dst) = dst.next() {
+                *dst = *slot;
+            }
+        })
+    }
+
+    /// Claims up to `max` consecutive readable slots, passing each claimed
+    /// slot to `f` in order, and returns the number of slots claimed.
+    ///
+    /// If `f` panics, the slots that were claimed but not yet passed to it
+    /// are released, and their elements are discarded.
+    #[cfg(feature = "alloc")]
+    fn pop_n_with<'slots, T: 'slots, S: Slots<T> + ?Sized>(
+        &self,
+        slots: &'slots S,
+        max: usize,
+        mut f: impl FnMut(Ref<'slots, T>),
+    ) -> Result<usize, TryRecvError> {
+        debug_assert!(max > 0, "must claim at least one slot");
+        let mut backoff = Backoff::new();
+        let mut head = test_dbg!(self.head.load(Relaxed));
+        let n = loop {
+            // Count how many slots starting at the head are readable.
+            let mut n = 0;
+            let mut next_head = head;
+            while n < max {
+                let (idx, gen) = self.idx_gen(next_head);
+                if test_dbg!(slots.get(idx).state.load(Acquire)) != next_head + 1 {
+                    break;
+                }
+                next_head = self.next(idx, gen);
+                n += 1;
+            }
+
+            if n == 0 {
+                f(self.pop_ref(slots)?);
+                return Ok(1);
+            }
+
+            match test_dbg!(self
+                .head
+                .compare_exchange_weak(head, next_head, SeqCst, Acquire))
+            {
+                Ok(_) => {
+                    test_println!("advanced head {} to {}", head, next_head);
+                    break n;
+                }
+                Err(actual) => {
+                    test_println!("failed to advance head, head={}, actual={}", head, actual);
+                    head = actual;
+                    backoff.spin();
+                }
+            }
+        };
+
+        #[cfg(feature = "stats")]
+        self.record_occupancy();
+        #[cfg(feature = "seq")]
+        let skipped = self.rx_skipped.load(Relaxed);
+
+        // We now have exclusive ownership over every slot in the range. Start
+        // loading the first few elements while the earlier ones are read, and
+        // keep `ahead` that many slots ahead of `head` from then on.
+        #[cfg(feature = "prefetch")]
+        let mut ahead = head;
+        #[cfg(feature = "prefetch")]
+        for _ in 0..n.min(util::prefetch::DISTANCE) {
+            let (idx, gen) = self.idx_gen(ahead);
+            util::prefetch::prefetch(slots.get(idx));
+            ahead = self.next(idx, gen);
+        }
+        let mut batch = ClaimedBatch {
+            core: self,
+            slots,
+            head,
+            remaining: n,
+            _elem: core::marker::PhantomData,
+        };
+        for _i in 0..n {
+            #[cfg(feature = "prefetch")]
+            if _i + util::prefetch::DISTANCE < n {
+                let (idx, gen) = self.idx_gen(ahead);
+                util::prefetch::prefetch(slots.get(idx));
+                ahead = self.next(idx, gen);
+            }
+            let head = batch.head;
+            let (idx, gen) = self.idx_gen(head);
+            let slot = slots.get(idx);
+            let new_state = set_has_reader(wrapping_add(head, self.gen));
+            Self::start_batch_read(slot, head, new_state);
+            // From here on, the `Ref` releases the slot.
+            batch.head = self.next(idx, gen);
+            batch.remaining -= 1;
+            #[cfg(all(feature = "stats", feature = "timestamps"))]
+            self.record_latency(slot);
+            f(Ref {
+                new_state,
+                ptr: slot.value.get_mut(),
+                slot,
+                is_pop: true,
+                token: RefToken::pop(new_state, self.idx_mask),
+                #[cfg(feature = "seq")]
+                seq: self.seq(head, skipped),
+                #[cfg(feature = "high-integrity")]
+                checksum: None,
+                #[cfg(all(feature = "std", not(all(loom, test))))]
+                wake: None,
+            });
+        }
+        Ok(n)
+    }
+
+    #[inline(always)]
+    fn pop_ref<'slots, T, S: Slots<T> + ?Sized>(
+        &self,
+        slots: &'slots S,
+    ) -> Result<Ref<'slots, T>, TryRecvError> {
+        self.pop_ref_from(slots, None)
+    }
+
+    /// Like `pop_ref`, for a queue with a single consumer, which caches the
+    /// head index in `head`.
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    fn pop_ref_spsc<'slots, T, S: Slots<T> + ?Sized>(
+        &self,
+        slots: &'slots S,
+        head: &mut usize,
+    ) -> Result<Ref<'slots, T>, TryRecvError> {
+        self.pop_ref_from(slots, Some(head))
+    }
+
     #[inline(always)]
-    fn pop_ref<'slots, T>(&self, slots: &'slots [Slot<T>]) -> Result<Ref<'slots, T>, TryRecvError> {
+    fn pop_ref_from<'slots, T, S: Slots<T> + ?Sized>(
+        &self,
+        slots: &'slots S,
+        mut cached_head: Option<&mut usize>,
+    ) -> Result<Ref<'slots, T>, TryRecvError> {
         test_println!("pop_ref");
         let mut backoff = Backoff::new();
-        let mut head = self.head.load(Relaxed);
+        let mut head = match cached_head {
+            Some(ref head) => **head,
+            // Acquire the head index, so that if `pin_at` saw the head before
+            // it reached a slot, we see that the slot is pinned.
+            None => self.head.load(Acquire),
+        };
 
         loop {
             test_dbg!(head);
@@ -360,21 +916,34 @@ impl Core {
             // If the slot's state is ahead of the head index by one, we can pop it.
             if test_dbg!(raw_state == head + 1) {
                 // try to advance the head index
-                match test_dbg!(self
-                    .head
-                    .compare_exchange_weak(head, next_head, SeqCst, Acquire))
-                {
+                match test_dbg!(Self::advance(
+                    &self.head,
+                    head,
+                    next_head,
+                    cached_head.as_deref_mut()
+                )) {
                     Ok(_) => {
                         test_println!("advanced head {} to {}", head, next_head);
                         test_println!("claimed slot [{}]", idx);
                         let mut new_state = wrapping_add(head, self.gen);
                         new_state = set_has_reader(new_state);
                         test_dbg!(slot.state.store(test_dbg!(new_state), SeqCst));
+                        #[cfg(feature = "stats")]
+                        self.record_occupancy();
+                        #[cfg(all(feature = "stats", feature = "timestamps"))]
+                        self.record_latency(slot);
                         return Ok(Ref {
                             new_state,
                             ptr: slot.value.get_mut(),
                             slot,
                             is_pop: true,
+                            token: RefToken::pop(new_state, self.idx_mask),
+                            #[cfg(feature = "seq")]
+                            seq: self.seq(head, self.rx_skipped.load(Relaxed)),
+                            #[cfg(feature = "high-integrity")]
+                            checksum: None,
+                            #[cfg(all(feature = "std", not(all(loom, test))))]
+                            wake: None,
                         });
                     }
                     Err(actual) => {
@@ -413,9 +982,15 @@ impl Core {
                 }
 
                 // The slot is in an invalid state (was skipped). Try to advance the head index.
-                match test_dbg!(self.head.compare_exchange(head, next_head, SeqCst, Acquire)) {
+                let skipped = match cached_head.as_deref_mut() {
+                    Some(cached) => Self::advance(&self.head, head, next_head, Some(cached)),
+                    None => self.head.compare_exchange(head, next_head, SeqCst, Acquire),
+                };
+                match test_dbg!(skipped) {
                     Ok(_) => {
                         test_println!("skipped head slot [{}], new head={}", idx, next_head);
+                        #[cfg(feature = "seq")]
+                        self.rx_skipped.fetch_add(1, Relaxed);
                         head = next_head;
                     }
                     Err(actual) => {
@@ -433,6 +1008,337 @@ impl Core {
         }
     }
 
+    /// Marks a slot that was just claimed by a batch pop as having an active
+    /// reader.
+    ///
+    /// A batch pop checks every slot in the batch before advancing the head
+    /// index past all of them, so `pin_at` may have pinned a slot after it was
+    /// checked. If so, this waits until the slot is unpinned, so that the
+    /// element is not popped while it is being modified.
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    fn start_batch_read<T>(slot: SlotRef<'_, T>, pos: usize, new_state: usize) {
+        let mut backoff = Backoff::new();
+        while test_dbg!(slot
+            .state
+            .compare_exchange(pos + 1, test_dbg!(new_state), SeqCst, Acquire))
+        .is_err()
+        {
+            test_println!("slot at {} is pinned", pos);
+            backoff.spin_yield();
+        }
+    }
+
+    /// Waits until every push that had claimed a slot before this was called
+    /// has published its element.
+    ///
+    /// This yields rather than parking the thread, so it is only suited to
+    /// waiting for pushes that are already in progress.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    fn wait_for_pushes<T, S: Slots<T> + ?Sized>(&self, slots: &S) {
+        let (mut pos, tail) = self.snapshot_bounds();
+        while pos != tail {
+            let (idx, _) = self.idx_gen(pos);
+            let slot = slots.get(idx);
+            let mut backoff = Backoff::new();
+            // While a push is writing to the slot, its state is the slot's
+            // position. Once the element is published, or popped, it moves on.
+            while test_dbg!(slot.state.load(Acquire)) == pos {
+                test_println!("slot at {} is being pushed to", pos);
+                backoff.spin_yield();
+            }
+            pos = self.next_pos(pos);
+        }
+    }
+
+    /// Returns the positions of the first element in the queue and of the
+    /// next element to be pushed.
+    fn snapshot_bounds(&self) -> (usize, usize) {
+        // Load the head first, so that the tail is never behind it.
+        let head = self.head.load(SeqCst);
+        let tail = self.tail.load(SeqCst) & !self.closed;
+        (head, tail)
+    }
+
+    /// Returns the position after `pos`.
+    fn next_pos(&self, pos: usize) -> usize {
+        let (idx, gen) = self.idx_gen(pos);
+        self.next(idx, gen)
+    }
+
+    /// Returns the position before `pos`.
+    fn prev_pos(&self, pos: usize) -> usize {
+        let (idx, gen) = self.idx_gen(pos);
+        if idx > 0 {
+            pos - 1
+        } else {
+            // Wrap the index around to the end of the previous lap.
+            (gen.wrapping_sub(self.gen) & MAX_CAPACITY) | (self.capacity - 1)
+        }
+    }
+
+    /// Returns the number of positions from `from` up to `to`.
+    fn distance(&self, from: usize, to: usize) -> usize {
+        let (from_idx, from_gen) = self.idx_gen(from);
+        let (to_idx, to_gen) = self.idx_gen(to);
+        let laps = (to_gen.wrapping_sub(from_gen) & MAX_CAPACITY) / self.gen;
+        (laps * self.capacity + to_idx).wrapping_sub(from_idx)
+    }
+
+    /// Copies the element at position `pos` out of its slot, if it is still
+    /// in the queue.
+    ///
+    /// This never waits. The element is pinned with `pin_at` while it is
+    /// copied, so that it cannot be popped or overwritten in the meantime,
+    /// which means that the element at the head of the queue, and elements
+    /// that are being pushed or popped, are never copied. A pop that reaches
+    /// the element while it is pinned sees it as still being pushed, so once
+    /// this returns `Some`, the caller must wake any threads waiting to pop.
+    fn read_at<T: Copy, S: Slots<T> + ?Sized>(&self, slots: &S, pos: usize) -> Option<T> {
+        let mut pinned = self.pin_at(slots, pos)?;
+        Some(*pinned.as_mut())
+    }
+
+    /// Pins the element at position `pos`, if it is still in the queue, so
+    /// that it may be accessed until the returned `Pinned` guard is dropped.
+    ///
+    /// While the element is pinned, the slot's state is moved back to `pos`,
+    /// which readers and writers treat as a push still in progress, so a pop
+    /// that reaches it waits rather than popping it. The element at the head
+    /// of the queue is never pinned, since a pop may have seen that it was
+    /// readable just before it was pinned; a batch pop that saw a later
+    /// element before it was pinned waits in `start_batch_read`. If the slot
+    /// has already been popped (and possibly reused), is at the head, or is
+    /// already pinned, this returns `None` rather than waiting.
+    fn pin_at<'slots, T: 'slots, S: Slots<T> + ?Sized>(
+        &self,
+        slots: &'slots S,
+        pos: usize,
+    ) -> Option<Pinned<'slots, T>> {
+        let (idx, _) = self.idx_gen(pos);
+        let slot = slots.get(idx);
+        test_dbg!(slot.state.compare_exchange(pos + 1, pos, SeqCst, Acquire)).ok()?;
+        // The head index only advances, so if it hasn't reached `pos` yet, any
+        // pop that reaches `pos` later will see that it is pinned. This is a
+        // RMW rather than a load, so that it is part of the release sequence
+        // that popping threads acquire when they load or advance the head.
+        let ahead = self.distance(test_dbg!(self.head.fetch_or(0, SeqCst)), pos);
+        if test_dbg!(ahead) == 0 || ahead >= self.capacity {
+            // Unpin the slot, unless a pop has already claimed it.
+            let _ = test_dbg!(slot.state.compare_exchange(pos, pos + 1, SeqCst, Relaxed));
+            return None;
+        }
+        Some(Pinned {
+            ptr: slot.value.get_mut(),
+            state: slot.state,
+            readable: pos + 1,
+        })
+    }
+
+    /// Pins the most recently pushed element that can still be pinned, so
+    /// that it may be modified in place until the returned `Pinned` guard is
+    /// dropped.
+    ///
+    /// This walks back from the tail index, skipping slots that are still
+    /// being written to, were skipped, or are being read. Returns `None` if
+    /// no element in the queue could be pinned.
+    fn pin_last<'slots, T: 'slots, S: Slots<T> + ?Sized>(
+        &self,
+        slots: &'slots S,
+    ) -> Option<Pinned<'slots, T>> {
+        let (head, mut pos) = self.snapshot_bounds();
+        while test_dbg!(pos) != head {
+            pos = self.prev_pos(pos);
+            if let Some(pinned) = self.pin_at(slots, pos) {
+                return Some(pinned);
+            }
+        }
+        None
+    }
+
+    /// Returns a reference to the element at the head of the queue, without
+    /// popping it.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that no element is popped from the queue while
+    /// the returned reference exists.
+    unsafe fn peek<'slots, T, S: Slots<T> + ?Sized>(
+        &self,
+        slots: &'slots S,
+    ) -> Result<&'slots T, TryRecvError> {
+        test_println!("peek");
+        let mut head = self.head.load(Acquire);
+
+        loop {
+            test_dbg!(head);
+            let (idx, gen) = self.idx_gen(head);
+            let slot = slots.get(idx);
+            let raw_state = test_dbg!(slot.state.load(Acquire));
+
+            // If the slot's state is ahead of the head index by one, it has
+            // been written to. Because nothing else may pop from the queue,
+            // it will not be overwritten until the reference is released.
+            if test_dbg!(raw_state == head + 1) {
+                // Safety: a readable slot has always been initialized.
+                return Ok(&*slot.value.get_mut().deref().as_ptr());
+            }
+
+            // See `pop_ref` for why this is a RMW rather than a load.
+            let tail = test_dbg!(self.tail.fetch_or(0, SeqCst));
+            if test_dbg!(tail & !self.closed == head) {
+                return if test_dbg!(tail & self.closed != 0) {
+                    Err(TryRecvError::Closed)
+                } else {
+                    Err(TryRecvError::Empty)
+                };
+            }
+
+            // Someone is still writing to the slot.
+            if test_dbg!(raw_state == head) {
+                return Err(TryRecvError::Empty);
+            }
+
+            // The slot was skipped. Advance the head index past it, just as
+            // `pop_ref` would.
+            let next_head = self.next(idx, gen);
+            head = match test_dbg!(self.head.compare_exchange(head, next_head, SeqCst, Acquire)) {
+                Ok(_) => {
+                    #[cfg(feature = "seq")]
+                    self.rx_skipped.fetch_add(1, Relaxed);
+                    next_head
+                }
+                Err(actual) => actual,
+            };
+        }
+    }
+
+    /// Hints that the elements in the next `n` slots starting at the head,
+    /// up to `prefetch::DISTANCE` of them, will be read soon.
+    ///
+    /// This only reads the head index, so it is only meaningful when called
+    /// by the single consumer.
+    #[cfg(all(feature = "prefetch", feature = "alloc"))]
+    fn prefetch<T, S: Slots<T> + ?Sized>(&self, slots: &S, n: usize) {
+        let mut head = self.head.load(Relaxed);
+        for _ in 0..n.min(util::prefetch::DISTANCE) {
+            let (idx, gen) = self.idx_gen(head);
+            util::prefetch::prefetch(slots.get(idx));
+            head = self.next(idx, gen);
+        }
+    }
+
+    /// Returns the sequence number of the element at position `pos`, given
+    /// the number of slots that were skipped before it.
+    ///
+    /// Each lap around the ring assigns `capacity` sequence numbers, so this
+    /// counts the elements pushed before `pos`.
+    #[cfg(feature = "seq")]
+    fn seq(&self, pos: usize, skipped: usize) -> usize {
+        let (idx, gen) = self.idx_gen(pos);
+        (gen / self.gen)
+            .wrapping_mul(self.capacity)
+            .wrapping_add(idx)
+            .wrapping_sub(skipped)
+    }
+
+    /// Returns the sequence number that will be assigned to the next element
+    /// pushed to the queue.
+    #[cfg(feature = "seq")]
+    fn published_seq(&self) -> usize {
+        let tail = self.tail.load(Acquire);
+        self.seq(tail, self.tx_skipped.load(Acquire))
+    }
+
+    /// Returns the sequence number of the next element that will be popped
+    /// from the queue.
+    #[cfg(feature = "seq")]
+    fn consumed_seq(&self) -> usize {
+        let head = self.head.load(Acquire);
+        self.seq(head, self.rx_skipped.load(Acquire))
+    }
+
+    /// Recycles the element in a slot that has been claimed by a push,
+    /// recording the capacity shed by the recycling policy.
+    #[inline]
+    fn recycle_element<T, R: Recycle<T>>(&self, recycle: &R, element: &mut T) {
+        #[cfg(feature = "stats")]
+        self.recycling
+            .record_reused(recycle.recycle_shrunk(element));
+        #[cfg(not(feature = "stats"))]
+        recycle.recycle(element);
+    }
+
+    #[cfg(feature = "stats")]
+    #[inline]
+    fn record_occupancy(&self) {
+        self.occupancy.record(self.len(), self.capacity);
+    }
+
+    /// Records the time the element in `slot` spent in the queue. The slot
+    /// must have been claimed by a pop.
+    #[cfg(all(feature = "stats", feature = "timestamps"))]
+    #[inline]
+    fn record_latency<T>(&self, slot: SlotRef<'_, T>) {
+        let enqueued_at = slot.enqueued_at.with(|enqueued_at| unsafe {
+            // Safety: the slot has been claimed by a pop, so its pusher has
+            // finished writing to it.
+            *enqueued_at
+        });
+        if let Some(enqueued_at) = enqueued_at {
+            self.latency.record(enqueued_at.elapsed());
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    fn stats(&self) -> stats::Stats {
+        stats::Stats {
+            occupancy: self.occupancy.histogram(self.capacity),
+            #[cfg(feature = "timestamps")]
+            latency: self.latency.histogram(),
+            recycling: self.recycling.snapshot(),
+        }
+    }
+
+    /// Returns a snapshot of the state of each slot.
+    ///
+    /// The slots between the head and the tail have been claimed by pushes,
+    /// and are occupied once their elements have been published. Any other
+    /// slot is empty, unless a pop that has already advanced the head past it
+    /// is still reading from it.
+    #[cfg(all(feature = "stats", feature = "alloc"))]
+    fn slot_states<T, S: Slots<T> + ?Sized>(&self, slots: &S) -> stats::SlotStates {
+        use stats::SlotState;
+
+        let mut states = stats::SlotStates::new(self.capacity);
+        let (head, tail) = self.snapshot_bounds();
+        let mut in_queue = alloc::vec![false; self.capacity];
+        let mut pos = head;
+        for _ in 0..self.distance(head, tail).min(self.capacity) {
+            let (idx, _) = self.idx_gen(pos);
+            in_queue[idx] = true;
+            let state = test_dbg!(slots.get(idx).state.load(Acquire));
+            if check_has_reader(state) {
+                // The slot was skipped by a push, as it is still being read.
+                states.set(idx, SlotState::Claimed);
+            } else if state == pos + 1 {
+                states.set(idx, SlotState::Occupied);
+            } else if state == pos {
+                states.set(idx, SlotState::Claimed);
+            }
+            pos = self.next_pos(pos);
+        }
+
+        for (idx, _) in in_queue.iter().enumerate().filter(|(_, &seen)| !seen) {
+            let state = test_dbg!(slots.get(idx).state.load(Acquire));
+            if check_has_reader(state) {
+                states.set(idx, SlotState::Claimed);
+            }
+        }
+
+        states
+    }
+
     fn len(&self) -> usize {
         loop {
             let tail = self.tail.load(SeqCst);
@@ -455,7 +1361,140 @@ impl Core {
         }
     }
 
-    fn drop_slots<T>(&mut self, slots: &mut [Slot<T>]) {
+    /// Recomputes the checksum of every element in the queue, after the
+    /// elements have been modified in place.
+    ///
+    /// This requires exclusive access to the core and the slots, so no `Ref`s
+    /// may be outstanding.
+    #[cfg(all(feature = "high-integrity", feature = "alloc"))]
+    fn stamp_checksums<T, S: Slots<T> + ?Sized>(&mut self, slots: &S, checksum: fn(&T) -> u64) {
+        let (head, tail) = self.snapshot_bounds();
+        let mut pos = head;
+        while pos != tail {
+            let (idx, _) = self.idx_gen(pos);
+            let slot = slots.get(idx);
+            let sum = slot.value.with(|value| unsafe {
+                // Safety: every slot between the head and the tail has been
+                // initialized, and we have exclusive access to the slots.
+                checksum(&*(*value).as_ptr())
+            });
+            slot.checksum.with_mut(|slot_sum| unsafe {
+                // Safety: as above.
+                *slot_sum = sum
+            });
+            pos = self.next_pos(pos);
+        }
+    }
+
+    /// Removes every element for which `f` returns `false`, shifting the
+    /// retained elements towards the head so that their order is preserved.
+    ///
+    /// This requires exclusive access to the core and the slots, so no `Ref`s
+    /// may be outstanding. Removed elements are left in place to be recycled
+    /// by a later `push_ref`, unless the slot they occupy would be considered
+    /// uninitialized once the tail index is moved back, in which case they are
+    /// dropped.
+    fn retain<T, S: Slots<T> + ?Sized>(
+        &mut self,
+        slots: &mut S,
+        mut f: impl FnMut(&mut T) -> bool,
+    ) {
+        let head = self.head.load(SeqCst);
+        let raw_tail = self.tail.load(SeqCst);
+        let tail = raw_tail & !self.closed;
+
+        let mut read = head;
+        let mut write = head;
+        while read != tail {
+            let (read_idx, read_gen) = self.idx_gen(read);
+            let state = clear_has_reader(slots.get(read_idx).state.load(SeqCst));
+            // Slots that were skipped by a writer while they had an active
+            // reader don't hold an element for this lap.
+            if state == read + 1 {
+                let keep = slots.get(read_idx).value.with_mut(|value| unsafe {
+                    // Safety: every slot between the head and the tail has been
+                    // initialized, and we have exclusive access to the slots.
+                    f(&mut *(*value).as_mut_ptr())
+                });
+                if keep {
+                    if write != read {
+                        let (write_idx, _) = self.idx_gen(write);
+                        let dst = slots.get(write_idx).value.with_mut(|value| value);
+                        slots.get(read_idx).value.with_mut(|src| unsafe {
+                            // Safety: both slots are initialized, and are
+                            // distinct, since `write` is always behind `read`.
+                            ptr::swap(src, dst)
+                        });
+                        #[cfg(feature = "timestamps")]
+                        {
+                            let dst = slots.get(write_idx).enqueued_at.with_mut(|t| t);
+                            // Safety: as above.
+                            slots
+                                .get(read_idx)
+                                .enqueued_at
+                                .with_mut(|src| unsafe { ptr::swap(src, dst) });
+                        }
+                    }
+                    let (write_idx, write_gen) = self.idx_gen(write);
+                    slots.get(write_idx).state.store(write + 1, SeqCst);
+                    write = self.next(write_idx, write_gen);
+                }
+            }
+            read = self.next(read_idx, read_gen);
+        }
+
+        // Everything between the new tail and the old one is now free.
+        let mut free = write;
+        while free != tail {
+            let (idx, gen) = self.idx_gen(free);
+            let slot = slots.get(idx);
+            if gen == 0 && !self.prefilled {
+                // `push_ref` (and `drop_slots`) treat slots in the first lap
+                // as uninitialized, so drop the removed element now rather
+                // than leaking it.
+                unsafe {
+                    slot.value
+                        .with_mut(|value| ptr::drop_in_place((*value).as_mut_ptr()));
+                }
+            }
+            slot.state.store(free, SeqCst);
+            free = self.next(idx, gen);
+        }
+
+        self.tail.store(write | (raw_tail & self.closed), SeqCst);
+    }
+
+    /// Initializes every slot that has not been pushed to yet with a new
+    /// element from `recycle`, so that pushes in the first lap recycle an
+    /// existing element rather than creating one.
+    ///
+    /// This requires exclusive access to the core and the slots.
+    #[cfg(feature = "alloc")]
+    fn pre_fill<T, S, R>(&mut self, slots: &mut S, recycle: &R)
+    where
+        S: Slots<T> + ?Sized,
+        R: Recycle<T>,
+    {
+        if self.prefilled {
+            return;
+        }
+
+        let tail = self.tail.load(SeqCst);
+        let (idx, gen) = self.idx_gen(tail);
+        if gen == 0 {
+            for idx in idx..self.capacity() {
+                slots.get(idx).value.with_mut(|value| unsafe {
+                    // Safety: slots at or after the tail in the first lap
+                    // have never been written to.
+                    (*value).as_mut_ptr().write(recycle.new_element())
+                });
+            }
+        }
+        self.prefilled = true;
+        test_println!("pre-filled slots {}..{}", idx, self.capacity());
+    }
+
+    fn drop_slots<T, S: Slots<T> + ?Sized>(&mut self, slots: &mut S) {
         debug_assert!(
             !self.has_dropped_slots,
             "tried to drop slots twice! core={:#?}",
@@ -467,8 +1506,13 @@ impl Core {
 
         let tail = self.tail.load(SeqCst);
         let (idx, gen) = self.idx_gen(tail);
-        let num_initialized = if gen > 0 { self.capacity() } else { idx };
-        for slot in &mut slots[..num_initialized] {
+        let num_initialized = if gen > 0 || self.prefilled {
+            self.capacity()
+        } else {
+            idx
+        };
+        for idx in 0..num_initialized {
+            let slot = slots.get(idx);
             unsafe {
                 slot.value
                     .with_mut(|value| ptr::drop_in_place((*value).as_mut_ptr()));
@@ -512,8 +1556,15 @@ impl Drop for Core {
 // === impl Ref ===
 
 impl<T> Ref<'_, T> {
+    /// Panics if this `Ref`'s slot has been claimed by someone else.
+    #[inline]
+    fn check_token(&self) {
+        self.token.check(self.slot.state, self.is_pop);
+    }
+
     #[inline]
     fn with<U>(&self, f: impl FnOnce(&T) -> U) -> U {
+        self.check_token();
         self.ptr.with(|value| unsafe {
             // Safety: if a `Ref` exists, we have exclusive ownership of the
             // slot. A `Ref` is only created if the slot has already been
@@ -526,6 +1577,7 @@ impl<T> Ref<'_, T> {
 
     #[inline]
     fn with_mut<U>(&mut self, f: impl FnOnce(&mut T) -> U) -> U {
+        self.check_token();
         self.ptr.with(|value| unsafe {
             // Safety: if a `Ref` exists, we have exclusive ownership of the
             // slot.
@@ -541,6 +1593,7 @@ impl<T> ops::Deref for Ref<'_, T> {
 
     #[inline]
     fn deref(&self) -> &Self::Target {
+        self.check_token();
         unsafe {
             // Safety: if a `Ref` exists, we have exclusive ownership of the
             // slot. A `Ref` is only created if the slot has already been
@@ -553,6 +1606,7 @@ impl<T> ops::Deref for Ref<'_, T> {
 impl<T> ops::DerefMut for Ref<'_, T> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
+        self.check_token();
         unsafe {
             // Safety: if a `Ref` exists, we have exclusive ownership of the
             // slot. A `Ref` is only created if the slot has already been
@@ -562,9 +1616,65 @@ impl<T> ops::DerefMut for Ref<'_, T> {
     }
 }
 
+impl<'slot, T> Ref<'slot, T> {
+    /// Sets the function used to compute the checksum of a pushed element
+    /// when it is published.
+    #[cfg(all(feature = "high-integrity", feature = "alloc"))]
+    #[inline]
+    fn with_checksum(mut self, checksum: Option<fn(&T) -> u64>) -> Self {
+        debug_assert!(!self.is_pop, "only pushed elements are checksummed");
+        self.checksum = checksum;
+        self
+    }
+
+    /// Sets the queue whose waiting thread is woken once this slot is
+    /// released.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    #[inline]
+    fn with_wake(mut self, wake: &'slot wait::WaitQueue<mpsc::blocking::park::Unparker>) -> Self {
+        self.wake = Some(wake);
+        self
+    }
+
+    /// Panics if the checksum of a popped element does not match the one
+    /// computed when it was pushed.
+    #[cfg(all(feature = "high-integrity", feature = "alloc"))]
+    fn verify_checksum(&self, checksum: fn(&T) -> u64) {
+        let expected = self.slot.checksum.with(|sum| unsafe {
+            // Safety: if a `Ref` exists, we have exclusive ownership of the
+            // slot.
+            *sum
+        });
+        let actual = self.with(checksum);
+        assert!(
+            actual == expected,
+            "a popped element failed its integrity check: its checksum was {:#x} \
+            when it was pushed, but is now {:#x}; the element was corrupted \
+            while it was in the queue",
+            expected,
+            actual,
+        );
+    }
+
+    /// Returns when the element in this slot was pushed.
+    #[cfg(feature = "timestamps")]
+    #[inline]
+    fn enqueued_at(&self) -> Option<std::time::Instant> {
+        self.slot.enqueued_at.with(|enqueued_at| unsafe {
+            // Safety: if a `Ref` exists, we have exclusive ownership of the
+            // slot.
+            *enqueued_at
+        })
+    }
+}
+
 impl<T> Drop for Ref<'_, T> {
     #[inline]
     fn drop(&mut self) {
+        // Don't panic while already unwinding.
+        if !crate::util::panic::panicking() {
+            self.check_token();
+        }
         if self.is_pop {
             test_println!("drop Ref<{}> (pop)", core::any::type_name::<T>());
             test_dbg!(self.slot.state.fetch_and(!HAS_READER, SeqCst));
@@ -574,8 +1684,54 @@ impl<T> Drop for Ref<'_, T> {
                 core::any::type_name::<T>(),
                 self.new_state
             );
+            #[cfg(feature = "timestamps")]
+            self.slot.enqueued_at.with_mut(|enqueued_at| unsafe {
+                // Safety: we have exclusive ownership of the slot until its
+                // state is updated.
+                *enqueued_at = Some(std::time::Instant::now())
+            });
+            #[cfg(feature = "high-integrity")]
+            if let Some(checksum) = self.checksum {
+                let sum = self.ptr.with(|value| unsafe {
+                    // Safety: we have exclusive ownership of the slot, and it
+                    // has been initialized.
+                    checksum(&*(*value).as_ptr())
+                });
+                self.slot.checksum.with_mut(|slot_sum| unsafe {
+                    // Safety: we have exclusive ownership of the slot until its
+                    // state is updated.
+                    *slot_sum = sum
+                });
+            }
             test_dbg!(self.slot.state.store(test_dbg!(self.new_state), Release));
         }
+        #[cfg(all(feature = "std", not(all(loom, test))))]
+        if let Some(wake) = self.wake {
+            // Only wake a thread if one is waiting, so that queues that are
+            // never blocked on don't write to the wait queue's state.
+            wake.notify_waiting();
+        }
+    }
+}
+
+impl<T> AsRef<T> for Ref<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T> AsMut<T> for Ref<'_, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: PartialEq> PartialEq<T> for Ref<'_, T> {
+    #[inline]
+    fn eq(&self, other: &T) -> bool {
+        self.with(|val| val == other)
     }
 }
 
@@ -614,17 +1770,52 @@ unsafe impl<T: Send> Sync for Ref<'_, T> {}
 // === impl Slot ===
 
 impl<T> Slot<T> {
+    /// Returns a heap-allocated array of `capacity` empty slots.
     #[cfg(feature = "alloc")]
-    pub(crate) fn make_boxed_array(capacity: usize) -> alloc::boxed::Box<[Self]> {
+    #[must_use]
+    pub fn make_boxed_array(capacity: usize) -> alloc::boxed::Box<[Self]> {
         (0..capacity).map(|i| Slot::new(i)).collect()
     }
 
+    /// Calls `place` to allocate an array of `capacity` empty slots, and
+    /// checks that it actually did so.
+    ///
+    /// # Panics
+    ///
+    /// If the returned array's length is not `capacity`, or if any of its
+    /// slots are not empty.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn place_boxed_array(
+        capacity: usize,
+        place: impl FnOnce(usize) -> alloc::boxed::Box<[Self]>,
+    ) -> alloc::boxed::Box<[Self]> {
+        let slots = place(capacity);
+        assert_eq!(
+            slots.len(),
+            capacity,
+            "slot placement returned an array of the wrong length"
+        );
+        assert!(
+            slots
+                .iter()
+                .enumerate()
+                .all(|(idx, slot)| slot.state.load(Acquire) == idx),
+            "slot placement must return a new array from `Slot::make_boxed_array`"
+        );
+        slots
+    }
+
     feature! {
         #![all(feature = "static", not(all(loom, test)))]
 
+        #[allow(clippy::declare_interior_mutable_const)]
         const EMPTY: Self = Self::new(usize::MAX);
 
-        pub(crate) const fn make_static_array<const CAPACITY: usize>() -> [Self; CAPACITY] {
+        /// Returns an array of `CAPACITY` empty slots.
+        ///
+        /// This is a `const fn`, so it may be used to initialize a `static`.
+        #[must_use]
+        pub const fn make_static_array<const CAPACITY: usize>() -> [Self; CAPACITY] {
             let mut array = [Self::EMPTY; CAPACITY];
             let mut i = 0;
             while i < CAPACITY {
@@ -636,24 +1827,100 @@ impl<T> Slot<T> {
         }
     }
 
-    #[cfg(not(all(loom, test)))]
-    const fn new(idx: usize) -> Self {
-        Self {
-            value: UnsafeCell::new(MaybeUninit::uninit()),
-            state: AtomicUsize::new(idx),
+    loom_const_fn! {
+        fn new(idx: usize) -> Self {
+            Self {
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                state: AtomicUsize::new(idx),
+                #[cfg(feature = "timestamps")]
+                enqueued_at: UnsafeCell::new(None),
+                #[cfg(feature = "high-integrity")]
+                checksum: UnsafeCell::new(0),
+            }
         }
     }
+}
+
+unsafe impl<T: Sync> Sync for Slot<T> {}
+
+impl<T> Slots<T> for [Slot<T>] {
+    #[inline]
+    fn len(&self) -> usize {
+        <[Slot<T>]>::len(self)
+    }
 
-    #[cfg(all(loom, test))]
-    fn new(idx: usize) -> Self {
-        Self {
-            value: UnsafeCell::new(MaybeUninit::uninit()),
-            state: AtomicUsize::new(idx),
+    #[inline]
+    unsafe fn get_unchecked(&self, idx: usize) -> SlotRef<'_, T> {
+        let slot = <[Slot<T>]>::get_unchecked(self, idx);
+        SlotRef {
+            value: &slot.value,
+            state: &slot.state,
+            #[cfg(feature = "timestamps")]
+            enqueued_at: &slot.enqueued_at,
+            #[cfg(feature = "high-integrity")]
+            checksum: &slot.checksum,
         }
     }
 }
 
-unsafe impl<T: Sync> Sync for Slot<T> {}
+impl<T, const CAPACITY: usize> Slots<T> for [Slot<T>; CAPACITY] {
+    #[inline]
+    fn len(&self) -> usize {
+        CAPACITY
+    }
+
+    #[inline]
+    unsafe fn get_unchecked(&self, idx: usize) -> SlotRef<'_, T> {
+        Slots::get_unchecked(&self[..], idx)
+    }
+}
+
+// === impl Pinned ===
+
+impl<T> Pinned<'_, T> {
+    /// Returns a mutable reference to the pinned element.
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        unsafe {
+            // Safety: the slot holds an initialized element, and pinning it
+            // prevents anyone else from accessing it until it is unpinned.
+            &mut *self.ptr.deref().as_mut_ptr()
+        }
+    }
+}
+
+impl<T> Drop for Pinned<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        test_dbg!(self.state.store(self.readable, SeqCst));
+    }
+}
+
+// === impl ClaimedBatch ===
+
+#[cfg(feature = "alloc")]
+impl<T, S: Slots<T> + ?Sized> Drop for ClaimedBatch<'_, T, S> {
+    fn drop(&mut self) {
+        for _ in 0..self.remaining {
+            let (idx, gen) = self.core.idx_gen(self.head);
+            test_println!("releasing unread slot [{}]", idx);
+            let new_state = wrapping_add(self.head, self.core.gen);
+            Core::start_batch_read(self.slots.get(idx), self.head, new_state);
+            self.head = self.core.next(idx, gen);
+        }
+    }
+}
+
+// === impl SlotRef ===
+
+impl<T> Clone for SlotRef<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SlotRef<'_, T> {}
 
 // === impl Full ===
 
@@ -682,10 +1949,49 @@ impl<T> fmt::Display for Full<T> {
 #[cfg(feature = "std")]
 impl<T> std::error::Error for Full<T> {}
 
+#[cfg(feature = "std")]
+impl<T: Send + Sync + 'static> From<Full<T>> for std::io::Error {
+    fn from(err: Full<T>) -> Self {
+        std::io::Error::new(std::io::ErrorKind::WouldBlock, err)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // `StaticThingBuf::new` must be usable in a `static` even when the crate's
+    // own tests are being built.
+    #[cfg(all(feature = "static", not(loom)))]
+    #[test]
+    fn static_new_in_test_cfg() {
+        static BUF: StaticThingBuf<usize, 4> = StaticThingBuf::new();
+        BUF.push(1).unwrap();
+        assert_eq!(BUF.pop(), Some(1));
+    }
+
+    #[cfg(all(feature = "alloc", not(loom)))]
+    #[test]
+    fn builder_aligns_slots() {
+        let q = ThingBuf::<u8>::builder(3).align(4096).build();
+        assert_eq!(q.slots.as_ptr() as usize % 4096, 0);
+        q.push(1).unwrap();
+        assert_eq!(q.pop(), Some(1));
+
+        let q = ThingBuf::<u8>::builder(3)
+            .align(4096)
+            .layout(SlotLayout::Split)
+            .build();
+        assert_eq!(q.slots.as_ptr() as usize % 4096, 0);
+        q.push(1).unwrap();
+        assert_eq!(q.pop(), Some(1));
+
+        let q = ThingBuf::<u64>::builder(1 << 18).huge_pages(true).build();
+        #[cfg(all(target_os = "linux", feature = "huge-pages"))]
+        assert_eq!(q.slots.as_ptr() as usize % (2 * 1024 * 1024), 0);
+        assert_eq!(q.capacity(), 1 << 18);
+    }
+
     #[test]
     fn zero_len() {
         const CAP: usize = 16;
@@ -709,6 +2015,22 @@ mod tests {
         core.has_dropped_slots = true;
     }
 
+    #[cfg(all(any(feature = "debug-refs", debug_assertions), not(loom)))]
+    #[test]
+    #[should_panic(expected = "accessed a stale push `Ref` to slot [0]")]
+    fn stale_ref_panics() {
+        let mut core = Core::new(2);
+        // don't panic in drop impl.
+        core.has_dropped_slots = true;
+        let slots: Box<[Slot<usize>]> = Slot::<usize>::make_boxed_array(2);
+        let recycle = recycling::DefaultRecycle::new();
+
+        let mut slot = core.push_ref(&slots[..], &recycle).unwrap();
+        // Simulate a bug that lets another push claim the same slot.
+        slots[0].state.store(core.gen, SeqCst);
+        *slot = 1;
+    }
+
     #[test]
     fn full_simple() {
         const CAP: usize = 3;
@@ -716,11 +2038,11 @@ mod tests {
         let slots: Box<[Slot<usize>]> = Slot::<usize>::make_boxed_array(CAP);
         let recycle = recycling::DefaultRecycle::new();
 
-        core.push_ref(&slots, &recycle).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
         assert!(matches!(
-            core.push_ref(&slots, &recycle),
+            core.push_ref(&slots[..], &recycle),
             Err(TrySendError::Full(()))
         ));
 
@@ -735,15 +2057,15 @@ mod tests {
         let slots: Box<[Slot<usize>]> = Slot::<usize>::make_boxed_array(CAP);
         let recycle = recycling::DefaultRecycle::new();
 
-        core.push_ref(&slots, &recycle).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
-        core.pop_ref(&slots).unwrap();
-        core.pop_ref(&slots).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.pop_ref(&slots[..]).unwrap();
+        core.pop_ref(&slots[..]).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
         assert!(matches!(
-            core.push_ref(&slots, &recycle),
+            core.push_ref(&slots[..], &recycle),
             Err(TrySendError::Full(()))
         ));
 
@@ -758,18 +2080,18 @@ mod tests {
         let slots: Box<[Slot<usize>]> = Slot::<usize>::make_boxed_array(CAP);
         let recycle = recycling::DefaultRecycle::new();
 
-        core.push_ref(&slots, &recycle).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
-        core.pop_ref(&slots).unwrap();
-        let _hold = core.pop_ref(&slots).unwrap();
-        core.pop_ref(&slots).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
-        core.pop_ref(&slots).unwrap();
-        core.push_ref(&slots, &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.pop_ref(&slots[..]).unwrap();
+        let _hold = core.pop_ref(&slots[..]).unwrap();
+        core.pop_ref(&slots[..]).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
+        core.pop_ref(&slots[..]).unwrap();
+        core.push_ref(&slots[..], &recycle).unwrap();
         assert!(matches!(
-            core.push_ref(&slots, &recycle),
+            core.push_ref(&slots[..], &recycle),
             Err(TrySendError::Full(()))
         ));
 
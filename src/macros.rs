@@ -78,6 +78,27 @@ macro_rules! feature {
     }
 }
 
+/// Defines a function that is a `const fn`, except when building the crate's
+/// own loom tests.
+///
+/// Loom's atomics and cells can't be constructed in a `const` context, so
+/// constructors that create them can only be `const` when loom is not in use.
+/// This lets both configurations share a single function body.
+macro_rules! loom_const_fn {
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident($($arg:ident: $T:ty),* $(,)?) -> $Ret:ty $body:block
+    ) => {
+        $(#[$meta])*
+        #[cfg(not(all(loom, test)))]
+        $vis const fn $name($($arg: $T),*) -> $Ret $body
+
+        $(#[$meta])*
+        #[cfg(all(loom, test))]
+        $vis fn $name($($arg: $T),*) -> $Ret $body
+    };
+}
+
 macro_rules! fmt_bits {
     ($self: expr, $f: expr, $has_states: ident, $($name: ident),+) => {
         $(
@@ -28,7 +28,8 @@
 //! As these are multi-producer, single-consumer channels, the [`Sender`] type
 //! implements `Clone`; it may be cloned any number of times to create multiple
 //! [`Sender`]s that send messages to the same channel. On the other hand, each
-//! channel instance has only a single [`Receiver`].
+//! channel instance has only a single [`Receiver`]. Additional [`Sender`]s may
+//! also be created from the [`Receiver`], using [`Receiver::sender`].
 //!
 //! # Disconnection
 //!
@@ -43,6 +44,23 @@
 //! be read out of the channel. In this case, all further attempts to send will
 //! result in an error.
 //!
+//! # Ordering
+//!
+//! Messages sent by the same [`Sender`] from a single thread or task are
+//! always received in the order in which they were sent. This holds even when
+//! the [`Receiver`] has enabled [load shedding], which may drop messages but
+//! never reorders them. Messages sent concurrently by different senders are
+//! interleaved in the order in which they claimed slots in the channel.
+//!
+//! The [sharded] blocking channel only orders messages within each shard, and
+//! assigns each clone of a sender to a different shard. Attach the same
+//! [producer token] to several of its senders to keep the messages sent
+//! through them in order.
+//!
+//! [load shedding]: Receiver::set_load_shedding
+//! [sharded]: blocking::sharded
+//! [producer token]: blocking::sharded::ProducerToken
+//!
 //! # Channel Flavors
 //!
 //! This module contains several different "flavors" of multi-producer,
@@ -151,6 +169,12 @@
 //! that dynamic allocation is not needed.The [`StaticChannel`] and
 //! [`blocking::StaticChannel`] types are used to construct static channels.
 //!
+//! Between the two, a [`Channel`] has a capacity that is determined at
+//! compile-time, like a static channel, but is split into an
+//! [`InlineSender`]/[`InlineReceiver`] pair by moving it into a single heap
+//! allocation, so that its slots are stored inline with the rest of the
+//! channel's state.
+//!
 //! A dynamically allocated channel's size can be determined at runtime:
 //!
 //! ```
@@ -247,8 +271,16 @@
 //! [blocking receiver]: blocking::Receiver
 //! [blocking sender]: blocking::Sender
 use crate::{
-    loom::{atomic::AtomicUsize, hint},
+    loom::{
+        atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed},
+        hint,
+    },
     recycling::{take, Recycle},
+    util::{
+        ref_audit::{AuditGuard, RefAudit},
+        ref_watch::RefWatch,
+        Backoff,
+    },
     wait::{Notify, WaitCell, WaitQueue, WaitResult},
     Core, Ref, Slot,
 };
@@ -263,6 +295,84 @@ struct ChannelCore<N> {
     rx_wait: WaitCell<N>,
     tx_count: AtomicUsize,
     tx_wait: WaitQueue<N>,
+    /// Senders waiting in `flush` for the messages sent before them to be
+    /// received.
+    #[cfg(feature = "seq")]
+    flush_wait: WaitQueue<N>,
+    /// The number of times to retry an operation (with backoff) before
+    /// registering as a waiter.
+    spin_budget: AtomicUsize,
+    /// If `true`, `spin_budget` is adjusted based on whether spinning
+    /// succeeds.
+    adaptive_spin: AtomicBool,
+    /// The occupancy above which sends are probabilistically dropped, or
+    /// `usize::MAX` if load shedding is disabled.
+    shed_threshold: AtomicUsize,
+    /// State for the random number generator used for load shedding.
+    shed_rng: AtomicUsize,
+    /// The number of messages dropped by load shedding.
+    shed_count: AtomicUsize,
+    /// The number of queued messages at which a send wakes the receiver. If
+    /// this is 1 (the default), every send wakes the receiver.
+    wake_watermark: AtomicUsize,
+    /// How long a blocking receiver waits for the wake watermark to be
+    /// reached, in microseconds.
+    #[cfg(feature = "std")]
+    wake_max_delay: AtomicUsize,
+    /// The [`group::ChannelGroup`]s that this channel has been added to.
+    #[cfg(all(feature = "alloc", not(all(loom, test))))]
+    groups: group::Links,
+    /// Counts outstanding `SendRef`s and `RecvRef`s, when the "audit-refs"
+    /// feature is enabled.
+    ref_audit: RefAudit,
+    /// The [`NotifyPolicy`] used to wake waiting senders, encoded by
+    /// [`NotifyPolicy::to_usize`].
+    notify_policy: AtomicUsize,
+}
+
+/// How a channel wakes senders that are waiting for capacity, when a receiver
+/// frees slots.
+///
+/// By default, a channel uses [`NotifyPolicy::PerSlot`]. Which policy gives
+/// the best tail latency depends on the workload: see the documentation for
+/// each variant for details. The policy is set with the `set_notify_policy`
+/// method on a channel's receiver.
+///
+/// Regardless of the policy, a slot that is freed while no senders are
+/// waiting is remembered, so that the next sender to wait for capacity is not
+/// put to sleep.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum NotifyPolicy {
+    /// Wake one waiting sender for each freed slot, one at a time.
+    ///
+    /// When a receiver frees several slots at once, such as with
+    /// `recv_into`, each wakeup acquires the wait queue's lock separately,
+    /// letting senders that have already been woken start sending while the
+    /// rest are being woken.
+    PerSlot,
+    /// Wake as many waiting senders as there are freed slots, or every
+    /// waiting sender if there are fewer of them, all at once.
+    ///
+    /// This acquires the wait queue's lock once per batch of freed slots,
+    /// rather than once per slot, which reduces contention with senders
+    /// that are starting to wait when a receiver drains the channel in large
+    /// batches.
+    Batched,
+    /// Wake *every* waiting sender when at least `threshold` slots are freed
+    /// at once, and otherwise behave like [`NotifyPolicy::Batched`].
+    ///
+    /// When a large batch of slots is freed, waking every sender lets them
+    /// all compete for the free slots immediately; senders that lose the
+    /// race go back to waiting. This can improve tail latency for channels
+    /// with many bursty senders, at the cost of spurious wakeups.
+    ///
+    /// A `threshold` of 0 is treated as 1, and a `threshold` greater than
+    /// the channel's capacity is treated as the channel's capacity.
+    WakeAll {
+        /// The number of slots that must be freed at once for every waiting
+        /// sender to be woken.
+        threshold: usize,
+    },
 }
 
 struct SendRefInner<'a, T, N: Notify> {
@@ -283,6 +393,16 @@ struct SendRefInner<'a, T, N: Notify> {
     // bad option here. Just don't reorder these fields. :)
     slot: Ref<'a, T>,
     _notify: NotifyRx<'a, N>,
+    _audit: AuditGuard<'a>,
+    _watch: RefWatch,
+}
+
+#[cfg(feature = "std")]
+struct TransactionInner<'a, T, N: Notify> {
+    // The slots must be released before the receiver is notified, as with
+    // `SendRefInner`. `Drop` releases them, in reverse order.
+    slots: alloc::vec::Vec<Ref<'a, T>>,
+    _notify: NotifyRx<'a, N>,
 }
 
 struct RecvRefInner<'a, T, N: Notify + Unpin> {
@@ -303,32 +423,232 @@ struct RecvRefInner<'a, T, N: Notify + Unpin> {
     // bad option here. Just don't reorder these fields. :)
     slot: Ref<'a, T>,
     _notify: crate::mpsc::NotifyTx<'a, N>,
+    _audit: AuditGuard<'a>,
+    _watch: RefWatch,
 }
 
-struct NotifyRx<'a, N: Notify>(&'a WaitCell<N>);
-struct NotifyTx<'a, N: Notify + Unpin>(&'a WaitQueue<N>);
+struct NotifyRx<'a, N: Notify>(&'a ChannelCore<N>);
+struct NotifyTx<'a, N: Notify + Unpin>(&'a ChannelCore<N>);
 
 // ==== impl Inner ====
 
 impl<N> ChannelCore<N> {
-    #[cfg(not(loom))]
-    const fn new(capacity: usize) -> Self {
-        Self {
-            core: Core::new(capacity),
-            rx_wait: WaitCell::new(),
-            tx_count: AtomicUsize::new(1),
-            tx_wait: WaitQueue::new(),
+    loom_const_fn! {
+        fn new(capacity: usize) -> Self {
+            Self {
+                core: Core::new(capacity),
+                rx_wait: WaitCell::new(),
+                tx_count: AtomicUsize::new(1),
+                tx_wait: WaitQueue::new(),
+                #[cfg(feature = "seq")]
+                flush_wait: WaitQueue::new(),
+                spin_budget: AtomicUsize::new(0),
+                adaptive_spin: AtomicBool::new(false),
+                shed_threshold: AtomicUsize::new(usize::MAX),
+                shed_rng: AtomicUsize::new(0),
+                shed_count: AtomicUsize::new(0),
+                wake_watermark: AtomicUsize::new(1),
+                #[cfg(feature = "std")]
+                wake_max_delay: AtomicUsize::new(0),
+                #[cfg(all(feature = "alloc", not(all(loom, test))))]
+                groups: group::Links::new(),
+                ref_audit: RefAudit::new(),
+                notify_policy: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    /// The largest spin budget that adaptive spinning will grow to.
+    const MAX_ADAPTIVE_SPINS: usize = 1 << 10;
+
+    /// Returns the number of times to retry an operation before waiting.
+    ///
+    /// When adaptive spinning is enabled, this is always at least 1, so that
+    /// the channel can observe whether spinning would succeed.
+    #[inline]
+    fn current_spin_budget(&self) -> (usize, bool) {
+        let budget = self.spin_budget.load(Relaxed);
+        if self.adaptive_spin.load(Relaxed) {
+            (budget.max(1), true)
+        } else {
+            (budget, false)
         }
     }
 
-    #[cfg(loom)]
-    fn new(capacity: usize) -> Self {
-        Self {
-            core: Core::new(capacity),
-            rx_wait: WaitCell::new(),
-            tx_count: AtomicUsize::new(1),
-            tx_wait: WaitQueue::new(),
+    /// Records whether an operation that exhausted `budget` retries
+    /// succeeded, doubling the budget if so and halving it otherwise.
+    fn adapt_spin_budget(&self, budget: usize, succeeded: bool) {
+        let budget = if succeeded {
+            budget.saturating_mul(2).min(Self::MAX_ADAPTIVE_SPINS)
+        } else {
+            (budget / 2).max(1)
+        };
+        test_dbg!(self.spin_budget.store(budget, Relaxed));
+    }
+
+    /// Enables load shedding above the given occupancy ratio, or disables it
+    /// if `ratio` is `None`.
+    fn set_load_shedding(&self, ratio: Option<f64>) {
+        let threshold = match ratio {
+            Some(ratio) => {
+                assert!(
+                    (0.0..=1.0).contains(&ratio),
+                    "load shedding ratio must be between 0.0 and 1.0"
+                );
+                (ratio * self.core.capacity() as f64) as usize
+            }
+            None => usize::MAX,
+        };
+        self.shed_threshold.store(threshold, Relaxed);
+    }
+
+    /// Returns `true` if a message should be dropped rather than sent.
+    ///
+    /// Above the shedding threshold, the probability that a message is
+    /// dropped rises linearly with the channel's occupancy, reaching 1 when
+    /// the channel is full. Messages are never dropped once the channel has
+    /// closed, so that senders still observe the closed channel, or if the
+    /// threshold is the channel's capacity, so that senders still observe
+    /// the full channel.
+    #[inline]
+    fn shed(&self) -> bool {
+        let threshold = self.shed_threshold.load(Relaxed);
+        if threshold == usize::MAX {
+            return false;
+        }
+
+        let len = self.core.len();
+        if len < threshold || self.core.is_closed() {
+            return false;
+        }
+
+        let window = self.core.capacity() - threshold;
+        if window == 0 {
+            return false;
+        }
+        if self.random() % window < len - threshold {
+            test_println!("shedding message; len={}, threshold={}", len, threshold);
+            self.shed_count.fetch_add(1, Relaxed);
+            return true;
+        }
+        false
+    }
+
+    /// Sets the number of queued messages at which a send wakes the
+    /// receiver, and how long a blocking receiver waits for that many
+    /// messages before checking the channel anyway.
+    #[cfg(feature = "std")]
+    fn set_wake_watermark(&self, watermark: usize, max_delay: std::time::Duration) {
+        let max_delay = core::cmp::min(max_delay.as_micros(), usize::MAX as u128) as usize;
+        self.wake_max_delay.store(max_delay, Relaxed);
+        self.set_watermark(watermark);
+    }
+
+    /// Sets the number of queued messages at which a send wakes the
+    /// receiver.
+    #[cfg(feature = "alloc")]
+    fn set_watermark(&self, watermark: usize) {
+        // A watermark above the capacity could never be reached.
+        let watermark = watermark.clamp(1, self.core.capacity().max(1));
+        self.wake_watermark.store(watermark, Relaxed);
+    }
+
+    /// Returns how long a blocking receiver should wait before checking the
+    /// channel, or `None` if every send wakes the receiver.
+    #[cfg(feature = "std")]
+    #[inline]
+    fn wake_max_delay(&self) -> Option<std::time::Duration> {
+        if self.wake_watermark.load(Relaxed) <= 1 {
+            return None;
         }
+        let micros = self.wake_max_delay.load(Relaxed);
+        Some(std::time::Duration::from_micros(micros as u64))
+    }
+
+    /// Sets the policy used to wake waiting senders when slots are freed.
+    fn set_notify_policy(&self, policy: NotifyPolicy) {
+        let policy = match policy {
+            // A drain can't free more slots than the channel's capacity.
+            NotifyPolicy::WakeAll { threshold } => NotifyPolicy::WakeAll {
+                threshold: threshold.clamp(1, self.core.capacity().max(1)),
+            },
+            policy => policy,
+        };
+        self.notify_policy.store(policy.to_usize(), Relaxed);
+    }
+
+    #[inline]
+    fn notify_policy(&self) -> NotifyPolicy {
+        NotifyPolicy::from_usize(self.notify_policy.load(Relaxed))
+    }
+
+    /// Wakes waiting senders after `freed` slots were released at once,
+    /// according to the channel's [`NotifyPolicy`].
+    #[inline]
+    fn notify_tx(&self, freed: usize) -> usize
+    where
+        N: Notify + Unpin,
+    {
+        // Every flushing sender must check whether the messages it is waiting
+        // for have now been received.
+        #[cfg(feature = "seq")]
+        self.flush_wait.notify_all_waiting();
+
+        match test_dbg!(self.notify_policy()) {
+            NotifyPolicy::PerSlot => {
+                let mut notified = 0;
+                // Once there are no senders left to wake, the remaining
+                // notifications would be discarded anyway.
+                while notified < freed && self.tx_wait.notify() {
+                    notified += 1;
+                }
+                notified
+            }
+            NotifyPolicy::WakeAll { threshold } if freed >= threshold => {
+                self.tx_wait.notify_n(usize::MAX)
+            }
+            _ => self.tx_wait.notify_n(freed),
+        }
+    }
+
+    /// Returns whether a newly sent message should wake the receiver, which
+    /// it should not while fewer messages than the wake watermark are queued.
+    #[inline]
+    fn should_notify_rx(&self) -> bool {
+        let watermark = self.wake_watermark.load(Relaxed);
+        if watermark > 1 && test_dbg!(self.core.len()) < watermark {
+            test_println!("below wake watermark ({}); not notifying rx", watermark);
+            return false;
+        }
+        true
+    }
+
+    /// Returns a pseudo-random number, for load shedding.
+    fn random(&self) -> usize {
+        // A Weyl sequence, mixed with the `splitmix64` finalizer.
+        let mut x = self
+            .shed_rng
+            .fetch_add(0x9E37_79B9_7F4A_7C15_u64 as usize, Relaxed) as u64;
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (x ^ (x >> 31)) as usize
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<N> ChannelCore<N> {
+    /// Converts a channel's storage back into a [`ThingBuf`], reopening the
+    /// queue so that it may be pushed to again.
+    ///
+    /// [`ThingBuf`]: crate::ThingBuf
+    fn into_thingbuf<T, R>(
+        self,
+        slots: alloc::boxed::Box<[Slot<T>]>,
+        recycle: R,
+    ) -> crate::ThingBuf<T, R> {
+        let mut core = self.core;
+        core.reopen();
+        crate::ThingBuf::from_parts(core, slots, recycle)
     }
 }
 
@@ -341,8 +661,82 @@ where
             crate::loom::hint::spin_loop();
             test_println!("draining_queue");
             self.tx_wait.close();
+            #[cfg(feature = "seq")]
+            self.flush_wait.close();
+            self.notify_groups();
+        }
+    }
+
+    /// Closes the channel on behalf of a [`group::ChannelGroup`], waking both
+    /// the receiver and any waiting senders.
+    #[cfg(all(feature = "alloc", not(all(loom, test))))]
+    fn shutdown(&self) {
+        if self.core.close() {
+            test_println!("channel shut down by its group");
+            self.rx_wait.close_tx();
+            self.tx_wait.close();
+            #[cfg(feature = "seq")]
+            self.flush_wait.close();
+            self.notify_groups();
         }
     }
+
+    /// Returns `true` if every message with a sequence number below `target`
+    /// has been received.
+    #[cfg(feature = "seq")]
+    #[inline]
+    fn is_flushed(&self, target: usize) -> bool {
+        // Sequence numbers wrap around, so compare them by their distance.
+        self.core.consumed_seq().wrapping_sub(target) as isize >= 0
+    }
+
+    /// Shuts down the groups that this channel belongs to, once it has
+    /// closed.
+    #[inline]
+    fn notify_groups(&self) {
+        #[cfg(all(feature = "alloc", not(all(loom, test))))]
+        self.groups.closed();
+    }
+
+    /// Increments the sender count, unless it has already reached zero.
+    ///
+    /// Returns `false` if all senders have been dropped. Once that has
+    /// happened, the channel is closed for good, and no new senders may be
+    /// created.
+    fn add_tx(&self) -> bool {
+        let mut count = self.tx_count.load(Relaxed);
+        loop {
+            if test_dbg!(count) == 0 {
+                return false;
+            }
+
+            match self
+                .tx_count
+                .compare_exchange_weak(count, count + 1, Relaxed, Relaxed)
+            {
+                Ok(_) => return true,
+                Err(actual) => count = actual,
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "alloc", not(all(loom, test))))]
+impl<N> group::Close for ChannelCore<N>
+where
+    N: Notify + Unpin,
+{
+    fn close(&self) {
+        self.shutdown();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.core.is_closed()
+    }
+
+    fn links(&self) -> &group::Links {
+        &self.groups
+    }
 }
 
 impl<N> ChannelCore<N>
@@ -354,16 +748,163 @@ where
         slots: &'a [Slot<T>],
         recycle: &R,
     ) -> Result<SendRefInner<'a, T, N>, TrySendError>
+    where
+        R: Recycle<T>,
+    {
+        self.try_send_ref_watched(slots, recycle, RefWatch::send)
+    }
+
+    /// Like `try_send_ref`, but the returned guard is not tracked by the
+    /// watchdog, whose registry is protected by a lock.
+    #[cfg(feature = "rt-safe")]
+    fn try_send_ref_untracked<'a, T, R>(
+        &'a self,
+        slots: &'a [Slot<T>],
+        recycle: &R,
+    ) -> Result<SendRefInner<'a, T, N>, TrySendError>
+    where
+        R: Recycle<T>,
+    {
+        self.try_send_ref_watched(slots, recycle, RefWatch::untracked)
+    }
+
+    #[inline]
+    fn try_send_ref_watched<'a, T, R>(
+        &'a self,
+        slots: &'a [Slot<T>],
+        recycle: &R,
+        watch: fn() -> RefWatch,
+    ) -> Result<SendRefInner<'a, T, N>, TrySendError>
     where
         R: Recycle<T>,
     {
         self.core.push_ref(slots, recycle).map(|slot| SendRefInner {
-            _notify: NotifyRx(&self.rx_wait),
+            _notify: NotifyRx(self),
+            _audit: self.ref_audit.send(),
+            _watch: watch(),
             slot,
         })
     }
 
+    /// Claims `n` consecutive slots, which are published to the receiver
+    /// together when the returned transaction is dropped.
+    #[cfg(feature = "std")]
+    fn try_transaction<'a, T, R>(
+        &'a self,
+        slots: &'a [Slot<T>],
+        recycle: &R,
+        n: usize,
+    ) -> Result<TransactionInner<'a, T, N>, TrySendError>
+    where
+        R: Recycle<T>,
+    {
+        self.core
+            .push_n_ref(slots, recycle, n)
+            .map(|slots| TransactionInner {
+                slots,
+                _notify: NotifyRx(self),
+            })
+    }
+
+    #[inline]
+    fn spin_backoff(backoff: &mut Backoff, adaptive: bool) {
+        // adaptive spinning escalates from spinning to yielding the thread,
+        // but an async task must not yield its executor's thread.
+        if adaptive && N::IS_THREAD {
+            backoff.spin_yield();
+        } else {
+            backoff.spin();
+        }
+    }
+
+    /// Like `try_send_ref`, but if the channel is full, retries up to
+    /// `spin_budget` times (with backoff) before giving up.
+    ///
+    /// Senders call this before registering in the wait queue.
+    fn try_send_ref_spinning<'a, T, R>(
+        &'a self,
+        slots: &'a [Slot<T>],
+        recycle: &R,
+    ) -> Result<SendRefInner<'a, T, N>, TrySendError>
+    where
+        R: Recycle<T>,
+    {
+        let mut result = self.try_send_ref(slots, recycle);
+        if !matches!(result, Err(TrySendError::Full(()))) {
+            return result;
+        }
+
+        let (budget, adaptive) = self.current_spin_budget();
+        let mut backoff = Backoff::new();
+        let mut spins = 0;
+        while spins < budget && matches!(result, Err(TrySendError::Full(()))) {
+            Self::spin_backoff(&mut backoff, adaptive);
+            spins += 1;
+            result = self.try_send_ref(slots, recycle);
+        }
+
+        if adaptive {
+            self.adapt_spin_budget(budget, result.is_ok());
+        }
+        result
+    }
+
     fn try_send<T, R>(&self, slots: &[Slot<T>], val: T, recycle: &R) -> Result<(), TrySendError<T>>
+    where
+        R: Recycle<T>,
+    {
+        self.try_send_watched(slots, val, recycle, RefWatch::send)
+    }
+
+    /// Like `try_send`, but the slot is not tracked by the watchdog.
+    #[cfg(feature = "rt-safe")]
+    fn try_send_untracked<T, R>(
+        &self,
+        slots: &[Slot<T>],
+        val: T,
+        recycle: &R,
+    ) -> Result<(), TrySendError<T>>
+    where
+        R: Recycle<T>,
+    {
+        self.try_send_watched(slots, val, recycle, RefWatch::untracked)
+    }
+
+    #[inline]
+    fn try_send_watched<T, R>(
+        &self,
+        slots: &[Slot<T>],
+        val: T,
+        recycle: &R,
+        watch: fn() -> RefWatch,
+    ) -> Result<(), TrySendError<T>>
+    where
+        R: Recycle<T>,
+    {
+        if self.shed() {
+            return Err(TrySendError::Shed(val));
+        }
+        match self.try_send_ref_watched(slots, recycle, watch) {
+            Ok(mut slot) => {
+                slot.with_mut(|slot| *slot = val);
+                Ok(())
+            }
+            Err(e) => Err(e.with_value(val)),
+        }
+    }
+
+    /// Sends `val`, or, if the channel is full, merges it into the most
+    /// recently sent message that is not at the front of the queue, by calling
+    /// `f`.
+    ///
+    /// Unlike `try_send`, this never sheds the message.
+    fn try_send_or_modify<T, R>(
+        &self,
+        slots: &[Slot<T>],
+        val: T,
+        recycle: &R,
+        f: impl FnOnce(&mut T, T),
+    ) -> Result<(), TrySendError<T>>
     where
         R: Recycle<T>,
     {
@@ -372,6 +913,25 @@ where
                 slot.with_mut(|slot| *slot = val);
                 Ok(())
             }
+            Err(TrySendError::Full(())) => match self.core.pin_last(slots) {
+                Some(mut queued) => {
+                    test_println!("channel full; merging into a queued message");
+                    f(queued.as_mut(), val);
+                    // While the message was pinned, its slot looked like it was
+                    // still being written, so the receiver may have parked
+                    // waiting for it. Republish it before waking the receiver.
+                    drop(queued);
+                    self.rx_wait.notify();
+                    Ok(())
+                }
+                None => {
+                    // `pin_last` may have briefly pinned the message at the
+                    // head of the queue, while the receiver was waiting for
+                    // it.
+                    self.rx_wait.notify();
+                    Err(TrySendError::Full(val))
+                }
+            },
             Err(e) => Err(e.with_value(val)),
         }
     }
@@ -379,9 +939,30 @@ where
     fn try_recv_ref<'a, T>(
         &'a self,
         slots: &'a [Slot<T>],
+    ) -> Result<RecvRefInner<'a, T, N>, TryRecvError> {
+        self.try_recv_ref_watched(slots, RefWatch::recv)
+    }
+
+    /// Like `try_recv_ref`, but the returned guard is not tracked by the
+    /// watchdog, whose registry is protected by a lock.
+    #[cfg(feature = "rt-safe")]
+    fn try_recv_ref_untracked<'a, T>(
+        &'a self,
+        slots: &'a [Slot<T>],
+    ) -> Result<RecvRefInner<'a, T, N>, TryRecvError> {
+        self.try_recv_ref_watched(slots, RefWatch::untracked)
+    }
+
+    #[inline]
+    fn try_recv_ref_watched<'a, T>(
+        &'a self,
+        slots: &'a [Slot<T>],
+        watch: fn() -> RefWatch,
     ) -> Result<RecvRefInner<'a, T, N>, TryRecvError> {
         self.core.pop_ref(slots).map(|slot| RecvRefInner {
-            _notify: NotifyTx(&self.tx_wait),
+            _notify: NotifyTx(self),
+            _audit: self.ref_audit.recv(),
+            _watch: watch(),
             slot,
         })
     }
@@ -396,6 +977,80 @@ where
         }
     }
 
+    /// Like `try_recv`, but the slot is not tracked by the watchdog.
+    #[cfg(feature = "rt-safe")]
+    fn try_recv_untracked<T, R>(&self, slots: &[Slot<T>], recycle: &R) -> Result<T, TryRecvError>
+    where
+        R: Recycle<T>,
+    {
+        match self.try_recv_ref_untracked(slots) {
+            Ok(mut slot) => Ok(take(&mut *slot, recycle)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Copies as many ready messages as fit into `out`, waking waiting
+    /// senders for the slots that were freed.
+    #[cfg(feature = "std")]
+    fn try_recv_into<T: Copy>(
+        &self,
+        slots: &[Slot<T>],
+        out: &mut [T],
+    ) -> Result<usize, TryRecvError> {
+        let n = self.core.pop_into(slots, out)?;
+        self.notify_tx(n);
+        Ok(n)
+    }
+
+    /// Moves up to `max` ready messages onto the end of `chunk`, claiming
+    /// them with a single update to the head index, and waking waiting
+    /// senders for the slots that were freed.
+    #[cfg(feature = "alloc")]
+    fn try_recv_chunk<T, R>(
+        &self,
+        slots: &[Slot<T>],
+        recycle: &R,
+        chunk: &mut alloc::vec::Vec<T>,
+        max: usize,
+    ) -> Result<usize, TryRecvError>
+    where
+        R: Recycle<T>,
+    {
+        let n = self
+            .core
+            .pop_n_with(slots, max, |mut slot| chunk.push(take(&mut *slot, recycle)))?;
+        self.notify_tx(n);
+        Ok(n)
+    }
+
+    /// Receives the next message if it matches `f`, or returns `Ok(None)`
+    /// if it does not.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that no other message is received concurrently.
+    unsafe fn try_recv_if<T, R>(
+        &self,
+        slots: &[Slot<T>],
+        recycle: &R,
+        f: impl FnOnce(&T) -> bool,
+    ) -> Result<Option<T>, TryRecvError>
+    where
+        R: Recycle<T>,
+    {
+        if !f(self.core.peek(slots)?) {
+            return Ok(None);
+        }
+        self.try_recv(slots, recycle).map(Some)
+    }
+
+    /// Hints that the next `n` messages, up to a small fixed number of them,
+    /// will be received soon.
+    #[cfg(all(feature = "prefetch", feature = "alloc"))]
+    fn prefetch<T>(&self, slots: &[Slot<T>], n: usize) {
+        self.core.prefetch(slots, n);
+    }
+
     /// Performs one iteration of the `recv_ref` loop.
     ///
     /// The loop itself has to be written in the actual `send` method's
@@ -406,20 +1061,68 @@ where
         slots: &'a [Slot<T>],
         mk_waiter: impl Fn() -> N,
     ) -> Poll<Option<Ref<'a, T>>> {
+        test_println!("poll_recv_ref");
+        self.poll_recv_with(mk_waiter, || self.core.pop_ref(slots))
+    }
+
+    /// Performs one iteration of the `peek` loop.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that no message is received while the returned
+    /// reference exists.
+    unsafe fn poll_peek<'a, T>(
+        &'a self,
+        slots: &'a [Slot<T>],
+        mk_waiter: impl Fn() -> N,
+    ) -> Poll<Option<&'a T>> {
+        test_println!("poll_peek");
+        self.poll_recv_with(mk_waiter, || self.core.peek(slots))
+    }
+
+    /// Polls `try_recv` until it returns a message or the channel closes,
+    /// registering a waiter if the channel is empty.
+    fn poll_recv_with<U>(
+        &self,
+        mk_waiter: impl Fn() -> N,
+        mut try_recv: impl FnMut() -> Result<U, TryRecvError>,
+    ) -> Poll<Option<U>> {
         macro_rules! try_poll_recv {
             () => {
                 // If we got a value, return it!
-                match self.core.pop_ref(slots) {
-                    Ok(slot) => return Poll::Ready(Some(slot)),
+                match try_recv() {
+                    Ok(msg) => return Poll::Ready(Some(msg)),
                     Err(TryRecvError::Closed) => return Poll::Ready(None),
                     _ => {}
                 }
             };
         }
 
-        test_println!("poll_recv_ref");
+        // spin for a while before registering the waiter, if configured to.
+        let (budget, adaptive) = self.current_spin_budget();
+        if budget > 0 {
+            try_poll_recv!();
+            let mut backoff = Backoff::new();
+            for _ in 0..budget {
+                Self::spin_backoff(&mut backoff, adaptive);
+                match try_recv() {
+                    Ok(msg) => {
+                        if adaptive {
+                            self.adapt_spin_budget(budget, true);
+                        }
+                        return Poll::Ready(Some(msg));
+                    }
+                    Err(TryRecvError::Closed) => return Poll::Ready(None),
+                    _ => {}
+                }
+            }
+            if adaptive {
+                self.adapt_spin_budget(budget, false);
+            }
+        }
+
         loop {
-            test_println!("poll_recv_ref => loop");
+            test_println!("poll_recv_with => loop");
 
             // try to receive a reference, returning if we succeeded or the
             // channel is closed.
@@ -439,7 +1142,7 @@ where
                     // the channel is closed (all the receivers are dropped).
                     // however, there may be messages left in the queue. try
                     // popping from the queue until it's empty.
-                    return Poll::Ready(self.core.pop_ref(slots).ok());
+                    return Poll::Ready(try_recv().ok());
                 }
                 WaitResult::Notified => {
                     // we were notified while we were trying to register the
@@ -451,6 +1154,34 @@ where
     }
 }
 
+// === impl NotifyPolicy ===
+
+impl NotifyPolicy {
+    fn to_usize(self) -> usize {
+        match self {
+            Self::PerSlot => 0,
+            Self::Batched => 1,
+            Self::WakeAll { threshold } => threshold.max(1).saturating_add(1),
+        }
+    }
+
+    fn from_usize(policy: usize) -> Self {
+        match policy {
+            0 => Self::PerSlot,
+            1 => Self::Batched,
+            threshold => Self::WakeAll {
+                threshold: threshold - 1,
+            },
+        }
+    }
+}
+
+impl Default for NotifyPolicy {
+    fn default() -> Self {
+        Self::PerSlot
+    }
+}
+
 // === impl SendRefInner ===
 
 impl<T, N: Notify> core::ops::Deref for SendRefInner<'_, T, N> {
@@ -479,6 +1210,23 @@ impl<T, N: Notify> SendRefInner<'_, T, N> {
     pub(crate) fn with_mut<U>(&mut self, f: impl FnOnce(&mut T) -> U) -> U {
         self.slot.with_mut(f)
     }
+
+    /// Gives up the claimed slot without publishing its message, so that the
+    /// receiver skips it.
+    #[cfg(feature = "serbuf")]
+    pub(crate) fn abandon(self) {
+        let Self { slot, _notify, .. } = self;
+        _notify.0.core.abandon_ref(slot);
+        // `_notify` is dropped after the slot is released, waking the
+        // receiver in case it was waiting for this slot to be written.
+    }
+}
+
+impl<T: PartialEq, N: Notify> PartialEq<T> for SendRefInner<'_, T, N> {
+    #[inline]
+    fn eq(&self, other: &T) -> bool {
+        self.slot.eq(other)
+    }
 }
 
 impl<T: fmt::Debug, N: Notify> fmt::Debug for SendRefInner<'_, T, N> {
@@ -527,6 +1275,13 @@ impl<T, N: Notify + Unpin> core::ops::DerefMut for RecvRefInner<'_, T, N> {
     }
 }
 
+impl<T: PartialEq, N: Notify + Unpin> PartialEq<T> for RecvRefInner<'_, T, N> {
+    #[inline]
+    fn eq(&self, other: &T) -> bool {
+        self.slot.eq(other)
+    }
+}
+
 impl<T: fmt::Debug, N: Notify + Unpin> fmt::Debug for RecvRefInner<'_, T, N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.slot.fmt(f)
@@ -556,11 +1311,27 @@ impl<T: fmt::Write, N: Notify + Unpin> fmt::Write for RecvRefInner<'_, T, N> {
     }
 }
 
+// === impl TransactionInner ===
+
+#[cfg(feature = "std")]
+impl<T, N: Notify> Drop for TransactionInner<'_, T, N> {
+    fn drop(&mut self) {
+        // Release the slots from last to first. The receiver reads them in
+        // order, so by the time it can read the first slot, every other slot
+        // is readable too, and it never observes a partial transaction.
+        while let Some(slot) = self.slots.pop() {
+            drop(slot);
+        }
+    }
+}
+
 impl<N: Notify> Drop for NotifyRx<'_, N> {
     #[inline]
     fn drop(&mut self) {
-        test_println!("notifying rx ({})", core::any::type_name::<N>());
-        self.0.notify();
+        if self.0.should_notify_rx() {
+            test_println!("notifying rx ({})", core::any::type_name::<N>());
+            self.0.rx_wait.notify();
+        }
     }
 }
 
@@ -568,7 +1339,7 @@ impl<N: Notify + Unpin> Drop for NotifyTx<'_, N> {
     #[inline]
     fn drop(&mut self) {
         test_println!("notifying tx ({})", core::any::type_name::<N>());
-        self.0.notify();
+        self.0.notify_tx(1);
     }
 }
 
@@ -593,6 +1364,27 @@ macro_rules! impl_ref_inner {
             }
         }
 
+        impl<T> AsRef<T> for $name<'_, T> {
+            #[inline]
+            fn as_ref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> AsMut<T> for $name<'_, T> {
+            #[inline]
+            fn as_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T: PartialEq> PartialEq<T> for $name<'_, T> {
+            #[inline]
+            fn eq(&self, other: &T) -> bool {
+                self.0.eq(other)
+            }
+        }
+
         impl<T: fmt::Debug> fmt::Debug for $name<'_, T> {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                 self.0.fmt(f)
@@ -627,22 +1419,164 @@ macro_rules! impl_ref_inner {
 macro_rules! impl_send_ref {
     ($(#[$m:meta])* pub struct $name:ident<$notify:ty>;) => {
         impl_ref_inner!($(#[$m])*, SendRefInner, $name, $notify);
+
+        #[cfg(feature = "serbuf")]
+        impl<T> $name<'_, T> {
+            /// Gives up the reserved slot without sending its message.
+            pub(crate) fn abandon(self) {
+                self.0.abandon()
+            }
+        }
+
+        /// Writes to the `BytesMut` in the reserved slot.
+        #[cfg(feature = "bytes")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+        unsafe impl ::bytes::BufMut for $name<'_, ::bytes::BytesMut> {
+            #[inline]
+            fn remaining_mut(&self) -> usize {
+                self.0.remaining_mut()
+            }
+
+            #[inline]
+            unsafe fn advance_mut(&mut self, cnt: usize) {
+                self.0.advance_mut(cnt)
+            }
+
+            #[inline]
+            fn chunk_mut(&mut self) -> &mut ::bytes::buf::UninitSlice {
+                self.0.chunk_mut()
+            }
+
+            #[inline]
+            fn put_slice(&mut self, src: &[u8]) {
+                self.0.put_slice(src)
+            }
+        }
     };
 }
 
 macro_rules! impl_recv_ref {
     ($(#[$m:meta])* pub struct $name:ident<$notify:ty>;) => {
         impl_ref_inner!($(#[$m])*, RecvRefInner, $name, $notify);
+
+        /// Reads from the `BytesMut` in the received slot.
+        #[cfg(feature = "bytes")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+        impl ::bytes::Buf for $name<'_, ::bytes::BytesMut> {
+            #[inline]
+            fn remaining(&self) -> usize {
+                self.0.remaining()
+            }
+
+            #[inline]
+            fn chunk(&self) -> &[u8] {
+                self.0.chunk()
+            }
+
+            #[inline]
+            fn advance(&mut self, cnt: usize) {
+                self.0.advance(cnt)
+            }
+
+            #[inline]
+            fn copy_to_bytes(&mut self, len: usize) -> ::bytes::Bytes {
+                self.0.copy_to_bytes(len)
+            }
+        }
+
+        #[cfg(feature = "seq")]
+        impl<T> $name<'_, T> {
+            /// Returns the sequence number of this message.
+            ///
+            /// Messages are numbered in the order in which they were placed in
+            /// the channel, starting at 0. If every message sent on the channel
+            /// is received, each message's sequence number is one greater than
+            /// the previous message's.
+            ///
+            /// Sequence numbers eventually wrap around. On 64-bit platforms,
+            /// this will not happen in practice, but on 32-bit platforms, it
+            /// may happen after roughly a billion messages.
+            #[inline]
+            #[must_use]
+            pub fn seq(&self) -> usize {
+                self.0.slot.seq
+            }
+        }
+
+        #[cfg(feature = "timestamps")]
+        impl<T> $name<'_, T> {
+            /// Returns the time at which this message was sent.
+            ///
+            /// A message is stamped when its [`SendRef`] is dropped (or when
+            /// it is sent by value), so this is the time at which it became
+            /// available to the receiver. Subtracting it from the current time
+            /// gives the time the message spent waiting in the channel.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            /// use thingbuf::mpsc::blocking;
+            /// use std::time::Instant;
+            ///
+            /// let (tx, rx) = blocking::channel::<i32>(8);
+            /// let before = Instant::now();
+            /// tx.send(1).unwrap();
+            ///
+            /// let msg = rx.recv_ref().unwrap();
+            /// let enqueued_at = msg.enqueued_at();
+            /// assert!(enqueued_at >= before);
+            /// println!("queue delay: {:?}", enqueued_at.elapsed());
+            /// ```
+            ///
+            /// [`SendRef`]: crate::mpsc::SendRef
+            #[inline]
+            #[must_use]
+            pub fn enqueued_at(&self) -> std::time::Instant {
+                self.0
+                    .slot
+                    .enqueued_at()
+                    .expect("a received message must have been stamped when it was sent")
+            }
+        }
     };
 }
 
 mod async_impl;
 pub use self::async_impl::*;
 
+feature! {
+    #![feature = "alloc"]
+    mod mailbox;
+    pub use self::mailbox::{mailbox, mailbox_with_recycle, MailboxReceiver, MailboxSender};
+}
+
+feature! {
+    #![feature = "alloc"]
+    #[cfg(not(all(loom, test)))]
+    pub mod budget;
+}
+
 feature! {
     #![feature = "std"]
     pub mod blocking;
 }
 
+feature! {
+    #![feature = "alloc"]
+    #[cfg(not(all(loom, test)))]
+    pub mod group;
+}
+
+feature! {
+    #![feature = "serbuf"]
+    pub mod serbuf;
+}
+
+feature! {
+    #![feature = "rt-safe"]
+    #[cfg(not(all(loom, test)))]
+    pub mod rt;
+}
+
 #[cfg(all(loom, test))]
 mod tests;
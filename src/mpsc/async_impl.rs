@@ -16,6 +16,7 @@ feature! {
 
     use crate::{MAX_CAPACITY, loom::sync::Arc};
     use alloc::boxed::Box;
+    use core::{mem, ptr};
 
     /// Returns a new asynchronous multi-producer, single consumer (MPSC)
     /// channel with the provided capacity.
@@ -54,6 +55,56 @@ feature! {
         (tx, rx)
     }
 
+    /// Returns a new asynchronous multi-producer, single consumer (MPSC)
+    /// channel with the provided capacity and [recycling policy], whose slot
+    /// array is allocated by the `place` callback.
+    ///
+    /// `place` is called with the capacity, and must return a new array
+    /// created by [`Slot::make_boxed_array`]. This allows controlling *where*
+    /// the slot array is allocated, such as on the NUMA node that the sending
+    /// and receiving tasks run on.
+    ///
+    /// # Panics
+    ///
+    /// - If the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`.
+    /// - If `place` returns an array whose length is not `capacity`, or an
+    ///   array that has already been used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{mpsc, recycling::DefaultRecycle, Slot};
+    ///
+    /// let (tx, rx) = mpsc::with_placement::<usize, _>(8, DefaultRecycle::new(), |capacity| {
+    ///     // allocate the slots on the desired NUMA node here (e.g. from a
+    ///     // thread pinned to that node).
+    ///     Slot::make_boxed_array(capacity)
+    /// });
+    /// # drop((tx, rx));
+    /// ```
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    #[must_use]
+    pub fn with_placement<T, R: Recycle<T>>(
+        capacity: usize,
+        recycle: R,
+        place: impl FnOnce(usize) -> Box<[Slot<T>]>,
+    ) -> (Sender<T, R>, Receiver<T, R>) {
+        assert!(capacity > 0);
+        assert!(capacity <= MAX_CAPACITY);
+        let slots = Slot::place_boxed_array(capacity, place);
+        let inner = Arc::new(Inner {
+            core: ChannelCore::new(capacity),
+            slots,
+            recycle,
+        });
+        let tx = Sender {
+            inner: inner.clone(),
+        };
+        let rx = Receiver { inner };
+        (tx, rx)
+    }
+
 
     /// Asynchronously receives values from associated [`Sender`]s.
     ///
@@ -73,6 +124,50 @@ feature! {
         inner: Arc<Inner<T, R>>,
     }
 
+    /// A [`Sink`] that sends messages to a channel by filling its slots in
+    /// place.
+    ///
+    /// The items accepted by this sink are closures of type `F`, which are
+    /// called with a mutable reference to a slot in the channel once one is
+    /// available. This allows `Sink`-based pipelines to reuse the allocations
+    /// in the channel's slots, like [`Sender::send_ref`], rather than sending
+    /// owned values.
+    ///
+    /// Like other `Sink`s, this buffers a single item: [`poll_ready`] waits
+    /// until the previously started item has been written to the channel.
+    ///
+    /// Instances of this struct are created by the [`Sender::into_sink_with`]
+    /// method. This type requires the "futures-sink" feature flag.
+    ///
+    /// [`Sink`]: futures_sink::Sink
+    /// [`poll_ready`]: futures_sink::Sink::poll_ready
+    #[cfg(feature = "futures-sink")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "futures-sink")))]
+    #[pin_project::pin_project(PinnedDrop)]
+    pub struct SinkWith<T, F, R = recycling::DefaultRecycle> {
+        tx: Sender<T, R>,
+        pending: Option<F>,
+        state: State,
+        #[pin]
+        waiter: queue::Waiter<Waker>,
+    }
+
+    /// A [`Sender`] for messages of type `U`, which converts each message into
+    /// the channel's message type `T` in place, inside the slot it is sent in.
+    ///
+    /// This allows several producers with different input types to share a
+    /// single channel, and, because each message is converted directly into
+    /// its slot, the conversion can reuse the allocations in the channel's
+    /// slots, like [`Sender::send_ref`].
+    ///
+    /// Instances of this struct are created by the [`Sender::with_map`]
+    /// method.
+    pub struct SenderMap<T, U, F, R = recycling::DefaultRecycle> {
+        tx: Sender<T, R>,
+        map: F,
+        _input: core::marker::PhantomData<fn(U)>,
+    }
+
     struct Inner<T, R> {
         core: super::ChannelCore<Waker>,
         slots: Box<[Slot<T>]>,
@@ -81,6 +176,23 @@ feature! {
 
     // === impl Sender ===
 
+    #[cfg(not(all(loom, test)))]
+    impl<T, R> Receiver<T, R> {
+        /// Returns the channel's recycling policy, for the
+        /// [`budget`](super::budget) channel.
+        pub(super) fn recycle(&self) -> &R {
+            &self.inner.recycle
+        }
+    }
+
+    #[cfg(feature = "rt-safe")]
+    impl<T, R> Sender<T, R> {
+        /// Returns the channel's state, for the [`rt`](super::rt) handles.
+        pub(super) fn parts(&self) -> (&ChannelCore<Waker>, &[Slot<T>], &R) {
+            (&self.inner.core, &self.inner.slots, &self.inner.recycle)
+        }
+    }
+
     impl<T, R> Sender<T, R>
     where
         R: Recycle<T>,
@@ -163,6 +275,13 @@ feature! {
         /// If the [`Receiver`] end of the channel has been dropped, this
         /// returns a [`Closed`] error containing the sent value.
         ///
+        /// # Cancel safety
+        ///
+        /// This method is **not** cancel safe. If the returned future is
+        /// dropped before it completes, for example because it lost a race in
+        /// `select!`, the message is dropped along with it. Use [`send_from`]
+        /// to keep the message when the future is cancelled.
+        ///
         /// # Examples
         ///
         /// ```
@@ -188,6 +307,7 @@ feature! {
         /// }
         /// ```
         /// [`send_ref`]: Self::send_ref
+        /// [`send_from`]: Self::send_from
         pub async fn send(&self, val: T) -> Result<(), Closed<T>> {
             match self.send_ref().await {
                 Err(Closed(())) => Err(Closed(val)),
@@ -198,6 +318,293 @@ feature! {
             }
         }
 
+        /// Sends the message in `val`, waiting until there is a free slot to
+        /// write to.
+        ///
+        /// Unlike [`send`], this method borrows the message from an `Option`,
+        /// and only takes it out of the `Option` once a slot has been reserved
+        /// for it, immediately before the returned future completes.
+        ///
+        /// If `val` is `None`, this completes immediately without sending
+        /// anything.
+        ///
+        /// # Errors
+        ///
+        /// If the [`Receiver`] end of the channel has been dropped, this
+        /// returns a [`Closed`] error, and the message is left in `val`.
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is cancel safe. If the returned future is dropped
+        /// before it completes, no slot was reserved, and the message is still
+        /// in `val`. This makes it suitable for sending in a `select!` loop
+        /// without losing the message when another branch completes first.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        /// use std::time::Duration;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = mpsc::channel(1);
+        ///     tx.send(0).await.unwrap();
+        ///
+        ///     let mut msg = Some(1);
+        ///     tokio::select! {
+        ///         res = tx.send_from(&mut msg) => res.unwrap(),
+        ///         _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+        ///     }
+        ///     // The channel is full, so the send was cancelled, and the
+        ///     // message is still ours.
+        ///     assert_eq!(msg, Some(1));
+        ///
+        ///     assert_eq!(rx.recv().await, Some(0));
+        ///     tx.send_from(&mut msg).await.unwrap();
+        ///     assert_eq!(msg, None);
+        ///     assert_eq!(rx.recv().await, Some(1));
+        /// }
+        /// ```
+        ///
+        /// [`send`]: Self::send
+        pub async fn send_from(&self, val: &mut Option<T>) -> Result<(), Closed> {
+            if val.is_none() {
+                return Ok(());
+            }
+            let mut slot = self.send_ref().await?;
+            *slot = val.take().expect("message must still be present");
+            Ok(())
+        }
+
+        /// Sends every item produced by `iter` to the channel, waiting for
+        /// capacity whenever the channel is full.
+        ///
+        /// Returns the number of items sent. This is equivalent to calling
+        /// [`send`] in a loop, stopping once the channel closes.
+        ///
+        /// # Errors
+        ///
+        /// If the [`Receiver`] end of the channel is dropped before every
+        /// item has been sent, this returns a [`SendAllError`] containing the
+        /// number of items that were sent, the item that could not be sent,
+        /// and the rest of the iterator.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = mpsc::channel(4);
+        ///
+        ///     tokio::spawn(async move {
+        ///         // Waits for capacity whenever the channel is full.
+        ///         let sent = tx.send_all(0..10).await.unwrap();
+        ///         assert_eq!(sent, 10);
+        ///     });
+        ///
+        ///     for i in 0..10 {
+        ///         assert_eq!(rx.recv().await, Some(i));
+        ///     }
+        ///     assert_eq!(rx.recv().await, None);
+        /// }
+        /// ```
+        ///
+        /// [`send`]: Self::send
+        pub async fn send_all<I>(&self, iter: I) -> Result<usize, SendAllError<T, I::IntoIter>>
+        where
+            I: IntoIterator<Item = T>,
+        {
+            let mut iter = iter.into_iter();
+            let mut sent = 0;
+            for item in &mut iter {
+                if let Err(Closed(item)) = self.send(item).await {
+                    return Err(SendAllError { sent, item, rest: iter });
+                }
+                sent += 1;
+            }
+            Ok(sent)
+        }
+
+        /// Sends every message in `batch` to the channel, in order, waiting
+        /// for capacity whenever the channel is full.
+        ///
+        /// The batch may be larger than the channel's capacity: messages are
+        /// moved into slots as they are freed by the receiver, so a producer
+        /// with a burst of messages does not need its own retry loop around
+        /// [`try_send`].
+        ///
+        /// # Errors
+        ///
+        /// If the [`Receiver`] end of the channel is dropped before every
+        /// message has been sent, this returns a [`Closed`] error containing
+        /// the messages that were not sent, in order.
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is **not** cancel safe. If the returned future is
+        /// dropped before it completes, the messages that have not yet been
+        /// sent are lost.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = mpsc::channel(4);
+        ///
+        ///     tokio::spawn(async move {
+        ///         // The batch is larger than the channel's capacity.
+        ///         tx.send_batch((0..10).collect()).await.unwrap();
+        ///     });
+        ///
+        ///     for i in 0..10 {
+        ///         assert_eq!(rx.recv().await, Some(i));
+        ///     }
+        ///     assert_eq!(rx.recv().await, None);
+        /// }
+        /// ```
+        ///
+        /// [`try_send`]: Self::try_send
+        pub async fn send_batch(
+            &self,
+            batch: alloc::vec::Vec<T>,
+        ) -> Result<(), Closed<alloc::vec::Vec<T>>> {
+            let mut batch = alloc::collections::VecDeque::from(batch);
+            while let Some(item) = batch.pop_front() {
+                if let Err(Closed(item)) = self.send(item).await {
+                    batch.push_front(item);
+                    return Err(Closed(batch.into()));
+                }
+            }
+            Ok(())
+        }
+
+        /// Sends every item produced by `stream` to the channel, waiting for
+        /// capacity whenever the channel is full.
+        ///
+        /// This behaves like [`send_all`], but takes items from an
+        /// asynchronous [`Stream`] rather than an iterator. The stream must be
+        /// [`Unpin`]; a stream which is not `Unpin` may be pinned with
+        /// [`Box::pin`] first.
+        ///
+        /// This method requires the "futures-core" feature flag.
+        ///
+        /// # Errors
+        ///
+        /// If the [`Receiver`] end of the channel is dropped before the stream
+        /// has ended, this returns a [`SendAllError`] containing the number of
+        /// items that were sent, the item that could not be sent, and the
+        /// stream.
+        ///
+        /// [`send_all`]: Self::send_all
+        /// [`Stream`]: futures_core::Stream
+        /// [`Box::pin`]: https://doc.rust-lang.org/std/boxed/struct.Box.html#method.pin
+        #[cfg(feature = "futures-core")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "futures-core")))]
+        pub async fn send_all_stream<S>(&self, mut stream: S) -> Result<usize, SendAllError<T, S>>
+        where
+            S: futures_core::Stream<Item = T> + Unpin,
+        {
+            let mut sent = 0;
+            while let Some(item) = NextItem(&mut stream).await {
+                if let Err(Closed(item)) = self.send(item).await {
+                    return Err(SendAllError { sent, item, rest: stream });
+                }
+                sent += 1;
+            }
+            Ok(sent)
+        }
+
+        /// Converts this `Sender` into a [`SinkWith`], a [`Sink`] whose items
+        /// are closures that fill a slot in the channel in place.
+        ///
+        /// This method requires the "futures-sink" feature flag.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        /// use futures_util::SinkExt;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = mpsc::channel::<String>(8);
+        ///     let mut sink = Box::pin(tx.into_sink_with());
+        ///
+        ///     for i in 0..3 {
+        ///         sink.send(move |slot: &mut String| slot.push_str(&i.to_string()))
+        ///             .await
+        ///             .unwrap();
+        ///     }
+        ///     drop(sink);
+        ///
+        ///     assert_eq!(rx.recv().await.as_deref(), Some("0"));
+        ///     assert_eq!(rx.recv().await.as_deref(), Some("1"));
+        ///     assert_eq!(rx.recv().await.as_deref(), Some("2"));
+        ///     assert_eq!(rx.recv().await, None);
+        /// }
+        /// ```
+        ///
+        /// [`Sink`]: futures_sink::Sink
+        #[cfg(feature = "futures-sink")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "futures-sink")))]
+        pub fn into_sink_with<F>(self) -> SinkWith<T, F, R>
+        where
+            F: FnOnce(&mut T),
+        {
+            SinkWith {
+                tx: self,
+                pending: None,
+                state: State::Start,
+                waiter: queue::Waiter::new(),
+            }
+        }
+
+        /// Converts this `Sender` into a [`SenderMap`], which sends messages of
+        /// type `U` by calling `map` to write each one into a slot in the
+        /// channel.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = mpsc::channel::<String>(8);
+        ///
+        ///     // Producers of numbers and of byte strings share one channel.
+        ///     let numbers = tx.clone().with_map(|n: u32, slot: &mut String| {
+        ///         use std::fmt::Write;
+        ///         write!(slot, "{}", n).unwrap();
+        ///     });
+        ///     let bytes = tx.with_map(|b: &[u8], slot: &mut String| {
+        ///         slot.push_str(&String::from_utf8_lossy(b));
+        ///     });
+        ///
+        ///     numbers.send(42).await.unwrap();
+        ///     bytes.send(b"hello").await.unwrap();
+        ///
+        ///     assert_eq!(rx.recv().await.as_deref(), Some("42"));
+        ///     assert_eq!(rx.recv().await.as_deref(), Some("hello"));
+        /// }
+        /// ```
+        pub fn with_map<U, F>(self, map: F) -> SenderMap<T, U, F, R>
+        where
+            F: Fn(U, &mut T),
+        {
+            SenderMap {
+                tx: self,
+                map,
+                _input: core::marker::PhantomData,
+            }
+        }
+
         /// Attempts to reserve a slot in the channel to mutate in place,
         /// without waiting for capacity.
         ///
@@ -255,7 +662,10 @@ feature! {
         /// Once the channel has closed, subsequent calls to `try_send` will
         /// never succeed.
         ///
-        /// In both cases, the error includes the value passed to `try_send`.
+        /// If the message is dropped by the channel's load shedding (see
+        /// [`Receiver::set_load_shedding`]), [`TrySendError::Shed`] is returned.
+        ///
+        /// In each case, the error includes the value passed to `try_send`.
         ///
         /// [`send`]: Self::send
         pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
@@ -264,6 +674,134 @@ feature! {
                 .try_send(self.inner.slots.as_ref(), val, &self.inner.recycle)
         }
 
+        /// Sends a message by value if the channel has capacity, or, if it is
+        /// full, merges the message into the most recently queued message by
+        /// calling `f`.
+        ///
+        /// `f` is called with a mutable reference to the queued message, and with
+        /// the new message. This gives producers of cumulative data, such as
+        /// counters or deltas, bounded memory use without losing information:
+        /// rather than waiting for the receiver, or dropping an update, the update
+        /// is folded into a message that the receiver has not yet received.
+        ///
+        /// The message that `f` modifies is the most recently sent message that is
+        /// not at the front of the queue, where the receiver may already be
+        /// receiving it. The receiver cannot receive that message until `f`
+        /// returns. This method never waits for capacity, and messages
+        /// sent with it are never dropped by [load shedding].
+        ///
+        /// # Errors
+        ///
+        /// If the channel is closed, this returns [`TrySendError::Closed`]. If the
+        /// channel is full, and every queued message is still being sent or is at
+        /// the front of the queue, so there is no message to merge into, this returns
+        /// [`TrySendError::Full`]. In both cases, the error includes the value
+        /// passed to `send_or_modify`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// let (tx, rx) = mpsc::channel::<u64>(2);
+        ///
+        /// for delta in 1..=10 {
+        ///     tx.send_or_modify(delta, |queued, delta| *queued += delta)
+        ///         .unwrap();
+        /// }
+        ///
+        /// // Once the channel filled up, the remaining deltas were added to the
+        /// // last queued message.
+        /// assert_eq!(rx.try_recv().unwrap(), 1);
+        /// assert_eq!(rx.try_recv().unwrap(), 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9 + 10);
+        /// ```
+        ///
+        /// [load shedding]: Receiver::set_load_shedding
+        pub fn send_or_modify<F>(&self, val: T, f: F) -> Result<(), TrySendError<T>>
+        where
+            F: FnOnce(&mut T, T),
+        {
+            self.inner
+                        .core
+                        .try_send_or_modify(self.inner.slots.as_ref(), val, &self.inner.recycle, f)
+        }
+
+        /// Attempts to claim a slot in the channel immediately, without waiting
+        /// for capacity, and fills it in place by calling `f`.
+        ///
+        /// This is equivalent to calling [`try_send_ref`] and writing to the
+        /// returned [`SendRef`], and likewise allows the existing allocations
+        /// in the channel's slots to be reused. The message is sent once `f`
+        /// returns.
+        ///
+        /// # Errors
+        ///
+        /// If the channel is full, [`TrySendError::Full`] is returned, and if the
+        /// channel has closed, [`TrySendError::Closed`] is returned. In either
+        /// case, `f` is not called.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        /// use std::fmt::Write;
+        ///
+        /// let (tx, rx) = mpsc::channel::<String>(4);
+        ///
+        /// tx.try_send_with(|msg: &mut String| write!(msg, "hello").unwrap())
+        ///     .unwrap();
+        /// assert_eq!(rx.try_recv().unwrap(), "hello");
+        /// ```
+        ///
+        /// [`try_send_ref`]: Self::try_send_ref
+        pub fn try_send_with<F>(&self, f: F) -> Result<(), TrySendError>
+        where
+            F: FnOnce(&mut T),
+        {
+            self.try_send_ref().map(|mut slot| f(&mut slot))
+        }
+
+        /// Attempts to send a message by value immediately, calling `f` with
+        /// the message instead if the channel is full, or the message is dropped
+        /// by the channel's load shedding.
+        ///
+        /// This makes it easy to handle overflow, for example by spilling
+        /// messages to disk, or by logging that they were dropped, rather than
+        /// waiting for capacity.
+        ///
+        /// # Errors
+        ///
+        /// If the channel has closed, the message is returned in a [`Closed`]
+        /// error, and `f` is not called.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// let (tx, rx) = mpsc::channel(1);
+        ///
+        /// let mut dropped = Vec::new();
+        /// tx.try_send_or_else(1, |msg| dropped.push(msg)).unwrap();
+        /// tx.try_send_or_else(2, |msg| dropped.push(msg)).unwrap();
+        ///
+        /// assert_eq!(dropped, vec![2]);
+        /// # drop(rx);
+        /// ```
+        pub fn try_send_or_else<F>(&self, val: T, f: F) -> Result<(), Closed<T>>
+        where
+            F: FnOnce(T),
+        {
+            match self.try_send(val) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(val) | TrySendError::Shed(val)) => {
+                    f(val);
+                    Ok(())
+                }
+                Err(TrySendError::Closed(val)) => Err(Closed(val)),
+            }
+        }
+
         /// Returns the *total* capacity of the channel for this [`Sender`].
         /// This includes both occupied and unoccupied entries.
         ///
@@ -299,6 +837,27 @@ feature! {
             self.inner.core.core.capacity()
         }
 
+        /// Returns a snapshot of the statistics recorded for this channel.
+        ///
+        /// These are the same statistics returned by the receiver's `stats`
+        /// method. See the [`stats`](crate::stats) module for details.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::channel;
+        ///
+        /// let (tx, rx) = channel::<usize>(100);
+        /// tx.try_send(1).unwrap();
+        ///
+        /// assert_eq!(tx.stats().occupancy().samples(), 1);
+        /// ```
+        #[cfg(feature = "stats")]
+        #[must_use]
+        pub fn stats(&self) -> crate::stats::Stats {
+            self.inner.core.core.stats()
+        }
+
         /// Returns the unoccupied capacity of the channel for this [`Sender`]
         /// (i.e., how many additional elements can be sent before the channel
         /// will be full).
@@ -376,9 +935,133 @@ feature! {
         pub fn is_empty(&self) -> bool {
             self.len() == 0
         }
-    }
 
-    impl<T, R> Clone for Sender<T, R> {
+        /// Returns the number of [`Sender`]s (including this one) that are
+        /// currently attached to the channel.
+        ///
+        /// This may be used by supervisors to report how many producers are still
+        /// attached to a channel. Note that the count may change as soon as it has
+        /// been read, if other senders are cloned or dropped concurrently.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// let (tx, _rx) = mpsc::channel::<usize>(8);
+        /// assert_eq!(tx.sender_count(), 1);
+        ///
+        /// let tx2 = tx.clone();
+        /// assert_eq!(tx.sender_count(), 2);
+        ///
+        /// drop(tx2);
+        /// assert_eq!(tx.sender_count(), 1);
+        /// ```
+        #[inline]
+        #[must_use]
+        pub fn sender_count(&self) -> usize {
+            test_dbg!(self.inner.core.tx_count.load(Ordering::SeqCst))
+        }
+
+        /// Returns the sequence number that will be assigned to the next
+        /// message sent on this channel.
+        ///
+        /// This is the number of messages that have been sent so far. Each
+        /// message is assigned the next sequence number in the order in which
+        /// it is placed in the channel, which can be retrieved from the
+        /// [`Receiver`] with [`RecvRef::seq`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// let (tx, _rx) = mpsc::channel::<usize>(8);
+        /// assert_eq!(tx.published_seq(), 0);
+        ///
+        /// tx.try_send(1).unwrap();
+        /// tx.try_send(1).unwrap();
+        /// assert_eq!(tx.published_seq(), 2);
+        /// ```
+        #[cfg(feature = "seq")]
+        #[inline]
+        #[must_use]
+        pub fn published_seq(&self) -> usize {
+            self.inner.core.core.published_seq()
+        }
+
+        /// Waits until every message sent on this channel before `flush` was
+        /// called has been received.
+        ///
+        /// This establishes a barrier: once it returns, the [`Receiver`] has
+        /// taken every earlier message out of the channel, including those
+        /// sent by other [`Sender`]s, without the channel having to be closed.
+        /// A message counts as received once it has been taken out of the
+        /// channel, so `flush` does not wait for the receiver to finish
+        /// processing it.
+        ///
+        /// This must not be called while this task holds a [`SendRef`] for
+        /// the same channel, as the message in it cannot be received until
+        /// the `SendRef` is dropped.
+        ///
+        /// # Errors
+        ///
+        /// If the [`Receiver`] end of the channel is dropped before every
+        /// earlier message has been received, this returns a [`Closed`] error.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = mpsc::channel::<usize>(8);
+        ///
+        ///     let producer = tokio::spawn(async move {
+        ///         tx.send(1).await.unwrap();
+        ///         tx.send(2).await.unwrap();
+        ///         // Wait until both messages have been received.
+        ///         tx.flush().await.unwrap();
+        ///         tx
+        ///     });
+        ///
+        ///     assert_eq!(rx.recv().await, Some(1));
+        ///     assert_eq!(rx.recv().await, Some(2));
+        ///
+        ///     let tx = producer.await.unwrap();
+        ///     assert_eq!(tx.published_seq(), rx.consumed_seq());
+        /// }
+        /// ```
+        #[cfg(feature = "seq")]
+        pub async fn flush(&self) -> Result<(), Closed> {
+            FlushFuture::new(&self.inner.core).await
+        }
+
+        /// Returns `true` if the [`Receiver`] for this channel has not been
+        /// dropped.
+        ///
+        /// If this returns `false`, all future sends on this channel will fail.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// let (tx, rx) = mpsc::channel::<usize>(8);
+        /// assert!(tx.receiver_alive());
+        ///
+        /// drop(rx);
+        /// assert!(!tx.receiver_alive());
+        /// ```
+        #[inline]
+        #[must_use]
+        pub fn receiver_alive(&self) -> bool {
+            !self.inner.core.core.is_closed()
+        }
+    }
+
+    impl<T, R> Clone for Sender<T, R> {
         fn clone(&self) -> Self {
             test_dbg!(self.inner.core.tx_count.fetch_add(1, Ordering::Relaxed));
             Self {
@@ -395,13 +1078,198 @@ feature! {
 
             // if we are the last sender, synchronize
             test_dbg!(atomic::fence(Ordering::SeqCst));
-            self.inner.core.core.close();
+            if self.inner.core.core.close() {
+                self.inner.core.notify_groups();
+            }
             self.inner.core.rx_wait.close_tx();
         }
     }
 
+    // === impl SinkWith ===
+
+    #[cfg(feature = "futures-sink")]
+    impl<T, F, R> SinkWith<T, F, R> {
+        /// Returns a reference to the [`Sender`] this sink sends to.
+        pub fn get_ref(&self) -> &Sender<T, R> {
+            &self.tx
+        }
+
+        /// Writes the pending item (if there is one) to the channel, waiting
+        /// for a slot if the channel is full.
+        fn poll_send_pending(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Closed>>
+        where
+            F: FnOnce(&mut T),
+            R: Recycle<T>,
+        {
+            let this = self.project();
+            if this.pending.is_none() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let inner = &*this.tx.inner;
+            let res = match poll_send_ref(
+                &inner.core,
+                inner.slots.as_ref(),
+                &inner.recycle,
+                this.state,
+                this.waiter,
+                cx,
+            ) {
+                Poll::Ready(res) => res,
+                Poll::Pending => return Poll::Pending,
+            };
+            *this.state = State::Start;
+
+            let fill = this.pending.take().expect("pending item must exist");
+            let mut slot = res?;
+            fill(&mut slot);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "futures-sink")]
+    impl<T, F, R> futures_sink::Sink<F> for SinkWith<T, F, R>
+    where
+        F: FnOnce(&mut T),
+        R: Recycle<T>,
+    {
+        type Error = Closed;
+
+        fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+            self.poll_send_pending(cx)
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: F) -> Result<(), Closed> {
+            let this = self.project();
+            debug_assert!(
+                this.pending.is_none(),
+                "SinkWith::start_send called without waiting for poll_ready"
+            );
+            *this.pending = Some(item);
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+            self.poll_send_pending(cx)
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+            self.poll_send_pending(cx)
+        }
+    }
+
+    #[cfg(feature = "futures-sink")]
+    #[pin_project::pinned_drop]
+    impl<T, F, R> PinnedDrop for SinkWith<T, F, R> {
+        fn drop(self: Pin<&mut Self>) {
+            let this = self.project();
+            if test_dbg!(*this.state) == State::Waiting && test_dbg!(this.waiter.is_linked()) {
+                this.waiter.remove(&this.tx.inner.core.tx_wait)
+            }
+        }
+    }
+
+    #[cfg(feature = "futures-sink")]
+    impl<T: fmt::Debug, F, R: fmt::Debug> fmt::Debug for SinkWith<T, F, R> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SinkWith")
+                .field("tx", &self.tx)
+                .field("pending", &self.pending.is_some())
+                .field("state", &self.state)
+                .finish()
+        }
+    }
+
+    // === impl SenderMap ===
+
+    impl<T, U, F, R> SenderMap<T, U, F, R>
+    where
+        F: Fn(U, &mut T),
+        R: Recycle<T>,
+    {
+        /// Sends a message, converting it into a slot in the channel, waiting
+        /// until there is capacity.
+        ///
+        /// # Errors
+        ///
+        /// If the [`Receiver`] end of the channel has been dropped, this
+        /// returns a [`Closed`] error containing the message.
+        pub async fn send(&self, val: U) -> Result<(), Closed<U>> {
+            match self.tx.send_ref().await {
+                Err(Closed(())) => Err(Closed(val)),
+                Ok(mut slot) => {
+                    (self.map)(val, &mut slot);
+                    Ok(())
+                }
+            }
+        }
+
+        /// Attempts to send a message, converting it into a slot in the
+        /// channel, without waiting for capacity.
+        ///
+        /// # Errors
+        ///
+        /// - [`Err`]`(`[`TrySendError::Full`]`)` if the channel is at capacity.
+        /// - [`Err`]`(`[`TrySendError::Closed`]`)` if the [`Receiver`] end of
+        ///   the channel has been dropped.
+        /// - [`Err`]`(`[`TrySendError::Shed`]`)` if the message was dropped by
+        ///   the channel's load shedding.
+        ///
+        /// In each case, the error includes the value passed to `try_send`.
+        pub fn try_send(&self, val: U) -> Result<(), TrySendError<U>> {
+            if self.tx.inner.core.shed() {
+                return Err(TrySendError::Shed(val));
+            }
+            match self.tx.try_send_ref() {
+                Err(e) => Err(e.with_value(val)),
+                Ok(mut slot) => {
+                    (self.map)(val, &mut slot);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    impl<T, U, F, R> SenderMap<T, U, F, R> {
+        /// Returns a reference to the [`Sender`] this handle sends to.
+        pub fn get_ref(&self) -> &Sender<T, R> {
+            &self.tx
+        }
+
+        /// Consumes this `SenderMap`, returning the underlying [`Sender`].
+        pub fn into_inner(self) -> Sender<T, R> {
+            self.tx
+        }
+    }
+
+    impl<T, U, F: Clone, R> Clone for SenderMap<T, U, F, R> {
+        fn clone(&self) -> Self {
+            Self {
+                tx: self.tx.clone(),
+                map: self.map.clone(),
+                _input: core::marker::PhantomData,
+            }
+        }
+    }
+
+    impl<T: fmt::Debug, U, F, R: fmt::Debug> fmt::Debug for SenderMap<T, U, F, R> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SenderMap")
+                .field("tx", &self.tx)
+                .finish_non_exhaustive()
+        }
+    }
+
     // === impl Receiver ===
 
+    #[cfg(feature = "rt-safe")]
+    impl<T, R> Receiver<T, R> {
+        /// Returns the channel's state, for the [`rt`](super::rt) handles.
+        pub(super) fn parts(&self) -> (&ChannelCore<Waker>, &[Slot<T>], &R) {
+            (&self.inner.core, &self.inner.slots, &self.inner.recycle)
+        }
+    }
+
     impl<T, R> Receiver<T, R> {
         /// Receives the next message for this receiver, **by reference**.
         ///
@@ -540,6 +1408,218 @@ feature! {
             }
         }
 
+        /// Receives messages onto the end of `batch` until it contains exactly
+        /// `n` messages, waiting until all of them have been sent.
+        ///
+        /// The returned future completes once `batch` holds `n` messages, or
+        /// once the channel has closed and every remaining message has been
+        /// received. In the latter case, `batch` is left with fewer than `n`
+        /// messages (and may be empty). Returns the number of messages received
+        /// by this call.
+        ///
+        /// This is useful for consumers that process messages in fixed-size
+        /// batches, such as block writers.
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is cancel safe. Every message is pushed onto `batch` as
+        /// soon as it is received, so if the returned future is dropped before
+        /// it completes, no messages are lost, and calling `recv_exact` again
+        /// with the same `batch` continues filling it.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = mpsc::channel(8);
+        ///
+        ///     tokio::spawn(async move {
+        ///         for i in 0..5 {
+        ///             tx.send(i).await.unwrap();
+        ///         }
+        ///     });
+        ///
+        ///     let mut batch = Vec::new();
+        ///     assert_eq!(rx.recv_exact(&mut batch, 2).await, 2);
+        ///     assert_eq!(batch, vec![0, 1]);
+        ///
+        ///     batch.clear();
+        ///     assert_eq!(rx.recv_exact(&mut batch, 2).await, 2);
+        ///     assert_eq!(batch, vec![2, 3]);
+        ///
+        ///     // The channel closes, so only a partial batch is received.
+        ///     batch.clear();
+        ///     assert_eq!(rx.recv_exact(&mut batch, 2).await, 1);
+        ///     assert_eq!(batch, vec![4]);
+        ///     assert_eq!(rx.recv_exact(&mut batch, 2).await, 0);
+        /// }
+        /// ```
+        pub async fn recv_exact(&self, batch: &mut alloc::vec::Vec<T>, n: usize) -> usize
+        where
+            R: Recycle<T>,
+        {
+            let start = batch.len();
+            batch.reserve(n.saturating_sub(start));
+            while batch.len() < n {
+                #[cfg(feature = "prefetch")]
+                self.inner.core.prefetch(self.inner.slots.as_ref(), n - batch.len());
+                match self.recv().await {
+                    Some(msg) => batch.push(msg),
+                    None => break,
+                }
+            }
+            batch.len() - start
+        }
+
+        /// Receives every message sent on the channel, **by reference**, passing
+        /// each one to `f`, until the channel closes.
+        ///
+        /// The returned future waits for each message in turn, and completes once
+        /// all [`Sender`]s have been dropped and every message has been
+        /// received. This replaces the usual `while let Some(msg) =
+        /// rx.recv_ref().await { ... }` consumer loop. Each message's slot is
+        /// released as soon as `f` returns, or drops the [`RecvRef`].
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is cancel safe. If the returned future is dropped, every
+        /// message that was passed to `f` has been received, and no other messages
+        /// are lost.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = mpsc::channel(8);
+        ///
+        ///     tokio::spawn(async move {
+        ///         for i in 1..=4 {
+        ///             tx.send(i).await.unwrap();
+        ///         }
+        ///     });
+        ///
+        ///     let mut sum = 0;
+        ///     // Completes once the sender has been dropped.
+        ///     rx.recv_for_each(|msg| sum += *msg).await;
+        ///     assert_eq!(sum, 10);
+        /// }
+        /// ```
+        pub async fn recv_for_each<F>(&self, mut f: F)
+        where
+            F: FnMut(RecvRef<'_, T>),
+        {
+            while let Some(msg) = self.recv_ref().await {
+                f(msg);
+            }
+        }
+
+        /// Waits for at least one message, and then receives every message
+        /// that is ready, up to `n` messages, as a single chunk.
+        ///
+        /// Unlike [`recv_exact`], this does not wait for the chunk to fill
+        /// up: once a message has been received, the messages already in the
+        /// channel are received without waiting. This lets consumers that
+        /// process messages in batches anyway handle a burst of messages with
+        /// a single task wakeup. Returns `None` if the channel has closed and
+        /// all messages have been received.
+        ///
+        /// To receive chunks from a `poll`-based API, such as a `Stream`
+        /// implementation, use [`poll_recv_chunk`].
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is cancel safe. Messages are only received once the
+        /// first message is ready, after which the chunk is completed without
+        /// waiting.
+        ///
+        /// # Panics
+        ///
+        /// If `n` is 0.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = mpsc::channel(8);
+        ///
+        ///     for i in 0..5 {
+        ///         tx.send(i).await.unwrap();
+        ///     }
+        ///     drop(tx);
+        ///
+        ///     assert_eq!(rx.recv_chunk(3).await, Some(vec![0, 1, 2]));
+        ///     // Only two messages are ready, so a partial chunk is returned.
+        ///     assert_eq!(rx.recv_chunk(3).await, Some(vec![3, 4]));
+        ///     assert_eq!(rx.recv_chunk(3).await, None);
+        /// }
+        /// ```
+        ///
+        /// [`recv_exact`]: Self::recv_exact
+        /// [`poll_recv_chunk`]: Self::poll_recv_chunk
+        pub async fn recv_chunk(&self, n: usize) -> Option<alloc::vec::Vec<T>>
+        where
+            R: Recycle<T>,
+        {
+            assert!(n > 0, "a chunk must contain at least one message");
+            let first = self.recv().await?;
+            Some(self.fill_chunk(first, n))
+        }
+
+        /// Attempts to receive a chunk of up to `n` ready messages,
+        /// registering the current task for wakeup if no message is
+        /// available yet, and returning `None` if the channel has closed and
+        /// all messages have been received.
+        ///
+        /// This is the `poll`-based version of [`recv_chunk`]. Like
+        /// [`poll_recv`], only the [`Waker`] from the [`Context`] passed to the
+        /// most recent call is scheduled to receive a wakeup.
+        ///
+        /// # Panics
+        ///
+        /// If `n` is 0.
+        ///
+        /// [`recv_chunk`]: Self::recv_chunk
+        /// [`poll_recv`]: Self::poll_recv
+        pub fn poll_recv_chunk(
+            &self,
+            cx: &mut Context<'_>,
+            n: usize,
+        ) -> Poll<Option<alloc::vec::Vec<T>>>
+        where
+            R: Recycle<T>,
+        {
+            assert!(n > 0, "a chunk must contain at least one message");
+            self.poll_recv(cx).map(|first| Some(self.fill_chunk(first?, n)))
+        }
+
+        fn fill_chunk(&self, first: T, n: usize) -> alloc::vec::Vec<T>
+        where
+            R: Recycle<T>,
+        {
+            let mut chunk = alloc::vec::Vec::with_capacity(core::cmp::min(n, self.capacity()));
+            chunk.push(first);
+            if n > 1 {
+                // The rest of the chunk is claimed in one batch, so that it is
+                // prefetched when the "prefetch" feature is enabled.
+                let _ = self.inner.core.try_recv_chunk(
+                    self.inner.slots.as_ref(),
+                    &self.inner.recycle,
+                    &mut chunk,
+                    n - 1,
+                );
+            }
+            chunk
+        }
+
         /// Attempts to receive the next message for this receiver by reference
         /// without waiting for a new message when the channel is empty.
         ///
@@ -608,20 +1688,62 @@ feature! {
             self.inner.core.try_recv(self.inner.slots.as_ref(), &self.inner.recycle)
         }
 
-        /// Attempts to receive a message *by reference* from this channel,
-        /// registering the current task for wakeup if the a message is not yet
-        /// available, and returning `None` if the channel has closed and all
-        /// messages have been received.
+        /// Attempts to receive the next message, but only if it matches the
+        /// predicate `f`, without waiting for a new message when the channel
+        /// is empty.
         ///
-        /// Like [`Receiver::recv_ref`], this method returns a [`RecvRef`] that
-        /// can be used to read from (or mutate) the received message by
-        /// reference. When the [`RecvRef`] is dropped, the receive operation
-        /// completes and the slot occupied by the received message becomes
-        /// usable for a future [`send_ref`] operation.
+        /// If the next message does not match, it is left in the channel, and
+        /// `Ok(None)` is returned. This is useful for consumers that must leave
+        /// certain messages to be handled by another code path.
         ///
-        /// If all [`Sender`]s for this channel write to the channel's slots in
-        /// place by using the [`send_ref`] or [`try_send_ref`] methods, this
-        /// method allows messages that own heap allocations to be reused in
+        /// Because this method takes `&mut self`, no other message can be
+        /// received while the predicate is evaluated.
+        ///
+        /// # Errors
+        ///
+        /// This method returns an error when the channel is closed, or when there
+        /// are no remaining messages in the channel's buffer.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::{channel, errors::TryRecvError};
+        ///
+        /// let (tx, mut rx) = channel(100);
+        /// tx.try_send(1).unwrap();
+        ///
+        /// assert_eq!(rx.try_recv_if(|&msg| msg > 1), Ok(None));
+        /// assert_eq!(rx.try_recv_if(|&msg| msg == 1), Ok(Some(1)));
+        /// assert_eq!(rx.try_recv_if(|_| true), Err(TryRecvError::Empty));
+        /// ```
+        pub fn try_recv_if<F>(&mut self, f: F) -> Result<Option<T>, TryRecvError>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            // Safety: this method takes `&mut self`, so no messages can be
+            // received concurrently.
+            unsafe {
+                self.inner
+                    .core
+                    .try_recv_if(self.inner.slots.as_ref(), &self.inner.recycle, f)
+            }
+        }
+
+        /// Attempts to receive a message *by reference* from this channel,
+        /// registering the current task for wakeup if the a message is not yet
+        /// available, and returning `None` if the channel has closed and all
+        /// messages have been received.
+        ///
+        /// Like [`Receiver::recv_ref`], this method returns a [`RecvRef`] that
+        /// can be used to read from (or mutate) the received message by
+        /// reference. When the [`RecvRef`] is dropped, the receive operation
+        /// completes and the slot occupied by the received message becomes
+        /// usable for a future [`send_ref`] operation.
+        ///
+        /// If all [`Sender`]s for this channel write to the channel's slots in
+        /// place by using the [`send_ref`] or [`try_send_ref`] methods, this
+        /// method allows messages that own heap allocations to be reused in
         /// place.
         ///
         /// To wait asynchronously until a message becomes available, use the
@@ -692,13 +1814,385 @@ feature! {
                 .map(|opt| opt.map(|mut r| recycling::take(&mut *r, &self.inner.recycle)))
         }
 
+        /// Waits for the next message, and returns a reference to it *without*
+        /// receiving it.
+        ///
+        /// The message stays at the front of the channel, so it is returned
+        /// again by the next call to `peek`, [`recv`], or [`recv_ref`]. This
+        /// allows a consumer to wait for a message, inspect it, and only then
+        /// decide whether to take it. The returned future completes with `None`
+        /// if the channel has closed and all messages have been received.
+        ///
+        /// Because this method takes `&mut self`, no message can be received
+        /// while the returned reference exists.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, mut rx) = mpsc::channel(8);
+        ///
+        ///     tokio::spawn(async move {
+        ///         tx.send(1).await.unwrap();
+        ///         tx.send(2).await.unwrap();
+        ///     });
+        ///
+        ///     assert_eq!(rx.peek().await, Some(&1));
+        ///     // Peeking does not consume the message.
+        ///     assert_eq!(rx.peek().await, Some(&1));
+        ///     assert_eq!(rx.recv().await, Some(1));
+        ///
+        ///     assert_eq!(rx.peek().await, Some(&2));
+        ///     assert_eq!(rx.recv().await, Some(2));
+        ///     assert_eq!(rx.peek().await, None);
+        /// }
+        /// ```
+        ///
+        /// [`recv`]: Self::recv
+        /// [`recv_ref`]: Self::recv_ref
+        pub fn peek(&mut self) -> PeekFuture<'_, T> {
+            PeekFuture {
+                core: &self.inner.core,
+                slots: self.inner.slots.as_ref(),
+            }
+        }
+
+        /// Waits for the next message, and receives it only if it matches the
+        /// predicate `f`.
+        ///
+        /// If the next message does not match, it is left at the front of the
+        /// channel, and `Ok(None)` is returned, so that it can be handled by
+        /// another code path (such as [`recv`]). The returned future completes
+        /// with a [`Closed`] error if the channel has closed and all messages
+        /// have been received.
+        ///
+        /// Because this method takes `&mut self`, no other message can be
+        /// received while the predicate is evaluated.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, mut rx) = mpsc::channel(8);
+        ///
+        ///     tokio::spawn(async move {
+        ///         tx.send(1).await.unwrap();
+        ///         tx.send(2).await.unwrap();
+        ///     });
+        ///
+        ///     // The first message is rejected, and stays in the channel.
+        ///     assert_eq!(rx.recv_if(|&msg| msg == 2).await, Ok(None));
+        ///     assert_eq!(rx.recv().await, Some(1));
+        ///
+        ///     assert_eq!(rx.recv_if(|&msg| msg == 2).await, Ok(Some(2)));
+        ///     assert!(rx.recv_if(|_| true).await.is_err());
+        /// }
+        /// ```
+        ///
+        /// [`recv`]: Self::recv
+        pub async fn recv_if<F>(&mut self, f: F) -> Result<Option<T>, Closed>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            if self.peek().await.is_none() {
+                return Err(Closed(()));
+            }
+            Ok(self.recv_peeked_if(f))
+        }
+
+        /// Attempts to return a reference to the next message *without*
+        /// receiving it, registering the current task for wakeup if a message
+        /// is not yet available.
+        ///
+        /// To wait asynchronously until a message becomes available, use the
+        /// [`peek`] method instead.
+        ///
+        /// # Returns
+        ///
+        ///  * `Poll::Pending` if no messages are available but the channel is not
+        ///    closed, or if a spurious failure happens.
+        ///  * `Poll::Ready(Some(&T))` if a message is available. The message is
+        ///    not removed from the channel.
+        ///  * `Poll::Ready(None)` if the channel has been closed (i.e., all
+        ///    [`Sender`]s have been dropped) and all messages sent before it
+        ///    was closed have been received.
+        ///
+        /// When the method returns [`Poll::Pending`], the [`Waker`] in the provided
+        /// [`Context`] is scheduled to receive a wakeup when a message is sent on any
+        /// sender, or when the channel is closed.
+        ///
+        /// [`peek`]: Self::peek
+        pub fn poll_peek(&mut self, cx: &mut Context<'_>) -> Poll<Option<&T>> {
+            poll_peek(&self.inner.core, self.inner.slots.as_ref(), cx)
+        }
+
+        /// Attempts to receive the next message, but only if it matches the
+        /// predicate `f`, registering the current task for wakeup if a message
+        /// is not yet available.
+        ///
+        /// This is the `poll`-based version of [`recv_if`].
+        ///
+        /// # Returns
+        ///
+        ///  * `Poll::Pending` if no messages are available but the channel is not
+        ///    closed, or if a spurious failure happens.
+        ///  * `Poll::Ready(Ok(Some(message)))` if the next message matches the
+        ///    predicate.
+        ///  * `Poll::Ready(Ok(None))` if the next message does not match the
+        ///    predicate. The message is left at the front of the channel.
+        ///  * `Poll::Ready(Err(Closed))` if the channel has been closed (i.e., all
+        ///    [`Sender`]s have been dropped) and all messages sent before it
+        ///    was closed have been received.
+        ///
+        /// [`recv_if`]: Self::recv_if
+        pub fn poll_recv_if<F>(
+            &mut self,
+            cx: &mut Context<'_>,
+            f: F,
+        ) -> Poll<Result<Option<T>, Closed>>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            match poll_peek(&self.inner.core, self.inner.slots.as_ref(), cx) {
+                Poll::Ready(Some(_)) => Poll::Ready(Ok(self.recv_peeked_if(f))),
+                Poll::Ready(None) => Poll::Ready(Err(Closed(()))),
+                Poll::Pending => Poll::Pending,
+            }
+        }
+
+        fn recv_peeked_if<F>(&mut self, f: F) -> Option<T>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            self.try_recv_if(f)
+                .expect("a peeked message stays at the front of the channel")
+        }
+
         /// Returns `true` if the channel has closed (all corresponding
-        /// [`Sender`]s have been dropped).
+        /// [`Sender`]s have been dropped, or the channel's group has shut it
+        /// down).
         ///
         /// If this method returns `true`, no new messages will become available
         /// on this channel. Previously sent messages may still be available.
         pub fn is_closed(&self) -> bool {
-            test_dbg!(self.inner.core.tx_count.load(Ordering::SeqCst)) <= 1
+            test_dbg!(self.inner.core.tx_count.load(Ordering::SeqCst)) == 0
+                || self.inner.core.core.is_closed()
+        }
+
+        /// Returns a new [`Sender`] for this channel, or `None` if the channel has
+        /// already closed.
+        ///
+        /// This allows producers to attach to an existing channel without
+        /// threading a [`Sender`] through to them from where the channel was
+        /// created. Any [`Sender`] may also be cloned to create additional senders.
+        ///
+        /// Once every [`Sender`] for a channel has been dropped, the channel is
+        /// closed permanently, and this method will always return `None`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// let (tx, rx) = mpsc::channel::<usize>(4);
+        ///
+        /// // Create a new sender from the receiver.
+        /// let tx2 = rx.sender().expect("channel is still open");
+        /// tx2.try_send(1).unwrap();
+        /// assert_eq!(rx.try_recv(), Ok(1));
+        ///
+        /// // Once all senders have been dropped, no new ones can be created.
+        /// drop((tx, tx2));
+        /// assert!(rx.sender().is_none());
+        /// ```
+        pub fn sender(&self) -> Option<Sender<T, R>> {
+            if !self.inner.core.add_tx() {
+                return None;
+            }
+
+            Some(Sender {
+            inner: self.inner.clone(),
+        })
+        }
+
+        /// Sets the number of times senders and this receiver will retry an
+        /// operation on a full (or empty) channel before waiting.
+        ///
+        /// By default, the spin budget is 0: when the channel is full, a
+        /// [`Sender`] immediately registers itself in the channel's wait queue,
+        /// and when the channel is empty, the receiver immediately waits to be woken.
+        /// Raising the budget makes them retry (with a short backoff between
+        /// attempts) first, which can reduce latency when capacity or messages are
+        /// expected to become available very soon, at the cost of burning CPU time.
+        /// Oversubscribed systems should generally leave the budget at 0.
+        ///
+        /// The spin budget applies to the whole channel, and may be changed at any
+        /// time.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// let (_tx, rx) = mpsc::channel::<usize>(8);
+        ///
+        /// // Low-latency consumers may want to spin for a while before waiting.
+        /// rx.set_spin_budget(64);
+        /// assert_eq!(rx.spin_budget(), 64);
+        /// ```
+        #[inline]
+        pub fn set_spin_budget(&self, spins: usize) {
+            self.inner.core.spin_budget.store(spins, Ordering::Relaxed);
+        }
+
+        /// Returns the channel's current spin budget.
+        ///
+        /// See [`set_spin_budget`](Self::set_spin_budget) for details.
+        #[inline]
+        #[must_use]
+        pub fn spin_budget(&self) -> usize {
+            self.inner.core.spin_budget.load(Ordering::Relaxed)
+        }
+
+        /// Enables or disables adaptive spinning for this channel.
+        ///
+        /// When adaptive spinning is enabled, the channel's [spin
+        /// budget](Self::spin_budget) is tuned at runtime: whenever a sender or
+        /// receiver spins until it runs out of budget, the budget is halved, and
+        /// whenever spinning succeeds before the budget runs out, it is doubled (up
+        /// to an upper limit of 1024). This lets the channel spin when messages
+        /// arrive in quick succession, and stop burning CPU time when they do
+        /// not. Unlike the blocking channels, async senders and receivers never
+        /// yield the thread to the OS scheduler while spinning, since that would
+        /// block the executor.
+        ///
+        /// Adaptive spinning is disabled by default.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// let (_tx, rx) = mpsc::channel::<usize>(8);
+        ///
+        /// rx.set_adaptive_spinning(true);
+        /// assert!(rx.is_adaptive_spinning());
+        /// ```
+        #[inline]
+        pub fn set_adaptive_spinning(&self, adaptive: bool) {
+            self.inner.core.adaptive_spin.store(adaptive, Ordering::Relaxed);
+        }
+
+        /// Returns `true` if adaptive spinning is enabled for this channel.
+        ///
+        /// See [`set_adaptive_spinning`](Self::set_adaptive_spinning) for details.
+        #[inline]
+        #[must_use]
+        pub fn is_adaptive_spinning(&self) -> bool {
+            self.inner.core.adaptive_spin.load(Ordering::Relaxed)
+        }
+
+        /// Enables probabilistic load shedding above the given occupancy
+        /// `ratio`, or disables it if `ratio` is `None`.
+        ///
+        /// When load shedding is enabled and the fraction of the channel's
+        /// capacity that is occupied exceeds `ratio`, messages sent with
+        /// `try_send` are randomly dropped rather than sent. The probability that
+        /// a message is dropped rises linearly from 0 at the threshold to 1 when
+        /// the channel is full. A dropped message is handed back in a
+        /// [`TrySendError::Shed`] error, and counted by
+        /// [`shed_count`](Self::shed_count). Messages sent with `send`, which
+        /// waits for capacity instead, or with `send_ref` or `try_send_ref`, are
+        /// never dropped. If `ratio` is 1.0, no messages are dropped, and a full
+        /// channel is still reported as full.
+        ///
+        /// This lets producers that can tolerate lost messages, such as
+        /// metrics pipelines, degrade gracefully as a slow consumer falls
+        /// behind, rather than all being stalled as soon as the channel fills
+        /// up.
+        ///
+        /// Load shedding is disabled by default.
+        ///
+        /// # Panics
+        ///
+        /// If `ratio` is not between 0.0 and 1.0.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        /// use thingbuf::mpsc::errors::TrySendError;
+        ///
+        /// let (tx, rx) = mpsc::channel::<usize>(64);
+        ///
+        /// // Start shedding load once the channel is half full.
+        /// rx.set_load_shedding(Some(0.5));
+        ///
+        /// for i in 0..1000 {
+        ///     match tx.try_send(i) {
+        ///         // A shed message is handed back, and may be dropped.
+        ///         Ok(()) | Err(TrySendError::Shed(_)) => {}
+        ///         Err(e) => panic!("unexpected error: {}", e),
+        ///     }
+        ///     if i % 2 == 0 {
+        ///         rx.try_recv().unwrap();
+        ///     }
+        /// }
+        /// assert!(rx.shed_count() > 0);
+        /// ```
+        #[inline]
+        pub fn set_load_shedding(&self, ratio: Option<f64>) {
+            self.inner.core.set_load_shedding(ratio);
+        }
+
+        /// Returns the number of messages that have been dropped by load
+        /// shedding.
+        ///
+        /// See [`set_load_shedding`](Self::set_load_shedding) for details.
+        #[inline]
+        #[must_use]
+        pub fn shed_count(&self) -> usize {
+            self.inner.core.shed_count.load(Ordering::Relaxed)
+        }
+
+        /// Sets the policy used to wake senders that are waiting for capacity,
+        /// when this receiver frees slots.
+        ///
+        /// By default, one waiting sender is woken for each freed slot. See
+        /// [`NotifyPolicy`] for the available policies.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::{self, NotifyPolicy};
+        ///
+        /// let (tx, rx) = mpsc::channel::<usize>(64);
+        ///
+        /// // Wake every waiting sender when 16 or more slots are freed at once.
+        /// rx.set_notify_policy(NotifyPolicy::WakeAll { threshold: 16 });
+        /// assert_eq!(rx.notify_policy(), NotifyPolicy::WakeAll { threshold: 16 });
+        /// ```
+        #[inline]
+        pub fn set_notify_policy(&self, policy: NotifyPolicy) {
+            self.inner.core.set_notify_policy(policy);
+        }
+
+        /// Returns the policy used to wake waiting senders.
+        ///
+        /// See [`set_notify_policy`](Self::set_notify_policy) for details.
+        #[inline]
+        #[must_use]
+        pub fn notify_policy(&self) -> NotifyPolicy {
+            self.inner.core.notify_policy()
         }
 
         /// Returns the *total* capacity of the channel for this [`Receiver`].
@@ -736,6 +2230,58 @@ feature! {
             self.inner.core.core.capacity()
         }
 
+        /// Returns a snapshot of the statistics recorded for this channel.
+        ///
+        /// See the [`stats`](crate::stats) module for details.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::channel;
+        ///
+        /// let (tx, rx) = channel::<usize>(100);
+        /// tx.try_send(1).unwrap();
+        ///
+        /// assert_eq!(rx.stats().occupancy().samples(), 1);
+        /// ```
+        #[cfg(feature = "stats")]
+        #[must_use]
+        pub fn stats(&self) -> crate::stats::Stats {
+            self.inner.core.core.stats()
+        }
+
+        /// Returns the sequence number of the next message that will be
+        /// received from this channel.
+        ///
+        /// This is the number of messages that have been received so far.
+        /// Comparing it with the [`seq`] of a received message, or with the
+        /// [`Sender::published_seq`], allows consumers to detect gaps in the
+        /// sequence of messages.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// let (tx, rx) = mpsc::channel::<usize>(8);
+        /// tx.try_send(1).unwrap();
+        /// tx.try_send(1).unwrap();
+        /// assert_eq!(rx.consumed_seq(), 0);
+        ///
+        /// let msg = rx.try_recv_ref().unwrap();
+        /// assert_eq!(msg.seq(), 0);
+        /// drop(msg);
+        /// assert_eq!(rx.consumed_seq(), 1);
+        /// ```
+        ///
+        /// [`seq`]: RecvRef::seq
+        #[cfg(feature = "seq")]
+        #[inline]
+        #[must_use]
+        pub fn consumed_seq(&self) -> usize {
+            self.inner.core.core.consumed_seq()
+        }
+
         /// Returns the unoccupied capacity of the channel for this [`Receiver`]
         /// (i.e., how many additional elements can be sent before the channel
         /// will be full).
@@ -812,22 +2358,89 @@ feature! {
         pub fn is_empty(&self) -> bool {
             self.len() == 0
         }
-    }
-
-    impl<T, R> Drop for Receiver<T, R> {
-        fn drop(&mut self) {
-            self.inner.core.close_rx();
-        }
-    }
 
-    impl<T, R: fmt::Debug> fmt::Debug for Inner<T, R> {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            f.debug_struct("Inner")
-                .field("core", &self.core)
-                .field("slots", &format_args!("Box<[..]>"))
-                .field("recycle", &self.recycle)
-                .finish()
-        }
+        /// Converts this `Receiver` back into a [`ThingBuf`] containing any
+        /// messages that were not received, once every [`Sender`] has been dropped.
+        ///
+        /// This allows the channel's storage (and any allocations owned by the
+        /// messages in it) to be reused or inspected after the channel has shut
+        /// down. The returned `ThingBuf` has the same capacity and [recycling
+        /// policy] as the channel, and may be pushed to again.
+        ///
+        /// # Errors
+        ///
+        /// If any [`Sender`]s still exist, this returns the `Receiver` unchanged.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// # #[tokio::main(flavor = "current_thread")]
+        /// # async fn main() {
+        /// let (tx, rx) = mpsc::channel(4);
+        /// tx.send(1).await.unwrap();
+        /// tx.send(2).await.unwrap();
+        ///
+        /// // The sender still exists, so the buffer can't be recovered yet.
+        /// let rx = rx.into_inner().unwrap_err();
+        /// assert_eq!(rx.recv().await, Some(1));
+        ///
+        /// drop(tx);
+        /// let buf = rx.into_inner().unwrap();
+        /// assert_eq!(buf.pop(), Some(2));
+        ///
+        /// // The buffer may be reused.
+        /// buf.push(3).unwrap();
+        /// assert_eq!(buf.pop(), Some(3));
+        /// # }
+        /// ```
+        ///
+        /// [`ThingBuf`]: crate::ThingBuf
+        /// [recycling policy]: crate::recycling::Recycle
+        pub fn into_inner(self) -> Result<crate::ThingBuf<T, R>, Self> {
+            // `Receiver` closes the channel when it's dropped, so move the `Arc`
+            // out of it without running its destructor.
+            let this = mem::ManuallyDrop::new(self);
+            // Safety: `this` is never used again, and is not dropped.
+            let inner = unsafe { ptr::read(&this.inner) };
+            Arc::try_unwrap(inner)
+                .map(Inner::into_thingbuf)
+                .map_err(|inner| Self { inner })
+        }
+    }
+
+    impl<T, R> Drop for Receiver<T, R> {
+        fn drop(&mut self) {
+            self.inner.core.close_rx();
+        }
+    }
+
+    impl<T, R: fmt::Debug> fmt::Debug for Inner<T, R> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Inner")
+                .field("core", &self.core)
+                .field("slots", &format_args!("Box<[..]>"))
+                .field("recycle", &self.recycle)
+                .finish()
+        }
+    }
+
+    impl<T, R> Inner<T, R> {
+        fn into_thingbuf(self) -> crate::ThingBuf<T, R> {
+            // `Inner` drops the slots when it's dropped, so move its fields out
+            // without running its destructor.
+            let this = mem::ManuallyDrop::new(self);
+            // Safety: each field is read exactly once, and `this` is not dropped.
+            let (core, slots, recycle) = unsafe {
+                (
+                    ptr::read(&this.core),
+                    ptr::read(&this.slots),
+                    ptr::read(&this.recycle),
+                )
+            };
+            core.into_thingbuf(slots, recycle)
+        }
     }
 
     impl<T, R> Drop for Inner<T, R> {
@@ -837,344 +2450,2726 @@ feature! {
     }
 }
 
-#[cfg(not(all(loom, test)))]
 feature! {
-    #![feature = "static"]
-    use crate::loom::atomic::AtomicBool;
+    #![feature = "alloc"]
 
-    /// A statically-allocated, asynchronous bounded MPSC channel.
-    ///
-    /// A statically-allocated channel allows using a MPSC channel without
-    /// requiring _any_ heap allocations, and can be used in environments that
-    /// don't support `liballoc`.
-    ///
-    /// In order to use a statically-allocated channel, a `StaticChannel` must
-    /// be constructed in a `static` initializer. This reserves storage for the
-    /// channel's message queue at compile-time. Then, at runtime, the channel
-    /// is [`split`] into a [`StaticSender`]/[`StaticReceiver`] pair in order to
-    /// be used.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use thingbuf::mpsc::StaticChannel;
+    use alloc::boxed::Box as TimerBox;
+    use core::time::Duration;
+    use crate::time::{Sleep, Timer};
+
+    impl<T, R> Receiver<T, R> {
+        /// Returns a stream of messages received from this channel, which
+        /// yields an [`Elapsed`] error whenever no message is received within
+        /// `timeout`, as measured by `timer`.
+        ///
+        /// This is the runtime-agnostic version of [`timeout_items`], which
+        /// accepts any [`Timer`]. See [the `time` module](crate::time) for
+        /// the provided timers, and an example of this method.
+        ///
+        /// [`timeout_items`]: Self::timeout_items
+        pub fn timeout_items_with<'a, Tm>(
+            &'a self,
+            timeout: Duration,
+            timer: Tm,
+        ) -> TimeoutItems<'a, T, R>
+        where
+            Tm: Timer,
+            Tm::Sleep: Send + 'a,
+        {
+            TimeoutItems {
+                core: &self.inner.core,
+                slots: self.inner.slots.as_ref(),
+                recycle: &self.inner.recycle,
+                timeout,
+                sleep: TimerBox::pin(timer.sleep(timeout)),
+                skip_while: None,
+            }
+        }
+    }
+
+    /// A stream of messages received from a [`Receiver`], which yields an
+    /// [`Elapsed`] error when no message is received within a timeout.
     ///
-    /// // Construct a statically-allocated channel of `usize`s with a capacity
-    /// // of 16 messages.
-    /// static MY_CHANNEL: StaticChannel<usize, 16> = StaticChannel::new();
+    /// This type is returned by [`Receiver::timeout_items`] and
+    /// [`Receiver::timeout_items_with`].
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TimeoutItems<'a, T, R = recycling::DefaultRecycle, F = fn(&T) -> bool> {
+        core: &'a ChannelCore<Waker>,
+        slots: &'a [Slot<T>],
+        recycle: &'a R,
+        timeout: Duration,
+        sleep: Pin<TimerBox<dyn Sleep + Send + 'a>>,
+        skip_while: Option<F>,
+    }
+
+    /// A [`Future`] that waits for the next item in a [`TimeoutItems`] stream.
     ///
-    /// fn main() {
-    ///     // Split the `StaticChannel` into a sender-receiver pair.
-    ///     let (tx, rx) = MY_CHANNEL.split();
+    /// This type is returned by [`TimeoutItems::next`].
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct NextTimeoutItem<'items, 'a, T, R = recycling::DefaultRecycle, F = fn(&T) -> bool> {
+        items: &'items mut TimeoutItems<'a, T, R, F>,
+    }
+
+    // === impl TimeoutItems ===
+
+    impl<'a, T, R, F> TimeoutItems<'a, T, R, F>
+    where
+        R: Recycle<T>,
+        F: FnMut(&T) -> bool,
+    {
+        /// Returns a stream which skips messages while `f` returns `true`.
+        ///
+        /// Once `f` returns `false` for a message, that message and every
+        /// subsequent message are yielded, and `f` is no longer called.
+        /// Skipped messages are received from the channel and discarded, and
+        /// restart the timeout like any other message.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::channel;
+        /// use std::time::Duration;
+        ///
+        /// # // `timeout_items` requires the "tokio" feature.
+        /// # #[cfg(not(feature = "tokio"))]
+        /// # fn main() {}
+        /// # #[cfg(feature = "tokio")]
+        /// #[tokio::main]
+        /// async fn main() {
+        /// let (tx, rx) = channel(8);
+        /// let mut items = rx
+        ///     .timeout_items(Duration::from_secs(1))
+        ///     .skip_while(|&msg| msg < 3);
+        ///
+        /// for i in 0..5 {
+        ///     tx.send(i).await.unwrap();
+        /// }
+        /// drop(tx);
+        ///
+        /// assert_eq!(items.next().await, Some(Ok(3)));
+        /// assert_eq!(items.next().await, Some(Ok(4)));
+        /// assert_eq!(items.next().await, None);
+        /// # }
+        /// ```
+        pub fn skip_while<F2>(self, f: F2) -> TimeoutItems<'a, T, R, F2>
+        where
+            F2: FnMut(&T) -> bool,
+        {
+            TimeoutItems {
+                core: self.core,
+                slots: self.slots,
+                recycle: self.recycle,
+                timeout: self.timeout,
+                sleep: self.sleep,
+                skip_while: Some(f),
+            }
+        }
+
+        /// Waits for the next item in the stream.
+        ///
+        /// This returns `None` once the channel has closed and every message
+        /// has been received.
+        #[allow(clippy::should_implement_trait)]
+        pub fn next(&mut self) -> NextTimeoutItem<'_, 'a, T, R, F> {
+            NextTimeoutItem { items: self }
+        }
+
+        /// Polls for the next item in the stream.
+        ///
+        /// This has the same signature as `Stream::poll_next`, so that a
+        /// `TimeoutItems` may easily be adapted into a `Stream`.
+        pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<T, Elapsed>>> {
+            while let Poll::Ready(msg) = poll_recv_ref(self.core, self.slots, cx) {
+                self.reset();
+                let mut msg = match msg {
+                    Some(msg) => msg,
+                    None => return Poll::Ready(None),
+                };
+                if let Some(skip_while) = self.skip_while.as_mut() {
+                    if skip_while(&*msg) {
+                        continue;
+                    }
+                    self.skip_while = None;
+                }
+                return Poll::Ready(Some(Ok(recycling::take(&mut *msg, self.recycle))));
+            }
+
+            match self.sleep.as_mut().poll(cx) {
+                Poll::Ready(()) => {
+                    self.reset();
+                    Poll::Ready(Some(Err(Elapsed(()))))
+                }
+                Poll::Pending => Poll::Pending,
+            }
+        }
+
+        fn reset(&mut self) {
+            self.sleep.as_mut().reset(self.timeout);
+        }
+    }
+
+    impl<T, R: fmt::Debug, F> fmt::Debug for TimeoutItems<'_, T, R, F> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("TimeoutItems")
+                .field("core", &self.core)
+                .field("slots", &format_args!("&[..]"))
+                .field("recycle", &self.recycle)
+                .field("timeout", &self.timeout)
+                .field("skip_while", &self.skip_while.is_some())
+                .finish()
+        }
+    }
+
+    // === impl NextTimeoutItem ===
+
+    impl<T, R, F> Future for NextTimeoutItem<'_, '_, T, R, F>
+    where
+        R: Recycle<T>,
+        F: FnMut(&T) -> bool,
+    {
+        type Output = Option<Result<T, Elapsed>>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            self.items.poll_next(cx)
+        }
+    }
+
+    impl<T, R: fmt::Debug, F> fmt::Debug for NextTimeoutItem<'_, '_, T, R, F> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("NextTimeoutItem")
+                .field("items", &self.items)
+                .finish()
+        }
+    }
+
+    impl<T, R> Receiver<T, R> {
+        /// Returns a stream of messages received from this channel, which is
+        /// only woken once at least `watermark` messages are queued, or once
+        /// `max_delay` has elapsed, as measured by `timer`.
+        ///
+        /// This is the async equivalent of the blocking receivers'
+        /// [`set_wake_watermark`]. While the returned [`WatermarkItems`]
+        /// exists, senders only wake the receiving task once `watermark`
+        /// messages are queued, so a consumer that processes messages in
+        /// batches is woken once per batch rather than once per message. So
+        /// that a partial batch is never left waiting indefinitely, the stream
+        /// also checks the channel every `max_delay` while it waits. A
+        /// watermark greater than the channel's capacity is lowered to the
+        /// capacity, and dropping the stream restores the default of waking
+        /// the receiver on every send.
+        ///
+        /// Receiving never waits for the watermark when messages are already
+        /// queued: the watermark only controls when a *waiting* task is woken.
+        ///
+        /// See [the `time` module](crate::time) for the provided timers.
+        ///
+        /// [`set_wake_watermark`]: crate::mpsc::blocking::Receiver::set_wake_watermark
+        pub fn watermark_items_with<'a, Tm>(
+            &'a self,
+            watermark: usize,
+            max_delay: Duration,
+            timer: Tm,
+        ) -> WatermarkItems<'a, T, R>
+        where
+            Tm: Timer,
+            Tm::Sleep: Send + 'a,
+        {
+            self.inner.core.set_watermark(watermark);
+            WatermarkItems {
+                core: &self.inner.core,
+                slots: self.inner.slots.as_ref(),
+                recycle: &self.inner.recycle,
+                max_delay,
+                sleep: TimerBox::pin(timer.sleep(max_delay)),
+                waiting: false,
+            }
+        }
+    }
+
+    /// A stream of messages received from a [`Receiver`], whose task is only
+    /// woken once a number of messages are queued, or a delay has elapsed.
     ///
-    ///     // Now, `tx` and `rx` can be used just like any other async MPSC
-    ///     // channel...
-    /// # drop(tx); drop(rx);
-    /// }
-    /// ```
-    /// [`split`]: StaticChannel::split
-    pub struct StaticChannel<T, const CAPACITY: usize, R = recycling::DefaultRecycle> {
-        core: ChannelCore<Waker>,
-        recycle: R,
-        slots: [Slot<T>; CAPACITY],
-        is_split: AtomicBool,
+    /// This type is returned by [`Receiver::watermark_items`] and
+    /// [`Receiver::watermark_items_with`].
+    #[must_use = "streams do nothing unless polled"]
+    pub struct WatermarkItems<'a, T, R = recycling::DefaultRecycle> {
+        core: &'a ChannelCore<Waker>,
+        slots: &'a [Slot<T>],
+        recycle: &'a R,
+        max_delay: Duration,
+        sleep: Pin<TimerBox<dyn Sleep + Send + 'a>>,
+        /// Whether `sleep` was started when the stream last began waiting.
+        waiting: bool,
     }
 
-    /// Asynchronously sends values to an associated [`StaticReceiver`].
+    /// A [`Future`] that waits for the next item in a [`WatermarkItems`]
+    /// stream.
     ///
-    /// Instances of this struct are created by the [`StaticChannel::split`] and
-    /// [``StaticChannel::try_split`] functions.
-    pub struct StaticSender<T: 'static, R: 'static = recycling::DefaultRecycle> {
-        core: &'static ChannelCore<Waker>,
-        recycle: &'static R,
-        slots: &'static [Slot<T>],
+    /// This type is returned by [`WatermarkItems::next`].
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct NextWatermarkItem<'items, 'a, T, R = recycling::DefaultRecycle> {
+        items: &'items mut WatermarkItems<'a, T, R>,
+    }
+
+    // === impl WatermarkItems ===
+
+    impl<'a, T, R> WatermarkItems<'a, T, R>
+    where
+        R: Recycle<T>,
+    {
+        /// Waits for the next message in the stream.
+        ///
+        /// This returns `None` once the channel has closed and every message
+        /// has been received.
+        #[allow(clippy::should_implement_trait)]
+        pub fn next(&mut self) -> NextWatermarkItem<'_, 'a, T, R> {
+            NextWatermarkItem { items: self }
+        }
+
+        /// Polls for the next message in the stream.
+        ///
+        /// This has the same signature as `Stream::poll_next`, so that a
+        /// `WatermarkItems` may easily be adapted into a `Stream`.
+        pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+            loop {
+                if let Poll::Ready(msg) = poll_recv_ref(self.core, self.slots, cx) {
+                    self.waiting = false;
+                    return Poll::Ready(msg.map(|mut msg| recycling::take(&mut *msg, self.recycle)));
+                }
+
+                // Senders may not wake the task until the watermark is
+                // reached, so also wake it once `max_delay` has elapsed.
+                if !self.waiting {
+                    self.sleep.as_mut().reset(self.max_delay);
+                    self.waiting = true;
+                }
+                if self.sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                // The delay elapsed, so check the channel again, and start a
+                // new delay if it is still empty.
+                self.waiting = false;
+            }
+        }
+    }
+
+    impl<T, R> Drop for WatermarkItems<'_, T, R> {
+        fn drop(&mut self) {
+            self.core.set_watermark(1);
+        }
+    }
+
+    impl<T, R: fmt::Debug> fmt::Debug for WatermarkItems<'_, T, R> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("WatermarkItems")
+                .field("core", &self.core)
+                .field("slots", &format_args!("&[..]"))
+                .field("recycle", &self.recycle)
+                .field("max_delay", &self.max_delay)
+                .field("waiting", &self.waiting)
+                .finish()
+        }
+    }
+
+    // === impl NextWatermarkItem ===
+
+    impl<T, R> Future for NextWatermarkItem<'_, '_, T, R>
+    where
+        R: Recycle<T>,
+    {
+        type Output = Option<T>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            self.items.poll_next(cx)
+        }
+    }
+
+    impl<T, R: fmt::Debug> fmt::Debug for NextWatermarkItem<'_, '_, T, R> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("NextWatermarkItem")
+                .field("items", &self.items)
+                .finish()
+        }
+    }
+}
+
+feature! {
+    #![all(feature = "alloc", feature = "tokio")]
+
+    impl<T, R> Receiver<T, R> {
+        /// Returns a stream of messages received from this channel, which
+        /// yields an [`Elapsed`] error whenever no message is received within
+        /// `timeout`.
+        ///
+        /// The timeout is restarted every time the stream yields an item, so
+        /// an `Err(Elapsed)` is yielded for every `timeout` that passes
+        /// without a message. This makes it possible to detect a stalled
+        /// producer (for example, when messages are heartbeats) without
+        /// wrapping every call to [`recv`] in a separate timeout.
+        ///
+        /// The returned [`TimeoutItems`] uses a [Tokio timer], and must be
+        /// polled within a Tokio runtime with the time driver enabled. This
+        /// method requires the "tokio" feature flag; to use another runtime's
+        /// timer, use [`timeout_items_with`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::channel;
+        /// use std::time::Duration;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        /// let (tx, rx) = channel(8);
+        /// let mut items = rx.timeout_items(Duration::from_secs(1));
+        ///
+        /// tx.send(1).await.unwrap();
+        /// assert_eq!(items.next().await, Some(Ok(1)));
+        ///
+        /// // No message is sent within the timeout.
+        /// assert!(items.next().await.unwrap().is_err());
+        ///
+        /// drop(tx);
+        /// assert_eq!(items.next().await, None);
+        /// # }
+        /// ```
+        ///
+        /// [`recv`]: Self::recv
+        /// [`timeout_items_with`]: Self::timeout_items_with
+        /// [Tokio timer]: crate::time::TokioTimer
+        pub fn timeout_items(&self, timeout: Duration) -> TimeoutItems<'_, T, R> {
+            self.timeout_items_with(timeout, crate::time::TokioTimer)
+        }
+
+        /// Returns a stream of messages received from this channel, which is
+        /// only woken once at least `watermark` messages are queued, or once
+        /// `max_delay` has elapsed.
+        ///
+        /// The returned [`WatermarkItems`] uses a [Tokio timer], and must be
+        /// polled within a Tokio runtime with the time driver enabled. This
+        /// method requires the "tokio" feature flag; to use another runtime's
+        /// timer, use [`watermark_items_with`], which also describes the
+        /// watermark in more detail.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::channel;
+        /// use std::time::Duration;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        /// let (tx, rx) = channel(64);
+        /// // Wake up once 16 messages are ready, or after at most 10ms.
+        /// let mut items = rx.watermark_items(16, Duration::from_millis(10));
+        ///
+        /// tx.send(1).await.unwrap();
+        /// assert_eq!(items.next().await, Some(1));
+        ///
+        /// drop(tx);
+        /// assert_eq!(items.next().await, None);
+        /// # }
+        /// ```
+        ///
+        /// [`watermark_items_with`]: Self::watermark_items_with
+        /// [Tokio timer]: crate::time::TokioTimer
+        pub fn watermark_items(
+            &self,
+            watermark: usize,
+            max_delay: Duration,
+        ) -> WatermarkItems<'_, T, R> {
+            self.watermark_items_with(watermark, max_delay, crate::time::TokioTimer)
+        }
     }
+}
+
+feature! {
+    #![all(feature = "alloc", feature = "futures-sink")]
+
+    use alloc::vec::Vec;
+
+    impl<T, R> Sender<T, R>
+    where
+        R: Recycle<T>,
+    {
+        /// Returns a [`BatchSink`] that sends messages to this channel in
+        /// batches of up to `batch` messages, using `timer` to measure the
+        /// maximum delay.
+        ///
+        /// This is the runtime-agnostic version of [`batch_sink`], which
+        /// accepts any [`Timer`]. See [the `time` module](crate::time) for
+        /// the provided timers. This method requires the "futures-sink"
+        /// feature flag.
+        ///
+        /// # Panics
+        ///
+        /// If `batch` is 0 or larger than the channel's capacity.
+        ///
+        /// [`batch_sink`]: Self::batch_sink
+        pub fn batch_sink_with<'a, Tm>(
+            &'a self,
+            batch: usize,
+            max_delay: Duration,
+            timer: Tm,
+        ) -> BatchSink<'a, T, R>
+        where
+            Tm: Timer,
+            Tm::Sleep: Send + 'a,
+        {
+            assert!(batch > 0, "a batch must contain at least one message");
+            assert!(
+                batch <= self.capacity(),
+                "a batch of {} messages will never fit in a channel with capacity {}",
+                batch,
+                self.capacity()
+            );
+            BatchSink {
+                core: &self.inner.core,
+                slots: self.inner.slots.as_ref(),
+                recycle: &self.inner.recycle,
+                batch,
+                max_delay,
+                reserved: None,
+                filled: Vec::with_capacity(batch),
+                sleep: TimerBox::pin(timer.sleep(max_delay)),
+                state: State::Start,
+                waiter: queue::Waiter::new(),
+            }
+        }
+    }
+
+    /// A [`Sink`] that buffers messages in claimed slots, and publishes them
+    /// to the [`Receiver`] in batches.
+    ///
+    /// A batch is published when it contains the configured number of
+    /// messages, or when the configured delay has elapsed since its first
+    /// message was written. [`poll_flush`] waits until the current batch has
+    /// been published; [`poll_close`] publishes it immediately. If the channel
+    /// is full, the current batch is published before waiting for capacity.
+    ///
+    /// Messages are received in the order they were written, but the
+    /// receiver cannot receive past an unpublished message, so messages sent
+    /// by other senders after this sink claimed a slot are also delayed until
+    /// the batch is published. The delay is only enforced while the sink is
+    /// being flushed or written to.
+    ///
+    /// Instances of this struct are created by the [`Sender::batch_sink`] and
+    /// [`Sender::batch_sink_with`] methods. This type requires the
+    /// "futures-sink" feature flag.
+    ///
+    /// [`Sink`]: futures_sink::Sink
+    /// [`poll_flush`]: futures_sink::Sink::poll_flush
+    /// [`poll_close`]: futures_sink::Sink::poll_close
+    #[pin_project::pin_project(PinnedDrop)]
+    pub struct BatchSink<'a, T, R = recycling::DefaultRecycle> {
+        core: &'a ChannelCore<Waker>,
+        slots: &'a [Slot<T>],
+        recycle: &'a R,
+        batch: usize,
+        max_delay: Duration,
+        /// A slot claimed by `poll_ready` for the next call to `start_send`.
+        reserved: Option<crate::Ref<'a, T>>,
+        /// Slots that have been written to, but not yet published.
+        filled: Vec<crate::Ref<'a, T>>,
+        sleep: Pin<TimerBox<dyn Sleep + Send + 'a>>,
+        state: State,
+        #[pin]
+        waiter: queue::Waiter<Waker>,
+    }
+
+    // === impl BatchSink ===
+
+    impl<T, R> BatchSink<'_, T, R> {
+        /// Returns the number of messages that have been written to the sink
+        /// but not yet published.
+        pub fn pending(&self) -> usize {
+            self.filled.len()
+        }
+    }
+
+    /// Publishes every written slot, and wakes the receiver once.
+    fn publish<T>(core: &ChannelCore<Waker>, filled: &mut Vec<crate::Ref<'_, T>>) {
+        if filled.is_empty() {
+            return;
+        }
+        test_println!("publishing batch of {}", filled.len());
+        filled.clear();
+        core.rx_wait.notify();
+    }
+
+    impl<T, R> futures_sink::Sink<T> for BatchSink<'_, T, R>
+    where
+        R: Recycle<T>,
+    {
+        type Error = Closed;
+
+        fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+            let this = self.project();
+            if this.reserved.is_some() {
+                return Poll::Ready(Ok(()));
+            }
+
+            match this.core.core.push_ref(*this.slots, *this.recycle) {
+                Ok(slot) => {
+                    *this.reserved = Some(slot);
+                    return Poll::Ready(Ok(()));
+                }
+                Err(TrySendError::Closed(_)) => {
+                    publish(this.core, this.filled);
+                    return Poll::Ready(Err(Closed(())));
+                }
+                Err(_) => {}
+            }
+
+            // The channel is full. The receiver can't make room while it is
+            // waiting for our unpublished slots, so publish them before
+            // waiting.
+            publish(this.core, this.filled);
+            let SendRef(inner) = match poll_send_ref(
+                this.core,
+                this.slots,
+                *this.recycle,
+                this.state,
+                this.waiter,
+                cx,
+            ) {
+                Poll::Ready(res) => res?,
+                Poll::Pending => return Poll::Pending,
+            };
+            *this.state = State::Start;
+            *this.reserved = Some(inner.slot);
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Closed> {
+            let this = self.project();
+            let mut slot = this
+                .reserved
+                .take()
+                .expect("BatchSink::start_send called without waiting for poll_ready");
+            *slot = item;
+            this.filled.push(slot);
+            if this.filled.len() == 1 {
+                this.sleep.as_mut().reset(*this.max_delay);
+            }
+            if this.filled.len() >= *this.batch {
+                publish(this.core, this.filled);
+            }
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+            let this = self.project();
+            if this.filled.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+            if this.sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            publish(this.core, this.filled);
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+            let this = self.project();
+            if let Some(slot) = this.reserved.take() {
+                this.core.core.abandon_ref(slot);
+            }
+            publish(this.core, this.filled);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[pin_project::pinned_drop]
+    impl<T, R> PinnedDrop for BatchSink<'_, T, R> {
+        fn drop(self: Pin<&mut Self>) {
+            let this = self.project();
+            if test_dbg!(*this.state) == State::Waiting && test_dbg!(this.waiter.is_linked()) {
+                this.waiter.remove(&this.core.tx_wait)
+            }
+            if let Some(slot) = this.reserved.take() {
+                this.core.core.abandon_ref(slot);
+            }
+            publish(this.core, this.filled);
+        }
+    }
+
+    impl<T, R: fmt::Debug> fmt::Debug for BatchSink<'_, T, R> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("BatchSink")
+                .field("batch", &self.batch)
+                .field("max_delay", &self.max_delay)
+                .field("pending", &self.filled.len())
+                .field("reserved", &self.reserved.is_some())
+                .field("state", &self.state)
+                .field("recycle", &self.recycle)
+                .finish()
+        }
+    }
+}
+
+feature! {
+    #![all(feature = "alloc", feature = "tokio", feature = "futures-sink")]
+
+    impl<T, R> Sender<T, R>
+    where
+        R: Recycle<T>,
+    {
+        /// Returns a [`BatchSink`] that sends messages to this channel in
+        /// batches of up to `batch` messages.
+        ///
+        /// Messages written to the sink are held in claimed slots, and are
+        /// published to the [`Receiver`] together once the batch is full, or
+        /// once `max_delay` has elapsed since the first message in the batch
+        /// was written, whichever comes first. This can improve throughput
+        /// for receivers that process messages in bulk, at the cost of up to
+        /// `max_delay` of additional latency.
+        ///
+        /// The returned sink uses a [Tokio timer], and must be polled within a
+        /// Tokio runtime with the time driver enabled. This method requires
+        /// the "futures-sink" and "tokio" feature flags; to use another
+        /// runtime's timer, use [`batch_sink_with`].
+        ///
+        /// # Panics
+        ///
+        /// If `batch` is 0 or larger than the channel's capacity.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        /// use futures_util::SinkExt;
+        /// use std::time::Duration;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        /// let (tx, rx) = mpsc::channel(16);
+        /// let mut sink = Box::pin(tx.batch_sink(4, Duration::from_millis(10)));
+        ///
+        /// for i in 0..3 {
+        ///     sink.feed(i).await.unwrap();
+        /// }
+        /// // The batch is neither full nor old enough to be published yet.
+        /// assert!(rx.try_recv().is_err());
+        ///
+        /// // Flushing waits until the batch is published.
+        /// sink.flush().await.unwrap();
+        /// for i in 0..3 {
+        ///     assert_eq!(rx.try_recv(), Ok(i));
+        /// }
+        /// # }
+        /// ```
+        ///
+        /// [Tokio timer]: crate::time::TokioTimer
+        /// [`batch_sink_with`]: Self::batch_sink_with
+        pub fn batch_sink(&self, batch: usize, max_delay: Duration) -> BatchSink<'_, T, R> {
+            self.batch_sink_with(batch, max_delay, crate::time::TokioTimer)
+        }
+    }
+}
+
+feature! {
+    #![feature = "alloc"]
+    mod lines;
+    pub use self::lines::Lines;
+}
+
+feature! {
+    #![all(feature = "tower", not(all(loom, test)))]
+    pub mod tower;
+}
+
+feature! {
+    #![all(feature = "tokio-io", not(all(loom, test)))]
+    pub mod io;
+}
+
+feature! {
+    #![all(feature = "alloc", not(all(loom, test)))]
+
+    use super::group::{self, Close, MemberRef};
+
+    impl<T, R> Close for Inner<T, R> {
+        fn close(&self) {
+            self.core.close();
+        }
+
+        fn is_closed(&self) -> bool {
+            self.core.is_closed()
+        }
+
+        fn links(&self) -> &group::Links {
+            self.core.links()
+        }
+    }
+
+    impl<T: Send + 'static, R: Send + 'static> group::Sealed for Sender<T, R> {
+        fn member_ref(&self) -> MemberRef {
+            MemberRef::Dynamic(Arc::<Inner<T, R>>::downgrade(&self.inner))
+        }
+    }
+
+    impl<T: Send + 'static, R: Send + 'static> group::Member for Sender<T, R> {}
+
+    impl<T: Send + 'static, R: Send + 'static> group::Sealed for Receiver<T, R> {
+        fn member_ref(&self) -> MemberRef {
+            MemberRef::Dynamic(Arc::<Inner<T, R>>::downgrade(&self.inner))
+        }
+    }
+
+    impl<T: Send + 'static, R: Send + 'static> group::Member for Receiver<T, R> {}
+
+    #[cfg(feature = "static")]
+    impl<T, R> group::Sealed for StaticSender<T, R> {
+        fn member_ref(&self) -> MemberRef {
+            MemberRef::Static(self.core)
+        }
+    }
+
+    #[cfg(feature = "static")]
+    impl<T, R> group::Member for StaticSender<T, R> {}
+
+    #[cfg(feature = "static")]
+    impl<T, R> group::Sealed for StaticReceiver<T, R> {
+        fn member_ref(&self) -> MemberRef {
+            MemberRef::Static(self.core)
+        }
+    }
+
+    #[cfg(feature = "static")]
+    impl<T, R> group::Member for StaticReceiver<T, R> {}
+}
+
+#[cfg(not(all(loom, test)))]
+feature! {
+    #![feature = "static"]
+    use crate::loom::atomic::AtomicBool;
+
+    /// A statically-allocated, asynchronous bounded MPSC channel.
+    ///
+    /// A statically-allocated channel allows using a MPSC channel without
+    /// requiring _any_ heap allocations, and can be used in environments that
+    /// don't support `liballoc`.
+    ///
+    /// In order to use a statically-allocated channel, a `StaticChannel` must
+    /// be constructed in a `static` initializer. This reserves storage for the
+    /// channel's message queue at compile-time. Then, at runtime, the channel
+    /// is [`split`] into a [`StaticSender`]/[`StaticReceiver`] pair in order to
+    /// be used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::StaticChannel;
+    ///
+    /// // Construct a statically-allocated channel of `usize`s with a capacity
+    /// // of 16 messages.
+    /// static MY_CHANNEL: StaticChannel<usize, 16> = StaticChannel::new();
+    ///
+    /// fn main() {
+    ///     // Split the `StaticChannel` into a sender-receiver pair.
+    ///     let (tx, rx) = MY_CHANNEL.split();
+    ///
+    ///     // Now, `tx` and `rx` can be used just like any other async MPSC
+    ///     // channel...
+    /// # drop(tx); drop(rx);
+    /// }
+    /// ```
+    /// [`split`]: StaticChannel::split
+    pub struct StaticChannel<T, const CAPACITY: usize, R = recycling::DefaultRecycle> {
+        core: ChannelCore<Waker>,
+        recycle: R,
+        slots: [Slot<T>; CAPACITY],
+        is_split: AtomicBool,
+    }
+
+    /// Asynchronously sends values to an associated [`StaticReceiver`].
+    ///
+    /// Instances of this struct are created by the [`StaticChannel::split`] and
+    /// [``StaticChannel::try_split`] functions.
+    pub struct StaticSender<T: 'static, R: 'static = recycling::DefaultRecycle> {
+        core: &'static ChannelCore<Waker>,
+        recycle: &'static R,
+        slots: &'static [Slot<T>],
+    }
+
+    /// Asynchronously receives values from associated [`StaticSender`]s.
+    ///
+    /// Instances of this struct are created by the [`StaticChannel::split`] and
+    /// [``StaticChannel::try_split`] functions.
+    pub struct StaticReceiver<T: 'static, R: 'static = recycling::DefaultRecycle> {
+        core: &'static ChannelCore<Waker>,
+        recycle: &'static R,
+        slots: &'static [Slot<T>],
+    }
+
+    // === impl StaticChannel ===
+
+    impl<T, const CAPACITY: usize> StaticChannel<T, CAPACITY> {
+        /// Constructs a new statically-allocated, asynchronous bounded MPSC channel.
+        ///
+        /// A statically-allocated channel allows using a MPSC channel without
+        /// requiring _any_ heap allocations, and can be used in environments that
+        /// don't support `liballoc`.
+        ///
+        /// In order to use a statically-allocated channel, a `StaticChannel` must
+        /// be constructed in a `static` initializer. This reserves storage for the
+        /// channel's message queue at compile-time. Then, at runtime, the channel
+        /// is [`split`] into a [`StaticSender`]/[`StaticReceiver`] pair in order to
+        /// be used.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// // Construct a statically-allocated channel of `usize`s with a capacity
+        /// // of 16 messages.
+        /// static MY_CHANNEL: StaticChannel<usize, 16> = StaticChannel::new();
+        ///
+        /// fn main() {
+        ///     // Split the `StaticChannel` into a sender-receiver pair.
+        ///     let (tx, rx) = MY_CHANNEL.split();
+        ///
+        ///     // Now, `tx` and `rx` can be used just like any other async MPSC
+        ///     // channel...
+        /// # drop(tx); drop(rx);
+        /// }
+        /// ```
+        /// [`split`]: StaticChannel::split
+        #[must_use]
+        #[allow(clippy::new_without_default)]
+        pub const fn new() -> Self {
+            Self {
+                core: ChannelCore::new(CAPACITY),
+                slots: Slot::make_static_array::<CAPACITY>(),
+                is_split: AtomicBool::new(false),
+                recycle: recycling::DefaultRecycle::new(),
+            }
+        }
+    }
+
+    impl<T, R, const CAPACITY: usize> StaticChannel<T, CAPACITY, R> {
+        /// Split a [`StaticChannel`] into a [`StaticSender`]/[`StaticReceiver`]
+        /// pair.
+        ///
+        /// A static channel can only be split a single time. If
+        /// [`StaticChannel::split`] or [`StaticChannel::try_split`] have been
+        /// called previously, this method will panic. For a non-panicking version
+        /// of this method, see [`StaticChannel::try_split`].
+        ///
+        /// # Panics
+        ///
+        /// If the channel has already been split.
+        #[must_use]
+        pub fn split(&'static self) -> (StaticSender<T, R>, StaticReceiver<T, R>) {
+            self.try_split().expect("channel already split")
+        }
+
+        /// Try to split a [`StaticChannel`] into a [`StaticSender`]/[`StaticReceiver`]
+        /// pair, returning `None` if it has already been split.
+        ///
+        /// A static channel can only be split a single time. If
+        /// [`StaticChannel::split`] or [`StaticChannel::try_split`] have been
+        /// called previously, this method returns `None`.
+        #[must_use]
+        pub fn try_split(&'static self) -> Option<(StaticSender<T, R>, StaticReceiver<T, R>)> {
+            self.is_split
+                .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                .ok()?;
+            let tx = StaticSender {
+                core: &self.core,
+                recycle: &self.recycle,
+                slots: &self.slots[..],
+            };
+            let rx = StaticReceiver {
+                core: &self.core,
+                recycle: &self.recycle,
+                slots: &self.slots[..],
+            };
+            Some((tx, rx))
+        }
+    }
+
+    // === impl StaticSender ===
+
+    #[cfg(feature = "rt-safe")]
+    impl<T, R> StaticSender<T, R> {
+        /// Returns the channel's state, for the [`rt`](super::rt) handles.
+        pub(super) fn parts(&self) -> (&ChannelCore<Waker>, &[Slot<T>], &R) {
+            (self.core, self.slots, self.recycle)
+        }
+    }
+
+    impl<T, R> StaticSender<T, R>
+    where
+        R: Recycle<T>,
+    {
+        /// Reserves a slot in the channel to mutate in place, waiting until
+        /// there is a free slot to write to.
+        ///
+        /// This is similar to the [`send`] method, but, rather than taking a
+        /// message by value to write to the channel, this method reserves a
+        /// writable slot in the channel, and returns a [`SendRef`] that allows
+        /// mutating the slot in place. If the [`StaticReceiver`] end of the
+        /// channel uses the [`StaticReceiver::recv_ref`] or
+        /// [`StaticReceiver::poll_recv_ref`] methods for receiving from the
+        /// channel, this allows allocations for channel messages to be reused in place.
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel has been dropped, this
+        /// returns a [`Closed`] error.
+        ///
+        /// # Examples
+        ///
+        /// Sending formatted strings by writing them directly to channel slots,
+        /// in place:
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        /// use std::fmt::Write;
+        ///
+        /// static CHANNEL: mpsc::StaticChannel<String, 8> = mpsc::StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     // Spawn a task that prints each message received from the channel:
+        ///     tokio::spawn(async move {
+        ///         for _ in 0..10 {
+        ///             let msg = rx.recv_ref().await.unwrap();
+        ///             println!("{}", msg);
+        ///         }
+        ///     });
+        ///
+        ///     // Until the channel closes, write formatted messages to the channel.
+        ///     let mut count = 1;
+        ///     while let Ok(mut value) = tx.send_ref().await {
+        ///         // Writing to the `SendRef` will reuse the *existing* string
+        ///         // allocation in place.
+        ///         write!(value, "hello from message {}", count)
+        ///             .expect("writing to a `String` should never fail");
+        ///         count += 1;
+        ///     }
+        /// }
+        /// ```
+        /// [`send`]: Self::send
+        pub async fn send_ref(&self) -> Result<SendRef<'_, T>, Closed> {
+            SendRefFuture {
+                core: self.core,
+                slots: self.slots,
+                recycle: self.recycle,
+                state: State::Start,
+                waiter: queue::Waiter::new(),
+            }
+            .await
+        }
+
+        /// Sends a message by value, waiting until there is a free slot to
+        /// write to.
+        ///
+        /// This method takes the message by value, and replaces any previous
+        /// value in the slot. This means that the channel will *not* function
+        /// as an object pool while sending messages with `send`. This method is
+        /// most appropriate when messages don't own reusable heap allocations,
+        /// or when the [`StaticReceiver`] end of the channel must receive
+        /// messages by moving them out of the channel by value (using the
+        /// [`StaticReceiver::recv`] method). When messages in the channel own
+        /// reusable heap allocations (such as `String`s or `Vec`s), and the
+        /// [`StaticReceiver`] doesn't need to receive them by value, consider
+        /// using [`send_ref`] instead, to enable allocation reuse.
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel has been dropped, this
+        /// returns a [`Closed`] error containing the sent value.
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is **not** cancel safe. If the returned future is
+        /// dropped before it completes, for example because it lost a race in
+        /// `select!`, the message is dropped along with it. Use [`send_from`]
+        /// to keep the message when the future is cancelled.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// static CHANNEL: mpsc::StaticChannel<i32, 8> = mpsc::StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     // Spawn a task that prints each message received from the channel:
+        ///     tokio::spawn(async move {
+        ///         for _ in 0..10 {
+        ///             let msg = rx.recv().await.unwrap();
+        ///             println!("received message {}", msg);
+        ///         }
+        ///     });
+        ///
+        ///     // Until the channel closes, write the current iteration to the channel.
+        ///     let mut count = 1;
+        ///     while tx.send(count).await.is_ok() {
+        ///         count += 1;
+        ///     }
+        /// }
+        /// ```
+        /// [`send_ref`]: Self::send_ref
+        /// [`send_from`]: Self::send_from
+        pub async fn send(&self, val: T) -> Result<(), Closed<T>> {
+            match self.send_ref().await {
+                Err(Closed(())) => Err(Closed(val)),
+                Ok(mut slot) => {
+                    *slot = val;
+                    Ok(())
+                }
+            }
+        }
+
+        /// Sends the message in `val`, waiting until there is a free slot to
+        /// write to.
+        ///
+        /// Unlike [`send`], this method borrows the message from an `Option`,
+        /// and only takes it out of the `Option` once a slot has been reserved
+        /// for it, immediately before the returned future completes.
+        ///
+        /// If `val` is `None`, this completes immediately without sending
+        /// anything.
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel has been dropped, this
+        /// returns a [`Closed`] error, and the message is left in `val`.
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is cancel safe. If the returned future is dropped
+        /// before it completes, no slot was reserved, and the message is still
+        /// in `val`. This makes it suitable for sending in a `select!` loop
+        /// without losing the message when another branch completes first.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        /// use std::time::Duration;
+        ///
+        /// static CHANNEL: mpsc::StaticChannel<i32, 1> = mpsc::StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///     tx.send(0).await.unwrap();
+        ///
+        ///     let mut msg = Some(1);
+        ///     tokio::select! {
+        ///         res = tx.send_from(&mut msg) => res.unwrap(),
+        ///         _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+        ///     }
+        ///     // The channel is full, so the send was cancelled, and the
+        ///     // message is still ours.
+        ///     assert_eq!(msg, Some(1));
+        ///
+        ///     assert_eq!(rx.recv().await, Some(0));
+        ///     tx.send_from(&mut msg).await.unwrap();
+        ///     assert_eq!(msg, None);
+        ///     assert_eq!(rx.recv().await, Some(1));
+        /// }
+        /// ```
+        ///
+        /// [`send`]: Self::send
+        pub async fn send_from(&self, val: &mut Option<T>) -> Result<(), Closed> {
+            if val.is_none() {
+                return Ok(());
+            }
+            let mut slot = self.send_ref().await?;
+            *slot = val.take().expect("message must still be present");
+            Ok(())
+        }
+
+        /// Sends every item produced by `iter` to the channel, waiting for
+        /// capacity whenever the channel is full.
+        ///
+        /// Returns the number of items sent. This is equivalent to calling
+        /// [`send`] in a loop, stopping once the channel closes.
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel is dropped before every
+        /// item has been sent, this returns a [`SendAllError`] containing the
+        /// number of items that were sent, the item that could not be sent,
+        /// and the rest of the iterator.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 4> = StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     tokio::spawn(async move {
+        ///         // Waits for capacity whenever the channel is full.
+        ///         let sent = tx.send_all(0..10).await.unwrap();
+        ///         assert_eq!(sent, 10);
+        ///     });
+        ///
+        ///     for i in 0..10 {
+        ///         assert_eq!(rx.recv().await, Some(i));
+        ///     }
+        ///     assert_eq!(rx.recv().await, None);
+        /// }
+        /// ```
+        ///
+        /// [`send`]: Self::send
+        pub async fn send_all<I>(&self, iter: I) -> Result<usize, SendAllError<T, I::IntoIter>>
+        where
+            I: IntoIterator<Item = T>,
+        {
+            let mut iter = iter.into_iter();
+            let mut sent = 0;
+            for item in &mut iter {
+                if let Err(Closed(item)) = self.send(item).await {
+                    return Err(SendAllError { sent, item, rest: iter });
+                }
+                sent += 1;
+            }
+            Ok(sent)
+        }
+
+        /// Sends every message in `batch` to the channel, in order, waiting
+        /// for capacity whenever the channel is full.
+        ///
+        /// The batch may be larger than the channel's capacity: messages are
+        /// moved into slots as they are freed by the receiver, so a producer
+        /// with a burst of messages does not need its own retry loop around
+        /// [`try_send`].
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel is dropped before every
+        /// message has been sent, this returns a [`Closed`] error containing
+        /// the messages that were not sent, in order.
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is **not** cancel safe. If the returned future is
+        /// dropped before it completes, the messages that have not yet been
+        /// sent are lost.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 4> = StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     tokio::spawn(async move {
+        ///         // The batch is larger than the channel's capacity.
+        ///         tx.send_batch((0..10).collect()).await.unwrap();
+        ///     });
+        ///
+        ///     for i in 0..10 {
+        ///         assert_eq!(rx.recv().await, Some(i));
+        ///     }
+        ///     assert_eq!(rx.recv().await, None);
+        /// }
+        /// ```
+        ///
+        /// [`try_send`]: Self::try_send
+        #[cfg(feature = "alloc")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+        pub async fn send_batch(
+            &self,
+            batch: alloc::vec::Vec<T>,
+        ) -> Result<(), Closed<alloc::vec::Vec<T>>> {
+            let mut batch = alloc::collections::VecDeque::from(batch);
+            while let Some(item) = batch.pop_front() {
+                if let Err(Closed(item)) = self.send(item).await {
+                    batch.push_front(item);
+                    return Err(Closed(batch.into()));
+                }
+            }
+            Ok(())
+        }
+
+        /// Sends every item produced by `stream` to the channel, waiting for
+        /// capacity whenever the channel is full.
+        ///
+        /// This behaves like [`send_all`], but takes items from an
+        /// asynchronous [`Stream`] rather than an iterator. The stream must be
+        /// [`Unpin`]; a stream which is not `Unpin` may be pinned with
+        /// [`Box::pin`] first.
+        ///
+        /// This method requires the "futures-core" feature flag.
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel is dropped before the stream
+        /// has ended, this returns a [`SendAllError`] containing the number of
+        /// items that were sent, the item that could not be sent, and the
+        /// stream.
+        ///
+        /// [`send_all`]: Self::send_all
+        /// [`Stream`]: futures_core::Stream
+        /// [`Box::pin`]: https://doc.rust-lang.org/std/boxed/struct.Box.html#method.pin
+        #[cfg(feature = "futures-core")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "futures-core")))]
+        pub async fn send_all_stream<S>(&self, mut stream: S) -> Result<usize, SendAllError<T, S>>
+        where
+            S: futures_core::Stream<Item = T> + Unpin,
+        {
+            let mut sent = 0;
+            while let Some(item) = NextItem(&mut stream).await {
+                if let Err(Closed(item)) = self.send(item).await {
+                    return Err(SendAllError { sent, item, rest: stream });
+                }
+                sent += 1;
+            }
+            Ok(sent)
+        }
+
+        /// Attempts to reserve a slot in the channel to mutate in place,
+        /// without waiting for capacity.
+        ///
+        /// This method differs from [`send_ref`] by returning immediately if the
+        /// channel’s buffer is full or no [`Receiver`] exists. Compared with
+        /// [`send_ref`], this method has two failure cases instead of one (one for
+        /// disconnection, one for a full buffer), and this method is not
+        /// `async`, because it will never wait.
+        ///
+        /// Like [`send_ref`], this method returns a [`SendRef`] that may be
+        /// used to mutate a slot in the channel's buffer in place. Dropping the
+        /// [`SendRef`] completes the send operation and makes the mutated value
+        /// available to the [`Receiver`].
+        ///
+        /// # Errors
+        ///
+        /// If the channel capacity has been reached (i.e., the channel has `n`
+        /// buffered values where `n` is the `usize` const generic parameter of
+        /// the [`StaticChannel`]), then [`TrySendError::Full`] is returned. In
+        /// this case, a future call to `try_send` may succeed if additional
+        /// capacity becomes available.
+        ///
+        /// If the receive half of the channel is closed (i.e., the
+        /// [`StaticReceiver`] handle was dropped), the function returns
+        /// [`TrySendError::Closed`]. Once the channel has closed, subsequent
+        /// calls to `try_send_ref` will never succeed.
+        ///
+        /// [`send_ref`]: Self::send_ref
+        pub fn try_send_ref(&self) -> Result<SendRef<'_, T>, TrySendError> {
+            self.core
+                .try_send_ref(self.slots, self.recycle)
+                .map(SendRef)
+        }
+
+        /// Attempts to send a message by value immediately, without waiting for
+        /// capacity.
+        ///
+        /// This method differs from [`send`] by returning immediately if the
+        /// channel’s buffer is full or no [`StaticReceiver`] exists. Compared with
+        /// [`send`], this method has two failure cases instead of one (one for
+        /// disconnection, one for a full buffer), and this method is not
+        /// `async`, because it will never wait.
+        ///
+        /// # Errors
+        ///
+        /// If the channel capacity has been reached (i.e., the channel has `n`
+        /// buffered values where `n` is the `usize` const generic parameter of
+        /// the [`StaticChannel`]), then [`TrySendError::Full`] is returned. In
+        /// this case, a future call to `try_send` may succeed if additional
+        /// capacity becomes available.
+        ///
+        /// If the receive half of the channel is closed (i.e., the
+        /// [`StaticReceiver`] handle was dropped), the function returns
+        /// [`TrySendError::Closed`]. Once the channel has closed, subsequent
+        /// calls to `try_send` will never succeed.
+        ///
+        /// If the message is dropped by the channel's load shedding (see
+        /// [`StaticReceiver::set_load_shedding`]), [`TrySendError::Shed`] is returned.
+        ///
+        /// In each case, the error includes the value passed to `try_send`.
+        ///
+        /// [`send`]: Self::send
+        pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
+            self.core.try_send(self.slots, val, self.recycle)
+        }
+
+        /// Sends a message by value if the channel has capacity, or, if it is
+        /// full, merges the message into the most recently queued message by
+        /// calling `f`.
+        ///
+        /// `f` is called with a mutable reference to the queued message, and with
+        /// the new message. This gives producers of cumulative data, such as
+        /// counters or deltas, bounded memory use without losing information:
+        /// rather than waiting for the receiver, or dropping an update, the update
+        /// is folded into a message that the receiver has not yet received.
+        ///
+        /// The message that `f` modifies is the most recently sent message that is
+        /// not at the front of the queue, where the receiver may already be
+        /// receiving it. The receiver cannot receive that message until `f`
+        /// returns. This method never waits for capacity, and messages
+        /// sent with it are never dropped by [load shedding].
+        ///
+        /// # Errors
+        ///
+        /// If the channel is closed, this returns [`TrySendError::Closed`]. If the
+        /// channel is full, and every queued message is still being sent or is at
+        /// the front of the queue, so there is no message to merge into, this returns
+        /// [`TrySendError::Full`]. In both cases, the error includes the value
+        /// passed to `send_or_modify`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<u64, 2> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// for delta in 1..=10 {
+        ///     tx.send_or_modify(delta, |queued, delta| *queued += delta)
+        ///         .unwrap();
+        /// }
+        ///
+        /// // Once the channel filled up, the remaining deltas were added to the
+        /// // last queued message.
+        /// assert_eq!(rx.try_recv().unwrap(), 1);
+        /// assert_eq!(rx.try_recv().unwrap(), 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9 + 10);
+        /// ```
+        ///
+        /// [load shedding]: StaticReceiver::set_load_shedding
+        pub fn send_or_modify<F>(&self, val: T, f: F) -> Result<(), TrySendError<T>>
+        where
+            F: FnOnce(&mut T, T),
+        {
+            self.core.try_send_or_modify(self.slots, val, self.recycle, f)
+        }
+
+        /// Attempts to claim a slot in the channel immediately, without waiting
+        /// for capacity, and fills it in place by calling `f`.
+        ///
+        /// This is equivalent to calling [`try_send_ref`] and writing to the
+        /// returned [`SendRef`], and likewise allows the existing allocations
+        /// in the channel's slots to be reused. The message is sent once `f`
+        /// returns.
+        ///
+        /// # Errors
+        ///
+        /// If the channel is full, [`TrySendError::Full`] is returned, and if the
+        /// channel has closed, [`TrySendError::Closed`] is returned. In either
+        /// case, `f` is not called.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        /// use std::fmt::Write;
+        ///
+        /// static CHANNEL: StaticChannel<String, 4> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// tx.try_send_with(|msg: &mut String| write!(msg, "hello").unwrap())
+        ///     .unwrap();
+        /// assert_eq!(rx.try_recv().unwrap(), "hello");
+        /// ```
+        ///
+        /// [`try_send_ref`]: Self::try_send_ref
+        pub fn try_send_with<F>(&self, f: F) -> Result<(), TrySendError>
+        where
+            F: FnOnce(&mut T),
+        {
+            self.try_send_ref().map(|mut slot| f(&mut slot))
+        }
+
+        /// Attempts to send a message by value immediately, calling `f` with
+        /// the message instead if the channel is full, or the message is dropped
+        /// by the channel's load shedding.
+        ///
+        /// This makes it easy to handle overflow, for example by spilling
+        /// messages to disk, or by logging that they were dropped, rather than
+        /// waiting for capacity.
+        ///
+        /// # Errors
+        ///
+        /// If the channel has closed, the message is returned in a [`Closed`]
+        /// error, and `f` is not called.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 1> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// let mut dropped = Vec::new();
+        /// tx.try_send_or_else(1, |msg| dropped.push(msg)).unwrap();
+        /// tx.try_send_or_else(2, |msg| dropped.push(msg)).unwrap();
+        ///
+        /// assert_eq!(dropped, vec![2]);
+        /// # drop(rx);
+        /// ```
+        pub fn try_send_or_else<F>(&self, val: T, f: F) -> Result<(), Closed<T>>
+        where
+            F: FnOnce(T),
+        {
+            match self.try_send(val) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(val) | TrySendError::Shed(val)) => {
+                    f(val);
+                    Ok(())
+                }
+                Err(TrySendError::Closed(val)) => Err(Closed(val)),
+            }
+        }
+
+        /// Returns the *total* capacity of the channel for this [`StaticSender`].
+        /// This includes both occupied and unoccupied entries.
+        ///
+        /// To determine the channel's remaining *unoccupied* capacity, use
+        /// [`remaining`] instead.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (tx, _) = CHANNEL.split();
+        /// assert_eq!(tx.capacity(), 100);
+        /// ```
+        ///
+        /// Even after sending several messages, the capacity remains
+        /// the same:
+        /// ```
+        /// # use thingbuf::mpsc::StaticChannel;
+        ///
+        /// # static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (tx, rx) = CHANNEL.split();
+        /// *tx.try_send_ref().unwrap() = 1;
+        /// *tx.try_send_ref().unwrap() = 2;
+        /// *tx.try_send_ref().unwrap() = 3;
+        ///
+        /// assert_eq!(tx.capacity(), 100);
+        /// ```
+        ///
+        /// [`remaining`]: Self::remaining
+        #[inline]
+        #[must_use]
+        pub fn capacity(&self) -> usize {
+            self.core.core.capacity()
+        }
+
+        /// Returns a snapshot of the statistics recorded for this channel.
+        ///
+        /// These are the same statistics returned by the receiver's `stats`
+        /// method. See the [`stats`](crate::stats) module for details.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        /// tx.try_send(1).unwrap();
+        ///
+        /// assert_eq!(tx.stats().occupancy().samples(), 1);
+        /// ```
+        #[cfg(feature = "stats")]
+        #[must_use]
+        pub fn stats(&self) -> crate::stats::Stats {
+            self.core.core.stats()
+        }
+
+        /// Returns the unoccupied capacity of the channel for this [`StaticSender`]
+        /// (i.e., how many additional elements can be sent before the channel
+        /// will be full).
+        ///
+        /// This is equivalent to subtracting the channel's [`len`] from its [`capacity`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (tx, rx) = CHANNEL.split();
+        /// assert_eq!(tx.remaining(), 100);
+        ///
+        /// *tx.try_send_ref().unwrap() = 1;
+        /// *tx.try_send_ref().unwrap() = 2;
+        /// *tx.try_send_ref().unwrap() = 3;
+        /// assert_eq!(tx.remaining(), 97);
+        ///
+        /// let _ = rx.try_recv_ref().unwrap();
+        /// assert_eq!(tx.remaining(), 98)
+        /// ```
+        ///
+        /// [`len`]: Self::len
+        /// [`capacity`]: Self::capacity
+        #[must_use]
+        pub fn remaining(&self) -> usize {
+            self.capacity() - self.len()
+        }
+
+        /// Returns the number of elements in the channel of this [`StaticSender`].
+        ///
+        /// To determine the channel's remaining *unoccupied* capacity, use
+        /// [`remaining`] instead.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (tx, rx) = CHANNEL.split();
+        /// assert_eq!(tx.len(), 0);
+        ///
+        /// *tx.try_send_ref().unwrap() = 1;
+        /// *tx.try_send_ref().unwrap() = 2;
+        /// *tx.try_send_ref().unwrap() = 3;
+        /// assert_eq!(tx.len(), 3);
+        ///
+        /// let _ = rx.try_recv_ref().unwrap();
+        /// assert_eq!(tx.len(), 2);
+        /// ```
+        ///
+        /// [`remaining`]: Self::remaining
+        #[inline]
+        #[must_use]
+        pub fn len(&self) -> usize {
+            self.core.core.len()
+        }
+
+        /// Returns whether the number of elements in the channel of this [`StaticSender`] is 0.
+        ///
+        /// # Examples
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (tx, rx) = CHANNEL.split();
+        /// assert!(tx.is_empty());
+        ///
+        /// *tx.try_send_ref().unwrap() = 1;
+        ///
+        /// assert!(!tx.is_empty());
+        /// ```
+        ///
+        #[inline]
+        #[must_use]
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Returns the number of [`StaticSender`]s (including this one) that are
+        /// currently attached to the channel.
+        ///
+        /// This may be used by supervisors to report how many producers are still
+        /// attached to a channel. Note that the count may change as soon as it has
+        /// been read, if other senders are cloned or dropped concurrently.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (tx, _rx) = CHANNEL.split();
+        /// assert_eq!(tx.sender_count(), 1);
+        ///
+        /// let tx2 = tx.clone();
+        /// assert_eq!(tx.sender_count(), 2);
+        /// ```
+        #[inline]
+        #[must_use]
+        pub fn sender_count(&self) -> usize {
+            test_dbg!(self.core.tx_count.load(Ordering::SeqCst))
+        }
+
+        /// Returns the sequence number that will be assigned to the next
+        /// message sent on this channel.
+        ///
+        /// This is the number of messages that have been sent so far. Each
+        /// message is assigned the next sequence number in the order in which
+        /// it is placed in the channel, which can be retrieved from the
+        /// [`StaticReceiver`] with [`RecvRef::seq`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (tx, _rx) = CHANNEL.split();
+        /// assert_eq!(tx.published_seq(), 0);
+        ///
+        /// tx.try_send(1).unwrap();
+        /// tx.try_send(1).unwrap();
+        /// assert_eq!(tx.published_seq(), 2);
+        /// ```
+        #[cfg(feature = "seq")]
+        #[inline]
+        #[must_use]
+        pub fn published_seq(&self) -> usize {
+            self.core.core.published_seq()
+        }
+
+        /// Waits until every message sent on this channel before `flush` was
+        /// called has been received.
+        ///
+        /// See [`Sender::flush`] for details.
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel is dropped before
+        /// every earlier message has been received, this returns a [`Closed`]
+        /// error.
+        #[cfg(feature = "seq")]
+        pub async fn flush(&self) -> Result<(), Closed> {
+            FlushFuture::new(self.core).await
+        }
+
+        /// Returns `true` if the [`StaticReceiver`] for this channel has not been
+        /// dropped.
+        ///
+        /// If this returns `false`, all future sends on this channel will fail.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        /// assert!(tx.receiver_alive());
+        ///
+        /// drop(rx);
+        /// assert!(!tx.receiver_alive());
+        /// ```
+        #[inline]
+        #[must_use]
+        pub fn receiver_alive(&self) -> bool {
+            !self.core.core.is_closed()
+        }
+    }
+
+    impl<T> Clone for StaticSender<T> {
+        fn clone(&self) -> Self {
+            test_dbg!(self.core.tx_count.fetch_add(1, Ordering::Relaxed));
+            Self {
+                core: self.core,
+                slots: self.slots,
+                recycle: self.recycle,
+            }
+        }
+    }
+
+    impl<T, R> Drop for StaticSender<T, R> {
+        fn drop(&mut self) {
+            if test_dbg!(self.core.tx_count.fetch_sub(1, Ordering::Release)) > 1 {
+                return;
+            }
+
+            // if we are the last sender, synchronize
+            test_dbg!(atomic::fence(Ordering::SeqCst));
+            if self.core.core.close() {
+                self.core.notify_groups();
+            }
+            self.core.rx_wait.close_tx();
+        }
+    }
+
+    impl<T, R: fmt::Debug> fmt::Debug for StaticSender<T, R> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("StaticSender")
+                .field("core", &self.core)
+                .field("slots", &format_args!("&[..]"))
+                .field("recycle", self.recycle)
+                .finish()
+        }
+    }
+
+    // === impl StaticReceiver ===
+
+    #[cfg(feature = "rt-safe")]
+    impl<T, R> StaticReceiver<T, R> {
+        /// Returns the channel's state, for the [`rt`](super::rt) handles.
+        pub(super) fn parts(&self) -> (&ChannelCore<Waker>, &[Slot<T>], &R) {
+            (self.core, self.slots, self.recycle)
+        }
+    }
+
+    impl<T, R> StaticReceiver<T, R> {
+        /// Receives the next message for this receiver, **by reference**.
+        ///
+        /// This method returns `None` if the channel has been closed and there are
+        /// no remaining messages in the channel's buffer. This indicates that no
+        /// further values can ever be received from this `StaticReceiver`. The
+        /// channel is closed when all [`StaticSender`]s have been dropped.
+        ///
+        /// If there are no messages in the channel's buffer, but the channel has
+        /// not yet been closed, this method will wait until a message is sent or
+        /// the channel is closed.
+        ///
+        /// This method returns a [`RecvRef`] that can be used to read from (or
+        /// mutate) the received message by reference. When the [`RecvRef`] is
+        /// dropped, the receive operation completes and the slot occupied by
+        /// the received message becomes usable for a future [`send_ref`] operation.
+        ///
+        /// If all [`StaticSender`]s for this channel write to the channel's slots in
+        /// place by using the [`send_ref`] or [`try_send_ref`] methods, this
+        /// method allows messages that own heap allocations to be reused in
+        /// place.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        /// use std::fmt::Write;
+        ///
+        /// static CHANNEL: StaticChannel<String, 100> = StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     tokio::spawn(async move {
+        ///         let mut value = tx.send_ref().await.unwrap();
+        ///         write!(value, "hello world!")
+        ///             .expect("writing to a `String` should never fail");
+        ///     });
+        ///
+        ///     assert_eq!(Some("hello world!"), rx.recv_ref().await.as_deref().map(String::as_str));
+        ///     assert_eq!(None, rx.recv().await.as_deref());
+        /// }
+        /// ```
+        ///
+        /// Values are buffered:
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        /// use std::fmt::Write;
+        ///
+        /// static CHANNEL: StaticChannel<String, 100> = StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     write!(tx.send_ref().await.unwrap(), "hello").unwrap();
+        ///     write!(tx.send_ref().await.unwrap(), "world").unwrap();
+        ///
+        ///     assert_eq!("hello", rx.recv_ref().await.unwrap().as_str());
+        ///     assert_eq!("world", rx.recv_ref().await.unwrap().as_str());
+        /// }
+        /// ```
+        ///
+        /// [`send_ref`]: StaticSender::send_ref
+        /// [`try_send_ref`]: StaticSender::try_send_ref
+        pub fn recv_ref(&self) -> RecvRefFuture<'_, T> {
+            RecvRefFuture {
+                core: self.core,
+                slots: self.slots,
+            }
+        }
+
+        /// Receives the next message for this receiver, **by value**.
+        ///
+        /// This method returns `None` if the channel has been closed and there are
+        /// no remaining messages in the channel's buffer. This indicates that no
+        /// further values can ever be received from this `StaticReceiver`. The
+        /// channel is closed when all [`StaticSender`]s have been dropped.
+        ///
+        /// If there are no messages in the channel's buffer, but the channel has
+        /// not yet been closed, this method will wait until a message is sent or
+        /// the channel is closed.
+        ///
+        /// When a message is received, it is moved out of the channel by value,
+        /// and replaced with a new slot according to the configured [recycling
+        /// policy]. If all [`StaticSender`]s for this channel write to the channel's
+        /// slots in place by using the [`send_ref`] or [`try_send_ref`] methods,
+        /// consider using the [`recv_ref`] method instead, to enable the
+        /// reuse of heap allocations.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 100> = StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     tokio::spawn(async move {
+        ///         tx.send(1).await.unwrap();
+        ///     });
+        ///
+        ///     assert_eq!(Some(1), rx.recv().await);
+        ///     assert_eq!(None, rx.recv().await);
+        /// }
+        /// ```
+        ///
+        /// Values are buffered:
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 100> = StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     tx.send(1).await.unwrap();
+        ///     tx.send(2).await.unwrap();
+        ///
+        ///     assert_eq!(Some(1), rx.recv().await);
+        ///     assert_eq!(Some(2), rx.recv().await);
+        /// }
+        /// ```
+        ///
+        /// [`send_ref`]: StaticSender::send_ref
+        /// [`try_send_ref`]: StaticSender::try_send_ref
+        /// [recycling policy]: crate::recycling::Recycle
+        /// [`recv_ref`]: Self::recv_ref
+        pub fn recv(&self) -> RecvFuture<'_, T, R>
+        where
+            R: Recycle<T>,
+        {
+            RecvFuture {
+                core: self.core,
+                slots: self.slots,
+                recycle: self.recycle,
+            }
+        }
+
+        /// Receives messages onto the end of `batch` until it contains exactly
+        /// `n` messages, waiting until all of them have been sent.
+        ///
+        /// The returned future completes once `batch` holds `n` messages, or
+        /// once the channel has closed and every remaining message has been
+        /// received. In the latter case, `batch` is left with fewer than `n`
+        /// messages (and may be empty). Returns the number of messages received
+        /// by this call.
+        ///
+        /// This is useful for consumers that process messages in fixed-size
+        /// batches, such as block writers.
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is cancel safe. Every message is pushed onto `batch` as
+        /// soon as it is received, so if the returned future is dropped before
+        /// it completes, no messages are lost, and calling `recv_exact` again
+        /// with the same `batch` continues filling it.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 8> = StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     tokio::spawn(async move {
+        ///         for i in 0..5 {
+        ///             tx.send(i).await.unwrap();
+        ///         }
+        ///     });
+        ///
+        ///     let mut batch = Vec::new();
+        ///     assert_eq!(rx.recv_exact(&mut batch, 2).await, 2);
+        ///     assert_eq!(batch, vec![0, 1]);
+        ///
+        ///     batch.clear();
+        ///     assert_eq!(rx.recv_exact(&mut batch, 2).await, 2);
+        ///     assert_eq!(batch, vec![2, 3]);
+        ///
+        ///     // The channel closes, so only a partial batch is received.
+        ///     batch.clear();
+        ///     assert_eq!(rx.recv_exact(&mut batch, 2).await, 1);
+        ///     assert_eq!(batch, vec![4]);
+        /// }
+        /// ```
+        #[cfg(feature = "alloc")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+        pub async fn recv_exact(&self, batch: &mut alloc::vec::Vec<T>, n: usize) -> usize
+        where
+            R: Recycle<T>,
+        {
+            let start = batch.len();
+            batch.reserve(n.saturating_sub(start));
+            while batch.len() < n {
+                #[cfg(feature = "prefetch")]
+                self.core.prefetch(self.slots, n - batch.len());
+                match self.recv().await {
+                    Some(msg) => batch.push(msg),
+                    None => break,
+                }
+            }
+            batch.len() - start
+        }
+
+        /// Receives every message sent on the channel, **by reference**, passing
+        /// each one to `f`, until the channel closes.
+        ///
+        /// The returned future waits for each message in turn, and completes once
+        /// all [`StaticSender`]s have been dropped and every message has been
+        /// received. This replaces the usual `while let Some(msg) =
+        /// rx.recv_ref().await { ... }` consumer loop. Each message's slot is
+        /// released as soon as `f` returns, or drops the [`RecvRef`].
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is cancel safe. If the returned future is dropped, every
+        /// message that was passed to `f` has been received, and no other messages
+        /// are lost.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 8> = StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     tokio::spawn(async move {
+        ///         for i in 1..=4 {
+        ///             tx.send(i).await.unwrap();
+        ///         }
+        ///     });
+        ///
+        ///     let mut sum = 0;
+        ///     // Completes once the sender has been dropped.
+        ///     rx.recv_for_each(|msg| sum += *msg).await;
+        ///     assert_eq!(sum, 10);
+        /// }
+        /// ```
+        pub async fn recv_for_each<F>(&self, mut f: F)
+        where
+            F: FnMut(RecvRef<'_, T>),
+        {
+            while let Some(msg) = self.recv_ref().await {
+                f(msg);
+            }
+        }
+
+        /// Waits for at least one message, and then receives every message
+        /// that is ready, up to `n` messages, as a single chunk.
+        ///
+        /// Unlike [`recv_exact`], this does not wait for the chunk to fill
+        /// up: once a message has been received, the messages already in the
+        /// channel are received without waiting. This lets consumers that
+        /// process messages in batches anyway handle a burst of messages with
+        /// a single task wakeup. Returns `None` if the channel has closed and
+        /// all messages have been received.
+        ///
+        /// To receive chunks from a `poll`-based API, such as a `Stream`
+        /// implementation, use [`poll_recv_chunk`].
+        ///
+        /// # Cancel safety
+        ///
+        /// This method is cancel safe. Messages are only received once the
+        /// first message is ready, after which the chunk is completed without
+        /// waiting.
+        ///
+        /// # Panics
+        ///
+        /// If `n` is 0.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 8> = StaticChannel::new();
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = CHANNEL.split();
+        ///
+        ///     for i in 0..5 {
+        ///         tx.send(i).await.unwrap();
+        ///     }
+        ///     drop(tx);
+        ///
+        ///     assert_eq!(rx.recv_chunk(3).await, Some(vec![0, 1, 2]));
+        ///     // Only two messages are ready, so a partial chunk is returned.
+        ///     assert_eq!(rx.recv_chunk(3).await, Some(vec![3, 4]));
+        ///     assert_eq!(rx.recv_chunk(3).await, None);
+        /// }
+        /// ```
+        ///
+        /// [`recv_exact`]: Self::recv_exact
+        /// [`poll_recv_chunk`]: Self::poll_recv_chunk
+        #[cfg(feature = "alloc")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+        pub async fn recv_chunk(&self, n: usize) -> Option<alloc::vec::Vec<T>>
+        where
+            R: Recycle<T>,
+        {
+            assert!(n > 0, "a chunk must contain at least one message");
+            let first = self.recv().await?;
+            Some(self.fill_chunk(first, n))
+        }
+
+        /// Attempts to receive a chunk of up to `n` ready messages,
+        /// registering the current task for wakeup if no message is
+        /// available yet, and returning `None` if the channel has closed and
+        /// all messages have been received.
+        ///
+        /// This is the `poll`-based version of [`recv_chunk`]. Like
+        /// [`poll_recv`], only the [`Waker`] from the [`Context`] passed to the
+        /// most recent call is scheduled to receive a wakeup.
+        ///
+        /// # Panics
+        ///
+        /// If `n` is 0.
+        ///
+        /// [`recv_chunk`]: Self::recv_chunk
+        /// [`poll_recv`]: Self::poll_recv
+        #[cfg(feature = "alloc")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+        pub fn poll_recv_chunk(
+            &self,
+            cx: &mut Context<'_>,
+            n: usize,
+        ) -> Poll<Option<alloc::vec::Vec<T>>>
+        where
+            R: Recycle<T>,
+        {
+            assert!(n > 0, "a chunk must contain at least one message");
+            self.poll_recv(cx).map(|first| Some(self.fill_chunk(first?, n)))
+        }
+
+        #[cfg(feature = "alloc")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+        fn fill_chunk(&self, first: T, n: usize) -> alloc::vec::Vec<T>
+        where
+            R: Recycle<T>,
+        {
+            let mut chunk = alloc::vec::Vec::with_capacity(core::cmp::min(n, self.capacity()));
+            chunk.push(first);
+            if n > 1 {
+                // The rest of the chunk is claimed in one batch, so that it is
+                // prefetched when the "prefetch" feature is enabled.
+                let _ =
+                    self.core
+                        .try_recv_chunk(self.slots.as_ref(), self.recycle, &mut chunk, n - 1);
+            }
+            chunk
+        }
+
+        /// Attempts to receive the next message for this receiver by reference
+        /// without waiting for a new message when the channel is empty.
+        ///
+        /// This method differs from [`recv_ref`] by returning immediately if the
+        /// channel is empty or closed.
+        ///
+        /// # Errors
+        ///
+        /// This method returns an error when the channel is closed or there are
+        /// no remaining messages in the channel's buffer.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::{channel, errors::TryRecvError};
+        ///
+        /// let (tx, rx) = channel(100);
+        /// assert!(matches!(rx.try_recv_ref(), Err(TryRecvError::Empty)));
+        ///
+        /// tx.try_send(1).unwrap();
+        /// drop(tx);
+        ///
+        /// assert_eq!(*rx.try_recv_ref().unwrap(), 1);
+        /// assert!(matches!(rx.try_recv_ref(), Err(TryRecvError::Closed)));
+        /// ```
+        ///
+        /// [`recv_ref`]: Self::recv_ref
+        pub fn try_recv_ref(&self) -> Result<RecvRef<'_, T>, TryRecvError>
+        where
+            R: Recycle<T>,
+        {
+            self.core.try_recv_ref(self.slots.as_ref()).map(RecvRef)
+        }
+
+        /// Attempts to receive the next message for this receiver by reference
+        /// without waiting for a new message when the channel is empty.
+        ///
+        /// This method differs from [`recv`] by returning immediately if the
+        /// channel is empty or closed.
+        ///
+        /// # Errors
+        ///
+        /// This method returns an error when the channel is closed or there are
+        /// no remaining messages in the channel's buffer.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::{channel, errors::TryRecvError};
+        ///
+        /// let (tx, rx) = channel(100);
+        /// assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        ///
+        /// tx.try_send(1).unwrap();
+        /// drop(tx);
+        ///
+        /// assert_eq!(rx.try_recv().unwrap(), 1);
+        /// assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+        /// ```
+        ///
+        /// [`recv`]: Self::recv
+        pub fn try_recv(&self) -> Result<T, TryRecvError>
+        where
+            R: Recycle<T>,
+        {
+            self.core.try_recv(self.slots.as_ref(), self.recycle)
+        }
+
+        /// Attempts to receive the next message, but only if it matches the
+        /// predicate `f`, without waiting for a new message when the channel
+        /// is empty.
+        ///
+        /// If the next message does not match, it is left in the channel, and
+        /// `Ok(None)` is returned. This is useful for consumers that must leave
+        /// certain messages to be handled by another code path.
+        ///
+        /// Because this method takes `&mut self`, no other message can be
+        /// received while the predicate is evaluated.
+        ///
+        /// # Errors
+        ///
+        /// This method returns an error when the channel is closed, or when there
+        /// are no remaining messages in the channel's buffer.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::{StaticChannel, errors::TryRecvError};
+        ///
+        /// static CHANNEL: StaticChannel<i32, 100> = StaticChannel::new();
+        /// let (tx, mut rx) = CHANNEL.split();
+        /// tx.try_send(1).unwrap();
+        ///
+        /// assert_eq!(rx.try_recv_if(|&msg| msg > 1), Ok(None));
+        /// assert_eq!(rx.try_recv_if(|&msg| msg == 1), Ok(Some(1)));
+        /// assert_eq!(rx.try_recv_if(|_| true), Err(TryRecvError::Empty));
+        /// ```
+        pub fn try_recv_if<F>(&mut self, f: F) -> Result<Option<T>, TryRecvError>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            // Safety: this method takes `&mut self`, so no messages can be
+            // received concurrently.
+            unsafe { self.core.try_recv_if(self.slots.as_ref(), self.recycle, f) }
+        }
+
+        /// Attempts to receive a message *by reference* from this channel,
+        /// registering the current task for wakeup if the a message is not yet
+        /// available, and returning `None` if the channel has closed and all
+        /// messages have been received.
+        ///
+        /// Like [`StaticReceiver::recv_ref`], this method returns a [`RecvRef`]
+        /// that can be used to read from (or mutate) the received message by
+        /// reference. When the [`RecvRef`] is dropped, the receive operation
+        /// completes and the slot occupied by the received message becomes
+        /// usable for a future [`send_ref`] operation.
+        ///
+        /// If all [`StaticSender`]s for this channel write to the channel's slots in
+        /// place by using the [`send_ref`] or [`try_send_ref`] methods, this
+        /// method allows messages that own heap allocations to be reused in
+        /// place.
+        ///
+        /// To wait asynchronously until a message becomes available, use the
+        /// [`recv_ref`] method instead.
+        ///
+        /// # Returns
+        ///
+        ///  * `Poll::Pending` if no messages are available but the channel is not
+        ///    closed, or if a spurious failure happens.
+        ///  * `Poll::Ready(Some(RecvRef<T>))` if a message is available.
+        ///  * `Poll::Ready(None)` if the channel has been closed (i.e., all
+        ///    [`StaticSender`]s have been dropped), and all messages sent before it
+        ///    was closed have been received.
+        ///
+        /// When the method returns [`Poll::Pending`], the [`Waker`] in the provided
+        /// [`Context`] is scheduled to receive a wakeup when a message is sent on any
+        /// sender, or when the channel is closed.  Note that on multiple calls to
+        /// `poll_recv_ref`, only the [`Waker`] from the [`Context`] passed to the most
+        /// recent call is scheduled to receive a wakeup.
+        ///
+        /// [`send_ref`]: StaticSender::send_ref
+        /// [`try_send_ref`]: StaticSender::try_send_ref
+        /// [`recv_ref`]: Self::recv_ref
+        pub fn poll_recv_ref(&self, cx: &mut Context<'_>) -> Poll<Option<RecvRef<'_, T>>> {
+            poll_recv_ref(self.core, self.slots, cx)
+        }
+
+        /// Attempts to receive a message *by value* from this channel,
+        /// registering the current task for wakeup if the value is not yet
+        /// available, and returning `None` if the channel has closed and all
+        /// messages have been received.
+        ///
+        /// When a message is received, it is moved out of the channel by value,
+        /// and replaced with a new slot according to the configured [recycling
+        /// policy]. If all [`StaticSender`]s for this channel write to the channel's
+        /// slots in place by using the [`send_ref`] or [`try_send_ref`] methods,
+        /// consider using the [`poll_recv_ref`] method instead, to enable the
+        /// reuse of heap allocations.
+        ///
+        /// To wait asynchronously until a message becomes available, use the
+        /// [`recv`] method instead.
+        ///
+        /// # Returns
+        ///
+        ///  * `Poll::Pending` if no messages are available but the channel is not
+        ///    closed, or if a spurious failure happens.
+        ///  * `Poll::Ready(Some(message))` if a message is available.
+        ///  * `Poll::Ready(None)` if the channel has been closed (i.e., all
+        ///    [`StaticSender`]s have been dropped) and all messages sent before it
+        ///    was closed have been received.
+        ///
+        /// When the method returns [`Poll::Pending`], the [`Waker`] in the provided
+        /// [`Context`] is scheduled to receive a wakeup when a message is sent on any
+        /// sender, or when the channel is closed.  Note that on multiple calls to
+        /// `poll_recv`, only the [`Waker`] from the [`Context`] passed to the most
+        /// recent call is scheduled to receive a wakeup.
+        ///
+        /// [`send_ref`]: StaticSender::send_ref
+        /// [`try_send_ref`]: StaticSender::try_send_ref
+        /// [recycling policy]: crate::recycling::Recycle
+        /// [`poll_recv_ref`]: Self::poll_recv_ref
+        /// [`recv`]: Self::recv
+        pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>>
+        where
+            R: Recycle<T>,
+        {
+            self.poll_recv_ref(cx)
+                .map(|opt| opt.map(|mut r| recycling::take(&mut *r, self.recycle)))
+        }
+
+        /// Waits for the next message, and returns a reference to it *without*
+        /// receiving it.
+        ///
+        /// The message stays at the front of the channel, so it is returned
+        /// again by the next call to `peek`, [`recv`], or [`recv_ref`]. This
+        /// allows a consumer to wait for a message, inspect it, and only then
+        /// decide whether to take it. The returned future completes with `None`
+        /// if the channel has closed and all messages have been received.
+        ///
+        /// Because this method takes `&mut self`, no message can be received
+        /// while the returned reference exists.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     static CHANNEL: mpsc::StaticChannel<i32, 8> = mpsc::StaticChannel::new();
+        ///     let (tx, mut rx) = CHANNEL.split();
+        ///
+        ///     tokio::spawn(async move {
+        ///         tx.send(1).await.unwrap();
+        ///         tx.send(2).await.unwrap();
+        ///     });
+        ///
+        ///     assert_eq!(rx.peek().await, Some(&1));
+        ///     // Peeking does not consume the message.
+        ///     assert_eq!(rx.peek().await, Some(&1));
+        ///     assert_eq!(rx.recv().await, Some(1));
+        ///
+        ///     assert_eq!(rx.peek().await, Some(&2));
+        ///     assert_eq!(rx.recv().await, Some(2));
+        ///     assert_eq!(rx.peek().await, None);
+        /// }
+        /// ```
+        ///
+        /// [`recv`]: Self::recv
+        /// [`recv_ref`]: Self::recv_ref
+        pub fn peek(&mut self) -> PeekFuture<'_, T> {
+            PeekFuture {
+                core: self.core,
+                slots: self.slots,
+            }
+        }
+
+        /// Waits for the next message, and receives it only if it matches the
+        /// predicate `f`.
+        ///
+        /// If the next message does not match, it is left at the front of the
+        /// channel, and `Ok(None)` is returned, so that it can be handled by
+        /// another code path (such as [`recv`]). The returned future completes
+        /// with a [`Closed`] error if the channel has closed and all messages
+        /// have been received.
+        ///
+        /// Because this method takes `&mut self`, no other message can be
+        /// received while the predicate is evaluated.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc;
+        ///
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     static CHANNEL: mpsc::StaticChannel<i32, 8> = mpsc::StaticChannel::new();
+        ///     let (tx, mut rx) = CHANNEL.split();
+        ///
+        ///     tokio::spawn(async move {
+        ///         tx.send(1).await.unwrap();
+        ///         tx.send(2).await.unwrap();
+        ///     });
+        ///
+        ///     // The first message is rejected, and stays in the channel.
+        ///     assert_eq!(rx.recv_if(|&msg| msg == 2).await, Ok(None));
+        ///     assert_eq!(rx.recv().await, Some(1));
+        ///
+        ///     assert_eq!(rx.recv_if(|&msg| msg == 2).await, Ok(Some(2)));
+        ///     assert!(rx.recv_if(|_| true).await.is_err());
+        /// }
+        /// ```
+        ///
+        /// [`recv`]: Self::recv
+        pub async fn recv_if<F>(&mut self, f: F) -> Result<Option<T>, Closed>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            if self.peek().await.is_none() {
+                return Err(Closed(()));
+            }
+            Ok(self.recv_peeked_if(f))
+        }
+
+        /// Attempts to return a reference to the next message *without*
+        /// receiving it, registering the current task for wakeup if a message
+        /// is not yet available.
+        ///
+        /// To wait asynchronously until a message becomes available, use the
+        /// [`peek`] method instead.
+        ///
+        /// # Returns
+        ///
+        ///  * `Poll::Pending` if no messages are available but the channel is not
+        ///    closed, or if a spurious failure happens.
+        ///  * `Poll::Ready(Some(&T))` if a message is available. The message is
+        ///    not removed from the channel.
+        ///  * `Poll::Ready(None)` if the channel has been closed (i.e., all
+        ///    [`StaticSender`]s have been dropped) and all messages sent before it
+        ///    was closed have been received.
+        ///
+        /// When the method returns [`Poll::Pending`], the [`Waker`] in the provided
+        /// [`Context`] is scheduled to receive a wakeup when a message is sent on any
+        /// sender, or when the channel is closed.
+        ///
+        /// [`peek`]: Self::peek
+        pub fn poll_peek(&mut self, cx: &mut Context<'_>) -> Poll<Option<&T>> {
+            poll_peek(self.core, self.slots, cx)
+        }
+
+        /// Attempts to receive the next message, but only if it matches the
+        /// predicate `f`, registering the current task for wakeup if a message
+        /// is not yet available.
+        ///
+        /// This is the `poll`-based version of [`recv_if`].
+        ///
+        /// # Returns
+        ///
+        ///  * `Poll::Pending` if no messages are available but the channel is not
+        ///    closed, or if a spurious failure happens.
+        ///  * `Poll::Ready(Ok(Some(message)))` if the next message matches the
+        ///    predicate.
+        ///  * `Poll::Ready(Ok(None))` if the next message does not match the
+        ///    predicate. The message is left at the front of the channel.
+        ///  * `Poll::Ready(Err(Closed))` if the channel has been closed (i.e., all
+        ///    [`StaticSender`]s have been dropped) and all messages sent before it
+        ///    was closed have been received.
+        ///
+        /// [`recv_if`]: Self::recv_if
+        pub fn poll_recv_if<F>(
+            &mut self,
+            cx: &mut Context<'_>,
+            f: F,
+        ) -> Poll<Result<Option<T>, Closed>>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            match poll_peek(self.core, self.slots, cx) {
+                Poll::Ready(Some(_)) => Poll::Ready(Ok(self.recv_peeked_if(f))),
+                Poll::Ready(None) => Poll::Ready(Err(Closed(()))),
+                Poll::Pending => Poll::Pending,
+            }
+        }
 
-    /// Asynchronously receives values from associated [`StaticSender`]s.
-    ///
-    /// Instances of this struct are created by the [`StaticChannel::split`] and
-    /// [``StaticChannel::try_split`] functions.
-    pub struct StaticReceiver<T: 'static, R: 'static = recycling::DefaultRecycle> {
-        core: &'static ChannelCore<Waker>,
-        recycle: &'static R,
-        slots: &'static [Slot<T>],
-    }
+        fn recv_peeked_if<F>(&mut self, f: F) -> Option<T>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            self.try_recv_if(f)
+                .expect("a peeked message stays at the front of the channel")
+        }
 
-    // === impl StaticChannel ===
+        /// Returns `true` if the channel has closed (all corresponding
+        /// [`StaticSender`]s have been dropped, or the channel's group has shut it
+        /// down).
+        ///
+        /// If this method returns `true`, no new messages will become available
+        /// on this channel. Previously sent messages may still be available.
+        pub fn is_closed(&self) -> bool {
+            test_dbg!(self.core.tx_count.load(Ordering::SeqCst)) == 0
+                || self.core.core.is_closed()
+        }
 
-    impl<T, const CAPACITY: usize> StaticChannel<T, CAPACITY> {
-        /// Constructs a new statically-allocated, asynchronous bounded MPSC channel.
+        /// Returns a new [`StaticSender`] for this channel, or `None` if the channel has
+        /// already closed.
         ///
-        /// A statically-allocated channel allows using a MPSC channel without
-        /// requiring _any_ heap allocations, and can be used in environments that
-        /// don't support `liballoc`.
+        /// This allows producers to attach to an existing channel without
+        /// threading a [`StaticSender`] through to them from where the channel was
+        /// created. Any [`StaticSender`] may also be cloned to create additional senders.
         ///
-        /// In order to use a statically-allocated channel, a `StaticChannel` must
-        /// be constructed in a `static` initializer. This reserves storage for the
-        /// channel's message queue at compile-time. Then, at runtime, the channel
-        /// is [`split`] into a [`StaticSender`]/[`StaticReceiver`] pair in order to
-        /// be used.
+        /// Once every [`StaticSender`] for a channel has been dropped, the channel is
+        /// closed permanently, and this method will always return `None`.
         ///
         /// # Examples
         ///
         /// ```
         /// use thingbuf::mpsc::StaticChannel;
         ///
-        /// // Construct a statically-allocated channel of `usize`s with a capacity
-        /// // of 16 messages.
-        /// static MY_CHANNEL: StaticChannel<usize, 16> = StaticChannel::new();
+        /// static CHANNEL: StaticChannel<usize, 4> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
         ///
-        /// fn main() {
-        ///     // Split the `StaticChannel` into a sender-receiver pair.
-        ///     let (tx, rx) = MY_CHANNEL.split();
+        /// // Create a new sender from the receiver.
+        /// let tx2 = rx.sender().expect("channel is still open");
+        /// tx2.try_send(1).unwrap();
+        /// assert_eq!(rx.try_recv(), Ok(1));
         ///
-        ///     // Now, `tx` and `rx` can be used just like any other async MPSC
-        ///     // channel...
-        /// # drop(tx); drop(rx);
-        /// }
+        /// // Once all senders have been dropped, no new ones can be created.
+        /// drop((tx, tx2));
+        /// assert!(rx.sender().is_none());
         /// ```
-        /// [`split`]: StaticChannel::split
-        #[must_use]
-        pub const fn new() -> Self {
-            Self {
-                core: ChannelCore::new(CAPACITY),
-                slots: Slot::make_static_array::<CAPACITY>(),
-                is_split: AtomicBool::new(false),
-                recycle: recycling::DefaultRecycle::new(),
+        pub fn sender(&self) -> Option<StaticSender<T, R>> {
+            if !self.core.add_tx() {
+                return None;
             }
+
+            Some(StaticSender {
+            core: self.core,
+            recycle: self.recycle,
+            slots: self.slots,
+        })
         }
-    }
 
-    impl<T, R, const CAPACITY: usize> StaticChannel<T, CAPACITY, R> {
-        /// Split a [`StaticChannel`] into a [`StaticSender`]/[`StaticReceiver`]
-        /// pair.
+        /// Sets the number of times senders and this receiver will retry an
+        /// operation on a full (or empty) channel before waiting.
         ///
-        /// A static channel can only be split a single time. If
-        /// [`StaticChannel::split`] or [`StaticChannel::try_split`] have been
-        /// called previously, this method will panic. For a non-panicking version
-        /// of this method, see [`StaticChannel::try_split`].
+        /// By default, the spin budget is 0: when the channel is full, a
+        /// [`StaticSender`] immediately registers itself in the channel's wait queue,
+        /// and when the channel is empty, the receiver immediately waits to be woken.
+        /// Raising the budget makes them retry (with a short backoff between
+        /// attempts) first, which can reduce latency when capacity or messages are
+        /// expected to become available very soon, at the cost of burning CPU time.
+        /// Oversubscribed systems should generally leave the budget at 0.
         ///
-        /// # Panics
+        /// The spin budget applies to the whole channel, and may be changed at any
+        /// time.
         ///
-        /// If the channel has already been split.
-        #[must_use]
-        pub fn split(&'static self) -> (StaticSender<T, R>, StaticReceiver<T, R>) {
-            self.try_split().expect("channel already split")
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (_tx, rx) = CHANNEL.split();
+        ///
+        /// // Low-latency consumers may want to spin for a while before waiting.
+        /// rx.set_spin_budget(64);
+        /// assert_eq!(rx.spin_budget(), 64);
+        /// ```
+        #[inline]
+        pub fn set_spin_budget(&self, spins: usize) {
+            self.core.spin_budget.store(spins, Ordering::Relaxed);
         }
 
-        /// Try to split a [`StaticChannel`] into a [`StaticSender`]/[`StaticReceiver`]
-        /// pair, returning `None` if it has already been split.
+        /// Returns the channel's current spin budget.
         ///
-        /// A static channel can only be split a single time. If
-        /// [`StaticChannel::split`] or [`StaticChannel::try_split`] have been
-        /// called previously, this method returns `None`.
+        /// See [`set_spin_budget`](Self::set_spin_budget) for details.
+        #[inline]
         #[must_use]
-        pub fn try_split(&'static self) -> Option<(StaticSender<T, R>, StaticReceiver<T, R>)> {
-            self.is_split
-                .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
-                .ok()?;
-            let tx = StaticSender {
-                core: &self.core,
-                recycle: &self.recycle,
-                slots: &self.slots[..],
-            };
-            let rx = StaticReceiver {
-                core: &self.core,
-                recycle: &self.recycle,
-                slots: &self.slots[..],
-            };
-            Some((tx, rx))
+        pub fn spin_budget(&self) -> usize {
+            self.core.spin_budget.load(Ordering::Relaxed)
         }
-    }
-
-    // === impl StaticSender ===
 
-    impl<T, R> StaticSender<T, R>
-    where
-        R: Recycle<T>,
-    {
-        /// Reserves a slot in the channel to mutate in place, waiting until
-        /// there is a free slot to write to.
-        ///
-        /// This is similar to the [`send`] method, but, rather than taking a
-        /// message by value to write to the channel, this method reserves a
-        /// writable slot in the channel, and returns a [`SendRef`] that allows
-        /// mutating the slot in place. If the [`StaticReceiver`] end of the
-        /// channel uses the [`StaticReceiver::recv_ref`] or
-        /// [`StaticReceiver::poll_recv_ref`] methods for receiving from the
-        /// channel, this allows allocations for channel messages to be reused in place.
+        /// Enables or disables adaptive spinning for this channel.
         ///
-        /// # Errors
+        /// When adaptive spinning is enabled, the channel's [spin
+        /// budget](Self::spin_budget) is tuned at runtime: whenever a sender or
+        /// receiver spins until it runs out of budget, the budget is halved, and
+        /// whenever spinning succeeds before the budget runs out, it is doubled (up
+        /// to an upper limit of 1024). This lets the channel spin when messages
+        /// arrive in quick succession, and stop burning CPU time when they do
+        /// not. Unlike the blocking channels, async senders and receivers never
+        /// yield the thread to the OS scheduler while spinning, since that would
+        /// block the executor.
         ///
-        /// If the [`StaticReceiver`] end of the channel has been dropped, this
-        /// returns a [`Closed`] error.
+        /// Adaptive spinning is disabled by default.
         ///
         /// # Examples
         ///
-        /// Sending formatted strings by writing them directly to channel slots,
-        /// in place:
-        ///
         /// ```
-        /// use thingbuf::mpsc;
-        /// use std::fmt::Write;
-        ///
-        /// static CHANNEL: mpsc::StaticChannel<String, 8> = mpsc::StaticChannel::new();
-        ///
-        /// #[tokio::main]
-        /// async fn main() {
-        ///     let (tx, rx) = CHANNEL.split();
+        /// use thingbuf::mpsc::StaticChannel;
         ///
-        ///     // Spawn a task that prints each message received from the channel:
-        ///     tokio::spawn(async move {
-        ///         for _ in 0..10 {
-        ///             let msg = rx.recv_ref().await.unwrap();
-        ///             println!("{}", msg);
-        ///         }
-        ///     });
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (_tx, rx) = CHANNEL.split();
         ///
-        ///     // Until the channel closes, write formatted messages to the channel.
-        ///     let mut count = 1;
-        ///     while let Ok(mut value) = tx.send_ref().await {
-        ///         // Writing to the `SendRef` will reuse the *existing* string
-        ///         // allocation in place.
-        ///         write!(value, "hello from message {}", count)
-        ///             .expect("writing to a `String` should never fail");
-        ///         count += 1;
-        ///     }
-        /// }
+        /// rx.set_adaptive_spinning(true);
+        /// assert!(rx.is_adaptive_spinning());
         /// ```
-        /// [`send`]: Self::send
-        pub async fn send_ref(&self) -> Result<SendRef<'_, T>, Closed> {
-            SendRefFuture {
-                core: self.core,
-                slots: self.slots,
-                recycle: self.recycle,
-                state: State::Start,
-                waiter: queue::Waiter::new(),
-            }
-            .await
+        #[inline]
+        pub fn set_adaptive_spinning(&self, adaptive: bool) {
+            self.core.adaptive_spin.store(adaptive, Ordering::Relaxed);
         }
 
-        /// Sends a message by value, waiting until there is a free slot to
-        /// write to.
+        /// Returns `true` if adaptive spinning is enabled for this channel.
         ///
-        /// This method takes the message by value, and replaces any previous
-        /// value in the slot. This means that the channel will *not* function
-        /// as an object pool while sending messages with `send`. This method is
-        /// most appropriate when messages don't own reusable heap allocations,
-        /// or when the [`StaticReceiver`] end of the channel must receive
-        /// messages by moving them out of the channel by value (using the
-        /// [`StaticReceiver::recv`] method). When messages in the channel own
-        /// reusable heap allocations (such as `String`s or `Vec`s), and the
-        /// [`StaticReceiver`] doesn't need to receive them by value, consider
-        /// using [`send_ref`] instead, to enable allocation reuse.
+        /// See [`set_adaptive_spinning`](Self::set_adaptive_spinning) for details.
+        #[inline]
+        #[must_use]
+        pub fn is_adaptive_spinning(&self) -> bool {
+            self.core.adaptive_spin.load(Ordering::Relaxed)
+        }
+
+        /// Enables probabilistic load shedding above the given occupancy
+        /// `ratio`, or disables it if `ratio` is `None`.
+        ///
+        /// When load shedding is enabled and the fraction of the channel's
+        /// capacity that is occupied exceeds `ratio`, messages sent with
+        /// `try_send` are randomly dropped rather than sent. The probability that
+        /// a message is dropped rises linearly from 0 at the threshold to 1 when
+        /// the channel is full. A dropped message is handed back in a
+        /// [`TrySendError::Shed`] error, and counted by
+        /// [`shed_count`](Self::shed_count). Messages sent with `send`, which
+        /// waits for capacity instead, or with `send_ref` or `try_send_ref`, are
+        /// never dropped. If `ratio` is 1.0, no messages are dropped, and a full
+        /// channel is still reported as full.
+        ///
+        /// This lets producers that can tolerate lost messages, such as
+        /// metrics pipelines, degrade gracefully as a slow consumer falls
+        /// behind, rather than all being stalled as soon as the channel fills
+        /// up.
+        ///
+        /// Load shedding is disabled by default.
         ///
-        /// # Errors
+        /// # Panics
         ///
-        /// If the [`StaticReceiver`] end of the channel has been dropped, this
-        /// returns a [`Closed`] error containing the sent value.
+        /// If `ratio` is not between 0.0 and 1.0.
         ///
         /// # Examples
         ///
         /// ```
-        /// use thingbuf::mpsc;
-        ///
-        /// static CHANNEL: mpsc::StaticChannel<i32, 8> = mpsc::StaticChannel::new();
+        /// use thingbuf::mpsc::StaticChannel;
+        /// use thingbuf::mpsc::errors::TrySendError;
         ///
-        /// #[tokio::main]
-        /// async fn main() {
-        ///     let (tx, rx) = CHANNEL.split();
+        /// static CHANNEL: StaticChannel<usize, 64> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
         ///
-        ///     // Spawn a task that prints each message received from the channel:
-        ///     tokio::spawn(async move {
-        ///         for _ in 0..10 {
-        ///             let msg = rx.recv().await.unwrap();
-        ///             println!("received message {}", msg);
-        ///         }
-        ///     });
+        /// // Start shedding load once the channel is half full.
+        /// rx.set_load_shedding(Some(0.5));
         ///
-        ///     // Until the channel closes, write the current iteration to the channel.
-        ///     let mut count = 1;
-        ///     while tx.send(count).await.is_ok() {
-        ///         count += 1;
+        /// for i in 0..1000 {
+        ///     match tx.try_send(i) {
+        ///         // A shed message is handed back, and may be dropped.
+        ///         Ok(()) | Err(TrySendError::Shed(_)) => {}
+        ///         Err(e) => panic!("unexpected error: {}", e),
+        ///     }
+        ///     if i % 2 == 0 {
+        ///         rx.try_recv().unwrap();
         ///     }
         /// }
+        /// assert!(rx.shed_count() > 0);
         /// ```
-        /// [`send_ref`]: Self::send_ref
-        pub async fn send(&self, val: T) -> Result<(), Closed<T>> {
-            match self.send_ref().await {
-                Err(Closed(())) => Err(Closed(val)),
-                Ok(mut slot) => {
-                    *slot = val;
-                    Ok(())
-                }
-            }
+        #[inline]
+        pub fn set_load_shedding(&self, ratio: Option<f64>) {
+            self.core.set_load_shedding(ratio);
         }
 
-        /// Attempts to reserve a slot in the channel to mutate in place,
-        /// without waiting for capacity.
-        ///
-        /// This method differs from [`send_ref`] by returning immediately if the
-        /// channel’s buffer is full or no [`Receiver`] exists. Compared with
-        /// [`send_ref`], this method has two failure cases instead of one (one for
-        /// disconnection, one for a full buffer), and this method is not
-        /// `async`, because it will never wait.
+        /// Returns the number of messages that have been dropped by load
+        /// shedding.
         ///
-        /// Like [`send_ref`], this method returns a [`SendRef`] that may be
-        /// used to mutate a slot in the channel's buffer in place. Dropping the
-        /// [`SendRef`] completes the send operation and makes the mutated value
-        /// available to the [`Receiver`].
-        ///
-        /// # Errors
-        ///
-        /// If the channel capacity has been reached (i.e., the channel has `n`
-        /// buffered values where `n` is the `usize` const generic parameter of
-        /// the [`StaticChannel`]), then [`TrySendError::Full`] is returned. In
-        /// this case, a future call to `try_send` may succeed if additional
-        /// capacity becomes available.
-        ///
-        /// If the receive half of the channel is closed (i.e., the
-        /// [`StaticReceiver`] handle was dropped), the function returns
-        /// [`TrySendError::Closed`]. Once the channel has closed, subsequent
-        /// calls to `try_send_ref` will never succeed.
-        ///
-        /// [`send_ref`]: Self::send_ref
-        pub fn try_send_ref(&self) -> Result<SendRef<'_, T>, TrySendError> {
-            self.core
-                .try_send_ref(self.slots, self.recycle)
-                .map(SendRef)
+        /// See [`set_load_shedding`](Self::set_load_shedding) for details.
+        #[inline]
+        #[must_use]
+        pub fn shed_count(&self) -> usize {
+            self.core.shed_count.load(Ordering::Relaxed)
         }
 
-        /// Attempts to send a message by value immediately, without waiting for
-        /// capacity.
+        /// Sets the policy used to wake senders that are waiting for capacity,
+        /// when this receiver frees slots.
         ///
-        /// This method differs from [`send`] by returning immediately if the
-        /// channel’s buffer is full or no [`StaticReceiver`] exists. Compared with
-        /// [`send`], this method has two failure cases instead of one (one for
-        /// disconnection, one for a full buffer), and this method is not
-        /// `async`, because it will never wait.
+        /// By default, one waiting sender is woken for each freed slot. See
+        /// [`NotifyPolicy`] for the available policies.
         ///
-        /// # Errors
+        /// # Examples
         ///
-        /// If the channel capacity has been reached (i.e., the channel has `n`
-        /// buffered values where `n` is the `usize` const generic parameter of
-        /// the [`StaticChannel`]), then [`TrySendError::Full`] is returned. In
-        /// this case, a future call to `try_send` may succeed if additional
-        /// capacity becomes available.
+        /// ```
+        /// use thingbuf::mpsc::{NotifyPolicy, StaticChannel};
         ///
-        /// If the receive half of the channel is closed (i.e., the
-        /// [`StaticReceiver`] handle was dropped), the function returns
-        /// [`TrySendError::Closed`]. Once the channel has closed, subsequent
-        /// calls to `try_send` will never succeed.
+        /// static CHANNEL: StaticChannel<usize, 64> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
         ///
-        /// In both cases, the error includes the value passed to `try_send`.
+        /// // Wake every waiting sender when 16 or more slots are freed at once.
+        /// rx.set_notify_policy(NotifyPolicy::WakeAll { threshold: 16 });
+        /// assert_eq!(rx.notify_policy(), NotifyPolicy::WakeAll { threshold: 16 });
+        /// ```
+        #[inline]
+        pub fn set_notify_policy(&self, policy: NotifyPolicy) {
+            self.core.set_notify_policy(policy);
+        }
+
+        /// Returns the policy used to wake waiting senders.
         ///
-        /// [`send`]: Self::send
-        pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
-            self.core.try_send(self.slots, val, self.recycle)
+        /// See [`set_notify_policy`](Self::set_notify_policy) for details.
+        #[inline]
+        #[must_use]
+        pub fn notify_policy(&self) -> NotifyPolicy {
+            self.core.notify_policy()
         }
 
-        /// Returns the *total* capacity of the channel for this [`StaticSender`].
+        /// Returns the *total* capacity of the channel for this [`StaticReceiver`].
         /// This includes both occupied and unoccupied entries.
         ///
         /// To determine the channel's remaining *unoccupied* capacity, use
@@ -1187,8 +5182,8 @@ feature! {
         ///
         /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
         ///
-        /// let (tx, _) = CHANNEL.split();
-        /// assert_eq!(tx.capacity(), 100);
+        /// let (_, rx) = CHANNEL.split();
+        /// assert_eq!(rx.capacity(), 100);
         /// ```
         ///
         /// Even after sending several messages, the capacity remains
@@ -1203,7 +5198,7 @@ feature! {
         /// *tx.try_send_ref().unwrap() = 2;
         /// *tx.try_send_ref().unwrap() = 3;
         ///
-        /// assert_eq!(tx.capacity(), 100);
+        /// assert_eq!(rx.capacity(), 100);
         /// ```
         ///
         /// [`remaining`]: Self::remaining
@@ -1213,12 +5208,65 @@ feature! {
             self.core.core.capacity()
         }
 
-        /// Returns the unoccupied capacity of the channel for this [`StaticSender`]
+        /// Returns a snapshot of the statistics recorded for this channel.
+        ///
+        /// See the [`stats`](crate::stats) module for details.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        /// tx.try_send(1).unwrap();
+        ///
+        /// assert_eq!(rx.stats().occupancy().samples(), 1);
+        /// ```
+        #[cfg(feature = "stats")]
+        #[must_use]
+        pub fn stats(&self) -> crate::stats::Stats {
+            self.core.core.stats()
+        }
+
+        /// Returns the sequence number of the next message that will be
+        /// received from this channel.
+        ///
+        /// This is the number of messages that have been received so far.
+        /// Comparing it with the [`seq`] of a received message, or with the
+        /// [`StaticSender::published_seq`], allows consumers to detect gaps in the
+        /// sequence of messages.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        /// tx.try_send(1).unwrap();
+        /// tx.try_send(1).unwrap();
+        /// assert_eq!(rx.consumed_seq(), 0);
+        ///
+        /// let msg = rx.try_recv_ref().unwrap();
+        /// assert_eq!(msg.seq(), 0);
+        /// drop(msg);
+        /// assert_eq!(rx.consumed_seq(), 1);
+        /// ```
+        ///
+        /// [`seq`]: RecvRef::seq
+        #[cfg(feature = "seq")]
+        #[inline]
+        #[must_use]
+        pub fn consumed_seq(&self) -> usize {
+            self.core.core.consumed_seq()
+        }
+
+        /// Returns the unoccupied capacity of the channel for this [`StaticReceiver`]
         /// (i.e., how many additional elements can be sent before the channel
         /// will be full).
         ///
         /// This is equivalent to subtracting the channel's [`len`] from its [`capacity`].
-        ///
         /// # Examples
         ///
         /// ```
@@ -1227,15 +5275,15 @@ feature! {
         /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
         ///
         /// let (tx, rx) = CHANNEL.split();
-        /// assert_eq!(tx.remaining(), 100);
+        /// assert_eq!(rx.remaining(), 100);
         ///
         /// *tx.try_send_ref().unwrap() = 1;
         /// *tx.try_send_ref().unwrap() = 2;
         /// *tx.try_send_ref().unwrap() = 3;
-        /// assert_eq!(tx.remaining(), 97);
+        /// assert_eq!(rx.remaining(), 97);
         ///
         /// let _ = rx.try_recv_ref().unwrap();
-        /// assert_eq!(tx.remaining(), 98)
+        /// assert_eq!(rx.remaining(), 98)
         /// ```
         ///
         /// [`len`]: Self::len
@@ -1245,7 +5293,7 @@ feature! {
             self.capacity() - self.len()
         }
 
-        /// Returns the number of elements in the channel of this [`StaticSender`].
+        /// Returns the number of elements in the channel of this [`StaticReceiver`].
         ///
         /// To determine the channel's remaining *unoccupied* capacity, use
         /// [`remaining`] instead.
@@ -1258,15 +5306,15 @@ feature! {
         /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
         ///
         /// let (tx, rx) = CHANNEL.split();
-        /// assert_eq!(tx.len(), 0);
+        /// assert_eq!(rx.len(), 0);
         ///
         /// *tx.try_send_ref().unwrap() = 1;
         /// *tx.try_send_ref().unwrap() = 2;
         /// *tx.try_send_ref().unwrap() = 3;
-        /// assert_eq!(tx.len(), 3);
+        /// assert_eq!(rx.len(), 3);
         ///
         /// let _ = rx.try_recv_ref().unwrap();
-        /// assert_eq!(tx.len(), 2);
+        /// assert_eq!(rx.len(), 2);
         /// ```
         ///
         /// [`remaining`]: Self::remaining
@@ -1276,7 +5324,7 @@ feature! {
             self.core.core.len()
         }
 
-        /// Returns whether the number of elements in the channel of this [`StaticSender`] is 0.
+        /// Returns whether the number of elements in the channel of this [`StaticReceiver`] is 0.
         ///
         /// # Examples
         /// ```
@@ -1285,11 +5333,11 @@ feature! {
         /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
         ///
         /// let (tx, rx) = CHANNEL.split();
-        /// assert!(tx.is_empty());
+        /// assert!(rx.is_empty());
         ///
         /// *tx.try_send_ref().unwrap() = 1;
         ///
-        /// assert!(!tx.is_empty());
+        /// assert!(!rx.is_empty());
         /// ```
         ///
         #[inline]
@@ -1299,466 +5347,595 @@ feature! {
         }
     }
 
-    impl<T> Clone for StaticSender<T> {
-        fn clone(&self) -> Self {
-            test_dbg!(self.core.tx_count.fetch_add(1, Ordering::Relaxed));
-            Self {
-                core: self.core,
-                slots: self.slots,
-                recycle: self.recycle,
-            }
-        }
-    }
-
-    impl<T, R> Drop for StaticSender<T, R> {
+    impl<T, R> Drop for StaticReceiver<T, R> {
         fn drop(&mut self) {
-            if test_dbg!(self.core.tx_count.fetch_sub(1, Ordering::Release)) > 1 {
-                return;
-            }
-
-            // if we are the last sender, synchronize
-            test_dbg!(atomic::fence(Ordering::SeqCst));
-            self.core.core.close();
-            self.core.rx_wait.close_tx();
+            self.core.close_rx();
         }
     }
 
-    impl<T, R: fmt::Debug> fmt::Debug for StaticSender<T, R> {
+    impl<T, R: fmt::Debug> fmt::Debug for StaticReceiver<T, R> {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            f.debug_struct("StaticSender")
+            f.debug_struct("StaticReceiver")
                 .field("core", &self.core)
                 .field("slots", &format_args!("&[..]"))
-                .field("recycle", self.recycle)
+                .field("recycle", &self.recycle)
                 .finish()
         }
     }
+}
 
-    // === impl StaticReceiver ===
+feature! {
+    #![feature = "alloc"]
 
-    impl<T, R> StaticReceiver<T, R> {
-        /// Receives the next message for this receiver, **by reference**.
-        ///
-        /// This method returns `None` if the channel has been closed and there are
-        /// no remaining messages in the channel's buffer. This indicates that no
-        /// further values can ever be received from this `StaticReceiver`. The
-        /// channel is closed when all [`StaticSender`]s have been dropped.
-        ///
-        /// If there are no messages in the channel's buffer, but the channel has
-        /// not yet been closed, this method will wait until a message is sent or
-        /// the channel is closed.
+    /// An asynchronous bounded MPSC channel whose capacity is a const generic
+    /// parameter, and whose slots are stored inline.
+    ///
+    /// Unlike the channels returned by [`channel`], which allocate the array
+    /// of slots separately from the rest of the channel's state, a `Channel`
+    /// contains its slots, like a [`StaticChannel`]. When it is [`split`]
+    /// into an [`InlineSender`]/[`InlineReceiver`] pair, the whole channel is
+    /// moved into a single heap allocation. Because the capacity is known at
+    /// compile time, it is also checked at compile time: a `Channel` with a
+    /// capacity of 0, or a capacity greater than
+    /// `usize::MAX & !(1 << (usize::BITS - 1))`, fails to build.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::Channel;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let (tx, rx) = Channel::<usize, 16>::new().split();
+    ///
+    ///     tokio::spawn(async move {
+    ///         for i in 0..10 {
+    ///             tx.send(i).await.unwrap();
+    ///         }
+    ///     });
+    ///
+    ///     for i in 0..10 {
+    ///         assert_eq!(rx.recv().await, Some(i));
+    ///     }
+    ///     assert_eq!(rx.recv().await, None);
+    /// }
+    /// ```
+    ///
+    /// A capacity of 0 is a compile-time error:
+    ///
+    /// ```compile_fail
+    /// let channel = thingbuf::mpsc::Channel::<usize, 0>::new();
+    /// ```
+    ///
+    /// [`split`]: Channel::split
+    pub struct Channel<T, const CAPACITY: usize, R = recycling::DefaultRecycle> {
+        core: ChannelCore<Waker>,
+        recycle: R,
+        slots: [Slot<T>; CAPACITY],
+    }
+
+    /// Asynchronously sends values to an associated [`InlineReceiver`].
+    ///
+    /// Instances of this struct are created by the [`Channel::split`]
+    /// method.
+    pub struct InlineSender<T, const CAPACITY: usize, R = recycling::DefaultRecycle> {
+        inner: Arc<Channel<T, CAPACITY, R>>,
+    }
+
+    /// Asynchronously receives values from associated [`InlineSender`]s.
+    ///
+    /// Instances of this struct are created by the [`Channel::split`]
+    /// method.
+    pub struct InlineReceiver<T, const CAPACITY: usize, R = recycling::DefaultRecycle> {
+        inner: Arc<Channel<T, CAPACITY, R>>,
+    }
+
+    // === impl Channel ===
+
+    impl<T, const CAPACITY: usize> Channel<T, CAPACITY> {
+        /// Constructs a new asynchronous bounded MPSC channel with a capacity
+        /// of `CAPACITY` messages, using the [default recycling policy].
         ///
-        /// This method returns a [`RecvRef`] that can be used to read from (or
-        /// mutate) the received message by reference. When the [`RecvRef`] is
-        /// dropped, the receive operation completes and the slot occupied by
-        /// the received message becomes usable for a future [`send_ref`] operation.
+        /// [default recycling policy]: crate::recycling::DefaultRecycle
+        #[must_use]
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self::with_recycle(recycling::DefaultRecycle::new())
+        }
+
+        /// Constructs a new asynchronous bounded MPSC channel with a capacity
+        /// of `CAPACITY` messages, using the [default recycling policy], and
+        /// splits it into an [`InlineSender`]/[`InlineReceiver`] pair.
         ///
-        /// If all [`StaticSender`]s for this channel write to the channel's slots in
-        /// place by using the [`send_ref`] or [`try_send_ref`] methods, this
-        /// method allows messages that own heap allocations to be reused in
-        /// place.
+        /// Unlike `Channel::new().split()`, this initializes the channel
+        /// directly on the heap, so the channel is never stored on the stack.
+        /// This should be used for channels that are too large to fit on the
+        /// stack. See [`Channel::split_with_recycle`] for the memory this
+        /// uses while the channel is created.
         ///
         /// # Examples
         ///
         /// ```
-        /// use thingbuf::mpsc::StaticChannel;
-        /// use std::fmt::Write;
+        /// use thingbuf::mpsc::Channel;
         ///
-        /// static CHANNEL: StaticChannel<String, 100> = StaticChannel::new();
+        /// let (tx, rx) = Channel::<u64, 262_144>::new_split();
+        /// tx.try_send(1).unwrap();
+        /// assert_eq!(rx.try_recv(), Ok(1));
+        /// ```
         ///
-        /// #[tokio::main]
-        /// async fn main() {
-        ///     let (tx, rx) = CHANNEL.split();
+        /// [default recycling policy]: crate::recycling::DefaultRecycle
+        #[must_use]
+        pub fn new_split() -> (InlineSender<T, CAPACITY>, InlineReceiver<T, CAPACITY>) {
+            Self::split_with_recycle(recycling::DefaultRecycle::new())
+        }
+    }
+
+    impl<T, R, const CAPACITY: usize> Channel<T, CAPACITY, R> {
+        const CAPACITY_OK: () = assert!(
+            CAPACITY > 0 && CAPACITY <= MAX_CAPACITY,
+            "channel capacity must be greater than 0 and at most `MAX_CAPACITY`"
+        );
+
+        /// Constructs a new asynchronous bounded MPSC channel with a capacity
+        /// of `CAPACITY` messages, using the provided [recycling policy].
         ///
-        ///     tokio::spawn(async move {
-        ///         let mut value = tx.send_ref().await.unwrap();
-        ///         write!(value, "hello world!")
-        ///             .expect("writing to a `String` should never fail");
-        ///     });
+        /// # Examples
         ///
-        ///     assert_eq!(Some("hello world!"), rx.recv_ref().await.as_deref().map(String::as_str));
-        ///     assert_eq!(None, rx.recv().await.as_deref());
-        /// }
         /// ```
+        /// use thingbuf::{mpsc::Channel, recycling::WithCapacity};
         ///
-        /// Values are buffered:
-        ///
+        /// let recycle = WithCapacity::new().with_max_capacity(1024);
+        /// let (tx, rx) = Channel::<String, 8, _>::with_recycle(recycle).split();
+        /// # drop((tx, rx));
         /// ```
-        /// use thingbuf::mpsc::StaticChannel;
-        /// use std::fmt::Write;
         ///
-        /// static CHANNEL: StaticChannel<String, 100> = StaticChannel::new();
+        /// [recycling policy]: crate::recycling::Recycle
+        #[must_use]
+        pub fn with_recycle(recycle: R) -> Self {
+            #[allow(clippy::let_unit_value)]
+            let () = Self::CAPACITY_OK;
+            let mut idx = 0;
+            let slots = [(); CAPACITY].map(|()| {
+                let slot = Slot::new(idx);
+                idx += 1;
+                slot
+            });
+            Self {
+                core: ChannelCore::new(CAPACITY),
+                recycle,
+                slots,
+            }
+        }
+
+        /// Split a [`Channel`] into an [`InlineSender`]/[`InlineReceiver`]
+        /// pair.
         ///
-        /// #[tokio::main]
-        /// async fn main() {
-        ///     let (tx, rx) = CHANNEL.split();
+        /// This moves the channel, including its slots, into a single heap
+        /// allocation that is shared by the sender and receiver. Because the
+        /// channel is first constructed on the stack, channels with a large
+        /// `CAPACITY` should be created with [`Channel::new_split`] or
+        /// [`Channel::split_with_recycle`] instead.
+        #[must_use]
+        pub fn split(self) -> (InlineSender<T, CAPACITY, R>, InlineReceiver<T, CAPACITY, R>) {
+            Self::split_arc(Arc::new(self))
+        }
+
+        /// Constructs a new asynchronous bounded MPSC channel with a capacity
+        /// of `CAPACITY` messages, using the provided [recycling policy], and
+        /// splits it into an [`InlineSender`]/[`InlineReceiver`] pair.
         ///
-        ///     write!(tx.send_ref().await.unwrap(), "hello").unwrap();
-        ///     write!(tx.send_ref().await.unwrap(), "world").unwrap();
+        /// Like [`Channel::new_split`], this initializes the channel directly
+        /// on the heap, so the channel is never stored on the stack. It is
+        /// initialized in a temporary allocation, which is then copied into
+        /// the allocation shared by the sender and receiver, so creating the
+        /// channel briefly uses twice its size in memory.
+        ///
+        /// # Examples
         ///
-        ///     assert_eq!("hello", rx.recv_ref().await.unwrap().as_str());
-        ///     assert_eq!("world", rx.recv_ref().await.unwrap().as_str());
-        /// }
         /// ```
+        /// use thingbuf::{mpsc::Channel, recycling::WithCapacity};
         ///
-        /// [`send_ref`]: StaticSender::send_ref
-        /// [`try_send_ref`]: StaticSender::try_send_ref
-        pub fn recv_ref(&self) -> RecvRefFuture<'_, T> {
-            RecvRefFuture {
-                core: self.core,
-                slots: self.slots,
-            }
+        /// let recycle = WithCapacity::new().with_max_capacity(1024);
+        /// let (tx, rx) = Channel::<String, 65_536, _>::split_with_recycle(recycle);
+        /// # drop((tx, rx));
+        /// ```
+        ///
+        /// [recycling policy]: crate::recycling::Recycle
+        #[must_use]
+        pub fn split_with_recycle(
+            recycle: R,
+        ) -> (InlineSender<T, CAPACITY, R>, InlineReceiver<T, CAPACITY, R>) {
+            #[allow(clippy::let_unit_value)]
+            let () = Self::CAPACITY_OK;
+            let layout = alloc::alloc::Layout::new::<Self>();
+            // Safety: `Self` always contains a `ChannelCore`, so it is never
+            // zero-sized. Every field is initialized before the allocation
+            // is turned into a `Box`, and the slots are written one at a time
+            // so that the array is never built on the stack.
+            let boxed = unsafe {
+                let ptr = alloc::alloc::alloc(layout) as *mut Self;
+                if ptr.is_null() {
+                    alloc::alloc::handle_alloc_error(layout);
+                }
+                ptr::addr_of_mut!((*ptr).core).write(ChannelCore::new(CAPACITY));
+                ptr::addr_of_mut!((*ptr).recycle).write(recycle);
+                let slots = ptr::addr_of_mut!((*ptr).slots) as *mut Slot<T>;
+                for idx in 0..CAPACITY {
+                    slots.add(idx).write(Slot::new(idx));
+                }
+                Box::from_raw(ptr)
+            };
+            // Converting a `Box` into an `Arc` allocates the `Arc` and copies
+            // the channel into it, since an `Arc`'s contents can only be
+            // initialized in place with `Arc::new_uninit`, which requires Rust
+            // 1.82 (above our MSRV of 1.57). This is a one-time cost when the
+            // channel is created, and still never puts it on the stack.
+            #[cfg(not(all(loom, test)))]
+            let inner = Arc::from(boxed);
+            #[cfg(all(loom, test))]
+            let inner = Arc::from_std(std::sync::Arc::from(boxed));
+            Self::split_arc(inner)
         }
 
-        /// Receives the next message for this receiver, **by value**.
-        ///
-        /// This method returns `None` if the channel has been closed and there are
-        /// no remaining messages in the channel's buffer. This indicates that no
-        /// further values can ever be received from this `StaticReceiver`. The
-        /// channel is closed when all [`StaticSender`]s have been dropped.
+        fn split_arc(
+            inner: Arc<Self>,
+        ) -> (InlineSender<T, CAPACITY, R>, InlineReceiver<T, CAPACITY, R>) {
+            let tx = InlineSender {
+                inner: inner.clone(),
+            };
+            let rx = InlineReceiver { inner };
+            (tx, rx)
+        }
+
+        /// Returns the total capacity of the channel, `CAPACITY`.
+        #[inline]
+        #[must_use]
+        pub const fn capacity(&self) -> usize {
+            CAPACITY
+        }
+    }
+
+    impl<T, R, const CAPACITY: usize> Drop for Channel<T, CAPACITY, R> {
+        fn drop(&mut self) {
+            self.core.core.drop_slots(&mut self.slots[..])
+        }
+    }
+
+    impl<T, R: fmt::Debug, const CAPACITY: usize> fmt::Debug for Channel<T, CAPACITY, R> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Channel")
+                .field("core", &self.core)
+                .field("slots", &format_args!("[..; {}]", CAPACITY))
+                .field("recycle", &self.recycle)
+                .finish()
+        }
+    }
+
+    // === impl InlineSender ===
+
+    impl<T, R, const CAPACITY: usize> InlineSender<T, CAPACITY, R>
+    where
+        R: Recycle<T>,
+    {
+        /// Reserves a slot in the channel to mutate in place, waiting until
+        /// there is a free slot to write to.
         ///
-        /// If there are no messages in the channel's buffer, but the channel has
-        /// not yet been closed, this method will wait until a message is sent or
-        /// the channel is closed.
+        /// This is equivalent to [`Sender::send_ref`].
         ///
-        /// When a message is received, it is moved out of the channel by value,
-        /// and replaced with a new slot according to the configured [recycling
-        /// policy]. If all [`StaticSender`]s for this channel write to the channel's
-        /// slots in place by using the [`send_ref`] or [`try_send_ref`] methods,
-        /// consider using the [`recv_ref`] method instead, to enable the
-        /// reuse of heap allocations.
+        /// # Errors
+        ///
+        /// If the [`InlineReceiver`] end of the channel has been dropped,
+        /// this returns a [`Closed`] error.
         ///
         /// # Examples
         ///
         /// ```
-        /// use thingbuf::mpsc::StaticChannel;
-        ///
-        /// static CHANNEL: StaticChannel<i32, 100> = StaticChannel::new();
+        /// use thingbuf::mpsc::Channel;
+        /// use std::fmt::Write;
         ///
         /// #[tokio::main]
         /// async fn main() {
-        ///     let (tx, rx) = CHANNEL.split();
-        ///
-        ///     tokio::spawn(async move {
-        ///         tx.send(1).await.unwrap();
-        ///     });
+        ///     let (tx, rx) = Channel::<String, 8>::new().split();
         ///
-        ///     assert_eq!(Some(1), rx.recv().await);
-        ///     assert_eq!(None, rx.recv().await);
+        ///     write!(tx.send_ref().await.unwrap(), "hello").unwrap();
+        ///     assert_eq!(rx.recv_ref().await.unwrap().as_str(), "hello");
         /// }
         /// ```
+        pub async fn send_ref(&self) -> Result<SendRef<'_, T>, Closed> {
+            SendRefFuture {
+                core: &self.inner.core,
+                slots: &self.inner.slots[..],
+                recycle: &self.inner.recycle,
+                state: State::Start,
+                waiter: queue::Waiter::new(),
+            }
+            .await
+        }
+
+        /// Sends a message by value, waiting until there is capacity.
         ///
-        /// Values are buffered:
+        /// This is equivalent to [`Sender::send`].
         ///
-        /// ```
-        /// use thingbuf::mpsc::StaticChannel;
+        /// # Errors
         ///
-        /// static CHANNEL: StaticChannel<i32, 100> = StaticChannel::new();
+        /// If the [`InlineReceiver`] end of the channel has been dropped,
+        /// this returns a [`Closed`] error containing the sent value.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::Channel;
         ///
         /// #[tokio::main]
         /// async fn main() {
-        ///     let (tx, rx) = CHANNEL.split();
+        ///     let (tx, rx) = Channel::<i32, 4>::new().split();
         ///
         ///     tx.send(1).await.unwrap();
-        ///     tx.send(2).await.unwrap();
-        ///
-        ///     assert_eq!(Some(1), rx.recv().await);
-        ///     assert_eq!(Some(2), rx.recv().await);
+        ///     drop(rx);
+        ///     assert!(tx.send(2).await.is_err());
         /// }
         /// ```
-        ///
-        /// [`send_ref`]: StaticSender::send_ref
-        /// [`try_send_ref`]: StaticSender::try_send_ref
-        /// [recycling policy]: crate::recycling::Recycle
-        /// [`recv_ref`]: Self::recv_ref
-        pub fn recv(&self) -> RecvFuture<'_, T, R>
-        where
-            R: Recycle<T>,
-        {
-            RecvFuture {
-                core: self.core,
-                slots: self.slots,
-                recycle: self.recycle,
+        pub async fn send(&self, val: T) -> Result<(), Closed<T>> {
+            match self.send_ref().await {
+                Err(Closed(())) => Err(Closed(val)),
+                Ok(mut slot) => {
+                    *slot = val;
+                    Ok(())
+                }
             }
         }
 
-        /// Attempts to receive the next message for this receiver by reference
-        /// without waiting for a new message when the channel is empty.
+        /// Attempts to reserve a slot in the channel to mutate in place,
+        /// without waiting for capacity.
         ///
-        /// This method differs from [`recv_ref`] by returning immediately if the
-        /// channel is empty or closed.
+        /// This is equivalent to [`Sender::try_send_ref`].
         ///
         /// # Errors
         ///
-        /// This method returns an error when the channel is closed or there are
-        /// no remaining messages in the channel's buffer.
-        ///
-        /// # Examples
-        ///
-        /// ```
-        /// use thingbuf::mpsc::{channel, errors::TryRecvError};
-        ///
-        /// let (tx, rx) = channel(100);
-        /// assert!(matches!(rx.try_recv_ref(), Err(TryRecvError::Empty)));
-        ///
-        /// tx.try_send(1).unwrap();
-        /// drop(tx);
-        ///
-        /// assert_eq!(*rx.try_recv_ref().unwrap(), 1);
-        /// assert!(matches!(rx.try_recv_ref(), Err(TryRecvError::Closed)));
-        /// ```
-        ///
-        /// [`recv_ref`]: Self::recv_ref
-        pub fn try_recv_ref(&self) -> Result<RecvRef<'_, T>, TryRecvError>
-        where
-            R: Recycle<T>,
-        {
-            self.core.try_recv_ref(self.slots.as_ref()).map(RecvRef)
+        /// - [`TrySendError::Full`] if the channel is full.
+        /// - [`TrySendError::Closed`] if the [`InlineReceiver`] has been
+        ///   dropped.
+        pub fn try_send_ref(&self) -> Result<SendRef<'_, T>, TrySendError> {
+            self.inner
+                .core
+                .try_send_ref(&self.inner.slots[..], &self.inner.recycle)
+                .map(SendRef)
         }
 
-        /// Attempts to receive the next message for this receiver by reference
-        /// without waiting for a new message when the channel is empty.
+        /// Attempts to send a message by value, without waiting for capacity.
         ///
-        /// This method differs from [`recv`] by returning immediately if the
-        /// channel is empty or closed.
+        /// This is equivalent to [`Sender::try_send`].
         ///
         /// # Errors
         ///
-        /// This method returns an error when the channel is closed or there are
-        /// no remaining messages in the channel's buffer.
+        /// - [`TrySendError::Full`] if the channel is full.
+        /// - [`TrySendError::Closed`] if the [`InlineReceiver`] has been
+        ///   dropped.
         ///
         /// # Examples
         ///
         /// ```
-        /// use thingbuf::mpsc::{channel, errors::TryRecvError};
+        /// use thingbuf::mpsc::{errors::TrySendError, Channel};
         ///
-        /// let (tx, rx) = channel(100);
-        /// assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        /// let (tx, rx) = Channel::<i32, 1>::new().split();
         ///
         /// tx.try_send(1).unwrap();
-        /// drop(tx);
-        ///
-        /// assert_eq!(rx.try_recv().unwrap(), 1);
-        /// assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+        /// assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+        /// # drop(rx);
         /// ```
-        ///
-        /// [`recv`]: Self::recv
-        pub fn try_recv(&self) -> Result<T, TryRecvError>
-        where
-            R: Recycle<T>,
-        {
-            self.core.try_recv(self.slots.as_ref(), self.recycle)
+        pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
+            self.inner
+                .core
+                .try_send(&self.inner.slots[..], val, &self.inner.recycle)
         }
+    }
 
-        /// Attempts to receive a message *by reference* from this channel,
-        /// registering the current task for wakeup if the a message is not yet
-        /// available, and returning `None` if the channel has closed and all
-        /// messages have been received.
-        ///
-        /// Like [`StaticReceiver::recv_ref`], this method returns a [`RecvRef`]
-        /// that can be used to read from (or mutate) the received message by
-        /// reference. When the [`RecvRef`] is dropped, the receive operation
-        /// completes and the slot occupied by the received message becomes
-        /// usable for a future [`send_ref`] operation.
-        ///
-        /// If all [`StaticSender`]s for this channel write to the channel's slots in
-        /// place by using the [`send_ref`] or [`try_send_ref`] methods, this
-        /// method allows messages that own heap allocations to be reused in
-        /// place.
-        ///
-        /// To wait asynchronously until a message becomes available, use the
-        /// [`recv_ref`] method instead.
-        ///
-        /// # Returns
-        ///
-        ///  * `Poll::Pending` if no messages are available but the channel is not
-        ///    closed, or if a spurious failure happens.
-        ///  * `Poll::Ready(Some(RecvRef<T>))` if a message is available.
-        ///  * `Poll::Ready(None)` if the channel has been closed (i.e., all
-        ///    [`StaticSender`]s have been dropped), and all messages sent before it
-        ///    was closed have been received.
-        ///
-        /// When the method returns [`Poll::Pending`], the [`Waker`] in the provided
-        /// [`Context`] is scheduled to receive a wakeup when a message is sent on any
-        /// sender, or when the channel is closed.  Note that on multiple calls to
-        /// `poll_recv_ref`, only the [`Waker`] from the [`Context`] passed to the most
-        /// recent call is scheduled to receive a wakeup.
-        ///
-        /// [`send_ref`]: StaticSender::send_ref
-        /// [`try_send_ref`]: StaticSender::try_send_ref
-        /// [`recv_ref`]: Self::recv_ref
-        pub fn poll_recv_ref(&self, cx: &mut Context<'_>) -> Poll<Option<RecvRef<'_, T>>> {
-            poll_recv_ref(self.core, self.slots, cx)
+    impl<T, R, const CAPACITY: usize> InlineSender<T, CAPACITY, R> {
+        /// Returns the total capacity of the channel, `CAPACITY`.
+        #[inline]
+        #[must_use]
+        pub const fn capacity(&self) -> usize {
+            CAPACITY
         }
 
-        /// Attempts to receive a message *by value* from this channel,
-        /// registering the current task for wakeup if the value is not yet
-        /// available, and returning `None` if the channel has closed and all
-        /// messages have been received.
-        ///
-        /// When a message is received, it is moved out of the channel by value,
-        /// and replaced with a new slot according to the configured [recycling
-        /// policy]. If all [`StaticSender`]s for this channel write to the channel's
-        /// slots in place by using the [`send_ref`] or [`try_send_ref`] methods,
-        /// consider using the [`poll_recv_ref`] method instead, to enable the
-        /// reuse of heap allocations.
-        ///
-        /// To wait asynchronously until a message becomes available, use the
-        /// [`recv`] method instead.
-        ///
-        /// # Returns
-        ///
-        ///  * `Poll::Pending` if no messages are available but the channel is not
-        ///    closed, or if a spurious failure happens.
-        ///  * `Poll::Ready(Some(message))` if a message is available.
-        ///  * `Poll::Ready(None)` if the channel has been closed (i.e., all
-        ///    [`StaticSender`]s have been dropped) and all messages sent before it
-        ///    was closed have been received.
-        ///
-        /// When the method returns [`Poll::Pending`], the [`Waker`] in the provided
-        /// [`Context`] is scheduled to receive a wakeup when a message is sent on any
-        /// sender, or when the channel is closed.  Note that on multiple calls to
-        /// `poll_recv`, only the [`Waker`] from the [`Context`] passed to the most
-        /// recent call is scheduled to receive a wakeup.
-        ///
-        /// [`send_ref`]: StaticSender::send_ref
-        /// [`try_send_ref`]: StaticSender::try_send_ref
-        /// [recycling policy]: crate::recycling::Recycle
-        /// [`poll_recv_ref`]: Self::poll_recv_ref
-        /// [`recv`]: Self::recv
-        pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>>
-        where
-            R: Recycle<T>,
-        {
-            self.poll_recv_ref(cx)
-                .map(|opt| opt.map(|mut r| recycling::take(&mut *r, self.recycle)))
+        /// Returns the number of messages in the channel.
+        #[inline]
+        #[must_use]
+        pub fn len(&self) -> usize {
+            self.inner.core.core.len()
         }
 
-        /// Returns `true` if the channel has closed (all corresponding
-        /// [`StaticSender`]s have been dropped).
-        ///
-        /// If this method returns `true`, no new messages will become available
-        /// on this channel. Previously sent messages may still be available.
-        pub fn is_closed(&self) -> bool {
-            test_dbg!(self.core.tx_count.load(Ordering::SeqCst)) <= 1
+        /// Returns `true` if the channel is empty.
+        #[inline]
+        #[must_use]
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Returns `true` if the [`InlineReceiver`] has not been dropped.
+        #[inline]
+        #[must_use]
+        pub fn receiver_alive(&self) -> bool {
+            !self.inner.core.core.is_closed()
+        }
+    }
+
+    impl<T, R, const CAPACITY: usize> Clone for InlineSender<T, CAPACITY, R> {
+        fn clone(&self) -> Self {
+            test_dbg!(self.inner.core.tx_count.fetch_add(1, Ordering::Relaxed));
+            Self {
+                inner: self.inner.clone(),
+            }
+        }
+    }
+
+    impl<T, R, const CAPACITY: usize> Drop for InlineSender<T, CAPACITY, R> {
+        fn drop(&mut self) {
+            if test_dbg!(self.inner.core.tx_count.fetch_sub(1, Ordering::Release)) > 1 {
+                return;
+            }
+
+            // if we are the last sender, synchronize
+            test_dbg!(atomic::fence(Ordering::SeqCst));
+            if self.inner.core.core.close() {
+                self.inner.core.notify_groups();
+            }
+            self.inner.core.rx_wait.close_tx();
+        }
+    }
+
+    impl<T, R: fmt::Debug, const CAPACITY: usize> fmt::Debug for InlineSender<T, CAPACITY, R> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("InlineSender")
+                .field("inner", &self.inner)
+                .finish()
+        }
+    }
+
+    // === impl InlineReceiver ===
+
+    impl<T, R, const CAPACITY: usize> InlineReceiver<T, CAPACITY, R> {
+        /// Receives the next message for this receiver, **by reference**.
+        ///
+        /// This is equivalent to [`Receiver::recv_ref`]. It returns `None`
+        /// once all [`InlineSender`]s have been dropped and every message
+        /// has been received.
+        pub fn recv_ref(&self) -> RecvRefFuture<'_, T> {
+            RecvRefFuture {
+                core: &self.inner.core,
+                slots: &self.inner.slots[..],
+            }
         }
 
-        /// Returns the *total* capacity of the channel for this [`StaticReceiver`].
-        /// This includes both occupied and unoccupied entries.
+        /// Receives the next message for this receiver, **by value**.
         ///
-        /// To determine the channel's remaining *unoccupied* capacity, use
-        /// [`remaining`] instead.
+        /// This is equivalent to [`Receiver::recv`]. It returns `None` once
+        /// all [`InlineSender`]s have been dropped and every message has
+        /// been received.
         ///
         /// # Examples
         ///
         /// ```
-        /// use thingbuf::mpsc::StaticChannel;
-        ///
-        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
-        ///
-        /// let (_, rx) = CHANNEL.split();
-        /// assert_eq!(rx.capacity(), 100);
-        /// ```
-        ///
-        /// Even after sending several messages, the capacity remains
-        /// the same:
-        /// ```
-        /// # use thingbuf::mpsc::StaticChannel;
+        /// use thingbuf::mpsc::Channel;
         ///
-        /// # static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        /// #[tokio::main]
+        /// async fn main() {
+        ///     let (tx, rx) = Channel::<i32, 4>::new().split();
         ///
-        /// let (tx, rx) = CHANNEL.split();
-        /// *tx.try_send_ref().unwrap() = 1;
-        /// *tx.try_send_ref().unwrap() = 2;
-        /// *tx.try_send_ref().unwrap() = 3;
+        ///     tx.send(1).await.unwrap();
+        ///     drop(tx);
         ///
-        /// assert_eq!(rx.capacity(), 100);
+        ///     assert_eq!(rx.recv().await, Some(1));
+        ///     assert_eq!(rx.recv().await, None);
+        /// }
         /// ```
-        ///
-        /// [`remaining`]: Self::remaining
-        #[inline]
-        #[must_use]
-        pub fn capacity(&self) -> usize {
-            self.core.core.capacity()
+        pub fn recv(&self) -> RecvFuture<'_, T, R>
+        where
+            R: Recycle<T>,
+        {
+            RecvFuture {
+                core: &self.inner.core,
+                slots: &self.inner.slots[..],
+                recycle: &self.inner.recycle,
+            }
         }
 
-        /// Returns the unoccupied capacity of the channel for this [`StaticReceiver`]
-        /// (i.e., how many additional elements can be sent before the channel
-        /// will be full).
-        ///
-        /// This is equivalent to subtracting the channel's [`len`] from its [`capacity`].
-        /// # Examples
-        ///
-        /// ```
-        /// use thingbuf::mpsc::StaticChannel;
-        ///
-        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
-        ///
-        /// let (tx, rx) = CHANNEL.split();
-        /// assert_eq!(rx.remaining(), 100);
+        /// Attempts to receive the next message for this receiver by
+        /// reference, without waiting for a new message to be sent.
         ///
-        /// *tx.try_send_ref().unwrap() = 1;
-        /// *tx.try_send_ref().unwrap() = 2;
-        /// *tx.try_send_ref().unwrap() = 3;
-        /// assert_eq!(rx.remaining(), 97);
+        /// This is equivalent to [`Receiver::try_recv_ref`].
         ///
-        /// let _ = rx.try_recv_ref().unwrap();
-        /// assert_eq!(rx.remaining(), 98)
-        /// ```
+        /// # Errors
         ///
-        /// [`len`]: Self::len
-        /// [`capacity`]: Self::capacity
-        #[must_use]
-        pub fn remaining(&self) -> usize {
-            self.capacity() - self.len()
+        /// - [`TryRecvError::Empty`] if there are no messages in the channel.
+        /// - [`TryRecvError::Closed`] if all [`InlineSender`]s have been
+        ///   dropped and the channel is empty.
+        pub fn try_recv_ref(&self) -> Result<RecvRef<'_, T>, TryRecvError>
+        where
+            R: Recycle<T>,
+        {
+            self.inner.core.try_recv_ref(&self.inner.slots[..]).map(RecvRef)
         }
 
-        /// Returns the number of elements in the channel of this [`StaticReceiver`].
+        /// Attempts to receive the next message for this receiver by value,
+        /// without waiting for a new message to be sent.
         ///
-        /// To determine the channel's remaining *unoccupied* capacity, use
-        /// [`remaining`] instead.
+        /// This is equivalent to [`Receiver::try_recv`].
+        ///
+        /// # Errors
+        ///
+        /// - [`TryRecvError::Empty`] if there are no messages in the channel.
+        /// - [`TryRecvError::Closed`] if all [`InlineSender`]s have been
+        ///   dropped and the channel is empty.
         ///
         /// # Examples
         ///
         /// ```
-        /// use thingbuf::mpsc::StaticChannel;
+        /// use thingbuf::mpsc::{errors::TryRecvError, Channel};
         ///
-        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
-        ///
-        /// let (tx, rx) = CHANNEL.split();
-        /// assert_eq!(rx.len(), 0);
+        /// let (tx, rx) = Channel::<i32, 4>::new().split();
+        /// assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
         ///
-        /// *tx.try_send_ref().unwrap() = 1;
-        /// *tx.try_send_ref().unwrap() = 2;
-        /// *tx.try_send_ref().unwrap() = 3;
-        /// assert_eq!(rx.len(), 3);
+        /// tx.try_send(1).unwrap();
+        /// drop(tx);
         ///
-        /// let _ = rx.try_recv_ref().unwrap();
-        /// assert_eq!(rx.len(), 2);
+        /// assert_eq!(rx.try_recv(), Ok(1));
+        /// assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
         /// ```
+        pub fn try_recv(&self) -> Result<T, TryRecvError>
+        where
+            R: Recycle<T>,
+        {
+            self.inner
+                .core
+                .try_recv(&self.inner.slots[..], &self.inner.recycle)
+        }
+
+        /// Attempts to receive a message *by reference* from this channel,
+        /// registering the current task for wakeup if a message is not yet
+        /// available.
         ///
-        /// [`remaining`]: Self::remaining
+        /// This is equivalent to [`Receiver::poll_recv_ref`].
+        pub fn poll_recv_ref(&self, cx: &mut Context<'_>) -> Poll<Option<RecvRef<'_, T>>> {
+            poll_recv_ref(&self.inner.core, &self.inner.slots[..], cx)
+        }
+
+        /// Attempts to receive a message *by value* from this channel,
+        /// registering the current task for wakeup if a message is not yet
+        /// available.
+        ///
+        /// This is equivalent to [`Receiver::poll_recv`].
+        pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>>
+        where
+            R: Recycle<T>,
+        {
+            self.poll_recv_ref(cx)
+                .map(|opt| opt.map(|mut r| recycling::take(&mut *r, &self.inner.recycle)))
+        }
+
+        /// Returns `true` if the channel has closed (all corresponding
+        /// [`InlineSender`]s have been dropped).
+        #[must_use]
+        pub fn is_closed(&self) -> bool {
+            test_dbg!(self.inner.core.tx_count.load(Ordering::SeqCst)) == 0
+                || self.inner.core.core.is_closed()
+        }
+
+        /// Returns the total capacity of the channel, `CAPACITY`.
+        #[inline]
+        #[must_use]
+        pub const fn capacity(&self) -> usize {
+            CAPACITY
+        }
+
+        /// Returns the number of messages in the channel.
         #[inline]
         #[must_use]
         pub fn len(&self) -> usize {
-            self.core.core.len()
+            self.inner.core.core.len()
         }
 
-        /// Returns whether the number of elements in the channel of this [`StaticReceiver`] is 0.
-        ///
-        /// # Examples
-        /// ```
-        /// use thingbuf::mpsc::StaticChannel;
-        ///
-        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
-        ///
-        /// let (tx, rx) = CHANNEL.split();
-        /// assert!(rx.is_empty());
-        ///
-        /// *tx.try_send_ref().unwrap() = 1;
-        ///
-        /// assert!(!rx.is_empty());
-        /// ```
-        ///
+        /// Returns `true` if the channel is empty.
         #[inline]
         #[must_use]
         pub fn is_empty(&self) -> bool {
@@ -1766,18 +5943,16 @@ feature! {
         }
     }
 
-    impl<T, R> Drop for StaticReceiver<T, R> {
+    impl<T, R, const CAPACITY: usize> Drop for InlineReceiver<T, CAPACITY, R> {
         fn drop(&mut self) {
-            self.core.close_rx();
+            self.inner.core.close_rx();
         }
     }
 
-    impl<T, R: fmt::Debug> fmt::Debug for StaticReceiver<T, R> {
+    impl<T, R: fmt::Debug, const CAPACITY: usize> fmt::Debug for InlineReceiver<T, CAPACITY, R> {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            f.debug_struct("StaticReceiver")
-                .field("core", &self.core)
-                .field("slots", &format_args!("&[..]"))
-                .field("recycle", &self.recycle)
+            f.debug_struct("InlineReceiver")
+                .field("inner", &self.inner)
                 .finish()
         }
     }
@@ -1844,6 +6019,16 @@ pub struct RecvFuture<'a, T, R = recycling::DefaultRecycle> {
     recycle: &'a R,
 }
 
+/// A [`Future`] that waits for the next message in a channel, without
+/// receiving it.
+///
+/// This type is returned by [`Receiver::peek`] and [`StaticReceiver::peek`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct PeekFuture<'a, T> {
+    core: &'a ChannelCore<Waker>,
+    slots: &'a [Slot<T>],
+}
+
 #[pin_project::pin_project(PinnedDrop)]
 struct SendRefFuture<'sender, T, R> {
     core: &'sender ChannelCore<Waker>,
@@ -1854,6 +6039,18 @@ struct SendRefFuture<'sender, T, R> {
     waiter: queue::Waiter<Waker>,
 }
 
+/// Waits for the messages sent before it was created to be received.
+#[cfg(feature = "seq")]
+#[pin_project::pin_project(PinnedDrop)]
+struct FlushFuture<'a> {
+    core: &'a ChannelCore<Waker>,
+    /// The sequence number of the first message that need not be received.
+    target: usize,
+    state: State,
+    #[pin]
+    waiter: queue::Waiter<Waker>,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum State {
     Start,
@@ -1861,6 +6058,13 @@ enum State {
     Done,
 }
 
+/// Waits for the next item from a stream.
+///
+/// This is equivalent to `StreamExt::next`, without depending on
+/// `futures-util`.
+#[cfg(feature = "futures-core")]
+struct NextItem<'a, S>(&'a mut S);
+
 // === impl RecvRefFuture ===
 
 #[inline]
@@ -1873,7 +6077,9 @@ fn poll_recv_ref<'a, T>(
         .map(|some| {
             some.map(|slot| {
                 RecvRef(RecvRefInner {
-                    _notify: super::NotifyTx(&core.tx_wait),
+                    _notify: super::NotifyTx(core),
+                    _audit: core.ref_audit.recv(),
+                    _watch: RefWatch::recv(),
                     slot,
                 })
             })
@@ -1902,6 +6108,41 @@ where
     }
 }
 
+// === impl PeekFuture ===
+
+#[inline]
+fn poll_peek<'a, T>(
+    core: &'a ChannelCore<Waker>,
+    slots: &'a [Slot<T>],
+    cx: &mut Context<'_>,
+) -> Poll<Option<&'a T>> {
+    // Safety: the `Receiver` is mutably borrowed for `'a`, so no messages can
+    // be received while the reference exists.
+    unsafe { core.poll_peek(slots, || cx.waker().clone()) }
+}
+
+impl<'a, T> Future for PeekFuture<'a, T> {
+    type Output = Option<&'a T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        poll_peek(self.core, self.slots, cx)
+    }
+}
+
+// === impl NextItem ===
+
+#[cfg(feature = "futures-core")]
+impl<S> Future for NextItem<'_, S>
+where
+    S: futures_core::Stream + Unpin,
+{
+    type Output = Option<S::Item>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.0).poll_next(cx)
+    }
+}
+
 // === impl SendRefFuture ===
 
 impl<'sender, T, R> Future for SendRefFuture<'sender, T, R>
@@ -1911,70 +6152,180 @@ where
 {
     type Output = Result<SendRef<'sender, T>, Closed>;
 
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         test_println!("SendRefFuture::poll({:p})", self);
+        let this = self.project();
+        poll_send_ref(
+            this.core,
+            this.slots,
+            *this.recycle,
+            this.state,
+            this.waiter,
+            cx,
+        )
+    }
+}
+
+/// Polls to reserve a slot in the channel, registering `node` in the channel's
+/// wait queue if it is full.
+///
+/// This is shared by [`SendRefFuture`] and [`SinkWith`], which both store the
+/// wait state and a pinned [`queue::Waiter`] across polls.
+fn poll_send_ref<'a, T, R: Recycle<T>>(
+    core: &'a ChannelCore<Waker>,
+    slots: &'a [Slot<T>],
+    recycle: &R,
+    state: &mut State,
+    mut node: Pin<&mut queue::Waiter<Waker>>,
+    cx: &mut Context<'_>,
+) -> Poll<Result<SendRef<'a, T>, Closed>> {
+    loop {
+        match test_dbg!(*state) {
+            State::Start => {
+                match core.try_send_ref_spinning(slots, recycle) {
+                    Ok(slot) => return Poll::Ready(Ok(SendRef(slot))),
+                    Err(TrySendError::Closed(_)) => return Poll::Ready(Err(Closed(()))),
+                    Err(_) => {}
+                }
+
+                let start_wait = core.tx_wait.start_wait(node.as_mut(), cx.waker());
+
+                match test_dbg!(start_wait) {
+                    WaitResult::Closed => {
+                        // the channel closed while we were registering the waiter!
+                        *state = State::Done;
+                        return Poll::Ready(Err(Closed(())));
+                    }
+                    WaitResult::Wait => {
+                        // okay, we are now queued to wait.
+                        // gotosleep!
+                        *state = State::Waiting;
+                        return Poll::Pending;
+                    }
+                    WaitResult::Notified => continue,
+                }
+            }
+            State::Waiting => {
+                let continue_wait = core.tx_wait.continue_wait(node.as_mut(), cx.waker());
+
+                match test_dbg!(continue_wait) {
+                    WaitResult::Closed => {
+                        *state = State::Done;
+                        return Poll::Ready(Err(Closed(())));
+                    }
+                    WaitResult::Wait => return Poll::Pending,
+                    WaitResult::Notified => {
+                        *state = State::Done;
+                    }
+                }
+            }
+            State::Done => match core.try_send_ref(slots, recycle) {
+                Ok(slot) => return Poll::Ready(Ok(SendRef(slot))),
+                Err(TrySendError::Closed(_)) => return Poll::Ready(Err(Closed(()))),
+                Err(_) => {
+                    *state = State::Start;
+                }
+            },
+        }
+    }
+}
+
+#[pin_project::pinned_drop]
+impl<T, R> PinnedDrop for SendRefFuture<'_, T, R> {
+    fn drop(self: Pin<&mut Self>) {
+        test_println!("SendRefFuture::drop({:p})", self);
+        let this = self.project();
+        if test_dbg!(*this.state) == State::Waiting && test_dbg!(this.waiter.is_linked()) {
+            this.waiter.remove(&this.core.tx_wait)
+        }
+    }
+}
+
+// === impl FlushFuture ===
+
+#[cfg(feature = "seq")]
+impl<'a> FlushFuture<'a> {
+    fn new(core: &'a ChannelCore<Waker>) -> Self {
+        Self {
+            core,
+            target: core.core.published_seq(),
+            state: State::Start,
+            waiter: queue::Waiter::new(),
+        }
+    }
+}
+
+#[cfg(feature = "seq")]
+impl Future for FlushFuture<'_> {
+    type Output = Result<(), Closed>;
 
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        test_println!("FlushFuture::poll({:p})", self);
+        let mut this = self.project();
         loop {
-            let this = self.as_mut().project();
-            let node = this.waiter;
             match test_dbg!(*this.state) {
                 State::Start => {
-                    match this.core.try_send_ref(this.slots, *this.recycle) {
-                        Ok(slot) => return Poll::Ready(Ok(SendRef(slot))),
-                        Err(TrySendError::Closed(_)) => return Poll::Ready(Err(Closed(()))),
-                        Err(_) => {}
+                    if this.core.is_flushed(*this.target) {
+                        return Poll::Ready(Ok(()));
                     }
-
-                    let start_wait = this.core.tx_wait.start_wait(node, cx.waker());
-
-                    match test_dbg!(start_wait) {
-                        WaitResult::Closed => {
-                            // the channel closed while we were registering the waiter!
-                            *this.state = State::Done;
-                            return Poll::Ready(Err(Closed(())));
-                        }
+                    match test_dbg!(this
+                        .core
+                        .flush_wait
+                        .start_wait(this.waiter.as_mut(), cx.waker()))
+                    {
+                        WaitResult::Closed => *this.state = State::Done,
+                        // We were just added to the queue, so check again
+                        // before returning `Pending`, in case the last message
+                        // was received before the receiver could see us in the
+                        // queue. This fence pairs with the one in
+                        // `notify_all_waiting`.
                         WaitResult::Wait => {
-                            // okay, we are now queued to wait.
-                            // gotosleep!
                             *this.state = State::Waiting;
+                            test_dbg!(atomic::fence(Ordering::SeqCst));
+                            if this.core.is_flushed(*this.target) {
+                                if this.waiter.is_linked() {
+                                    this.waiter.as_mut().remove(&this.core.flush_wait);
+                                }
+                                *this.state = State::Done;
+                                return Poll::Ready(Ok(()));
+                            }
                             return Poll::Pending;
                         }
-                        WaitResult::Notified => continue,
+                        // A message was received; check whether it was the
+                        // last one we are waiting for.
+                        WaitResult::Notified => {}
                     }
                 }
                 State::Waiting => {
-                    let continue_wait = this.core.tx_wait.continue_wait(node, cx.waker());
-
-                    match test_dbg!(continue_wait) {
-                        WaitResult::Closed => {
-                            *this.state = State::Done;
-                            return Poll::Ready(Err(Closed(())));
-                        }
+                    match test_dbg!(this
+                        .core
+                        .flush_wait
+                        .continue_wait(this.waiter.as_mut(), cx.waker()))
+                    {
+                        WaitResult::Closed => *this.state = State::Done,
                         WaitResult::Wait => return Poll::Pending,
-                        WaitResult::Notified => {
-                            *this.state = State::Done;
-                        }
+                        WaitResult::Notified => *this.state = State::Start,
                     }
                 }
-                State::Done => match this.core.try_send_ref(this.slots, *this.recycle) {
-                    Ok(slot) => return Poll::Ready(Ok(SendRef(slot))),
-                    Err(TrySendError::Closed(_)) => return Poll::Ready(Err(Closed(()))),
-                    Err(_) => {
-                        *this.state = State::Start;
-                    }
-                },
+                // The receiver was dropped, but it may have received every
+                // message we were waiting for first.
+                State::Done if this.core.is_flushed(*this.target) => {
+                    return Poll::Ready(Ok(()));
+                }
+                State::Done => return Poll::Ready(Err(Closed(()))),
             }
         }
     }
 }
 
+#[cfg(feature = "seq")]
 #[pin_project::pinned_drop]
-impl<T, R> PinnedDrop for SendRefFuture<'_, T, R> {
+impl PinnedDrop for FlushFuture<'_> {
     fn drop(self: Pin<&mut Self>) {
-        test_println!("SendRefFuture::drop({:p})", self);
+        test_println!("FlushFuture::drop({:p})", self);
         let this = self.project();
         if test_dbg!(*this.state) == State::Waiting && test_dbg!(this.waiter.is_linked()) {
-            this.waiter.remove(&this.core.tx_wait)
+            this.waiter.remove(&this.core.flush_wait)
         }
     }
 }
@@ -1987,6 +6338,36 @@ mod tests {
     fn _assert_sync<T: Sync>(_: T) {}
     fn _assert_send<T: Send>(_: T) {}
 
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    async fn next_timeout_item_is_debug_without_debug_messages() {
+        #[derive(Default, Clone)]
+        struct NotDebug;
+
+        let (_tx, rx) = channel::<NotDebug>(4);
+        let mut items = rx.timeout_items(Duration::from_secs(1));
+        let debug = format!("{:?}", items.next());
+        assert!(
+            debug.starts_with("NextTimeoutItem { items: TimeoutItems { core: "),
+            "unexpected Debug output: {}",
+            debug
+        );
+        assert!(
+            debug.contains("timeout: 1s"),
+            "unexpected Debug output: {}",
+            debug
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "seq", not(loom)))]
+    fn recv_without_flusher_leaves_flush_wait_idle() {
+        let (tx, rx) = channel::<usize>(4);
+        tx.try_send(1).unwrap();
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert!(tx.inner.core.flush_wait.is_idle());
+    }
+
     #[test]
     fn recv_ref_future_is_send() {
         fn _compiles() {
@@ -0,0 +1,321 @@
+//! [`AsyncRead`] and [`AsyncWrite`] over channels of byte buffers.
+//!
+//! A [`ChannelWriter`] wraps the [`Sender`] half of a channel of `Vec<u8>`s,
+//! and implements Tokio's [`AsyncWrite`] by copying each write into a slot in
+//! the channel. A [`ChannelReader`] wraps the [`Receiver`] half, and
+//! implements [`AsyncRead`] by reading from the buffers it receives. Because
+//! the buffers stay in the channel's slots and are [recycled] between
+//! messages, a pipe built from these types does not allocate once its
+//! buffers have grown to the size of the writes passing through it.
+//!
+//! The [`duplex`] function returns a pair of connected [`DuplexStream`]s,
+//! each of which implements both traits, as an in-memory alternative to a
+//! socket.
+//!
+//! Shutting down a [`ChannelWriter`] drops its sender, so once every sender
+//! for the channel has been dropped, the reader observes the end of the
+//! stream after reading any data that is still in the channel. Writing to a
+//! channel whose reader has been dropped fails with
+//! [`io::ErrorKind::BrokenPipe`].
+//!
+//! This module requires the "tokio-io" feature flag.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::io::duplex;
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (mut client, mut server) = duplex(8);
+//!
+//!     client.write_all(b"ping").await.unwrap();
+//!     let mut buf = [0; 4];
+//!     server.read_exact(&mut buf).await.unwrap();
+//!     assert_eq!(&buf, b"ping");
+//!
+//!     server.write_all(b"pong").await.unwrap();
+//!     // Shutting down the server's writer ends the client's stream.
+//!     server.shutdown().await.unwrap();
+//!
+//!     let mut response = Vec::new();
+//!     client.read_to_end(&mut response).await.unwrap();
+//!     assert_eq!(response, b"pong");
+//! }
+//! ```
+//!
+//! [recycled]: crate::recycling
+use super::{channel, poll_send_ref, Receiver, Sender, State};
+use crate::{
+    recycling::{self, Recycle},
+    wait::queue,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    cmp, fmt, mem,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Implements [`AsyncWrite`] by sending each write as a message on a channel
+/// of byte buffers.
+///
+/// See the [module-level documentation](self) for details.
+pub struct ChannelWriter<R = recycling::DefaultRecycle> {
+    /// The sender, or `None` once the writer has been shut down.
+    tx: Option<Sender<Vec<u8>, R>>,
+    state: State,
+    /// Boxed, so that the writer is `Unpin`.
+    waiter: Pin<Box<queue::Waiter<Waker>>>,
+}
+
+/// Implements [`AsyncRead`] by reading the messages received from a channel
+/// of byte buffers.
+///
+/// See the [module-level documentation](self) for details.
+pub struct ChannelReader<R = recycling::DefaultRecycle> {
+    rx: Receiver<Vec<u8>, R>,
+    /// A message that did not fit in the caller's buffer, swapped out of its
+    /// slot so that the slot can be reused.
+    buf: Vec<u8>,
+    /// How much of `buf` has been read.
+    pos: usize,
+}
+
+/// One end of an in-memory duplex pipe, created by [`duplex`].
+///
+/// Data written to one end can be read from the other.
+pub struct DuplexStream {
+    writer: ChannelWriter,
+    reader: ChannelReader,
+}
+
+/// Returns a pair of connected [`DuplexStream`]s, each of which has space for
+/// `capacity` unread writes from the other.
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+#[must_use]
+pub fn duplex(capacity: usize) -> (DuplexStream, DuplexStream) {
+    let (a_tx, a_rx) = channel(capacity);
+    let (b_tx, b_rx) = channel(capacity);
+    let a = DuplexStream {
+        writer: ChannelWriter::new(a_tx),
+        reader: ChannelReader::new(b_rx),
+    };
+    let b = DuplexStream {
+        writer: ChannelWriter::new(b_tx),
+        reader: ChannelReader::new(a_rx),
+    };
+    (a, b)
+}
+
+fn broken_pipe() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "channel closed")
+}
+
+// === impl ChannelWriter ===
+
+impl<R> ChannelWriter<R> {
+    /// Returns a new `ChannelWriter` that sends to `tx`.
+    #[must_use]
+    pub fn new(tx: Sender<Vec<u8>, R>) -> Self {
+        Self {
+            tx: Some(tx),
+            state: State::Start,
+            waiter: Box::pin(queue::Waiter::new()),
+        }
+    }
+
+    /// Returns a reference to the [`Sender`] this writer sends to, or `None`
+    /// if the writer has been shut down.
+    pub fn get_ref(&self) -> Option<&Sender<Vec<u8>, R>> {
+        self.tx.as_ref()
+    }
+
+    /// Stops waiting for capacity, if this writer is waiting.
+    fn cancel_wait(&mut self) {
+        if let Some(ref tx) = self.tx {
+            if test_dbg!(self.state) == State::Waiting && test_dbg!(self.waiter.is_linked()) {
+                self.waiter.as_mut().remove(&tx.inner.core.tx_wait)
+            }
+        }
+        self.state = State::Start;
+    }
+}
+
+impl<R: Recycle<Vec<u8>>> AsyncWrite for ChannelWriter<R> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let inner = match this.tx {
+            Some(ref tx) => &*tx.inner,
+            None => return Poll::Ready(Err(broken_pipe())),
+        };
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let res = match poll_send_ref(
+            &inner.core,
+            inner.slots.as_ref(),
+            &inner.recycle,
+            &mut this.state,
+            this.waiter.as_mut(),
+            cx,
+        ) {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+        this.state = State::Start;
+
+        let mut slot = res.map_err(|_| broken_pipe())?;
+        slot.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Every write is visible to the reader as soon as it completes.
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        this.cancel_wait();
+        // Dropping the last sender closes the channel.
+        this.tx = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<R> Drop for ChannelWriter<R> {
+    fn drop(&mut self) {
+        self.cancel_wait();
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for ChannelWriter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChannelWriter")
+            .field("tx", &self.tx)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+// === impl ChannelReader ===
+
+impl<R> ChannelReader<R> {
+    /// Returns a new `ChannelReader` that reads the messages received by
+    /// `rx`.
+    #[must_use]
+    pub fn new(rx: Receiver<Vec<u8>, R>) -> Self {
+        Self {
+            rx,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Returns a reference to the [`Receiver`] this reader receives from.
+    pub fn get_ref(&self) -> &Receiver<Vec<u8>, R> {
+        &self.rx
+    }
+}
+
+impl<R> AsyncRead for ChannelReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        out: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if out.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        while this.pos == this.buf.len() {
+            let mut msg = match this.rx.poll_recv_ref(cx) {
+                Poll::Ready(Some(msg)) => msg,
+                // The channel has closed, so this is the end of the stream.
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            };
+            if msg.len() <= out.remaining() {
+                if msg.is_empty() {
+                    // Returning without reading anything would signal the
+                    // end of the stream.
+                    continue;
+                }
+                out.put_slice(&msg);
+                return Poll::Ready(Ok(()));
+            }
+
+            // Keep the rest of the message for the next read, and leave the
+            // previous buffer in the slot to be recycled.
+            this.buf.clear();
+            mem::swap(&mut this.buf, &mut *msg);
+            this.pos = 0;
+        }
+
+        let n = cmp::min(out.remaining(), this.buf.len() - this.pos);
+        out.put_slice(&this.buf[this.pos..this.pos + n]);
+        this.pos += n;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for ChannelReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChannelReader")
+            .field("rx", &self.rx)
+            .field("buffered", &(self.buf.len() - self.pos))
+            .finish()
+    }
+}
+
+// === impl DuplexStream ===
+
+impl AsyncRead for DuplexStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        out: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().reader).poll_read(cx, out)
+    }
+}
+
+impl AsyncWrite for DuplexStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().writer).poll_shutdown(cx)
+    }
+}
+
+impl fmt::Debug for DuplexStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DuplexStream")
+            .field("writer", &self.writer)
+            .field("reader", &self.reader)
+            .finish()
+    }
+}
@@ -0,0 +1,210 @@
+//! Splitting the messages received from a channel of `String`s into lines.
+use super::{Receiver, RecvRef};
+use alloc::string::String;
+use core::fmt;
+
+/// An asynchronous stream of the lines of text received from a channel of
+/// `String`s.
+///
+/// This is returned by [`Receiver::lines`]. See its documentation for
+/// details.
+pub struct Lines<'rx, R> {
+    rx: &'rx Receiver<String, R>,
+    /// The message being split into lines, and the offset of the first byte
+    /// of it that has not been returned yet.
+    current: Option<(RecvRef<'rx, String>, usize)>,
+    /// The start of a line that continues in a later message.
+    partial: String,
+    /// Whether the last line returned was `partial`, which must be cleared
+    /// before the next line is split.
+    returned_partial: bool,
+}
+
+/// Where a complete line is buffered.
+enum Line {
+    /// The line is `current[start..end]`.
+    Current { start: usize, end: usize },
+    /// The line was reassembled in `partial`.
+    Partial,
+}
+
+// === impl Receiver ===
+
+impl<R> Receiver<String, R> {
+    /// Returns an asynchronous stream of the lines of text received from the
+    /// channel.
+    ///
+    /// Messages are treated as chunks of a single stream of text, which are
+    /// split into lines at each `\n`. A line may span several messages,
+    /// and a message may contain several lines. As with
+    /// [`str::lines`], the line ending, which is either `\n` or `\r\n`, is
+    /// not included in the returned lines. Once the channel closes, any text
+    /// after the last line ending is returned as the final line.
+    ///
+    /// [`Lines::next_line`] returns each line by reference. A line that
+    /// falls within a single message borrows it directly from that message's
+    /// slot, so it is not copied. Only a line that spans several messages is
+    /// copied, into a buffer owned by the [`Lines`], which is reused for
+    /// every such line. Each message's slot is released once every line
+    /// that starts in it has been returned, so that its buffer can be
+    /// [recycled].
+    ///
+    /// When the "futures-core" feature flag is enabled, [`Lines`] also
+    /// implements [`Stream`], yielding each line as an owned `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let (tx, rx) = mpsc::channel::<String>(8);
+    ///
+    ///     tokio::spawn(async move {
+    ///         // Writes need not line up with lines.
+    ///         for chunk in ["GET / HT", "TP/1.1\r\nHost: exa", "mple.com\r\n\r\n", "trailing"] {
+    ///             tx.send(chunk.to_string()).await.unwrap();
+    ///         }
+    ///     });
+    ///
+    ///     let mut lines = rx.lines();
+    ///     assert_eq!(lines.next_line().await, Some("GET / HTTP/1.1"));
+    ///     assert_eq!(lines.next_line().await, Some("Host: example.com"));
+    ///     assert_eq!(lines.next_line().await, Some(""));
+    ///     assert_eq!(lines.next_line().await, Some("trailing"));
+    ///     assert_eq!(lines.next_line().await, None);
+    /// }
+    /// ```
+    ///
+    /// [recycled]: crate::recycling
+    /// [`Stream`]: futures_core::Stream
+    pub fn lines(&self) -> Lines<'_, R> {
+        Lines {
+            rx: self,
+            current: None,
+            partial: String::new(),
+            returned_partial: false,
+        }
+    }
+}
+
+// === impl Lines ===
+
+impl<'rx, R> Lines<'rx, R> {
+    /// Waits for the next complete line.
+    ///
+    /// Returns `None` once the channel has closed, and every line received
+    /// from it has been returned.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. If the returned future is dropped before
+    /// it completes, no text is lost, and the next call returns the line
+    /// that it would have returned.
+    pub async fn next_line(&mut self) -> Option<&str> {
+        let line = loop {
+            if let Some(line) = self.split_line() {
+                break line;
+            }
+            match self.rx.recv_ref().await {
+                Some(msg) => self.current = Some((msg, 0)),
+                None => break self.finish()?,
+            }
+        };
+        Some(self.line(line))
+    }
+
+    /// Splits the next complete line from the current message, if there is
+    /// one. Otherwise, moves the rest of the current message to the partial
+    /// line, and releases it.
+    fn split_line(&mut self) -> Option<Line> {
+        if self.returned_partial {
+            self.partial.clear();
+            self.returned_partial = false;
+        }
+
+        let (msg, pos) = self.current.as_mut()?;
+        match msg[*pos..].find('\n') {
+            Some(len) => {
+                let (start, end) = (*pos, *pos + len);
+                *pos = end + 1;
+                if self.partial.is_empty() {
+                    return Some(Line::Current { start, end });
+                }
+                self.partial.push_str(&msg[start..end]);
+                self.returned_partial = true;
+                Some(Line::Partial)
+            }
+            None => {
+                self.partial.push_str(&msg[*pos..]);
+                self.current = None;
+                None
+            }
+        }
+    }
+
+    /// Returns the text after the last line ending as the final line, once
+    /// the channel has closed.
+    fn finish(&mut self) -> Option<Line> {
+        if self.partial.is_empty() {
+            return None;
+        }
+        self.returned_partial = true;
+        Some(Line::Partial)
+    }
+
+    fn line(&self, line: Line) -> &str {
+        let line = match line {
+            Line::Current { start, end } => {
+                let (msg, _) = self
+                    .current
+                    .as_ref()
+                    .expect("a line in the current message requires a current message");
+                &msg[start..end]
+            }
+            Line::Partial => &self.partial[..],
+        };
+        line.strip_suffix('\r').unwrap_or(line)
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<R> futures_core::Stream for Lines<'_, R> {
+    type Item = String;
+
+    fn poll_next(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Option<String>> {
+        use core::task::Poll;
+
+        let this = &mut *self;
+        let line = loop {
+            if let Some(line) = this.split_line() {
+                break line;
+            }
+            match this.rx.poll_recv_ref(cx) {
+                Poll::Ready(Some(msg)) => this.current = Some((msg, 0)),
+                Poll::Ready(None) => match this.finish() {
+                    Some(line) => break line,
+                    None => return Poll::Ready(None),
+                },
+                Poll::Pending => return Poll::Pending,
+            }
+        };
+        Poll::Ready(Some(this.line(line).into()))
+    }
+}
+
+impl<R> fmt::Debug for Lines<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lines")
+            .field(
+                "current",
+                &self.current.as_ref().map(|(msg, pos)| &msg[*pos..]),
+            )
+            .field("partial", &self.partial)
+            .finish_non_exhaustive()
+    }
+}
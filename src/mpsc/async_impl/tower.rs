@@ -0,0 +1,649 @@
+//! A [`tower`] middleware that buffers requests to a service in a channel.
+//!
+//! [`ThingbufBuffer`] is an alternative to `tower::buffer::Buffer`. It allows
+//! a [`Service`] that must be driven by a single task to be shared by many
+//! tasks: each clone of a `ThingbufBuffer` sends its requests to a bounded
+//! channel, and a [`Worker`] task receives them and dispatches them to the
+//! inner service, in the order they were sent.
+//!
+//! The channel's slots are allocated once, when the buffer is created, and
+//! each request is written directly into a reserved slot along with the
+//! handle used to return its response. Unlike `tower::buffer`, the queue
+//! itself does not allocate as requests pass through it.
+//!
+//! Like other channels, a `ThingbufBuffer`'s channel may only hold messages
+//! that are [`Sync`], so requests must be `Send + Sync`.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{convert::Infallible, future::{ready, Ready}, task::{Context, Poll}};
+//! use thingbuf::mpsc::tower::ThingbufBuffer;
+//! use tower_service::Service;
+//!
+//! struct Double;
+//!
+//! impl Service<u32> for Double {
+//!     type Response = u32;
+//!     type Error = Infallible;
+//!     type Future = Ready<Result<u32, Infallible>>;
+//!
+//!     fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+//!         Poll::Ready(Ok(()))
+//!     }
+//!
+//!     fn call(&mut self, req: u32) -> Self::Future {
+//!         ready(Ok(req * 2))
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     // Spawns a worker task that drives the `Double` service.
+//!     let mut svc = ThingbufBuffer::new(Double, 8);
+//!
+//!     futures_util::future::poll_fn(|cx| svc.poll_ready(cx)).await.unwrap();
+//!     assert_eq!(svc.call(21).await.unwrap(), 42);
+//! }
+//! ```
+//!
+//! [`tower`]: https://docs.rs/tower
+use super::{Receiver, Sender, State};
+use crate::{
+    mpsc::errors::{Closed, TrySendError},
+    recycling::Recycle,
+    util::mutex::Mutex,
+    wait::{queue, WaitCell, WaitResult},
+};
+use alloc::{boxed::Box, sync::Arc};
+use core::{
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// An error returned by a [`ThingbufBuffer`].
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Buffers requests to the inner service `S` in a channel.
+///
+/// See the [module-level documentation](self) for details.
+pub struct ThingbufBuffer<S, Req>
+where
+    S: Service<Req>,
+{
+    tx: Sender<Message<Req, S::Future>, MessageRecycle>,
+    state: State,
+    waiter: Pin<Box<queue::Waiter<Waker>>>,
+    /// Set when `poll_ready` has observed a free slot for the next call.
+    ready: bool,
+}
+
+/// Receives requests from a [`ThingbufBuffer`] and dispatches them to the
+/// inner service.
+///
+/// This future completes once every [`ThingbufBuffer`] sending to it has
+/// been dropped. It is returned by [`ThingbufBuffer::pair`], and must be
+/// spawned or otherwise polled for the buffer's requests to make progress.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[pin_project::pin_project]
+pub struct Worker<S, Req>
+where
+    S: Service<Req>,
+{
+    rx: Receiver<Message<Req, S::Future>, MessageRecycle>,
+    service: S,
+    /// A received request waiting for the service to become ready.
+    current: Option<(Req, Responder<S::Future>)>,
+    failed: Option<ServiceError>,
+}
+
+/// Future returned by [`ThingbufBuffer::call`], which completes with the
+/// inner service's response.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[pin_project::pin_project]
+pub struct ResponseFuture<F> {
+    /// Sends the request, if the channel was full when it was called.
+    send: Option<SendFuture>,
+    #[pin]
+    state: ResponseState<F>,
+}
+
+type SendFuture = Pin<Box<dyn Future<Output = Result<(), Closed>> + Send>>;
+
+/// A [`Layer`] that wraps services in a [`ThingbufBuffer`].
+pub struct ThingbufBufferLayer<Req> {
+    capacity: usize,
+    _p: PhantomData<fn(Req)>,
+}
+
+/// The error returned for every request once the inner service has failed.
+///
+/// Once the inner service's `poll_ready` returns an error, the [`Worker`]
+/// fails every subsequent request with a `ServiceError` wrapping that error.
+#[derive(Clone)]
+pub struct ServiceError {
+    inner: Arc<BoxError>,
+}
+
+struct Message<Req, F> {
+    request: Option<Req>,
+    responder: Option<Responder<F>>,
+}
+
+/// Empties message slots, without requiring requests to be `Default`.
+#[derive(Debug, Default)]
+struct MessageRecycle;
+
+#[pin_project::pin_project(project = ResponseStateProj)]
+enum ResponseState<F> {
+    Waiting { rx: Response<F> },
+    Running(#[pin] F),
+    Failed(Option<BoxError>),
+}
+
+/// The sending half of the one-shot cell used to return the inner service's
+/// response future to the caller.
+struct Responder<F>(Arc<Shared<F>>);
+
+struct Response<F>(Arc<Shared<F>>);
+
+struct Shared<F> {
+    value: Mutex<Option<Result<F, BoxError>>>,
+    rx_wait: WaitCell<Waker>,
+}
+
+// === impl ThingbufBuffer ===
+
+impl<S, Req> ThingbufBuffer<S, Req>
+where
+    S: Service<Req>,
+    S::Error: Into<BoxError>,
+{
+    /// Returns a new `ThingbufBuffer` wrapping `service`, with space for
+    /// `capacity` requests, and spawns its [`Worker`] on the current Tokio
+    /// runtime.
+    ///
+    /// # Panics
+    ///
+    /// - If `capacity` is 0.
+    /// - If called outside of a Tokio runtime.
+    pub fn new(service: S, capacity: usize) -> Self
+    where
+        S: Send + 'static,
+        S::Future: Send + 'static,
+        Req: Send + Sync + 'static,
+    {
+        let (buffer, worker) = Self::pair(service, capacity);
+        tokio::spawn(worker);
+        buffer
+    }
+
+    /// Returns a new `ThingbufBuffer` wrapping `service`, with space for
+    /// `capacity` requests, and the [`Worker`] that drives it.
+    ///
+    /// The worker must be spawned or otherwise polled for the buffer's
+    /// requests to make progress.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0.
+    pub fn pair(service: S, capacity: usize) -> (Self, Worker<S, Req>) {
+        assert!(capacity > 0, "a buffer must have capacity");
+        let (tx, rx) = super::with_recycle(capacity, MessageRecycle);
+        let buffer = Self {
+            tx,
+            state: State::Start,
+            waiter: Box::pin(queue::Waiter::new()),
+            ready: false,
+        };
+        let worker = Worker {
+            rx,
+            service,
+            current: None,
+            failed: None,
+        };
+        (buffer, worker)
+    }
+}
+
+impl<S, Req> ThingbufBuffer<S, Req>
+where
+    S: Service<Req>,
+{
+    /// Returns the total number of requests that can be buffered.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.tx.capacity()
+    }
+
+    /// Returns the number of requests waiting for the [`Worker`].
+    pub fn len(&self) -> usize {
+        self.tx.len()
+    }
+
+    /// Returns `true` if no requests are waiting for the [`Worker`].
+    pub fn is_empty(&self) -> bool {
+        self.tx.is_empty()
+    }
+}
+
+impl<S, Req> Service<Req> for ThingbufBuffer<S, Req>
+where
+    S: Service<Req>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+    Req: Send + Sync + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    /// Waits until the channel has a free slot for the next request.
+    ///
+    /// A `ThingbufBuffer` and its clones share the channel's capacity, so a
+    /// clone may take the free slot before [`call`](Self::call) is called.
+    /// In that case, the returned [`ResponseFuture`] waits for the next free
+    /// slot before waiting for the response.
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        if self.ready {
+            return Poll::Ready(Ok(()));
+        }
+
+        let tx_wait = &self.tx.inner.core.tx_wait;
+        loop {
+            match test_dbg!(self.state) {
+                State::Start | State::Done => {
+                    if !self.tx.receiver_alive() {
+                        return Poll::Ready(Err(Closed(()).into()));
+                    }
+                    if self.tx.remaining() > 0 {
+                        self.state = State::Start;
+                        self.ready = true;
+                        return Poll::Ready(Ok(()));
+                    }
+
+                    match test_dbg!(tx_wait.start_wait(self.waiter.as_mut(), cx.waker())) {
+                        WaitResult::Closed => {
+                            self.state = State::Done;
+                            return Poll::Ready(Err(Closed(()).into()));
+                        }
+                        WaitResult::Wait => {
+                            self.state = State::Waiting;
+                            return Poll::Pending;
+                        }
+                        WaitResult::Notified => continue,
+                    }
+                }
+                State::Waiting => {
+                    match test_dbg!(tx_wait.continue_wait(self.waiter.as_mut(), cx.waker())) {
+                        WaitResult::Closed => {
+                            self.state = State::Done;
+                            return Poll::Ready(Err(Closed(()).into()));
+                        }
+                        WaitResult::Wait => return Poll::Pending,
+                        WaitResult::Notified => self.state = State::Done,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes `request` to the channel.
+    ///
+    /// # Panics
+    ///
+    /// If [`poll_ready`](Self::poll_ready) has not returned `Ready(Ok(()))`
+    /// since the last call.
+    fn call(&mut self, request: Req) -> Self::Future {
+        assert!(
+            self.ready,
+            "ThingbufBuffer::call called without waiting for poll_ready"
+        );
+        self.ready = false;
+
+        let (responder, rx) = oneshot();
+        let state = ResponseState::Waiting { rx };
+        match self.tx.try_send_ref() {
+            Ok(mut slot) => {
+                slot.request = Some(request);
+                slot.responder = Some(responder);
+                ResponseFuture { send: None, state }
+            }
+            Err(TrySendError::Full(())) => {
+                test_println!("ThingbufBuffer::call: slot taken by a clone, waiting");
+                let tx = self.tx.clone();
+                let send = Box::pin(async move {
+                    let mut slot = tx.send_ref().await?;
+                    slot.request = Some(request);
+                    slot.responder = Some(responder);
+                    Ok(())
+                });
+                ResponseFuture {
+                    send: Some(send),
+                    state,
+                }
+            }
+            Err(_) => ResponseFuture {
+                send: None,
+                state: ResponseState::Failed(Some(Closed(()).into())),
+            },
+        }
+    }
+}
+
+impl<S, Req> Clone for ThingbufBuffer<S, Req>
+where
+    S: Service<Req>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            state: State::Start,
+            waiter: Box::pin(queue::Waiter::new()),
+            ready: false,
+        }
+    }
+}
+
+impl<S, Req> Drop for ThingbufBuffer<S, Req>
+where
+    S: Service<Req>,
+{
+    fn drop(&mut self) {
+        let tx_wait = &self.tx.inner.core.tx_wait;
+        if test_dbg!(self.state) == State::Waiting && test_dbg!(self.waiter.is_linked()) {
+            self.waiter.as_mut().remove(tx_wait);
+        }
+        if self.ready {
+            // We may have been woken for a free slot that we will never use,
+            // so pass the wakeup on to the next waiting sender.
+            tx_wait.notify();
+        }
+    }
+}
+
+impl<S, Req> fmt::Debug for ThingbufBuffer<S, Req>
+where
+    S: Service<Req>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThingbufBuffer")
+            .field("len", &self.tx.len())
+            .field("capacity", &self.tx.capacity())
+            .field("ready", &self.ready)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+// === impl Worker ===
+
+impl<S, Req> Future for Worker<S, Req>
+where
+    S: Service<Req>,
+    S::Error: Into<BoxError>,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.project();
+        loop {
+            let (request, responder) = match this.current.take() {
+                Some(current) => current,
+                None => match this.rx.poll_recv_ref(cx) {
+                    Poll::Ready(Some(mut msg)) => {
+                        match (msg.request.take(), msg.responder.take()) {
+                            (Some(request), Some(responder)) => (request, responder),
+                            _ => continue,
+                        }
+                    }
+                    // Every `ThingbufBuffer` has been dropped.
+                    Poll::Ready(None) => return Poll::Ready(()),
+                    Poll::Pending => return Poll::Pending,
+                },
+            };
+
+            if responder.is_canceled() {
+                test_println!("Worker: response future dropped, skipping request");
+                continue;
+            }
+
+            if let Some(failed) = this.failed {
+                responder.send(Err(Box::new(failed.clone())));
+                continue;
+            }
+
+            match this.service.poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    let future = this.service.call(request);
+                    responder.send(Ok(future));
+                }
+                Poll::Ready(Err(error)) => {
+                    let error = ServiceError {
+                        inner: Arc::new(error.into()),
+                    };
+                    responder.send(Err(Box::new(error.clone())));
+                    *this.failed = Some(error);
+                }
+                Poll::Pending => {
+                    *this.current = Some((request, responder));
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+impl<S, Req> fmt::Debug for Worker<S, Req>
+where
+    S: Service<Req> + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Worker")
+            .field("service", &self.service)
+            .field("len", &self.rx.len())
+            .field("failed", &self.failed)
+            .finish()
+    }
+}
+
+// === impl ResponseFuture ===
+
+impl<F, T, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<BoxError>,
+{
+    type Output = Result<T, BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        if let Some(send) = this.send.as_mut() {
+            match send.as_mut().poll(cx) {
+                Poll::Ready(Ok(())) => *this.send = None,
+                Poll::Ready(Err(closed)) => {
+                    *this.send = None;
+                    return Poll::Ready(Err(closed.into()));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        loop {
+            match this.state.as_mut().project() {
+                ResponseStateProj::Waiting { rx } => match rx.poll(cx) {
+                    Poll::Ready(Ok(future)) => this.state.set(ResponseState::Running(future)),
+                    Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                    Poll::Pending => return Poll::Pending,
+                },
+                ResponseStateProj::Running(future) => return future.poll(cx).map_err(Into::into),
+                ResponseStateProj::Failed(error) => {
+                    let error = error
+                        .take()
+                        .expect("ResponseFuture polled after completion");
+                    return Poll::Ready(Err(error));
+                }
+            }
+        }
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            ResponseState::Waiting { .. } => "Waiting",
+            ResponseState::Running(_) => "Running",
+            ResponseState::Failed(_) => "Failed",
+        };
+        f.debug_struct("ResponseFuture")
+            .field("sending", &self.send.is_some())
+            .field("state", &state)
+            .finish()
+    }
+}
+
+// === impl ThingbufBufferLayer ===
+
+impl<Req> ThingbufBufferLayer<Req> {
+    /// Returns a new `ThingbufBufferLayer`, which wraps services in a
+    /// [`ThingbufBuffer`] with space for `capacity` requests.
+    ///
+    /// Each wrapped service's [`Worker`] is spawned on the current Tokio
+    /// runtime, as by [`ThingbufBuffer::new`].
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0.
+    #[must_use]
+    pub const fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "a buffer must have capacity");
+        Self {
+            capacity,
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<S, Req> Layer<S> for ThingbufBufferLayer<Req>
+where
+    S: Service<Req> + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+    Req: Send + Sync + 'static,
+{
+    type Service = ThingbufBuffer<S, Req>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        ThingbufBuffer::new(service, self.capacity)
+    }
+}
+
+impl<Req> Clone for ThingbufBufferLayer<Req> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Req> Copy for ThingbufBufferLayer<Req> {}
+
+impl<Req> fmt::Debug for ThingbufBufferLayer<Req> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThingbufBufferLayer")
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+// === impl ServiceError ===
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "buffered service failed: {}", self.inner)
+    }
+}
+
+impl fmt::Debug for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ServiceError").field(&self.inner).finish()
+    }
+}
+
+impl std::error::Error for ServiceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&**self.inner)
+    }
+}
+
+// === impl MessageRecycle ===
+
+impl<Req, F> Recycle<Message<Req, F>> for MessageRecycle {
+    fn new_element(&self) -> Message<Req, F> {
+        Message {
+            request: None,
+            responder: None,
+        }
+    }
+
+    fn recycle(&self, element: &mut Message<Req, F>) {
+        element.request = None;
+        element.responder = None;
+    }
+}
+
+// === impl Responder ===
+
+fn oneshot<F>() -> (Responder<F>, Response<F>) {
+    let shared = Arc::new(Shared {
+        value: Mutex::new(None),
+        rx_wait: WaitCell::new(),
+    });
+    (Responder(shared.clone()), Response(shared))
+}
+
+impl<F> Responder<F> {
+    fn send(self, value: Result<F, BoxError>) {
+        *self.0.value.lock() = Some(value);
+        // dropping the responder wakes the response future.
+    }
+
+    fn is_canceled(&self) -> bool {
+        Arc::strong_count(&self.0) == 1
+    }
+}
+
+impl<F> Drop for Responder<F> {
+    fn drop(&mut self) {
+        self.0.rx_wait.close_tx();
+    }
+}
+
+impl<F> Response<F> {
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Result<F, BoxError>> {
+        loop {
+            if let Some(value) = self.0.value.lock().take() {
+                return Poll::Ready(value);
+            }
+
+            match test_dbg!(self.0.rx_wait.wait_with(|| cx.waker().clone())) {
+                WaitResult::Wait => {
+                    // the response may have been sent while we were registering.
+                    if let Some(value) = self.0.value.lock().take() {
+                        return Poll::Ready(value);
+                    }
+                    return Poll::Pending;
+                }
+                WaitResult::Notified => core::hint::spin_loop(),
+                WaitResult::Closed => {
+                    // the worker dropped the request without responding.
+                    let value = self.0.value.lock().take();
+                    return Poll::Ready(value.unwrap_or_else(|| Err(Closed(()).into())));
+                }
+            }
+        }
+    }
+}
@@ -8,17 +8,51 @@ use crate::{
     loom::{
         atomic::{self, Ordering},
         sync::Arc,
-        thread::{self, Thread},
+        thread,
     },
     recycling::{self, Recycle},
     util::Backoff,
     wait::queue,
     MAX_CAPACITY,
 };
-use core::{fmt, pin::Pin};
+use core::{cmp, fmt, mem, ops, pin::Pin, ptr};
 use errors::*;
+use park::Unparker;
 use std::time::{Duration, Instant};
 
+#[cfg(not(all(loom, test)))]
+pub mod budget;
+
+#[cfg(not(all(loom, test)))]
+pub mod elastic;
+
+#[cfg(all(feature = "eventfd", target_os = "linux", not(all(loom, test))))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "eventfd", target_os = "linux"))))]
+pub mod eventfd;
+
+#[cfg(not(all(loom, test)))]
+pub mod intercept;
+
+#[cfg(not(all(loom, test)))]
+pub mod pipeline;
+#[cfg(not(all(loom, test)))]
+pub use self::pipeline::pipeline;
+
+pub mod park;
+
+#[cfg(not(all(loom, test)))]
+pub mod rewind;
+
+#[cfg(all(feature = "crossbeam-channel", not(all(loom, test))))]
+#[cfg_attr(docsrs, doc(cfg(feature = "crossbeam-channel")))]
+pub mod select;
+
+#[cfg(not(all(loom, test)))]
+pub mod sharded;
+
+#[cfg(not(all(loom, test)))]
+pub mod ttl;
+
 /// Returns a new synchronous multi-producer, single consumer (MPSC)
 /// channel with  the provided capacity.
 ///
@@ -58,6 +92,62 @@ pub fn with_recycle<T, R: Recycle<T>>(
     (tx, rx)
 }
 
+/// Returns a new synchronous multi-producer, single consumer channel with
+/// the provided capacity and [recycling policy], whose slot array is allocated
+/// by the `place` callback.
+///
+/// `place` is called with the capacity, and must return a new array created by
+/// [`Slot::make_boxed_array`]. This allows controlling *where* the slot array
+/// is allocated, such as on the NUMA node that the sending and receiving
+/// threads run on.
+///
+/// # Panics
+///
+/// - If the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`.
+/// - If `place` returns an array whose length is not `capacity`, or an array
+///   that has already been used.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::{mpsc::blocking, recycling::DefaultRecycle, Slot};
+/// use std::thread;
+///
+/// let (tx, rx) = blocking::with_placement::<usize, _>(8, DefaultRecycle::new(), |capacity| {
+///     // On Linux, memory is placed on the NUMA node of the thread that first
+///     // writes to it, so building the slot array on a thread that is pinned
+///     // to the desired node allocates it on that node.
+///     thread::spawn(move || Slot::make_boxed_array(capacity))
+///         .join()
+///         .unwrap()
+/// });
+///
+/// tx.send(1).unwrap();
+/// assert_eq!(rx.recv(), Some(1));
+/// ```
+///
+/// [recycling policy]: crate::recycling::Recycle
+#[must_use]
+pub fn with_placement<T, R: Recycle<T>>(
+    capacity: usize,
+    recycle: R,
+    place: impl FnOnce(usize) -> Box<[Slot<T>]>,
+) -> (Sender<T, R>, Receiver<T, R>) {
+    assert!(capacity > 0);
+    assert!(capacity <= MAX_CAPACITY);
+    let slots = Slot::place_boxed_array(capacity, place);
+    let inner = Arc::new(Inner {
+        core: ChannelCore::new(capacity),
+        slots,
+        recycle,
+    });
+    let tx = Sender {
+        inner: inner.clone(),
+    };
+    let rx = Receiver { inner };
+    (tx, rx)
+}
+
 /// Synchronously receives values from associated [`Sender`]s.
 ///
 /// Instances of this struct are created by the [`channel`] and
@@ -77,7 +167,7 @@ pub struct Receiver<T, R = recycling::DefaultRecycle> {
 }
 
 struct Inner<T, R> {
-    core: super::ChannelCore<Thread>,
+    core: super::ChannelCore<Unparker>,
     slots: Box<[Slot<T>]>,
     recycle: R,
 }
@@ -118,7 +208,7 @@ feature! {
     ///     // Split the `StaticChannel` into a sender-receiver pair.
     ///     let (tx, rx) = MY_CHANNEL.split();
     ///
-    ///     // Now, `tx` and `rx` can be used just like any other async MPSC
+    ///     // Now, `tx` and `rx` can be used just like any other blocking MPSC
     ///     // channel...
     /// # drop(tx); drop(rx);
     /// }
@@ -127,7 +217,7 @@ feature! {
     /// [async]: crate::mpsc::StaticChannel
     /// [`split`]: StaticChannel::split
     pub struct StaticChannel<T, const CAPACITY: usize, R = recycling::DefaultRecycle> {
-        core: ChannelCore<Thread>,
+        core: ChannelCore<Unparker>,
         slots: [Slot<T>; CAPACITY],
         is_split: AtomicBool,
         recycle: R,
@@ -136,9 +226,9 @@ feature! {
     /// Synchronously sends values to an associated [`StaticReceiver`].
     ///
     /// Instances of this struct are created by the [`StaticChannel::split`] and
-    /// [``StaticChannel::try_split`] functions.
+    /// [`StaticChannel::try_split`] functions.
     pub struct StaticSender<T: 'static, R: 'static = recycling::DefaultRecycle> {
-        core: &'static ChannelCore<Thread>,
+        core: &'static ChannelCore<Unparker>,
         slots: &'static [Slot<T>],
         recycle: &'static R,
     }
@@ -146,9 +236,9 @@ feature! {
     /// Synchronously receives values from associated [`StaticSender`]s.
     ///
     /// Instances of this struct are created by the [`StaticChannel::split`] and
-    /// [``StaticChannel::try_split`] functions.
+    /// [`StaticChannel::try_split`] functions.
     pub struct StaticReceiver<T: 'static, R: 'static = recycling::DefaultRecycle> {
-        core: &'static ChannelCore<Thread>,
+        core: &'static ChannelCore<Unparker>,
         slots: &'static [Slot<T>],
         recycle: &'static R,
     }
@@ -195,6 +285,7 @@ feature! {
         /// [async]: crate::mpsc::StaticChannel
         /// [`split`]: StaticChannel::split
         #[must_use]
+        #[allow(clippy::new_without_default)]
         pub const fn new() -> Self {
             Self {
                 core: ChannelCore::new(CAPACITY),
@@ -249,6 +340,14 @@ feature! {
 
     // === impl StaticSender ===
 
+    #[cfg(feature = "rt-safe")]
+    impl<T, R> StaticSender<T, R> {
+        /// Returns the channel's state, for the [`rt`](super::rt) handles.
+        pub(super) fn parts(&self) -> (&ChannelCore<Unparker>, &[Slot<T>], &R) {
+            (self.core, self.slots, self.recycle)
+        }
+    }
+
     impl<T, R> StaticSender<T, R>
     where
         R: Recycle<T>,
@@ -358,6 +457,56 @@ feature! {
             }
         }
 
+        /// Sends every item produced by `iter` to the channel, blocking the current
+        /// thread whenever the channel is full until capacity becomes available.
+        ///
+        /// Returns the number of items sent. This is equivalent to calling
+        /// [`send`] in a loop, stopping once the channel closes.
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel is dropped before every item has
+        /// been sent, this returns a [`SendAllError`] containing the number of items
+        /// that were sent, the item that could not be sent, and the rest of the
+        /// iterator.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        /// use std::thread;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 4> = StaticChannel::new();
+        ///
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// let producer = thread::spawn(move || {
+        ///     // Blocks whenever the channel is full.
+        ///     tx.send_all(0..10).unwrap()
+        /// });
+        ///
+        /// for i in 0..10 {
+        ///     assert_eq!(rx.recv(), Some(i));
+        /// }
+        /// assert_eq!(producer.join().unwrap(), 10);
+        /// ```
+        ///
+        /// [`send`]: Self::send
+        pub fn send_all<I>(&self, iter: I) -> Result<usize, SendAllError<T, I::IntoIter>>
+        where
+            I: IntoIterator<Item = T>,
+        {
+            let mut iter = iter.into_iter();
+            let mut sent = 0;
+            for item in &mut iter {
+                if let Err(Closed(item)) = self.send(item) {
+                    return Err(SendAllError { sent, item, rest: iter });
+                }
+                sent += 1;
+            }
+            Ok(sent)
+        }
+
         /// Reserves a slot in the channel to mutate in place, blocking until
         /// there is a free slot to write to, waiting for at most `timeout`.
         ///
@@ -433,6 +582,8 @@ feature! {
         ///
         /// - [`Err`]`(`[`SendTimeoutError::Timeout`]`)` if the timeout has elapsed.
         /// - [`Err`]`(`[`SendTimeoutError::Closed`]`)` if the channel has closed.
+        /// - [`Err`]`(`[`SendTimeoutError::Shed`]`)` if the message was dropped
+        ///   by the channel's load shedding.
         ///
         /// # Examples
         ///
@@ -467,6 +618,9 @@ feature! {
         /// [`send_ref_timeout`]: Self::send_ref_timeout
         #[cfg(not(all(test, loom)))]
         pub fn send_timeout(&self, val: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+            if self.core.shed() {
+                return Err(SendTimeoutError::Shed(val));
+            }
             match self.send_ref_timeout(timeout) {
                 Err(e) => Err(e.with_value(val)),
                 Ok(mut slot) => {
@@ -530,13 +684,359 @@ feature! {
         /// [`TrySendError::Closed`]. Once the channel has closed, subsequent
         /// calls to `try_send` will  never succeed.
         ///
-        /// In both cases, the error includes the value passed to `try_send`.
+        /// If the message is dropped by the channel's load shedding (see
+        /// [`StaticReceiver::set_load_shedding`]), [`TrySendError::Shed`] is returned.
+        ///
+        /// In each case, the error includes the value passed to `try_send`.
         ///
         /// [`send`]: Self::send
         pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
             self.core.try_send(self.slots, val, self.recycle)
         }
 
+        /// Sends a message by value if the channel has capacity, or, if it is
+        /// full, merges the message into the most recently queued message by
+        /// calling `f`.
+        ///
+        /// `f` is called with a mutable reference to the queued message, and with
+        /// the new message. This gives producers of cumulative data, such as
+        /// counters or deltas, bounded memory use without losing information:
+        /// rather than waiting for the receiver, or dropping an update, the update
+        /// is folded into a message that the receiver has not yet received.
+        ///
+        /// The message that `f` modifies is the most recently sent message that is
+        /// not at the front of the queue, where the receiver may already be
+        /// receiving it. The receiver cannot receive that message until `f`
+        /// returns. This method never waits for capacity, and messages
+        /// sent with it are never dropped by [load shedding].
+        ///
+        /// # Errors
+        ///
+        /// If the channel is closed, this returns [`TrySendError::Closed`]. If the
+        /// channel is full, and every queued message is still being sent or is at
+        /// the front of the queue, so there is no message to merge into, this returns
+        /// [`TrySendError::Full`]. In both cases, the error includes the value
+        /// passed to `send_or_modify`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<u64, 2> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// for delta in 1..=10 {
+        ///     tx.send_or_modify(delta, |queued, delta| *queued += delta)
+        ///         .unwrap();
+        /// }
+        ///
+        /// // Once the channel filled up, the remaining deltas were added to the
+        /// // last queued message.
+        /// assert_eq!(rx.try_recv().unwrap(), 1);
+        /// assert_eq!(rx.try_recv().unwrap(), 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9 + 10);
+        /// ```
+        ///
+        /// [load shedding]: StaticReceiver::set_load_shedding
+        pub fn send_or_modify<F>(&self, val: T, f: F) -> Result<(), TrySendError<T>>
+        where
+            F: FnOnce(&mut T, T),
+        {
+            self.core.try_send_or_modify(self.slots, val, self.recycle, f)
+        }
+
+        /// Attempts to send a `Copy` message from a Unix signal handler, without
+        /// blocking until capacity is available.
+        ///
+        /// This behaves exactly like [`try_send`], but is restricted to message types
+        /// whose slots can be overwritten without running any destructors, so that it
+        /// is *async-signal-safe*: it may be called from a signal handler to enqueue
+        /// an event, such as a request to shut down or to dump statistics, for the
+        /// application's main loop to handle.
+        ///
+        /// # Async-signal safety
+        ///
+        /// A signal handler may interrupt any code on the thread it runs on,
+        /// including this channel's own [`StaticReceiver`] or another sender, so this
+        /// method:
+        ///
+        /// - **Does not allocate or free memory.** The message is `Copy`, so writing
+        ///   it into a slot does not drop the slot's previous value. The channel's
+        ///   [recycling policy] is still applied to the slot before it is written;
+        ///   the default policy writes `T::default()`, which must not allocate. A
+        ///   custom policy must not allocate either.
+        /// - **Does not take any locks.** Claiming a slot is a lock-free
+        ///   compare-and-swap loop that never waits for a sender or receiver that may
+        ///   have been interrupted. Waking the receiver goes through a lock-free
+        ///   wait cell: if the handler interrupts the receiver while it is
+        ///   registering to wait, the receiver observes the notification once it
+        ///   resumes, instead of the handler waiting for it.
+        /// - **Does not park.** If the channel is full or closed, this returns an
+        ///   error immediately. While racing with another sender for the same slot,
+        ///   it may briefly spin or yield the CPU before retrying.
+        ///
+        /// Waking a [`StaticReceiver`] that is blocked waiting for messages unparks its
+        /// [`Thread`], which is async-signal-safe on platforms where the standard
+        /// library implements thread parking with futexes or an equivalent
+        /// primitive, such as Linux, Android, and the BSDs. On platforms where
+        /// parking is implemented with a mutex and condition variable, the receiver
+        /// should poll with [`try_recv`] or a timeout instead of blocking.
+        ///
+        /// This only holds if the receiver blocks with the default parker. A
+        /// receiver whose thread has installed a custom parker with
+        /// [`park::set_thread_parker`] is woken through its [`Unpark`]
+        /// implementation, which may take a lock, as [`CondvarUnparker`] does, so
+        /// such a receiver must not block while a signal handler may send to it.
+        /// In debug builds, waking one from this method panics.
+        ///
+        /// Messages sent with this method are not tracked when the "watchdog"
+        /// feature flag is enabled, as registering them would take the watchdog's
+        /// lock.
+        ///
+        /// If [load shedding] is enabled for the channel, the message may be
+        /// shed, exactly as it would be by [`try_send`].
+        ///
+        /// # Errors
+        ///
+        /// If the channel is full, [`TrySendError::Full`] is returned, if the
+        /// channel has closed, [`TrySendError::Closed`] is returned, and if the
+        /// message is shed, [`TrySendError::Shed`] is returned. In each case, the
+        /// error includes the message.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        /// enum Event {
+        ///     None,
+        ///     Shutdown,
+        /// }
+        ///
+        /// impl Default for Event {
+        ///     fn default() -> Self {
+        ///         Event::None
+        ///     }
+        /// }
+        ///
+        /// static EVENTS: StaticChannel<Event, 4> = StaticChannel::new();
+        /// let (tx, rx) = EVENTS.split();
+        ///
+        /// // In a real application, this would be called from a signal handler.
+        /// tx.try_send_from_signal(Event::Shutdown).unwrap();
+        /// assert_eq!(rx.recv(), Some(Event::Shutdown));
+        /// ```
+        ///
+        /// [`try_send`]: Self::try_send
+        /// [`try_recv`]: StaticReceiver::try_recv
+        /// [recycling policy]: crate::recycling::Recycle
+        /// [load shedding]: StaticReceiver::set_load_shedding
+        /// [`Thread`]: std::thread::Thread
+        /// [`Unpark`]: park::Unpark
+        /// [`CondvarUnparker`]: park::CondvarUnparker
+        pub fn try_send_from_signal(&self, val: T) -> Result<(), TrySendError<T>>
+        where
+            T: Copy,
+        {
+            send_from_signal(self.core, self.slots, val, self.recycle)
+        }
+
+        /// Attempts to claim a slot in the channel immediately, without waiting
+        /// for capacity, and fills it in place by calling `f`.
+        ///
+        /// This is equivalent to calling [`try_send_ref`] and writing to the
+        /// returned [`SendRef`], and likewise allows the existing allocations
+        /// in the channel's slots to be reused. The message is sent once `f`
+        /// returns.
+        ///
+        /// # Errors
+        ///
+        /// If the channel is full, [`TrySendError::Full`] is returned, and if the
+        /// channel has closed, [`TrySendError::Closed`] is returned. In either
+        /// case, `f` is not called.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        /// use std::fmt::Write;
+        ///
+        /// static CHANNEL: StaticChannel<String, 4> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// tx.try_send_with(|msg: &mut String| write!(msg, "hello").unwrap())
+        ///     .unwrap();
+        /// assert_eq!(rx.recv().unwrap(), "hello");
+        /// ```
+        ///
+        /// [`try_send_ref`]: Self::try_send_ref
+        pub fn try_send_with<F>(&self, f: F) -> Result<(), TrySendError>
+        where
+            F: FnOnce(&mut T),
+        {
+            self.try_send_ref().map(|mut slot| f(&mut slot))
+        }
+
+        /// Attempts to send a message by value immediately, calling `f` with
+        /// the message instead if the channel is full, or the message is dropped
+        /// by the channel's load shedding.
+        ///
+        /// This makes it easy to handle overflow, for example by spilling
+        /// messages to disk, or by logging that they were dropped, rather than
+        /// waiting for capacity.
+        ///
+        /// # Errors
+        ///
+        /// If the channel has closed, the message is returned in a [`Closed`]
+        /// error, and `f` is not called.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 1> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// let mut dropped = Vec::new();
+        /// tx.try_send_or_else(1, |msg| dropped.push(msg)).unwrap();
+        /// tx.try_send_or_else(2, |msg| dropped.push(msg)).unwrap();
+        ///
+        /// assert_eq!(dropped, vec![2]);
+        /// # drop(rx);
+        /// ```
+        pub fn try_send_or_else<F>(&self, val: T, f: F) -> Result<(), Closed<T>>
+        where
+            F: FnOnce(T),
+        {
+            match self.try_send(val) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(val) | TrySendError::Shed(val)) => {
+                    f(val);
+                    Ok(())
+                }
+                Err(TrySendError::Closed(val)) => Err(Closed(val)),
+            }
+        }
+
+        /// Claims `n` consecutive slots in the channel, blocking the current
+        /// thread until they are all available, and returns a [`Transaction`] that
+        /// can be used to write to them.
+        ///
+        /// The messages in a transaction are published to the [`StaticReceiver`] all at
+        /// once, when the transaction is [committed] or dropped. Until then, none
+        /// of them can be received; afterwards, they are received one after the
+        /// other, without any other sender's messages in between. This can be used
+        /// to send units made up of several messages, such as a header followed by
+        /// its fragments, which must never be observed partially.
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel has been dropped, this returns a
+        /// [`Closed`] error.
+        ///
+        /// # Panics
+        ///
+        /// If `n` is greater than the channel's [capacity].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<String, 8> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// let mut tx = tx.transaction(3).unwrap();
+        /// tx[0] = String::from("header");
+        /// tx[1] = String::from("fragment 1");
+        /// tx[2] = String::from("fragment 2");
+        /// // nothing has been published yet...
+        /// assert!(rx.try_recv().is_err());
+        ///
+        /// // ...until the transaction is committed.
+        /// tx.commit();
+        /// assert_eq!(rx.try_recv().as_deref(), Ok("header"));
+        /// assert_eq!(rx.try_recv().as_deref(), Ok("fragment 1"));
+        /// assert_eq!(rx.try_recv().as_deref(), Ok("fragment 2"));
+        /// ```
+        ///
+        /// [committed]: Transaction::commit
+        /// [capacity]: Self::capacity
+        #[cfg(not(all(test, loom)))]
+        pub fn transaction(&self, n: usize) -> Result<Transaction<'_, T>, Closed> {
+            assert_transaction_fits(n, self.capacity());
+            transaction(self.core, self.slots, self.recycle, n)
+        }
+
+        /// Attempts to claim `n` consecutive slots in the channel, without blocking
+        /// until they are available.
+        ///
+        /// See [`transaction`] for details on transactions.
+        ///
+        /// # Errors
+        ///
+        /// - [`TrySendError::Full`] if there are not `n` consecutive slots
+        ///   available. A future call to `try_transaction` may succeed once the
+        ///   [`StaticReceiver`] has received more messages.
+        /// - [`TrySendError::Closed`] if the [`StaticReceiver`] end of the channel has been
+        ///   dropped.
+        ///
+        /// # Panics
+        ///
+        /// If `n` is greater than the channel's [capacity].
+        ///
+        /// [`transaction`]: Self::transaction
+        /// [capacity]: Self::capacity
+        pub fn try_transaction(&self, n: usize) -> Result<Transaction<'_, T>, TrySendError> {
+            assert_transaction_fits(n, self.capacity());
+            self.core
+                .try_transaction(self.slots, self.recycle, n)
+                .map(Transaction)
+        }
+
+        /// Returns a [`Dedicated`] handle, which can claim slots in the channel
+        /// in blocks of up to `batch` slots, rather than one at a time.
+        ///
+        /// A producer that sends many messages in quick succession from a single
+        /// thread can use [`Dedicated::send_all`] to avoid contending with other
+        /// senders on the channel's shared tail index for every message. Instead,
+        /// each call claims up to `batch` consecutive slots at once, and then fills
+        /// them one by one, publishing each message as soon as it is written.
+        ///
+        /// Batch claiming only happens within a `send_all` call: any slots that
+        /// were claimed but not written to are given up before it returns, so an
+        /// idle dedicated handle never holds slots that the [`StaticReceiver`] is waiting for.
+        /// [`Dedicated::send`] and [`Dedicated::send_ref`] claim a single slot.
+        ///
+        /// # Panics
+        ///
+        /// If `batch` is zero, or greater than the channel's [capacity].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// let mut dedicated = tx.dedicated(4);
+        /// assert_eq!(dedicated.send_all(0..6).unwrap(), 6);
+        ///
+        /// for i in 0..6 {
+        ///     assert_eq!(rx.recv(), Some(i));
+        /// }
+        /// ```
+        ///
+        /// [capacity]: Self::capacity
+        pub fn dedicated(&self, batch: usize) -> Dedicated<'_, T, R> {
+            assert!(batch > 0, "a dedicated handle must claim at least one slot");
+            assert_transaction_fits(batch, self.capacity());
+            Dedicated::new(self.core, self.slots, self.recycle, batch)
+        }
+
         /// Returns the *total* capacity of the channel for this [`StaticSender`].
         /// This includes both occupied and unoccupied entries.
         ///
@@ -576,6 +1076,28 @@ feature! {
             self.core.core.capacity()
         }
 
+        /// Returns a snapshot of the statistics recorded for this channel.
+        ///
+        /// These are the same statistics returned by the receiver's `stats`
+        /// method. See the [`stats`](crate::stats) module for details.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        /// tx.try_send(1).unwrap();
+        ///
+        /// assert_eq!(tx.stats().occupancy().samples(), 1);
+        /// ```
+        #[cfg(feature = "stats")]
+        #[must_use]
+        pub fn stats(&self) -> crate::stats::Stats {
+            self.core.core.stats()
+        }
+
         /// Returns the unoccupied capacity of the channel for this [`StaticSender`]
         /// (i.e., how many additional elements can be sent before the channel
         /// will be full).
@@ -663,29 +1185,121 @@ feature! {
         pub fn is_empty(&self) -> bool {
             self.len() == 0
         }
-    }
 
-    impl<T, R> Clone for StaticSender<T, R> {
-        fn clone(&self) -> Self {
-            test_dbg!(self.core.tx_count.fetch_add(1, Ordering::Relaxed));
-            Self {
-                core: self.core,
-                slots: self.slots,
-                recycle: self.recycle,
-            }
+        /// Returns the number of [`StaticSender`]s (including this one) that are
+        /// currently attached to the channel.
+        ///
+        /// This may be used by supervisors to report how many producers are still
+        /// attached to a channel. Note that the count may change as soon as it has
+        /// been read, if other senders are cloned or dropped concurrently.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (tx, _rx) = CHANNEL.split();
+        /// assert_eq!(tx.sender_count(), 1);
+        ///
+        /// let tx2 = tx.clone();
+        /// assert_eq!(tx.sender_count(), 2);
+        /// ```
+        #[inline]
+        #[must_use]
+        pub fn sender_count(&self) -> usize {
+            test_dbg!(self.core.tx_count.load(Ordering::SeqCst))
         }
-    }
-
-    impl<T, R> Drop for StaticSender<T, R> {
-        fn drop(&mut self) {
-            if test_dbg!(self.core.tx_count.fetch_sub(1, Ordering::Release)) > 1 {
-                return;
-            }
 
-            // if we are the last sender, synchronize
-            test_dbg!(atomic::fence(Ordering::SeqCst));
-            if self.core.core.close() {
-                self.core.rx_wait.close_tx();
+        /// Returns the sequence number that will be assigned to the next
+        /// message sent on this channel.
+        ///
+        /// This is the number of messages that have been sent so far. Each
+        /// message is assigned the next sequence number in the order in which
+        /// it is placed in the channel, which can be retrieved from the
+        /// [`StaticReceiver`] with [`RecvRef::seq`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (tx, _rx) = CHANNEL.split();
+        /// assert_eq!(tx.published_seq(), 0);
+        ///
+        /// tx.send(1).unwrap();
+        /// tx.send(1).unwrap();
+        /// assert_eq!(tx.published_seq(), 2);
+        /// ```
+        #[cfg(feature = "seq")]
+        #[inline]
+        #[must_use]
+        pub fn published_seq(&self) -> usize {
+            self.core.core.published_seq()
+        }
+
+        /// Blocks the current thread until every message sent on this channel
+        /// before `flush` was called has been received.
+        ///
+        /// See [`Sender::flush`] for details.
+        ///
+        /// # Errors
+        ///
+        /// If the [`StaticReceiver`] end of the channel is dropped before
+        /// every earlier message has been received, this returns a
+        /// [`Closed`] error.
+        #[cfg(feature = "seq")]
+        pub fn flush(&self) -> Result<(), Closed> {
+            flush(self.core)
+        }
+
+        /// Returns `true` if the [`StaticReceiver`] for this channel has not been
+        /// dropped.
+        ///
+        /// If this returns `false`, all future sends on this channel will fail.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        /// assert!(tx.receiver_alive());
+        ///
+        /// drop(rx);
+        /// assert!(!tx.receiver_alive());
+        /// ```
+        #[inline]
+        #[must_use]
+        pub fn receiver_alive(&self) -> bool {
+            !self.core.core.is_closed()
+        }
+    }
+
+    impl<T, R> Clone for StaticSender<T, R> {
+        fn clone(&self) -> Self {
+            test_dbg!(self.core.tx_count.fetch_add(1, Ordering::Relaxed));
+            Self {
+                core: self.core,
+                slots: self.slots,
+                recycle: self.recycle,
+            }
+        }
+    }
+
+    impl<T, R> Drop for StaticSender<T, R> {
+        fn drop(&mut self) {
+            if test_dbg!(self.core.tx_count.fetch_sub(1, Ordering::Release)) > 1 {
+                return;
+            }
+
+            // if we are the last sender, synchronize
+            test_dbg!(atomic::fence(Ordering::SeqCst));
+            if self.core.core.close() {
+                self.core.rx_wait.close_tx();
+                self.core.notify_groups();
             }
         }
     }
@@ -702,6 +1316,14 @@ feature! {
 
     // === impl StaticReceiver ===
 
+    #[cfg(feature = "rt-safe")]
+    impl<T, R> StaticReceiver<T, R> {
+        /// Returns the channel's state, for the [`rt`](super::rt) handles.
+        pub(super) fn parts(&self) -> (&ChannelCore<Unparker>, &[Slot<T>], &R) {
+            (self.core, self.slots, self.recycle)
+        }
+    }
+
     impl<T, R> StaticReceiver<T, R> {
         /// Receives the next message for this receiver, **by reference**.
         ///
@@ -827,6 +1449,93 @@ feature! {
             Some(recycling::take(&mut *val, self.recycle))
         }
 
+        /// Receives every message sent on the channel, **by reference**, passing
+        /// each one to `f`, until the channel closes.
+        ///
+        /// This blocks waiting for each message in turn, and returns once all
+        /// [`StaticSender`]s have been dropped and every message has been received.
+        /// This replaces the usual `while let Some(msg) = rx.recv_ref() { ... }`
+        /// consumer loop. Each message's slot is released as soon as `f` returns,
+        /// or drops the [`RecvRef`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        /// use std::thread;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 8> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// thread::spawn(move || {
+        ///     for i in 1..=4 {
+        ///         tx.send(i).unwrap();
+        ///     }
+        /// });
+        ///
+        /// let mut sum = 0;
+        /// // Returns once the sender has been dropped.
+        /// rx.recv_for_each(|msg| sum += *msg);
+        /// assert_eq!(sum, 10);
+        /// ```
+        pub fn recv_for_each<F>(&self, mut f: F)
+        where
+            F: FnMut(RecvRef<'_, T>),
+        {
+            while let Some(msg) = self.recv_ref() {
+                f(msg);
+            }
+        }
+
+        /// Receives as many messages as fit into `out`, copying them into it in
+        /// order, and returns the number of messages received.
+        ///
+        /// If there are no messages in the channel's buffer, but the channel has
+        /// not yet been closed, this method will block until a message is sent or
+        /// the channel is closed. Once any messages are available, every message
+        /// that is ready and fits in `out` is received at once, so consumers that
+        /// process messages in blocks, such as DSP stages or disk writers, can
+        /// avoid receiving them one at a time.
+        ///
+        /// # Returns
+        ///
+        /// - The number of messages copied into `out`.
+        /// - `0` if the channel has closed and every message has been received,
+        ///   or if `out` is empty.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 8> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        /// use std::thread;
+        ///
+        /// thread::spawn(move || {
+        ///     for i in 0..6 {
+        ///         tx.send(i).unwrap();
+        ///     }
+        /// });
+        ///
+        /// let mut samples = [0; 4];
+        /// let mut received = Vec::new();
+        /// loop {
+        ///     let n = rx.recv_into(&mut samples);
+        ///     if n == 0 {
+        ///         break;
+        ///     }
+        ///     received.extend_from_slice(&samples[..n]);
+        /// }
+        /// assert_eq!(received, vec![0, 1, 2, 3, 4, 5]);
+        /// ```
+        pub fn recv_into(&self, out: &mut [T]) -> usize
+        where
+            T: Copy,
+        {
+            recv_into(self.core, self.slots, out)
+        }
+
         /// Receives the next message for this receiver, **by reference**, waiting for at most `timeout`.
         ///
         /// If there are no messages in the channel's buffer, but the channel has
@@ -1001,144 +1710,554 @@ feature! {
             self.core.try_recv(self.slots.as_ref(), self.recycle)
         }
 
+        /// Attempts to receive the next message, but only if it matches the
+        /// predicate `f`, without waiting for a new message when the channel
+        /// is empty.
+        ///
+        /// If the next message does not match, it is left in the channel, and
+        /// `Ok(None)` is returned. This is useful for consumers that must leave
+        /// certain messages to be handled by another code path.
+        ///
+        /// Because this method takes `&mut self`, no other message can be
+        /// received while the predicate is evaluated.
+        ///
+        /// # Errors
+        ///
+        /// This method returns an error when the channel is closed, or when there
+        /// are no remaining messages in the channel's buffer.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::{blocking::StaticChannel, errors::TryRecvError};
+        ///
+        /// static CHANNEL: StaticChannel<i32, 100> = StaticChannel::new();
+        /// let (tx, mut rx) = CHANNEL.split();
+        /// tx.try_send(1).unwrap();
+        ///
+        /// assert_eq!(rx.try_recv_if(|&msg| msg > 1), Ok(None));
+        /// assert_eq!(rx.try_recv_if(|&msg| msg == 1), Ok(Some(1)));
+        /// assert_eq!(rx.try_recv_if(|_| true), Err(TryRecvError::Empty));
+        /// ```
+        pub fn try_recv_if<F>(&mut self, f: F) -> Result<Option<T>, TryRecvError>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            // Safety: this method takes `&mut self`, so no messages can be
+            // received concurrently.
+            unsafe { self.core.try_recv_if(self.slots.as_ref(), self.recycle, f) }
+        }
+
+        /// Waits for the next message, and receives it only if it matches the
+        /// predicate `f`.
+        ///
+        /// If the next message does not match, it is left at the front of the
+        /// channel, and `Ok(None)` is returned, so that it can be handled by
+        /// another code path (such as [`recv`]). If the channel is empty, this
+        /// blocks the current thread until a message is sent.
+        ///
+        /// Because this method takes `&mut self`, no other message can be
+        /// received while the predicate is evaluated.
+        ///
+        /// # Errors
+        ///
+        /// This method returns a [`Closed`] error if the channel has closed and
+        /// all messages have been received.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<i32, 100> = StaticChannel::new();
+        /// let (tx, mut rx) = CHANNEL.split();
+        ///
+        /// std::thread::spawn(move || {
+        ///     tx.send(1).unwrap();
+        ///     tx.send(2).unwrap();
+        /// });
+        ///
+        /// // The first message is rejected, and stays in the channel.
+        /// assert_eq!(rx.recv_if(|&msg| msg == 2), Ok(None));
+        /// assert_eq!(rx.recv(), Some(1));
+        ///
+        /// assert_eq!(rx.recv_if(|&msg| msg == 2), Ok(Some(2)));
+        /// assert!(rx.recv_if(|_| true).is_err());
+        /// ```
+        ///
+        /// [`recv`]: Self::recv
+        pub fn recv_if<F>(&mut self, f: F) -> Result<Option<T>, Closed>
+        where
+            R: Recycle<T>,
+            F: FnOnce(&T) -> bool,
+        {
+            if !wait_for_message(self.core, self.slots) {
+                return Err(Closed(()));
+            }
+            Ok(self
+                .try_recv_if(f)
+                .expect("a peeked message stays at the front of the channel"))
+        }
+
         /// Returns `true` if the channel has closed (all corresponding
-        /// [`StaticSender`]s have been dropped).
+        /// [`StaticSender`]s have been dropped, or the channel's group has shut it
+        /// down).
         ///
         /// If this method returns `true`, no new messages will become available
         /// on this channel. Previously sent messages may still be available.
         pub fn is_closed(&self) -> bool {
-            test_dbg!(self.core.tx_count.load(Ordering::SeqCst)) <= 1
+            test_dbg!(self.core.tx_count.load(Ordering::SeqCst)) == 0
+                || self.core.core.is_closed()
         }
 
-        /// Returns the *total* capacity of the channel for this [`StaticReceiver`].
-        /// This includes both occupied and unoccupied entries.
+        /// Returns a new [`StaticSender`] for this channel, or `None` if the channel has
+        /// already closed.
         ///
-        /// To determine the channel's remaining *unoccupied* capacity, use
-        /// [`remaining`] instead.
+        /// This allows producers to attach to an existing channel without
+        /// threading a [`StaticSender`] through to them from where the channel was
+        /// created. Any [`StaticSender`] may also be cloned to create additional senders.
+        ///
+        /// Once every [`StaticSender`] for a channel has been dropped, the channel is
+        /// closed permanently, and this method will always return `None`.
         ///
         /// # Examples
         ///
         /// ```
         /// use thingbuf::mpsc::blocking::StaticChannel;
         ///
-        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        /// static CHANNEL: StaticChannel<usize, 4> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
         ///
-        /// let (_, rx) = CHANNEL.split();
-        /// assert_eq!(rx.capacity(), 100);
-        /// ```
+        /// // Create a new sender from the receiver.
+        /// let tx2 = rx.sender().expect("channel is still open");
+        /// tx2.try_send(1).unwrap();
+        /// assert_eq!(rx.try_recv(), Ok(1));
         ///
-        /// Even after sending several messages, the capacity remains
-        /// the same:
+        /// // Once all senders have been dropped, no new ones can be created.
+        /// drop((tx, tx2));
+        /// assert!(rx.sender().is_none());
         /// ```
-        /// # use thingbuf::mpsc::blocking::StaticChannel;
+        pub fn sender(&self) -> Option<StaticSender<T, R>> {
+            if !self.core.add_tx() {
+                return None;
+            }
+
+            Some(StaticSender {
+            core: self.core,
+            slots: self.slots,
+            recycle: self.recycle,
+        })
+        }
+
+        /// Sets the number of times senders and this receiver will retry an
+        /// operation on a full (or empty) channel before waiting.
         ///
-        /// # static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        /// By default, the spin budget is 0: when the channel is full, a
+        /// [`StaticSender`] immediately registers itself in the channel's wait queue,
+        /// and when the channel is empty, the receiver immediately parks its thread.
+        /// Raising the budget makes them retry (with a short backoff between
+        /// attempts) first, which can reduce latency when capacity or messages are
+        /// expected to become available very soon, at the cost of burning CPU time.
+        /// Oversubscribed systems should generally leave the budget at 0.
         ///
-        /// let (tx, rx) = CHANNEL.split();
-        /// *tx.try_send_ref().unwrap() = 1;
-        /// *tx.try_send_ref().unwrap() = 2;
-        /// *tx.try_send_ref().unwrap() = 3;
+        /// The spin budget applies to the whole channel, and may be changed at any
+        /// time.
+        ///
+        /// # Examples
         ///
-        /// assert_eq!(rx.capacity(), 100);
         /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
         ///
-        /// [`remaining`]: Self::remaining
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (_tx, rx) = CHANNEL.split();
+        ///
+        /// // Low-latency consumers may want to spin for a while before waiting.
+        /// rx.set_spin_budget(64);
+        /// assert_eq!(rx.spin_budget(), 64);
+        /// ```
+        #[inline]
+        pub fn set_spin_budget(&self, spins: usize) {
+            self.core.spin_budget.store(spins, Ordering::Relaxed);
+        }
+
+        /// Returns the channel's current spin budget.
+        ///
+        /// See [`set_spin_budget`](Self::set_spin_budget) for details.
         #[inline]
         #[must_use]
-        pub fn capacity(&self) -> usize {
-            self.core.core.capacity()
+        pub fn spin_budget(&self) -> usize {
+            self.core.spin_budget.load(Ordering::Relaxed)
         }
 
-        /// Returns the unoccupied capacity of the channel for this [`StaticReceiver`]
-        /// (i.e., how many additional elements can be sent before the channel
-        /// will be full).
+        /// Enables or disables adaptive spinning for this channel.
+        ///
+        /// When adaptive spinning is enabled, the channel's [spin
+        /// budget](Self::spin_budget) is tuned at runtime: whenever a sender or
+        /// receiver spins until it runs out of budget, the budget is halved, and
+        /// whenever spinning succeeds before the budget runs out, it is doubled (up
+        /// to an upper limit of 1024). Adaptive spinning also backs off further than
+        /// fixed spinning, yielding the thread to the OS scheduler once the budget
+        /// is long enough, before finally waiting. This lets the channel spin when
+        /// messages arrive in quick succession, and stop burning CPU time when they
+        /// do not.
+        ///
+        /// Adaptive spinning is disabled by default.
         ///
-        /// This is equivalent to subtracting the channel's [`len`] from its [`capacity`].
         /// # Examples
         ///
         /// ```
         /// use thingbuf::mpsc::blocking::StaticChannel;
         ///
-        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
-        ///
-        /// let (tx, rx) = CHANNEL.split();
-        /// assert_eq!(rx.remaining(), 100);
-        ///
-        /// *tx.try_send_ref().unwrap() = 1;
-        /// *tx.try_send_ref().unwrap() = 2;
-        /// *tx.try_send_ref().unwrap() = 3;
-        /// assert_eq!(rx.remaining(), 97);
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (_tx, rx) = CHANNEL.split();
         ///
-        /// let _ = rx.try_recv_ref().unwrap();
-        /// assert_eq!(rx.remaining(), 98)
+        /// rx.set_adaptive_spinning(true);
+        /// assert!(rx.is_adaptive_spinning());
         /// ```
+        #[inline]
+        pub fn set_adaptive_spinning(&self, adaptive: bool) {
+            self.core.adaptive_spin.store(adaptive, Ordering::Relaxed);
+        }
+
+        /// Returns `true` if adaptive spinning is enabled for this channel.
         ///
-        /// [`len`]: Self::len
-        /// [`capacity`]: Self::capacity
+        /// See [`set_adaptive_spinning`](Self::set_adaptive_spinning) for details.
+        #[inline]
         #[must_use]
-        pub fn remaining(&self) -> usize {
-            self.capacity() - self.len()
+        pub fn is_adaptive_spinning(&self) -> bool {
+            self.core.adaptive_spin.load(Ordering::Relaxed)
         }
 
-        /// Returns the number of elements in the channel of this [`StaticReceiver`].
+        /// Enables probabilistic load shedding above the given occupancy
+        /// `ratio`, or disables it if `ratio` is `None`.
+        ///
+        /// When load shedding is enabled and the fraction of the channel's
+        /// capacity that is occupied exceeds `ratio`, messages sent with
+        /// `try_send` or `send_timeout` are randomly dropped rather than sent. The
+        /// probability that a message is dropped rises linearly from 0 at the
+        /// threshold to 1 when the channel is full. A dropped message is handed
+        /// back in a [`TrySendError::Shed`] or [`SendTimeoutError::Shed`] error,
+        /// and counted by [`shed_count`](Self::shed_count). Messages sent with
+        /// `send`, which waits for capacity instead, or with `send_ref` or
+        /// `try_send_ref`, are never dropped. If `ratio` is 1.0, no messages are
+        /// dropped, and a full channel is still reported as full.
+        ///
+        /// This lets producers that can tolerate lost messages, such as
+        /// metrics pipelines, degrade gracefully as a slow consumer falls
+        /// behind, rather than all being stalled as soon as the channel fills
+        /// up.
+        ///
+        /// Load shedding is disabled by default.
         ///
-        /// To determine the channel's remaining *unoccupied* capacity, use
-        /// [`remaining`] instead.
+        /// # Panics
+        ///
+        /// If `ratio` is not between 0.0 and 1.0.
         ///
         /// # Examples
         ///
         /// ```
         /// use thingbuf::mpsc::blocking::StaticChannel;
+        /// use thingbuf::mpsc::errors::TrySendError;
         ///
-        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
-        ///
+        /// static CHANNEL: StaticChannel<usize, 64> = StaticChannel::new();
         /// let (tx, rx) = CHANNEL.split();
-        /// assert_eq!(rx.len(), 0);
         ///
-        /// *tx.try_send_ref().unwrap() = 1;
-        /// *tx.try_send_ref().unwrap() = 2;
-        /// *tx.try_send_ref().unwrap() = 3;
-        /// assert_eq!(rx.len(), 3);
+        /// // Start shedding load once the channel is half full.
+        /// rx.set_load_shedding(Some(0.5));
         ///
-        /// let _ = rx.try_recv_ref().unwrap();
-        /// assert_eq!(rx.len(), 2);
+        /// for i in 0..1000 {
+        ///     match tx.try_send(i) {
+        ///         // A shed message is handed back, and may be dropped.
+        ///         Ok(()) | Err(TrySendError::Shed(_)) => {}
+        ///         Err(e) => panic!("unexpected error: {}", e),
+        ///     }
+        ///     if i % 2 == 0 {
+        ///         rx.try_recv().unwrap();
+        ///     }
+        /// }
+        /// assert!(rx.shed_count() > 0);
         /// ```
+        #[inline]
+        pub fn set_load_shedding(&self, ratio: Option<f64>) {
+            self.core.set_load_shedding(ratio);
+        }
+
+        /// Returns the number of messages that have been dropped by load
+        /// shedding.
         ///
-        /// [`remaining`]: Self::remaining
+        /// See [`set_load_shedding`](Self::set_load_shedding) for details.
         #[inline]
         #[must_use]
-        pub fn len(&self) -> usize {
-            self.core.core.len()
+        pub fn shed_count(&self) -> usize {
+            self.core.shed_count.load(Ordering::Relaxed)
         }
 
-        /// Returns whether the number of elements in the channel of this
-        /// [`StaticReceiver`] is 0.
+        /// Sets the policy used to wake senders that are waiting for capacity,
+        /// when this receiver frees slots.
+        ///
+        /// By default, one waiting sender is woken for each freed slot. See
+        /// [`NotifyPolicy`] for the available policies.
         ///
         /// # Examples
-        /// ```
-        /// use thingbuf::mpsc::blocking::StaticChannel;
         ///
-        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        /// ```
+        /// use thingbuf::mpsc::{blocking::StaticChannel, NotifyPolicy};
         ///
+        /// static CHANNEL: StaticChannel<usize, 64> = StaticChannel::new();
         /// let (tx, rx) = CHANNEL.split();
-        /// assert!(rx.is_empty());
-        ///
-        /// *tx.try_send_ref().unwrap() = 1;
         ///
-        /// assert!(!rx.is_empty());
+        /// // Wake every waiting sender when 16 or more slots are freed at once.
+        /// rx.set_notify_policy(NotifyPolicy::WakeAll { threshold: 16 });
+        /// assert_eq!(rx.notify_policy(), NotifyPolicy::WakeAll { threshold: 16 });
         /// ```
+        #[inline]
+        pub fn set_notify_policy(&self, policy: NotifyPolicy) {
+            self.core.set_notify_policy(policy);
+        }
+
+        /// Returns the policy used to wake waiting senders.
         ///
+        /// See [`set_notify_policy`](Self::set_notify_policy) for details.
         #[inline]
         #[must_use]
-        pub fn is_empty(&self) -> bool {
-            self.len() == 0
+        pub fn notify_policy(&self) -> NotifyPolicy {
+            self.core.notify_policy()
         }
-    }
 
-    impl<'a, T, R> Iterator for &'a StaticReceiver<T, R> {
-        type Item = RecvRef<'a, T>;
-
-        fn next(&mut self) -> Option<Self::Item> {
+        /// Makes senders wake this receiver only once at least `watermark`
+        /// messages are queued, or disables batching if `watermark` is 1.
+        ///
+        /// By default, every message sent wakes a waiting receiver. A receiver
+        /// that prefers to process messages in batches can raise the watermark,
+        /// so that it is woken up once per batch rather than once per message,
+        /// which reduces wakeups (and context switches) when messages arrive in
+        /// bursts. So that a partial batch is never left waiting indefinitely,
+        /// while a watermark is set the receiver also checks the channel every
+        /// `max_delay` while it waits. A watermark greater than the channel's
+        /// capacity is lowered to the capacity.
+        ///
+        /// Receiving never waits for the watermark when messages are already
+        /// queued: the watermark only controls when a *waiting* receiver is
+        /// woken.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        /// use std::time::Duration;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 64> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        ///
+        /// // Wake up once 16 messages are ready, or after at most 10ms.
+        /// rx.set_wake_watermark(16, Duration::from_millis(10));
+        /// assert_eq!(rx.wake_watermark(), 16);
+        ///
+        /// tx.send(1).unwrap();
+        /// assert_eq!(rx.recv(), Some(1));
+        /// ```
+        #[inline]
+        pub fn set_wake_watermark(&self, watermark: usize, max_delay: Duration) {
+            self.core.set_wake_watermark(watermark, max_delay);
+            // Wake the receiver, if it is waiting, so that it observes the new
+            // watermark.
+            self.core.rx_wait.notify();
+        }
+
+        /// Returns the number of queued messages at which senders wake this
+        /// receiver.
+        ///
+        /// See [`set_wake_watermark`](Self::set_wake_watermark) for details.
+        #[inline]
+        #[must_use]
+        pub fn wake_watermark(&self) -> usize {
+            self.core.wake_watermark.load(Ordering::Relaxed)
+        }
+
+        /// Returns the *total* capacity of the channel for this [`StaticReceiver`].
+        /// This includes both occupied and unoccupied entries.
+        ///
+        /// To determine the channel's remaining *unoccupied* capacity, use
+        /// [`remaining`] instead.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (_, rx) = CHANNEL.split();
+        /// assert_eq!(rx.capacity(), 100);
+        /// ```
+        ///
+        /// Even after sending several messages, the capacity remains
+        /// the same:
+        /// ```
+        /// # use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// # static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (tx, rx) = CHANNEL.split();
+        /// *tx.try_send_ref().unwrap() = 1;
+        /// *tx.try_send_ref().unwrap() = 2;
+        /// *tx.try_send_ref().unwrap() = 3;
+        ///
+        /// assert_eq!(rx.capacity(), 100);
+        /// ```
+        ///
+        /// [`remaining`]: Self::remaining
+        #[inline]
+        #[must_use]
+        pub fn capacity(&self) -> usize {
+            self.core.core.capacity()
+        }
+
+        /// Returns a snapshot of the statistics recorded for this channel.
+        ///
+        /// See the [`stats`](crate::stats) module for details.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        /// tx.try_send(1).unwrap();
+        ///
+        /// assert_eq!(rx.stats().occupancy().samples(), 1);
+        /// ```
+        #[cfg(feature = "stats")]
+        #[must_use]
+        pub fn stats(&self) -> crate::stats::Stats {
+            self.core.core.stats()
+        }
+
+        /// Returns the sequence number of the next message that will be
+        /// received from this channel.
+        ///
+        /// This is the number of messages that have been received so far.
+        /// Comparing it with the [`seq`] of a received message, or with the
+        /// [`StaticSender::published_seq`], allows consumers to detect gaps in the
+        /// sequence of messages.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 8> = StaticChannel::new();
+        /// let (tx, rx) = CHANNEL.split();
+        /// tx.send(1).unwrap();
+        /// tx.send(1).unwrap();
+        /// assert_eq!(rx.consumed_seq(), 0);
+        ///
+        /// let msg = rx.recv_ref().unwrap();
+        /// assert_eq!(msg.seq(), 0);
+        /// drop(msg);
+        /// assert_eq!(rx.consumed_seq(), 1);
+        /// ```
+        ///
+        /// [`seq`]: RecvRef::seq
+        #[cfg(feature = "seq")]
+        #[inline]
+        #[must_use]
+        pub fn consumed_seq(&self) -> usize {
+            self.core.core.consumed_seq()
+        }
+
+        /// Returns the unoccupied capacity of the channel for this [`StaticReceiver`]
+        /// (i.e., how many additional elements can be sent before the channel
+        /// will be full).
+        ///
+        /// This is equivalent to subtracting the channel's [`len`] from its [`capacity`].
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (tx, rx) = CHANNEL.split();
+        /// assert_eq!(rx.remaining(), 100);
+        ///
+        /// *tx.try_send_ref().unwrap() = 1;
+        /// *tx.try_send_ref().unwrap() = 2;
+        /// *tx.try_send_ref().unwrap() = 3;
+        /// assert_eq!(rx.remaining(), 97);
+        ///
+        /// let _ = rx.try_recv_ref().unwrap();
+        /// assert_eq!(rx.remaining(), 98)
+        /// ```
+        ///
+        /// [`len`]: Self::len
+        /// [`capacity`]: Self::capacity
+        #[must_use]
+        pub fn remaining(&self) -> usize {
+            self.capacity() - self.len()
+        }
+
+        /// Returns the number of elements in the channel of this [`StaticReceiver`].
+        ///
+        /// To determine the channel's remaining *unoccupied* capacity, use
+        /// [`remaining`] instead.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (tx, rx) = CHANNEL.split();
+        /// assert_eq!(rx.len(), 0);
+        ///
+        /// *tx.try_send_ref().unwrap() = 1;
+        /// *tx.try_send_ref().unwrap() = 2;
+        /// *tx.try_send_ref().unwrap() = 3;
+        /// assert_eq!(rx.len(), 3);
+        ///
+        /// let _ = rx.try_recv_ref().unwrap();
+        /// assert_eq!(rx.len(), 2);
+        /// ```
+        ///
+        /// [`remaining`]: Self::remaining
+        #[inline]
+        #[must_use]
+        pub fn len(&self) -> usize {
+            self.core.core.len()
+        }
+
+        /// Returns whether the number of elements in the channel of this
+        /// [`StaticReceiver`] is 0.
+        ///
+        /// # Examples
+        /// ```
+        /// use thingbuf::mpsc::blocking::StaticChannel;
+        ///
+        /// static CHANNEL: StaticChannel<usize, 100> = StaticChannel::new();
+        ///
+        /// let (tx, rx) = CHANNEL.split();
+        /// assert!(rx.is_empty());
+        ///
+        /// *tx.try_send_ref().unwrap() = 1;
+        ///
+        /// assert!(!rx.is_empty());
+        /// ```
+        ///
+        #[inline]
+        #[must_use]
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+    }
+
+    impl<'a, T, R> Iterator for &'a StaticReceiver<T, R> {
+        type Item = RecvRef<'a, T>;
+
+        fn next(&mut self) -> Option<Self::Item> {
             self.recv_ref()
         }
     }
@@ -1175,7 +2294,7 @@ impl_send_ref! {
     ///
     /// [implements `DerefMut<T>`]: #impl-DerefMut
     /// [`Ref`]: crate::Ref
-    pub struct SendRef<Thread>;
+    pub struct SendRef<Unparker>;
 }
 
 impl_recv_ref! {
@@ -1191,11 +2310,61 @@ impl_recv_ref! {
     ///
     /// [implements `DerefMut<T>`]: #impl-DerefMut
     /// [`Ref`]: crate::Ref
-    pub struct RecvRef<Thread>;
+    pub struct RecvRef<Unparker>;
+}
+
+/// A set of consecutive slots in a blocking channel, which are published to
+/// the receiver together.
+///
+/// A `Transaction` represents the exclusive permission to mutate each of the
+/// elements it has claimed, which are accessed by indexing it. When the
+/// transaction is [committed](Self::commit) or dropped, all of its messages
+/// become available to the receiver at once.
+///
+/// This type is returned by the [`Sender::transaction`] and
+/// [`Sender::try_transaction`] (or [`StaticSender::transaction`] and
+/// [`StaticSender::try_transaction`]) methods.
+pub struct Transaction<'a, T>(super::TransactionInner<'a, T, Unparker>);
+
+/// A sender handle that claims slots in a blocking channel in batches, for use
+/// by a single hot producer thread.
+///
+/// This type is returned by the [`Sender::dedicated`] and
+/// [`StaticSender::dedicated`] methods. Slots are only claimed in batches by
+/// [`send_all`](Self::send_all), which gives up any it did not write to
+/// before returning.
+pub struct Dedicated<'a, T, R = recycling::DefaultRecycle> {
+    core: &'a ChannelCore<Unparker>,
+    slots: &'a [Slot<T>],
+    recycle: &'a R,
+    batch: usize,
+}
+
+/// A [`Sender`] for messages of type `U`, which converts each message into
+/// the channel's message type `T` in place, inside the slot it is sent in.
+///
+/// This allows several producers with different input types to share a single
+/// channel, and, because each message is converted directly into its slot,
+/// the conversion can reuse the allocations in the channel's slots, like
+/// [`Sender::send_ref`].
+///
+/// This type is returned by the [`Sender::with_map`] method.
+pub struct SenderMap<T, U, F, R = recycling::DefaultRecycle> {
+    tx: Sender<T, R>,
+    map: F,
+    _input: core::marker::PhantomData<fn(U)>,
 }
 
 // === impl Sender ===
 
+#[cfg(feature = "rt-safe")]
+impl<T, R> Sender<T, R> {
+    /// Returns the channel's state, for the [`rt`](super::rt) handles.
+    pub(super) fn parts(&self) -> (&ChannelCore<Unparker>, &[Slot<T>], &R) {
+        (&self.inner.core, &self.inner.slots, &self.inner.recycle)
+    }
+}
+
 impl<T, R> Sender<T, R>
 where
     R: Recycle<T>,
@@ -1305,6 +2474,67 @@ where
         }
     }
 
+    /// Sends every item produced by `iter` to the channel, blocking the current
+    /// thread whenever the channel is full until capacity becomes available.
+    ///
+    /// Returns the number of items sent. This is equivalent to calling
+    /// [`send`] in a loop, stopping once the channel closes.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] end of the channel is dropped before every item has
+    /// been sent, this returns a [`SendAllError`] containing the number of items
+    /// that were sent, the item that could not be sent, and the rest of the
+    /// iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use std::thread;
+    ///
+    /// let (tx, rx) = blocking::channel(4);
+    ///
+    /// let producer = thread::spawn(move || {
+    ///     // Blocks whenever the channel is full.
+    ///     tx.send_all(0..10).unwrap()
+    /// });
+    ///
+    /// for i in 0..10 {
+    ///     assert_eq!(rx.recv(), Some(i));
+    /// }
+    /// assert_eq!(producer.join().unwrap(), 10);
+    ///
+    /// // Once the receiver is dropped, the unsent items are returned.
+    /// let (tx, rx) = blocking::channel(4);
+    /// drop(rx);
+    /// let err = tx.send_all(vec![1, 2, 3]).unwrap_err();
+    /// assert_eq!(err.sent(), 0);
+    /// let (item, rest) = err.into_inner();
+    /// assert_eq!(item, 1);
+    /// assert_eq!(rest.collect::<Vec<_>>(), vec![2, 3]);
+    /// ```
+    ///
+    /// [`send`]: Self::send
+    pub fn send_all<I>(&self, iter: I) -> Result<usize, SendAllError<T, I::IntoIter>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        let mut sent = 0;
+        for item in &mut iter {
+            if let Err(Closed(item)) = self.send(item) {
+                return Err(SendAllError {
+                    sent,
+                    item,
+                    rest: iter,
+                });
+            }
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
     /// Reserves a slot in the channel to mutate in place, blocking until
     /// there is a free slot to write to, waiting for at most `timeout`.
     ///
@@ -1384,6 +2614,8 @@ where
     ///
     /// - [`Err`]`(`[`SendTimeoutError::Timeout`]`)` if the timeout has elapsed.
     /// - [`Err`]`(`[`SendTimeoutError::Closed`]`)` if the channel has closed.
+    /// - [`Err`]`(`[`SendTimeoutError::Shed`]`)` if the message was dropped
+    ///   by the channel's load shedding.
     ///
     /// # Examples
     ///
@@ -1417,6 +2649,9 @@ where
     /// [`send_ref_timeout`]: Self::send_ref_timeout
     #[cfg(not(all(test, loom)))]
     pub fn send_timeout(&self, val: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        if self.inner.core.shed() {
+            return Err(SendTimeoutError::Shed(val));
+        }
         match self.send_ref_timeout(timeout) {
             Err(e) => Err(e.with_value(val)),
             Ok(mut slot) => {
@@ -1481,7 +2716,10 @@ where
     /// Once the channel has closed, subsequent calls to `try_send` will
     /// never succeed.
     ///
-    /// In both cases, the error includes the value passed to `try_send`.
+    /// If the message is dropped by the channel's load shedding (see
+    /// [`Receiver::set_load_shedding`]), [`TrySendError::Shed`] is returned.
+    ///
+    /// In each case, the error includes the value passed to `try_send`.
     ///
     /// [`send`]: Self::send
     pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
@@ -1490,46 +2728,442 @@ where
             .try_send(self.inner.slots.as_ref(), val, &self.inner.recycle)
     }
 
-    /// Returns the *total* capacity of the channel for this [`Sender`].
-    /// This includes both occupied and unoccupied entries.
+    /// Sends a message by value if the channel has capacity, or, if it is
+    /// full, merges the message into the most recently queued message by
+    /// calling `f`.
+    ///
+    /// `f` is called with a mutable reference to the queued message, and with
+    /// the new message. This gives producers of cumulative data, such as
+    /// counters or deltas, bounded memory use without losing information:
+    /// rather than waiting for the receiver, or dropping an update, the update
+    /// is folded into a message that the receiver has not yet received.
+    ///
+    /// The message that `f` modifies is the most recently sent message that is
+    /// not at the front of the queue, where the receiver may already be
+    /// receiving it. The receiver cannot receive that message until `f`
+    /// returns. This method never waits for capacity, and messages
+    /// sent with it are never dropped by [load shedding].
     ///
-    /// To determine the channel's remaining *unoccupied* capacity, use
-    /// [`remaining`] instead.
+    /// # Errors
     ///
-    /// # Examples
+    /// If the channel is closed, this returns [`TrySendError::Closed`]. If the
+    /// channel is full, and every queued message is still being sent or is at
+    /// the front of the queue, so there is no message to merge into, this returns
+    /// [`TrySendError::Full`]. In both cases, the error includes the value
+    /// passed to `send_or_modify`.
     ///
-    /// ```
-    /// use thingbuf::mpsc::blocking::channel;
+    /// # Examples
     ///
-    /// let (tx, _) = channel::<usize>(100);
-    /// assert_eq!(tx.capacity(), 100);
     /// ```
+    /// use thingbuf::mpsc::blocking;
     ///
-    /// Even after sending several messages, the capacity remains
-    /// the same:
-    /// ```
-    /// # use thingbuf::mpsc::blocking::channel;
+    /// let (tx, rx) = blocking::channel::<u64>(2);
     ///
-    /// let (tx, rx) = channel::<usize>(100);
-    /// *tx.try_send_ref().unwrap() = 1;
-    /// *tx.try_send_ref().unwrap() = 2;
-    /// *tx.try_send_ref().unwrap() = 3;
+    /// for delta in 1..=10 {
+    ///     tx.send_or_modify(delta, |queued, delta| *queued += delta)
+    ///         .unwrap();
+    /// }
     ///
-    /// assert_eq!(tx.capacity(), 100);
+    /// // Once the channel filled up, the remaining deltas were added to the
+    /// // last queued message.
+    /// assert_eq!(rx.try_recv().unwrap(), 1);
+    /// assert_eq!(rx.try_recv().unwrap(), 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9 + 10);
     /// ```
     ///
-    /// [`remaining`]: Self::remaining
-    #[inline]
-    #[must_use]
-    pub fn capacity(&self) -> usize {
-        self.inner.core.core.capacity()
+    /// [load shedding]: Receiver::set_load_shedding
+    pub fn send_or_modify<F>(&self, val: T, f: F) -> Result<(), TrySendError<T>>
+    where
+        F: FnOnce(&mut T, T),
+    {
+        self.inner
+            .core
+            .try_send_or_modify(self.inner.slots.as_ref(), val, &self.inner.recycle, f)
     }
 
-    /// Returns the unoccupied capacity of the channel for this [`Sender`]
-    /// (i.e., how many additional elements can be sent before the channel
-    /// will be full).
+    /// Attempts to send a `Copy` message from a Unix signal handler, without
+    /// blocking until capacity is available.
+    ///
+    /// This behaves exactly like [`try_send`], but is restricted to message types
+    /// whose slots can be overwritten without running any destructors, so that it
+    /// is *async-signal-safe*: it may be called from a signal handler to enqueue
+    /// an event, such as a request to shut down or to dump statistics, for the
+    /// application's main loop to handle.
+    ///
+    /// # Async-signal safety
+    ///
+    /// A signal handler may interrupt any code on the thread it runs on,
+    /// including this channel's own [`Receiver`] or another sender, so this
+    /// method:
+    ///
+    /// - **Does not allocate or free memory.** The message is `Copy`, so writing
+    ///   it into a slot does not drop the slot's previous value. The channel's
+    ///   [recycling policy] is still applied to the slot before it is written;
+    ///   the default policy writes `T::default()`, which must not allocate. A
+    ///   custom policy must not allocate either.
+    /// - **Does not take any locks.** Claiming a slot is a lock-free
+    ///   compare-and-swap loop that never waits for a sender or receiver that may
+    ///   have been interrupted. Waking the receiver goes through a lock-free
+    ///   wait cell: if the handler interrupts the receiver while it is
+    ///   registering to wait, the receiver observes the notification once it
+    ///   resumes, instead of the handler waiting for it.
+    /// - **Does not park.** If the channel is full or closed, this returns an
+    ///   error immediately. While racing with another sender for the same slot,
+    ///   it may briefly spin or yield the CPU before retrying.
+    ///
+    /// Waking a [`Receiver`] that is blocked waiting for messages unparks its
+    /// [`Thread`], which is async-signal-safe on platforms where the standard
+    /// library implements thread parking with futexes or an equivalent
+    /// primitive, such as Linux, Android, and the BSDs. On platforms where
+    /// parking is implemented with a mutex and condition variable, the receiver
+    /// should poll with [`try_recv`] or a timeout instead of blocking.
+    ///
+    /// This only holds if the receiver blocks with the default parker. A
+    /// receiver whose thread has installed a custom parker with
+    /// [`park::set_thread_parker`] is woken through its [`Unpark`]
+    /// implementation, which may take a lock, as [`CondvarUnparker`] does, so
+    /// such a receiver must not block while a signal handler may send to it.
+    /// In debug builds, waking one from this method panics.
+    ///
+    /// Messages sent with this method are not tracked when the "watchdog"
+    /// feature flag is enabled, as registering them would take the watchdog's
+    /// lock.
+    ///
+    /// If [load shedding] is enabled for the channel, the message may be
+    /// shed, exactly as it would be by [`try_send`].
     ///
-    /// This is equivalent to subtracting the channel's [`len`] from its [`capacity`].
+    /// # Errors
+    ///
+    /// If the channel is full, [`TrySendError::Full`] is returned, if the
+    /// channel has closed, [`TrySendError::Closed`] is returned, and if the
+    /// message is shed, [`TrySendError::Shed`] is returned. In each case, the
+    /// error includes the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (tx, rx) = blocking::channel::<u32>(4);
+    ///
+    /// // In a real application, this would be called from a signal handler.
+    /// tx.try_send_from_signal(15).unwrap();
+    /// assert_eq!(rx.recv(), Some(15));
+    /// ```
+    ///
+    /// [`try_send`]: Self::try_send
+    /// [`try_recv`]: Receiver::try_recv
+    /// [recycling policy]: crate::recycling::Recycle
+    /// [load shedding]: Receiver::set_load_shedding
+    /// [`Thread`]: std::thread::Thread
+    /// [`Unpark`]: park::Unpark
+    /// [`CondvarUnparker`]: park::CondvarUnparker
+    pub fn try_send_from_signal(&self, val: T) -> Result<(), TrySendError<T>>
+    where
+        T: Copy,
+    {
+        send_from_signal(
+            &self.inner.core,
+            self.inner.slots.as_ref(),
+            val,
+            &self.inner.recycle,
+        )
+    }
+
+    /// Attempts to claim a slot in the channel immediately, without waiting
+    /// for capacity, and fills it in place by calling `f`.
+    ///
+    /// This is equivalent to calling [`try_send_ref`] and writing to the
+    /// returned [`SendRef`], and likewise allows the existing allocations
+    /// in the channel's slots to be reused. The message is sent once `f`
+    /// returns.
+    ///
+    /// # Errors
+    ///
+    /// If the channel is full, [`TrySendError::Full`] is returned, and if the
+    /// channel has closed, [`TrySendError::Closed`] is returned. In either
+    /// case, `f` is not called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use std::fmt::Write;
+    ///
+    /// let (tx, rx) = blocking::channel::<String>(4);
+    ///
+    /// tx.try_send_with(|msg: &mut String| write!(msg, "hello").unwrap())
+    ///     .unwrap();
+    /// assert_eq!(rx.recv().unwrap(), "hello");
+    /// ```
+    ///
+    /// [`try_send_ref`]: Self::try_send_ref
+    pub fn try_send_with<F>(&self, f: F) -> Result<(), TrySendError>
+    where
+        F: FnOnce(&mut T),
+    {
+        self.try_send_ref().map(|mut slot| f(&mut slot))
+    }
+
+    /// Attempts to send a message by value immediately, calling `f` with
+    /// the message instead if the channel is full, or the message is dropped
+    /// by the channel's load shedding.
+    ///
+    /// This makes it easy to handle overflow, for example by spilling
+    /// messages to disk, or by logging that they were dropped, rather than
+    /// waiting for capacity.
+    ///
+    /// # Errors
+    ///
+    /// If the channel has closed, the message is returned in a [`Closed`]
+    /// error, and `f` is not called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (tx, rx) = blocking::channel(1);
+    ///
+    /// let mut dropped = Vec::new();
+    /// tx.try_send_or_else(1, |msg| dropped.push(msg)).unwrap();
+    /// tx.try_send_or_else(2, |msg| dropped.push(msg)).unwrap();
+    ///
+    /// assert_eq!(dropped, vec![2]);
+    /// # drop(rx);
+    /// ```
+    pub fn try_send_or_else<F>(&self, val: T, f: F) -> Result<(), Closed<T>>
+    where
+        F: FnOnce(T),
+    {
+        match self.try_send(val) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(val) | TrySendError::Shed(val)) => {
+                f(val);
+                Ok(())
+            }
+            Err(TrySendError::Closed(val)) => Err(Closed(val)),
+        }
+    }
+
+    /// Claims `n` consecutive slots in the channel, blocking the current
+    /// thread until they are all available, and returns a [`Transaction`] that
+    /// can be used to write to them.
+    ///
+    /// The messages in a transaction are published to the [`Receiver`] all at
+    /// once, when the transaction is [committed] or dropped. Until then, none
+    /// of them can be received; afterwards, they are received one after the
+    /// other, without any other sender's messages in between. This can be used
+    /// to send units made up of several messages, such as a header followed by
+    /// its fragments, which must never be observed partially.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] end of the channel has been dropped, this returns a
+    /// [`Closed`] error.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is greater than the channel's [capacity].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (tx, rx) = blocking::channel::<String>(8);
+    ///
+    /// let mut tx = tx.transaction(3).unwrap();
+    /// tx[0] = String::from("header");
+    /// tx[1] = String::from("fragment 1");
+    /// tx[2] = String::from("fragment 2");
+    /// // nothing has been published yet...
+    /// assert!(rx.try_recv().is_err());
+    ///
+    /// // ...until the transaction is committed.
+    /// tx.commit();
+    /// assert_eq!(rx.try_recv().as_deref(), Ok("header"));
+    /// assert_eq!(rx.try_recv().as_deref(), Ok("fragment 1"));
+    /// assert_eq!(rx.try_recv().as_deref(), Ok("fragment 2"));
+    /// ```
+    ///
+    /// [committed]: Transaction::commit
+    /// [capacity]: Self::capacity
+    #[cfg(not(all(test, loom)))]
+    pub fn transaction(&self, n: usize) -> Result<Transaction<'_, T>, Closed> {
+        assert_transaction_fits(n, self.capacity());
+        transaction(&self.inner.core, &self.inner.slots, &self.inner.recycle, n)
+    }
+
+    /// Attempts to claim `n` consecutive slots in the channel, without blocking
+    /// until they are available.
+    ///
+    /// See [`transaction`] for details on transactions.
+    ///
+    /// # Errors
+    ///
+    /// - [`TrySendError::Full`] if there are not `n` consecutive slots
+    ///   available. A future call to `try_transaction` may succeed once the
+    ///   [`Receiver`] has received more messages.
+    /// - [`TrySendError::Closed`] if the [`Receiver`] end of the channel has been
+    ///   dropped.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is greater than the channel's [capacity].
+    ///
+    /// [`transaction`]: Self::transaction
+    /// [capacity]: Self::capacity
+    pub fn try_transaction(&self, n: usize) -> Result<Transaction<'_, T>, TrySendError> {
+        assert_transaction_fits(n, self.capacity());
+        self.inner
+            .core
+            .try_transaction(&self.inner.slots, &self.inner.recycle, n)
+            .map(Transaction)
+    }
+
+    /// Returns a [`Dedicated`] handle, which can claim slots in the channel
+    /// in blocks of up to `batch` slots, rather than one at a time.
+    ///
+    /// A producer that sends many messages in quick succession from a single
+    /// thread can use [`Dedicated::send_all`] to avoid contending with other
+    /// senders on the channel's shared tail index for every message. Instead,
+    /// each call claims up to `batch` consecutive slots at once, and then fills
+    /// them one by one, publishing each message as soon as it is written.
+    ///
+    /// Batch claiming only happens within a `send_all` call: any slots that
+    /// were claimed but not written to are given up before it returns, so an
+    /// idle dedicated handle never holds slots that the [`Receiver`] is waiting for.
+    /// [`Dedicated::send`] and [`Dedicated::send_ref`] claim a single slot.
+    ///
+    /// # Panics
+    ///
+    /// If `batch` is zero, or greater than the channel's [capacity].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (tx, rx) = blocking::channel(8);
+    ///
+    /// let mut dedicated = tx.dedicated(4);
+    /// assert_eq!(dedicated.send_all(0..6).unwrap(), 6);
+    ///
+    /// for i in 0..6 {
+    ///     assert_eq!(rx.recv(), Some(i));
+    /// }
+    /// ```
+    ///
+    /// [capacity]: Self::capacity
+    pub fn dedicated(&self, batch: usize) -> Dedicated<'_, T, R> {
+        assert!(batch > 0, "a dedicated handle must claim at least one slot");
+        assert_transaction_fits(batch, self.capacity());
+        Dedicated::new(
+            &self.inner.core,
+            &self.inner.slots,
+            &self.inner.recycle,
+            batch,
+        )
+    }
+
+    /// Converts this `Sender` into a [`SenderMap`], which sends messages of
+    /// type `U` by calling `map` to write each one into a slot in the
+    /// channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use std::fmt::Write;
+    ///
+    /// let (tx, rx) = blocking::channel::<String>(8);
+    ///
+    /// // Producers of numbers and of byte strings share one channel.
+    /// let numbers = tx.clone().with_map(|n: u32, slot: &mut String| {
+    ///     write!(slot, "{}", n).unwrap();
+    /// });
+    /// let bytes = tx.with_map(|b: &[u8], slot: &mut String| {
+    ///     slot.push_str(&String::from_utf8_lossy(b));
+    /// });
+    ///
+    /// numbers.send(42).unwrap();
+    /// bytes.send(b"hello").unwrap();
+    ///
+    /// assert_eq!(rx.recv().as_deref(), Some("42"));
+    /// assert_eq!(rx.recv().as_deref(), Some("hello"));
+    /// ```
+    pub fn with_map<U, F>(self, map: F) -> SenderMap<T, U, F, R>
+    where
+        F: Fn(U, &mut T),
+    {
+        SenderMap {
+            tx: self,
+            map,
+            _input: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns the *total* capacity of the channel for this [`Sender`].
+    /// This includes both occupied and unoccupied entries.
+    ///
+    /// To determine the channel's remaining *unoccupied* capacity, use
+    /// [`remaining`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking::channel;
+    ///
+    /// let (tx, _) = channel::<usize>(100);
+    /// assert_eq!(tx.capacity(), 100);
+    /// ```
+    ///
+    /// Even after sending several messages, the capacity remains
+    /// the same:
+    /// ```
+    /// # use thingbuf::mpsc::blocking::channel;
+    ///
+    /// let (tx, rx) = channel::<usize>(100);
+    /// *tx.try_send_ref().unwrap() = 1;
+    /// *tx.try_send_ref().unwrap() = 2;
+    /// *tx.try_send_ref().unwrap() = 3;
+    ///
+    /// assert_eq!(tx.capacity(), 100);
+    /// ```
+    ///
+    /// [`remaining`]: Self::remaining
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.inner.core.core.capacity()
+    }
+
+    /// Returns a snapshot of the statistics recorded for this channel.
+    ///
+    /// These are the same statistics returned by the receiver's `stats`
+    /// method. See the [`stats`](crate::stats) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking::channel;
+    ///
+    /// let (tx, rx) = channel::<usize>(100);
+    /// tx.try_send(1).unwrap();
+    ///
+    /// assert_eq!(tx.stats().occupancy().samples(), 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    #[must_use]
+    pub fn stats(&self) -> crate::stats::Stats {
+        self.inner.core.core.stats()
+    }
+
+    /// Returns the unoccupied capacity of the channel for this [`Sender`]
+    /// (i.e., how many additional elements can be sent before the channel
+    /// will be full).
+    ///
+    /// This is equivalent to subtracting the channel's [`len`] from its [`capacity`].
     ///
     /// # Examples
     ///
@@ -1605,89 +3239,282 @@ where
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
-}
-
-impl<T, R> Clone for Sender<T, R> {
-    fn clone(&self) -> Self {
-        test_dbg!(self.inner.core.tx_count.fetch_add(1, Ordering::Relaxed));
-        Self {
-            inner: self.inner.clone(),
-        }
-    }
-}
-
-impl<T, R> Drop for Sender<T, R> {
-    fn drop(&mut self) {
-        if test_dbg!(self.inner.core.tx_count.fetch_sub(1, Ordering::Release)) > 1 {
-            return;
-        }
-
-        // if we are the last sender, synchronize
-        test_dbg!(atomic::fence(Ordering::SeqCst));
-        if self.inner.core.core.close() {
-            self.inner.core.rx_wait.close_tx();
-        }
-    }
-}
-
-// === impl Receiver ===
 
-impl<T, R> Receiver<T, R> {
-    /// Receives the next message for this receiver, **by reference**.
-    ///
-    /// This method returns `None` if the channel has been closed and there are
-    /// no remaining messages in the channel's buffer. This indicates that no
-    /// further values can ever be received from this `Receiver`. The channel is
-    /// closed when all [`Sender`]s have been dropped.
-    ///
-    /// If there are no messages in the channel's buffer, but the channel has
-    /// not yet been closed, this method will block until a message is sent or
-    /// the channel is closed.
-    ///
-    /// This method returns a [`RecvRef`] that can be used to read from (or
-    /// mutate) the received message by reference. When the [`RecvRef`] is
-    /// dropped, the receive operation completes and the slot occupied by
-    /// the received message becomes usable for a future [`send_ref`] operation.
+    /// Returns the number of [`Sender`]s (including this one) that are
+    /// currently attached to the channel.
     ///
-    /// If all [`Sender`]s for this channel write to the channel's slots in
-    /// place by using the [`send_ref`] or [`try_send_ref`] methods, this
-    /// method allows messages that own heap allocations to be reused in
-    /// place.
+    /// This may be used by supervisors to report how many producers are still
+    /// attached to a channel. Note that the count may change as soon as it has
+    /// been read, if other senders are cloned or dropped concurrently.
     ///
     /// # Examples
     ///
     /// ```
     /// use thingbuf::mpsc::blocking;
-    /// use std::{thread, fmt::Write};
     ///
-    /// let (tx, rx) = blocking::channel::<String>(100);
+    /// let (tx, _rx) = blocking::channel::<usize>(8);
+    /// assert_eq!(tx.sender_count(), 1);
     ///
-    /// thread::spawn(move || {
-    ///     let mut value = tx.send_ref().unwrap();
-    ///     write!(value, "hello world!")
-    ///         .expect("writing to a `String` should never fail");
-    /// });
+    /// let tx2 = tx.clone();
+    /// assert_eq!(tx.sender_count(), 2);
     ///
-    /// assert_eq!(Some("hello world!"), rx.recv_ref().as_deref().map(String::as_str));
-    /// assert_eq!(None, rx.recv().as_deref());
+    /// drop(tx2);
+    /// assert_eq!(tx.sender_count(), 1);
     /// ```
+    #[inline]
+    #[must_use]
+    pub fn sender_count(&self) -> usize {
+        test_dbg!(self.inner.core.tx_count.load(Ordering::SeqCst))
+    }
+
+    /// Returns the sequence number that will be assigned to the next
+    /// message sent on this channel.
     ///
-    /// Values are buffered:
+    /// This is the number of messages that have been sent so far. Each
+    /// message is assigned the next sequence number in the order in which
+    /// it is placed in the channel, which can be retrieved from the
+    /// [`Receiver`] with [`RecvRef::seq`].
+    ///
+    /// # Examples
     ///
     /// ```
     /// use thingbuf::mpsc::blocking;
-    /// use std::fmt::Write;
-    ///
-    /// let (tx, rx) = blocking::channel::<String>(100);
     ///
-    /// write!(tx.send_ref().unwrap(), "hello").unwrap();
-    /// write!(tx.send_ref().unwrap(), "world").unwrap();
+    /// let (tx, _rx) = blocking::channel::<usize>(8);
+    /// assert_eq!(tx.published_seq(), 0);
     ///
-    /// assert_eq!("hello", rx.recv_ref().unwrap().as_str());
-    /// assert_eq!("world", rx.recv_ref().unwrap().as_str());
+    /// tx.send(1).unwrap();
+    /// tx.send(1).unwrap();
+    /// assert_eq!(tx.published_seq(), 2);
     /// ```
-    ///
-    /// [`send_ref`]: Sender::send_ref
+    #[cfg(feature = "seq")]
+    #[inline]
+    #[must_use]
+    pub fn published_seq(&self) -> usize {
+        self.inner.core.core.published_seq()
+    }
+
+    /// Blocks the current thread until every message sent on this channel
+    /// before `flush` was called has been received.
+    ///
+    /// This establishes a barrier: once it returns, the [`Receiver`] has
+    /// taken every earlier message out of the channel, including those sent
+    /// by other [`Sender`]s, without the channel having to be closed. A
+    /// message counts as received once it has been taken out of the channel,
+    /// so `flush` does not wait for the receiver to finish processing it.
+    ///
+    /// This must not be called while this thread holds a [`SendRef`] for the
+    /// same channel, as the message in it cannot be received until the
+    /// `SendRef` is dropped.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] end of the channel is dropped before every earlier
+    /// message has been received, this returns a [`Closed`] error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use std::thread;
+    ///
+    /// let (tx, rx) = blocking::channel::<usize>(8);
+    ///
+    /// let producer = thread::spawn(move || {
+    ///     tx.send(1).unwrap();
+    ///     tx.send(2).unwrap();
+    ///     // Wait until both messages have been received.
+    ///     tx.flush().unwrap();
+    ///     tx
+    /// });
+    ///
+    /// assert_eq!(rx.recv(), Some(1));
+    /// assert_eq!(rx.recv(), Some(2));
+    ///
+    /// let tx = producer.join().unwrap();
+    /// assert_eq!(tx.published_seq(), rx.consumed_seq());
+    /// ```
+    #[cfg(feature = "seq")]
+    pub fn flush(&self) -> Result<(), Closed> {
+        flush(&self.inner.core)
+    }
+
+    /// Returns `true` if the [`Receiver`] for this channel has not been
+    /// dropped.
+    ///
+    /// If this returns `false`, all future sends on this channel will fail.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (tx, rx) = blocking::channel::<usize>(8);
+    /// assert!(tx.receiver_alive());
+    ///
+    /// drop(rx);
+    /// assert!(!tx.receiver_alive());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn receiver_alive(&self) -> bool {
+        !self.inner.core.core.is_closed()
+    }
+}
+
+feature! {
+    #![feature = "rayon"]
+
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    impl<T, R> Sender<T, R>
+    where
+        T: Send,
+        R: Recycle<T>,
+        Self: Sync,
+    {
+        /// Sends every item produced by a [parallel iterator] to the
+        /// channel, blocking the [`rayon`] worker threads that produce them
+        /// whenever the channel is full until capacity becomes available.
+        ///
+        /// This feeds a single consumer from a parallel computation, with
+        /// the channel applying backpressure to the computation. Items may be
+        /// sent in any order.
+        ///
+        /// This returns once every item has been sent, so the [`Receiver`]
+        /// must not be running on the same [`rayon`] thread pool, or else it
+        /// may never get to run while the workers are blocked.
+        ///
+        /// # Errors
+        ///
+        /// If the [`Receiver`] end of the channel is dropped before every item
+        /// has been sent, this returns a [`Closed`] error. The workers stop
+        /// producing items as soon as possible, and any items that were
+        /// produced but not sent are dropped.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use rayon::prelude::*;
+        /// use thingbuf::mpsc::blocking;
+        /// use std::thread;
+        ///
+        /// let (tx, rx) = blocking::channel(16);
+        ///
+        /// let consumer = thread::spawn(move || {
+        ///     let mut sum = 0u64;
+        ///     while let Some(i) = rx.recv() {
+        ///         sum += i;
+        ///     }
+        ///     sum
+        /// });
+        ///
+        /// tx.par_send_all((0..1000u64).into_par_iter().map(|i| i * 2)).unwrap();
+        /// drop(tx);
+        /// assert_eq!(consumer.join().unwrap(), 999 * 1000);
+        /// ```
+        ///
+        /// [parallel iterator]: rayon::iter::IntoParallelIterator
+        pub fn par_send_all<I>(&self, iter: I) -> Result<(), Closed>
+        where
+            I: IntoParallelIterator<Item = T>,
+        {
+            iter.into_par_iter()
+                .try_for_each(|item| self.send(item).map_err(|_| Closed(())))
+        }
+    }
+}
+
+impl<T, R> Clone for Sender<T, R> {
+    fn clone(&self) -> Self {
+        test_dbg!(self.inner.core.tx_count.fetch_add(1, Ordering::Relaxed));
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, R> Drop for Sender<T, R> {
+    fn drop(&mut self) {
+        if test_dbg!(self.inner.core.tx_count.fetch_sub(1, Ordering::Release)) > 1 {
+            return;
+        }
+
+        // if we are the last sender, synchronize
+        test_dbg!(atomic::fence(Ordering::SeqCst));
+        if self.inner.core.core.close() {
+            self.inner.core.rx_wait.close_tx();
+            self.inner.core.notify_groups();
+        }
+    }
+}
+
+// === impl Receiver ===
+
+#[cfg(feature = "rt-safe")]
+impl<T, R> Receiver<T, R> {
+    /// Returns the channel's state, for the [`rt`](super::rt) handles.
+    pub(super) fn parts(&self) -> (&ChannelCore<Unparker>, &[Slot<T>], &R) {
+        (&self.inner.core, &self.inner.slots, &self.inner.recycle)
+    }
+}
+
+impl<T, R> Receiver<T, R> {
+    /// Receives the next message for this receiver, **by reference**.
+    ///
+    /// This method returns `None` if the channel has been closed and there are
+    /// no remaining messages in the channel's buffer. This indicates that no
+    /// further values can ever be received from this `Receiver`. The channel is
+    /// closed when all [`Sender`]s have been dropped.
+    ///
+    /// If there are no messages in the channel's buffer, but the channel has
+    /// not yet been closed, this method will block until a message is sent or
+    /// the channel is closed.
+    ///
+    /// This method returns a [`RecvRef`] that can be used to read from (or
+    /// mutate) the received message by reference. When the [`RecvRef`] is
+    /// dropped, the receive operation completes and the slot occupied by
+    /// the received message becomes usable for a future [`send_ref`] operation.
+    ///
+    /// If all [`Sender`]s for this channel write to the channel's slots in
+    /// place by using the [`send_ref`] or [`try_send_ref`] methods, this
+    /// method allows messages that own heap allocations to be reused in
+    /// place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use std::{thread, fmt::Write};
+    ///
+    /// let (tx, rx) = blocking::channel::<String>(100);
+    ///
+    /// thread::spawn(move || {
+    ///     let mut value = tx.send_ref().unwrap();
+    ///     write!(value, "hello world!")
+    ///         .expect("writing to a `String` should never fail");
+    /// });
+    ///
+    /// assert_eq!(Some("hello world!"), rx.recv_ref().as_deref().map(String::as_str));
+    /// assert_eq!(None, rx.recv().as_deref());
+    /// ```
+    ///
+    /// Values are buffered:
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use std::fmt::Write;
+    ///
+    /// let (tx, rx) = blocking::channel::<String>(100);
+    ///
+    /// write!(tx.send_ref().unwrap(), "hello").unwrap();
+    /// write!(tx.send_ref().unwrap(), "world").unwrap();
+    ///
+    /// assert_eq!("hello", rx.recv_ref().unwrap().as_str());
+    /// assert_eq!("world", rx.recv_ref().unwrap().as_str());
+    /// ```
+    ///
+    /// [`send_ref`]: Sender::send_ref
     /// [`try_send_ref`]: Sender::try_send_ref
     pub fn recv_ref(&self) -> Option<RecvRef<'_, T>> {
         recv_ref(&self.inner.core, self.inner.slots.as_ref())
@@ -1753,6 +3580,91 @@ impl<T, R> Receiver<T, R> {
         Some(recycling::take(&mut *val, &self.inner.recycle))
     }
 
+    /// Receives every message sent on the channel, **by reference**, passing
+    /// each one to `f`, until the channel closes.
+    ///
+    /// This blocks waiting for each message in turn, and returns once all
+    /// [`Sender`]s have been dropped and every message has been received.
+    /// This replaces the usual `while let Some(msg) = rx.recv_ref() { ... }`
+    /// consumer loop. Each message's slot is released as soon as `f` returns,
+    /// or drops the [`RecvRef`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use std::thread;
+    ///
+    /// let (tx, rx) = blocking::channel(8);
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 1..=4 {
+    ///         tx.send(i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let mut sum = 0;
+    /// // Returns once the sender has been dropped.
+    /// rx.recv_for_each(|msg| sum += *msg);
+    /// assert_eq!(sum, 10);
+    /// ```
+    pub fn recv_for_each<F>(&self, mut f: F)
+    where
+        F: FnMut(RecvRef<'_, T>),
+    {
+        while let Some(msg) = self.recv_ref() {
+            f(msg);
+        }
+    }
+
+    /// Receives as many messages as fit into `out`, copying them into it in
+    /// order, and returns the number of messages received.
+    ///
+    /// If there are no messages in the channel's buffer, but the channel has
+    /// not yet been closed, this method will block until a message is sent or
+    /// the channel is closed. Once any messages are available, every message
+    /// that is ready and fits in `out` is received at once, so consumers that
+    /// process messages in blocks, such as DSP stages or disk writers, can
+    /// avoid receiving them one at a time.
+    ///
+    /// # Returns
+    ///
+    /// - The number of messages copied into `out`.
+    /// - `0` if the channel has closed and every message has been received,
+    ///   or if `out` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (tx, rx) = blocking::channel(8);
+    /// use std::thread;
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 0..6 {
+    ///         tx.send(i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let mut samples = [0; 4];
+    /// let mut received = Vec::new();
+    /// loop {
+    ///     let n = rx.recv_into(&mut samples);
+    ///     if n == 0 {
+    ///         break;
+    ///     }
+    ///     received.extend_from_slice(&samples[..n]);
+    /// }
+    /// assert_eq!(received, vec![0, 1, 2, 3, 4, 5]);
+    /// ```
+    pub fn recv_into(&self, out: &mut [T]) -> usize
+    where
+        T: Copy,
+    {
+        recv_into(&self.inner.core, self.inner.slots.as_ref(), out)
+    }
+
     /// Receives the next message for this receiver, **by reference**, waiting for at most `timeout`.
     ///
     /// If there are no messages in the channel's buffer, but the channel has
@@ -1927,34 +3839,395 @@ impl<T, R> Receiver<T, R> {
             .try_recv(self.inner.slots.as_ref(), &self.inner.recycle)
     }
 
+    /// Attempts to receive the next message, but only if it matches the
+    /// predicate `f`, without waiting for a new message when the channel
+    /// is empty.
+    ///
+    /// If the next message does not match, it is left in the channel, and
+    /// `Ok(None)` is returned. This is useful for consumers that must leave
+    /// certain messages to be handled by another code path.
+    ///
+    /// Because this method takes `&mut self`, no other message can be
+    /// received while the predicate is evaluated.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error when the channel is closed, or when there
+    /// are no remaining messages in the channel's buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::{blocking, errors::TryRecvError};
+    ///
+    /// let (tx, mut rx) = blocking::channel(100);
+    /// tx.try_send(1).unwrap();
+    ///
+    /// assert_eq!(rx.try_recv_if(|&msg| msg > 1), Ok(None));
+    /// assert_eq!(rx.try_recv_if(|&msg| msg == 1), Ok(Some(1)));
+    /// assert_eq!(rx.try_recv_if(|_| true), Err(TryRecvError::Empty));
+    /// ```
+    pub fn try_recv_if<F>(&mut self, f: F) -> Result<Option<T>, TryRecvError>
+    where
+        R: Recycle<T>,
+        F: FnOnce(&T) -> bool,
+    {
+        // Safety: this method takes `&mut self`, so no messages can be
+        // received concurrently.
+        unsafe {
+            self.inner
+                .core
+                .try_recv_if(self.inner.slots.as_ref(), &self.inner.recycle, f)
+        }
+    }
+
+    /// Waits for the next message, and receives it only if it matches the
+    /// predicate `f`.
+    ///
+    /// If the next message does not match, it is left at the front of the
+    /// channel, and `Ok(None)` is returned, so that it can be handled by
+    /// another code path (such as [`recv`]). If the channel is empty, this
+    /// blocks the current thread until a message is sent.
+    ///
+    /// Because this method takes `&mut self`, no other message can be
+    /// received while the predicate is evaluated.
+    ///
+    /// # Errors
+    ///
+    /// This method returns a [`Closed`] error if the channel has closed and
+    /// all messages have been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (tx, mut rx) = blocking::channel(100);
+    ///
+    /// std::thread::spawn(move || {
+    ///     tx.send(1).unwrap();
+    ///     tx.send(2).unwrap();
+    /// });
+    ///
+    /// // The first message is rejected, and stays in the channel.
+    /// assert_eq!(rx.recv_if(|&msg| msg == 2), Ok(None));
+    /// assert_eq!(rx.recv(), Some(1));
+    ///
+    /// assert_eq!(rx.recv_if(|&msg| msg == 2), Ok(Some(2)));
+    /// assert!(rx.recv_if(|_| true).is_err());
+    /// ```
+    ///
+    /// [`recv`]: Self::recv
+    pub fn recv_if<F>(&mut self, f: F) -> Result<Option<T>, Closed>
+    where
+        R: Recycle<T>,
+        F: FnOnce(&T) -> bool,
+    {
+        if !wait_for_message(&self.inner.core, self.inner.slots.as_ref()) {
+            return Err(Closed(()));
+        }
+        Ok(self
+            .try_recv_if(f)
+            .expect("a peeked message stays at the front of the channel"))
+    }
+
     /// Returns `true` if the channel has closed (all corresponding
-    /// [`Sender`]s have been dropped).
+    /// [`Sender`]s have been dropped, or the channel's group has shut it
+    /// down).
     ///
     /// If this method returns `true`, no new messages will become available
     /// on this channel. Previously sent messages may still be available.
     pub fn is_closed(&self) -> bool {
-        test_dbg!(self.inner.core.tx_count.load(Ordering::SeqCst)) <= 1
+        test_dbg!(self.inner.core.tx_count.load(Ordering::SeqCst)) == 0
+            || self.inner.core.core.is_closed()
     }
 
-    /// Returns the *total* capacity of the channel for this [`Receiver`].
-    /// This includes both occupied and unoccupied entries.
+    /// Returns a new [`Sender`] for this channel, or `None` if the channel has
+    /// already closed.
     ///
-    /// To determine the channel's remaining *unoccupied* capacity, use
-    /// [`remaining`] instead.
+    /// This allows producers to attach to an existing channel without
+    /// threading a [`Sender`] through to them from where the channel was
+    /// created. Any [`Sender`] may also be cloned to create additional senders.
+    ///
+    /// Once every [`Sender`] for a channel has been dropped, the channel is
+    /// closed permanently, and this method will always return `None`.
     ///
     /// # Examples
     ///
     /// ```
-    /// use thingbuf::mpsc::blocking::channel;
+    /// use thingbuf::mpsc::blocking;
     ///
-    /// let (_, rx) = channel::<usize>(100);
-    /// assert_eq!(rx.capacity(), 100);
-    /// ```
+    /// let (tx, rx) = blocking::channel::<usize>(4);
     ///
-    /// Even after sending several messages, the capacity remains
-    /// the same:
-    /// ```
-    /// # use thingbuf::mpsc::blocking::channel;
+    /// // Create a new sender from the receiver.
+    /// let tx2 = rx.sender().expect("channel is still open");
+    /// tx2.try_send(1).unwrap();
+    /// assert_eq!(rx.try_recv(), Ok(1));
+    ///
+    /// // Once all senders have been dropped, no new ones can be created.
+    /// drop((tx, tx2));
+    /// assert!(rx.sender().is_none());
+    /// ```
+    pub fn sender(&self) -> Option<Sender<T, R>> {
+        if !self.inner.core.add_tx() {
+            return None;
+        }
+
+        Some(Sender {
+            inner: self.inner.clone(),
+        })
+    }
+
+    /// Sets the number of times senders and this receiver will retry an
+    /// operation on a full (or empty) channel before waiting.
+    ///
+    /// By default, the spin budget is 0: when the channel is full, a
+    /// [`Sender`] immediately registers itself in the channel's wait queue,
+    /// and when the channel is empty, the receiver immediately parks its thread.
+    /// Raising the budget makes them retry (with a short backoff between
+    /// attempts) first, which can reduce latency when capacity or messages are
+    /// expected to become available very soon, at the cost of burning CPU time.
+    /// Oversubscribed systems should generally leave the budget at 0.
+    ///
+    /// The spin budget applies to the whole channel, and may be changed at any
+    /// time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (_tx, rx) = blocking::channel::<usize>(8);
+    ///
+    /// // Low-latency consumers may want to spin for a while before waiting.
+    /// rx.set_spin_budget(64);
+    /// assert_eq!(rx.spin_budget(), 64);
+    /// ```
+    #[inline]
+    pub fn set_spin_budget(&self, spins: usize) {
+        self.inner.core.spin_budget.store(spins, Ordering::Relaxed);
+    }
+
+    /// Returns the channel's current spin budget.
+    ///
+    /// See [`set_spin_budget`](Self::set_spin_budget) for details.
+    #[inline]
+    #[must_use]
+    pub fn spin_budget(&self) -> usize {
+        self.inner.core.spin_budget.load(Ordering::Relaxed)
+    }
+
+    /// Enables or disables adaptive spinning for this channel.
+    ///
+    /// When adaptive spinning is enabled, the channel's [spin
+    /// budget](Self::spin_budget) is tuned at runtime: whenever a sender or
+    /// receiver spins until it runs out of budget, the budget is halved, and
+    /// whenever spinning succeeds before the budget runs out, it is doubled (up
+    /// to an upper limit of 1024). Adaptive spinning also backs off further than
+    /// fixed spinning, yielding the thread to the OS scheduler once the budget
+    /// is long enough, before finally waiting. This lets the channel spin when
+    /// messages arrive in quick succession, and stop burning CPU time when they
+    /// do not.
+    ///
+    /// Adaptive spinning is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (_tx, rx) = blocking::channel::<usize>(8);
+    ///
+    /// rx.set_adaptive_spinning(true);
+    /// assert!(rx.is_adaptive_spinning());
+    /// ```
+    #[inline]
+    pub fn set_adaptive_spinning(&self, adaptive: bool) {
+        self.inner
+            .core
+            .adaptive_spin
+            .store(adaptive, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if adaptive spinning is enabled for this channel.
+    ///
+    /// See [`set_adaptive_spinning`](Self::set_adaptive_spinning) for details.
+    #[inline]
+    #[must_use]
+    pub fn is_adaptive_spinning(&self) -> bool {
+        self.inner.core.adaptive_spin.load(Ordering::Relaxed)
+    }
+
+    /// Enables probabilistic load shedding above the given occupancy
+    /// `ratio`, or disables it if `ratio` is `None`.
+    ///
+    /// When load shedding is enabled and the fraction of the channel's
+    /// capacity that is occupied exceeds `ratio`, messages sent with
+    /// `try_send` or `send_timeout` are randomly dropped rather than sent. The
+    /// probability that a message is dropped rises linearly from 0 at the
+    /// threshold to 1 when the channel is full. A dropped message is handed
+    /// back in a [`TrySendError::Shed`] or [`SendTimeoutError::Shed`] error,
+    /// and counted by [`shed_count`](Self::shed_count). Messages sent with
+    /// `send`, which waits for capacity instead, or with `send_ref` or
+    /// `try_send_ref`, are never dropped. If `ratio` is 1.0, no messages are
+    /// dropped, and a full channel is still reported as full.
+    ///
+    /// This lets producers that can tolerate lost messages, such as
+    /// metrics pipelines, degrade gracefully as a slow consumer falls
+    /// behind, rather than all being stalled as soon as the channel fills
+    /// up.
+    ///
+    /// Load shedding is disabled by default.
+    ///
+    /// # Panics
+    ///
+    /// If `ratio` is not between 0.0 and 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use thingbuf::mpsc::errors::TrySendError;
+    ///
+    /// let (tx, rx) = blocking::channel::<usize>(64);
+    ///
+    /// // Start shedding load once the channel is half full.
+    /// rx.set_load_shedding(Some(0.5));
+    ///
+    /// for i in 0..1000 {
+    ///     match tx.try_send(i) {
+    ///         // A shed message is handed back, and may be dropped.
+    ///         Ok(()) | Err(TrySendError::Shed(_)) => {}
+    ///         Err(e) => panic!("unexpected error: {}", e),
+    ///     }
+    ///     if i % 2 == 0 {
+    ///         rx.try_recv().unwrap();
+    ///     }
+    /// }
+    /// assert!(rx.shed_count() > 0);
+    /// ```
+    #[inline]
+    pub fn set_load_shedding(&self, ratio: Option<f64>) {
+        self.inner.core.set_load_shedding(ratio);
+    }
+
+    /// Returns the number of messages that have been dropped by load
+    /// shedding.
+    ///
+    /// See [`set_load_shedding`](Self::set_load_shedding) for details.
+    #[inline]
+    #[must_use]
+    pub fn shed_count(&self) -> usize {
+        self.inner.core.shed_count.load(Ordering::Relaxed)
+    }
+
+    /// Sets the policy used to wake senders that are waiting for capacity,
+    /// when this receiver frees slots.
+    ///
+    /// By default, one waiting sender is woken for each freed slot. See
+    /// [`NotifyPolicy`] for the available policies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::{blocking, NotifyPolicy};
+    ///
+    /// let (tx, rx) = blocking::channel::<usize>(64);
+    ///
+    /// // Wake every waiting sender when 16 or more slots are freed at once.
+    /// rx.set_notify_policy(NotifyPolicy::WakeAll { threshold: 16 });
+    /// assert_eq!(rx.notify_policy(), NotifyPolicy::WakeAll { threshold: 16 });
+    /// ```
+    #[inline]
+    pub fn set_notify_policy(&self, policy: NotifyPolicy) {
+        self.inner.core.set_notify_policy(policy);
+    }
+
+    /// Returns the policy used to wake waiting senders.
+    ///
+    /// See [`set_notify_policy`](Self::set_notify_policy) for details.
+    #[inline]
+    #[must_use]
+    pub fn notify_policy(&self) -> NotifyPolicy {
+        self.inner.core.notify_policy()
+    }
+
+    /// Makes senders wake this receiver only once at least `watermark`
+    /// messages are queued, or disables batching if `watermark` is 1.
+    ///
+    /// By default, every message sent wakes a waiting receiver. A receiver
+    /// that prefers to process messages in batches can raise the watermark,
+    /// so that it is woken up once per batch rather than once per message,
+    /// which reduces wakeups (and context switches) when messages arrive in
+    /// bursts. So that a partial batch is never left waiting indefinitely,
+    /// while a watermark is set the receiver also checks the channel every
+    /// `max_delay` while it waits. A watermark greater than the channel's
+    /// capacity is lowered to the capacity.
+    ///
+    /// Receiving never waits for the watermark when messages are already
+    /// queued: the watermark only controls when a *waiting* receiver is
+    /// woken.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use std::{thread, time::Duration};
+    ///
+    /// let (tx, rx) = blocking::channel::<usize>(64);
+    ///
+    /// // Wake up once 16 messages are ready, or after at most 10ms.
+    /// rx.set_wake_watermark(16, Duration::from_millis(10));
+    /// assert_eq!(rx.wake_watermark(), 16);
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 0..4 {
+    ///         tx.send(i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// // Fewer messages than the watermark are still received, once the
+    /// // maximum delay has elapsed.
+    /// let received = rx.into_iter().take(4).collect::<Vec<_>>();
+    /// assert_eq!(received, vec![0, 1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn set_wake_watermark(&self, watermark: usize, max_delay: Duration) {
+        self.inner.core.set_wake_watermark(watermark, max_delay);
+        // Wake the receiver, if it is waiting, so that it observes the new
+        // watermark.
+        self.inner.core.rx_wait.notify();
+    }
+
+    /// Returns the number of queued messages at which senders wake this
+    /// receiver.
+    ///
+    /// See [`set_wake_watermark`](Self::set_wake_watermark) for details.
+    #[inline]
+    #[must_use]
+    pub fn wake_watermark(&self) -> usize {
+        self.inner.core.wake_watermark.load(Ordering::Relaxed)
+    }
+
+    /// Returns the *total* capacity of the channel for this [`Receiver`].
+    /// This includes both occupied and unoccupied entries.
+    ///
+    /// To determine the channel's remaining *unoccupied* capacity, use
+    /// [`remaining`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking::channel;
+    ///
+    /// let (_, rx) = channel::<usize>(100);
+    /// assert_eq!(rx.capacity(), 100);
+    /// ```
+    ///
+    /// Even after sending several messages, the capacity remains
+    /// the same:
+    /// ```
+    /// # use thingbuf::mpsc::blocking::channel;
     ///
     /// let (tx, rx) = channel::<usize>(100);
     /// *tx.try_send_ref().unwrap() = 1;
@@ -1971,95 +4244,498 @@ impl<T, R> Receiver<T, R> {
         self.inner.core.core.capacity()
     }
 
-    /// Returns the unoccupied capacity of the channel for this [`Receiver`]
-    /// (i.e., how many additional elements can be sent before the channel
-    /// will be full).
+    /// Returns a snapshot of the statistics recorded for this channel.
+    ///
+    /// See the [`stats`](crate::stats) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking::channel;
+    ///
+    /// let (tx, rx) = channel::<usize>(100);
+    /// tx.try_send(1).unwrap();
+    ///
+    /// assert_eq!(rx.stats().occupancy().samples(), 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    #[must_use]
+    pub fn stats(&self) -> crate::stats::Stats {
+        self.inner.core.core.stats()
+    }
+
+    /// Returns the sequence number of the next message that will be
+    /// received from this channel.
+    ///
+    /// This is the number of messages that have been received so far.
+    /// Comparing it with the [`seq`] of a received message, or with the
+    /// [`Sender::published_seq`], allows consumers to detect gaps in the
+    /// sequence of messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (tx, rx) = blocking::channel::<usize>(8);
+    /// tx.send(1).unwrap();
+    /// tx.send(1).unwrap();
+    /// assert_eq!(rx.consumed_seq(), 0);
+    ///
+    /// let msg = rx.recv_ref().unwrap();
+    /// assert_eq!(msg.seq(), 0);
+    /// drop(msg);
+    /// assert_eq!(rx.consumed_seq(), 1);
+    /// ```
+    ///
+    /// [`seq`]: RecvRef::seq
+    #[cfg(feature = "seq")]
+    #[inline]
+    #[must_use]
+    pub fn consumed_seq(&self) -> usize {
+        self.inner.core.core.consumed_seq()
+    }
+
+    /// Returns the unoccupied capacity of the channel for this [`Receiver`]
+    /// (i.e., how many additional elements can be sent before the channel
+    /// will be full).
+    ///
+    /// This is equivalent to subtracting the channel's [`len`] from its [`capacity`].
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking::channel;
+    ///
+    /// let (tx, rx) = channel::<usize>(100);
+    /// assert_eq!(rx.remaining(), 100);
+    ///
+    /// *tx.try_send_ref().unwrap() = 1;
+    /// *tx.try_send_ref().unwrap() = 2;
+    /// *tx.try_send_ref().unwrap() = 3;
+    /// assert_eq!(rx.remaining(), 97);
+    ///
+    /// let _ = rx.try_recv_ref().unwrap();
+    /// assert_eq!(rx.remaining(), 98)
+    /// ```
+    ///
+    /// [`len`]: Self::len
+    /// [`capacity`]: Self::capacity
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    /// Returns the number of elements in the channel of this [`Receiver`].
+    ///
+    /// To determine the channel's remaining *unoccupied* capacity, use
+    /// [`remaining`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking::channel;
+    ///
+    /// let (tx, rx) = channel::<usize>(100);
+    /// assert_eq!(rx.len(), 0);
+    ///
+    /// *tx.try_send_ref().unwrap() = 1;
+    /// *tx.try_send_ref().unwrap() = 2;
+    /// *tx.try_send_ref().unwrap() = 3;
+    /// assert_eq!(rx.len(), 3);
+    ///
+    /// let _ = rx.try_recv_ref().unwrap();
+    /// assert_eq!(rx.len(), 2);
+    /// ```
+    ///
+    /// [`remaining`]: Self::remaining
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.core.core.len()
+    }
+
+    /// Returns whether the number of elements in the channel of this [`Receiver`] is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use thingbuf::mpsc::channel;
+    /// let (tx, rx) = channel::<usize>(100);
+    /// assert!(rx.is_empty());
+    ///
+    /// *tx.try_send_ref().unwrap() = 1;
+    ///
+    /// assert!(!rx.is_empty());
+    /// ```
+    ///
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Converts this `Receiver` back into a [`ThingBuf`] containing any
+    /// messages that were not received, once every [`Sender`] has been dropped.
+    ///
+    /// This allows the channel's storage (and any allocations owned by the
+    /// messages in it) to be reused or inspected after the channel has shut
+    /// down. The returned `ThingBuf` has the same capacity and [recycling
+    /// policy] as the channel, and may be pushed to again.
+    ///
+    /// # Errors
+    ///
+    /// If any [`Sender`]s still exist, this returns the `Receiver` unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    ///
+    /// let (tx, rx) = blocking::channel(4);
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    ///
+    /// // The sender still exists, so the buffer can't be recovered yet.
+    /// let rx = rx.into_inner().unwrap_err();
+    /// assert_eq!(rx.recv(), Some(1));
+    ///
+    /// drop(tx);
+    /// let buf = rx.into_inner().unwrap();
+    /// assert_eq!(buf.pop(), Some(2));
+    ///
+    /// // The buffer may be reused.
+    /// buf.push(3).unwrap();
+    /// assert_eq!(buf.pop(), Some(3));
+    /// ```
+    ///
+    /// [`ThingBuf`]: crate::ThingBuf
+    /// [recycling policy]: crate::recycling::Recycle
+    pub fn into_inner(self) -> Result<crate::ThingBuf<T, R>, Self> {
+        // `Receiver` closes the channel when it's dropped, so move the `Arc`
+        // out of it without running its destructor.
+        let this = mem::ManuallyDrop::new(self);
+        // Safety: `this` is never used again, and is not dropped.
+        let inner = unsafe { ptr::read(&this.inner) };
+        Arc::try_unwrap(inner)
+            .map(Inner::into_thingbuf)
+            .map_err(|inner| Self { inner })
+    }
+}
+
+impl<'a, T, R> Iterator for &'a Receiver<T, R> {
+    type Item = RecvRef<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv_ref()
+    }
+}
+
+impl<T, R> Drop for Receiver<T, R> {
+    fn drop(&mut self) {
+        self.inner.core.close_rx();
+    }
+}
+
+// === impl Transaction ===
+
+impl<T> Transaction<'_, T> {
+    /// Returns the number of messages in this transaction.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.slots.len()
+    }
+
+    /// Returns `true` if this transaction contains no messages.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.slots.is_empty()
+    }
+
+    /// Publishes every message in this transaction to the receiver.
+    ///
+    /// This is equivalent to dropping the transaction.
+    #[inline]
+    pub fn commit(self) {
+        drop(self)
+    }
+}
+
+impl<T> ops::Index<usize> for Transaction<'_, T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, idx: usize) -> &T {
+        &self.0.slots[idx]
+    }
+}
+
+impl<T> ops::IndexMut<usize> for Transaction<'_, T> {
+    #[inline]
+    fn index_mut(&mut self, idx: usize) -> &mut T {
+        &mut self.0.slots[idx]
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Transaction<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.slots.iter()).finish()
+    }
+}
+
+// === impl Dedicated ===
+
+impl<'a, T, R: Recycle<T>> Dedicated<'a, T, R> {
+    fn new(
+        core: &'a ChannelCore<Unparker>,
+        slots: &'a [Slot<T>],
+        recycle: &'a R,
+        batch: usize,
+    ) -> Self {
+        Self {
+            core,
+            slots,
+            recycle,
+            batch,
+        }
+    }
+
+    /// Reserves a single slot in the channel to mutate in place, blocking
+    /// until there is capacity.
     ///
-    /// This is equivalent to subtracting the channel's [`len`] from its [`capacity`].
-    /// # Examples
+    /// This does not claim a block of slots; it behaves exactly like
+    /// [`Sender::send_ref`]. Use [`send_all`](Self::send_all) to send
+    /// messages in batches.
     ///
-    /// ```
-    /// use thingbuf::mpsc::blocking::channel;
+    /// # Errors
     ///
-    /// let (tx, rx) = channel::<usize>(100);
-    /// assert_eq!(rx.remaining(), 100);
+    /// If the [`Receiver`] end of the channel has been dropped, this
+    /// returns a [`Closed`] error.
+    pub fn send_ref(&mut self) -> Result<SendRef<'_, T>, Closed> {
+        send_ref(self.core, self.slots, self.recycle)
+    }
+
+    /// Sends a single message by value, blocking until there is capacity.
     ///
-    /// *tx.try_send_ref().unwrap() = 1;
-    /// *tx.try_send_ref().unwrap() = 2;
-    /// *tx.try_send_ref().unwrap() = 3;
-    /// assert_eq!(rx.remaining(), 97);
+    /// See [`send_ref`](Self::send_ref) for details.
     ///
-    /// let _ = rx.try_recv_ref().unwrap();
-    /// assert_eq!(rx.remaining(), 98)
-    /// ```
+    /// # Errors
     ///
-    /// [`len`]: Self::len
-    /// [`capacity`]: Self::capacity
-    #[must_use]
-    pub fn remaining(&self) -> usize {
-        self.capacity() - self.len()
+    /// If the [`Receiver`] end of the channel has been dropped, this
+    /// returns a [`Closed`] error containing the message.
+    pub fn send(&mut self, val: T) -> Result<(), Closed<T>> {
+        match self.send_ref() {
+            Err(Closed(())) => Err(Closed(val)),
+            Ok(mut slot) => {
+                *slot = val;
+                Ok(())
+            }
+        }
     }
 
-    /// Returns the number of elements in the channel of this [`Receiver`].
+    /// Sends every item produced by `iter` to the channel, claiming slots in
+    /// blocks of up to `batch` slots at a time.
     ///
-    /// To determine the channel's remaining *unoccupied* capacity, use
-    /// [`remaining`] instead.
+    /// The size of each block is bounded by the number of items `iter`
+    /// reports it has left (the lower bound of its [`size_hint`]), so an
+    /// iterator of unknown length is sent one slot at a time. Each message is
+    /// published as soon as it is written. If there are not enough
+    /// consecutive free slots for a block, this waits for a single slot
+    /// instead, like [`Sender::send`].
     ///
-    /// # Examples
+    /// No slots remain claimed once this returns: if `iter` ends before a
+    /// claimed block is filled, the rest of the block is given up and
+    /// skipped by the receiver. While a block is being filled, the receiver
+    /// cannot receive messages sent after it by other senders, so `iter`
+    /// should produce its items without blocking.
     ///
-    /// ```
-    /// use thingbuf::mpsc::blocking::channel;
+    /// Returns the number of items sent.
     ///
-    /// let (tx, rx) = channel::<usize>(100);
-    /// assert_eq!(rx.len(), 0);
+    /// # Errors
     ///
-    /// *tx.try_send_ref().unwrap() = 1;
-    /// *tx.try_send_ref().unwrap() = 2;
-    /// *tx.try_send_ref().unwrap() = 3;
-    /// assert_eq!(rx.len(), 3);
+    /// If the [`Receiver`] end of the channel is dropped before every item
+    /// has been sent, this returns a [`SendAllError`] containing the number
+    /// of items that were sent, the item that could not be sent, and the rest
+    /// of the iterator.
     ///
-    /// let _ = rx.try_recv_ref().unwrap();
-    /// assert_eq!(rx.len(), 2);
-    /// ```
+    /// [`size_hint`]: Iterator::size_hint
+    pub fn send_all<I>(&mut self, iter: I) -> Result<usize, SendAllError<T, I::IntoIter>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        let mut sent = 0;
+        while let Some(item) = iter.next() {
+            let n = cmp::min(self.batch, iter.size_hint().0.saturating_add(1));
+            let block = if n > 1 {
+                match self.core.core.push_n_ref(self.slots, self.recycle, n) {
+                    Ok(block) => block,
+                    Err(TrySendError::Closed(_)) => {
+                        return Err(SendAllError {
+                            sent,
+                            item,
+                            rest: iter,
+                        })
+                    }
+                    Err(_) => Vec::new(),
+                }
+            } else {
+                Vec::new()
+            };
+
+            if block.is_empty() {
+                if let Err(Closed(item)) = self.send(item) {
+                    return Err(SendAllError {
+                        sent,
+                        item,
+                        rest: iter,
+                    });
+                }
+                sent += 1;
+                continue;
+            }
+
+            let mut item = Some(item);
+            let mut abandoned = false;
+            for slot in block {
+                match item.take().or_else(|| iter.next()) {
+                    Some(val) => {
+                        let mut slot = SendRef(SendRefInner {
+                            slot,
+                            _notify: NotifyRx(self.core),
+                            _audit: self.core.ref_audit.send(),
+                            _watch: RefWatch::send(),
+                        });
+                        *slot = val;
+                        sent += 1;
+                    }
+                    None => {
+                        self.core.core.abandon_ref(slot);
+                        abandoned = true;
+                    }
+                }
+            }
+            if abandoned {
+                // the receiver may be waiting for the first of these slots.
+                self.core.rx_wait.notify();
+            }
+        }
+        Ok(sent)
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for Dedicated<'_, T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dedicated")
+            .field("core", &self.core)
+            .field("batch", &self.batch)
+            .field("recycle", &self.recycle)
+            .finish()
+    }
+}
+
+// === impl SenderMap ===
+
+impl<T, U, F, R> SenderMap<T, U, F, R>
+where
+    F: Fn(U, &mut T),
+    R: Recycle<T>,
+{
+    /// Sends a message, converting it into a slot in the channel, blocking
+    /// until there is capacity.
     ///
-    /// [`remaining`]: Self::remaining
-    #[inline]
-    #[must_use]
-    pub fn len(&self) -> usize {
-        self.inner.core.core.len()
+    /// # Errors
+    ///
+    /// If the [`Receiver`] end of the channel has been dropped, this returns
+    /// a [`Closed`] error containing the message.
+    pub fn send(&self, val: U) -> Result<(), Closed<U>> {
+        match self.tx.send_ref() {
+            Err(Closed(())) => Err(Closed(val)),
+            Ok(mut slot) => {
+                (self.map)(val, &mut slot);
+                Ok(())
+            }
+        }
     }
 
-    /// Returns whether the number of elements in the channel of this [`Receiver`] is 0.
+    /// Sends a message, converting it into a slot in the channel, blocking
+    /// for at most `timeout` until there is capacity.
     ///
-    /// # Examples
-    /// ```
-    /// use thingbuf::mpsc::channel;
-    /// let (tx, rx) = channel::<usize>(100);
-    /// assert!(rx.is_empty());
+    /// # Errors
     ///
-    /// *tx.try_send_ref().unwrap() = 1;
+    /// - [`Err`]`(`[`SendTimeoutError::Timeout`]`)` if the timeout has
+    ///   elapsed.
+    /// - [`Err`]`(`[`SendTimeoutError::Closed`]`)` if the [`Receiver`] end of
+    ///   the channel has been dropped.
     ///
-    /// assert!(!rx.is_empty());
-    /// ```
+    /// In both cases, the error includes the value passed to `send_timeout`.
+    #[cfg(not(all(test, loom)))]
+    pub fn send_timeout(&self, val: U, timeout: Duration) -> Result<(), SendTimeoutError<U>> {
+        if self.tx.inner.core.shed() {
+            return Err(SendTimeoutError::Shed(val));
+        }
+        match self.tx.send_ref_timeout(timeout) {
+            Err(e) => Err(e.with_value(val)),
+            Ok(mut slot) => {
+                (self.map)(val, &mut slot);
+                Ok(())
+            }
+        }
+    }
+
+    /// Attempts to send a message, converting it into a slot in the channel,
+    /// without blocking.
     ///
-    #[inline]
-    #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TrySendError::Full`]`)` if the channel is at capacity.
+    /// - [`Err`]`(`[`TrySendError::Closed`]`)` if the [`Receiver`] end of the
+    ///   channel has been dropped.
+    /// - [`Err`]`(`[`TrySendError::Shed`]`)` if the message was dropped by
+    ///   the channel's load shedding.
+    ///
+    /// In each case, the error includes the value passed to `try_send`.
+    pub fn try_send(&self, val: U) -> Result<(), TrySendError<U>> {
+        if self.tx.inner.core.shed() {
+            return Err(TrySendError::Shed(val));
+        }
+        match self.tx.try_send_ref() {
+            Err(e) => Err(e.with_value(val)),
+            Ok(mut slot) => {
+                (self.map)(val, &mut slot);
+                Ok(())
+            }
+        }
     }
 }
 
-impl<'a, T, R> Iterator for &'a Receiver<T, R> {
-    type Item = RecvRef<'a, T>;
+impl<T, U, F, R> SenderMap<T, U, F, R> {
+    /// Returns a reference to the [`Sender`] this handle sends to.
+    pub fn get_ref(&self) -> &Sender<T, R> {
+        &self.tx
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.recv_ref()
+    /// Consumes this `SenderMap`, returning the underlying [`Sender`].
+    pub fn into_inner(self) -> Sender<T, R> {
+        self.tx
     }
 }
 
-impl<T, R> Drop for Receiver<T, R> {
-    fn drop(&mut self) {
-        self.inner.core.close_rx();
+impl<T, U, F: Clone, R> Clone for SenderMap<T, U, F, R> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            map: self.map.clone(),
+            _input: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, U, F, R: fmt::Debug> fmt::Debug for SenderMap<T, U, F, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SenderMap")
+            .field("tx", &self.tx)
+            .finish_non_exhaustive()
     }
 }
 
@@ -2075,47 +4751,176 @@ impl<T, R: fmt::Debug> fmt::Debug for Inner<T, R> {
     }
 }
 
+impl<T, R> Inner<T, R> {
+    fn into_thingbuf(self) -> crate::ThingBuf<T, R> {
+        // `Inner` drops the slots when it's dropped, so move its fields out
+        // without running its destructor.
+        let this = mem::ManuallyDrop::new(self);
+        // Safety: each field is read exactly once, and `this` is not dropped.
+        let (core, slots, recycle) = unsafe {
+            (
+                ptr::read(&this.core),
+                ptr::read(&this.slots),
+                ptr::read(&this.recycle),
+            )
+        };
+        core.into_thingbuf(slots, recycle)
+    }
+}
+
 impl<T, R> Drop for Inner<T, R> {
     fn drop(&mut self) {
         self.core.core.drop_slots(&mut self.slots[..])
     }
 }
 
+// === impl group::Member ===
+
+#[cfg(not(all(loom, test)))]
+use super::group::{self, Close, MemberRef};
+
+#[cfg(not(all(loom, test)))]
+impl<T, R> Close for Inner<T, R> {
+    fn close(&self) {
+        self.core.close();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.core.is_closed()
+    }
+
+    fn links(&self) -> &group::Links {
+        self.core.links()
+    }
+}
+
+#[cfg(not(all(loom, test)))]
+impl<T: Send + 'static, R: Send + 'static> group::Sealed for Sender<T, R> {
+    fn member_ref(&self) -> MemberRef {
+        MemberRef::Dynamic(Arc::<Inner<T, R>>::downgrade(&self.inner))
+    }
+}
+
+#[cfg(not(all(loom, test)))]
+impl<T: Send + 'static, R: Send + 'static> group::Member for Sender<T, R> {}
+
+#[cfg(not(all(loom, test)))]
+impl<T: Send + 'static, R: Send + 'static> group::Sealed for Receiver<T, R> {
+    fn member_ref(&self) -> MemberRef {
+        MemberRef::Dynamic(Arc::<Inner<T, R>>::downgrade(&self.inner))
+    }
+}
+
+#[cfg(not(all(loom, test)))]
+impl<T: Send + 'static, R: Send + 'static> group::Member for Receiver<T, R> {}
+
+#[cfg(all(feature = "static", not(all(loom, test))))]
+impl<T, R> group::Sealed for StaticSender<T, R> {
+    fn member_ref(&self) -> MemberRef {
+        MemberRef::Static(self.core)
+    }
+}
+
+#[cfg(all(feature = "static", not(all(loom, test))))]
+impl<T, R> group::Member for StaticSender<T, R> {}
+
+#[cfg(all(feature = "static", not(all(loom, test))))]
+impl<T, R> group::Sealed for StaticReceiver<T, R> {
+    fn member_ref(&self) -> MemberRef {
+        MemberRef::Static(self.core)
+    }
+}
+
+#[cfg(all(feature = "static", not(all(loom, test))))]
+impl<T, R> group::Member for StaticReceiver<T, R> {}
+
 #[inline]
-fn recv_ref<'a, T>(core: &'a ChannelCore<Thread>, slots: &'a [Slot<T>]) -> Option<RecvRef<'a, T>> {
+fn recv_ref<'a, T>(
+    core: &'a ChannelCore<Unparker>,
+    slots: &'a [Slot<T>],
+) -> Option<RecvRef<'a, T>> {
     loop {
-        match core.poll_recv_ref(slots, thread::current) {
+        match core.poll_recv_ref(slots, park::current) {
             Poll::Ready(r) => {
                 return r.map(|slot| {
                     RecvRef(RecvRefInner {
-                        _notify: super::NotifyTx(&core.tx_wait),
+                        _notify: super::NotifyTx(core),
+                        _audit: core.ref_audit.recv(),
+                        _watch: RefWatch::recv(),
                         slot,
                     })
                 })
             }
             Poll::Pending => {
                 test_println!("parking ({:?})", thread::current());
-                thread::park();
+                park_rx(core);
+            }
+        }
+    }
+}
+
+#[inline]
+fn recv_into<T: Copy>(core: &ChannelCore<Unparker>, slots: &[Slot<T>], out: &mut [T]) -> usize {
+    if out.is_empty() {
+        return 0;
+    }
+    loop {
+        match core.poll_recv_with(park::current, || core.try_recv_into(slots, out)) {
+            Poll::Ready(n) => return n.unwrap_or(0),
+            Poll::Pending => {
+                test_println!("parking ({:?})", thread::current());
+                park_rx(core);
+            }
+        }
+    }
+}
+
+/// Parks the receiving thread until a message is available, returning `false`
+/// if the channel has closed and all messages have been received instead.
+#[inline]
+fn wait_for_message<T>(core: &ChannelCore<Unparker>, slots: &[Slot<T>]) -> bool {
+    loop {
+        // Safety: the peeked reference is dropped before this returns.
+        match unsafe { core.poll_peek(slots, park::current) } {
+            Poll::Ready(msg) => return msg.is_some(),
+            Poll::Pending => {
+                test_println!("parking ({:?})", thread::current());
+                park_rx(core);
             }
         }
     }
 }
 
+/// Parks the receiving thread until it is notified, or, if the channel has a
+/// wake watermark, until its maximum wake delay has elapsed.
+#[inline]
+fn park_rx(core: &ChannelCore<Unparker>) {
+    match core.wake_max_delay() {
+        // loom does not model timeouts, so a loom receiver is always woken by
+        // a sender.
+        #[cfg(not(all(test, loom)))]
+        Some(max_delay) => park::park_timeout(max_delay),
+        _ => park::park(),
+    }
+}
+
 #[cfg(not(all(test, loom)))]
 #[inline]
 fn recv_ref_timeout<'a, T>(
-    core: &'a ChannelCore<Thread>,
+    core: &'a ChannelCore<Unparker>,
     slots: &'a [Slot<T>],
     timeout: Duration,
 ) -> Result<RecvRef<'a, T>, RecvTimeoutError> {
     let beginning_park = Instant::now();
     loop {
-        match core.poll_recv_ref(slots, thread::current) {
+        match core.poll_recv_ref(slots, park::current) {
             Poll::Ready(r) => {
                 return r
                     .map(|slot| {
                         RecvRef(RecvRefInner {
-                            _notify: super::NotifyTx(&core.tx_wait),
+                            _notify: super::NotifyTx(core),
+                            _audit: core.ref_audit.recv(),
+                            _watch: RefWatch::recv(),
                             slot,
                         })
                     })
@@ -2123,7 +4928,8 @@ fn recv_ref_timeout<'a, T>(
             }
             Poll::Pending => {
                 test_println!("park_timeout ({:?})", thread::current());
-                thread::park_timeout(timeout);
+                let max_delay = core.wake_max_delay().unwrap_or(timeout);
+                park::park_timeout(timeout.min(max_delay));
                 let elapsed = beginning_park.elapsed();
                 if elapsed >= timeout {
                     return Err(RecvTimeoutError::Timeout);
@@ -2133,15 +4939,49 @@ fn recv_ref_timeout<'a, T>(
     }
 }
 
+/// Sends `val` without taking any locks, for `try_send_from_signal`.
+///
+/// The slot is written directly, rather than through a `SendRef`, so that it
+/// is not tracked by the watchdog, and a receiver that blocks with a custom
+/// parker, whose `Unpark` implementation may not be async-signal-safe, is
+/// caught by a debug assertion.
+#[inline]
+fn send_from_signal<T: Copy, R: Recycle<T>>(
+    core: &ChannelCore<Unparker>,
+    slots: &[Slot<T>],
+    val: T,
+    recycle: &R,
+) -> Result<(), TrySendError<T>> {
+    if core.shed() {
+        return Err(TrySendError::Shed(val));
+    }
+    match core.core.push_ref(slots, recycle) {
+        // The message is published when the slot is dropped.
+        Ok(mut slot) => slot.with_mut(|slot| *slot = val),
+        Err(e) => return Err(e.with_value(val)),
+    }
+    if core.should_notify_rx() {
+        core.rx_wait.notify_with(|rx| {
+            debug_assert!(
+                matches!(rx, Unparker::Thread(_)),
+                "`try_send_from_signal` woke a receiver that blocks with a custom \
+                parker, which is not async-signal-safe"
+            );
+            rx.notify()
+        });
+    }
+    Ok(())
+}
+
 #[inline]
 fn send_ref<'a, T, R: Recycle<T>>(
-    core: &'a ChannelCore<Thread>,
+    core: &'a ChannelCore<Unparker>,
     slots: &'a [Slot<T>],
     recycle: &'a R,
 ) -> Result<SendRef<'a, T>, Closed<()>> {
     // fast path: avoid getting the thread and constructing the node if the
-    // slot is immediately ready.
-    match core.try_send_ref(slots, recycle) {
+    // slot is immediately ready (or becomes ready within the spin budget).
+    match core.try_send_ref_spinning(slots, recycle) {
         Ok(slot) => return Ok(SendRef(slot)),
         Err(TrySendError::Closed(_)) => return Err(Closed(())),
         _ => {}
@@ -2149,7 +4989,7 @@ fn send_ref<'a, T, R: Recycle<T>>(
 
     let mut waiter = queue::Waiter::new();
     let mut unqueued = true;
-    let thread = thread::current();
+    let thread = park::current();
     let mut boff = Backoff::new();
     loop {
         let node = unsafe {
@@ -2177,7 +5017,107 @@ fn send_ref<'a, T, R: Recycle<T>>(
             }
             WaitResult::Wait => {
                 unqueued = false;
-                thread::park();
+                park::park();
+            }
+        }
+    }
+}
+
+/// Blocks until every message sent before this was called has been received.
+#[cfg(feature = "seq")]
+fn flush(core: &ChannelCore<Unparker>) -> Result<(), Closed> {
+    let target = core.core.published_seq();
+    if core.is_flushed(target) {
+        return Ok(());
+    }
+
+    let mut waiter = queue::Waiter::new();
+    let mut queued = false;
+    let thread = park::current();
+    loop {
+        let node = unsafe {
+            // Safety: in this case, it's totally safe to pin the waiter, as
+            // it is owned uniquely by this function, and it cannot possibly
+            // be moved while this thread is parked.
+            Pin::new_unchecked(&mut waiter)
+        };
+
+        let wait = if queued {
+            test_dbg!(core.flush_wait.continue_wait(node, &thread))
+        } else {
+            test_dbg!(core.flush_wait.start_wait(node, &thread))
+        };
+
+        match wait {
+            // The receiver was dropped, but it may have received every
+            // message we were waiting for first.
+            WaitResult::Closed if core.is_flushed(target) => return Ok(()),
+            WaitResult::Closed => return Err(Closed(())),
+            // A message was received; check whether it was the last one we
+            // are waiting for. The waiter is no longer queued, so it must be
+            // queued again if we have to keep waiting.
+            WaitResult::Notified => {
+                if core.is_flushed(target) {
+                    return Ok(());
+                }
+                queued = false;
+            }
+            // We were just added to the queue, so check again before parking,
+            // in case the last message was received before the receiver could
+            // see us in the queue. This fence pairs with the one in
+            // `notify_all_waiting`.
+            WaitResult::Wait if !queued => {
+                queued = true;
+                test_dbg!(atomic::fence(Ordering::SeqCst));
+                if core.is_flushed(target) {
+                    // don't leave a dangling pointer to the waiter in the
+                    // queue.
+                    let node = unsafe { Pin::new_unchecked(&mut waiter) };
+                    if node.is_linked() {
+                        node.remove(&core.flush_wait);
+                    }
+                    return Ok(());
+                }
+            }
+            WaitResult::Wait => park::park(),
+        }
+    }
+}
+
+#[inline]
+fn assert_transaction_fits(n: usize, capacity: usize) {
+    assert!(
+        n <= capacity,
+        "a transaction of {} messages can never fit in a channel with capacity {}",
+        n,
+        capacity
+    );
+}
+
+/// Waits until `n` consecutive slots are available.
+///
+/// A transaction needs several slots to be released before it can proceed,
+/// while each release only notifies a single waiting sender. Rather than
+/// taking a place in the wait queue (and consuming notifications that another
+/// sender could have used), this polls with an increasing backoff.
+#[cfg(not(all(test, loom)))]
+fn transaction<'a, T, R: Recycle<T>>(
+    core: &'a ChannelCore<Unparker>,
+    slots: &'a [Slot<T>],
+    recycle: &'a R,
+    n: usize,
+) -> Result<Transaction<'a, T>, Closed> {
+    const MAX_SLEEP: Duration = Duration::from_millis(1);
+    let mut boff = Backoff::new();
+    let mut sleep = Duration::from_micros(10);
+    loop {
+        match core.try_transaction(slots, recycle, n) {
+            Ok(transaction) => return Ok(Transaction(transaction)),
+            Err(TrySendError::Closed(_)) => return Err(Closed(())),
+            Err(_) if !boff.done_spinning() => boff.spin_yield(),
+            Err(_) => {
+                thread::sleep(sleep);
+                sleep = (sleep * 2).min(MAX_SLEEP);
             }
         }
     }
@@ -2186,14 +5126,14 @@ fn send_ref<'a, T, R: Recycle<T>>(
 #[cfg(not(all(test, loom)))]
 #[inline]
 fn send_ref_timeout<'a, T, R: Recycle<T>>(
-    core: &'a ChannelCore<Thread>,
+    core: &'a ChannelCore<Unparker>,
     slots: &'a [Slot<T>],
     recycle: &'a R,
     timeout: Duration,
 ) -> Result<SendRef<'a, T>, SendTimeoutError> {
     // fast path: avoid getting the thread and constructing the node if the
-    // slot is immediately ready.
-    match core.try_send_ref(slots, recycle) {
+    // slot is immediately ready (or becomes ready within the spin budget).
+    match core.try_send_ref_spinning(slots, recycle) {
         Ok(slot) => return Ok(SendRef(slot)),
         Err(TrySendError::Closed(_)) => return Err(SendTimeoutError::Closed(())),
         _ => {}
@@ -2201,7 +5141,7 @@ fn send_ref_timeout<'a, T, R: Recycle<T>>(
 
     let mut waiter = queue::Waiter::new();
     let mut unqueued = true;
-    let thread = thread::current();
+    let thread = park::current();
     let mut boff = Backoff::new();
     let beginning_park = Instant::now();
     loop {
@@ -2230,9 +5170,14 @@ fn send_ref_timeout<'a, T, R: Recycle<T>>(
             }
             WaitResult::Wait => {
                 unqueued = false;
-                thread::park_timeout(timeout);
+                park::park_timeout(timeout);
                 let elapsed = beginning_park.elapsed();
                 if elapsed >= timeout {
+                    // don't leave a dangling pointer to the waiter in the queue.
+                    let node = unsafe { Pin::new_unchecked(&mut waiter) };
+                    if test_dbg!(node.is_linked()) {
+                        node.remove(&core.tx_wait);
+                    }
                     return Err(SendTimeoutError::Timeout(()));
                 }
             }
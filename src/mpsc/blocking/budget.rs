@@ -0,0 +1,479 @@
+//! A synchronous channel bounded by the total size of its messages, rather
+//! than only by their number.
+//!
+//! The channels in the [`blocking`](super) module hold a fixed number of
+//! messages. When message sizes vary by orders of magnitude, as they do in
+//! logging and networking pipelines, a bound on the number of messages does
+//! not bound the memory they hold: a channel with space for 1024 messages may
+//! hold 1024 bytes or 1024 megabytes. A *budget* channel also limits the
+//! total [payload size](Payload) of the messages waiting in it. Once the
+//! messages in the channel add up to its byte budget, senders wait until the
+//! [`Receiver`] has received enough of them to make room for their message.
+//!
+//! A message larger than the whole budget is still sent, once the channel is
+//! otherwise empty, so that it cannot block its sender forever. The channel
+//! is also bounded by its capacity in messages, as usual, so that it does not
+//! fill up with empty messages.
+//!
+//! An asynchronous version of this channel is provided by the
+//! [`mpsc::budget`] module.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::{blocking::budget, errors::TrySendError};
+//!
+//! // Up to 64 messages, holding at most 1KiB in total.
+//! let (tx, rx) = budget::channel::<Vec<u8>>(64, 1024);
+//!
+//! tx.send(vec![0; 1000]).unwrap();
+//! assert_eq!(tx.used_bytes(), 1000);
+//!
+//! // There is room for 63 more messages, but not for 100 more bytes.
+//! assert!(matches!(tx.try_send(vec![1; 100]), Err(TrySendError::Full(_))));
+//!
+//! assert_eq!(rx.recv().unwrap().len(), 1000);
+//! tx.try_send(vec![1; 100]).unwrap();
+//! assert_eq!(tx.used_bytes(), 100);
+//! ```
+//!
+//! [`mpsc::budget`]: crate::mpsc::budget
+use super::park::{self, Unparker};
+use crate::{
+    mpsc::{
+        budget::{Budget, Charged, ChargedRecycle},
+        errors::{Closed, RecvTimeoutError, TryRecvError, TrySendError},
+    },
+    recycling::{self, Recycle},
+    wait::{queue, WaitResult},
+};
+use alloc::sync::Arc;
+use core::{fmt, mem, ops, pin::Pin};
+use std::time::Duration;
+
+pub use crate::mpsc::budget::Payload;
+
+/// Sends messages to a [`Receiver`], waiting while the channel's byte budget
+/// is used up.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Sender<T, R = recycling::DefaultRecycle> {
+    tx: super::Sender<Charged<T>, ChargedRecycle<R>>,
+    budget: Arc<Budget<Unparker>>,
+}
+
+/// Receives messages from a budget channel, returning their size to the
+/// channel's byte budget.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Receiver<T, R = recycling::DefaultRecycle> {
+    rx: super::Receiver<Charged<T>, ChargedRecycle<R>>,
+    budget: Arc<Budget<Unparker>>,
+}
+
+/// A reference to a message being sent to a budget channel.
+///
+/// The message's payload size is charged to the channel's byte budget when
+/// the `SendRef` is dropped, and the message is sent.
+///
+/// This type is returned by the [`Sender::send_ref`] and
+/// [`Sender::try_send_ref`] methods.
+pub struct SendRef<'a, T: Payload> {
+    slot: super::SendRef<'a, Charged<T>>,
+    budget: &'a Budget<Unparker>,
+    reserved: usize,
+}
+
+/// Returns a new budget channel with space for `capacity` messages, whose
+/// sizes add up to at most `max_bytes`.
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+#[must_use]
+pub fn channel<T: Default + Clone>(capacity: usize, max_bytes: usize) -> (Sender<T>, Receiver<T>) {
+    with_recycle(capacity, max_bytes, recycling::DefaultRecycle::new())
+}
+
+/// Returns a new budget channel with space for `capacity` messages, whose
+/// sizes add up to at most `max_bytes`, and the provided [recycling policy].
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+///
+/// [recycling policy]: crate::recycling::Recycle
+#[must_use]
+pub fn with_recycle<T, R: Recycle<T>>(
+    capacity: usize,
+    max_bytes: usize,
+    recycle: R,
+) -> (Sender<T, R>, Receiver<T, R>) {
+    let (tx, rx) = super::with_recycle(capacity, ChargedRecycle(recycle));
+    let budget = Arc::new(Budget::new(max_bytes));
+    (
+        Sender {
+            tx,
+            budget: budget.clone(),
+        },
+        Receiver { rx, budget },
+    )
+}
+
+// === impl Sender ===
+
+impl<T, R> Sender<T, R>
+where
+    T: Payload,
+    R: Recycle<T>,
+{
+    /// Sends a message, waiting until there is room for its payload in the
+    /// byte budget, and a free slot in the channel.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] has been dropped, this returns a [`Closed`] error
+    /// containing the message.
+    pub fn send(&self, value: T) -> Result<(), Closed<T>> {
+        let size = value.payload_len();
+        if acquire(&self.budget, size).is_err() {
+            return Err(Closed(value));
+        }
+        self.tx
+            .send(Charged { value, size })
+            .map_err(|Closed(msg)| {
+                self.budget.release(size);
+                Closed(msg.value)
+            })
+    }
+
+    /// Attempts to send a message, without waiting for room in the byte
+    /// budget or for a free slot.
+    ///
+    /// # Errors
+    ///
+    /// If the message does not fit in the byte budget, the channel is full,
+    /// or the [`Receiver`] has been dropped, this returns a [`TrySendError`]
+    /// containing the message.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let size = value.payload_len();
+        if let Err(err) = self.budget.try_acquire(size) {
+            return Err(err.with_value(value));
+        }
+        self.tx.try_send(Charged { value, size }).map_err(|err| {
+            self.budget.release(size);
+            match err {
+                TrySendError::Full(msg) => TrySendError::Full(msg.value),
+                TrySendError::Closed(msg) => TrySendError::Closed(msg.value),
+                TrySendError::Shed(msg) => TrySendError::Shed(msg.value),
+            }
+        })
+    }
+
+    /// Reserves a slot in the channel to write a message of about `size`
+    /// bytes in place, waiting until there is room for `size` bytes in the
+    /// byte budget, and a free slot in the channel.
+    ///
+    /// This reuses the allocations of received messages, like
+    /// [`blocking::Sender::send_ref`](super::Sender::send_ref). As the
+    /// message is only written once the slot is reserved, `size` is charged
+    /// to the budget up front. When the returned [`SendRef`] is dropped, the
+    /// charge is corrected to the message's actual [payload
+    /// size](Payload::payload_len), so a message that turns out to be larger
+    /// than `size` may exceed the budget.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] has been dropped, this returns a [`Closed`] error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking::budget;
+    /// use std::io::Write;
+    ///
+    /// let (tx, rx) = budget::channel::<Vec<u8>>(8, 1024);
+    ///
+    /// let mut msg = tx.send_ref(5).unwrap();
+    /// write!(msg, "hello").unwrap();
+    /// drop(msg);
+    /// assert_eq!(tx.used_bytes(), 5);
+    ///
+    /// assert_eq!(rx.recv().as_deref(), Some(&b"hello"[..]));
+    /// assert_eq!(tx.used_bytes(), 0);
+    /// ```
+    pub fn send_ref(&self, size: usize) -> Result<SendRef<'_, T>, Closed> {
+        acquire(&self.budget, size)?;
+        match self.tx.send_ref() {
+            Ok(slot) => Ok(SendRef::new(slot, &self.budget, size)),
+            Err(closed) => {
+                self.budget.release(size);
+                Err(closed)
+            }
+        }
+    }
+
+    /// Attempts to reserve a slot in the channel to write a message of about
+    /// `size` bytes in place, without waiting for room in the byte budget or
+    /// for a free slot.
+    ///
+    /// See [`send_ref`](Self::send_ref) for how the message is charged to the
+    /// budget.
+    ///
+    /// # Errors
+    ///
+    /// If `size` bytes do not fit in the byte budget, the channel is full, or
+    /// the [`Receiver`] has been dropped, this returns a [`TrySendError`].
+    pub fn try_send_ref(&self, size: usize) -> Result<SendRef<'_, T>, TrySendError> {
+        self.budget.try_acquire(size)?;
+        match self.tx.try_send_ref() {
+            Ok(slot) => Ok(SendRef::new(slot, &self.budget, size)),
+            Err(err) => {
+                self.budget.release(size);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<T, R: Recycle<T>> Sender<T, R> {
+    /// Returns the channel's byte budget: the most that the payloads of the
+    /// messages waiting in the channel may add up to.
+    pub fn max_bytes(&self) -> usize {
+        self.budget.max
+    }
+
+    /// Returns the total payload size of the messages that have been sent,
+    /// but not yet received.
+    pub fn used_bytes(&self) -> usize {
+        self.budget.used()
+    }
+
+    /// Returns the total capacity of the channel, in messages.
+    pub fn capacity(&self) -> usize {
+        self.tx.capacity()
+    }
+}
+
+impl<T, R> Clone for Sender<T, R> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            budget: self.budget.clone(),
+        }
+    }
+}
+
+impl<T, R: Recycle<T>> fmt::Debug for Sender<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &self.capacity())
+            .field("max_bytes", &self.max_bytes())
+            .field("used_bytes", &self.used_bytes())
+            .finish()
+    }
+}
+
+// === impl Receiver ===
+
+impl<T, R> Receiver<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Receives the next message, waiting until one is available, and
+    /// returns its size to the byte budget.
+    ///
+    /// Returns `None` once the channel has closed and every message has been
+    /// received.
+    pub fn recv(&self) -> Option<T> {
+        let mut msg = self.rx.recv_ref()?;
+        Some(self.take(&mut msg))
+    }
+
+    /// Receives the next message, waiting for at most `timeout`, and returns
+    /// its size to the byte budget.
+    ///
+    /// # Errors
+    ///
+    /// - [`RecvTimeoutError::Timeout`] if no message was received within
+    ///   `timeout`.
+    /// - [`RecvTimeoutError::Closed`] if the channel has closed and every
+    ///   message has been received.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let mut msg = self.rx.recv_ref_timeout(timeout)?;
+        Ok(self.take(&mut msg))
+    }
+
+    /// Attempts to receive the next message without waiting, and returns its
+    /// size to the byte budget.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if there are no messages in the channel.
+    /// - [`TryRecvError::Closed`] if the channel has closed and every message
+    ///   has been received.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut msg = self.rx.try_recv_ref()?;
+        Ok(self.take(&mut msg))
+    }
+
+    fn take(&self, msg: &mut Charged<T>) -> T {
+        self.budget.release(mem::take(&mut msg.size));
+        recycling::take(&mut msg.value, &self.rx.inner.recycle.0)
+    }
+}
+
+impl<T, R> Receiver<T, R> {
+    /// Returns the channel's byte budget: the most that the payloads of the
+    /// messages waiting in the channel may add up to.
+    pub fn max_bytes(&self) -> usize {
+        self.budget.max
+    }
+
+    /// Returns the total payload size of the messages that have been sent,
+    /// but not yet received.
+    pub fn used_bytes(&self) -> usize {
+        self.budget.used()
+    }
+
+    /// Returns the total capacity of the channel, in messages.
+    pub fn capacity(&self) -> usize {
+        self.rx.capacity()
+    }
+
+    /// Returns the number of messages waiting in the channel.
+    pub fn len(&self) -> usize {
+        self.rx.len()
+    }
+
+    /// Returns `true` if there are no messages waiting in the channel.
+    pub fn is_empty(&self) -> bool {
+        self.rx.is_empty()
+    }
+
+    /// Returns `true` if the channel has closed (all corresponding
+    /// [`Sender`]s have been dropped).
+    pub fn is_closed(&self) -> bool {
+        self.rx.is_closed()
+    }
+}
+
+impl<T, R> Drop for Receiver<T, R> {
+    fn drop(&mut self) {
+        // Wake any senders waiting for room in the budget, which will never
+        // be made now.
+        self.budget.close();
+    }
+}
+
+impl<T, R: Recycle<T>> Iterator for &Receiver<T, R> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+impl<T, R> fmt::Debug for Receiver<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .field("max_bytes", &self.max_bytes())
+            .field("used_bytes", &self.used_bytes())
+            .field("is_closed", &self.is_closed())
+            .finish()
+    }
+}
+
+// === impl SendRef ===
+
+impl<'a, T: Payload> SendRef<'a, T> {
+    fn new(
+        slot: super::SendRef<'a, Charged<T>>,
+        budget: &'a Budget<Unparker>,
+        reserved: usize,
+    ) -> Self {
+        Self {
+            slot,
+            budget,
+            reserved,
+        }
+    }
+}
+
+impl<T: Payload> ops::Deref for SendRef<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.slot.value
+    }
+}
+
+impl<T: Payload> ops::DerefMut for SendRef<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.slot.value
+    }
+}
+
+impl<T: Payload> Drop for SendRef<'_, T> {
+    fn drop(&mut self) {
+        // Charge the message's actual size before it is sent, so that the
+        // receiver returns the same amount to the budget.
+        let size = self.slot.value.payload_len();
+        self.slot.size = size;
+        self.budget.settle(self.reserved, size);
+    }
+}
+
+impl<T: Payload + fmt::Debug> fmt::Debug for SendRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.slot.value.fmt(f)
+    }
+}
+
+/// Waits until there is room for `size` bytes in `budget`, and charges them
+/// to it.
+fn acquire(budget: &Budget<Unparker>, size: usize) -> Result<(), Closed> {
+    let mut waiter = queue::Waiter::new();
+    let mut queued = false;
+    let unparker = park::current();
+    let res = loop {
+        match budget.try_acquire(size) {
+            Ok(()) => break Ok(()),
+            Err(TrySendError::Closed(())) => break Err(Closed(())),
+            Err(TrySendError::Full(()) | TrySendError::Shed(())) => {}
+        }
+
+        let node = unsafe {
+            // Safety: in this case, it's totally safe to pin the waiter, as
+            // it is owned uniquely by this function, and it cannot possibly
+            // be moved while this thread is parked.
+            Pin::new_unchecked(&mut waiter)
+        };
+        let wait = if queued {
+            budget.released.continue_wait(node, &unparker)
+        } else {
+            budget.released.start_wait(node, &unparker)
+        };
+        match test_dbg!(wait) {
+            WaitResult::Wait => {
+                queued = true;
+                park::park();
+            }
+            // Bytes were returned to the budget; check whether they are
+            // enough.
+            WaitResult::Notified => queued = false,
+            WaitResult::Closed => break Err(Closed(())),
+        }
+    };
+
+    // don't leave a dangling pointer to the waiter in the queue.
+    let node = unsafe { Pin::new_unchecked(&mut waiter) };
+    if queued && node.is_linked() {
+        node.remove(&budget.released);
+    }
+    res
+}
@@ -0,0 +1,438 @@
+//! An experimental synchronous channel that grows its capacity under
+//! sustained backpressure.
+//!
+//! The channels in the [`blocking`](super) module are strictly bounded: once a
+//! channel is full, senders wait until the receiver makes room. An *elastic*
+//! channel instead trades strict boundedness for availability during bursts.
+//! If a [`Sender`] has been waiting for capacity for longer than a configured
+//! threshold, the channel grows by chaining an additional segment with the
+//! channel's base capacity, up to a configured maximum. Once the
+//! [`Receiver`] has drained a segment that was added in this way, the segment
+//! is freed, so the channel shrinks back to its base capacity when it is
+//! idle.
+//!
+//! Messages are received in the order in which they were sent, even across
+//! segments.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::blocking::elastic::{self, Config};
+//! use std::time::Duration;
+//!
+//! let config = Config::new(2)
+//!     .with_max_capacity(4)
+//!     .with_grow_after(Duration::from_millis(1));
+//! let (tx, rx) = elastic::channel(config);
+//!
+//! // Nothing is receiving, so the channel grows instead of blocking forever.
+//! for i in 0..4 {
+//!     tx.send(i).unwrap();
+//! }
+//! assert_eq!(tx.capacity(), 4);
+//!
+//! for i in 0..4 {
+//!     assert_eq!(rx.recv(), Some(i));
+//! }
+//! // Once the receiver has drained it, the extra segment is freed.
+//! assert_eq!(rx.capacity(), 2);
+//! ```
+use super::{recv_ref, send_ref_timeout, Inner};
+use crate::{
+    loom::{
+        atomic::{self, AtomicUsize, Ordering},
+        sync::Arc,
+    },
+    mpsc::{
+        errors::{Closed, SendTimeoutError},
+        ChannelCore,
+    },
+    recycling::{self, Recycle},
+    util::mutex::Mutex,
+    Slot, MAX_CAPACITY,
+};
+use alloc::collections::VecDeque;
+use core::fmt;
+use std::time::Duration;
+
+/// Configures an elastic channel.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::mpsc::blocking::elastic::Config;
+/// use std::time::Duration;
+///
+/// let config = Config::new(1024)
+///     .with_max_capacity(8 * 1024)
+///     .with_grow_after(Duration::from_millis(5));
+///
+/// assert_eq!(config.capacity(), 1024);
+/// assert_eq!(config.max_capacity(), 8 * 1024);
+/// assert_eq!(config.grow_after(), Duration::from_millis(5));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Config {
+    capacity: usize,
+    max_capacity: usize,
+    grow_after: Duration,
+}
+
+/// Synchronously sends values to an associated [`Receiver`], growing the
+/// channel if it has been full for too long.
+///
+/// Instances of this struct are created by the [`channel`] and
+/// [`with_recycle`] functions.
+pub struct Sender<T, R = recycling::DefaultRecycle> {
+    shared: Arc<Shared<T, R>>,
+}
+
+/// Synchronously receives values from associated [`Sender`]s.
+///
+/// Instances of this struct are created by the [`channel`] and
+/// [`with_recycle`] functions.
+pub struct Receiver<T, R = recycling::DefaultRecycle> {
+    shared: Arc<Shared<T, R>>,
+}
+
+struct Shared<T, R> {
+    /// The channel's segments, oldest first. The receiver receives from the
+    /// front segment, and senders send to the back segment. Every segment
+    /// but the back one has been closed.
+    segments: Mutex<VecDeque<Arc<Inner<T, R>>>>,
+    /// The total capacity of all segments.
+    capacity: AtomicUsize,
+    tx_count: AtomicUsize,
+    config: Config,
+    recycle: R,
+}
+
+/// Returns a new elastic channel with the provided configuration.
+///
+/// This channel will use the [default recycling policy].
+///
+/// # Panics
+///
+/// If the configured capacity is 0, or the configured maximum capacity exceeds
+/// `usize::MAX & !(1 << (usize::BITS - 1))`.
+///
+/// [default recycling policy]: crate::recycling::DefaultRecycle
+#[must_use]
+pub fn channel<T: Default + Clone>(config: Config) -> (Sender<T>, Receiver<T>) {
+    with_recycle(config, recycling::DefaultRecycle::new())
+}
+
+/// Returns a new elastic channel with the provided configuration and
+/// [recycling policy].
+///
+/// Each segment of the channel uses a clone of `recycle`.
+///
+/// # Panics
+///
+/// If the configured capacity is 0, or the configured maximum capacity exceeds
+/// `usize::MAX & !(1 << (usize::BITS - 1))`.
+///
+/// [recycling policy]: crate::recycling::Recycle
+#[must_use]
+pub fn with_recycle<T, R: Recycle<T> + Clone>(
+    config: Config,
+    recycle: R,
+) -> (Sender<T, R>, Receiver<T, R>) {
+    assert!(config.capacity > 0);
+    assert!(config.max_capacity <= MAX_CAPACITY);
+    let mut segments = VecDeque::new();
+    segments.push_back(Arc::new(new_segment(config.capacity, recycle.clone())));
+    let shared = Arc::new(Shared {
+        segments: Mutex::new(segments),
+        capacity: AtomicUsize::new(config.capacity),
+        tx_count: AtomicUsize::new(1),
+        config,
+        recycle,
+    });
+    let tx = Sender {
+        shared: shared.clone(),
+    };
+    let rx = Receiver { shared };
+    (tx, rx)
+}
+
+fn new_segment<T, R>(capacity: usize, recycle: R) -> Inner<T, R> {
+    Inner {
+        core: ChannelCore::new(capacity),
+        slots: Slot::make_boxed_array(capacity),
+        recycle,
+    }
+}
+
+// === impl Config ===
+
+impl Config {
+    /// Returns a new `Config` for a channel with the provided base
+    /// capacity.
+    ///
+    /// By default, the channel may grow to 4 times its base capacity, and
+    /// grows once a sender has waited for 10 milliseconds.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            max_capacity: capacity.saturating_mul(4).min(MAX_CAPACITY),
+            grow_after: Duration::from_millis(10),
+        }
+    }
+
+    /// Sets the maximum capacity that the channel may grow to.
+    ///
+    /// The channel grows in increments of its base capacity, so if this is
+    /// not a multiple of the base capacity, the channel never grows past the
+    /// largest multiple that is less than this value. If this is less than or
+    /// equal to the base capacity, the channel never grows.
+    #[must_use]
+    pub fn with_max_capacity(self, max_capacity: usize) -> Self {
+        Self {
+            max_capacity,
+            ..self
+        }
+    }
+
+    /// Sets how long a sender must wait for capacity before the channel
+    /// grows.
+    #[must_use]
+    pub fn with_grow_after(self, grow_after: Duration) -> Self {
+        Self { grow_after, ..self }
+    }
+
+    /// Returns the base capacity of the channel.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the maximum capacity that the channel may grow to.
+    #[must_use]
+    pub fn max_capacity(&self) -> usize {
+        self.max_capacity
+    }
+
+    /// Returns how long a sender must wait for capacity before the channel
+    /// grows.
+    #[must_use]
+    pub fn grow_after(&self) -> Duration {
+        self.grow_after
+    }
+}
+
+// === impl Sender ===
+
+impl<T, R> Sender<T, R>
+where
+    R: Recycle<T> + Clone,
+{
+    /// Sends a message by value, blocking the current thread until there is
+    /// capacity available, or the channel grows.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] end of the channel has been dropped, this
+    /// returns a [`Closed`] error containing the sent value.
+    pub fn send(&self, val: T) -> Result<(), Closed<T>> {
+        let grow_after = self.shared.config.grow_after;
+        loop {
+            let segment = self.shared.back();
+            let sent =
+                send_ref_timeout(&segment.core, &segment.slots, &segment.recycle, grow_after);
+            match sent {
+                Ok(mut slot) => {
+                    *slot = val;
+                    return Ok(());
+                }
+                Err(SendTimeoutError::Timeout(())) => self.shared.grow(&segment),
+                // If the segment was closed because the channel grew, try
+                // again with the new segment.
+                Err(SendTimeoutError::Closed(())) if !self.shared.is_back(&segment) => {}
+                Err(_) => return Err(Closed(val)),
+            }
+        }
+    }
+}
+
+impl<T, R> Sender<T, R> {
+    /// Returns the current *total* capacity of the channel, including any
+    /// segments added while it was under backpressure.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity.load(Ordering::Acquire)
+    }
+}
+
+impl<T, R> Clone for Sender<T, R> {
+    fn clone(&self) -> Self {
+        test_dbg!(self.shared.tx_count.fetch_add(1, Ordering::Relaxed));
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, R> Drop for Sender<T, R> {
+    fn drop(&mut self) {
+        if test_dbg!(self.shared.tx_count.fetch_sub(1, Ordering::Release)) > 1 {
+            return;
+        }
+
+        // if we are the last sender, synchronize
+        test_dbg!(atomic::fence(Ordering::SeqCst));
+        let segment = self.shared.back();
+        if segment.core.core.close() {
+            segment.core.rx_wait.close_tx();
+        }
+    }
+}
+
+impl<T, R> fmt::Debug for Sender<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &self.capacity())
+            .field("config", &self.shared.config)
+            .finish()
+    }
+}
+
+// === impl Receiver ===
+
+impl<T, R> Receiver<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Receives the next message for this receiver, **by value**.
+    ///
+    /// This method returns `None` if the channel has been closed and there are
+    /// no remaining messages in the channel's buffer. If there are no
+    /// messages in the buffer, but the channel has not yet been closed, this
+    /// method will block until a message is sent or the channel is closed.
+    pub fn recv(&self) -> Option<T> {
+        loop {
+            let segment = self.shared.front();
+            if let Some(mut slot) = recv_ref(&segment.core, &segment.slots) {
+                return Some(recycling::take(&mut *slot, &segment.recycle));
+            }
+
+            // The segment is closed and empty. If it was closed because the
+            // channel grew, free it and move on to the next segment.
+            if !self.shared.pop_front(&segment) {
+                return None;
+            }
+        }
+    }
+}
+
+impl<T, R> Receiver<T, R> {
+    /// Returns the current *total* capacity of the channel, including any
+    /// segments added while it was under backpressure.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity.load(Ordering::Acquire)
+    }
+}
+
+impl<T, R: Recycle<T>> Iterator for &Receiver<T, R> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+impl<T, R> Drop for Receiver<T, R> {
+    fn drop(&mut self) {
+        for segment in self.shared.segments.lock().iter() {
+            segment.core.close_rx();
+        }
+    }
+}
+
+impl<T, R> fmt::Debug for Receiver<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("capacity", &self.capacity())
+            .field("config", &self.shared.config)
+            .finish()
+    }
+}
+
+// === impl Shared ===
+
+impl<T, R> Shared<T, R> {
+    fn front(&self) -> Arc<Inner<T, R>> {
+        let segments = self.segments.lock();
+        segments.front().expect("channel has no segments").clone()
+    }
+
+    fn back(&self) -> Arc<Inner<T, R>> {
+        let segments = self.segments.lock();
+        segments.back().expect("channel has no segments").clone()
+    }
+
+    fn is_back(&self, segment: &Arc<Inner<T, R>>) -> bool {
+        let segments = self.segments.lock();
+        segments
+            .back()
+            .map_or(false, |back| Arc::ptr_eq(back, segment))
+    }
+
+    /// Frees `segment`, if it is the front segment and there is a newer one.
+    /// Returns `false` if `segment` is the only remaining segment.
+    fn pop_front(&self, segment: &Arc<Inner<T, R>>) -> bool {
+        let mut segments = self.segments.lock();
+        if segments.len() == 1 {
+            return false;
+        }
+
+        if segments
+            .front()
+            .map_or(false, |front| Arc::ptr_eq(front, segment))
+        {
+            segments.pop_front();
+            test_dbg!(self
+                .capacity
+                .fetch_sub(segment.core.core.capacity(), Ordering::Release));
+        }
+        true
+    }
+}
+
+impl<T, R: Clone> Shared<T, R> {
+    /// Grows the channel by adding a new segment after `full`, unless another
+    /// sender already did, or the channel is at its maximum capacity.
+    fn grow(&self, full: &Arc<Inner<T, R>>) {
+        let mut segments = self.segments.lock();
+        let is_back = segments
+            .back()
+            .map_or(false, |back| Arc::ptr_eq(back, full));
+        if !is_back || full.core.core.is_closed() {
+            return;
+        }
+
+        let capacity = self.config.capacity;
+        let total = self.capacity.load(Ordering::Acquire);
+        if total.saturating_add(capacity) > self.config.max_capacity {
+            return;
+        }
+
+        test_println!(
+            "elastic channel: growing from {} to {}",
+            total,
+            total + capacity
+        );
+        segments.push_back(Arc::new(new_segment(capacity, self.recycle.clone())));
+        test_dbg!(self.capacity.fetch_add(capacity, Ordering::Release));
+        drop(segments);
+
+        // Close the full segment, so that senders waiting on it move to the
+        // new one, and the receiver moves on once it has drained it.
+        if full.core.core.close() {
+            full.core.tx_wait.close();
+            full.core.rx_wait.close_tx();
+        }
+    }
+}
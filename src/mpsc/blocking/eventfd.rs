@@ -0,0 +1,235 @@
+//! Receiving from a blocking channel in an `epoll`-based event loop.
+//!
+//! A [`Receiver`] from this module signals that messages may be available
+//! through a Linux [`eventfd`], rather than by unparking a thread. The file
+//! descriptor becomes readable when a message is sent or the channel closes,
+//! so it can be registered with `epoll`, `poll`, or [`mio`] (through
+//! `mio::unix::SourceFd`), alongside sockets and other file descriptors. It
+//! may also be handed to code that is not written in Rust, and polled there.
+//!
+//! When the file descriptor is readable, [`try_recv`](Receiver::try_recv) or
+//! [`try_recv_ref`](Receiver::try_recv_ref) should be called until it returns
+//! [`TryRecvError::Empty`]. Each call that finds the channel empty clears the
+//! file descriptor's readiness, and arms it to become readable again when the
+//! next message is sent, so it is level-triggered: a message is never left in
+//! the channel while the file descriptor is not readable.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::{blocking::{self, eventfd}, errors::TryRecvError};
+//! use std::os::unix::io::AsRawFd;
+//!
+//! fn readable(fd: i32) -> bool {
+//!     let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+//!     unsafe { libc::poll(&mut pollfd, 1, 0) == 1 }
+//! }
+//!
+//! let (tx, rx) = blocking::channel::<u32>(8);
+//! let rx = eventfd::Receiver::new(rx).unwrap();
+//! let fd = rx.as_raw_fd();
+//!
+//! tx.send(1).unwrap();
+//! tx.send(2).unwrap();
+//! assert!(readable(fd));
+//!
+//! assert_eq!(rx.try_recv(), Ok(1));
+//! assert_eq!(rx.try_recv(), Ok(2));
+//! assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+//! assert!(!readable(fd));
+//!
+//! drop(tx);
+//! assert!(readable(fd));
+//! assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+//! ```
+//!
+//! [`eventfd`]: https://man7.org/linux/man-pages/man2/eventfd.2.html
+//! [`mio`]: https://crates.io/crates/mio
+use super::{
+    park::{Unpark, Unparker},
+    RecvRef,
+};
+use crate::{
+    mpsc::{errors::TryRecvError, NotifyTx, RecvRefInner},
+    recycling::{self, Recycle},
+    util::ref_watch::RefWatch,
+};
+use alloc::sync::Arc;
+use core::{fmt, task::Poll};
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+};
+
+/// A [`blocking::Receiver`](super::Receiver) that signals readiness through
+/// an `eventfd`.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Receiver<T, R = recycling::DefaultRecycle> {
+    rx: super::Receiver<T, R>,
+    signal: Arc<EventFd>,
+}
+
+/// Makes the `eventfd` readable when the channel wakes its receiver.
+struct EventFd(RawFd);
+
+// === impl Receiver ===
+
+impl<T, R> Receiver<T, R> {
+    /// Returns a new `Receiver` that receives messages from `rx`.
+    ///
+    /// The file descriptor starts out readable, so that messages that were
+    /// sent before the `Receiver` was created are received.
+    ///
+    /// # Errors
+    ///
+    /// If the `eventfd` could not be created, such as because the process
+    /// has too many open file descriptors.
+    pub fn new(rx: super::Receiver<T, R>) -> io::Result<Self> {
+        let signal = Arc::new(EventFd::new()?);
+        signal.unpark();
+        Ok(Self { rx, signal })
+    }
+
+    /// Attempts to receive the next message by reference, without blocking.
+    ///
+    /// If the channel is empty, the file descriptor is made unreadable, and
+    /// armed, so that it becomes readable when the next message is sent or
+    /// the channel closes. Otherwise, the file descriptor is left readable,
+    /// as more messages may be available.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if there are no messages in
+    ///   the channel.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if the channel has closed and
+    ///   every message has been received.
+    pub fn try_recv_ref(&self) -> Result<RecvRef<'_, T>, TryRecvError> {
+        let inner = &self.rx.inner;
+        // Clear the file descriptor before polling, rather than after, so
+        // that a message sent after the poll registers its waiter makes it
+        // readable again.
+        self.signal.clear();
+        let waiter = || Unparker::Custom(self.signal.clone());
+        match inner.core.poll_recv_ref(inner.slots.as_ref(), waiter) {
+            Poll::Ready(Some(slot)) => {
+                self.signal.unpark();
+                Ok(RecvRef(RecvRefInner {
+                    _notify: NotifyTx(&inner.core),
+                    _audit: inner.core.ref_audit.recv(),
+                    _watch: RefWatch::recv(),
+                    slot,
+                }))
+            }
+            Poll::Ready(None) => {
+                // Keep the file descriptor readable, so that an event loop
+                // that is still polling it observes that the channel has
+                // closed.
+                self.signal.unpark();
+                Err(TryRecvError::Closed)
+            }
+            Poll::Pending => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Attempts to receive the next message by value, without blocking.
+    ///
+    /// See [`try_recv_ref`](Self::try_recv_ref) for details.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if there are no messages in
+    ///   the channel.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if the channel has closed and
+    ///   every message has been received.
+    pub fn try_recv(&self) -> Result<T, TryRecvError>
+    where
+        R: Recycle<T>,
+    {
+        let mut slot = self.try_recv_ref()?;
+        Ok(recycling::take(&mut *slot, &self.rx.inner.recycle))
+    }
+
+    /// Returns a reference to the underlying
+    /// [`blocking::Receiver`](super::Receiver).
+    pub fn get_ref(&self) -> &super::Receiver<T, R> {
+        &self.rx
+    }
+
+    /// Consumes this `Receiver`, returning the underlying
+    /// [`blocking::Receiver`](super::Receiver).
+    ///
+    /// The `eventfd` is closed once the channel no longer references it.
+    pub fn into_inner(self) -> super::Receiver<T, R> {
+        self.rx
+    }
+}
+
+impl<T, R> AsRawFd for Receiver<T, R> {
+    /// Returns the `eventfd` that becomes readable when messages may be
+    /// available.
+    ///
+    /// The file descriptor is owned by the `Receiver`, and must not be
+    /// closed or read from by the caller.
+    fn as_raw_fd(&self) -> RawFd {
+        self.signal.0
+    }
+}
+
+impl<T: fmt::Debug, R: fmt::Debug> fmt::Debug for Receiver<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("rx", &self.rx)
+            .field("fd", &self.signal.0)
+            .finish()
+    }
+}
+
+// === impl EventFd ===
+
+impl EventFd {
+    fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(fd))
+    }
+
+    /// Resets the counter to 0, making the file descriptor unreadable.
+    fn clear(&self) {
+        let mut count = 0u64;
+        // If the counter is already 0, the read fails with `EAGAIN`, which
+        // is fine.
+        unsafe {
+            libc::read(
+                self.0,
+                &mut count as *mut u64 as *mut libc::c_void,
+                core::mem::size_of::<u64>(),
+            );
+        }
+    }
+}
+
+impl Unpark for EventFd {
+    fn unpark(&self) {
+        let one = 1u64;
+        // The write only fails if the counter would overflow, in which case
+        // the file descriptor is already readable.
+        unsafe {
+            libc::write(
+                self.0,
+                &one as *const u64 as *const libc::c_void,
+                core::mem::size_of::<u64>(),
+            );
+        }
+    }
+}
+
+impl Drop for EventFd {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.0);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Sender-side interceptors, which inspect each message before it is sent.
+//!
+//! An [`Interceptor`] is called with a mutable reference to every message
+//! sent through an intercepting [`Sender`], after the message has been
+//! written to its slot in the channel but before it is published to the
+//! receiver. The interceptor may observe the message, modify it in place, or
+//! veto it. A vetoed message is never received: its slot is given up and
+//! skipped by the receiver, and the message is left in the slot to be
+//! [recycled].
+//!
+//! This allows cross-cutting concerns, such as redacting sensitive fields,
+//! validating messages, or counting traffic, to be applied to every message
+//! a sender sends, without changing each place that sends a message.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::blocking::{self, intercept::{self, Verdict}};
+//!
+//! let (tx, rx) = blocking::channel::<String>(8);
+//!
+//! // Redact passwords, and refuse to send empty messages.
+//! let tx = intercept::Sender::new(tx, |msg: &mut String| {
+//!     if msg.is_empty() {
+//!         return Verdict::Veto;
+//!     }
+//!     if msg.starts_with("password=") {
+//!         msg.replace_range("password=".len().., "***");
+//!     }
+//!     Verdict::Publish
+//! });
+//!
+//! assert_eq!(tx.send(String::new()).unwrap(), Verdict::Veto);
+//! assert_eq!(tx.send("password=hunter2".to_string()).unwrap(), Verdict::Publish);
+//! drop(tx);
+//!
+//! assert_eq!(rx.recv().as_deref(), Some("password=***"));
+//! assert_eq!(rx.recv(), None);
+//! ```
+//!
+//! [recycled]: crate::recycling
+use super::SendRef;
+use crate::{
+    loom::atomic::{AtomicUsize, Ordering::Relaxed},
+    mpsc::{
+        errors::{Closed, TrySendError},
+        SendRefInner,
+    },
+    recycling::{self, Recycle},
+};
+use core::{fmt, mem};
+
+/// Inspects each message sent by an intercepting [`Sender`] before it is
+/// published.
+///
+/// This trait is implemented for any `Fn(&mut T) -> Verdict` closure.
+pub trait Interceptor<T> {
+    /// Inspects `msg`, which may be modified in place, and decides whether it
+    /// should be published.
+    fn intercept(&self, msg: &mut T) -> Verdict;
+}
+
+/// Whether an [`Interceptor`] allows a message to be sent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// The message is published to the receiver.
+    Publish,
+    /// The message is discarded, and its slot is returned to the channel.
+    Veto,
+}
+
+/// A [`blocking::Sender`](super::Sender) that passes every message to an
+/// [`Interceptor`] before publishing it.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Sender<T, I, R = recycling::DefaultRecycle> {
+    tx: super::Sender<T, R>,
+    interceptor: I,
+    vetoed: AtomicUsize,
+}
+
+impl<T, I, R> Sender<T, I, R>
+where
+    I: Interceptor<T>,
+    R: Recycle<T>,
+{
+    /// Returns a new `Sender` that sends messages on `tx`, after passing them
+    /// to `interceptor`.
+    #[must_use]
+    pub fn new(tx: super::Sender<T, R>, interceptor: I) -> Self {
+        Self {
+            tx,
+            interceptor,
+            vetoed: AtomicUsize::new(0),
+        }
+    }
+
+    /// Sends a message, blocking until there is capacity for it, if the
+    /// interceptor allows it to be sent.
+    ///
+    /// # Returns
+    ///
+    /// - [`Ok`]`(`[`Verdict`]`)` with the interceptor's verdict on the
+    ///   message, if it was written to the channel.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] end of the channel has been dropped, this returns
+    /// a [`Closed`] error containing the message.
+    ///
+    /// [`Receiver`]: super::Receiver
+    pub fn send(&self, val: T) -> Result<Verdict, Closed<T>> {
+        match self.tx.send_ref() {
+            Err(Closed(())) => Err(Closed(val)),
+            Ok(mut slot) => {
+                *slot = val;
+                Ok(self.intercept(slot))
+            }
+        }
+    }
+
+    /// Attempts to send a message without blocking, if the interceptor allows
+    /// it to be sent.
+    ///
+    /// # Returns
+    ///
+    /// - [`Ok`]`(`[`Verdict`]`)` with the interceptor's verdict on the
+    ///   message, if it was written to the channel.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TrySendError::Full`]`)` if the channel is at capacity.
+    /// - [`Err`]`(`[`TrySendError::Closed`]`)` if the [`Receiver`] end of the
+    ///   channel has been dropped.
+    /// - [`Err`]`(`[`TrySendError::Shed`]`)` if the message was dropped by
+    ///   the channel's load shedding.
+    ///
+    /// [`Receiver`]: super::Receiver
+    pub fn try_send(&self, val: T) -> Result<Verdict, TrySendError<T>> {
+        if self.tx.inner.core.shed() {
+            return Err(TrySendError::Shed(val));
+        }
+        match self.tx.try_send_ref() {
+            Err(e) => Err(e.with_value(val)),
+            Ok(mut slot) => {
+                *slot = val;
+                Ok(self.intercept(slot))
+            }
+        }
+    }
+
+    /// Reserves a slot, blocking until there is capacity, and calls `f` to
+    /// write the message in place, before passing it to the interceptor.
+    ///
+    /// This is the intercepting equivalent of [`blocking::Sender::send_ref`],
+    /// for messages whose allocations are reused.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] end of the channel has been dropped, this returns
+    /// a [`Closed`] error, and `f` is not called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fmt::Write;
+    /// use thingbuf::mpsc::blocking::{self, intercept::{self, Verdict}};
+    ///
+    /// let (tx, rx) = blocking::channel::<String>(8);
+    /// let tx = intercept::Sender::new(tx, |msg: &mut String| {
+    ///     msg.make_ascii_uppercase();
+    ///     Verdict::Publish
+    /// });
+    ///
+    /// tx.send_with(|msg| write!(msg, "hello {}", 1).unwrap()).unwrap();
+    /// assert_eq!(rx.recv().as_deref(), Some("HELLO 1"));
+    /// ```
+    ///
+    /// [`blocking::Sender::send_ref`]: super::Sender::send_ref
+    /// [`Receiver`]: super::Receiver
+    pub fn send_with(&self, f: impl FnOnce(&mut T)) -> Result<Verdict, Closed> {
+        let mut slot = self.tx.send_ref()?;
+        f(&mut slot);
+        Ok(self.intercept(slot))
+    }
+
+    /// Passes the message in `slot` to the interceptor, publishing it by
+    /// dropping `slot`, or giving up the slot if the message is vetoed.
+    fn intercept(&self, mut slot: SendRef<'_, T>) -> Verdict {
+        let verdict = self.interceptor.intercept(&mut slot);
+        if verdict == Verdict::Veto {
+            self.vetoed.fetch_add(1, Relaxed);
+            let SendRef(SendRefInner { slot, _notify, .. }) = slot;
+            self.tx.inner.core.core.abandon_ref(slot);
+            // Nothing was published, so there is no need to wake the
+            // receiver.
+            mem::forget(_notify);
+        }
+        verdict
+    }
+}
+
+impl<T, I, R> Sender<T, I, R> {
+    /// Returns the number of messages that the interceptor has vetoed.
+    pub fn vetoed_count(&self) -> usize {
+        self.vetoed.load(Relaxed)
+    }
+
+    /// Returns a reference to the underlying [`blocking::Sender`], which
+    /// sends messages without intercepting them.
+    ///
+    /// [`blocking::Sender`]: super::Sender
+    pub fn get_ref(&self) -> &super::Sender<T, R> {
+        &self.tx
+    }
+
+    /// Returns a reference to the interceptor.
+    pub fn interceptor(&self) -> &I {
+        &self.interceptor
+    }
+
+    /// Consumes this `Sender`, returning the underlying
+    /// [`blocking::Sender`](super::Sender) and the interceptor.
+    pub fn into_inner(self) -> (super::Sender<T, R>, I) {
+        (self.tx, self.interceptor)
+    }
+}
+
+impl<T, I: Clone, R> Clone for Sender<T, I, R> {
+    /// Returns a new `Sender` for the same channel, with a clone of the
+    /// interceptor, whose vetoed count starts at 0.
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            interceptor: self.interceptor.clone(),
+            vetoed: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<T: fmt::Debug, I, R: fmt::Debug> fmt::Debug for Sender<T, I, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("tx", &self.tx)
+            .field("vetoed", &self.vetoed_count())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Interceptor<T> for F
+where
+    F: Fn(&mut T) -> Verdict,
+{
+    #[inline]
+    fn intercept(&self, msg: &mut T) -> Verdict {
+        self(msg)
+    }
+}
@@ -0,0 +1,328 @@
+//! Pluggable thread parking for the blocking channels.
+//!
+//! By default, a thread that blocks on a [blocking channel] waits using
+//! [`std::thread::park`], and is woken with [`Thread::unpark`]. Some
+//! environments need a thread to block some other way: a thread created by
+//! foreign code may need to wait on a primitive its runtime knows about, and
+//! a real-time thread may need to wait on a futex with priority inheritance,
+//! so that a lower-priority thread that will wake it is not preempted.
+//!
+//! A thread can choose how it blocks by installing a [`Park`] implementation
+//! with [`set_thread_parker`]. Every blocking channel operation on that thread
+//! then waits with [`Park::park`], and the channel's other threads wake it
+//! through its [`Unpark`] handle. Parkers are installed per thread, so threads
+//! that park in different ways can share a channel.
+//!
+//! The following parkers are provided:
+//!
+//! - [`CondvarParker`], which waits on a [`Mutex`] and [`Condvar`].
+//! - [`parking::Parker`], from the [`parking`] crate, when the "parking"
+//!   feature flag is enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::blocking::{self, park};
+//! use std::thread;
+//!
+//! let (tx, rx) = blocking::channel(8);
+//!
+//! let consumer = thread::spawn(move || {
+//!     // This thread waits for messages on a condition variable.
+//!     park::set_thread_parker(park::CondvarParker::new());
+//!     rx.recv()
+//! });
+//!
+//! tx.send(1).unwrap();
+//! assert_eq!(consumer.join().unwrap(), Some(1));
+//! ```
+//!
+//! [blocking channel]: super
+//! [`Thread::unpark`]: std::thread::Thread::unpark
+//! [`Mutex`]: std::sync::Mutex
+//! [`Condvar`]: std::sync::Condvar
+//! [`parking::Parker`]: https://docs.rs/parking/latest/parking/struct.Parker.html
+//! [`parking`]: https://crates.io/crates/parking
+use crate::{
+    loom::thread::{self, Thread},
+    wait::Notify,
+};
+use alloc::sync::Arc;
+use core::{fmt, time::Duration};
+use std::sync::{Condvar, Mutex};
+
+/// Blocks the thread that owns it until it is woken by an [`Unpark`] handle.
+///
+/// Like [`std::thread::park`], a `Park` implementation holds a single
+/// *token*: [`Unpark::unpark`] makes the token available, and
+/// [`park`](Self::park) consumes it, blocking until it is available.
+/// Implementations may also return spuriously, without consuming a token.
+pub trait Park {
+    /// The handle used by other threads to wake this parker.
+    type Unpark: Unpark;
+
+    /// Blocks the current thread until the token is available, and
+    /// consumes it.
+    fn park(&self);
+
+    /// Blocks the current thread until the token is available, or until
+    /// `timeout` has elapsed.
+    fn park_timeout(&self, timeout: Duration);
+
+    /// Returns a handle that wakes this parker.
+    fn unparker(&self) -> Self::Unpark;
+}
+
+/// Wakes a thread that is blocked in [`Park::park`].
+pub trait Unpark: Send + Sync + 'static {
+    /// Makes the parker's token available, waking its thread if it is
+    /// parked.
+    fn unpark(&self);
+}
+
+/// Installs `parker` as the way the current thread blocks on blocking
+/// channels, replacing any parker that was installed before.
+///
+/// This should not be called while the current thread is registered to be
+/// woken by a channel, such as from a [`Recycle`] implementation that is
+/// called while sending.
+///
+/// See the [module-level documentation](self) for details.
+///
+/// [`Recycle`]: crate::recycling::Recycle
+#[cfg(not(all(loom, test)))]
+pub fn set_thread_parker<P: Park + 'static>(parker: P) {
+    let unparker = Arc::new(parker.unparker());
+    THREAD_PARKER.with(|current| {
+        *current.borrow_mut() = Some(ThreadParker {
+            park: alloc::boxed::Box::new(parker),
+            unpark: unparker,
+        })
+    });
+}
+
+/// Removes the current thread's parker, if it has one, so that it blocks
+/// with [`std::thread::park`] again.
+#[cfg(not(all(loom, test)))]
+pub fn reset_thread_parker() {
+    THREAD_PARKER.with(|current| current.borrow_mut().take());
+}
+
+/// A [`Park`] implementation that waits on a [`Mutex`] and a [`Condvar`].
+///
+/// [`Mutex`]: std::sync::Mutex
+/// [`Condvar`]: std::sync::Condvar
+#[derive(Debug, Default)]
+pub struct CondvarParker(Arc<CondvarState>);
+
+/// The [`Unpark`] handle for a [`CondvarParker`].
+#[derive(Clone, Debug)]
+pub struct CondvarUnparker(Arc<CondvarState>);
+
+#[derive(Debug, Default)]
+struct CondvarState {
+    token: Mutex<bool>,
+    condvar: Condvar,
+}
+
+/// The parker installed on a thread by [`set_thread_parker`].
+#[cfg(not(all(loom, test)))]
+struct ThreadParker {
+    park: alloc::boxed::Box<dyn ErasedPark>,
+    unpark: Arc<dyn Unpark>,
+}
+
+/// An object-safe version of [`Park`], without the `Unpark` type.
+#[cfg(not(all(loom, test)))]
+trait ErasedPark {
+    fn park(&self);
+    fn park_timeout(&self, timeout: Duration);
+}
+
+/// Wakes a thread that is waiting on a blocking channel.
+#[derive(Clone)]
+#[cfg_attr(all(loom, test), allow(dead_code))]
+pub(crate) enum Unparker {
+    Thread(Thread),
+    Custom(Arc<dyn Unpark>),
+}
+
+#[cfg(not(all(loom, test)))]
+std::thread_local! {
+    static THREAD_PARKER: core::cell::RefCell<Option<ThreadParker>> =
+        core::cell::RefCell::new(None);
+}
+
+/// Returns the handle that wakes the current thread.
+#[cfg(not(all(loom, test)))]
+pub(crate) fn current() -> Unparker {
+    THREAD_PARKER.with(|current| match *current.borrow() {
+        Some(ref parker) => Unparker::Custom(parker.unpark.clone()),
+        None => Unparker::Thread(thread::current()),
+    })
+}
+
+/// Blocks the current thread with its parker.
+#[cfg(not(all(loom, test)))]
+pub(crate) fn park() {
+    THREAD_PARKER.with(|current| match *current.borrow() {
+        Some(ref parker) => parker.park.park(),
+        None => thread::park(),
+    })
+}
+
+/// Blocks the current thread with its parker, for at most `timeout`.
+#[cfg(not(all(loom, test)))]
+pub(crate) fn park_timeout(timeout: Duration) {
+    THREAD_PARKER.with(|current| match *current.borrow() {
+        Some(ref parker) => parker.park.park_timeout(timeout),
+        None => thread::park_timeout(timeout),
+    })
+}
+
+// loom does not support `std` thread-locals, so loom tests always use loom's
+// thread parking.
+
+#[cfg(all(loom, test))]
+pub(crate) fn current() -> Unparker {
+    Unparker::Thread(thread::current())
+}
+
+#[cfg(all(loom, test))]
+pub(crate) fn park() {
+    thread::park()
+}
+
+// === impl ErasedPark ===
+
+#[cfg(not(all(loom, test)))]
+impl<P: Park> ErasedPark for P {
+    #[inline]
+    fn park(&self) {
+        Park::park(self)
+    }
+
+    #[inline]
+    fn park_timeout(&self, timeout: Duration) {
+        Park::park_timeout(self, timeout)
+    }
+}
+
+// === impl Unparker ===
+
+impl Notify for Unparker {
+    const IS_THREAD: bool = true;
+
+    #[inline]
+    fn notify(self) {
+        match self {
+            Unparker::Thread(thread) => thread.notify(),
+            Unparker::Custom(unpark) => {
+                test_println!("NOTIFYING custom parker (from {:?})", thread::current());
+                unpark.unpark()
+            }
+        }
+    }
+
+    #[inline]
+    fn same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Unparker::Thread(a), Unparker::Thread(b)) => a.same(b),
+            (Unparker::Custom(a), Unparker::Custom(b)) => {
+                // Compare only the data pointers, as the same type may have
+                // several vtables.
+                Arc::as_ptr(a) as *const () == Arc::as_ptr(b) as *const ()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for Unparker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unparker::Thread(thread) => f.debug_tuple("Thread").field(thread).finish(),
+            Unparker::Custom(unpark) => f
+                .debug_tuple("Custom")
+                .field(&(Arc::as_ptr(unpark) as *const ()))
+                .finish(),
+        }
+    }
+}
+
+// === impl CondvarParker ===
+
+impl CondvarParker {
+    /// Returns a new `CondvarParker`, whose token is not available.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Park for CondvarParker {
+    type Unpark = CondvarUnparker;
+
+    fn park(&self) {
+        let mut token = self.0.token.lock().unwrap_or_else(|e| e.into_inner());
+        while !*token {
+            token = self
+                .0
+                .condvar
+                .wait(token)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+        *token = false;
+    }
+
+    fn park_timeout(&self, timeout: Duration) {
+        let token = self.0.token.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut token, _) = self
+            .0
+            .condvar
+            .wait_timeout_while(token, timeout, |token| !*token)
+            .unwrap_or_else(|e| e.into_inner());
+        *token = false;
+    }
+
+    fn unparker(&self) -> CondvarUnparker {
+        CondvarUnparker(self.0.clone())
+    }
+}
+
+impl Unpark for CondvarUnparker {
+    fn unpark(&self) {
+        *self.0.token.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        self.0.condvar.notify_one();
+    }
+}
+
+feature! {
+    #![feature = "parking"]
+
+    impl Park for parking::Parker {
+        type Unpark = parking::Unparker;
+
+        #[inline]
+        fn park(&self) {
+            parking::Parker::park(self)
+        }
+
+        #[inline]
+        fn park_timeout(&self, timeout: Duration) {
+            parking::Parker::park_timeout(self, timeout);
+        }
+
+        #[inline]
+        fn unparker(&self) -> parking::Unparker {
+            parking::Parker::unparker(self)
+        }
+    }
+
+    impl Unpark for parking::Unparker {
+        #[inline]
+        fn unpark(&self) {
+            parking::Unparker::unpark(self);
+        }
+    }
+}
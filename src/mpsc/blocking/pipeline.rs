@@ -0,0 +1,199 @@
+//! Multi-stage processing pipelines built from blocking channels.
+//!
+//! A pipeline is a chain of *stages*, each of which runs on its own thread,
+//! receives messages from a channel, transforms them, and sends the results
+//! to the next stage's channel. The [`pipeline`] function returns a
+//! [`Builder`], which adds stages with [`Builder::stage`] and wires up the
+//! channels and threads with [`Builder::output`].
+//!
+//! Shutdown propagates through the pipeline in both directions: when every
+//! [`Sender`] for the pipeline's input is dropped, each stage finishes the
+//! messages it has already received and then exits, closing the channel to
+//! the next stage. When the pipeline's output [`Receiver`] is dropped, the
+//! last stage exits the next time it tries to send a message, which closes
+//! the channel from the previous stage, and so on.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::blocking;
+//!
+//! let (tx, rx, stages) = blocking::pipeline()
+//!     .stage(16, |line: String| line.len())
+//!     .stage(16, |len| len * 2)
+//!     .output(16);
+//!
+//! for line in ["hello", "world!"] {
+//!     tx.send(line.to_string()).unwrap();
+//! }
+//! drop(tx);
+//!
+//! assert_eq!(rx.recv(), Some(10));
+//! assert_eq!(rx.recv(), Some(12));
+//! // The input was dropped, so every stage shuts down.
+//! assert_eq!(rx.recv(), None);
+//! stages.join().unwrap();
+//! ```
+use super::{channel, Receiver, Sender};
+use alloc::{boxed::Box, vec::Vec};
+use core::{fmt, marker::PhantomData};
+use std::thread::{self, JoinHandle};
+
+/// Builds a pipeline whose input messages have type `I`, and whose last stage
+/// so far produces messages of type `O`.
+///
+/// This type is returned by the [`pipeline`] function.
+pub struct Builder<I, O> {
+    connect: Connect<I, O>,
+    stages: usize,
+    _p: PhantomData<fn(I) -> O>,
+}
+
+/// Given the sender for a builder's output, spawns every stage added so far
+/// and returns the sender for the pipeline's input.
+type Connect<I, O> = Box<dyn FnOnce(Sender<O>, &mut Vec<JoinHandle<()>>) -> Sender<I> + Send>;
+
+/// The threads running the stages of a pipeline.
+///
+/// This type is returned by [`Builder::output`].
+#[must_use = "dropping `Stages` detaches the pipeline's threads"]
+pub struct Stages {
+    handles: Vec<JoinHandle<()>>,
+}
+
+/// Returns a [`Builder`] for a new pipeline that receives messages of type
+/// `T`.
+///
+/// See the [module-level documentation](self) for details.
+pub fn pipeline<T>() -> Builder<T, T>
+where
+    T: Default + Clone + Send + Sync + 'static,
+{
+    Builder {
+        connect: Box::new(|tx, _| tx),
+        stages: 0,
+        _p: PhantomData,
+    }
+}
+
+// === impl Builder ===
+
+impl<I, O> Builder<I, O>
+where
+    I: 'static,
+    O: Default + Clone + Send + Sync + 'static,
+{
+    /// Adds a stage that receives the output of the previous stage (or the
+    /// pipeline's input, if this is the first stage) from a channel with
+    /// space for `capacity` messages, and transforms each message with `f`.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0.
+    #[must_use]
+    pub fn stage<U, F>(self, capacity: usize, mut f: F) -> Builder<I, U>
+    where
+        U: Default + Clone + Send + Sync + 'static,
+        F: FnMut(O) -> U + Send + 'static,
+    {
+        assert!(capacity > 0, "a stage's channel must have capacity");
+        let prev = self.connect;
+        let stage = self.stages;
+        Builder {
+            connect: Box::new(move |tx: Sender<U>, handles| {
+                let (stage_tx, stage_rx) = channel::<O>(capacity);
+                let handle = thread::Builder::new()
+                    .name(format!("pipeline-stage-{}", stage))
+                    .spawn(move || run_stage(stage_rx, tx, &mut f))
+                    .expect("failed to spawn pipeline stage thread");
+                handles.push(handle);
+                prev(stage_tx, handles)
+            }),
+            stages: stage + 1,
+            _p: PhantomData,
+        }
+    }
+
+    /// Spawns the pipeline's stages, returning the [`Sender`] for its input,
+    /// a [`Receiver`] for the output of its last stage, which has space for
+    /// `capacity` messages, and the [`Stages`] running the pipeline.
+    ///
+    /// # Panics
+    ///
+    /// - If `capacity` is 0.
+    /// - If a stage's thread could not be spawned.
+    pub fn output(self, capacity: usize) -> (Sender<I>, Receiver<O>, Stages) {
+        let (tx, rx) = channel::<O>(capacity);
+        let mut handles = Vec::with_capacity(self.stages);
+        let input = (self.connect)(tx, &mut handles);
+        // stages were spawned from the last to the first.
+        handles.reverse();
+        (input, rx, Stages { handles })
+    }
+
+    /// Returns the number of stages in the pipeline.
+    pub fn stages(&self) -> usize {
+        self.stages
+    }
+}
+
+fn run_stage<T, U>(rx: Receiver<T>, tx: Sender<U>, f: &mut impl FnMut(T) -> U)
+where
+    T: Default + Clone,
+    U: Default + Clone,
+{
+    while let Some(msg) = rx.recv() {
+        if tx.send(f(msg)).is_err() {
+            // The next stage has shut down, so stop receiving. Dropping the
+            // receiver shuts down the previous stage in turn.
+            return;
+        }
+    }
+}
+
+impl<I, O> fmt::Debug for Builder<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("stages", &self.stages)
+            .finish()
+    }
+}
+
+// === impl Stages ===
+
+impl Stages {
+    /// Waits for every stage of the pipeline to shut down.
+    ///
+    /// Returns an error containing the panic payload if any stage panicked.
+    /// A stage that panics drops its channels, so the rest of the pipeline
+    /// shuts down as if it had exited normally.
+    pub fn join(self) -> thread::Result<()> {
+        let mut result = Ok(());
+        for handle in self.handles {
+            if let Err(panic) = handle.join() {
+                if result.is_ok() {
+                    result = Err(panic);
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the number of stages in the pipeline.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns `true` if the pipeline has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+}
+
+impl fmt::Debug for Stages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stages")
+            .field("stages", &self.handles.len())
+            .finish()
+    }
+}
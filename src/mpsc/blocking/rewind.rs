@@ -0,0 +1,262 @@
+//! A synchronous channel whose receiver retains recently received messages
+//! so that they can be delivered again.
+//!
+//! A rewindable [`Receiver`] keeps a copy of the last `history` messages it
+//! has received. Calling [`Receiver::rewind`] moves the receiver back by up
+//! to that many messages, so that the next calls to [`Receiver::recv`]
+//! re-deliver them, in order, before any new messages are received from the
+//! channel. This is useful for replaying recent events to a debugging
+//! consumer that has just been attached to a running system.
+//!
+//! Retaining the history requires cloning each message as it is received, so
+//! the messages sent on a rewindable channel must implement [`Clone`]. The
+//! sending half of the channel is an ordinary blocking [`Sender`].
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::blocking::rewind;
+//!
+//! let (tx, rx) = rewind::channel(16, 2);
+//! for i in 0..4 {
+//!     tx.send(i).unwrap();
+//! }
+//!
+//! assert_eq!(rx.recv(), Some(0));
+//! assert_eq!(rx.recv(), Some(1));
+//! assert_eq!(rx.recv(), Some(2));
+//!
+//! // Only the last two messages are retained.
+//! assert_eq!(rx.rewind(3), 2);
+//! assert_eq!(rx.recv(), Some(1));
+//! assert_eq!(rx.recv(), Some(2));
+//! assert_eq!(rx.recv(), Some(3));
+//! ```
+use super::Sender;
+use crate::{
+    mpsc::errors::{RecvTimeoutError, TryRecvError},
+    recycling::{self, Recycle},
+    util::mutex::Mutex,
+};
+use alloc::collections::VecDeque;
+use core::fmt;
+use std::time::Duration;
+
+/// Receives messages from a rewindable channel, retaining the most recently
+/// received messages so that they can be re-delivered.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Receiver<T, R = recycling::DefaultRecycle> {
+    rx: super::Receiver<T, R>,
+    history: Mutex<History<T>>,
+}
+
+struct History<T> {
+    /// The most recently received messages, oldest first.
+    messages: VecDeque<T>,
+    /// The maximum number of messages to retain.
+    limit: usize,
+    /// How many of the newest retained messages are waiting to be
+    /// re-delivered.
+    replay: usize,
+}
+
+/// Returns a new rewindable channel with space for `capacity` messages,
+/// whose receiver retains the last `history` received messages.
+///
+/// # Panics
+///
+/// - If `capacity` is 0.
+/// - If `history` is 0.
+#[must_use]
+pub fn channel<T: Default + Clone>(capacity: usize, history: usize) -> (Sender<T>, Receiver<T>) {
+    with_recycle(capacity, history, recycling::DefaultRecycle::new())
+}
+
+/// Returns a new rewindable channel with space for `capacity` messages and
+/// the provided [recycling policy], whose receiver retains the last
+/// `history` received messages.
+///
+/// # Panics
+///
+/// - If `capacity` is 0.
+/// - If `history` is 0.
+///
+/// [recycling policy]: crate::recycling::Recycle
+#[must_use]
+pub fn with_recycle<T: Clone, R: Recycle<T>>(
+    capacity: usize,
+    history: usize,
+    recycle: R,
+) -> (Sender<T, R>, Receiver<T, R>) {
+    assert!(history > 0, "a rewindable channel must retain some history");
+    let (tx, rx) = super::with_recycle(capacity, recycle);
+    let history = History {
+        messages: VecDeque::with_capacity(history),
+        limit: history,
+        replay: 0,
+    };
+    (
+        tx,
+        Receiver {
+            rx,
+            history: Mutex::new(history),
+        },
+    )
+}
+
+// === impl Receiver ===
+
+impl<T, R> Receiver<T, R>
+where
+    T: Clone,
+    R: Recycle<T>,
+{
+    /// Receives the next message, waiting until one is available.
+    ///
+    /// If the receiver has been [rewound](Self::rewind), the retained
+    /// messages are re-delivered first. Otherwise, this behaves like
+    /// [`blocking::Receiver::recv`](super::Receiver::recv), and the received
+    /// message is added to the history.
+    ///
+    /// Returns `None` once the channel has closed and every message, including
+    /// any waiting to be re-delivered, has been received.
+    pub fn recv(&self) -> Option<T> {
+        if let Some(val) = self.replay() {
+            return Some(val);
+        }
+        let val = self.rx.recv()?;
+        self.retain(&val);
+        Some(val)
+    }
+
+    /// Receives the next message, waiting for at most `timeout`.
+    ///
+    /// See [`recv`](Self::recv) for how rewound messages are re-delivered.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        if let Some(val) = self.replay() {
+            return Ok(val);
+        }
+        let val = self.rx.recv_timeout(timeout)?;
+        self.retain(&val);
+        Ok(val)
+    }
+
+    /// Attempts to receive the next message without waiting.
+    ///
+    /// See [`recv`](Self::recv) for how rewound messages are re-delivered.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        if let Some(val) = self.replay() {
+            return Ok(val);
+        }
+        let val = self.rx.try_recv()?;
+        self.retain(&val);
+        Ok(val)
+    }
+
+    fn replay(&self) -> Option<T> {
+        let mut history = self.history.lock();
+        if history.replay == 0 {
+            return None;
+        }
+        let idx = history.messages.len() - history.replay;
+        history.replay -= 1;
+        Some(history.messages[idx].clone())
+    }
+
+    fn retain(&self, val: &T) {
+        let mut history = self.history.lock();
+        if history.messages.len() == history.limit {
+            history.messages.pop_front();
+        }
+        history.messages.push_back(val.clone());
+    }
+}
+
+impl<T, R> Receiver<T, R> {
+    /// Moves the receiver back by up to `n` messages, so that they are
+    /// delivered again by the next calls to [`recv`](Self::recv).
+    ///
+    /// At most the number of retained messages can be rewound, including any
+    /// that are already waiting to be re-delivered. Returns the number of
+    /// additional messages that will be re-delivered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking::rewind;
+    ///
+    /// let (tx, rx) = rewind::channel(8, 4);
+    /// tx.send("a").unwrap();
+    /// tx.send("b").unwrap();
+    /// drop(tx);
+    ///
+    /// assert_eq!(rx.recv(), Some("a"));
+    /// assert_eq!(rx.recv(), Some("b"));
+    /// assert_eq!(rx.recv(), None);
+    ///
+    /// // Even after the channel has closed, the history can be replayed.
+    /// assert_eq!(rx.rewind(1), 1);
+    /// assert_eq!(rx.recv(), Some("b"));
+    /// assert_eq!(rx.recv(), None);
+    /// ```
+    pub fn rewind(&self, n: usize) -> usize {
+        let mut history = self.history.lock();
+        let replay = core::cmp::min(history.replay.saturating_add(n), history.messages.len());
+        let rewound = replay - history.replay;
+        history.replay = replay;
+        rewound
+    }
+
+    /// Returns the number of received messages currently retained.
+    pub fn retained(&self) -> usize {
+        self.history.lock().messages.len()
+    }
+
+    /// Returns the maximum number of received messages that are retained.
+    pub fn history(&self) -> usize {
+        self.history.lock().limit
+    }
+
+    /// Returns the number of messages waiting in the channel, not counting
+    /// rewound messages waiting to be re-delivered.
+    pub fn len(&self) -> usize {
+        self.rx.len()
+    }
+
+    /// Returns `true` if there are no messages waiting in the channel.
+    pub fn is_empty(&self) -> bool {
+        self.rx.is_empty()
+    }
+
+    /// Returns the total capacity of the channel.
+    pub fn capacity(&self) -> usize {
+        self.rx.capacity()
+    }
+
+    /// Returns `true` if the channel has closed (all corresponding
+    /// [`Sender`]s have been dropped).
+    pub fn is_closed(&self) -> bool {
+        self.rx.is_closed()
+    }
+}
+
+impl<T: Clone, R: Recycle<T>> Iterator for &Receiver<T, R> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+impl<T: fmt::Debug, R: fmt::Debug> fmt::Debug for Receiver<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let history = self.history.lock();
+        f.debug_struct("Receiver")
+            .field("rx", &self.rx)
+            .field("retained", &history.messages.len())
+            .field("limit", &history.limit)
+            .field("replay", &history.replay)
+            .finish()
+    }
+}
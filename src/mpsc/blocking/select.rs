@@ -0,0 +1,192 @@
+//! Receiving from a blocking channel in a [`crossbeam_channel::select!`].
+//!
+//! A selecting [`Receiver`] wraps a [`blocking::Receiver`], and provides a
+//! readiness handle: a [`crossbeam_channel::Receiver`] that becomes ready
+//! when a message may have been sent to the channel, or when the channel may
+//! have closed. This handle can be used in `select!` alongside existing
+//! `crossbeam_channel` channels, which lets code that selects over several
+//! channels be migrated to `thingbuf` one channel at a time.
+//!
+//! Once the handle is selected, its token should be received, and the
+//! message received with [`Receiver::try_recv`] or
+//! [`Receiver::try_recv_ref`]. When one of these finds the channel empty,
+//! it arranges for the handle to become ready once the next message is
+//! sent, so the handle may occasionally become ready without a message
+//! being available; `try_recv` then returns [`TryRecvError::Empty`], and the
+//! receiver should simply select again.
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_channel::select;
+//! use thingbuf::mpsc::{blocking::{self, select}, errors::TryRecvError};
+//! use std::thread;
+//!
+//! let (tx, rx) = blocking::channel::<u32>(8);
+//! let rx = select::Receiver::new(rx);
+//! let (legacy_tx, mut legacy_rx) = crossbeam_channel::unbounded::<&str>();
+//!
+//! thread::spawn(move || {
+//!     tx.send(1).unwrap();
+//!     legacy_tx.send("hello").unwrap();
+//!     tx.send(2).unwrap();
+//! });
+//!
+//! let mut received = Vec::new();
+//! while received.len() < 3 {
+//!     select! {
+//!         recv(rx.ready()) -> _ => match rx.try_recv() {
+//!             Ok(n) => received.push(n.to_string()),
+//!             // The handle was ready, but the message isn't here yet.
+//!             Err(TryRecvError::Empty) => {}
+//!             Err(e) => panic!("unexpected error: {}", e),
+//!         },
+//!         recv(legacy_rx) -> msg => match msg {
+//!             Ok(msg) => received.push(msg.to_string()),
+//!             // The legacy sender is done, so stop selecting on it.
+//!             Err(_) => legacy_rx = crossbeam_channel::never(),
+//!         },
+//!     }
+//! }
+//!
+//! received.sort();
+//! assert_eq!(received, ["1", "2", "hello"]);
+//! ```
+//!
+//! [`blocking::Receiver`]: super::Receiver
+use super::{
+    park::{Unpark, Unparker},
+    RecvRef,
+};
+use crate::{
+    mpsc::{errors::TryRecvError, NotifyTx, RecvRefInner},
+    recycling::{self, Recycle},
+    util::ref_watch::RefWatch,
+};
+use alloc::sync::Arc;
+use core::{fmt, task::Poll};
+
+/// A [`blocking::Receiver`](super::Receiver) that may be used in a
+/// [`crossbeam_channel::select!`].
+///
+/// See the [module-level documentation](self) for details.
+pub struct Receiver<T, R = recycling::DefaultRecycle> {
+    rx: super::Receiver<T, R>,
+    ready: crossbeam_channel::Receiver<()>,
+    signal: Arc<Signal>,
+}
+
+/// Makes the readiness handle ready when the channel wakes its receiver.
+struct Signal(crossbeam_channel::Sender<()>);
+
+// === impl Receiver ===
+
+impl<T, R> Receiver<T, R> {
+    /// Returns a new `Receiver` that receives messages from `rx`.
+    ///
+    /// The readiness handle starts out ready, so that messages that were
+    /// sent before the `Receiver` was created are received.
+    #[must_use]
+    pub fn new(rx: super::Receiver<T, R>) -> Self {
+        let (tx, ready) = crossbeam_channel::bounded(1);
+        let signal = Arc::new(Signal(tx));
+        signal.unpark();
+        Self { rx, ready, signal }
+    }
+
+    /// Returns the readiness handle, for use in a
+    /// [`crossbeam_channel::select!`].
+    ///
+    /// The handle becomes ready when a message may be available, or when the
+    /// channel may have closed. Once it is selected, its token should be
+    /// received, and then [`try_recv`](Self::try_recv) or
+    /// [`try_recv_ref`](Self::try_recv_ref) called.
+    #[must_use]
+    pub fn ready(&self) -> &crossbeam_channel::Receiver<()> {
+        &self.ready
+    }
+
+    /// Attempts to receive the next message by reference, without blocking.
+    ///
+    /// If the channel is empty, the readiness handle is armed, so that it
+    /// becomes ready when the next message is sent or the channel closes.
+    /// Otherwise, the handle is left ready, as more messages may be
+    /// available.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if there are no messages in
+    ///   the channel.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if the channel has closed and
+    ///   every message has been received.
+    pub fn try_recv_ref(&self) -> Result<RecvRef<'_, T>, TryRecvError> {
+        let inner = &self.rx.inner;
+        let waiter = || Unparker::Custom(self.signal.clone());
+        match inner.core.poll_recv_ref(inner.slots.as_ref(), waiter) {
+            Poll::Ready(Some(slot)) => {
+                self.signal.unpark();
+                Ok(RecvRef(RecvRefInner {
+                    _notify: NotifyTx(&inner.core),
+                    _audit: inner.core.ref_audit.recv(),
+                    _watch: RefWatch::recv(),
+                    slot,
+                }))
+            }
+            Poll::Ready(None) => {
+                // Keep the handle ready, so that a `select!` that is still
+                // waiting on it observes that the channel has closed.
+                self.signal.unpark();
+                Err(TryRecvError::Closed)
+            }
+            Poll::Pending => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Attempts to receive the next message by value, without blocking.
+    ///
+    /// See [`try_recv_ref`](Self::try_recv_ref) for details.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if there are no messages in
+    ///   the channel.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if the channel has closed and
+    ///   every message has been received.
+    pub fn try_recv(&self) -> Result<T, TryRecvError>
+    where
+        R: Recycle<T>,
+    {
+        let mut slot = self.try_recv_ref()?;
+        Ok(recycling::take(&mut *slot, &self.rx.inner.recycle))
+    }
+
+    /// Returns a reference to the underlying
+    /// [`blocking::Receiver`](super::Receiver).
+    pub fn get_ref(&self) -> &super::Receiver<T, R> {
+        &self.rx
+    }
+
+    /// Consumes this `Receiver`, returning the underlying
+    /// [`blocking::Receiver`](super::Receiver).
+    pub fn into_inner(self) -> super::Receiver<T, R> {
+        self.rx
+    }
+}
+
+impl<T: fmt::Debug, R: fmt::Debug> fmt::Debug for Receiver<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("rx", &self.rx)
+            .field("ready", &!self.ready.is_empty())
+            .finish()
+    }
+}
+
+// === impl Signal ===
+
+impl Unpark for Signal {
+    fn unpark(&self) {
+        // If the handle is already ready, there's nothing to do.
+        let _ = self.0.try_send(());
+    }
+}
@@ -0,0 +1,521 @@
+//! An experimental synchronous channel that gives each producer its own ring.
+//!
+//! In the channels in the [`blocking`](super) module, every [`Sender`] claims
+//! slots from the same ring, so senders on different threads contend with
+//! each other on the ring's tail index. A *sharded* channel instead consists
+//! of several rings (*shards*), each with its own capacity. Each [`Sender`]
+//! is assigned to one shard when it is created, with senders distributed
+//! round-robin across the shards, and the [`Receiver`] receives from all of
+//! the shards in turn. If each producer thread uses its own [`Sender`] and
+//! there are at least as many shards as producers, producers never contend
+//! with each other at all. This is useful for workloads with very high
+//! fan-in, such as collecting events from a thread on every core.
+//!
+//! Messages sent by the same [`Sender`] are received in the order in which
+//! they were sent. However, messages sent by [`Sender`]s assigned to different
+//! shards may be received in a different order than they were sent in. Since
+//! each clone of a [`Sender`] is assigned to the next shard, a producer that
+//! sends through several clones does not, by default, get its messages back
+//! in order. To keep them in order, attach a [`ProducerToken`] to each of the
+//! clones: every [`Sender`] with the same token sends to the same shard.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::blocking::sharded;
+//! use std::thread;
+//!
+//! // a channel with 4 shards of 16 slots each.
+//! let (tx, rx) = sharded::channel(4, 16);
+//!
+//! // give each producer thread its own sender, and therefore its own shard.
+//! for producer in 0..4 {
+//!     let tx = tx.clone();
+//!     thread::spawn(move || {
+//!         for i in 0..10 {
+//!             tx.send((producer, i)).unwrap();
+//!         }
+//!     });
+//! }
+//! drop(tx);
+//!
+//! let mut received = 0;
+//! while let Some((producer, i)) = rx.recv() {
+//!     println!("received {} from producer {}", i, producer);
+//!     received += 1;
+//! }
+//! assert_eq!(received, 40);
+//! ```
+use super::{park, send_ref, Inner, RecvRef, SendRef};
+use crate::{
+    loom::{
+        atomic::{self, AtomicUsize, Ordering},
+        sync::Arc,
+    },
+    mpsc::{
+        errors::{Closed, TryRecvError, TrySendError},
+        ChannelCore,
+    },
+    recycling::{self, Recycle},
+    wait::WaitResult,
+    Slot, MAX_CAPACITY,
+};
+use alloc::boxed::Box;
+use core::fmt;
+
+/// Synchronously sends values to one shard of an associated [`Receiver`].
+///
+/// Instances of this struct are created by the [`channel`] and
+/// [`with_recycle`] functions, and by cloning an existing `Sender`. Each
+/// clone is assigned to the next shard.
+pub struct Sender<T, R = recycling::DefaultRecycle> {
+    shared: Arc<Shared<T, R>>,
+    shard: usize,
+}
+
+/// Keeps every [`Sender`] it is attached to on a single shard.
+///
+/// Messages sent from a single thread through [`Sender`]s with the same token
+/// are received in the order in which they were sent, even if they were sent
+/// through different clones. Tokens are created by [`Sender::token`], and
+/// attached with [`Sender::attach`]. They are cheap to create and to clone.
+///
+/// A token only pins a shard of the channel whose [`Sender`] created it.
+#[derive(Clone, Debug)]
+pub struct ProducerToken {
+    shard: usize,
+}
+
+/// Synchronously receives values from associated [`Sender`]s, on every shard
+/// of a sharded channel.
+///
+/// Instances of this struct are created by the [`channel`] and
+/// [`with_recycle`] functions.
+pub struct Receiver<T, R = recycling::DefaultRecycle> {
+    shared: Arc<Shared<T, R>>,
+    /// The shard to try receiving from first.
+    next: AtomicUsize,
+}
+
+struct Shared<T, R> {
+    shards: Box<[Inner<T, R>]>,
+    /// The shard that the next new sender is assigned to.
+    next_shard: AtomicUsize,
+    /// The number of senders across all shards. The shards are closed
+    /// together once every sender has been dropped.
+    tx_count: AtomicUsize,
+}
+
+/// Returns a new sharded channel with `shards` shards, each with the provided
+/// capacity.
+///
+/// This channel will use the [default recycling policy].
+///
+/// # Panics
+///
+/// If `shards` or `capacity` is 0, or `capacity` exceeds
+/// `usize::MAX & !(1 << (usize::BITS - 1))`.
+///
+/// [default recycling policy]: crate::recycling::DefaultRecycle
+#[must_use]
+pub fn channel<T: Default + Clone>(shards: usize, capacity: usize) -> (Sender<T>, Receiver<T>) {
+    with_recycle(shards, capacity, recycling::DefaultRecycle::new())
+}
+
+/// Returns a new sharded channel with `shards` shards, each with the provided
+/// capacity and [recycling policy].
+///
+/// Each shard of the channel uses a clone of `recycle`.
+///
+/// # Panics
+///
+/// If `shards` or `capacity` is 0, or `capacity` exceeds
+/// `usize::MAX & !(1 << (usize::BITS - 1))`.
+///
+/// [recycling policy]: crate::recycling::Recycle
+#[must_use]
+pub fn with_recycle<T, R: Recycle<T> + Clone>(
+    shards: usize,
+    capacity: usize,
+    recycle: R,
+) -> (Sender<T, R>, Receiver<T, R>) {
+    assert!(shards > 0);
+    assert!(capacity > 0);
+    assert!(capacity <= MAX_CAPACITY);
+    let shards = (0..shards)
+        .map(|_| Inner {
+            core: ChannelCore::new(capacity),
+            slots: Slot::make_boxed_array(capacity),
+            recycle: recycle.clone(),
+        })
+        .collect();
+    let shared = Arc::new(Shared {
+        shards,
+        next_shard: AtomicUsize::new(1),
+        tx_count: AtomicUsize::new(1),
+    });
+    let tx = Sender {
+        shared: shared.clone(),
+        shard: 0,
+    };
+    let rx = Receiver {
+        shared,
+        next: AtomicUsize::new(0),
+    };
+    (tx, rx)
+}
+
+// === impl Sender ===
+
+impl<T, R> Sender<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Reserves a slot in this sender's shard to mutate in place, blocking
+    /// until there is capacity in the shard.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] end of the channel has been dropped, this
+    /// returns a [`Closed`] error.
+    pub fn send_ref(&self) -> Result<SendRef<'_, T>, Closed> {
+        let shard = self.inner();
+        send_ref(&shard.core, &shard.slots, &shard.recycle)
+    }
+
+    /// Sends a message by value, blocking until there is capacity in this
+    /// sender's shard.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] end of the channel has been dropped, this
+    /// returns a [`Closed`] error containing the sent value.
+    pub fn send(&self, val: T) -> Result<(), Closed<T>> {
+        match self.send_ref() {
+            Err(Closed(())) => Err(Closed(val)),
+            Ok(mut slot) => {
+                *slot = val;
+                Ok(())
+            }
+        }
+    }
+
+    /// Attempts to reserve a slot in this sender's shard to mutate in place,
+    /// without blocking until capacity is available.
+    ///
+    /// # Errors
+    ///
+    /// - [`TrySendError::Full`] if this sender's shard is full, even if
+    ///   other shards have capacity.
+    /// - [`TrySendError::Closed`] if the [`Receiver`] end of the channel has
+    ///   been dropped.
+    pub fn try_send_ref(&self) -> Result<SendRef<'_, T>, TrySendError> {
+        let shard = self.inner();
+        shard
+            .core
+            .try_send_ref(&shard.slots, &shard.recycle)
+            .map(SendRef)
+    }
+
+    /// Attempts to send a message by value, without blocking until capacity
+    /// is available.
+    ///
+    /// # Errors
+    ///
+    /// - [`TrySendError::Full`] if this sender's shard is full, even if
+    ///   other shards have capacity.
+    /// - [`TrySendError::Closed`] if the [`Receiver`] end of the channel has
+    ///   been dropped.
+    ///
+    /// In both cases, the error includes the value passed to `try_send`.
+    pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
+        let shard = self.inner();
+        shard.core.try_send(&shard.slots, val, &shard.recycle)
+    }
+}
+
+impl<T, R> Sender<T, R> {
+    /// Returns the index of the shard this sender sends to.
+    #[must_use]
+    pub fn shard(&self) -> usize {
+        self.shard
+    }
+
+    /// Returns a [`ProducerToken`] for the shard this sender sends to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking::sharded;
+    ///
+    /// let (tx, rx) = sharded::channel(4, 16);
+    /// let token = tx.token();
+    ///
+    /// // clones are assigned to the next shard...
+    /// let mut tx2 = tx.clone();
+    /// assert_ne!(tx2.shard(), tx.shard());
+    ///
+    /// // ...unless the token is attached to them.
+    /// tx2.attach(&token);
+    /// assert_eq!(tx2.shard(), tx.shard());
+    ///
+    /// tx.send(1).unwrap();
+    /// tx2.send(2).unwrap();
+    /// assert_eq!(rx.recv(), Some(1));
+    /// assert_eq!(rx.recv(), Some(2));
+    /// ```
+    #[must_use]
+    pub fn token(&self) -> ProducerToken {
+        ProducerToken { shard: self.shard }
+    }
+
+    /// Attaches a [`ProducerToken`] to this sender, so that it sends to the
+    /// token's shard from now on.
+    ///
+    /// Messages this sender has already sent are unaffected, and may be
+    /// received after messages it sends to the new shard. See
+    /// [`token`](Self::token) for an example.
+    ///
+    /// # Panics
+    ///
+    /// If `token` is for a shard that this channel does not have.
+    pub fn attach(&mut self, token: &ProducerToken) {
+        assert!(
+            token.shard < self.shared.len(),
+            "producer token is for shard {}, but the channel only has {} shards",
+            token.shard,
+            self.shared.len(),
+        );
+        self.shard = token.shard;
+    }
+
+    /// Returns the capacity of this sender's shard.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.inner().core.core.capacity()
+    }
+
+    fn inner(&self) -> &Inner<T, R> {
+        &self.shared.shards[self.shard]
+    }
+}
+
+impl<T, R> Clone for Sender<T, R> {
+    fn clone(&self) -> Self {
+        test_dbg!(self.shared.tx_count.fetch_add(1, Ordering::Relaxed));
+        let shard = self.shared.next_shard.fetch_add(1, Ordering::Relaxed) % self.shared.len();
+        Self {
+            shared: self.shared.clone(),
+            shard,
+        }
+    }
+}
+
+impl<T, R> Drop for Sender<T, R> {
+    fn drop(&mut self) {
+        if test_dbg!(self.shared.tx_count.fetch_sub(1, Ordering::Release)) > 1 {
+            return;
+        }
+
+        // if we are the last sender, synchronize
+        test_dbg!(atomic::fence(Ordering::SeqCst));
+        for shard in self.shared.shards.iter() {
+            shard.core.core.close();
+            shard.core.rx_wait.close_tx();
+        }
+    }
+}
+
+impl<T, R> fmt::Debug for Sender<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("shard", &self.shard)
+            .field("shards", &self.shared.len())
+            .finish()
+    }
+}
+
+// === impl Receiver ===
+
+impl<T, R> Receiver<T, R> {
+    /// Receives the next message for this receiver, **by reference**.
+    ///
+    /// The receiver tries each shard in turn, starting with the shard after
+    /// the one it last received a message from, so that a busy shard cannot
+    /// starve the others.
+    ///
+    /// This method returns `None` if the channel has been closed and there are
+    /// no remaining messages in any shard. If there are no messages in any
+    /// shard, but the channel has not yet been closed, this method will block
+    /// until a message is sent or the channel is closed.
+    pub fn recv_ref(&self) -> Option<RecvRef<'_, T>> {
+        self.recv_shard().map(|(_, slot)| slot)
+    }
+
+    /// Receives the next message for this receiver, **by value**.
+    ///
+    /// See [`recv_ref`](Self::recv_ref) for details.
+    pub fn recv(&self) -> Option<T>
+    where
+        R: Recycle<T>,
+    {
+        let (shard, mut slot) = self.recv_shard()?;
+        Some(recycling::take(&mut *slot, &shard.recycle))
+    }
+
+    /// Attempts to receive the next message for this receiver by reference
+    /// without blocking.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if every shard is empty.
+    /// - [`TryRecvError::Closed`] if every shard is empty and the channel
+    ///   has been closed.
+    pub fn try_recv_ref(&self) -> Result<RecvRef<'_, T>, TryRecvError> {
+        self.try_recv_shard().map(|(_, slot)| slot)
+    }
+
+    /// Attempts to receive the next message for this receiver by value
+    /// without blocking.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if every shard is empty.
+    /// - [`TryRecvError::Closed`] if every shard is empty and the channel
+    ///   has been closed.
+    pub fn try_recv(&self) -> Result<T, TryRecvError>
+    where
+        R: Recycle<T>,
+    {
+        let (shard, mut slot) = self.try_recv_shard()?;
+        Ok(recycling::take(&mut *slot, &shard.recycle))
+    }
+
+    /// Returns the number of shards in the channel.
+    #[must_use]
+    pub fn shards(&self) -> usize {
+        self.shared.len()
+    }
+
+    /// Returns the *total* capacity of the channel, across all shards.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.shared
+            .shards
+            .iter()
+            .map(|shard| shard.core.core.capacity())
+            .sum()
+    }
+
+    /// Returns the total number of messages in the channel, across all
+    /// shards.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shared
+            .shards
+            .iter()
+            .map(|shard| shard.core.core.len())
+            .sum()
+    }
+
+    /// Returns `true` if there are currently no messages in any shard.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Receives the next message from any shard, blocking until one is
+    /// available, and returns it along with the shard it came from.
+    fn recv_shard(&self) -> Option<(&Inner<T, R>, RecvRef<'_, T>)> {
+        loop {
+            match self.try_recv_shard() {
+                Ok(received) => return Some(received),
+                Err(TryRecvError::Closed) => return None,
+                Err(_) => {}
+            }
+
+            // Register this thread as the waiter for every shard, so that a
+            // message on any of them unparks it. Then, try again, in case a
+            // message was sent before the waiter was registered.
+            let mut notified = false;
+            for shard in self.shared.shards.iter() {
+                match test_dbg!(shard.core.rx_wait.wait_with(park::current)) {
+                    WaitResult::Wait => {}
+                    WaitResult::Notified | WaitResult::Closed => notified = true,
+                }
+            }
+            if notified {
+                continue;
+            }
+
+            match self.try_recv_shard() {
+                Ok(received) => return Some(received),
+                Err(TryRecvError::Closed) => return None,
+                Err(_) => {
+                    test_println!("parking ({:?})", park::current());
+                    park::park();
+                }
+            }
+        }
+    }
+
+    /// Tries to receive a message from each shard in turn, starting with the
+    /// shard after the one the last message was received from, so that a busy
+    /// shard cannot starve the others.
+    fn try_recv_shard(&self) -> Result<(&Inner<T, R>, RecvRef<'_, T>), TryRecvError> {
+        let shards = &self.shared.shards;
+        let start = self.next.load(Ordering::Relaxed);
+        let mut closed = true;
+        for i in 0..shards.len() {
+            let idx = (start + i) % shards.len();
+            let shard = &shards[idx];
+            match shard.core.try_recv_ref(&shard.slots) {
+                Ok(slot) => {
+                    self.next.store((idx + 1) % shards.len(), Ordering::Relaxed);
+                    return Ok((shard, RecvRef(slot)));
+                }
+                Err(TryRecvError::Closed) => {}
+                Err(_) => closed = false,
+            }
+        }
+
+        if closed {
+            Err(TryRecvError::Closed)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+}
+
+impl<T, R: Recycle<T>> Iterator for &Receiver<T, R> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+impl<T, R> Drop for Receiver<T, R> {
+    fn drop(&mut self) {
+        for shard in self.shared.shards.iter() {
+            shard.core.close_rx();
+        }
+    }
+}
+
+impl<T, R> fmt::Debug for Receiver<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("shards", &self.shared.len())
+            .field("next", &self.next.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+// === impl Shared ===
+
+impl<T, R> Shared<T, R> {
+    fn len(&self) -> usize {
+        self.shards.len()
+    }
+}
@@ -0,0 +1,498 @@
+//! A synchronous channel whose messages expire if they are not received
+//! within a time-to-live (TTL).
+//!
+//! Each message sent on a TTL channel may be given a deadline, either by
+//! sending it with an explicit TTL using [`Sender::send_with_ttl`], or by the
+//! channel's default TTL, which applies to messages sent with
+//! [`Sender::send`]. When the [`Receiver`] reaches a message whose deadline has
+//! passed, the message is skipped: it is left in its slot to be recycled, and
+//! the receiver's [expired count](Receiver::expired_count) is incremented.
+//!
+//! This is useful for messages that are worthless once they are stale, such
+//! as telemetry samples or commands that are superseded by newer ones. A
+//! slow consumer that falls behind then catches up by skipping the stale
+//! messages, rather than processing every one of them.
+//!
+//! # Dead letters
+//!
+//! A channel created with [`with_dead_letter`] routes the messages it would
+//! otherwise lose into a secondary *dead-letter* channel, which the
+//! application can drain to log, count, or retry them. A message is
+//! dead-lettered when:
+//!
+//! - it expires before it is received, or
+//! - it is still in the channel when the [`Receiver`] is dropped.
+//!
+//! Messages dropped by the channel's [load shedding] are not dead-lettered,
+//! as they are handed back to the sender in a [`TrySendError::Shed`] error.
+//!
+//! Messages are sent to the dead-letter channel without waiting, so a
+//! dead-letter channel that is full (or closed) does not slow down the
+//! channel it serves; any dead letters that do not fit are dropped.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::blocking::ttl;
+//! use std::{thread, time::Duration};
+//!
+//! // Messages expire 10ms after they are sent, unless sent with another TTL.
+//! let (tx, rx) = ttl::channel(8, Some(Duration::from_millis(10)));
+//!
+//! tx.send("stale").unwrap();
+//! tx.send_with_ttl("fresh", Duration::from_secs(60)).unwrap();
+//! thread::sleep(Duration::from_millis(20));
+//!
+//! // The first message expired while it was waiting in the channel.
+//! assert_eq!(rx.recv(), Some("fresh"));
+//! assert_eq!(rx.expired_count(), 1);
+//! ```
+//!
+//! [load shedding]: super::Receiver::set_load_shedding
+use crate::{
+    loom::atomic::{AtomicUsize, Ordering::Relaxed},
+    mpsc::errors::{Closed, RecvTimeoutError, TryRecvError, TrySendError},
+    recycling::{self, Recycle},
+};
+use alloc::sync::Arc;
+use core::fmt;
+use std::time::{Duration, Instant};
+
+/// Sends messages that expire after a TTL to a [`Receiver`].
+///
+/// See the [module-level documentation](self) for details.
+pub struct Sender<T, R = recycling::DefaultRecycle> {
+    tx: super::Sender<Expiring<T>, ExpiringRecycle<R>>,
+    ttl: Option<Duration>,
+}
+
+/// Receives messages from a TTL channel, skipping messages that have
+/// expired.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Receiver<T, R = recycling::DefaultRecycle> {
+    rx: super::Receiver<Expiring<T>, ExpiringRecycle<R>>,
+    expired: AtomicUsize,
+    dead_letter: Option<DeadLetter<T>>,
+}
+
+/// A message, and the deadline after which it is no longer received.
+struct Expiring<T> {
+    value: T,
+    deadline: Option<Instant>,
+}
+
+/// Recycles the messages in an [`Expiring`] with the channel's recycling
+/// policy.
+struct ExpiringRecycle<R>(R);
+
+/// Moves a message out of its slot and into the dead-letter channel.
+type DeadLetter<T> = Arc<dyn Fn(&mut T) + Send + Sync>;
+
+/// Returns a new TTL channel with space for `capacity` messages.
+///
+/// Messages sent with [`Sender::send`] or [`Sender::try_send`] expire once
+/// `default_ttl` has elapsed, or never expire if `default_ttl` is `None`.
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+#[must_use]
+pub fn channel<T: Default + Clone>(
+    capacity: usize,
+    default_ttl: Option<Duration>,
+) -> (Sender<T>, Receiver<T>) {
+    with_recycle(capacity, default_ttl, recycling::DefaultRecycle::new())
+}
+
+/// Returns a new TTL channel with space for `capacity` messages and the
+/// provided [recycling policy].
+///
+/// Messages sent with [`Sender::send`] or [`Sender::try_send`] expire once
+/// `default_ttl` has elapsed, or never expire if `default_ttl` is `None`.
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+///
+/// [recycling policy]: crate::recycling::Recycle
+#[must_use]
+pub fn with_recycle<T, R: Recycle<T>>(
+    capacity: usize,
+    default_ttl: Option<Duration>,
+    recycle: R,
+) -> (Sender<T, R>, Receiver<T, R>) {
+    new(capacity, default_ttl, recycle, None)
+}
+
+/// Returns a new TTL channel with space for `capacity` messages, which sends
+/// the messages it would otherwise lose to `dead_letter`.
+///
+/// The channel uses the same [recycling policy] as the dead-letter channel.
+/// See [the module-level documentation](self#dead-letters) for which
+/// messages are dead-lettered.
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::mpsc::blocking::{self, ttl};
+/// use std::{thread, time::Duration};
+///
+/// let (dead_tx, dead_rx) = blocking::channel(8);
+/// let (tx, rx) = ttl::with_dead_letter(8, Some(Duration::from_millis(10)), dead_tx);
+///
+/// tx.send(1).unwrap();
+/// thread::sleep(Duration::from_millis(20));
+/// tx.send_with_ttl(2, Duration::from_secs(60)).unwrap();
+/// tx.send_with_ttl(3, Duration::from_secs(60)).unwrap();
+///
+/// assert_eq!(rx.recv(), Some(2));
+/// // Dropping the receiver dead-letters the message it did not receive.
+/// drop(rx);
+///
+/// assert_eq!(dead_rx.try_recv(), Ok(1));
+/// assert_eq!(dead_rx.try_recv(), Ok(3));
+/// ```
+///
+/// [recycling policy]: crate::recycling::Recycle
+#[must_use]
+pub fn with_dead_letter<T, R>(
+    capacity: usize,
+    default_ttl: Option<Duration>,
+    dead_letter: super::Sender<T, R>,
+) -> (Sender<T, R>, Receiver<T, R>)
+where
+    T: Send + Sync + 'static,
+    R: Recycle<T> + Clone + Send + Sync + 'static,
+{
+    let recycle = dead_letter.inner.recycle.clone();
+    let dead_letter: DeadLetter<T> = Arc::new(move |msg: &mut T| {
+        let msg = recycling::take(msg, &dead_letter.inner.recycle);
+        if dead_letter.try_send(msg).is_err() {
+            test_println!("dead-letter channel is full or closed; dropping message");
+        }
+    });
+    new(capacity, default_ttl, recycle, Some(dead_letter))
+}
+
+fn new<T, R: Recycle<T>>(
+    capacity: usize,
+    default_ttl: Option<Duration>,
+    recycle: R,
+    dead_letter: Option<DeadLetter<T>>,
+) -> (Sender<T, R>, Receiver<T, R>) {
+    let (tx, rx) = super::with_recycle(capacity, ExpiringRecycle(recycle));
+    (
+        Sender {
+            tx,
+            ttl: default_ttl,
+        },
+        Receiver {
+            rx,
+            expired: AtomicUsize::new(0),
+            dead_letter,
+        },
+    )
+}
+
+// === impl Sender ===
+
+impl<T, R> Sender<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Sends a message that expires after the channel's default TTL,
+    /// waiting until there is capacity.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] has been dropped, this returns a [`Closed`] error
+    /// containing the message.
+    pub fn send(&self, val: T) -> Result<(), Closed<T>> {
+        self.send_until(val, deadline(self.ttl))
+    }
+
+    /// Sends a message that expires once `ttl` has elapsed, waiting until
+    /// there is capacity.
+    ///
+    /// The TTL starts when this method is called, so time spent waiting for
+    /// capacity counts towards it.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] has been dropped, this returns a [`Closed`] error
+    /// containing the message.
+    pub fn send_with_ttl(&self, val: T, ttl: Duration) -> Result<(), Closed<T>> {
+        self.send_until(val, deadline(Some(ttl)))
+    }
+
+    /// Attempts to send a message that expires after the channel's default
+    /// TTL, without waiting for capacity.
+    ///
+    /// # Errors
+    ///
+    /// If the channel is full, the [`Receiver`] has been dropped, or the
+    /// message was dropped by the channel's load shedding, this returns a
+    /// [`TrySendError`] containing the message.
+    pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
+        self.try_send_until(val, deadline(self.ttl))
+    }
+
+    /// Attempts to send a message that expires once `ttl` has elapsed,
+    /// without waiting for capacity.
+    ///
+    /// # Errors
+    ///
+    /// If the channel is full, the [`Receiver`] has been dropped, or the
+    /// message was dropped by the channel's load shedding, this returns a
+    /// [`TrySendError`] containing the message.
+    pub fn try_send_with_ttl(&self, val: T, ttl: Duration) -> Result<(), TrySendError<T>> {
+        self.try_send_until(val, deadline(Some(ttl)))
+    }
+
+    fn send_until(&self, value: T, deadline: Option<Instant>) -> Result<(), Closed<T>> {
+        self.tx
+            .send(Expiring { value, deadline })
+            .map_err(|Closed(msg)| Closed(msg.value))
+    }
+
+    fn try_send_until(&self, value: T, deadline: Option<Instant>) -> Result<(), TrySendError<T>> {
+        self.tx
+            .try_send(Expiring { value, deadline })
+            .map_err(|err| match err {
+                TrySendError::Full(msg) => TrySendError::Full(msg.value),
+                TrySendError::Closed(msg) => TrySendError::Closed(msg.value),
+                TrySendError::Shed(msg) => TrySendError::Shed(msg.value),
+            })
+    }
+
+    /// Returns the total capacity of the channel.
+    pub fn capacity(&self) -> usize {
+        self.tx.capacity()
+    }
+
+    /// Returns the number of messages waiting in the channel, including any
+    /// that have expired but have not yet been skipped by the receiver.
+    pub fn len(&self) -> usize {
+        self.tx.len()
+    }
+
+    /// Returns `true` if there are no messages waiting in the channel.
+    pub fn is_empty(&self) -> bool {
+        self.tx.is_empty()
+    }
+}
+
+impl<T, R> Sender<T, R> {
+    /// Returns the TTL of messages sent with [`send`](Self::send) and
+    /// [`try_send`](Self::try_send), or `None` if they never expire.
+    pub fn default_ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+}
+
+impl<T, R> Clone for Sender<T, R> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            ttl: self.ttl,
+        }
+    }
+}
+
+impl<T, R: Recycle<T>> fmt::Debug for Sender<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .field("ttl", &self.ttl)
+            .finish()
+    }
+}
+
+// === impl Receiver ===
+
+impl<T, R> Receiver<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Receives the next message that has not expired, waiting until one is
+    /// available.
+    ///
+    /// Returns `None` once the channel has closed and every message has been
+    /// received or skipped.
+    pub fn recv(&self) -> Option<T> {
+        loop {
+            let mut msg = self.rx.recv_ref()?;
+            if !self.expire(&mut msg) {
+                return Some(recycling::take(&mut msg.value, &self.rx.inner.recycle.0));
+            }
+        }
+    }
+
+    /// Receives the next message that has not expired, waiting for at most
+    /// `timeout`.
+    ///
+    /// # Errors
+    ///
+    /// - [`RecvTimeoutError::Timeout`] if no unexpired message was received
+    ///   within `timeout`.
+    /// - [`RecvTimeoutError::Closed`] if the channel has closed and every
+    ///   message has been received or skipped.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let start = Instant::now();
+        loop {
+            let remaining = timeout
+                .checked_sub(start.elapsed())
+                .ok_or(RecvTimeoutError::Timeout)?;
+            let mut msg = self.rx.recv_ref_timeout(remaining)?;
+            if !self.expire(&mut msg) {
+                return Ok(recycling::take(&mut msg.value, &self.rx.inner.recycle.0));
+            }
+        }
+    }
+
+    /// Attempts to receive the next message that has not expired, without
+    /// waiting.
+    ///
+    /// Any expired messages at the front of the channel are skipped.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if there are no unexpired messages in the
+    ///   channel.
+    /// - [`TryRecvError::Closed`] if the channel has closed and every message
+    ///   has been received or skipped.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        loop {
+            let mut msg = self.rx.try_recv_ref()?;
+            if !self.expire(&mut msg) {
+                return Ok(recycling::take(&mut msg.value, &self.rx.inner.recycle.0));
+            }
+        }
+    }
+
+    /// Returns `true` if `msg` has expired, counting it as skipped and
+    /// dead-lettering it.
+    fn expire(&self, msg: &mut Expiring<T>) -> bool {
+        match msg.deadline {
+            Some(deadline) if deadline <= Instant::now() => {
+                test_println!("skipping message that expired at {:?}", deadline);
+                self.expired.fetch_add(1, Relaxed);
+                if let Some(ref dead_letter) = self.dead_letter {
+                    dead_letter(&mut msg.value);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<T, R> Receiver<T, R> {
+    /// Returns the number of messages that have been skipped because they
+    /// expired before they were received.
+    pub fn expired_count(&self) -> usize {
+        self.expired.load(Relaxed)
+    }
+
+    /// Enables or disables load shedding for this channel.
+    ///
+    /// This behaves like the blocking [`Receiver::set_load_shedding`]: shed
+    /// messages are handed back to the sender rather than dead-lettered.
+    ///
+    /// [`Receiver::set_load_shedding`]: super::Receiver::set_load_shedding
+    pub fn set_load_shedding(&self, ratio: Option<f64>) {
+        self.rx.set_load_shedding(ratio)
+    }
+
+    /// Returns the number of messages that have been shed by load shedding.
+    pub fn shed_count(&self) -> usize {
+        self.rx.shed_count()
+    }
+
+    /// Returns the total capacity of the channel.
+    pub fn capacity(&self) -> usize {
+        self.rx.capacity()
+    }
+
+    /// Returns the number of messages waiting in the channel, including any
+    /// that have expired but have not yet been skipped.
+    pub fn len(&self) -> usize {
+        self.rx.len()
+    }
+
+    /// Returns `true` if there are no messages waiting in the channel.
+    pub fn is_empty(&self) -> bool {
+        self.rx.is_empty()
+    }
+
+    /// Returns `true` if the channel has closed (all corresponding
+    /// [`Sender`]s have been dropped).
+    pub fn is_closed(&self) -> bool {
+        self.rx.is_closed()
+    }
+}
+
+impl<T, R> Drop for Receiver<T, R> {
+    fn drop(&mut self) {
+        let dead_letter = match self.dead_letter {
+            Some(ref dead_letter) => dead_letter,
+            None => return,
+        };
+        let inner = &self.rx.inner;
+        while let Ok(mut msg) = inner.core.try_recv_ref(&inner.slots) {
+            dead_letter(&mut msg.value);
+        }
+    }
+}
+
+impl<T, R: Recycle<T>> Iterator for &Receiver<T, R> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+impl<T, R> fmt::Debug for Receiver<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .field("is_closed", &self.is_closed())
+            .field("expired", &self.expired_count())
+            .field("dead_letter", &self.dead_letter.is_some())
+            .finish()
+    }
+}
+
+// === impl ExpiringRecycle ===
+
+impl<T, R: Recycle<T>> Recycle<Expiring<T>> for ExpiringRecycle<R> {
+    fn new_element(&self) -> Expiring<T> {
+        Expiring {
+            value: self.0.new_element(),
+            deadline: None,
+        }
+    }
+
+    fn recycle(&self, element: &mut Expiring<T>) {
+        self.0.recycle(&mut element.value);
+        element.deadline = None;
+    }
+
+    fn recycle_shrunk(&self, element: &mut Expiring<T>) -> usize {
+        element.deadline = None;
+        self.0.recycle_shrunk(&mut element.value)
+    }
+}
+
+fn deadline(ttl: Option<Duration>) -> Option<Instant> {
+    // A TTL too long to represent never expires.
+    ttl.and_then(|ttl| Instant::now().checked_add(ttl))
+}
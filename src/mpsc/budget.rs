@@ -0,0 +1,675 @@
+//! An asynchronous channel bounded by the total size of its messages, rather
+//! than only by their number.
+//!
+//! The channels in the [`mpsc`](super) module hold a fixed number of
+//! messages. When message sizes vary by orders of magnitude, as they do in
+//! logging and networking pipelines, a bound on the number of messages does
+//! not bound the memory they hold: a channel with space for 1024 messages may
+//! hold 1024 bytes or 1024 megabytes. A *budget* channel also limits the
+//! total [payload size](Payload) of the messages waiting in it. Once the
+//! messages in the channel add up to its byte budget, senders wait until the
+//! [`Receiver`] has received enough of them to make room for their message.
+//!
+//! A message larger than the whole budget is still sent, once the channel is
+//! otherwise empty, so that it cannot block its sender forever. The channel
+//! is also bounded by its capacity in messages, as usual, so that it does not
+//! fill up with empty messages.
+//!
+//! A blocking version of this channel is provided by the
+//! [`blocking::budget`] module.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::budget;
+//!
+//! # async fn docs() {
+//! // Up to 64 messages, holding at most 1KiB in total.
+//! let (tx, rx) = budget::channel::<Vec<u8>>(64, 1024);
+//!
+//! tokio::spawn(async move {
+//!     for len in [1000, 100, 10] {
+//!         // The second message waits until the first has been received.
+//!         tx.send(vec![0; len]).await.unwrap();
+//!     }
+//! });
+//!
+//! let mut lens = Vec::new();
+//! while let Some(msg) = rx.recv().await {
+//!     lens.push(msg.len());
+//! }
+//! assert_eq!(lens, [1000, 100, 10]);
+//! # }
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() { docs().await; }
+//! ```
+//!
+//! [`blocking::budget`]: super::blocking::budget
+use crate::{
+    loom::atomic::{AtomicBool, AtomicUsize, Ordering::*},
+    mpsc::errors::{Closed, TryRecvError, TrySendError},
+    recycling::{self, Recycle},
+    wait::{queue, Notify, WaitQueue, WaitResult},
+};
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    future::Future,
+    mem, ops,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+/// Sends messages to a [`Receiver`], waiting while the channel's byte budget
+/// is used up.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Sender<T, R = recycling::DefaultRecycle> {
+    tx: super::Sender<Charged<T>, ChargedRecycle<R>>,
+    budget: Arc<Budget<Waker>>,
+}
+
+/// Receives messages from a budget channel, returning their size to the
+/// channel's byte budget.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Receiver<T, R = recycling::DefaultRecycle> {
+    rx: super::Receiver<Charged<T>, ChargedRecycle<R>>,
+    budget: Arc<Budget<Waker>>,
+}
+
+/// A reference to a message being sent to a budget channel.
+///
+/// The message's payload size is charged to the channel's byte budget when
+/// the `SendRef` is dropped, and the message is sent.
+///
+/// This type is returned by the [`Sender::send_ref`] and
+/// [`Sender::try_send_ref`] methods.
+pub struct SendRef<'a, T: Payload> {
+    slot: super::SendRef<'a, Charged<T>>,
+    budget: &'a Budget<Waker>,
+    reserved: usize,
+}
+
+/// The size of a message's payload, which counts towards a budget channel's
+/// byte budget.
+pub trait Payload {
+    /// Returns the size of the message's payload, in bytes.
+    fn payload_len(&self) -> usize;
+}
+
+/// A message, and the size it was charged to the budget when it was sent.
+pub(crate) struct Charged<T> {
+    pub(crate) value: T,
+    pub(crate) size: usize,
+}
+
+/// Recycles the messages in a [`Charged`] with the channel's recycling policy.
+pub(crate) struct ChargedRecycle<R>(pub(crate) R);
+
+/// The byte budget shared by the senders and receiver of a channel.
+pub(crate) struct Budget<W> {
+    pub(crate) max: usize,
+    /// The total size of the messages that have been sent, but not yet
+    /// received.
+    used: AtomicUsize,
+    /// Set when the receiver is dropped, so that senders give up.
+    closed: AtomicBool,
+    /// Senders waiting for bytes to be returned to the budget.
+    pub(crate) released: WaitQueue<W>,
+}
+
+/// Waits until there is room for a message in a budget channel's byte budget,
+/// and charges it to the budget.
+#[pin_project::pin_project(PinnedDrop)]
+struct Acquire<'a> {
+    budget: &'a Budget<Waker>,
+    size: usize,
+    waiting: bool,
+    #[pin]
+    waiter: queue::Waiter<Waker>,
+}
+
+/// Returns a new budget channel with space for `capacity` messages, whose
+/// sizes add up to at most `max_bytes`.
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+#[must_use]
+pub fn channel<T: Default + Clone>(capacity: usize, max_bytes: usize) -> (Sender<T>, Receiver<T>) {
+    with_recycle(capacity, max_bytes, recycling::DefaultRecycle::new())
+}
+
+/// Returns a new budget channel with space for `capacity` messages, whose
+/// sizes add up to at most `max_bytes`, and the provided [recycling policy].
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+///
+/// [recycling policy]: crate::recycling::Recycle
+#[must_use]
+pub fn with_recycle<T, R: Recycle<T>>(
+    capacity: usize,
+    max_bytes: usize,
+    recycle: R,
+) -> (Sender<T, R>, Receiver<T, R>) {
+    let (tx, rx) = super::with_recycle(capacity, ChargedRecycle(recycle));
+    let budget = Arc::new(Budget::new(max_bytes));
+    (
+        Sender {
+            tx,
+            budget: budget.clone(),
+        },
+        Receiver { rx, budget },
+    )
+}
+
+// === impl Sender ===
+
+impl<T, R> Sender<T, R>
+where
+    T: Payload,
+    R: Recycle<T>,
+{
+    /// Sends a message, waiting until there is room for its payload in the
+    /// byte budget, and a free slot in the channel.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] has been dropped, this returns a [`Closed`] error
+    /// containing the message.
+    pub async fn send(&self, value: T) -> Result<(), Closed<T>> {
+        let size = value.payload_len();
+        if Acquire::new(&self.budget, size).await.is_err() {
+            return Err(Closed(value));
+        }
+        self.tx
+            .send(Charged { value, size })
+            .await
+            .map_err(|Closed(msg)| {
+                self.budget.release(size);
+                Closed(msg.value)
+            })
+    }
+
+    /// Attempts to send a message, without waiting for room in the byte
+    /// budget or for a free slot.
+    ///
+    /// # Errors
+    ///
+    /// If the message does not fit in the byte budget, the channel is full,
+    /// or the [`Receiver`] has been dropped, this returns a [`TrySendError`]
+    /// containing the message.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let size = value.payload_len();
+        if let Err(err) = self.budget.try_acquire(size) {
+            return Err(err.with_value(value));
+        }
+        self.tx.try_send(Charged { value, size }).map_err(|err| {
+            self.budget.release(size);
+            match err {
+                TrySendError::Full(msg) => TrySendError::Full(msg.value),
+                TrySendError::Closed(msg) => TrySendError::Closed(msg.value),
+                TrySendError::Shed(msg) => TrySendError::Shed(msg.value),
+            }
+        })
+    }
+
+    /// Reserves a slot in the channel to write a message of about `size`
+    /// bytes in place, waiting until there is room for `size` bytes in the
+    /// byte budget, and a free slot in the channel.
+    ///
+    /// This reuses the allocations of received messages, like
+    /// [`mpsc::Sender::send_ref`](super::Sender::send_ref). As the message is
+    /// only written once the slot is reserved, `size` is charged to the
+    /// budget up front. When the returned [`SendRef`] is dropped, the charge
+    /// is corrected to the message's actual [payload size](Payload::payload_len),
+    /// so a message that turns out to be larger than `size` may exceed the
+    /// budget.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Receiver`] has been dropped, this returns a [`Closed`] error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::budget;
+    ///
+    /// # async fn docs() {
+    /// let (tx, rx) = budget::channel::<Vec<u8>>(8, 1024);
+    ///
+    /// let mut msg = tx.send_ref(5).await.unwrap();
+    /// msg.extend_from_slice(b"hello");
+    /// drop(msg);
+    /// assert_eq!(tx.used_bytes(), 5);
+    ///
+    /// assert_eq!(rx.recv().await.as_deref(), Some(&b"hello"[..]));
+    /// assert_eq!(tx.used_bytes(), 0);
+    /// # }
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() { docs().await; }
+    /// ```
+    pub async fn send_ref(&self, size: usize) -> Result<SendRef<'_, T>, Closed> {
+        Acquire::new(&self.budget, size).await?;
+        match self.tx.send_ref().await {
+            Ok(slot) => Ok(SendRef::new(slot, &self.budget, size)),
+            Err(closed) => {
+                self.budget.release(size);
+                Err(closed)
+            }
+        }
+    }
+
+    /// Attempts to reserve a slot in the channel to write a message of about
+    /// `size` bytes in place, without waiting for room in the byte budget or
+    /// for a free slot.
+    ///
+    /// See [`send_ref`](Self::send_ref) for how the message is charged to the
+    /// budget.
+    ///
+    /// # Errors
+    ///
+    /// If `size` bytes do not fit in the byte budget, the channel is full, or
+    /// the [`Receiver`] has been dropped, this returns a [`TrySendError`].
+    pub fn try_send_ref(&self, size: usize) -> Result<SendRef<'_, T>, TrySendError> {
+        self.budget.try_acquire(size)?;
+        match self.tx.try_send_ref() {
+            Ok(slot) => Ok(SendRef::new(slot, &self.budget, size)),
+            Err(err) => {
+                self.budget.release(size);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<T, R: Recycle<T>> Sender<T, R> {
+    /// Returns the channel's byte budget: the most that the payloads of the
+    /// messages waiting in the channel may add up to.
+    pub fn max_bytes(&self) -> usize {
+        self.budget.max
+    }
+
+    /// Returns the total payload size of the messages that have been sent,
+    /// but not yet received.
+    pub fn used_bytes(&self) -> usize {
+        self.budget.used()
+    }
+
+    /// Returns the total capacity of the channel, in messages.
+    pub fn capacity(&self) -> usize {
+        self.tx.capacity()
+    }
+}
+
+impl<T, R> Clone for Sender<T, R> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            budget: self.budget.clone(),
+        }
+    }
+}
+
+impl<T, R: Recycle<T>> fmt::Debug for Sender<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &self.capacity())
+            .field("max_bytes", &self.max_bytes())
+            .field("used_bytes", &self.used_bytes())
+            .finish()
+    }
+}
+
+// === impl Receiver ===
+
+impl<T, R> Receiver<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Receives the next message, waiting until one is available, and
+    /// returns its size to the byte budget.
+    ///
+    /// Returns `None` once the channel has closed and every message has been
+    /// received.
+    pub async fn recv(&self) -> Option<T> {
+        let mut msg = self.rx.recv_ref().await?;
+        Some(self.take(&mut msg))
+    }
+
+    /// Attempts to receive the next message without waiting, and returns its
+    /// size to the byte budget.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if there are no messages in the channel.
+    /// - [`TryRecvError::Closed`] if the channel has closed and every message
+    ///   has been received.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut msg = self.rx.try_recv_ref()?;
+        Ok(self.take(&mut msg))
+    }
+
+    /// Attempts to receive the next message, registering the current task to
+    /// be woken when one is available, and returns its size to the byte
+    /// budget.
+    ///
+    /// # Returns
+    ///
+    ///  * `Poll::Pending` if no messages are available but the channel is not
+    ///    closed, or if a spurious failure happens.
+    ///  * `Poll::Ready(Some(message))` if a message is available.
+    ///  * `Poll::Ready(None)` if the channel has been closed and all messages
+    ///    sent before it was closed have been received.
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.rx
+            .poll_recv_ref(cx)
+            .map(|msg| msg.map(|mut msg| self.take(&mut msg)))
+    }
+
+    fn take(&self, msg: &mut Charged<T>) -> T {
+        self.budget.release(mem::take(&mut msg.size));
+        recycling::take(&mut msg.value, &self.rx.recycle().0)
+    }
+}
+
+impl<T, R> Receiver<T, R> {
+    /// Returns the channel's byte budget: the most that the payloads of the
+    /// messages waiting in the channel may add up to.
+    pub fn max_bytes(&self) -> usize {
+        self.budget.max
+    }
+
+    /// Returns the total payload size of the messages that have been sent,
+    /// but not yet received.
+    pub fn used_bytes(&self) -> usize {
+        self.budget.used()
+    }
+
+    /// Returns the total capacity of the channel, in messages.
+    pub fn capacity(&self) -> usize {
+        self.rx.capacity()
+    }
+
+    /// Returns the number of messages waiting in the channel.
+    pub fn len(&self) -> usize {
+        self.rx.len()
+    }
+
+    /// Returns `true` if there are no messages waiting in the channel.
+    pub fn is_empty(&self) -> bool {
+        self.rx.is_empty()
+    }
+
+    /// Returns `true` if the channel has closed (all corresponding
+    /// [`Sender`]s have been dropped).
+    pub fn is_closed(&self) -> bool {
+        self.rx.is_closed()
+    }
+}
+
+impl<T, R> Drop for Receiver<T, R> {
+    fn drop(&mut self) {
+        // Wake any senders waiting for room in the budget, which will never
+        // be made now.
+        self.budget.close();
+    }
+}
+
+impl<T, R> fmt::Debug for Receiver<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .field("max_bytes", &self.max_bytes())
+            .field("used_bytes", &self.used_bytes())
+            .field("is_closed", &self.is_closed())
+            .finish()
+    }
+}
+
+// === impl SendRef ===
+
+impl<'a, T: Payload> SendRef<'a, T> {
+    fn new(
+        slot: super::SendRef<'a, Charged<T>>,
+        budget: &'a Budget<Waker>,
+        reserved: usize,
+    ) -> Self {
+        Self {
+            slot,
+            budget,
+            reserved,
+        }
+    }
+}
+
+impl<T: Payload> ops::Deref for SendRef<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.slot.value
+    }
+}
+
+impl<T: Payload> ops::DerefMut for SendRef<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.slot.value
+    }
+}
+
+impl<T: Payload> Drop for SendRef<'_, T> {
+    fn drop(&mut self) {
+        // Charge the message's actual size before it is sent, so that the
+        // receiver returns the same amount to the budget.
+        let size = self.slot.value.payload_len();
+        self.slot.size = size;
+        self.budget.settle(self.reserved, size);
+    }
+}
+
+impl<T: Payload + fmt::Debug> fmt::Debug for SendRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.slot.value.fmt(f)
+    }
+}
+
+// === impl Payload ===
+
+impl<T> Payload for Vec<T> {
+    #[inline]
+    fn payload_len(&self) -> usize {
+        self.len() * mem::size_of::<T>()
+    }
+}
+
+impl<T> Payload for Box<[T]> {
+    #[inline]
+    fn payload_len(&self) -> usize {
+        self.len() * mem::size_of::<T>()
+    }
+}
+
+impl Payload for String {
+    #[inline]
+    fn payload_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Payload for bytes::Bytes {
+    #[inline]
+    fn payload_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Payload for bytes::BytesMut {
+    #[inline]
+    fn payload_len(&self) -> usize {
+        self.len()
+    }
+}
+
+// === impl Budget ===
+
+impl<W: Notify + Unpin> Budget<W> {
+    pub(crate) fn new(max: usize) -> Self {
+        Self {
+            max,
+            used: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            released: WaitQueue::new(),
+        }
+    }
+
+    /// Charges `size` bytes to the budget, if there is room for them.
+    ///
+    /// A message larger than the whole budget fits only once the channel is
+    /// otherwise empty, so that it can still be sent.
+    pub(crate) fn try_acquire(&self, size: usize) -> Result<(), TrySendError> {
+        if self.closed.load(Acquire) {
+            return Err(TrySendError::Closed(()));
+        }
+        let mut used = self.used.load(Relaxed);
+        loop {
+            if used != 0 && size > self.max.saturating_sub(used) {
+                return Err(TrySendError::Full(()));
+            }
+            match self
+                .used
+                .compare_exchange_weak(used, used + size, AcqRel, Relaxed)
+            {
+                Ok(_) => return Ok(()),
+                Err(actual) => used = actual,
+            }
+        }
+    }
+
+    /// Returns `size` bytes to the budget, waking the senders waiting for
+    /// room.
+    pub(crate) fn release(&self, size: usize) {
+        if size == 0 {
+            return;
+        }
+        self.used.fetch_sub(size, AcqRel);
+        // Messages vary in size, so a sender that was woken may still not
+        // fit, while one behind it would. Wake them all to check. This also
+        // leaves a notification for a sender that is about to wait, so that
+        // it checks again rather than missing the bytes returned here.
+        self.released.notify_n(usize::MAX);
+    }
+
+    /// Corrects the charge for a message that `reserved` bytes were charged
+    /// for, once its actual size is known.
+    pub(crate) fn settle(&self, reserved: usize, size: usize) {
+        if size > reserved {
+            self.used.fetch_add(size - reserved, AcqRel);
+        } else {
+            self.release(reserved - size);
+        }
+    }
+
+    /// Marks the budget as closed, waking every waiting sender.
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Release);
+        self.released.close();
+    }
+
+    pub(crate) fn used(&self) -> usize {
+        self.used.load(Acquire)
+    }
+}
+
+// === impl Acquire ===
+
+impl<'a> Acquire<'a> {
+    fn new(budget: &'a Budget<Waker>, size: usize) -> Self {
+        Self {
+            budget,
+            size,
+            waiting: false,
+            waiter: queue::Waiter::new(),
+        }
+    }
+}
+
+impl Future for Acquire<'_> {
+    type Output = Result<(), Closed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            if *this.waiting {
+                match test_dbg!(this
+                    .budget
+                    .released
+                    .continue_wait(this.waiter.as_mut(), cx.waker()))
+                {
+                    WaitResult::Closed => return Poll::Ready(Err(Closed(()))),
+                    WaitResult::Wait => return Poll::Pending,
+                    WaitResult::Notified => *this.waiting = false,
+                }
+            }
+
+            match this.budget.try_acquire(*this.size) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                Err(TrySendError::Closed(())) => return Poll::Ready(Err(Closed(()))),
+                Err(TrySendError::Full(()) | TrySendError::Shed(())) => {}
+            }
+
+            match test_dbg!(this
+                .budget
+                .released
+                .start_wait(this.waiter.as_mut(), cx.waker()))
+            {
+                WaitResult::Closed => return Poll::Ready(Err(Closed(()))),
+                WaitResult::Wait => {
+                    *this.waiting = true;
+                    return Poll::Pending;
+                }
+                // Bytes were returned to the budget; check whether they are
+                // enough.
+                WaitResult::Notified => {}
+            }
+        }
+    }
+}
+
+#[pin_project::pinned_drop]
+impl PinnedDrop for Acquire<'_> {
+    fn drop(self: Pin<&mut Self>) {
+        let this = self.project();
+        if *this.waiting && this.waiter.is_linked() {
+            this.waiter.remove(&this.budget.released)
+        }
+    }
+}
+
+// === impl ChargedRecycle ===
+
+impl<T, R: Recycle<T>> Recycle<Charged<T>> for ChargedRecycle<R> {
+    fn new_element(&self) -> Charged<T> {
+        Charged {
+            value: self.0.new_element(),
+            size: 0,
+        }
+    }
+
+    fn recycle(&self, element: &mut Charged<T>) {
+        self.0.recycle(&mut element.value);
+        element.size = 0;
+    }
+
+    fn recycle_shrunk(&self, element: &mut Charged<T>) -> usize {
+        element.size = 0;
+        self.0.recycle_shrunk(&mut element.value)
+    }
+}
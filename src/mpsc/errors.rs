@@ -1,5 +1,14 @@
 //! Errors returned by channels.
+//!
+//! When the "std" feature flag is enabled, every error type implements
+//! [`std::error::Error`], and can be converted into a [`std::io::Error`] with
+//! an appropriate [`ErrorKind`], so that channel errors may be propagated with
+//! `?` from functions that return an `io::Result`.
+//!
+//! [`ErrorKind`]: std::io::ErrorKind
 use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
 
 /// Error returned by the [`Sender::send_timeout`] or [`Sender::send_ref_timeout`]
 /// (and [`StaticSender::send_timeout`]/[`StaticSender::send_ref_timeout`]) methods
@@ -21,6 +30,11 @@ pub enum SendTimeoutError<T = ()> {
     ///
     /// [`Receiver`]: super::Receiver
     Closed(T),
+    /// The data was not sent because it was dropped by the channel's [load
+    /// shedding].
+    ///
+    /// [load shedding]: super::blocking::Receiver::set_load_shedding
+    Shed(T),
 }
 
 /// Error returned by the [`Sender::try_send`] or [`Sender::try_send_ref`] (and
@@ -41,6 +55,11 @@ pub enum TrySendError<T = ()> {
     ///
     /// [`Receiver`]: super::Receiver
     Closed(T),
+    /// The data was not sent because it was dropped by the channel's [load
+    /// shedding].
+    ///
+    /// [load shedding]: super::Receiver::set_load_shedding
+    Shed(T),
 }
 
 /// Error returned by the [`Receiver::recv_timeout`] and [`Receiver::recv_ref_timeout`] methods
@@ -58,10 +77,12 @@ pub enum RecvTimeoutError {
     Closed,
 }
 
-/// Error returned by the [`Receiver::try_recv`] and [`Receiver::try_recv_ref`] methods.
+/// Error returned by the [`Receiver::try_recv`], [`Receiver::try_recv_ref`],
+/// and [`Receiver::try_recv_if`] methods.
 ///
 /// [`Receiver::try_recv`]: super::Receiver::try_recv
 /// [`Receiver::try_recv_ref`]: super::Receiver::try_recv_ref
+/// [`Receiver::try_recv_if`]: super::Receiver::try_recv_if
 #[non_exhaustive]
 #[derive(Debug, PartialEq, Eq)]
 pub enum TryRecvError {
@@ -75,14 +96,43 @@ pub enum TryRecvError {
 /// [`StaticSender::send`]/[`StaticSender::send_ref`]), if the
 /// [`Receiver`] half of the channel has been dropped.
 ///
+/// This is also returned by [`Receiver::recv_if`] when the channel has closed
+/// and all messages have been received.
+///
 /// [`Sender::send`]: super::Sender::send
 /// [`Sender::send_ref`]: super::Sender::send_ref
 /// [`StaticSender::send`]: super::StaticSender::send
 /// [`StaticSender::send_ref`]: super::StaticSender::send_ref
 /// [`Receiver`]: super::Receiver
+/// [`Receiver::recv_if`]: super::Receiver::recv_if
 #[derive(PartialEq, Eq)]
 pub struct Closed<T = ()>(pub(crate) T);
 
+/// Error returned by the `send_all` methods on [`Sender`] and
+/// [`StaticSender`] (and their [blocking] equivalents) when the channel closes
+/// before every item has been sent.
+///
+/// This error records how many items were sent before the channel closed, and
+/// allows recovering the item that could not be sent along with the rest of
+/// the items that were never taken from the iterator (or stream).
+///
+/// [`Sender`]: super::Sender
+/// [`StaticSender`]: super::StaticSender
+/// [blocking]: super::blocking
+pub struct SendAllError<T, I> {
+    pub(crate) sent: usize,
+    pub(crate) item: T,
+    pub(crate) rest: I,
+}
+
+/// Error yielded by a [`TimeoutItems`] stream when no message was received
+/// within its timeout.
+///
+/// [`TimeoutItems`]: super::TimeoutItems
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Elapsed(pub(crate) ());
+
 // === impl Closed ===
 
 impl<T> Closed<T> {
@@ -110,6 +160,80 @@ impl<T> fmt::Display for Closed<T> {
 #[cfg(feature = "std")]
 impl<T> std::error::Error for Closed<T> {}
 
+#[cfg(feature = "std")]
+impl<T: Send + Sync + 'static> From<Closed<T>> for io::Error {
+    fn from(err: Closed<T>) -> Self {
+        io::Error::new(io::ErrorKind::BrokenPipe, err)
+    }
+}
+
+// === impl SendAllError ===
+
+impl<T, I> SendAllError<T, I> {
+    /// Returns the number of items that were sent before the channel closed.
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+
+    /// Unwraps the item that could not be sent, along with the remaining
+    /// items that were never taken from the iterator or stream.
+    pub fn into_inner(self) -> (T, I) {
+        (self.item, self.rest)
+    }
+}
+
+impl<T, I> fmt::Debug for SendAllError<T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendAllError")
+            .field("sent", &self.sent)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, I> fmt::Display for SendAllError<T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "channel closed after sending {} items", self.sent)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, I> std::error::Error for SendAllError<T, I> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        static CLOSED: Closed = Closed(());
+        Some(&CLOSED)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, I> From<SendAllError<T, I>> for io::Error
+where
+    T: Send + Sync + 'static,
+    I: Send + Sync + 'static,
+{
+    fn from(err: SendAllError<T, I>) -> Self {
+        io::Error::new(io::ErrorKind::BrokenPipe, err)
+    }
+}
+
+// === impl Elapsed ===
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("timed out waiting on channel")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Elapsed {}
+
+#[cfg(feature = "std")]
+impl From<Elapsed> for io::Error {
+    fn from(err: Elapsed) -> Self {
+        io::Error::new(io::ErrorKind::TimedOut, err)
+    }
+}
+
 // === impl SendTimeoutError ===
 
 #[cfg(feature = "std")]
@@ -118,6 +242,7 @@ impl SendTimeoutError {
         match self {
             Self::Timeout(()) => SendTimeoutError::Timeout(value),
             Self::Closed(()) => SendTimeoutError::Closed(value),
+            Self::Shed(()) => SendTimeoutError::Shed(value),
         }
     }
 }
@@ -144,6 +269,14 @@ impl<T> SendTimeoutError<T> {
         matches!(self, Self::Timeout(_))
     }
 
+    /// Returns `true` if this error was returned because the message was
+    /// dropped by the channel's [load shedding].
+    ///
+    /// [load shedding]: super::blocking::Receiver::set_load_shedding
+    pub fn is_shed(&self) -> bool {
+        matches!(self, Self::Shed(_))
+    }
+
     /// Unwraps the inner `T` value held by this error.
     ///
     /// This method allows recovering the original message when sending to a
@@ -152,6 +285,7 @@ impl<T> SendTimeoutError<T> {
         match self {
             Self::Timeout(val) => val,
             Self::Closed(val) => val,
+            Self::Shed(val) => val,
         }
     }
 }
@@ -162,6 +296,7 @@ impl<T> fmt::Debug for SendTimeoutError<T> {
         f.write_str(match self {
             Self::Timeout(_) => "SendTimeoutError::Timeout(..)",
             Self::Closed(_) => "SendTimeoutError::Closed(..)",
+            Self::Shed(_) => "SendTimeoutError::Shed(..)",
         })
     }
 }
@@ -172,6 +307,7 @@ impl<T> fmt::Display for SendTimeoutError<T> {
         f.write_str(match self {
             Self::Timeout(_) => "timed out waiting for channel capacity",
             Self::Closed(_) => "channel closed",
+            Self::Shed(_) => "message dropped by load shedding",
         })
     }
 }
@@ -179,6 +315,18 @@ impl<T> fmt::Display for SendTimeoutError<T> {
 #[cfg(feature = "std")]
 impl<T> std::error::Error for SendTimeoutError<T> {}
 
+#[cfg(feature = "std")]
+impl<T: Send + Sync + 'static> From<SendTimeoutError<T>> for io::Error {
+    fn from(err: SendTimeoutError<T>) -> Self {
+        let kind = match err {
+            SendTimeoutError::Timeout(_) => io::ErrorKind::TimedOut,
+            SendTimeoutError::Closed(_) => io::ErrorKind::BrokenPipe,
+            SendTimeoutError::Shed(_) => io::ErrorKind::WouldBlock,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
 // === impl TrySendError ===
 
 impl TrySendError {
@@ -186,6 +334,7 @@ impl TrySendError {
         match self {
             Self::Full(()) => TrySendError::Full(value),
             Self::Closed(()) => TrySendError::Closed(value),
+            Self::Shed(()) => TrySendError::Shed(value),
         }
     }
 }
@@ -211,6 +360,14 @@ impl<T> TrySendError<T> {
         matches!(self, Self::Full(_))
     }
 
+    /// Returns `true` if this error was returned because the message was
+    /// dropped by the channel's [load shedding].
+    ///
+    /// [load shedding]: super::Receiver::set_load_shedding
+    pub fn is_shed(&self) -> bool {
+        matches!(self, Self::Shed(_))
+    }
+
     /// Unwraps the inner `T` value held by this error.
     ///
     /// This method allows recovering the original message when sending to a
@@ -219,6 +376,7 @@ impl<T> TrySendError<T> {
         match self {
             Self::Full(val) => val,
             Self::Closed(val) => val,
+            Self::Shed(val) => val,
         }
     }
 }
@@ -228,6 +386,7 @@ impl<T> fmt::Debug for TrySendError<T> {
         f.write_str(match self {
             Self::Full(_) => "TrySendError::Full(..)",
             Self::Closed(_) => "TrySendError::Closed(..)",
+            Self::Shed(_) => "TrySendError::Shed(..)",
         })
     }
 }
@@ -237,6 +396,7 @@ impl<T> fmt::Display for TrySendError<T> {
         f.write_str(match self {
             Self::Full(_) => "no available capacity",
             Self::Closed(_) => "channel closed",
+            Self::Shed(_) => "message dropped by load shedding",
         })
     }
 }
@@ -244,6 +404,18 @@ impl<T> fmt::Display for TrySendError<T> {
 #[cfg(feature = "std")]
 impl<T> std::error::Error for TrySendError<T> {}
 
+#[cfg(feature = "std")]
+impl<T: Send + Sync + 'static> From<TrySendError<T>> for io::Error {
+    fn from(err: TrySendError<T>) -> Self {
+        let kind = match err {
+            TrySendError::Full(_) => io::ErrorKind::WouldBlock,
+            TrySendError::Closed(_) => io::ErrorKind::BrokenPipe,
+            TrySendError::Shed(_) => io::ErrorKind::WouldBlock,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
 // === impl RecvTimeoutError ===
 
 #[cfg(feature = "std")]
@@ -259,6 +431,17 @@ impl fmt::Display for RecvTimeoutError {
     }
 }
 
+#[cfg(feature = "std")]
+impl From<RecvTimeoutError> for io::Error {
+    fn from(err: RecvTimeoutError) -> Self {
+        let kind = match err {
+            RecvTimeoutError::Timeout => io::ErrorKind::TimedOut,
+            RecvTimeoutError::Closed => io::ErrorKind::UnexpectedEof,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
 // == impl TryRecvError ==
 
 #[cfg(feature = "std")]
@@ -272,3 +455,14 @@ impl fmt::Display for TryRecvError {
         })
     }
 }
+
+#[cfg(feature = "std")]
+impl From<TryRecvError> for io::Error {
+    fn from(err: TryRecvError) -> Self {
+        let kind = match err {
+            TryRecvError::Empty => io::ErrorKind::WouldBlock,
+            TryRecvError::Closed => io::ErrorKind::UnexpectedEof,
+        };
+        io::Error::new(kind, err)
+    }
+}
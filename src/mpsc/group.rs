@@ -0,0 +1,356 @@
+//! Linked shutdown for groups of channels.
+//!
+//! A pipeline built from several channels usually has to be torn down as a
+//! unit: once one stage fails, or its channel closes, the stages before and
+//! after it should stop too. A [`ChannelGroup`] links the channels that are
+//! [added](ChannelGroup::add) to it, so that when any one of them closes, or
+//! when [`ChannelGroup::shutdown`] is called, all of them are closed.
+//!
+//! Closing a channel in a group has the same effect on its senders as
+//! dropping its receiver: sending fails with a [`Closed`] error. The
+//! receiver observes it as if every sender had been dropped: it receives the
+//! messages that are still in the channel, and then receives `None`. The
+//! [`ShutdownReason`] is recorded in the group, so that every stage can find
+//! out why the pipeline was shut down.
+//!
+//! Both the asynchronous and [blocking] channels, including static channels,
+//! may be added to a group, and a channel may belong to several groups.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::{blocking, group::{ChannelGroup, ShutdownReason}};
+//! use std::thread;
+//!
+//! let (parse_tx, parse_rx) = blocking::channel::<String>(8);
+//! let (store_tx, store_rx) = blocking::channel::<u64>(8);
+//!
+//! let group: ChannelGroup = ChannelGroup::new();
+//! group.add(&parse_tx);
+//! let store = group.add(&store_tx);
+//!
+//! let parser = thread::spawn(move || {
+//!     while let Some(line) = parse_rx.recv() {
+//!         if store_tx.send(line.parse().unwrap()).is_err() {
+//!             break;
+//!         }
+//!     }
+//! });
+//!
+//! parse_tx.send("1".to_string()).unwrap();
+//! assert_eq!(store_rx.recv(), Some(1));
+//!
+//! // Dropping the last stage's receiver closes its channel, which shuts down
+//! // the whole pipeline.
+//! drop(store_rx);
+//! parser.join().unwrap();
+//!
+//! assert_eq!(group.reason(), Some(ShutdownReason::Closed(store)));
+//! assert!(parse_tx.send("2".to_string()).is_err());
+//! ```
+//!
+//! [`Closed`]: super::errors::Closed
+//! [blocking]: super::blocking
+use crate::{
+    loom::atomic::{AtomicBool, Ordering::SeqCst},
+    util::mutex::{const_mutex, Mutex},
+};
+use alloc::{sync::Arc, vec::Vec};
+use core::{fmt, mem};
+
+/// Closes all of the channels added to it when any one of them closes, or
+/// when it is [shut down](Self::shutdown).
+///
+/// A `ChannelGroup` is a handle to a shared group, and may be cloned to
+/// share it between the stages of a pipeline.
+///
+/// See the [module-level documentation](self) for details.
+pub struct ChannelGroup<E = ()> {
+    shared: Arc<Shared<E>>,
+}
+
+/// Why a [`ChannelGroup`] was shut down.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ShutdownReason<E = ()> {
+    /// The channel that [`ChannelGroup::add`] returned this index for
+    /// closed.
+    Closed(usize),
+    /// [`ChannelGroup::shutdown`] was called with this reason.
+    Shutdown(E),
+}
+
+/// A channel that may be added to a [`ChannelGroup`].
+///
+/// This trait is implemented by the sender and receiver handles of every
+/// channel in the [`mpsc`](super) module, and cannot be implemented outside
+/// of this crate.
+pub trait Member: private::Sealed {}
+
+struct Shared<E> {
+    /// The members' channels, which are closed when the group shuts down.
+    members: Mutex<Vec<private::MemberRef>>,
+    reason: Mutex<Option<ShutdownReason<E>>>,
+    is_shut_down: AtomicBool,
+}
+
+mod private {
+    use crate::util::mutex::Mutex;
+    use alloc::{sync::Weak, vec::Vec};
+
+    /// The type-erased half of a group that is stored in its channels.
+    pub trait Link: Send + Sync {
+        fn member_closed(&self, member: usize);
+    }
+
+    /// A channel that can be closed by a group.
+    pub trait Close {
+        fn close(&self);
+
+        fn is_closed(&self) -> bool;
+
+        fn links(&self) -> &Links;
+    }
+
+    /// The groups that a channel is a member of, stored in the channel.
+    pub struct Links {
+        pub(super) groups: Mutex<Vec<(Weak<dyn Link>, usize)>>,
+    }
+
+    pub trait Sealed {
+        fn member_ref(&self) -> MemberRef;
+    }
+
+    #[derive(Clone)]
+    pub enum MemberRef {
+        Dynamic(Weak<dyn Close>),
+        Static(&'static dyn Close),
+    }
+
+    // Safety: a `MemberRef` is only used to close its channel, which only
+    // accesses the channel's lock-free core. If the channel is dropped by the
+    // group, because it upgraded the `Weak` after every handle was dropped,
+    // the `Member` impls have ensured that the channel's messages are `Send`.
+    unsafe impl Send for MemberRef {}
+    unsafe impl Sync for MemberRef {}
+
+    impl MemberRef {
+        pub(super) fn close(&self) {
+            match self {
+                MemberRef::Dynamic(chan) => {
+                    if let Some(chan) = chan.upgrade() {
+                        chan.close()
+                    }
+                }
+                MemberRef::Static(chan) => chan.close(),
+            }
+        }
+
+        pub(super) fn with<U>(&self, f: impl FnOnce(&dyn Close) -> U) -> Option<U> {
+            match self {
+                MemberRef::Dynamic(chan) => chan.upgrade().map(|chan| f(&*chan)),
+                MemberRef::Static(chan) => Some(f(*chan)),
+            }
+        }
+    }
+}
+
+use self::private::Link;
+pub(crate) use self::private::{Close, Links, MemberRef, Sealed};
+
+// === impl ChannelGroup ===
+
+impl<E> ChannelGroup<E>
+where
+    E: Send + Sync + 'static,
+{
+    /// Returns a new, empty `ChannelGroup`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                members: const_mutex(Vec::new()),
+                reason: const_mutex(None),
+                is_shut_down: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Adds the channel that `member` sends to or receives from to this
+    /// group, and returns the index that identifies it in a
+    /// [`ShutdownReason::Closed`].
+    ///
+    /// If the group has already been shut down, the channel is closed
+    /// immediately. If the channel has already closed, the group is shut
+    /// down.
+    pub fn add<M: Member>(&self, member: &M) -> usize {
+        let member = member.member_ref();
+        let idx = {
+            let mut members = self.shared.members.lock();
+            members.push(member.clone());
+            members.len() - 1
+        };
+
+        let link: Arc<dyn Link> = self.shared.clone();
+        let link = Arc::downgrade(&link);
+        let closed = member
+            .with(|chan| {
+                chan.links().groups.lock().push((link, idx));
+                chan.is_closed()
+            })
+            // The channel has already been dropped.
+            .unwrap_or(true);
+
+        if closed {
+            self.shared.member_closed(idx);
+        } else if self.is_shut_down() {
+            // If the group shut down before the channel was added to its
+            // members, shutting down did not close it.
+            member.close();
+        }
+        idx
+    }
+
+    /// Shuts down the group, closing all of its channels.
+    ///
+    /// Returns `true` if this call shut down the group, or `false` if it
+    /// had already been shut down, in which case the original reason is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::{self, group::{ChannelGroup, ShutdownReason}};
+    ///
+    /// let (tx, rx) = mpsc::channel::<u32>(8);
+    /// let group = ChannelGroup::new();
+    /// group.add(&tx);
+    ///
+    /// assert!(group.shutdown("configuration reloaded"));
+    /// assert!(!group.shutdown("too late"));
+    /// assert_eq!(
+    ///     group.reason(),
+    ///     Some(ShutdownReason::Shutdown("configuration reloaded")),
+    /// );
+    ///
+    /// assert!(tx.try_send(1).is_err());
+    /// assert!(rx.is_closed());
+    /// ```
+    pub fn shutdown(&self, reason: E) -> bool {
+        self.shared.shutdown(ShutdownReason::Shutdown(reason))
+    }
+}
+
+impl<E> ChannelGroup<E> {
+    /// Returns `true` if the group has been shut down.
+    #[must_use]
+    pub fn is_shut_down(&self) -> bool {
+        self.shared.is_shut_down.load(SeqCst)
+    }
+
+    /// Returns why the group was shut down, or `None` if it has not been.
+    #[must_use]
+    pub fn reason(&self) -> Option<ShutdownReason<E>>
+    where
+        E: Clone,
+    {
+        self.shared.reason.lock().clone()
+    }
+
+    /// Returns the number of channels that have been added to the group.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shared.members.lock().len()
+    }
+
+    /// Returns `true` if no channels have been added to the group.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<E> Clone for ChannelGroup<E> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<E> Default for ChannelGroup<E>
+where
+    E: Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for ChannelGroup<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChannelGroup")
+            .field("len", &self.len())
+            .field("reason", &*self.shared.reason.lock())
+            .finish()
+    }
+}
+
+// === impl Shared ===
+
+impl<E> Shared<E> {
+    fn shutdown(&self, reason: ShutdownReason<E>) -> bool {
+        {
+            // Record the reason before closing any channels, so that it is
+            // available to the stages that observe their channels closing.
+            let mut current = self.reason.lock();
+            if current.is_some() {
+                return false;
+            }
+            *current = Some(reason);
+        }
+        self.is_shut_down.store(true, SeqCst);
+
+        // Closing a channel shuts down its other groups, which may include
+        // this one, so the lock must not be held while closing them.
+        let members = self.members.lock().clone();
+        for member in &members {
+            member.close();
+        }
+        true
+    }
+}
+
+impl<E: Send + Sync> Link for Shared<E> {
+    fn member_closed(&self, member: usize) {
+        test_println!("channel group member {} closed", member);
+        self.shutdown(ShutdownReason::Closed(member));
+    }
+}
+
+// === impl Links ===
+
+impl Links {
+    pub(crate) const fn new() -> Self {
+        Self {
+            groups: const_mutex(Vec::new()),
+        }
+    }
+
+    /// Shuts down every group that the channel belongs to, now that it has
+    /// closed.
+    pub(crate) fn closed(&self) {
+        let groups = mem::take(&mut *self.groups.lock());
+        for (group, member) in groups {
+            if let Some(group) = group.upgrade() {
+                group.member_closed(member);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Links {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Links")
+            .field("groups", &self.groups.lock().len())
+            .finish()
+    }
+}
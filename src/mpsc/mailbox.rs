@@ -0,0 +1,541 @@
+use super::errors::{Closed, TryRecvError, TrySendError};
+use crate::{
+    loom::{
+        atomic::{self, AtomicUsize, Ordering::*},
+        cell::UnsafeCell,
+        sync::Arc,
+    },
+    recycling::{self, Recycle},
+    wait::{queue, WaitCell, WaitQueue, WaitResult},
+};
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+/// Returns a new asynchronous channel with room for exactly one message.
+///
+/// A mailbox behaves exactly like a [`channel`](super::channel) with a
+/// capacity of 1, but is specialized for it: its single slot is managed by a
+/// small state machine, rather than by the ring buffer's head and tail
+/// indices, so that sending and receiving are cheaper, and the channel is
+/// smaller. This suits the common pattern of handing a task its next
+/// command, where the sender waits for the task to take one command before
+/// sending the next.
+///
+/// This channel will use the [default recycling policy].
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::mpsc::{self, errors::TrySendError};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let (tx, rx) = mpsc::mailbox::<&str>();
+///
+///     tx.send("start").await.unwrap();
+///     // The mailbox is full until the command is received.
+///     assert!(matches!(tx.try_send("stop"), Err(TrySendError::Full("stop"))));
+///
+///     assert_eq!(rx.recv().await, Some("start"));
+///     tx.try_send("stop").unwrap();
+///     assert_eq!(rx.recv().await, Some("stop"));
+/// }
+/// ```
+///
+/// [default recycling policy]: crate::recycling::DefaultRecycle
+#[must_use]
+pub fn mailbox<T: Default + Clone>() -> (MailboxSender<T>, MailboxReceiver<T>) {
+    mailbox_with_recycle(recycling::DefaultRecycle::new())
+}
+
+/// Returns a new asynchronous channel with room for exactly one message,
+/// and the provided [recycling policy].
+///
+/// See [`mailbox`] for details.
+///
+/// [recycling policy]: crate::recycling::Recycle
+#[must_use]
+pub fn mailbox_with_recycle<T, R: Recycle<T>>(
+    recycle: R,
+) -> (MailboxSender<T, R>, MailboxReceiver<T, R>) {
+    let inner = Arc::new(Inner {
+        state: AtomicUsize::new(EMPTY),
+        slot: UnsafeCell::new(recycle.new_element()),
+        rx_wait: WaitCell::new(),
+        tx_wait: WaitQueue::new(),
+        tx_count: AtomicUsize::new(1),
+        recycle,
+    });
+    (
+        MailboxSender {
+            inner: inner.clone(),
+        },
+        MailboxReceiver { inner },
+    )
+}
+
+/// Asynchronously sends messages to an associated [`MailboxReceiver`].
+///
+/// Instances of this struct are created by the [`mailbox`] and
+/// [`mailbox_with_recycle`] functions.
+pub struct MailboxSender<T, R = recycling::DefaultRecycle> {
+    inner: Arc<Inner<T, R>>,
+}
+
+/// Asynchronously receives messages from associated [`MailboxSender`]s.
+///
+/// Instances of this struct are created by the [`mailbox`] and
+/// [`mailbox_with_recycle`] functions.
+pub struct MailboxReceiver<T, R = recycling::DefaultRecycle> {
+    inner: Arc<Inner<T, R>>,
+}
+
+struct Inner<T, R> {
+    /// The state of the slot: one of `EMPTY`, `WRITING`, `FULL`, or
+    /// `READING`, and the `CLOSED` bit.
+    state: AtomicUsize,
+    slot: UnsafeCell<T>,
+    rx_wait: WaitCell<Waker>,
+    tx_wait: WaitQueue<Waker>,
+    tx_count: AtomicUsize,
+    recycle: R,
+}
+
+/// Waits for the slot to be empty, and claims it for writing.
+#[pin_project::pin_project(PinnedDrop)]
+struct Reserve<'a, T, R> {
+    inner: &'a Inner<T, R>,
+    state: State,
+    #[pin]
+    waiter: queue::Waiter<Waker>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum State {
+    Start,
+    Waiting,
+    Done,
+}
+
+/// A claim on the slot for writing, which publishes the message to the
+/// receiver when dropped.
+struct Claim<'a, T, R>(&'a Inner<T, R>);
+
+struct Recv<'a, T, R>(&'a MailboxReceiver<T, R>);
+
+/// The slot holds no message.
+const EMPTY: usize = 0b00;
+/// A sender is writing a message to the slot.
+const WRITING: usize = 0b01;
+/// The slot holds a message.
+const FULL: usize = 0b10;
+/// The receiver is reading the message in the slot.
+const READING: usize = 0b11;
+/// Every sender, or the receiver, has been dropped.
+const CLOSED: usize = 0b100;
+
+// === impl MailboxSender ===
+
+impl<T, R: Recycle<T>> MailboxSender<T, R> {
+    /// Sends a message, waiting until the mailbox is empty.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the message was sent.
+    /// - [`Err`]`(`[`Closed`]`)` if the receiver has been dropped. The error
+    ///   includes the message.
+    pub async fn send(&self, val: T) -> Result<(), Closed<T>> {
+        match self.reserve().await {
+            Ok(claim) => {
+                claim.with_mut(|slot| *slot = val);
+                Ok(())
+            }
+            Err(Closed(())) => Err(Closed(val)),
+        }
+    }
+
+    /// Waits until the mailbox is empty, and calls `f` to write the message
+    /// in place, reusing the allocations of the previous message.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(U)` with the value returned by `f`, if the message was sent.
+    /// - [`Err`]`(`[`Closed`]`)` if the receiver has been dropped, in which
+    ///   case `f` is not called.
+    pub async fn send_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Result<U, Closed> {
+        let claim = self.reserve().await?;
+        Ok(claim.with_mut(|slot| {
+            self.inner.recycle.recycle(slot);
+            f(slot)
+        }))
+    }
+
+    /// Attempts to send a message, without waiting for the mailbox to be
+    /// empty.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TrySendError::Full`]`)` if the mailbox already holds a
+    ///   message.
+    /// - [`Err`]`(`[`TrySendError::Closed`]`)` if the receiver has been
+    ///   dropped.
+    ///
+    /// In both cases, the error includes the message.
+    pub fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
+        match self.inner.try_claim() {
+            Ok(claim) => {
+                claim.with_mut(|slot| *slot = val);
+                Ok(())
+            }
+            Err(e) => Err(e.with_value(val)),
+        }
+    }
+
+    /// Attempts to claim the mailbox without waiting for it to be empty, and
+    /// calls `f` to write the message in place.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TrySendError::Full`]`)` if the mailbox already holds a
+    ///   message.
+    /// - [`Err`]`(`[`TrySendError::Closed`]`)` if the receiver has been
+    ///   dropped.
+    ///
+    /// In both cases, `f` is not called.
+    pub fn try_send_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Result<U, TrySendError> {
+        let claim = self.inner.try_claim()?;
+        Ok(claim.with_mut(|slot| {
+            self.inner.recycle.recycle(slot);
+            f(slot)
+        }))
+    }
+
+    fn reserve(&self) -> Reserve<'_, T, R> {
+        Reserve {
+            inner: &self.inner,
+            state: State::Start,
+            waiter: queue::Waiter::new(),
+        }
+    }
+}
+
+impl<T, R> MailboxSender<T, R> {
+    /// Returns `true` if the receiver has been dropped.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        test_dbg!(self.inner.state.load(SeqCst)) & CLOSED != 0
+    }
+
+    /// Returns `true` if the mailbox holds a message, or one is being sent
+    /// or received.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.inner.state.load(Acquire) & !CLOSED != EMPTY
+    }
+}
+
+impl<T, R> Clone for MailboxSender<T, R> {
+    fn clone(&self) -> Self {
+        test_dbg!(self.inner.tx_count.fetch_add(1, Relaxed));
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, R> Drop for MailboxSender<T, R> {
+    fn drop(&mut self) {
+        if test_dbg!(self.inner.tx_count.fetch_sub(1, Release)) > 1 {
+            return;
+        }
+
+        // if we are the last sender, synchronize
+        test_dbg!(atomic::fence(SeqCst));
+        self.inner.state.fetch_or(CLOSED, SeqCst);
+        self.inner.rx_wait.close_tx();
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for MailboxSender<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MailboxSender")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+// === impl MailboxReceiver ===
+
+impl<T, R: Recycle<T>> MailboxReceiver<T, R> {
+    /// Receives the next message, waiting for one to be sent.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(T)` if a message was received.
+    /// - `None` if every sender has been dropped, and the mailbox is empty.
+    pub async fn recv(&self) -> Option<T> {
+        Recv(self).await
+    }
+
+    /// Attempts to receive the next message, without waiting for one to be
+    /// sent.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if the mailbox is empty.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if every sender has been
+    ///   dropped and the mailbox is empty.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.inner
+            .try_read(|slot| recycling::take(slot, &self.inner.recycle))
+    }
+
+    /// Attempts to receive the next message without waiting for one to be
+    /// sent, and calls `f` with a mutable reference to it in place.
+    ///
+    /// The message stays in the mailbox's slot, so that its allocations may
+    /// be reused by the next [`send_with`](MailboxSender::send_with) or
+    /// [`try_send_with`](MailboxSender::try_send_with).
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if the mailbox is empty.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if every sender has been
+    ///   dropped and the mailbox is empty.
+    ///
+    /// In both cases, `f` is not called.
+    pub fn try_recv_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Result<U, TryRecvError> {
+        self.inner.try_read(f)
+    }
+
+    /// Attempts to receive a message, registering the current task to be
+    /// woken when one is sent.
+    ///
+    /// # Returns
+    ///
+    /// - `Poll::Pending` if no message is available, in which case the
+    ///   current task is woken when one is sent, or when every sender has
+    ///   been dropped.
+    /// - `Poll::Ready(Some(T))` if a message was received.
+    /// - `Poll::Ready(None)` if every sender has been dropped, and the
+    ///   mailbox is empty.
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        macro_rules! try_poll_recv {
+            () => {
+                match self.try_recv() {
+                    Ok(msg) => return Poll::Ready(Some(msg)),
+                    Err(TryRecvError::Closed) => return Poll::Ready(None),
+                    _ => {}
+                }
+            };
+        }
+
+        loop {
+            try_poll_recv!();
+
+            match test_dbg!(self.inner.rx_wait.wait_with(|| cx.waker().clone())) {
+                WaitResult::Wait => {
+                    // A message may have been sent while the waiter was
+                    // being registered.
+                    try_poll_recv!();
+                    return Poll::Pending;
+                }
+                WaitResult::Closed => return Poll::Ready(self.try_recv().ok()),
+                WaitResult::Notified => crate::loom::hint::spin_loop(),
+            }
+        }
+    }
+}
+
+impl<T, R> MailboxReceiver<T, R> {
+    /// Returns `true` if every sender has been dropped.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        test_dbg!(self.inner.state.load(SeqCst)) & CLOSED != 0
+    }
+
+    /// Returns `true` if the mailbox holds a message.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.inner.state.load(Acquire) & !CLOSED == FULL
+    }
+}
+
+impl<T, R> Drop for MailboxReceiver<T, R> {
+    fn drop(&mut self) {
+        self.inner.state.fetch_or(CLOSED, SeqCst);
+        self.inner.tx_wait.close();
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for MailboxReceiver<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MailboxReceiver")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+// === impl Inner ===
+
+impl<T, R> Inner<T, R> {
+    fn try_claim(&self) -> Result<Claim<'_, T, R>, TrySendError> {
+        match test_dbg!(self
+            .state
+            .compare_exchange(EMPTY, WRITING, Acquire, Acquire))
+        {
+            Ok(_) => Ok(Claim(self)),
+            Err(state) if state & CLOSED != 0 => Err(TrySendError::Closed(())),
+            Err(_) => Err(TrySendError::Full(())),
+        }
+    }
+
+    fn try_read<U>(&self, f: impl FnOnce(&mut T) -> U) -> Result<U, TryRecvError> {
+        let mut state = test_dbg!(self.state.load(Acquire));
+        loop {
+            if state & !CLOSED != FULL {
+                // Senders set the `CLOSED` bit only after their last message
+                // has been published, so a closed mailbox that is not full
+                // will stay empty.
+                return Err(if state & CLOSED != 0 {
+                    TryRecvError::Closed
+                } else {
+                    TryRecvError::Empty
+                });
+            }
+
+            // Claim the message, preserving the `CLOSED` bit.
+            match test_dbg!(self.state.compare_exchange_weak(
+                state,
+                state | READING,
+                Acquire,
+                Acquire
+            )) {
+                Ok(_) => break,
+                Err(actual) => state = actual,
+            }
+        }
+
+        let res = self.slot.with_mut(|slot| unsafe {
+            // Safety: the `READING` state grants exclusive access to the
+            // slot.
+            f(&mut *slot)
+        });
+        test_dbg!(self.state.fetch_and(CLOSED, Release));
+        self.tx_wait.notify();
+        Ok(res)
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for Inner<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.load(Acquire);
+        f.debug_struct("Inner")
+            .field("full", &(state & !CLOSED == FULL))
+            .field("closed", &(state & CLOSED != 0))
+            .field("tx_count", &self.tx_count.load(Acquire))
+            .field("recycle", &self.recycle)
+            .finish()
+    }
+}
+
+// Safety: the slot is only accessed by the sender that claimed it for
+// writing, or by the receiver that claimed it for reading, so the mailbox is
+// `Sync` as long as its messages may be sent between threads.
+unsafe impl<T: Send, R: Send> Send for Inner<T, R> {}
+unsafe impl<T: Send, R: Sync> Sync for Inner<T, R> {}
+
+// === impl Claim ===
+
+impl<T, R> Claim<'_, T, R> {
+    fn with_mut<U>(self, f: impl FnOnce(&mut T) -> U) -> U {
+        self.0.slot.with_mut(|slot| unsafe {
+            // Safety: the `WRITING` state grants exclusive access to the
+            // slot.
+            f(&mut *slot)
+        })
+    }
+}
+
+impl<T, R> Drop for Claim<'_, T, R> {
+    fn drop(&mut self) {
+        // `WRITING` + 1 = `FULL`, preserving the `CLOSED` bit.
+        test_dbg!(self.0.state.fetch_add(FULL - WRITING, Release));
+        self.0.rx_wait.notify();
+    }
+}
+
+// === impl Reserve ===
+
+impl<'a, T, R> Future for Reserve<'a, T, R> {
+    type Output = Result<Claim<'a, T, R>, Closed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let inner = *this.inner;
+        let mut node = this.waiter;
+        loop {
+            match test_dbg!(*this.state) {
+                State::Start => {
+                    match inner.try_claim() {
+                        Ok(claim) => return Poll::Ready(Ok(claim)),
+                        Err(TrySendError::Closed(_)) => return Poll::Ready(Err(Closed(()))),
+                        Err(_) => {}
+                    }
+
+                    match test_dbg!(inner.tx_wait.start_wait(node.as_mut(), cx.waker())) {
+                        WaitResult::Closed => {
+                            *this.state = State::Done;
+                            return Poll::Ready(Err(Closed(())));
+                        }
+                        WaitResult::Wait => {
+                            *this.state = State::Waiting;
+                            return Poll::Pending;
+                        }
+                        WaitResult::Notified => continue,
+                    }
+                }
+                State::Waiting => {
+                    match test_dbg!(inner.tx_wait.continue_wait(node.as_mut(), cx.waker())) {
+                        WaitResult::Closed => {
+                            *this.state = State::Done;
+                            return Poll::Ready(Err(Closed(())));
+                        }
+                        WaitResult::Wait => return Poll::Pending,
+                        WaitResult::Notified => *this.state = State::Done,
+                    }
+                }
+                State::Done => match inner.try_claim() {
+                    Ok(claim) => return Poll::Ready(Ok(claim)),
+                    Err(TrySendError::Closed(_)) => return Poll::Ready(Err(Closed(()))),
+                    Err(_) => *this.state = State::Start,
+                },
+            }
+        }
+    }
+}
+
+#[pin_project::pinned_drop]
+impl<T, R> PinnedDrop for Reserve<'_, T, R> {
+    fn drop(self: Pin<&mut Self>) {
+        let this = self.project();
+        if test_dbg!(*this.state) == State::Waiting && test_dbg!(this.waiter.is_linked()) {
+            this.waiter.remove(&this.inner.tx_wait)
+        }
+    }
+}
+
+// === impl Recv ===
+
+impl<T, R: Recycle<T>> Future for Recv<'_, T, R> {
+    type Output = Option<T>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.poll_recv(cx)
+    }
+}
@@ -0,0 +1,354 @@
+//! Channel handles restricted to real-time safe operations.
+//!
+//! Code that runs on a real-time thread, such as an audio callback, or in an
+//! interrupt handler, must never wait for another thread: it may not block on
+//! a full or empty channel, park the thread, or allocate memory. The channel
+//! handles in the rest of the [`mpsc`](super) module offer both waiting and
+//! non-waiting operations, so nothing stops real-time code from calling
+//! `send` instead of `try_send` by mistake.
+//!
+//! The [`rt::Sender`](Sender) and [`rt::Receiver`](Receiver) types in this
+//! module wrap an existing channel handle, and expose *only* its
+//! non-waiting, non-allocating operations. Moving the real-time side's
+//! handles into these wrappers turns an accidental call to a waiting method
+//! into a compile error. The other side of the channel keeps its ordinary
+//! handles, and may wait as usual.
+//!
+//! Every handle in the [`mpsc`](super) module may be wrapped, including the
+//! [blocking] and static channels' handles.
+//!
+//! # Real-time guarantees
+//!
+//! The operations on these handles never wait for capacity or messages, and
+//! never allocate. They do, however, *wake* the other side of the channel:
+//!
+//! - Sending wakes a waiting receiver. For a blocking channel, this unparks
+//!   its thread, which does not block the sender. For an asynchronous
+//!   channel, this calls the receiving task's [`Waker`], whose behavior is up
+//!   to the async runtime.
+//! - Receiving wakes a sender that is waiting for capacity. While senders
+//!   are waiting, this briefly takes the lock that protects the channel's
+//!   queue of waiting senders. If the real-time side receives, and must never
+//!   contend for a lock, the other side should only use the `try_` methods
+//!   to send, as well.
+//!
+//! When the "watchdog" feature flag is enabled, the messages sent and
+//! received through these handles are not tracked by the watchdog, as
+//! tracking them would take its lock.
+//!
+//! Sending a message by value [recycles] the message that was previously
+//! stored in its slot, and receiving one by value replaces it with a new
+//! empty message, so whether those operations allocate or free memory
+//! depends on the message type and the [recycling policy]. The `_with`
+//! methods, which read and write messages in place, never do.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::{blocking, rt};
+//! use std::thread;
+//!
+//! // Audio samples are sent from the real-time audio thread to a thread that
+//! // writes them to disk.
+//! let (tx, rx) = blocking::channel::<[f32; 4]>(64);
+//! let tx = rt::Sender::new(tx);
+//!
+//! let writer = thread::spawn(move || {
+//!     let mut frames = 0;
+//!     while let Some(frame) = rx.recv() {
+//!         // write the frame to disk...
+//!         # let _ = frame;
+//!         frames += 1;
+//!     }
+//!     frames
+//! });
+//!
+//! // In the audio callback:
+//! for _ in 0..8 {
+//!     // `tx.send(...)` would not compile, as it may block.
+//!     if tx.try_send_with(|frame| *frame = [0.5; 4]).is_err() {
+//!         // The writer has fallen behind, so this frame is dropped.
+//!     }
+//! }
+//!
+//! drop(tx);
+//! assert!(writer.join().unwrap() <= 8);
+//! ```
+//!
+//! [blocking]: super::blocking
+//! [`Waker`]: core::task::Waker
+//! [recycles]: crate::recycling
+//! [recycling policy]: crate::recycling::Recycle
+use super::errors::{TryRecvError, TrySendError};
+#[cfg(any(feature = "alloc", feature = "static"))]
+use crate::recycling::Recycle;
+use core::fmt;
+
+/// A sender that may only send messages without waiting.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Clone)]
+pub struct Sender<S> {
+    tx: S,
+}
+
+/// A receiver that may only receive messages without waiting.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Receiver<S> {
+    rx: S,
+}
+
+/// A channel sender that can be wrapped in an [`rt::Sender`](Sender).
+///
+/// This trait is implemented by every sender in the [`mpsc`](super) module,
+/// and cannot be implemented outside of this crate.
+pub trait RtSend: private::Sealed {
+    /// The type of the messages sent by this sender.
+    type Message;
+
+    #[doc(hidden)]
+    fn try_send(&self, val: Self::Message) -> Result<(), TrySendError<Self::Message>>;
+
+    #[doc(hidden)]
+    fn try_send_with<F>(&self, f: F) -> Result<(), TrySendError>
+    where
+        F: FnOnce(&mut Self::Message);
+}
+
+/// A channel receiver that can be wrapped in an [`rt::Receiver`](Receiver).
+///
+/// This trait is implemented by every receiver in the [`mpsc`](super)
+/// module, and cannot be implemented outside of this crate.
+pub trait RtRecv: private::Sealed {
+    /// The type of the messages received by this receiver.
+    type Message;
+
+    #[doc(hidden)]
+    fn try_recv(&self) -> Result<Self::Message, TryRecvError>;
+
+    #[doc(hidden)]
+    fn try_recv_with<U>(&self, f: impl FnOnce(&mut Self::Message) -> U) -> Result<U, TryRecvError>;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+#[cfg(feature = "test-util")]
+pub(crate) use self::private::Sealed;
+
+// === impl Sender ===
+
+impl<S: RtSend> Sender<S> {
+    /// Wraps `tx`, so that it may only send messages without waiting.
+    #[must_use]
+    pub fn new(tx: S) -> Self {
+        Self { tx }
+    }
+
+    /// Attempts to send a message by value, without waiting for capacity.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TrySendError::Full`]`)` if the channel is at capacity.
+    /// - [`Err`]`(`[`TrySendError::Closed`]`)` if the receiver has been
+    ///   dropped.
+    ///
+    /// In both cases, the error includes the message.
+    pub fn try_send(&self, val: S::Message) -> Result<(), TrySendError<S::Message>> {
+        self.tx.try_send(val)
+    }
+
+    /// Attempts to claim a slot in the channel without waiting for capacity,
+    /// and calls `f` to write the message in place.
+    ///
+    /// This reuses the allocations of the message previously stored in the
+    /// slot, if it has any.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TrySendError::Full`]`)` if the channel is at capacity.
+    /// - [`Err`]`(`[`TrySendError::Closed`]`)` if the receiver has been
+    ///   dropped.
+    ///
+    /// In both cases, `f` is not called.
+    pub fn try_send_with<F>(&self, f: F) -> Result<(), TrySendError>
+    where
+        F: FnOnce(&mut S::Message),
+    {
+        self.tx.try_send_with(f)
+    }
+}
+
+impl<S> Sender<S> {
+    /// Unwraps this `Sender`, returning the underlying sender, whose waiting
+    /// methods may be called again.
+    pub fn into_inner(self) -> S {
+        self.tx
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Sender<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("rt::Sender").field(&self.tx).finish()
+    }
+}
+
+// === impl Receiver ===
+
+impl<S: RtRecv> Receiver<S> {
+    /// Wraps `rx`, so that it may only receive messages without waiting.
+    #[must_use]
+    pub fn new(rx: S) -> Self {
+        Self { rx }
+    }
+
+    /// Attempts to receive the next message by value, without waiting for
+    /// one to be sent.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if there are no messages in the
+    ///   channel.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if every sender has been
+    ///   dropped and the channel is empty.
+    pub fn try_recv(&self) -> Result<S::Message, TryRecvError> {
+        self.rx.try_recv()
+    }
+
+    /// Attempts to receive the next message without waiting for one to be
+    /// sent, and calls `f` with a mutable reference to it in place.
+    ///
+    /// The message stays in its slot, so that its allocations may be reused
+    /// by a later send.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if there are no messages in the
+    ///   channel.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if every sender has been
+    ///   dropped and the channel is empty.
+    ///
+    /// In both cases, `f` is not called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::{self, rt};
+    ///
+    /// let (tx, rx) = mpsc::channel::<Vec<f32>>(4);
+    /// let rx = rt::Receiver::new(rx);
+    ///
+    /// tx.try_send(vec![0.25, 0.5]).unwrap();
+    ///
+    /// let mut mix = [0.0; 2];
+    /// rx.try_recv_with(|samples| mix.copy_from_slice(samples)).unwrap();
+    /// assert_eq!(mix, [0.25, 0.5]);
+    /// assert_eq!(rx.try_recv_with(|_| ()), Err(mpsc::errors::TryRecvError::Empty));
+    /// ```
+    pub fn try_recv_with<U>(
+        &self,
+        f: impl FnOnce(&mut S::Message) -> U,
+    ) -> Result<U, TryRecvError> {
+        self.rx.try_recv_with(f)
+    }
+}
+
+impl<S> Receiver<S> {
+    /// Unwraps this `Receiver`, returning the underlying receiver, whose
+    /// waiting methods may be called again.
+    pub fn into_inner(self) -> S {
+        self.rx
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Receiver<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("rt::Receiver").field(&self.rx).finish()
+    }
+}
+
+// === impl RtSend / RtRecv ===
+
+macro_rules! impl_rt {
+    ($(#[$m:meta])* $Sender:ident, $Receiver:ident) => {
+        $(#[$m])*
+        impl<T, R: Recycle<T>> private::Sealed for $Sender<T, R> {}
+
+        $(#[$m])*
+        impl<T, R: Recycle<T>> RtSend for $Sender<T, R> {
+            type Message = T;
+
+            #[inline]
+            fn try_send(&self, val: T) -> Result<(), TrySendError<T>> {
+                let (core, slots, recycle) = self.parts();
+                core.try_send_untracked(slots, val, recycle)
+            }
+
+            #[inline]
+            fn try_send_with<F>(&self, f: F) -> Result<(), TrySendError>
+            where
+                F: FnOnce(&mut T),
+            {
+                let (core, slots, recycle) = self.parts();
+                core.try_send_ref_untracked(slots, recycle)
+                    .map(|mut slot| slot.with_mut(f))
+            }
+        }
+
+        $(#[$m])*
+        impl<T, R: Recycle<T>> private::Sealed for $Receiver<T, R> {}
+
+        $(#[$m])*
+        impl<T, R: Recycle<T>> RtRecv for $Receiver<T, R> {
+            type Message = T;
+
+            #[inline]
+            fn try_recv(&self) -> Result<T, TryRecvError> {
+                let (core, slots, recycle) = self.parts();
+                core.try_recv_untracked(slots, recycle)
+            }
+
+            #[inline]
+            fn try_recv_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Result<U, TryRecvError> {
+                let (core, slots, _) = self.parts();
+                core.try_recv_ref_untracked(slots).map(|mut msg| f(&mut msg))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "alloc")]
+use super::{Receiver as AsyncReceiver, Sender as AsyncSender};
+impl_rt!(
+    #[cfg(feature = "alloc")]
+    AsyncSender,
+    AsyncReceiver
+);
+
+#[cfg(feature = "static")]
+use super::{StaticReceiver as AsyncStaticReceiver, StaticSender as AsyncStaticSender};
+impl_rt!(
+    #[cfg(feature = "static")]
+    AsyncStaticSender,
+    AsyncStaticReceiver
+);
+
+#[cfg(feature = "std")]
+use super::blocking::{Receiver as BlockingReceiver, Sender as BlockingSender};
+impl_rt!(
+    #[cfg(feature = "std")]
+    BlockingSender,
+    BlockingReceiver
+);
+
+#[cfg(all(feature = "std", feature = "static"))]
+use super::blocking::{
+    StaticReceiver as BlockingStaticReceiver, StaticSender as BlockingStaticSender,
+};
+impl_rt!(
+    #[cfg(all(feature = "std", feature = "static"))]
+    BlockingStaticSender,
+    BlockingStaticReceiver
+);
@@ -0,0 +1,368 @@
+//! Sending serialized messages through channels of byte buffers.
+//!
+//! A channel of `Vec<u8>`s is a pool of byte buffers: each slot's buffer is
+//! [recycled] rather than freed once its message has been received. The
+//! methods in this module serialize a message *directly* into the buffer in a
+//! channel slot, and deserialize it directly from the buffer in the received
+//! slot, so that shuttling serialized messages between threads or tasks
+//! requires no intermediate allocations once the buffers have grown to fit
+//! the messages.
+//!
+//! Each slot holds exactly one message, so a received slot is a complete
+//! frame. Zero-copy formats, such as `rkyv`, can access the message in place
+//! through the [`RecvRef`] returned by `recv_ref`, which dereferences to the
+//! serialized bytes.
+//!
+//! This module does not depend on any particular serialization format.
+//! Instead, messages are encoded and decoded by a codec, which implements the
+//! [`Encode`] and [`Decode`] traits. These traits are implemented for
+//! closures, so a format such as `bincode` or `postcard` can be plugged in
+//! with a closure that calls it, or by implementing the traits for a
+//! reusable codec type.
+//!
+//! The following methods are provided:
+//!
+//! | Type | Methods |
+//! |------|---------|
+//! | [`mpsc::Sender<Vec<u8>>`] | `send_serialized`, `try_send_serialized` |
+//! | [`mpsc::Receiver<Vec<u8>>`] | `recv_deserialized` |
+//! | [`blocking::Sender<Vec<u8>>`] | `send_serialized`, `try_send_serialized` |
+//! | [`blocking::Receiver<Vec<u8>>`] | `recv_deserialized` |
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::mpsc::{blocking, serbuf};
+//!
+//! // A codec for `(u32, String)` messages. A real application would call a
+//! // serialization library here instead.
+//! fn encode(msg: &(u32, &str), buf: &mut Vec<u8>) -> Result<(), serbuf::Never> {
+//!     buf.extend_from_slice(&msg.0.to_le_bytes());
+//!     buf.extend_from_slice(msg.1.as_bytes());
+//!     Ok(())
+//! }
+//!
+//! fn decode(buf: &[u8]) -> Result<(u32, String), std::str::Utf8Error> {
+//!     let (id, name) = buf.split_at(4);
+//!     let id = u32::from_le_bytes([id[0], id[1], id[2], id[3]]);
+//!     Ok((id, std::str::from_utf8(name)?.to_owned()))
+//! }
+//!
+//! let (tx, rx) = blocking::channel::<Vec<u8>>(8);
+//! tx.send_serialized(&(1, "hello"), encode).unwrap();
+//! tx.send_serialized(&(2, "world"), encode).unwrap();
+//!
+//! assert_eq!(rx.recv_deserialized(decode), Some(Ok((1, "hello".to_owned()))));
+//!
+//! // The serialized frame can also be borrowed in place.
+//! let frame = rx.recv_ref().unwrap();
+//! assert_eq!(frame.len(), 4 + "world".len());
+//! ```
+//!
+//! [recycled]: crate::recycling
+//! [`RecvRef`]: super::RecvRef
+//! [`mpsc::Sender<Vec<u8>>`]: super::Sender
+//! [`mpsc::Receiver<Vec<u8>>`]: super::Receiver
+//! [`blocking::Sender<Vec<u8>>`]: super::blocking::Sender
+//! [`blocking::Receiver<Vec<u8>>`]: super::blocking::Receiver
+use super::errors::{Closed, TrySendError};
+use crate::recycling::Recycle;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Serializes messages of type `M` into byte buffers.
+///
+/// This trait is implemented for any `Fn(&M, &mut Vec<u8>) -> Result<(), E>`
+/// closure.
+pub trait Encode<M: ?Sized> {
+    /// The error returned when a message cannot be serialized.
+    type Error;
+
+    /// Serializes `msg`, appending it to `buf`.
+    ///
+    /// `buf` is always empty when this is called, but it may have capacity
+    /// left over from a previous message.
+    fn encode(&self, msg: &M, buf: &mut Vec<u8>) -> Result<(), Self::Error>;
+}
+
+/// Deserializes messages of type `M` from byte buffers.
+///
+/// This trait is implemented for any `Fn(&[u8]) -> Result<M, E>` closure.
+pub trait Decode<M> {
+    /// The error returned when a message cannot be deserialized.
+    type Error;
+
+    /// Deserializes a message from `buf`, which holds exactly one serialized
+    /// message.
+    fn decode(&self, buf: &[u8]) -> Result<M, Self::Error>;
+}
+
+/// An error type for codecs that never fail.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Never {}
+
+/// Error returned by the `send_serialized` and `try_send_serialized` methods.
+#[non_exhaustive]
+#[derive(PartialEq, Eq)]
+pub enum SendError<E> {
+    /// The message could not be sent because the [`Receiver`] half of the
+    /// channel has been dropped.
+    ///
+    /// [`Receiver`]: super::Receiver
+    Closed,
+    /// The message could not be sent because the channel is currently full.
+    ///
+    /// This is only returned by `try_send_serialized`.
+    Full,
+    /// The message could not be serialized. Nothing was sent.
+    Encode(E),
+}
+
+// === impl Encode ===
+
+impl<M, E, F> Encode<M> for F
+where
+    M: ?Sized,
+    F: Fn(&M, &mut Vec<u8>) -> Result<(), E>,
+{
+    type Error = E;
+
+    #[inline]
+    fn encode(&self, msg: &M, buf: &mut Vec<u8>) -> Result<(), Self::Error> {
+        self(msg, buf)
+    }
+}
+
+// === impl Decode ===
+
+impl<M, E, F> Decode<M> for F
+where
+    F: Fn(&[u8]) -> Result<M, E>,
+{
+    type Error = E;
+
+    #[inline]
+    fn decode(&self, buf: &[u8]) -> Result<M, Self::Error> {
+        self(buf)
+    }
+}
+
+// === impl Never ===
+
+impl fmt::Display for Never {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Never {}
+
+// === impl SendError ===
+
+impl<E> SendError<E> {
+    /// Returns `true` if this error was returned because the channel was
+    /// closed.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, Self::Closed)
+    }
+
+    /// Returns `true` if this error was returned because the channel was
+    /// full.
+    pub fn is_full(&self) -> bool {
+        matches!(self, Self::Full)
+    }
+}
+
+impl<E> From<Closed> for SendError<E> {
+    fn from(_: Closed) -> Self {
+        Self::Closed
+    }
+}
+
+impl<E> From<TrySendError> for SendError<E> {
+    fn from(err: TrySendError) -> Self {
+        if err.is_full() {
+            Self::Full
+        } else {
+            Self::Closed
+        }
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for SendError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Closed => f.write_str("Closed"),
+            Self::Full => f.write_str("Full"),
+            Self::Encode(err) => f.debug_tuple("Encode").field(err).finish(),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for SendError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Closed => f.write_str("the receiving half of the channel was dropped"),
+            Self::Full => f.write_str("no available capacity"),
+            Self::Encode(err) => write!(f, "the message could not be serialized: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for SendError<E> {}
+
+/// Serializes `msg` into the claimed `slot`, giving up the slot if the
+/// message cannot be serialized.
+macro_rules! encode_into {
+    ($slot:expr, $msg:expr, $codec:expr) => {{
+        let mut slot = $slot;
+        slot.clear();
+        match $codec.encode($msg, &mut slot) {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                slot.abandon();
+                Err(SendError::Encode(err))
+            }
+        }
+    }};
+}
+
+// === impl mpsc::Sender ===
+
+impl<R: Recycle<Vec<u8>>> super::Sender<Vec<u8>, R> {
+    /// Serializes `msg` with `codec` directly into a slot in the channel,
+    /// waiting for capacity if the channel is full.
+    ///
+    /// The message is encoded into the slot's recycled buffer, so that no
+    /// intermediate buffer is allocated. If `codec` fails, nothing is sent,
+    /// and the slot is given up.
+    ///
+    /// See the [module-level documentation](self) for details.
+    ///
+    /// # Errors
+    ///
+    /// - [`SendError::Closed`] if the [`Receiver`](super::Receiver) has been
+    ///   dropped.
+    /// - [`SendError::Encode`] if `codec` failed to serialize the message.
+    pub async fn send_serialized<M, C>(&self, msg: &M, codec: C) -> Result<(), SendError<C::Error>>
+    where
+        M: ?Sized,
+        C: Encode<M>,
+    {
+        encode_into!(self.send_ref().await?, msg, codec)
+    }
+
+    /// Serializes `msg` with `codec` directly into a slot in the channel,
+    /// without waiting for capacity.
+    ///
+    /// This is the non-waiting version of
+    /// [`send_serialized`](Self::send_serialized).
+    ///
+    /// # Errors
+    ///
+    /// - [`SendError::Full`] if the channel is full.
+    /// - [`SendError::Closed`] if the [`Receiver`](super::Receiver) has been
+    ///   dropped.
+    /// - [`SendError::Encode`] if `codec` failed to serialize the message.
+    pub fn try_send_serialized<M, C>(&self, msg: &M, codec: C) -> Result<(), SendError<C::Error>>
+    where
+        M: ?Sized,
+        C: Encode<M>,
+    {
+        encode_into!(self.try_send_ref()?, msg, codec)
+    }
+}
+
+// === impl mpsc::Receiver ===
+
+impl<R> super::Receiver<Vec<u8>, R> {
+    /// Receives the next message, deserializing it with `codec` directly
+    /// from the buffer in its slot.
+    ///
+    /// Returns `None` if the channel has been closed and all messages have
+    /// been received, or `Some` with the result of deserializing the
+    /// message. The message is received even if it cannot be deserialized.
+    pub async fn recv_deserialized<M, C>(&self, codec: C) -> Option<Result<M, C::Error>>
+    where
+        C: Decode<M>,
+    {
+        let buf = self.recv_ref().await?;
+        Some(codec.decode(&buf))
+    }
+}
+
+feature! {
+    #![feature = "std"]
+
+    // === impl blocking::Sender ===
+
+    impl<R: Recycle<Vec<u8>>> super::blocking::Sender<Vec<u8>, R> {
+        /// Serializes `msg` with `codec` directly into a slot in the channel,
+        /// blocking until capacity is available if the channel is full.
+        ///
+        /// The message is encoded into the slot's recycled buffer, so that no
+        /// intermediate buffer is allocated. If `codec` fails, nothing is
+        /// sent, and the slot is given up.
+        ///
+        /// See the [module-level documentation](self) for details.
+        ///
+        /// # Errors
+        ///
+        /// - [`SendError::Closed`] if the
+        ///   [`Receiver`](super::blocking::Receiver) has been dropped.
+        /// - [`SendError::Encode`] if `codec` failed to serialize the message.
+        pub fn send_serialized<M, C>(&self, msg: &M, codec: C) -> Result<(), SendError<C::Error>>
+        where
+            M: ?Sized,
+            C: Encode<M>,
+        {
+            encode_into!(self.send_ref()?, msg, codec)
+        }
+
+        /// Serializes `msg` with `codec` directly into a slot in the channel,
+        /// without blocking until capacity is available.
+        ///
+        /// This is the non-blocking version of
+        /// [`send_serialized`](Self::send_serialized).
+        ///
+        /// # Errors
+        ///
+        /// - [`SendError::Full`] if the channel is full.
+        /// - [`SendError::Closed`] if the
+        ///   [`Receiver`](super::blocking::Receiver) has been dropped.
+        /// - [`SendError::Encode`] if `codec` failed to serialize the message.
+        pub fn try_send_serialized<M, C>(
+            &self,
+            msg: &M,
+            codec: C,
+        ) -> Result<(), SendError<C::Error>>
+        where
+            M: ?Sized,
+            C: Encode<M>,
+        {
+            encode_into!(self.try_send_ref()?, msg, codec)
+        }
+    }
+
+    // === impl blocking::Receiver ===
+
+    impl<R> super::blocking::Receiver<Vec<u8>, R> {
+        /// Receives the next message, deserializing it with `codec` directly
+        /// from the buffer in its slot, and blocking until a message is
+        /// available.
+        ///
+        /// Returns `None` if the channel has been closed and all messages
+        /// have been received, or `Some` with the result of deserializing the
+        /// message. The message is received even if it cannot be
+        /// deserialized.
+        pub fn recv_deserialized<M, C>(&self, codec: C) -> Option<Result<M, C::Error>>
+        where
+            C: Decode<M>,
+        {
+            let buf = self.recv_ref()?;
+            Some(codec.decode(&buf))
+        }
+    }
+}
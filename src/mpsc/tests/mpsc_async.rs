@@ -496,3 +496,75 @@ fn tx_close_drains_queue() {
         producer.join().unwrap();
     });
 }
+
+#[test]
+fn mailbox_spsc_send_recv() {
+    loom::model(|| {
+        let (tx, rx) = mailbox::<i32>();
+        let producer = thread::spawn(move || {
+            future::block_on(async move {
+                tx.send(1).await.unwrap();
+                tx.send(2).await.unwrap();
+            })
+        });
+
+        future::block_on(async move {
+            assert_eq_dbg!(rx.recv().await, Some(1));
+            assert_eq_dbg!(rx.recv().await, Some(2));
+            assert_eq_dbg!(rx.recv().await, None);
+        });
+
+        producer.join().unwrap();
+    })
+}
+
+#[test]
+fn mailbox_mpsc_try_send() {
+    loom::model(|| {
+        let (tx, rx) = mailbox::<Track<i32>>();
+        let tx2 = tx.clone();
+        let producer = thread::spawn(move || {
+            let _ = tx2.try_send(Track::new(1));
+        });
+
+        let sent = tx.try_send(Track::new(2)).is_ok();
+        producer.join().unwrap();
+        drop(tx);
+
+        future::block_on(async move {
+            let mut received = 0;
+            while let Some(msg) = rx.recv().await {
+                assert!(*msg.get_ref() == 1 || *msg.get_ref() == 2);
+                received += 1;
+            }
+            // Exactly one of the two messages fits in the mailbox.
+            assert_eq_dbg!(received, 1);
+            if sent {
+                test_println!("received message from main thread");
+            }
+        });
+    })
+}
+
+#[test]
+fn inline_channel_spsc_send_recv_wrap() {
+    loom::model(|| {
+        let (tx, rx) = Channel::<usize, 2>::new().split();
+        let producer = thread::spawn(move || {
+            future::block_on(async move {
+                for i in 0..3 {
+                    tx.send(i).await.unwrap();
+                }
+            })
+        });
+
+        future::block_on(async move {
+            for i in 0..3 {
+                assert_eq_dbg!(rx.recv().await, Some(i));
+            }
+            assert_eq_dbg!(rx.recv().await, None);
+        });
+
+        producer.join().unwrap();
+    })
+}
@@ -340,6 +340,62 @@ fn mpsc_send_recv_no_wrap() {
     })
 }
 
+#[test]
+fn mpsc_recv_into_batched_notify() {
+    loom::model(|| {
+        let (tx, rx) = blocking::channel::<usize>(1);
+        rx.set_notify_policy(crate::mpsc::NotifyPolicy::Batched);
+        let producer1 = do_producer(tx.clone(), 10);
+        let producer2 = do_producer(tx, 20);
+
+        let mut results = Vec::new();
+        let mut buf = [0; 2];
+        loop {
+            let n = rx.recv_into(&mut buf);
+            if n == 0 {
+                break;
+            }
+            test_println!("RECEIVED {:?}", &buf[..n]);
+            results.extend_from_slice(&buf[..n]);
+        }
+
+        producer1.join().expect("producer 1 panicked");
+        producer2.join().expect("producer 2 panicked");
+
+        results.sort_unstable();
+        assert_eq_dbg!(results, vec![10, 20]);
+    })
+}
+
+#[test]
+fn send_or_modify_races_recv() {
+    loom::model(|| {
+        let (tx, rx) = blocking::channel::<usize>(2);
+        let producer = thread::spawn(move || {
+            let mut sent = 0;
+            for delta in 1..=3 {
+                match tx.send_or_modify(delta, |queued, delta| *queued += delta) {
+                    Ok(()) => sent += delta,
+                    Err(errors::TrySendError::Full(_)) => {}
+                    Err(e) => panic!("unexpected error: {:?}", e),
+                }
+            }
+            sent
+        });
+
+        let mut received = 0;
+        while let Some(val) = rx.recv() {
+            test_println!("RECEIVED {:?}", val);
+            received += val;
+        }
+
+        // Every delta that was sent is received exactly once, whether or not
+        // it was merged into another message.
+        let sent = producer.join().expect("producer panicked");
+        assert_eq_dbg!(received, sent);
+    })
+}
+
 fn do_producer(tx: blocking::Sender<usize>, tag: usize) -> thread::JoinHandle<()> {
     thread::spawn(move || {
         test_println!("SENDING {:?}", tag);
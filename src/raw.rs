@@ -0,0 +1,240 @@
+//! Low-level building blocks for the lock-free ring buffer.
+//!
+//! This module exposes the [`Core`] state machine and the [`Slot`] storage
+//! type that [`ThingBuf`], [`StaticThingBuf`], and the [`mpsc`] channels are
+//! built on. It is intended for advanced users who wish to build their own
+//! concurrent data structures (such as priority rings or sharded queues) on
+//! top of the same primitives. Most users should use the higher-level types
+//! instead.
+//!
+//! # The slot state machine
+//!
+//! A [`Core`] tracks a `head` (the next position to read from) and a `tail`
+//! (the next position to write to). Each position is made up of an index into
+//! the slot array and a *generation*, which is incremented every time the index
+//! wraps around the end of the array. Every [`Slot`] has a `state` word, which
+//! determines which operations may currently claim it:
+//!
+//! - A slot may be claimed for **writing** when its state is equal to the
+//!   current tail position. [`Core::push_ref`] advances the tail and returns a
+//!   [`Ref`] granting exclusive access to the slot. When that `Ref` is dropped,
+//!   the write is *released*, and the slot's state becomes `tail + 1`.
+//! - A slot may be claimed for **reading** when its state is equal to the
+//!   current head position plus one. [`Core::pop_ref`] advances the head and
+//!   marks the slot as having an active reader. When that `Ref` is dropped,
+//!   the read is released, and the slot may be written to again once the tail
+//!   comes back around to it.
+//!
+//! A slot that is still being read when a writer reaches it is skipped, rather
+//! than blocking the writer. Elements are never moved out of the slots: the
+//! first write to a slot constructs a new element with
+//! [`Recycle::new_element`], and later writes reuse it with
+//! [`Recycle::recycle`].
+//!
+//! # Safety
+//!
+//! A [`Core`] does not own its slots. Instead, the slot array is passed to
+//! each operation, which is what makes it possible to use the same algorithm
+//! with both heap-allocated and `static` storage. This means that the caller
+//! is responsible for ensuring that:
+//!
+//! - every call on a given `Core` is passed the *same* slot array,
+//! - the slot array's length is equal to the `Core`'s [capacity], and
+//! - the slot array was created by [`Slot::make_boxed_array`] or
+//!   [`Slot::make_static_array`], and has not previously been used with any
+//!   other `Core`.
+//!
+//! Violating these requirements may result in reads of uninitialized memory
+//! or double-frees, so the operations that touch slots are `unsafe fn`s.
+//!
+//! [`ThingBuf`]: crate::ThingBuf
+//! [`StaticThingBuf`]: crate::StaticThingBuf
+//! [`mpsc`]: crate::mpsc
+//! [`Recycle::new_element`]: crate::Recycle::new_element
+//! [`Recycle::recycle`]: crate::Recycle::recycle
+//! [capacity]: Core::capacity
+use crate::{
+    mpsc::errors::{TryRecvError, TrySendError},
+    recycling::Recycle,
+    Ref,
+};
+use core::fmt;
+
+pub use crate::Slot;
+
+/// The state variables for a lock-free ring buffer.
+///
+/// A `Core`, when provided with the slot array it manages, knows how to
+/// perform ring buffer operations on that array. See the [module-level
+/// documentation](self) for details on the state machine it implements, and
+/// on the requirements its `unsafe` methods place on callers.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::{raw::{Core, Slot}, recycling::DefaultRecycle};
+///
+/// let mut core = Core::new(4);
+/// let mut slots = Slot::<usize>::make_boxed_array(core.capacity());
+/// let recycle = DefaultRecycle::new();
+///
+/// unsafe {
+///     // Safety: `slots` is only ever used with `core`, and has the same
+///     // length as `core`'s capacity.
+///     *core.push_ref(&slots, &recycle).unwrap() = 1;
+///     *core.push_ref(&slots, &recycle).unwrap() = 2;
+///
+///     assert_eq!(*core.pop_ref(&slots).unwrap(), 1);
+///     assert_eq!(*core.pop_ref(&slots).unwrap(), 2);
+///
+///     // The slots must be dropped before the `Core` is.
+///     core.drop_slots(&mut slots);
+/// }
+/// ```
+pub struct Core {
+    inner: crate::Core,
+}
+
+impl Core {
+    loom_const_fn! {
+        /// Returns a new `Core` for a ring buffer with space for `capacity`
+        /// elements.
+        ///
+        /// # Panics
+        ///
+        /// If `capacity` is greater than [`MAX_CAPACITY`](crate::MAX_CAPACITY).
+        #[must_use]
+        pub fn new(capacity: usize) -> Self {
+            Self {
+                inner: crate::Core::new(capacity),
+            }
+        }
+    }
+
+    /// Returns the total capacity of the ring buffer.
+    ///
+    /// The slot array used with this `Core` must have exactly this many slots.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns the number of elements currently in the ring buffer.
+    ///
+    /// This may be stale by the time it is observed if other threads are
+    /// concurrently pushing or popping.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if there are currently no elements in the ring buffer.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Closes the ring buffer, so that subsequent [`push_ref`] calls fail
+    /// with [`TrySendError::Closed`], and [`pop_ref`] calls fail with
+    /// [`TryRecvError::Closed`] once all remaining elements have been popped.
+    ///
+    /// Returns `true` if this call closed the ring buffer, or `false` if it
+    /// was already closed.
+    ///
+    /// [`push_ref`]: Self::push_ref
+    /// [`pop_ref`]: Self::pop_ref
+    #[inline]
+    pub fn close(&self) -> bool {
+        self.inner.close()
+    }
+
+    /// Claims the next slot for writing, returning a [`Ref`] that grants
+    /// exclusive access to it.
+    ///
+    /// If the slot has never been written to, a new element is created with
+    /// [`Recycle::new_element`]; otherwise, the existing element is prepared
+    /// for reuse with [`Recycle::recycle`]. The write is released when the
+    /// returned `Ref` is dropped.
+    ///
+    /// # Errors
+    ///
+    /// - [`TrySendError::Full`] if there are no free slots.
+    /// - [`TrySendError::Closed`] if [`close`](Self::close) has been called.
+    ///
+    /// # Safety
+    ///
+    /// `slots` must satisfy the requirements described in the [module-level
+    /// documentation](self#safety).
+    #[inline]
+    pub unsafe fn push_ref<'slots, T, R>(
+        &self,
+        slots: &'slots [Slot<T>],
+        recycle: &R,
+    ) -> Result<Ref<'slots, T>, TrySendError<()>>
+    where
+        R: Recycle<T>,
+    {
+        self.inner.push_ref(slots, recycle)
+    }
+
+    /// Claims the next slot for reading, returning a [`Ref`] that grants
+    /// exclusive access to it.
+    ///
+    /// The read is released when the returned `Ref` is dropped.
+    ///
+    /// # Errors
+    ///
+    /// - [`TryRecvError::Empty`] if there are no elements to read.
+    /// - [`TryRecvError::Closed`] if there are no elements to read and
+    ///   [`close`](Self::close) has been called.
+    ///
+    /// # Safety
+    ///
+    /// `slots` must satisfy the requirements described in the [module-level
+    /// documentation](self#safety).
+    #[inline]
+    pub unsafe fn pop_ref<'slots, T>(
+        &self,
+        slots: &'slots [Slot<T>],
+    ) -> Result<Ref<'slots, T>, TryRecvError> {
+        self.inner.pop_ref(slots)
+    }
+
+    /// Retains only the elements for which `f` returns `true`, preserving
+    /// their order.
+    ///
+    /// # Safety
+    ///
+    /// `slots` must satisfy the requirements described in the [module-level
+    /// documentation](self#safety).
+    pub unsafe fn retain<T>(&mut self, slots: &mut [Slot<T>], f: impl FnMut(&mut T) -> bool) {
+        self.inner.retain(slots, f)
+    }
+
+    /// Drops every initialized element in `slots`.
+    ///
+    /// This must be called exactly once, before the `Core` is dropped. In
+    /// debug builds, dropping a `Core` without calling this method will
+    /// panic.
+    ///
+    /// # Safety
+    ///
+    /// `slots` must satisfy the requirements described in the [module-level
+    /// documentation](self#safety). After this method is called, `slots` must
+    /// not be used again.
+    pub unsafe fn drop_slots<T>(&mut self, slots: &mut [Slot<T>]) {
+        self.inner.drop_slots(slots)
+    }
+}
+
+impl fmt::Debug for Core {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Core")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .finish()
+    }
+}
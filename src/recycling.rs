@@ -1,4 +1,5 @@
 //! Configurable policies for element reuse.
+use crate::CachePadded;
 
 /// A policy defining how pooled elements of type `T` are reused.
 ///
@@ -34,6 +35,22 @@ pub trait Recycle<T> {
     /// This method is called when a `T` value is returned to the pool that owns
     /// it.
     fn recycle(&self, element: &mut T);
+
+    /// Prepares `element` for reuse, as [`recycle`](Self::recycle) does, and
+    /// returns the amount of capacity that was released by shrinking it.
+    ///
+    /// When the `stats` feature is enabled, queues and channels call this
+    /// method rather than `recycle`, so that the capacity shed by a policy
+    /// such as [`WithCapacity`]'s [maximum capacity] is [recorded]. The
+    /// default implementation calls `recycle`, and returns 0.
+    ///
+    /// [maximum capacity]: WithCapacity::with_max_capacity
+    /// [recorded]: crate::stats::RecyclingStats::shed_capacity
+    #[inline]
+    fn recycle_shrunk(&self, element: &mut T) -> usize {
+        self.recycle(element);
+        0
+    }
 }
 
 /// A [`Recycle`] implementation for any type implementing [`Default`] and
@@ -220,6 +237,26 @@ where
     }
 }
 
+impl<T, R> Recycle<CachePadded<T>> for CachePadded<R>
+where
+    R: Recycle<T>,
+{
+    #[inline]
+    fn new_element(&self) -> CachePadded<T> {
+        CachePadded::new(self.0.new_element())
+    }
+
+    #[inline]
+    fn recycle(&self, element: &mut CachePadded<T>) {
+        self.0.recycle(&mut element.0)
+    }
+
+    #[inline]
+    fn recycle_shrunk(&self, element: &mut CachePadded<T>) -> usize {
+        self.0.recycle_shrunk(&mut element.0)
+    }
+}
+
 // === impl WithCapacity ===
 
 impl WithCapacity {
@@ -234,7 +271,7 @@ impl WithCapacity {
     /// initial capacity, use [`WithCapacity::with_min_capacity`].
     pub const fn new() -> Self {
         Self {
-            max: core::usize::MAX,
+            max: usize::MAX,
             min: 0,
         }
     }
@@ -407,6 +444,11 @@ feature! {
         fn recycle(&self, element: &mut T) {
             (**self).recycle(element)
         }
+
+        #[inline]
+        fn recycle_shrunk(&self, element: &mut T) -> usize {
+            (**self).recycle_shrunk(element)
+        }
     }
 
     impl<T> Recycle<Vec<T>> for WithCapacity {
@@ -418,6 +460,12 @@ feature! {
             element.clear();
             element.shrink_to(self.max);
         }
+
+        fn recycle_shrunk(&self, element: &mut Vec<T>) -> usize {
+            let capacity = element.capacity();
+            self.recycle(element);
+            capacity.saturating_sub(element.capacity())
+        }
     }
 
     impl Recycle<String> for WithCapacity {
@@ -429,6 +477,12 @@ feature! {
             element.clear();
             element.shrink_to(self.max);
         }
+
+        fn recycle_shrunk(&self, element: &mut String) -> usize {
+            let capacity = element.capacity();
+            self.recycle(element);
+            capacity.saturating_sub(element.capacity())
+        }
     }
 
     impl<T> Recycle<VecDeque<T>> for WithCapacity {
@@ -440,6 +494,12 @@ feature! {
             element.clear();
             element.shrink_to(self.max);
         }
+
+        fn recycle_shrunk(&self, element: &mut VecDeque<T>) -> usize {
+            let capacity = element.capacity();
+            self.recycle(element);
+            capacity.saturating_sub(element.capacity())
+        }
     }
 
     impl<T: core::cmp::Ord> Recycle<BinaryHeap<T>> for WithCapacity {
@@ -451,6 +511,12 @@ feature! {
             element.clear();
             element.shrink_to(self.max);
         }
+
+        fn recycle_shrunk(&self, element: &mut BinaryHeap<T>) -> usize {
+            let capacity = element.capacity();
+            self.recycle(element);
+            capacity.saturating_sub(element.capacity())
+        }
     }
 }
 
@@ -471,6 +537,12 @@ feature! {
             element.clear();
             element.shrink_to(self.max);
         }
+
+        fn recycle_shrunk(&self, element: &mut HashMap<K, V, S>) -> usize {
+            let capacity = element.capacity();
+            self.recycle(element);
+            capacity.saturating_sub(element.capacity())
+        }
     }
 
     impl<K, S> Recycle<HashSet<K, S>> for WithCapacity
@@ -486,5 +558,70 @@ feature! {
             element.clear();
             element.shrink_to(self.max);
         }
+
+        fn recycle_shrunk(&self, element: &mut HashSet<K, S>) -> usize {
+            let capacity = element.capacity();
+            self.recycle(element);
+            capacity.saturating_sub(element.capacity())
+        }
+    }
+}
+
+feature! {
+    #![feature = "bytes"]
+    use bytes::BytesMut;
+
+    /// Recycles [`BytesMut`] buffers by clearing them in place.
+    ///
+    /// A `BytesMut` that was split or frozen while in use, for example to send
+    /// its contents elsewhere as a `Bytes`, is left with only the capacity
+    /// after the split point. If it has less than the [minimum capacity]
+    /// when it is recycled, it reserves that much capacity again, which
+    /// reclaims the original allocation without copying once every handle to
+    /// the split-off data has been dropped.
+    ///
+    /// A buffer with more than the [maximum capacity] is replaced with a new
+    /// buffer of that capacity, since `BytesMut` cannot shrink in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::{BufMut, BytesMut};
+    /// use thingbuf::recycling::{Recycle, WithCapacity};
+    ///
+    /// let recycle = WithCapacity::new().with_min_capacity(64);
+    /// let mut buf: BytesMut = recycle.new_element();
+    /// buf.put_slice(b"hello");
+    ///
+    /// // Hand off the data, and drop it once it has been processed.
+    /// let frame = buf.split().freeze();
+    /// drop(frame);
+    ///
+    /// recycle.recycle(&mut buf);
+    /// assert!(buf.is_empty());
+    /// assert!(buf.capacity() >= 64);
+    /// ```
+    ///
+    /// [minimum capacity]: WithCapacity::with_min_capacity
+    /// [maximum capacity]: WithCapacity::with_max_capacity
+    impl Recycle<BytesMut> for WithCapacity {
+        fn new_element(&self) -> BytesMut {
+            BytesMut::with_capacity(self.min)
+        }
+
+        fn recycle(&self, element: &mut BytesMut) {
+            element.clear();
+            if element.capacity() > self.max {
+                *element = BytesMut::with_capacity(self.max);
+            } else {
+                element.reserve(self.min);
+            }
+        }
+
+        fn recycle_shrunk(&self, element: &mut BytesMut) -> usize {
+            let capacity = element.capacity();
+            self.recycle(element);
+            capacity.saturating_sub(element.capacity())
+        }
     }
 }
@@ -1,6 +1,6 @@
 use crate::{
     recycling::{self, Recycle},
-    Core, Full, Ref, Slot,
+    Core, Frozen, Full, Ref, Slot,
 };
 use core::fmt;
 
@@ -198,9 +198,18 @@ pub struct StaticThingBuf<T, const CAP: usize, R = recycling::DefaultRecycle> {
     slots: [Slot<T>; CAP],
 }
 
+/// An owning iterator over the elements remaining in a [`StaticThingBuf`].
+///
+/// This type is returned by the [`IntoIterator`] implementation for
+/// [`StaticThingBuf`]. Elements are yielded in first-in, first-out order,
+/// exactly as they would be returned by [`StaticThingBuf::pop`].
+#[cfg_attr(docsrs, doc(cfg(feature = "static")))]
+pub struct StaticIntoIter<T, const CAP: usize, R = recycling::DefaultRecycle> {
+    buf: StaticThingBuf<T, CAP, R>,
+}
+
 // === impl ThingBuf ===
 
-#[cfg(not(test))]
 impl<T, const CAP: usize> StaticThingBuf<T, CAP> {
     /// Returns a new `StaticThingBuf` with space for `CAP` elements.
     ///
@@ -208,6 +217,7 @@ impl<T, const CAP: usize> StaticThingBuf<T, CAP> {
     ///
     /// [recycling policy]: crate::recycling::DefaultRecycle
     #[must_use]
+    #[allow(clippy::new_without_default)]
     pub const fn new() -> Self {
         Self::with_recycle(recycling::DefaultRecycle::new())
     }
@@ -322,6 +332,65 @@ impl<T, const CAP: usize, R> StaticThingBuf<T, CAP, R> {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Returns a snapshot of the statistics recorded for this `StaticThingBuf`.
+    ///
+    /// See the [`stats`](crate::stats) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use thingbuf::StaticThingBuf;
+    /// static MY_THINGBUF: StaticThingBuf::<usize, 100> = StaticThingBuf::new();
+    ///
+    /// MY_THINGBUF.push(1).unwrap();
+    /// assert_eq!(MY_THINGBUF.pop(), Some(1));
+    ///
+    /// // with a capacity of 100, both samples fall in the lowest bucket.
+    /// let occupancy = MY_THINGBUF.stats().occupancy().clone();
+    /// assert_eq!(occupancy.samples(), 2);
+    /// assert_eq!(occupancy.counts()[0], 2);
+    /// ```
+    #[cfg(feature = "stats")]
+    #[must_use]
+    pub fn stats(&self) -> crate::stats::Stats {
+        self.core.stats()
+    }
+
+    /// Returns a read-only [`Frozen`] view of the elements in the queue, which
+    /// may be iterated from a panic hook or other fatal-error path.
+    ///
+    /// Iterating over the view copies each element out of the queue without
+    /// waiting for other threads or allocating. The element at the head of
+    /// the queue is skipped. See [`Frozen`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::StaticThingBuf;
+    ///
+    /// static EVENTS: StaticThingBuf<u32, 8> = StaticThingBuf::new();
+    ///
+    /// fn dump_events() {
+    ///     // Only the most recent events are printed.
+    ///     for event in EVENTS.freeze().newest(4) {
+    ///         eprintln!("event: {}", event);
+    ///     }
+    /// }
+    ///
+    /// for event in 0..6 {
+    ///     EVENTS.push(event).unwrap();
+    /// }
+    /// # dump_events();
+    /// // The oldest event, at the head of the queue, is skipped.
+    /// assert_eq!(EVENTS.freeze().count(), 5);
+    /// ```
+    pub fn freeze(&self) -> Frozen<'_, T>
+    where
+        T: Copy,
+    {
+        Frozen::new(&self.core, &self.slots)
+    }
 }
 
 impl<T, const CAP: usize, R> StaticThingBuf<T, CAP, R>
@@ -510,6 +579,62 @@ where
     pub fn pop_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Option<U> {
         self.pop_ref().map(|mut r| r.with_mut(f))
     }
+
+    /// Retains only the elements for which the predicate `f` returns `true`.
+    ///
+    /// Every element in the queue is visited exactly once, in first-in,
+    /// first-out order, and the retained elements keep their relative order.
+    /// Elements for which `f` returns `false` are removed from the queue; their
+    /// slots will be [recycled] by a subsequent [`push_ref`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::StaticThingBuf;
+    ///
+    /// let mut q = StaticThingBuf::<_, 4>::new();
+    /// q.push("keep").unwrap();
+    /// q.push("stale").unwrap();
+    /// q.push("keep too").unwrap();
+    ///
+    /// q.retain(|s| *s != "stale");
+    ///
+    /// assert_eq!(q.pop(), Some("keep"));
+    /// assert_eq!(q.pop(), Some("keep too"));
+    /// assert_eq!(q.pop(), None);
+    /// ```
+    ///
+    /// [recycled]: crate::recycling::Recycle
+    /// [`push_ref`]: Self::push_ref
+    pub fn retain(&mut self, f: impl FnMut(&mut T) -> bool) {
+        self.core.retain(&mut self.slots[..], f)
+    }
+}
+
+impl<T, const CAP: usize, R> IntoIterator for StaticThingBuf<T, CAP, R>
+where
+    R: Recycle<T>,
+{
+    type Item = T;
+    type IntoIter = StaticIntoIter<T, CAP, R>;
+
+    /// Consumes the `StaticThingBuf`, returning an iterator over the elements
+    /// that were still enqueued in it, in first-in, first-out order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::StaticThingBuf;
+    ///
+    /// let q = StaticThingBuf::<_, 4>::new();
+    /// q.push("hello").unwrap();
+    /// q.push("world").unwrap();
+    ///
+    /// assert_eq!(q.into_iter().collect::<Vec<_>>(), vec!["hello", "world"]);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        StaticIntoIter { buf: self }
+    }
 }
 
 impl<T, const CAP: usize, R: fmt::Debug> fmt::Debug for StaticThingBuf<T, CAP, R> {
@@ -528,3 +653,28 @@ impl<T, const CAP: usize, R> Drop for StaticThingBuf<T, CAP, R> {
         self.core.drop_slots(&mut self.slots[..]);
     }
 }
+
+// === impl StaticIntoIter ===
+
+impl<T, const CAP: usize, R> Iterator for StaticIntoIter<T, CAP, R>
+where
+    R: Recycle<T>,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buf.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.buf.len()))
+    }
+}
+
+impl<T, const CAP: usize, R: fmt::Debug> fmt::Debug for StaticIntoIter<T, CAP, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StaticIntoIter").field(&self.buf).finish()
+    }
+}
@@ -0,0 +1,595 @@
+//! Statistics about queue and channel usage.
+//!
+//! When the `stats` feature is enabled, every [`ThingBuf`], [`StaticThingBuf`]
+//! and [`mpsc`] channel samples its depth (the number of elements it contains)
+//! each time an element is pushed or popped. The distribution of those
+//! samples can be retrieved with the `stats()` method on the queue or
+//! channel receiver. Unlike the instantaneous [`len`], this shows how full the
+//! queue *tends* to be, which is useful when choosing its capacity.
+//!
+//! When the `timestamps` feature is also enabled, each element is stamped
+//! with the time at which it was pushed, and the time it spent in the queue
+//! (its *latency*) is recorded when it is popped. The distribution of
+//! latencies can be retrieved with [`Stats::latency`], from either a
+//! channel's sender or its receiver, so that a regression in a pipeline's
+//! latency is visible without external tracing.
+//!
+//! Every push that claims a slot either creates a new element, the first
+//! time that slot is used, or [recycles] the element already in it. The
+//! number of each, and the capacity released by recycling policies that
+//! shrink elements, can be retrieved with [`Stats::recycling`], to check that
+//! allocations are actually being reused.
+//!
+//! A [`ThingBuf`] can also return a snapshot of the state of each of its
+//! slots, with [`ThingBuf::slot_states`]. This shows how the queue's elements
+//! are laid out, and which slots are claimed by `Ref`s that are still held.
+//!
+//! [`ThingBuf`]: crate::ThingBuf
+//! [`ThingBuf::slot_states`]: crate::ThingBuf::slot_states
+//! [`StaticThingBuf`]: crate::StaticThingBuf
+//! [`mpsc`]: crate::mpsc
+//! [`len`]: crate::ThingBuf::len
+//! [recycles]: crate::recycling
+use core::{
+    fmt,
+    ops::RangeInclusive,
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+};
+
+/// A snapshot of the statistics recorded for a queue or channel.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    pub(crate) occupancy: OccupancyHistogram,
+    #[cfg(feature = "timestamps")]
+    pub(crate) latency: LatencyHistogram,
+    pub(crate) recycling: RecyclingStats,
+}
+
+/// A fixed-bucket histogram of a queue's depth, sampled on each operation.
+///
+/// The range of possible depths, from 0 to the queue's capacity, is divided
+/// into [`BUCKETS`](Self::BUCKETS) equally sized buckets.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::ThingBuf;
+///
+/// let q = ThingBuf::new(8);
+/// for i in 0..8 {
+///     q.push(i).unwrap();
+/// }
+/// while q.pop().is_some() {}
+///
+/// let stats = q.stats();
+/// let occupancy = stats.occupancy();
+/// // 8 pushes and 8 pops were sampled.
+/// assert_eq!(occupancy.samples(), 16);
+///
+/// for (depths, count) in occupancy.buckets() {
+///     println!("{:?}: {}", depths, count);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct OccupancyHistogram {
+    capacity: usize,
+    counts: [usize; OccupancyHistogram::BUCKETS],
+}
+
+/// Counts of how the elements in a queue's slots were produced when the
+/// slots were claimed by pushes.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::{recycling::WithCapacity, ThingBuf};
+///
+/// let q = ThingBuf::<String, _>::with_recycle(2, WithCapacity::new().with_max_capacity(8));
+/// for _ in 0..4 {
+///     q.push_ref().unwrap().push_str("a string longer than 8 bytes");
+///     q.pop_ref().unwrap();
+/// }
+///
+/// let stats = q.stats();
+/// let recycling = stats.recycling();
+/// // Each slot's first push created a new string, and every push after that
+/// // reused one, shrinking it to the maximum capacity.
+/// assert_eq!(recycling.fresh(), 2);
+/// assert_eq!(recycling.reused(), 2);
+/// assert!(recycling.shed_capacity() > 0);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecyclingStats {
+    fresh: usize,
+    reused: usize,
+    shed_capacity: usize,
+}
+
+/// Records how pushed elements were produced.
+pub(crate) struct RecyclingCounter {
+    fresh: AtomicUsize,
+    reused: AtomicUsize,
+    shed_capacity: AtomicUsize,
+}
+
+/// Records samples of a queue's depth.
+pub(crate) struct OccupancySampler {
+    counts: [AtomicUsize; OccupancyHistogram::BUCKETS],
+}
+
+// === impl Stats ===
+
+impl Stats {
+    /// Returns a histogram of the queue's depth.
+    #[must_use]
+    pub fn occupancy(&self) -> &OccupancyHistogram {
+        &self.occupancy
+    }
+
+    /// Returns a histogram of the time elements spent in the queue, from
+    /// being pushed to being popped.
+    #[cfg(feature = "timestamps")]
+    #[must_use]
+    pub fn latency(&self) -> &LatencyHistogram {
+        &self.latency
+    }
+
+    /// Returns counts of the elements that pushes created or recycled.
+    #[must_use]
+    pub fn recycling(&self) -> &RecyclingStats {
+        &self.recycling
+    }
+}
+
+// === impl OccupancyHistogram ===
+
+impl OccupancyHistogram {
+    /// The number of buckets in the histogram.
+    pub const BUCKETS: usize = 8;
+
+    /// Returns the capacity of the queue that this histogram was recorded
+    /// for.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the total number of samples in the histogram.
+    #[must_use]
+    pub fn samples(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the number of samples in each bucket, from the lowest depths
+    /// to the highest.
+    #[must_use]
+    pub fn counts(&self) -> &[usize; Self::BUCKETS] {
+        &self.counts
+    }
+
+    /// Returns an iterator over the range of depths covered by each bucket,
+    /// and the number of samples in that bucket.
+    ///
+    /// If the capacity is less than [`BUCKETS`](Self::BUCKETS), buckets that
+    /// would only contain depths greater than the capacity are skipped.
+    pub fn buckets(&self) -> impl Iterator<Item = (RangeInclusive<usize>, usize)> + '_ {
+        let width = bucket_width(self.capacity);
+        let capacity = self.capacity;
+        self.counts
+            .iter()
+            .enumerate()
+            .map(move |(i, &count)| {
+                let start = i * width;
+                let end = if i == Self::BUCKETS - 1 {
+                    capacity
+                } else {
+                    (start + width - 1).min(capacity)
+                };
+                (start..=end, count)
+            })
+            .filter(move |(depths, _)| *depths.start() <= capacity)
+    }
+}
+
+impl fmt::Debug for OccupancyHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.buckets()).finish()
+    }
+}
+
+// === impl OccupancySampler ===
+
+impl OccupancySampler {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: AtomicUsize = AtomicUsize::new(0);
+
+    pub(crate) const fn new() -> Self {
+        Self {
+            counts: [Self::ZERO; OccupancyHistogram::BUCKETS],
+        }
+    }
+
+    #[inline]
+    pub(crate) fn record(&self, depth: usize, capacity: usize) {
+        let bucket = (depth / bucket_width(capacity)).min(OccupancyHistogram::BUCKETS - 1);
+        self.counts[bucket].fetch_add(1, Relaxed);
+    }
+
+    pub(crate) fn histogram(&self, capacity: usize) -> OccupancyHistogram {
+        let mut counts = [0; OccupancyHistogram::BUCKETS];
+        for (count, sampled) in counts.iter_mut().zip(self.counts.iter()) {
+            *count = sampled.load(Relaxed);
+        }
+        OccupancyHistogram { capacity, counts }
+    }
+}
+
+impl fmt::Debug for OccupancySampler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupancySampler").finish_non_exhaustive()
+    }
+}
+
+// === impl RecyclingStats ===
+
+impl RecyclingStats {
+    /// Returns the number of pushes that created a new element, because the
+    /// slot they claimed had never been used.
+    ///
+    /// Slots initialized ahead of time by [`ThingBuf::pre_fill`] are not
+    /// counted.
+    ///
+    /// [`ThingBuf::pre_fill`]: crate::ThingBuf::pre_fill
+    #[must_use]
+    pub fn fresh(&self) -> usize {
+        self.fresh
+    }
+
+    /// Returns the number of pushes that [recycled] the element already in
+    /// the slot they claimed.
+    ///
+    /// [recycled]: crate::recycling::Recycle::recycle
+    #[must_use]
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    /// Returns the total capacity released by the recycling policy shrinking
+    /// elements as they were recycled, as reported by
+    /// [`Recycle::recycle_shrunk`].
+    ///
+    /// The unit is that of the elements' own capacity, such as bytes for a
+    /// `String`.
+    ///
+    /// [`Recycle::recycle_shrunk`]: crate::recycling::Recycle::recycle_shrunk
+    #[must_use]
+    pub fn shed_capacity(&self) -> usize {
+        self.shed_capacity
+    }
+}
+
+// === impl RecyclingCounter ===
+
+impl RecyclingCounter {
+    pub(crate) const fn new() -> Self {
+        Self {
+            fresh: AtomicUsize::new(0),
+            reused: AtomicUsize::new(0),
+            shed_capacity: AtomicUsize::new(0),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn record_fresh(&self) {
+        self.fresh.fetch_add(1, Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn record_reused(&self, shed_capacity: usize) {
+        self.reused.fetch_add(1, Relaxed);
+        if shed_capacity > 0 {
+            self.shed_capacity.fetch_add(shed_capacity, Relaxed);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> RecyclingStats {
+        RecyclingStats {
+            fresh: self.fresh.load(Relaxed),
+            reused: self.reused.load(Relaxed),
+            shed_capacity: self.shed_capacity.load(Relaxed),
+        }
+    }
+}
+
+impl fmt::Debug for RecyclingCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.snapshot(), f)
+    }
+}
+
+/// Returns the number of depths covered by each histogram bucket.
+#[inline]
+fn bucket_width(capacity: usize) -> usize {
+    // Round up, so that the last bucket is not much wider than the others.
+    (capacity / OccupancyHistogram::BUCKETS
+        + (capacity % OccupancyHistogram::BUCKETS != 0) as usize)
+        .max(1)
+}
+
+feature! {
+    #![feature = "timestamps"]
+    use core::{ops::Range, time::Duration};
+
+    /// A fixed-bucket histogram of the time elements spent in a queue, from
+    /// being pushed to being popped.
+    ///
+    /// Latencies are divided into [`BUCKETS`](Self::BUCKETS) buckets whose
+    /// bounds are powers of two microseconds: the first bucket holds
+    /// latencies under 1µs, the second those from 1µs to 2µs, the third those
+    /// from 2µs to 4µs, and so on. The last bucket holds every latency of
+    /// 2<sup>22</sup>µs (about 4.2 seconds) or more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::mpsc::blocking;
+    /// use std::time::Duration;
+    ///
+    /// let (tx, rx) = blocking::channel::<usize>(8);
+    /// tx.send(1).unwrap();
+    /// assert_eq!(rx.recv(), Some(1));
+    ///
+    /// // The histogram can be retrieved from either end of the channel.
+    /// let latency = tx.stats().latency().clone();
+    /// assert_eq!(latency.samples(), 1);
+    ///
+    /// for (latencies, count) in latency.buckets() {
+    ///     if count > 0 {
+    ///         println!("{:?}: {}", latencies, count);
+    ///     }
+    /// }
+    /// ```
+    #[derive(Clone)]
+    pub struct LatencyHistogram {
+        counts: [usize; LatencyHistogram::BUCKETS],
+    }
+
+    /// Records the latencies of popped elements.
+    pub(crate) struct LatencySampler {
+        counts: [AtomicUsize; LatencyHistogram::BUCKETS],
+    }
+
+    // === impl LatencyHistogram ===
+
+    impl LatencyHistogram {
+        /// The number of buckets in the histogram.
+        pub const BUCKETS: usize = 24;
+
+        /// Returns the total number of samples in the histogram.
+        #[must_use]
+        pub fn samples(&self) -> usize {
+            self.counts.iter().sum()
+        }
+
+        /// Returns the number of samples in each bucket, from the lowest
+        /// latencies to the highest.
+        #[must_use]
+        pub fn counts(&self) -> &[usize; Self::BUCKETS] {
+            &self.counts
+        }
+
+        /// Returns an iterator over the range of latencies covered by each
+        /// bucket, and the number of samples in that bucket.
+        ///
+        /// The last bucket's range ends at [`Duration::MAX`].
+        pub fn buckets(&self) -> impl Iterator<Item = (Range<Duration>, usize)> + '_ {
+            self.counts.iter().enumerate().map(|(i, &count)| {
+                let start = if i == 0 {
+                    Duration::ZERO
+                } else {
+                    Duration::from_micros(1 << (i - 1))
+                };
+                let end = if i == Self::BUCKETS - 1 {
+                    Duration::MAX
+                } else {
+                    Duration::from_micros(1 << i)
+                };
+                (start..end, count)
+            })
+        }
+    }
+
+    impl fmt::Debug for LatencyHistogram {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_map().entries(self.buckets()).finish()
+        }
+    }
+
+    // === impl LatencySampler ===
+
+    impl LatencySampler {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicUsize = AtomicUsize::new(0);
+
+        pub(crate) const fn new() -> Self {
+            Self {
+                counts: [Self::ZERO; LatencyHistogram::BUCKETS],
+            }
+        }
+
+        #[inline]
+        pub(crate) fn record(&self, latency: Duration) {
+            // The number of significant bits in the latency, in microseconds,
+            // is the index of the power-of-two bucket it falls into.
+            let micros = latency.as_micros();
+            let bits = (u128::BITS - micros.leading_zeros()) as usize;
+            let bucket = bits.min(LatencyHistogram::BUCKETS - 1);
+            self.counts[bucket].fetch_add(1, Relaxed);
+        }
+
+        pub(crate) fn histogram(&self) -> LatencyHistogram {
+            let mut counts = [0; LatencyHistogram::BUCKETS];
+            for (count, sampled) in counts.iter_mut().zip(self.counts.iter()) {
+                *count = sampled.load(Relaxed);
+            }
+            LatencyHistogram { counts }
+        }
+    }
+
+    impl fmt::Debug for LatencySampler {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("LatencySampler").finish_non_exhaustive()
+        }
+    }
+}
+
+feature! {
+    #![feature = "alloc"]
+    use alloc::vec::Vec;
+
+    /// A snapshot of the state of each slot in a queue, retrieved with
+    /// [`ThingBuf::slot_states`].
+    ///
+    /// Each slot is either [empty](SlotState::Empty), holds an element that is
+    /// waiting to be popped ([occupied](SlotState::Occupied)), or is
+    /// [claimed](SlotState::Claimed) by a [`Ref`] that is still being written
+    /// to or read from. Slots that stay claimed between snapshots point to
+    /// `Ref`s that are being held for a long time, which stops the queue from
+    /// reusing them.
+    ///
+    /// The states are stored as a pair of bitmaps, so a snapshot of even a
+    /// large queue is cheap to keep around. Its [`Display`](fmt::Display)
+    /// implementation draws one character per slot: `.` for an empty slot,
+    /// `#` for an occupied slot, and `~` for a claimed slot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{stats::SlotState, ThingBuf};
+    ///
+    /// let q = ThingBuf::new(4);
+    /// q.push(1).unwrap();
+    /// let writing = q.push_ref().unwrap();
+    ///
+    /// let states = q.slot_states();
+    /// assert_eq!(states.get(0), Some(SlotState::Occupied));
+    /// assert_eq!(states.get(1), Some(SlotState::Claimed));
+    /// assert_eq!(states.count(SlotState::Empty), 2);
+    /// assert_eq!(states.to_string(), "#~..");
+    /// # drop(writing);
+    /// ```
+    ///
+    /// [`ThingBuf::slot_states`]: crate::ThingBuf::slot_states
+    /// [`Ref`]: crate::Ref
+    #[derive(Clone, Eq, PartialEq)]
+    pub struct SlotStates {
+        capacity: usize,
+        occupied: Vec<usize>,
+        claimed: Vec<usize>,
+    }
+
+    /// The state of a single slot in a [`SlotStates`] snapshot.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+    pub enum SlotState {
+        /// The slot does not hold an element, and may be pushed to.
+        Empty,
+        /// The slot holds an element that has been pushed, and not yet popped.
+        Occupied,
+        /// The slot is being written to by a push, or read from by a pop, whose
+        /// [`Ref`](crate::Ref) has not yet been dropped.
+        Claimed,
+    }
+
+    // === impl SlotStates ===
+
+    impl SlotStates {
+        pub(crate) fn new(capacity: usize) -> Self {
+            let words = (capacity + usize::BITS as usize - 1) / usize::BITS as usize;
+            Self {
+                capacity,
+                occupied: alloc::vec![0; words],
+                claimed: alloc::vec![0; words],
+            }
+        }
+
+        pub(crate) fn set(&mut self, idx: usize, state: SlotState) {
+            let (word, bit) = Self::word_bit(idx);
+            match state {
+                SlotState::Empty => {}
+                SlotState::Occupied => self.occupied[word] |= bit,
+                SlotState::Claimed => self.claimed[word] |= bit,
+            }
+        }
+
+        /// Returns the number of slots in the queue.
+        #[must_use]
+        pub fn capacity(&self) -> usize {
+            self.capacity
+        }
+
+        /// Returns the state of the slot at `idx`, or `None` if `idx` is not
+        /// less than the [capacity](Self::capacity).
+        #[must_use]
+        pub fn get(&self, idx: usize) -> Option<SlotState> {
+            if idx >= self.capacity {
+                return None;
+            }
+            let (word, bit) = Self::word_bit(idx);
+            Some(if self.occupied[word] & bit != 0 {
+                SlotState::Occupied
+            } else if self.claimed[word] & bit != 0 {
+                SlotState::Claimed
+            } else {
+                SlotState::Empty
+            })
+        }
+
+        /// Returns the number of slots in `state`.
+        #[must_use]
+        pub fn count(&self, state: SlotState) -> usize {
+            let ones = |words: &[usize]| -> usize {
+                words.iter().map(|word| word.count_ones() as usize).sum()
+            };
+            match state {
+                SlotState::Occupied => ones(&self.occupied),
+                SlotState::Claimed => ones(&self.claimed),
+                SlotState::Empty => {
+                    self.capacity - ones(&self.occupied) - ones(&self.claimed)
+                }
+            }
+        }
+
+        /// Returns an iterator over the state of each slot, in index order.
+        pub fn iter(&self) -> impl Iterator<Item = SlotState> + '_ {
+            (0..self.capacity).filter_map(move |idx| self.get(idx))
+        }
+
+        #[inline]
+        fn word_bit(idx: usize) -> (usize, usize) {
+            let bits = usize::BITS as usize;
+            (idx / bits, 1 << (idx % bits))
+        }
+    }
+
+    impl fmt::Display for SlotStates {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            use fmt::Write;
+            for state in self.iter() {
+                f.write_char(match state {
+                    SlotState::Empty => '.',
+                    SlotState::Occupied => '#',
+                    SlotState::Claimed => '~',
+                })?;
+            }
+            Ok(())
+        }
+    }
+
+    impl fmt::Debug for SlotStates {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_tuple("SlotStates")
+                .field(&format_args!("{}", self))
+                .finish()
+        }
+    }
+}
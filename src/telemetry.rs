@@ -0,0 +1,806 @@
+//! Per-thread telemetry buffers, drained by a single collector.
+//!
+//! Recording metrics or trace events from many threads into one shared queue
+//! makes every recording thread contend on the same cache lines. A
+//! [`Telemetry`] handle instead gives each thread that records an event its
+//! own [`ThingBuf`], created the first time that thread records. A single
+//! [`Collector`] drains all of the threads' buffers in batches, and hands
+//! them to a sink that writes them out.
+//!
+//! Recording never blocks: if a thread records events faster than the
+//! collector drains them, and its buffer fills up, the events that do not fit
+//! are dropped and counted. The collector reports how many events were
+//! [missed](Collector::missed), so that gaps in the data can be detected.
+//!
+//! The collector may be driven manually, by calling
+//! [`Collector::collect_into`], or by a background thread, with
+//! [`Collector::spawn`]. Like the worker guard in [`tracing-appender`],
+//! `spawn` returns a [`FlushGuard`], which drains every remaining event into
+//! the sink when it is dropped, so that no events are lost when the program
+//! exits. The guard can also [flush](FlushGuard::flush) the recorded events
+//! on demand, without stopping the collector thread.
+//!
+//! By default, the collector thread passes events to the sink as soon as it
+//! collects them. A [`FlushPolicy`] set with [`Collector::flush_policy`] can
+//! instead pass each event to the sink on its own, or hold events back until
+//! a newline is recorded, until enough bytes have accumulated, or until an
+//! interval has elapsed since the previous flush, which suits sinks that
+//! write to a file or a socket.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::telemetry::Telemetry;
+//! use std::{sync::{Arc, Mutex}, thread, time::Duration};
+//!
+//! let (telemetry, collector) = Telemetry::<u64>::new(64);
+//!
+//! let written = Arc::new(Mutex::new(Vec::new()));
+//! let guard = {
+//!     let written = written.clone();
+//!     collector.spawn(32, Duration::from_millis(10), move |events, missed| {
+//!         assert_eq!(missed, 0);
+//!         written.lock().unwrap().extend_from_slice(events);
+//!     })
+//! };
+//!
+//! let workers = (0..4)
+//!     .map(|worker| {
+//!         let telemetry = telemetry.clone();
+//!         thread::spawn(move || {
+//!             for i in 0..16 {
+//!                 assert!(telemetry.record(worker * 100 + i));
+//!             }
+//!         })
+//!     })
+//!     .collect::<Vec<_>>();
+//! for worker in workers {
+//!     worker.join().unwrap();
+//! }
+//!
+//! // Dropping the guard flushes every event that has not been written yet.
+//! drop(guard);
+//! assert_eq!(written.lock().unwrap().len(), 64);
+//! ```
+//!
+//! [`tracing-appender`]: https://crates.io/crates/tracing-appender
+use crate::{
+    loom::atomic::{AtomicBool, AtomicUsize, Ordering::*},
+    recycling::{self, Recycle},
+    util::mutex::{const_mutex, Mutex},
+    ThingBuf,
+};
+use alloc::{sync::Arc, vec::Vec};
+use core::{any::Any, cell::RefCell, fmt, mem};
+use std::{
+    sync::{Condvar, Mutex as StdMutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// A handle for recording events into per-thread buffers.
+///
+/// Each thread that records an event through a `Telemetry` handle, or any of
+/// its clones, is given its own buffer, with the capacity passed to
+/// [`Telemetry::new`].
+///
+/// See the [module-level documentation](self) for details.
+pub struct Telemetry<T, R = recycling::DefaultRecycle> {
+    shared: Arc<Shared<T, R>>,
+}
+
+/// Drains the events recorded through a [`Telemetry`] handle.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Collector<T, R = recycling::DefaultRecycle> {
+    shared: Arc<Shared<T, R>>,
+    /// The collector's copy of the registered buffers, so that draining them
+    /// does not hold the registry lock.
+    rings: Vec<Arc<Ring<T, R>>>,
+    /// Events missed by the buffers of threads that have exited.
+    missed: usize,
+    /// When a spawned collector thread passes events to the sink.
+    policy: FlushPolicy<T>,
+}
+
+/// Determines when a [spawned](Collector::spawn) collector thread passes the
+/// events it has collected to its sink.
+///
+/// Whatever the policy, [`FlushGuard::flush`], [`FlushGuard::shutdown`], and
+/// dropping the [`FlushGuard`] pass every collected event to the sink. A
+/// policy that holds events back only delays them until then.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::telemetry::{FlushPolicy, Telemetry};
+/// use std::{fmt::Write, sync::{Arc, Mutex}, time::Duration};
+///
+/// let (telemetry, collector) = Telemetry::<String>::new(64);
+/// let lines = Arc::new(Mutex::new(String::new()));
+/// let guard = {
+///     let lines = lines.clone();
+///     collector
+///         .flush_policy(FlushPolicy::on_newline())
+///         .spawn(32, Duration::from_millis(1), move |events, _| {
+///             lines.lock().unwrap().extend(events.iter().map(String::as_str));
+///         })
+/// };
+///
+/// telemetry.record_with(|s| write!(s, "request ").unwrap());
+/// telemetry.record_with(|s| write!(s, "took {}ms\n", 12).unwrap());
+///
+/// guard.shutdown().unwrap();
+/// assert_eq!(*lines.lock().unwrap(), "request took 12ms\n");
+/// ```
+pub struct FlushPolicy<T> {
+    kind: PolicyKind<T>,
+}
+
+enum PolicyKind<T> {
+    Batch,
+    PerRecord,
+    Newline(fn(&T) -> &[u8]),
+    Bytes(usize, fn(&T) -> &[u8]),
+    Interval(Duration),
+}
+
+/// Flushes a [spawned](Collector::spawn) collector's remaining events when
+/// dropped, and waits for its thread to exit.
+#[must_use = "dropping a `FlushGuard` stops the collector thread immediately"]
+pub struct FlushGuard {
+    control: Arc<Control>,
+    worker: Option<JoinHandle<()>>,
+}
+
+/// State shared between a [`FlushGuard`] and its collector thread.
+struct Control {
+    stop: AtomicBool,
+    flushes: StdMutex<Flushes>,
+    flushed: Condvar,
+}
+
+struct Flushes {
+    /// The number of flushes requested by [`FlushGuard::flush`].
+    requested: usize,
+    /// The number of requested flushes that the collector thread has
+    /// completed.
+    completed: usize,
+    /// Whether the collector thread has exited, in which case no more
+    /// flushes will be completed.
+    exited: bool,
+}
+
+/// Marks the collector thread as exited when it returns or panics, and wakes
+/// any threads waiting for it to flush.
+struct ExitGuard(Arc<Control>);
+
+struct Shared<T, R> {
+    /// Identifies this telemetry instance in each thread's buffer cache.
+    id: usize,
+    capacity: usize,
+    recycle: R,
+    rings: Mutex<Vec<Arc<Ring<T, R>>>>,
+    /// Events that could not be recorded into any buffer, because the
+    /// recording thread was exiting.
+    missed: AtomicUsize,
+}
+
+struct Ring<T, R> {
+    buf: ThingBuf<T, R>,
+    /// Events dropped because the buffer was full.
+    missed: AtomicUsize,
+}
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+std::thread_local! {
+    /// The buffers that the current thread has registered, for each
+    /// telemetry instance, by ID.
+    static RINGS: RefCell<Vec<(usize, Arc<dyn Any + Send + Sync>)>> = RefCell::new(Vec::new());
+}
+
+// === impl Telemetry ===
+
+impl<T> Telemetry<T>
+where
+    T: Default + Clone + Send + Sync + 'static,
+{
+    /// Returns a new `Telemetry` handle, which gives each recording thread a
+    /// buffer with space for `capacity` events, and the [`Collector`] that
+    /// drains them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`. This value
+    /// represents the highest power of two that can be expressed by a `usize`, excluding the most
+    /// significant bit.
+    #[must_use]
+    pub fn new(capacity: usize) -> (Self, Collector<T>) {
+        Self::with_recycle(capacity, recycling::DefaultRecycle::new())
+    }
+}
+
+impl<T, R> Telemetry<T, R>
+where
+    T: Send + Sync + 'static,
+    R: Recycle<T> + Clone + Send + Sync + 'static,
+{
+    /// Returns a new `Telemetry` handle, which gives each recording thread a
+    /// buffer with space for `capacity` events and the provided [recycling
+    /// policy], and the [`Collector`] that drains them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`. This value
+    /// represents the highest power of two that can be expressed by a `usize`, excluding the most
+    /// significant bit.
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    #[must_use]
+    pub fn with_recycle(capacity: usize, recycle: R) -> (Self, Collector<T, R>) {
+        let shared = Arc::new(Shared {
+            id: NEXT_ID.fetch_add(1, Relaxed),
+            capacity,
+            recycle,
+            rings: const_mutex(Vec::new()),
+            missed: AtomicUsize::new(0),
+        });
+        let collector = Collector {
+            shared: shared.clone(),
+            rings: Vec::new(),
+            missed: 0,
+            policy: FlushPolicy::batch(),
+        };
+        (Self { shared }, collector)
+    }
+
+    /// Records an event into the current thread's buffer.
+    ///
+    /// Returns `false` if the buffer is full, in which case the event is
+    /// dropped and counted as [missed](Collector::missed).
+    pub fn record(&self, event: T) -> bool {
+        self.with_ring(|ring| ring.buf.push(event).is_ok())
+    }
+
+    /// Reserves a slot in the current thread's buffer, and calls `f` to write
+    /// the event in place, reusing the slot's existing allocations.
+    ///
+    /// Returns `false` if the buffer is full, in which case `f` is not
+    /// called, and the event is counted as [missed](Collector::missed).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::telemetry::Telemetry;
+    /// use std::fmt::Write;
+    ///
+    /// let (telemetry, mut collector) = Telemetry::<String>::new(8);
+    /// telemetry.record_with(|line| write!(line, "request took {}ms", 12).unwrap());
+    ///
+    /// let mut events = Vec::new();
+    /// collector.collect_into(&mut events, 16);
+    /// assert_eq!(events, vec!["request took 12ms".to_string()]);
+    /// ```
+    pub fn record_with(&self, f: impl FnOnce(&mut T)) -> bool {
+        self.with_ring(|ring| ring.buf.push_with(f).is_ok())
+    }
+
+    /// Calls `f` with the current thread's buffer, registering a new buffer
+    /// if this thread has not recorded an event before, and counts a missed
+    /// event if `f` returns `false`.
+    fn with_ring(&self, f: impl FnOnce(&Ring<T, R>) -> bool) -> bool {
+        // Clone the buffer out of the cache, so that the cache is not
+        // borrowed while `f` runs.
+        let ring = RINGS.try_with(|rings| {
+            let mut rings = rings.borrow_mut();
+            let cached = rings
+                .iter()
+                .find(|(id, _)| *id == self.shared.id)
+                .and_then(|(_, ring)| ring.clone().downcast::<Ring<T, R>>().ok());
+            cached.unwrap_or_else(|| {
+                // Forget the buffers of telemetry instances that have been
+                // dropped, now that this thread is registering a new one.
+                rings.retain(|(_, ring)| Arc::strong_count(ring) > 1);
+                let ring = self.shared.register();
+                rings.push((self.shared.id, ring.clone()));
+                ring
+            })
+        });
+
+        match ring {
+            Ok(ring) => {
+                let recorded = f(&ring);
+                if !recorded {
+                    ring.missed.fetch_add(1, Relaxed);
+                }
+                recorded
+            }
+            // The thread is exiting, and its buffers have been dropped.
+            Err(_) => {
+                self.shared.missed.fetch_add(1, Relaxed);
+                false
+            }
+        }
+    }
+}
+
+impl<T, R> Telemetry<T, R> {
+    /// Returns the number of threads that have recorded events through this
+    /// `Telemetry` handle and its clones, and whose buffers have not yet been
+    /// removed by the [`Collector`].
+    pub fn threads(&self) -> usize {
+        self.shared.rings.lock().len()
+    }
+}
+
+impl<T, R> Clone for Telemetry<T, R> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T, R> fmt::Debug for Telemetry<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Telemetry")
+            .field("capacity", &self.shared.capacity)
+            .field("threads", &self.threads())
+            .finish()
+    }
+}
+
+// === impl Collector ===
+
+impl<T, R> Collector<T, R>
+where
+    T: Send + Sync + 'static,
+    R: Recycle<T> + Send + Sync + 'static,
+{
+    /// Moves up to `max` recorded events into `out`, taking them from each
+    /// thread's buffer in turn.
+    ///
+    /// Events recorded by the same thread are collected in the order they
+    /// were recorded. Events recorded by different threads are not ordered
+    /// relative to each other.
+    ///
+    /// Returns the number of events moved into `out`.
+    pub fn collect_into(&mut self, out: &mut Vec<T>, max: usize) -> usize {
+        self.refresh();
+        let mut collected = 0;
+        // Take a share of the batch from each thread in turn, so that a busy
+        // thread cannot starve the others.
+        while collected < max {
+            let before = collected;
+            for ring in &self.rings {
+                if collected == max {
+                    break;
+                }
+                if let Some(event) = ring.buf.pop() {
+                    out.push(event);
+                    collected += 1;
+                }
+            }
+            if collected == before {
+                break;
+            }
+        }
+        collected
+    }
+
+    /// Returns the number of events that have been dropped because a
+    /// thread's buffer was full, since the last call to `missed`.
+    pub fn missed(&mut self) -> usize {
+        self.refresh();
+        let mut missed = core::mem::take(&mut self.missed) + self.shared.missed.swap(0, Relaxed);
+        for ring in &self.rings {
+            missed += ring.missed.swap(0, Relaxed);
+        }
+        missed
+    }
+
+    /// Moves this collector to a new thread, which drains up to `batch`
+    /// events at a time and passes them to `sink`, with the number of events
+    /// [missed](Self::missed) since the previous batch.
+    ///
+    /// The thread collects every `interval`, for as long as there are
+    /// events to collect, and passes them to `sink` as its [flush
+    /// policy](Self::flush_policy) allows. When the returned [`FlushGuard`]
+    /// is dropped, the thread passes every remaining event to `sink`, and
+    /// then exits.
+    ///
+    /// # Panics
+    ///
+    /// If `batch` is 0.
+    pub fn spawn<F>(mut self, batch: usize, interval: Duration, mut sink: F) -> FlushGuard
+    where
+        F: FnMut(&[T], usize) + Send + 'static,
+    {
+        assert!(batch > 0, "a telemetry batch must hold at least one event");
+        let control = Arc::new(Control {
+            stop: AtomicBool::new(false),
+            flushes: StdMutex::new(Flushes {
+                requested: 0,
+                completed: 0,
+                exited: false,
+            }),
+            flushed: Condvar::new(),
+        });
+        let worker = {
+            let control = control.clone();
+            thread::Builder::new()
+                .name("thingbuf-telemetry".into())
+                .spawn(move || {
+                    let _exit = ExitGuard(control.clone());
+                    let policy = self.policy;
+                    // Events that have been collected, but are held back by
+                    // the flush policy.
+                    let mut pending = Vec::with_capacity(batch);
+                    let mut missed = 0;
+                    let mut flushed_at = Instant::now();
+                    loop {
+                        // Read the flag and the flush requests before
+                        // draining, so that the drain sees every event
+                        // recorded before the guard was dropped or flushed.
+                        let stopping = control.stop.load(Acquire);
+                        let (requested, flushing) = {
+                            let flushes = control.flushes();
+                            (flushes.requested, flushes.completed < flushes.requested)
+                        };
+                        loop {
+                            let n = self.collect_into(&mut pending, batch);
+                            missed += self.missed();
+                            let ready = if stopping || flushing {
+                                pending.len()
+                            } else {
+                                policy.ready(&pending, flushed_at)
+                            };
+                            if ready == 0 && missed > 0 && pending.is_empty() {
+                                sink(&[], mem::take(&mut missed));
+                            }
+                            for events in pending[..ready].chunks(policy.chunk_len(batch)) {
+                                sink(events, mem::take(&mut missed));
+                            }
+                            if ready > 0 {
+                                pending.drain(..ready);
+                                flushed_at = Instant::now();
+                            }
+                            if n < batch {
+                                break;
+                            }
+                        }
+                        {
+                            let mut flushes = control.flushes();
+                            if flushes.completed < requested {
+                                flushes.completed = requested;
+                                control.flushed.notify_all();
+                            }
+                        }
+                        if stopping {
+                            return;
+                        }
+                        thread::park_timeout(interval);
+                    }
+                })
+                .expect("failed to spawn telemetry collector thread")
+        };
+        FlushGuard {
+            control,
+            worker: Some(worker),
+        }
+    }
+
+    /// Adds the buffers of newly registered threads, and removes the
+    /// buffers of exited threads once they have been drained.
+    fn refresh(&mut self) {
+        let mut rings = self.shared.rings.lock();
+        self.rings.clear();
+        let missed = &mut self.missed;
+        // A buffer referenced only by the registry belongs to a thread that
+        // has exited, so it will never be written to again.
+        rings.retain(|ring| {
+            let exited = Arc::strong_count(ring) == 1 && ring.buf.is_empty();
+            if exited {
+                *missed += ring.missed.swap(0, Relaxed);
+            }
+            !exited
+        });
+        self.rings.extend(rings.iter().cloned());
+    }
+}
+
+impl<T, R> Collector<T, R> {
+    /// Sets when the collector thread started by [`spawn`](Self::spawn)
+    /// passes the events it collects to its sink.
+    ///
+    /// By default, this is [`FlushPolicy::batch`].
+    #[must_use]
+    pub fn flush_policy(mut self, policy: FlushPolicy<T>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the number of threads whose buffers are being collected.
+    pub fn threads(&self) -> usize {
+        self.shared.rings.lock().len()
+    }
+}
+
+impl<T, R> fmt::Debug for Collector<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Collector")
+            .field("capacity", &self.shared.capacity)
+            .field("threads", &self.threads())
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+// === impl FlushPolicy ===
+
+impl<T> FlushPolicy<T> {
+    /// Passes events to the sink as soon as they are collected, in batches
+    /// of at most the size passed to [`Collector::spawn`].
+    ///
+    /// This is the default policy.
+    #[must_use]
+    pub const fn batch() -> Self {
+        Self {
+            kind: PolicyKind::Batch,
+        }
+    }
+
+    /// Passes each event to the sink on its own, as soon as it is collected.
+    #[must_use]
+    pub const fn per_record() -> Self {
+        Self {
+            kind: PolicyKind::PerRecord,
+        }
+    }
+
+    /// Holds collected events back until `interval` has elapsed since the
+    /// previous flush, and then passes them all to the sink.
+    ///
+    /// The collector thread only checks the policy when it collects, so
+    /// `interval` is effectively rounded up to a multiple of the interval
+    /// passed to [`Collector::spawn`].
+    #[must_use]
+    pub const fn interval(interval: Duration) -> Self {
+        Self {
+            kind: PolicyKind::Interval(interval),
+        }
+    }
+
+    /// Returns how many of the `pending` events may be passed to the sink.
+    fn ready(&self, pending: &[T], flushed_at: Instant) -> usize {
+        match self.kind {
+            PolicyKind::Batch | PolicyKind::PerRecord => pending.len(),
+            PolicyKind::Newline(bytes) => pending
+                .iter()
+                .rposition(|event| bytes(event).contains(&b'\n'))
+                .map_or(0, |last| last + 1),
+            PolicyKind::Bytes(threshold, bytes) => {
+                let len = pending
+                    .iter()
+                    .map(|event| bytes(event).len())
+                    .sum::<usize>();
+                if len >= threshold {
+                    pending.len()
+                } else {
+                    0
+                }
+            }
+            PolicyKind::Interval(interval) => {
+                if flushed_at.elapsed() >= interval {
+                    pending.len()
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    /// Returns the number of events to pass to the sink at a time.
+    fn chunk_len(&self, batch: usize) -> usize {
+        match self.kind {
+            PolicyKind::PerRecord => 1,
+            _ => batch,
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> FlushPolicy<T> {
+    /// Holds collected events back until an event containing a newline
+    /// (`b'\n'`) is collected, and then passes every event up to and
+    /// including it to the sink.
+    ///
+    /// This suits events that are fragments of lines of text, so that the
+    /// sink only ever writes out complete lines.
+    #[must_use]
+    pub fn on_newline() -> Self {
+        Self {
+            kind: PolicyKind::Newline(<T as AsRef<[u8]>>::as_ref),
+        }
+    }
+
+    /// Holds collected events back until they add up to at least `threshold`
+    /// bytes, and then passes them all to the sink.
+    #[must_use]
+    pub fn bytes(threshold: usize) -> Self {
+        Self {
+            kind: PolicyKind::Bytes(threshold, <T as AsRef<[u8]>>::as_ref),
+        }
+    }
+}
+
+impl<T> Clone for FlushPolicy<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for FlushPolicy<T> {}
+
+impl<T> Clone for PolicyKind<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for PolicyKind<T> {}
+
+impl<T> Default for FlushPolicy<T> {
+    fn default() -> Self {
+        Self::batch()
+    }
+}
+
+impl<T> fmt::Debug for FlushPolicy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            PolicyKind::Batch => f.write_str("FlushPolicy::Batch"),
+            PolicyKind::PerRecord => f.write_str("FlushPolicy::PerRecord"),
+            PolicyKind::Newline(_) => f.write_str("FlushPolicy::Newline"),
+            PolicyKind::Bytes(threshold, _) => f
+                .debug_tuple("FlushPolicy::Bytes")
+                .field(&threshold)
+                .finish(),
+            PolicyKind::Interval(interval) => f
+                .debug_tuple("FlushPolicy::Interval")
+                .field(&interval)
+                .finish(),
+        }
+    }
+}
+
+// === impl FlushGuard ===
+
+impl FlushGuard {
+    /// Wakes the collector thread, and blocks until it has passed every event
+    /// recorded before this call to the sink, including any events held back
+    /// by its [`FlushPolicy`].
+    ///
+    /// Unlike dropping the guard, this does not stop the collector thread,
+    /// which goes on collecting every `interval` afterwards.
+    ///
+    /// Returns `false` if the collector thread has exited because the sink
+    /// panicked, in which case the events were not flushed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::telemetry::Telemetry;
+    /// use std::{sync::{Arc, Mutex}, time::Duration};
+    ///
+    /// let (telemetry, collector) = Telemetry::<u64>::new(64);
+    /// let written = Arc::new(Mutex::new(Vec::new()));
+    /// let guard = {
+    ///     let written = written.clone();
+    ///     collector.spawn(32, Duration::from_secs(60), move |events, _| {
+    ///         written.lock().unwrap().extend_from_slice(events);
+    ///     })
+    /// };
+    ///
+    /// telemetry.record(1);
+    /// telemetry.record(2);
+    ///
+    /// // The events are written out without waiting for the interval.
+    /// assert!(guard.flush());
+    /// assert_eq!(*written.lock().unwrap(), vec![1, 2]);
+    /// ```
+    pub fn flush(&self) -> bool {
+        let mut flushes = self.control.flushes();
+        if flushes.exited {
+            return false;
+        }
+        flushes.requested += 1;
+        let target = flushes.requested;
+        if let Some(worker) = self.worker.as_ref() {
+            worker.thread().unpark();
+        }
+        while flushes.completed < target {
+            if flushes.exited {
+                return false;
+            }
+            flushes = self
+                .control
+                .flushed
+                .wait(flushes)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        true
+    }
+
+    /// Stops the collector thread, after it has passed every remaining event
+    /// to the sink, and waits for it to exit.
+    ///
+    /// This is equivalent to dropping the guard, except that if the sink
+    /// panicked, the panic is returned as an error, rather than ignored.
+    pub fn shutdown(mut self) -> thread::Result<()> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> thread::Result<()> {
+        self.control.stop.store(true, Release);
+        match self.worker.take() {
+            Some(worker) => {
+                worker.thread().unpark();
+                worker.join()
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        // If the sink panicked, the events it was given are lost, but there
+        // is nothing more to flush.
+        let _ = self.stop();
+    }
+}
+
+impl fmt::Debug for FlushGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlushGuard")
+            .field("stopped", &self.control.stop.load(Relaxed))
+            .finish()
+    }
+}
+
+// === impl Control ===
+
+impl Control {
+    fn flushes(&self) -> std::sync::MutexGuard<'_, Flushes> {
+        // The lock is never held while calling the sink, so it cannot be
+        // poisoned by a panicking sink.
+        self.flushes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        self.0.flushes().exited = true;
+        self.0.flushed.notify_all();
+    }
+}
+
+// === impl Shared ===
+
+impl<T, R> Shared<T, R>
+where
+    T: Send + Sync + 'static,
+    R: Recycle<T> + Clone + Send + Sync + 'static,
+{
+    fn register(&self) -> Arc<Ring<T, R>> {
+        let ring = Arc::new(Ring {
+            buf: ThingBuf::with_recycle(self.capacity, self.recycle.clone()),
+            missed: AtomicUsize::new(0),
+        });
+        self.rings.lock().push(ring.clone());
+        ring
+    }
+}
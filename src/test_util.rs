@@ -0,0 +1,90 @@
+//! Utilities for testing code that uses `thingbuf`.
+//!
+//! # Recording and replaying schedules
+//!
+//! Bugs that depend on the order in which several threads send and receive
+//! messages are hard to reproduce: the interleaving that triggered the bug in
+//! production may never happen again in a test. A [`Recorder`] wraps a
+//! channel's handles, and records every message that is successfully sent or
+//! received through them, in the order the channel observed them, as a
+//! [`Schedule`]. A recorded schedule can then be [replayed](Schedule::replay)
+//! against a fresh channel, on a single thread, reproducing exactly the same
+//! sequence of sends and receives every time.
+//!
+//! A recorder may store a clone of each message, or only a [hash] of it, for
+//! when messages are too large to keep or cannot be cloned. A schedule of
+//! hashes cannot be replayed, but it can be compared with the hashed schedule
+//! of a replayed run, to check that the run reproduced the original one.
+//!
+//! The recording handles only expose the non-waiting `try_` operations, so
+//! that every operation is recorded at the moment it takes effect. Recording
+//! serializes the channel's operations, so it changes the timing of the
+//! program being recorded, and should only be enabled while reproducing a
+//! bug.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::{mpsc::blocking, test_util::Recorder};
+//! use std::thread;
+//!
+//! let (tx, rx) = blocking::channel::<u32>(4);
+//! let recorder = Recorder::new();
+//! let rx = recorder.receiver(rx);
+//!
+//! let producers = (0..2)
+//!     .map(|i| {
+//!         let tx = recorder.sender(tx.clone());
+//!         thread::spawn(move || {
+//!             let mut sent = 0;
+//!             while sent < 2 {
+//!                 if tx.try_send(i * 10 + sent).is_ok() {
+//!                     sent += 1;
+//!                 }
+//!             }
+//!         })
+//!     })
+//!     .collect::<Vec<_>>();
+//! for producer in producers {
+//!     producer.join().unwrap();
+//! }
+//! while rx.try_recv().is_ok() {}
+//!
+//! // However the threads interleaved, the schedule reproduces it exactly.
+//! let schedule = recorder.schedule();
+//! assert_eq!(schedule.len(), 8);
+//!
+//! let (tx, rx) = blocking::channel::<u32>(4);
+//! schedule.replay(&tx, &rx).unwrap();
+//! ```
+//!
+//! # Differential testing
+//!
+//! A [`ModelQueue`] is a reference implementation of a bounded queue, backed
+//! by a [`VecDeque`](std::collections::VecDeque). A [`Differential`] harness
+//! applies the same sequence of [`Action`]s to a model queue and a real
+//! [`ThingBuf`](crate::ThingBuf), and reports the first action on which they
+//! [diverge](Divergence). This is useful when extending the crate, or when
+//! validating a custom [recycling policy](crate::recycling::Recycle).
+//!
+//! # Generating test cases
+//!
+//! A [`ChannelConfig`] describes a channel under test, and an [`Action`] is
+//! an operation on it. When the "arbitrary" feature flag is enabled, both
+//! implement the [`arbitrary`] crate's `Arbitrary` trait, so that they may
+//! be generated by a fuzzer. When the "proptest" feature flag is enabled,
+//! they implement [`proptest`]'s `Arbitrary` trait, and the `strategy`
+//! module provides strategies for generating sequences of actions.
+//!
+//! [hash]: Recorder::hashing
+//! [`arbitrary`]: https://crates.io/crates/arbitrary
+//! [`proptest`]: https://crates.io/crates/proptest
+mod action;
+mod model;
+mod replay;
+pub use self::action::{Action, ChannelConfig};
+pub use self::model::{Differential, Divergence, ModelQueue, Outcome};
+pub use self::replay::{Op, Recorder, RecordingReceiver, RecordingSender, ReplayError, Schedule};
+
+#[cfg(feature = "proptest")]
+pub mod strategy;
@@ -0,0 +1,143 @@
+use core::num::NonZeroUsize;
+
+/// The configuration of a channel or queue under test.
+///
+/// When the "arbitrary" or "proptest" feature flags are enabled, random
+/// configurations may be generated with the [`arbitrary`] crate's
+/// `Arbitrary` trait, or the [`proptest`] crate's `Arbitrary` trait and the
+/// strategies in the `test_util::strategy` module. Generated
+/// configurations have small capacities, so that a short sequence of
+/// [`Action`]s fills the queue and wraps around it several times.
+///
+/// [`arbitrary`]: https://crates.io/crates/arbitrary
+/// [`proptest`]: https://crates.io/crates/proptest
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::test_util::ChannelConfig;
+///
+/// let config = ChannelConfig::new(8).with_senders(2);
+/// assert_eq!(config.capacity(), 8);
+/// assert_eq!(config.senders(), 2);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChannelConfig {
+    capacity: NonZeroUsize,
+    senders: NonZeroUsize,
+}
+
+/// An operation on a channel or queue under test.
+///
+/// Sequences of actions may be generated with the [`arbitrary`] or
+/// [`proptest`] crates, when the "arbitrary" or "proptest" feature flags are
+/// enabled.
+///
+/// [`arbitrary`]: https://crates.io/crates/arbitrary
+/// [`proptest`]: https://crates.io/crates/proptest
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Action<T> {
+    /// Try to send, or push, `msg`.
+    Send {
+        /// The sender that sends the message. Senders are numbered from 0,
+        /// and this index wraps around the configured number of
+        /// [senders](ChannelConfig::senders).
+        sender: usize,
+        /// The message to send.
+        msg: T,
+    },
+    /// Try to receive, or pop, a message.
+    Recv,
+}
+
+// === impl ChannelConfig ===
+
+impl ChannelConfig {
+    /// The largest capacity of a generated configuration.
+    pub const MAX_GENERATED_CAPACITY: usize = 64;
+
+    /// The largest number of senders of a generated configuration.
+    pub const MAX_GENERATED_SENDERS: usize = 8;
+
+    /// Returns a configuration for a channel with the provided capacity, and
+    /// a single sender.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: NonZeroUsize::new(capacity).expect("capacity must be greater than 0"),
+            senders: NonZeroUsize::new(1).unwrap(),
+        }
+    }
+
+    /// Sets the number of senders.
+    ///
+    /// # Panics
+    ///
+    /// If `senders` is 0.
+    #[must_use]
+    pub fn with_senders(self, senders: usize) -> Self {
+        Self {
+            senders: NonZeroUsize::new(senders).expect("there must be at least one sender"),
+            ..self
+        }
+    }
+
+    /// Returns the channel's capacity.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    /// Returns the number of senders.
+    #[must_use]
+    pub fn senders(&self) -> usize {
+        self.senders.get()
+    }
+
+    /// Returns the index of the sender that performs `action`, if it is a
+    /// send.
+    #[must_use]
+    pub fn sender_of<T>(&self, action: &Action<T>) -> Option<usize> {
+        match action {
+            Action::Send { sender, .. } => Some(sender % self.senders()),
+            Action::Recv => None,
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ChannelConfig {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let capacity = u.int_in_range(1..=Self::MAX_GENERATED_CAPACITY)?;
+        let senders = u.int_in_range(1..=Self::MAX_GENERATED_SENDERS)?;
+        Ok(Self::new(capacity).with_senders(senders))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        arbitrary::size_hint::and(
+            <usize as arbitrary::Arbitrary>::size_hint(depth),
+            <usize as arbitrary::Arbitrary>::size_hint(depth),
+        )
+    }
+}
+
+// === impl Action ===
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: arbitrary::Arbitrary<'a>> arbitrary::Arbitrary<'a> for Action<T> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        // Send slightly more often than receiving, so that queues fill up.
+        if u.ratio(3u8, 5)? {
+            Ok(Action::Send {
+                sender: u.int_in_range(0..=ChannelConfig::MAX_GENERATED_SENDERS - 1)?,
+                msg: u.arbitrary()?,
+            })
+        } else {
+            Ok(Action::Recv)
+        }
+    }
+}
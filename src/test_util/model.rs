@@ -0,0 +1,435 @@
+use super::{Action, ChannelConfig};
+use crate::{
+    recycling::{self, Recycle},
+    Full, ThingBuf,
+};
+use alloc::collections::VecDeque;
+use core::fmt;
+
+/// A reference implementation of a bounded queue, backed by a
+/// [`VecDeque`].
+///
+/// A `ModelQueue` has the same API as a [`ThingBuf`], implemented in the
+/// most straightforward way possible, so that it obviously behaves as a
+/// bounded first-in, first-out queue should. It is meant to be compared
+/// against a real queue, using a [`Differential`] harness.
+///
+/// Unlike a [`ThingBuf`], a `ModelQueue` never reuses an element: each
+/// element is written to a new element returned by its [recycling
+/// policy]'s [`new_element`](Recycle::new_element) method, which is what a
+/// correctly recycled element must be equivalent to.
+///
+/// [recycling policy]: crate::recycling::Recycle
+pub struct ModelQueue<T, R = recycling::DefaultRecycle> {
+    items: VecDeque<T>,
+    capacity: usize,
+    recycle: R,
+}
+
+/// Applies the same [`Action`]s to a [`ModelQueue`] and a [`ThingBuf`], and
+/// checks that they behave the same.
+///
+/// After each action, the harness compares the action's [`Outcome`] in each
+/// queue, and the queues' lengths. If they differ, it returns a
+/// [`Divergence`] describing the first difference.
+///
+/// Messages may be pushed and popped by value, with [`apply`](Self::apply),
+/// or written and read in place in the queues' slots, with
+/// [`apply_with`](Self::apply_with). Using messages in place tests the
+/// queue's [recycling policy]: if it does not restore recycled elements to
+/// their initial state, the real queue's messages differ from the model's.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::{
+///     recycling::Recycle,
+///     test_util::{Action, ChannelConfig, Differential},
+/// };
+///
+/// // A recycling policy that forgets to clear recycled strings.
+/// #[derive(Clone)]
+/// struct Leaky;
+///
+/// impl Recycle<String> for Leaky {
+///     fn new_element(&self) -> String {
+///         String::new()
+///     }
+///
+///     fn recycle(&self, _: &mut String) {}
+/// }
+///
+/// let mut harness = Differential::with_recycle(ChannelConfig::new(1), Leaky);
+/// let actions = vec![
+///     Action::Send { sender: 0, msg: "hello" },
+///     Action::Recv,
+///     Action::Send { sender: 0, msg: "world" },
+///     Action::Recv,
+/// ];
+///
+/// let divergence = harness
+///     .run_with(actions, |slot: &mut String, msg| slot.push_str(msg))
+///     .unwrap_err();
+/// assert_eq!(divergence.step(), 3);
+/// ```
+///
+/// [recycling policy]: crate::recycling::Recycle
+pub struct Differential<T, R = recycling::DefaultRecycle> {
+    model: ModelQueue<T, R>,
+    real: ThingBuf<T, R>,
+    step: usize,
+}
+
+/// The result of applying an [`Action`] to a queue.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Outcome<T> {
+    /// A message was pushed.
+    Pushed,
+    /// A message could not be pushed, because the queue was full.
+    Full,
+    /// This message was popped.
+    Popped(T),
+    /// No message could be popped, because the queue was empty.
+    Empty,
+}
+
+/// Error returned by a [`Differential`] harness when the real queue behaves
+/// differently from the model.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Divergence<T> {
+    step: usize,
+    expected: (Outcome<T>, usize),
+    actual: (Outcome<T>, usize),
+}
+
+// === impl ModelQueue ===
+
+impl<T: Default + Clone> ModelQueue<T> {
+    /// Returns a new `ModelQueue` with space for `capacity` elements.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self::with_recycle(capacity, recycling::DefaultRecycle::new())
+    }
+}
+
+impl<T, R: Recycle<T>> ModelQueue<T, R> {
+    /// Returns a new `ModelQueue` with space for `capacity` elements and the
+    /// provided [recycling policy].
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    #[must_use]
+    pub fn with_recycle(capacity: usize, recycle: R) -> Self {
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+            recycle,
+        }
+    }
+
+    /// Pushes an element to the back of the queue.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the element was enqueued
+    /// - `Err(`[`Full`]`)` containing the element if the queue is at
+    ///   capacity
+    pub fn push(&mut self, val: T) -> Result<(), Full<T>> {
+        if self.is_full() {
+            return Err(Full(val));
+        }
+        self.items.push_back(val);
+        Ok(())
+    }
+
+    /// Pushes a new element to the back of the queue, and calls `f` to
+    /// write it.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(U)` with the value returned by `f`, if the element was enqueued
+    /// - `Err(`[`Full`]`)` if the queue is at capacity, in which case `f` is
+    ///   not called
+    pub fn push_with<U>(&mut self, f: impl FnOnce(&mut T) -> U) -> Result<U, Full> {
+        if self.is_full() {
+            return Err(Full(()));
+        }
+        let mut val = self.recycle.new_element();
+        let res = f(&mut val);
+        self.items.push_back(val);
+        Ok(res)
+    }
+
+    /// Pops the element at the front of the queue, or returns `None` if the
+    /// queue is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop_front()
+    }
+
+    /// Pops the element at the front of the queue, and calls `f` with it, or
+    /// returns `None` if the queue is empty.
+    pub fn pop_with<U>(&mut self, f: impl FnOnce(&mut T) -> U) -> Option<U> {
+        self.pop().map(|mut val| f(&mut val))
+    }
+}
+
+impl<T, R> ModelQueue<T, R> {
+    /// Returns the total capacity of the queue.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of elements in the queue.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if there are no elements in the queue.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns `true` if the queue is at capacity.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= self.capacity
+    }
+
+    /// Returns an iterator over the elements in the queue, from front to
+    /// back.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.items.iter()
+    }
+}
+
+impl<T: fmt::Debug, R: fmt::Debug> fmt::Debug for ModelQueue<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModelQueue")
+            .field("items", &self.items)
+            .field("capacity", &self.capacity)
+            .field("recycle", &self.recycle)
+            .finish()
+    }
+}
+
+// === impl Differential ===
+
+impl<T: Default + Clone + PartialEq> Differential<T> {
+    /// Returns a new harness for queues with the configured capacity.
+    #[must_use]
+    pub fn new(config: ChannelConfig) -> Self {
+        Self::with_recycle(config, recycling::DefaultRecycle::new())
+    }
+}
+
+impl<T, R> Differential<T, R>
+where
+    T: PartialEq + Clone,
+    R: Recycle<T> + Clone,
+{
+    /// Returns a new harness for queues with the configured capacity and the
+    /// provided [recycling policy].
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    #[must_use]
+    pub fn with_recycle(config: ChannelConfig, recycle: R) -> Self {
+        Self {
+            model: ModelQueue::with_recycle(config.capacity(), recycle.clone()),
+            real: ThingBuf::with_recycle(config.capacity(), recycle),
+            step: 0,
+        }
+    }
+
+    /// Applies `action` to both queues, pushing messages by value.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`Divergence`] if the real queue's outcome or length differs
+    /// from the model's.
+    pub fn apply(&mut self, action: Action<T>) -> Result<Outcome<T>, Divergence<T>> {
+        self.apply_inner(action, |slot, msg| *slot = msg, false)
+    }
+
+    /// Applies `action` to both queues, writing messages into the queues'
+    /// slots in place with `write`, and reading popped messages in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`Divergence`] if the real queue's outcome or length differs
+    /// from the model's.
+    pub fn apply_with<M: Clone>(
+        &mut self,
+        action: Action<M>,
+        write: impl FnMut(&mut T, M),
+    ) -> Result<Outcome<T>, Divergence<T>> {
+        self.apply_inner(action, write, true)
+    }
+
+    fn apply_inner<M: Clone>(
+        &mut self,
+        action: Action<M>,
+        mut write: impl FnMut(&mut T, M),
+        in_place: bool,
+    ) -> Result<Outcome<T>, Divergence<T>> {
+        let step = self.step;
+        self.step += 1;
+        let (expected, actual) = match action {
+            Action::Send { msg, .. } => {
+                let pushed = |res: Result<(), Full>| match res {
+                    Ok(()) => Outcome::Pushed,
+                    Err(_) => Outcome::Full,
+                };
+                let expected = pushed(self.model.push_with(|slot| write(slot, msg.clone())));
+                let actual = pushed(self.real.push_with(|slot| write(slot, msg)));
+                (expected, actual)
+            }
+            Action::Recv => {
+                let popped = |val: Option<T>| val.map_or(Outcome::Empty, Outcome::Popped);
+                let actual = if in_place {
+                    // Leave the message in its slot, so that the slot is
+                    // recycled by the next push into it.
+                    self.real.pop_with(|val| val.clone())
+                } else {
+                    self.real.pop()
+                };
+                (popped(self.model.pop()), popped(actual))
+            }
+        };
+
+        let expected = (expected, self.model.len());
+        let actual = (actual, self.real.len());
+        if expected != actual {
+            return Err(Divergence {
+                step,
+                expected,
+                actual,
+            });
+        }
+        Ok(actual.0)
+    }
+
+    /// Applies each of `actions` in turn, pushing messages by value, and
+    /// stops at the first divergence.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`Divergence`] if the real queue behaves differently from
+    /// the model.
+    pub fn run(
+        &mut self,
+        actions: impl IntoIterator<Item = Action<T>>,
+    ) -> Result<(), Divergence<T>> {
+        for action in actions {
+            self.apply(action)?;
+        }
+        Ok(())
+    }
+
+    /// Applies each of `actions` in turn, writing messages in place with
+    /// `write`, and stops at the first divergence.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`Divergence`] if the real queue behaves differently from
+    /// the model.
+    pub fn run_with<M: Clone>(
+        &mut self,
+        actions: impl IntoIterator<Item = Action<M>>,
+        mut write: impl FnMut(&mut T, M),
+    ) -> Result<(), Divergence<T>> {
+        for action in actions {
+            self.apply_with(action, &mut write)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, R> Differential<T, R> {
+    /// Returns the model queue.
+    #[must_use]
+    pub fn model(&self) -> &ModelQueue<T, R> {
+        &self.model
+    }
+
+    /// Returns the real queue.
+    #[must_use]
+    pub fn real(&self) -> &ThingBuf<T, R> {
+        &self.real
+    }
+
+    /// Returns the number of actions that have been applied.
+    #[must_use]
+    pub fn steps(&self) -> usize {
+        self.step
+    }
+}
+
+impl<T: fmt::Debug, R: fmt::Debug> fmt::Debug for Differential<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Differential")
+            .field("model", &self.model)
+            .field("real", &self.real)
+            .field("step", &self.step)
+            .finish()
+    }
+}
+
+// === impl Divergence ===
+
+impl<T> Divergence<T> {
+    /// Returns the index of the action that the queues diverged on.
+    #[must_use]
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Returns the model's outcome of the action.
+    #[must_use]
+    pub fn expected(&self) -> &Outcome<T> {
+        &self.expected.0
+    }
+
+    /// Returns the real queue's outcome of the action.
+    #[must_use]
+    pub fn actual(&self) -> &Outcome<T> {
+        &self.actual.0
+    }
+
+    /// Returns the model's length after the action.
+    #[must_use]
+    pub fn expected_len(&self) -> usize {
+        self.expected.1
+    }
+
+    /// Returns the real queue's length after the action.
+    #[must_use]
+    pub fn actual_len(&self) -> usize {
+        self.actual.1
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Divergence<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Divergence")
+            .field("step", &self.step)
+            .field("expected", &self.expected.0)
+            .field("actual", &self.actual.0)
+            .field("expected_len", &self.expected.1)
+            .field("actual_len", &self.actual.1)
+            .finish()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Display for Divergence<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "queue diverged from the model at step {}: expected {:?} (len {}), got {:?} (len {})",
+            self.step, self.expected.0, self.expected.1, self.actual.0, self.actual.1,
+        )
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for Divergence<T> {}
@@ -0,0 +1,598 @@
+use crate::{
+    loom::atomic::{AtomicUsize, Ordering::Relaxed},
+    mpsc::{
+        errors::{TryRecvError, TrySendError},
+        rt::{RtRecv, RtSend, Sealed},
+    },
+    util::mutex::{const_mutex, Mutex},
+};
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+use std::collections::hash_map::DefaultHasher;
+
+/// Records the messages sent and received through a channel's handles, in
+/// the order they took effect.
+///
+/// A `Recorder` is a handle to a shared log, and may be cloned. Handles are
+/// wrapped for recording with [`Recorder::sender`] and
+/// [`Recorder::receiver`], and the operations recorded so far are returned
+/// by [`Recorder::schedule`].
+///
+/// The `P` type parameter is the payload recorded for each message: either
+/// the message itself, for a recorder returned by [`Recorder::new`], or a
+/// hash of it, for one returned by [`Recorder::hashing`].
+///
+/// See the [module-level documentation](super) for details.
+pub struct Recorder<T, P = T> {
+    log: Arc<Log<T, P>>,
+}
+
+/// A sender whose successful sends are recorded by a [`Recorder`].
+///
+/// A `RecordingSender` may itself be used wherever a sender that
+/// [implements `RtSend`](RtSend) is expected, such as when
+/// [replaying](Schedule::replay) a schedule while recording it again.
+///
+/// This type is returned by the [`Recorder::sender`] method.
+pub struct RecordingSender<S: RtSend, P> {
+    tx: S,
+    id: usize,
+    log: Arc<Log<S::Message, P>>,
+}
+
+/// A receiver whose successful receives are recorded by a [`Recorder`].
+///
+/// Like a [`RecordingSender`], a `RecordingReceiver` implements [`RtRecv`].
+///
+/// This type is returned by the [`Recorder::receiver`] method.
+pub struct RecordingReceiver<S: RtRecv, P> {
+    rx: S,
+    log: Arc<Log<S::Message, P>>,
+}
+
+/// A recorded sequence of channel operations.
+///
+/// A schedule is usually returned by [`Recorder::schedule`], but may also be
+/// built from a `Vec` of [`Op`]s, such as one written down from a bug report.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Schedule<P> {
+    ops: Vec<Op<P>>,
+}
+
+/// A channel operation in a [`Schedule`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Op<P> {
+    /// A message was sent by the [`RecordingSender`] with this ID.
+    Send {
+        /// The ID of the sender, in the order the senders were created by
+        /// [`Recorder::sender`], starting from 0.
+        sender: usize,
+        /// The message, or its hash.
+        msg: P,
+    },
+    /// A message was received.
+    Recv {
+        /// The message, or its hash.
+        msg: P,
+    },
+}
+
+/// Error returned by [`Schedule::replay`] when the replayed channel does not
+/// behave as the recorded one did.
+#[derive(PartialEq, Eq)]
+pub enum ReplayError<T> {
+    /// The message of the operation at index `op` could not be sent.
+    Send {
+        /// The index of the operation in the schedule.
+        op: usize,
+        /// The error returned by the sender.
+        error: TrySendError<T>,
+    },
+    /// No message could be received for the operation at index `op`.
+    Recv {
+        /// The index of the operation in the schedule.
+        op: usize,
+        /// The error returned by the receiver.
+        error: TryRecvError,
+    },
+    /// A different message than the recorded one was received for the
+    /// operation at index `op`.
+    Mismatch {
+        /// The index of the operation in the schedule.
+        op: usize,
+        /// The recorded message.
+        expected: T,
+        /// The message that was received instead.
+        actual: T,
+    },
+}
+
+struct Log<T, P> {
+    ops: Mutex<Vec<Op<P>>>,
+    payload: fn(&T) -> P,
+    senders: AtomicUsize,
+}
+
+// === impl Recorder ===
+
+impl<T: Clone> Recorder<T> {
+    /// Returns a new `Recorder` that records a clone of each message.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_payload(T::clone)
+    }
+}
+
+impl<T: Hash> Recorder<T, u64> {
+    /// Returns a new `Recorder` that records a hash of each message, rather
+    /// than the message itself.
+    ///
+    /// Messages are hashed with the standard library's [`DefaultHasher`],
+    /// whose output is the same for every run of a program, but may change
+    /// between Rust releases.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{mpsc, test_util::Recorder};
+    ///
+    /// // A schedule recorded with hashes, such as one logged in production...
+    /// let recorded = {
+    ///     let (tx, rx) = mpsc::channel::<String>(4);
+    ///     let recorder = Recorder::hashing();
+    ///     let (tx, rx) = (recorder.sender(tx), recorder.receiver(rx));
+    ///     tx.try_send("hello".to_string()).unwrap();
+    ///     rx.try_recv().unwrap();
+    ///     recorder.schedule()
+    /// };
+    ///
+    /// // ...can be compared with the hashed schedule of a later run.
+    /// let (tx, rx) = mpsc::channel::<String>(4);
+    /// let recorder = Recorder::hashing();
+    /// let (tx, rx) = (recorder.sender(tx), recorder.receiver(rx));
+    /// tx.try_send("hello".to_string()).unwrap();
+    /// rx.try_recv().unwrap();
+    /// assert_eq!(recorder.schedule(), recorded);
+    /// ```
+    #[must_use]
+    pub fn hashing() -> Self {
+        Self::with_payload(hash::<T>)
+    }
+}
+
+impl<T, P> Recorder<T, P> {
+    fn with_payload(payload: fn(&T) -> P) -> Self {
+        Self {
+            log: Arc::new(Log {
+                ops: const_mutex(Vec::new()),
+                payload,
+                senders: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Wraps `tx`, so that the messages it sends are recorded.
+    ///
+    /// Each wrapped sender is given the next sender ID, starting from 0,
+    /// which identifies it in the recorded [`Op::Send`] operations.
+    pub fn sender<S>(&self, tx: S) -> RecordingSender<S, P>
+    where
+        S: RtSend<Message = T>,
+    {
+        RecordingSender {
+            tx,
+            id: self.log.senders.fetch_add(1, Relaxed),
+            log: self.log.clone(),
+        }
+    }
+
+    /// Wraps `rx`, so that the messages it receives are recorded.
+    pub fn receiver<S>(&self, rx: S) -> RecordingReceiver<S, P>
+    where
+        S: RtRecv<Message = T>,
+    {
+        RecordingReceiver {
+            rx,
+            log: self.log.clone(),
+        }
+    }
+
+    /// Returns the operations that have been recorded so far.
+    #[must_use]
+    pub fn schedule(&self) -> Schedule<P>
+    where
+        P: Clone,
+    {
+        Schedule {
+            ops: self.log.ops.lock().clone(),
+        }
+    }
+}
+
+impl<T: Clone> Default for Recorder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, P> Clone for Recorder<T, P> {
+    fn clone(&self) -> Self {
+        Self {
+            log: self.log.clone(),
+        }
+    }
+}
+
+impl<T, P> fmt::Debug for Recorder<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("ops", &self.log.ops.lock().len())
+            .field("senders", &self.log.senders.load(Relaxed))
+            .finish()
+    }
+}
+
+// === impl RecordingSender ===
+
+impl<S: RtSend, P> RecordingSender<S, P> {
+    /// Attempts to send a message by value, without waiting for capacity,
+    /// and records it if it was sent.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TrySendError::Full`]`)` if the channel is at capacity.
+    /// - [`Err`]`(`[`TrySendError::Closed`]`)` if the receiver has been
+    ///   dropped.
+    ///
+    /// In both cases, the error includes the message.
+    pub fn try_send(&self, val: S::Message) -> Result<(), TrySendError<S::Message>> {
+        let msg = (self.log.payload)(&val);
+        // Hold the lock while sending, so that the operation is recorded in
+        // the same order as the channel observes it.
+        let mut ops = self.log.ops.lock();
+        self.tx.try_send(val)?;
+        ops.push(Op::Send {
+            sender: self.id,
+            msg,
+        });
+        Ok(())
+    }
+
+    /// Attempts to claim a slot in the channel without waiting for capacity,
+    /// calls `f` to write the message in place, and records it.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TrySendError::Full`]`)` if the channel is at capacity.
+    /// - [`Err`]`(`[`TrySendError::Closed`]`)` if the receiver has been
+    ///   dropped.
+    ///
+    /// In both cases, `f` is not called.
+    pub fn try_send_with<F>(&self, f: F) -> Result<(), TrySendError>
+    where
+        F: FnOnce(&mut S::Message),
+    {
+        let payload = self.log.payload;
+        let mut ops = self.log.ops.lock();
+        let mut msg = None;
+        self.tx.try_send_with(|val| {
+            f(val);
+            msg = Some(payload(val));
+        })?;
+        if let Some(msg) = msg {
+            ops.push(Op::Send {
+                sender: self.id,
+                msg,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the ID that identifies this sender in the recorded
+    /// [`Op::Send`] operations.
+    #[must_use]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Unwraps this `RecordingSender`, returning the underlying sender.
+    pub fn into_inner(self) -> S {
+        self.tx
+    }
+}
+
+impl<S: RtSend + Clone, P> Clone for RecordingSender<S, P> {
+    /// Clones the underlying sender, and gives the clone the next sender ID.
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            id: self.log.senders.fetch_add(1, Relaxed),
+            log: self.log.clone(),
+        }
+    }
+}
+
+impl<S: RtSend + fmt::Debug, P> fmt::Debug for RecordingSender<S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingSender")
+            .field("tx", &self.tx)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl<S: RtSend, P> Sealed for RecordingSender<S, P> {}
+
+impl<S: RtSend, P> RtSend for RecordingSender<S, P> {
+    type Message = S::Message;
+
+    #[inline]
+    fn try_send(&self, val: S::Message) -> Result<(), TrySendError<S::Message>> {
+        RecordingSender::try_send(self, val)
+    }
+
+    #[inline]
+    fn try_send_with<F>(&self, f: F) -> Result<(), TrySendError>
+    where
+        F: FnOnce(&mut S::Message),
+    {
+        RecordingSender::try_send_with(self, f)
+    }
+}
+
+// === impl RecordingReceiver ===
+
+impl<S: RtRecv, P> RecordingReceiver<S, P> {
+    /// Attempts to receive the next message by value, without waiting for
+    /// one to be sent, and records it if one was received.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if there are no messages in the
+    ///   channel.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if every sender has been
+    ///   dropped and the channel is empty.
+    pub fn try_recv(&self) -> Result<S::Message, TryRecvError> {
+        let mut ops = self.log.ops.lock();
+        let val = self.rx.try_recv()?;
+        ops.push(Op::Recv {
+            msg: (self.log.payload)(&val),
+        });
+        Ok(val)
+    }
+
+    /// Attempts to receive the next message without waiting for one to be
+    /// sent, records it, and calls `f` with a mutable reference to it in
+    /// place.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`TryRecvError::Empty`]`)` if there are no messages in the
+    ///   channel.
+    /// - [`Err`]`(`[`TryRecvError::Closed`]`)` if every sender has been
+    ///   dropped and the channel is empty.
+    ///
+    /// In both cases, `f` is not called.
+    pub fn try_recv_with<U>(
+        &self,
+        f: impl FnOnce(&mut S::Message) -> U,
+    ) -> Result<U, TryRecvError> {
+        let payload = self.log.payload;
+        let mut ops = self.log.ops.lock();
+        self.rx.try_recv_with(|val| {
+            ops.push(Op::Recv { msg: payload(val) });
+            f(val)
+        })
+    }
+
+    /// Unwraps this `RecordingReceiver`, returning the underlying receiver.
+    pub fn into_inner(self) -> S {
+        self.rx
+    }
+}
+
+impl<S: RtRecv + fmt::Debug, P> fmt::Debug for RecordingReceiver<S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingReceiver")
+            .field("rx", &self.rx)
+            .finish()
+    }
+}
+
+impl<S: RtRecv, P> Sealed for RecordingReceiver<S, P> {}
+
+impl<S: RtRecv, P> RtRecv for RecordingReceiver<S, P> {
+    type Message = S::Message;
+
+    #[inline]
+    fn try_recv(&self) -> Result<S::Message, TryRecvError> {
+        RecordingReceiver::try_recv(self)
+    }
+
+    #[inline]
+    fn try_recv_with<U>(&self, f: impl FnOnce(&mut S::Message) -> U) -> Result<U, TryRecvError> {
+        RecordingReceiver::try_recv_with(self, f)
+    }
+}
+
+// === impl Schedule ===
+
+impl<P> Schedule<P> {
+    /// Returns the recorded operations, in the order they took effect.
+    #[must_use]
+    pub fn ops(&self) -> &[Op<P>] {
+        &self.ops[..]
+    }
+
+    /// Returns the number of recorded operations.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if no operations were recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+impl<T: Hash> Schedule<T> {
+    /// Returns a schedule of the [hashes](Recorder::hashing) of this
+    /// schedule's messages, to compare with a schedule recorded with hashes.
+    #[must_use]
+    pub fn hashed(&self) -> Schedule<u64> {
+        let ops = self
+            .ops
+            .iter()
+            .map(|op| match op {
+                Op::Send { sender, msg } => Op::Send {
+                    sender: *sender,
+                    msg: hash(msg),
+                },
+                Op::Recv { msg } => Op::Recv { msg: hash(msg) },
+            })
+            .collect();
+        Schedule { ops }
+    }
+}
+
+impl<T> Schedule<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Replays this schedule against a fresh channel, from the current
+    /// thread.
+    ///
+    /// Each recorded send is performed with `tx`, and each recorded receive
+    /// with `rx`, in the recorded order. Every sender's messages are sent
+    /// with `tx`. The channel should have the same capacity as the recorded
+    /// one, and be empty.
+    ///
+    /// # Errors
+    ///
+    /// - [`Err`]`(`[`ReplayError::Send`]`)` if a recorded message could not
+    ///   be sent.
+    /// - [`Err`]`(`[`ReplayError::Recv`]`)` if no message could be received
+    ///   where one was recorded.
+    /// - [`Err`]`(`[`ReplayError::Mismatch`]`)` if a different message than
+    ///   the recorded one was received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{mpsc, test_util::{Op, ReplayError, Schedule}};
+    ///
+    /// // A schedule that a bug report says the channel produced.
+    /// let schedule = Schedule::from(vec![
+    ///     Op::Send { sender: 0, msg: 1 },
+    ///     Op::Send { sender: 1, msg: 2 },
+    ///     Op::Recv { msg: 2 },
+    /// ]);
+    ///
+    /// let (tx, rx) = mpsc::channel::<u32>(4);
+    /// assert_eq!(
+    ///     schedule.replay(&tx, &rx),
+    ///     Err(ReplayError::Mismatch { op: 2, expected: 2, actual: 1 }),
+    /// );
+    /// ```
+    pub fn replay<S, R>(&self, tx: &S, rx: &R) -> Result<(), ReplayError<T>>
+    where
+        S: RtSend<Message = T>,
+        R: RtRecv<Message = T>,
+    {
+        for (op, recorded) in self.ops.iter().enumerate() {
+            match recorded {
+                Op::Send { msg, .. } => tx
+                    .try_send(msg.clone())
+                    .map_err(|error| ReplayError::Send { op, error })?,
+                Op::Recv { msg } => {
+                    let actual = rx
+                        .try_recv()
+                        .map_err(|error| ReplayError::Recv { op, error })?;
+                    if &actual != msg {
+                        return Err(ReplayError::Mismatch {
+                            op,
+                            expected: msg.clone(),
+                            actual,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<P> From<Vec<Op<P>>> for Schedule<P> {
+    fn from(ops: Vec<Op<P>>) -> Self {
+        Self { ops }
+    }
+}
+
+impl<P> IntoIterator for Schedule<P> {
+    type Item = Op<P>;
+    type IntoIter = alloc::vec::IntoIter<Op<P>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ops.into_iter()
+    }
+}
+
+// === impl ReplayError ===
+
+impl<T: fmt::Debug> fmt::Debug for ReplayError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Send { op, error } => f
+                .debug_struct("Send")
+                .field("op", op)
+                .field("error", error)
+                .finish(),
+            Self::Recv { op, error } => f
+                .debug_struct("Recv")
+                .field("op", op)
+                .field("error", error)
+                .finish(),
+            Self::Mismatch {
+                op,
+                expected,
+                actual,
+            } => f
+                .debug_struct("Mismatch")
+                .field("op", op)
+                .field("expected", expected)
+                .field("actual", actual)
+                .finish(),
+        }
+    }
+}
+
+impl<T> fmt::Display for ReplayError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Send { op, error } => {
+                write!(f, "replaying operation {} failed to send: {}", op, error)
+            }
+            Self::Recv { op, error } => {
+                write!(f, "replaying operation {} failed to receive: {}", op, error)
+            }
+            Self::Mismatch { op, .. } => write!(
+                f,
+                "replaying operation {} received a different message than was recorded",
+                op
+            ),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for ReplayError<T> {}
+
+fn hash<T: Hash>(msg: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    msg.hash(&mut hasher);
+    hasher.finish()
+}
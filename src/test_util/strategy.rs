@@ -0,0 +1,112 @@
+//! [`proptest`] strategies for generating channel configurations and
+//! sequences of [`Action`]s.
+//!
+//! These strategies may be used to property-test code that uses `thingbuf`,
+//! by applying the generated actions to a channel and checking that the code
+//! upholds its invariants after each one. [`ChannelConfig`] and [`Action`]
+//! also implement proptest's `Arbitrary` trait, so they may be generated
+//! with [`any`](proptest::prelude::any).
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use thingbuf::{test_util::{strategy, Action}, ThingBuf};
+//!
+//! proptest! {
+//!     fn len_never_exceeds_capacity(
+//!         (config, actions) in strategy::config_and_actions(any::<u8>(), 0..128),
+//!     ) {
+//!         let q = ThingBuf::new(config.capacity());
+//!         for action in actions {
+//!             match action {
+//!                 Action::Send { msg, .. } => { let _ = q.push(msg); }
+//!                 Action::Recv => { let _ = q.pop(); }
+//!             }
+//!             prop_assert!(q.len() <= q.capacity());
+//!         }
+//!     }
+//! }
+//! # len_never_exceeds_capacity();
+//! ```
+//!
+//! [`proptest`]: https://crates.io/crates/proptest
+use super::{Action, ChannelConfig};
+use alloc::vec::Vec;
+use core::fmt;
+use proptest::{
+    arbitrary::{any_with, Arbitrary},
+    collection::{self, SizeRange},
+    prop_oneof,
+    strategy::{BoxedStrategy, Just, Strategy},
+};
+
+/// Returns a strategy that generates [`ChannelConfig`]s with capacities of
+/// up to [`ChannelConfig::MAX_GENERATED_CAPACITY`], and up to
+/// [`ChannelConfig::MAX_GENERATED_SENDERS`] senders.
+pub fn config() -> impl Strategy<Value = ChannelConfig> {
+    (
+        1..=ChannelConfig::MAX_GENERATED_CAPACITY,
+        1..=ChannelConfig::MAX_GENERATED_SENDERS,
+    )
+        .prop_map(|(capacity, senders)| ChannelConfig::new(capacity).with_senders(senders))
+}
+
+/// Returns a strategy that generates sequences of [`Action`]s for a channel
+/// with the provided configuration, with messages generated by `msg`, and
+/// lengths in the `len` range.
+///
+/// Every generated [`Action::Send`] uses one of the configured senders.
+/// Sends are generated slightly more often than receives, so that the
+/// channel fills up.
+pub fn actions<S>(
+    config: ChannelConfig,
+    msg: S,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<Action<S::Value>>>
+where
+    S: Strategy,
+    S::Value: Clone,
+{
+    let send = (0..config.senders(), msg).prop_map(|(sender, msg)| Action::Send { sender, msg });
+    collection::vec(prop_oneof![3 => send, 2 => Just(Action::Recv)], len)
+}
+
+/// Returns a strategy that generates a [`ChannelConfig`], and a sequence of
+/// [`Action`]s for it.
+///
+/// This combines the [`config`] and [`actions`] strategies.
+pub fn config_and_actions<S>(
+    msg: S,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = (ChannelConfig, Vec<Action<S::Value>>)>
+where
+    S: Strategy + Clone,
+    S::Value: Clone,
+{
+    let len = len.into();
+    config().prop_flat_map(move |config| (Just(config), actions(config, msg.clone(), len.clone())))
+}
+
+impl Arbitrary for ChannelConfig {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        config().boxed()
+    }
+}
+
+impl<T> Arbitrary for Action<T>
+where
+    T: Arbitrary + Clone + fmt::Debug + 'static,
+{
+    type Parameters = T::Parameters;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: T::Parameters) -> Self::Strategy {
+        let send = (0..ChannelConfig::MAX_GENERATED_SENDERS, any_with::<T>(args))
+            .prop_map(|(sender, msg)| Action::Send { sender, msg });
+        prop_oneof![3 => send, 2 => Just(Action::Recv)].boxed()
+    }
+}
@@ -1,10 +1,25 @@
-use crate::{recycling::{self, Recycle}, Core, Full, Ref, Slot, MAX_CAPACITY};
-use alloc::boxed::Box;
+#[cfg(all(feature = "std", not(all(loom, test))))]
+use crate::{
+    loom::atomic::{AtomicUsize, Ordering::*},
+    mpsc::blocking::park::Unparker,
+    wait::WaitQueue,
+};
+use crate::{
+    recycling::{self, Recycle},
+    Core, Frozen, Full, Ref, Slot, Slots, MAX_CAPACITY,
+};
+use alloc::{boxed::Box, vec::Vec};
 use core::fmt;
 
+mod builder;
+mod split;
 #[cfg(all(loom, test))]
 mod tests;
 
+use self::builder::SlotStorage;
+pub use self::builder::{SlotLayout, ThingBufBuilder};
+pub use self::split::{Consumer, Producer};
+
 /// A fixed-size, lock-free, multi-producer multi-consumer (MPMC) queue.
 ///
 /// This is a fixed-capacity, first-in, first-out data structure. Elements are
@@ -179,8 +194,57 @@ mod tests;
 #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 pub struct ThingBuf<T, R = recycling::DefaultRecycle> {
     pub(crate) core: Core,
-    pub(crate) slots: Box<[Slot<T>]>,
-    recycle: R,
+    pub(crate) slots: SlotStorage<T>,
+    pub(crate) recycle: R,
+    /// Computes the checksums of elements, if they are integrity checked.
+    #[cfg(feature = "high-integrity")]
+    pub(crate) checksum: Option<fn(&T) -> u64>,
+    /// Threads waiting for a slot to be freed.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    pub(crate) push_wait: WaitQueue<Unparker>,
+    /// Threads waiting for an element to be pushed.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    pub(crate) pop_wait: WaitQueue<Unparker>,
+    /// Whether pushes and pops wake blocked threads. They only do once a
+    /// thread has blocked on the queue, so that queues which are never
+    /// blocked on don't pay for it.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    pub(crate) wake: AtomicUsize,
+}
+
+/// No thread has blocked on the queue yet.
+#[cfg(all(feature = "std", not(all(loom, test))))]
+pub(crate) const WAKE_NONE: usize = 0;
+/// A thread is about to block on the queue, and is waiting for the pushes
+/// that started before it did, which may not wake it, to complete.
+#[cfg(all(feature = "std", not(all(loom, test))))]
+const WAKE_ARMING: usize = 1;
+/// Every push and pop that is still in progress wakes blocked threads.
+#[cfg(all(feature = "std", not(all(loom, test))))]
+const WAKE_ARMED: usize = 2;
+
+/// An owning iterator over the elements remaining in a [`ThingBuf`].
+///
+/// This type is returned by the [`IntoIterator`] implementation for
+/// [`ThingBuf`]. Elements are yielded in first-in, first-out order, exactly as
+/// they would be returned by [`ThingBuf::pop`].
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct IntoIter<T, R = recycling::DefaultRecycle> {
+    buf: ThingBuf<T, R>,
+}
+
+/// An iterator over copies of the elements in a [`ThingBuf`] when the
+/// snapshot was taken.
+///
+/// This type is returned by [`ThingBuf::iter_snapshot`].
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct SnapshotIter<'a, T> {
+    core: &'a Core,
+    slots: &'a SlotStorage<T>,
+    pos: usize,
+    end: usize,
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    pop_wait: &'a WaitQueue<Unparker>,
 }
 
 // === impl ThingBuf ===
@@ -193,6 +257,25 @@ impl<T: Default + Clone> ThingBuf<T> {
     }
 }
 
+impl<T> ThingBuf<T> {
+    /// Returns a [`ThingBufBuilder`] for a `ThingBuf` with space for
+    /// `capacity` elements, which can be used to configure how the
+    /// `ThingBuf`'s slot array is allocated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    ///
+    /// let q = ThingBuf::<usize>::builder(1024).align(4096).build();
+    /// assert_eq!(q.capacity(), 1024);
+    /// ```
+    #[must_use]
+    pub fn builder(capacity: usize) -> ThingBufBuilder<T> {
+        ThingBufBuilder::new(capacity)
+    }
+}
+
 impl<T, R> ThingBuf<T, R> {
     /// Returns the *total* capacity of this queue. This includes both
     /// occupied and unoccupied entries.
@@ -288,6 +371,231 @@ impl<T, R> ThingBuf<T, R> {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Returns a snapshot of the statistics recorded for this `ThingBuf`.
+    ///
+    /// See the [`stats`](crate::stats) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    ///
+    /// let q = ThingBuf::new(8);
+    /// for i in 0..4 {
+    ///     q.push(i).unwrap();
+    /// }
+    ///
+    /// let stats = q.stats();
+    /// assert_eq!(stats.occupancy().samples(), 4);
+    /// ```
+    #[cfg(feature = "stats")]
+    #[must_use]
+    pub fn stats(&self) -> crate::stats::Stats {
+        self.core.stats()
+    }
+
+    /// Returns a snapshot of the state of each slot in the queue: whether it
+    /// is empty, holds an element, or is claimed by a [`Ref`] that has not
+    /// yet been dropped.
+    ///
+    /// As other threads may push and pop concurrently, the snapshot is only
+    /// approximate while the queue is in use. See [`SlotStates`] for
+    /// details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{stats::SlotState, ThingBuf};
+    ///
+    /// let q = ThingBuf::new(4);
+    /// for i in 0..3 {
+    ///     q.push(i).unwrap();
+    /// }
+    /// let reading = q.pop_ref().unwrap();
+    ///
+    /// assert_eq!(q.slot_states().to_string(), "~##.");
+    ///
+    /// drop(reading);
+    /// let states = q.slot_states();
+    /// assert_eq!(states.to_string(), ".##.");
+    /// assert_eq!(states.count(SlotState::Claimed), 0);
+    /// ```
+    ///
+    /// [`SlotStates`]: crate::stats::SlotStates
+    #[cfg(feature = "stats")]
+    #[must_use]
+    pub fn slot_states(&self) -> crate::stats::SlotStates {
+        self.core.slot_states(&self.slots)
+    }
+
+    /// Returns an iterator over copies of the elements that are in the queue
+    /// at the time this method is called, in first-in, first-out order.
+    ///
+    /// The elements are not removed from the queue, and other threads may
+    /// continue to push and pop elements while the snapshot is iterated. An
+    /// element that is popped before the iterator reaches it is skipped, as
+    /// are elements pushed after the snapshot was taken. This makes it
+    /// possible for debugging or metrics code to inspect a live queue.
+    ///
+    /// The iterator never waits for other threads. Each element is pinned in
+    /// its slot while it is copied out, so that it cannot be popped or
+    /// overwritten while it is being read, and a pop that reaches it in the
+    /// meantime treats it as not yet pushed. The element at the head of the
+    /// queue may be popped at any moment, so it cannot be pinned, and is
+    /// skipped too, as are elements that are being pushed or popped when the
+    /// iterator reaches them. Elements are copied, so this requires `T: Copy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    ///
+    /// let q = ThingBuf::new(8);
+    /// for i in 0..5 {
+    ///     q.push(i).unwrap();
+    /// }
+    ///
+    /// // The element at the head of the queue is skipped.
+    /// let mut snapshot = q.iter_snapshot();
+    /// assert_eq!(snapshot.next(), Some(1));
+    ///
+    /// // Elements popped before the snapshot reaches them are skipped, as is
+    /// // the element that is at the head of the queue by then...
+    /// assert_eq!(q.pop(), Some(0));
+    /// assert_eq!(q.pop(), Some(1));
+    /// assert_eq!(q.pop(), Some(2));
+    /// // ...and elements pushed after it was taken are not included.
+    /// q.push(5).unwrap();
+    ///
+    /// assert_eq!(snapshot.collect::<Vec<_>>(), vec![4]);
+    /// ```
+    pub fn iter_snapshot(&self) -> SnapshotIter<'_, T>
+    where
+        T: Copy,
+    {
+        let (pos, end) = self.core.snapshot_bounds();
+        SnapshotIter {
+            core: &self.core,
+            slots: &self.slots,
+            pos,
+            end,
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            pop_wait: &self.pop_wait,
+        }
+    }
+
+    /// Returns a read-only [`Frozen`] view of the elements in the queue, which
+    /// may be iterated from a panic hook or other fatal-error path.
+    ///
+    /// Iterating over the view copies each element out of the queue without
+    /// waiting for other threads or allocating, so it can be used to dump the
+    /// last elements that were queued before a crash. The element at the head
+    /// of the queue is skipped. See [`Frozen`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    /// use std::{panic, sync::Arc};
+    ///
+    /// let events = Arc::new(ThingBuf::new(64));
+    ///
+    /// let hook_events = events.clone();
+    /// panic::set_hook(Box::new(move |info| {
+    ///     eprintln!("{}", info);
+    ///     eprintln!("last queued events:");
+    ///     for event in hook_events.freeze().newest(16) {
+    ///         eprintln!("  {}", event);
+    ///     }
+    /// }));
+    ///
+    /// events.push("connected").unwrap();
+    /// events.push("request received").unwrap();
+    /// events.push("response sent").unwrap();
+    ///
+    /// // The oldest event, at the head of the queue, is skipped.
+    /// let events = events.freeze().collect::<Vec<_>>();
+    /// assert_eq!(events, vec!["request received", "response sent"]);
+    /// # drop(panic::take_hook());
+    /// ```
+    pub fn freeze(&self) -> Frozen<'_, T>
+    where
+        T: Copy,
+    {
+        let frozen = Frozen::new(&self.core, &self.slots);
+        #[cfg(all(feature = "std", not(all(loom, test))))]
+        let frozen = frozen.with_wake(&self.pop_wait);
+        frozen
+    }
+
+    pub(crate) fn from_parts(core: Core, slots: Box<[Slot<T>]>, recycle: R) -> Self {
+        Self {
+            core,
+            slots: slots.into(),
+            recycle,
+            #[cfg(feature = "high-integrity")]
+            checksum: None,
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            push_wait: WaitQueue::new(),
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            pop_wait: WaitQueue::new(),
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            wake: AtomicUsize::new(WAKE_NONE),
+        }
+    }
+
+    /// Wakes up to `n` threads that are blocked waiting to push, after `n`
+    /// elements were popped without going through [`Ref`]s.
+    #[inline]
+    fn notify_popped(&self, _n: usize) {
+        #[cfg(all(feature = "std", not(all(loom, test))))]
+        self.push_wait.notify_n(_n);
+    }
+
+    /// Makes `slot` wake a thread blocked on `queue` once it is released, if
+    /// a thread has ever blocked on this queue.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    #[inline]
+    fn with_wake<'a>(&self, slot: Ref<'a, T>, queue: &'a WaitQueue<Unparker>) -> Ref<'a, T> {
+        // The slot was claimed with `SeqCst` operations, so if this load
+        // doesn't see that a thread is about to block, that thread sees the
+        // claim: `arm_wake` waits for the push to complete, and a blocked
+        // push sees that the popped slot is free.
+        if self.wake.load(SeqCst) == WAKE_NONE {
+            return slot;
+        }
+        slot.with_wake(queue)
+    }
+
+    /// Makes pushes and pops wake blocked threads, before the current thread
+    /// blocks on the queue.
+    ///
+    /// Pushes that were already in progress the first time a thread blocked
+    /// on the queue won't wake it, so this waits for them to complete. Pops
+    /// that were in progress don't matter, as a push skips over a slot that
+    /// is still being popped from, rather than waiting for it.
+    #[cfg(all(feature = "std", not(all(loom, test))))]
+    fn arm_wake(&self) {
+        if self.wake.load(Acquire) == WAKE_ARMED {
+            return;
+        }
+        let _ = self
+            .wake
+            .compare_exchange(WAKE_NONE, WAKE_ARMING, SeqCst, SeqCst);
+        self.core.wait_for_pushes(&self.slots);
+        self.wake.store(WAKE_ARMED, Release);
+    }
+
+    /// Checks the integrity of a popped element, if this queue has a
+    /// checksum function.
+    #[inline]
+    fn verify(&self, _slot: &Ref<'_, T>) {
+        #[cfg(feature = "high-integrity")]
+        if let Some(checksum) = self.checksum {
+            _slot.verify_checksum(checksum);
+        }
+    }
 }
 
 impl<T, R> ThingBuf<T, R>
@@ -310,11 +618,124 @@ where
         assert!(capacity <= MAX_CAPACITY);
         Self {
             core: Core::new(capacity),
-            slots: Slot::make_boxed_array(capacity),
+            slots: Slot::make_boxed_array(capacity).into(),
+            recycle,
+            #[cfg(feature = "high-integrity")]
+            checksum: None,
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            push_wait: WaitQueue::new(),
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            pop_wait: WaitQueue::new(),
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            wake: AtomicUsize::new(WAKE_NONE),
+        }
+    }
+
+    /// Returns a new `ThingBuf` with space for `capacity` elements and the
+    /// provided [recycling policy], whose slot array is allocated by the
+    /// `place` callback.
+    ///
+    /// `place` is called with the capacity, and must return a new array
+    /// created by [`Slot::make_boxed_array`]. This allows controlling *where*
+    /// the slot array is allocated: for example, on a multi-socket system,
+    /// placing the array on the same NUMA node as the threads that push and
+    /// pop avoids cross-node cache coherence traffic on every operation.
+    ///
+    /// # Panics
+    ///
+    /// - If the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`.
+    /// - If `place` returns an array whose length is not `capacity`, or an
+    ///   array that has already been used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{recycling::DefaultRecycle, Slot, ThingBuf};
+    /// use std::thread;
+    ///
+    /// let q = ThingBuf::<String>::with_placement(1024, DefaultRecycle::new(), |capacity| {
+    ///     // On Linux, memory is placed on the NUMA node of the thread that
+    ///     // first writes to it, so building the slot array on a thread that
+    ///     // is pinned to the desired node allocates it on that node.
+    ///     thread::spawn(move || Slot::make_boxed_array(capacity))
+    ///         .join()
+    ///         .unwrap()
+    /// });
+    ///
+    /// q.push(String::from("hello")).unwrap();
+    /// assert_eq!(q.pop().as_deref(), Some("hello"));
+    /// ```
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    #[must_use]
+    pub fn with_placement(
+        capacity: usize,
+        recycle: R,
+        place: impl FnOnce(usize) -> Box<[Slot<T>]>,
+    ) -> Self {
+        assert!(capacity > 0);
+        assert!(capacity <= MAX_CAPACITY);
+        // place the slots first, so that a `Core` is not dropped without its
+        // slots if `place` panics.
+        let slots = Slot::place_boxed_array(capacity, place).into();
+        Self {
+            core: Core::new(capacity),
+            slots,
             recycle,
+            #[cfg(feature = "high-integrity")]
+            checksum: None,
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            push_wait: WaitQueue::new(),
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            pop_wait: WaitQueue::new(),
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            wake: AtomicUsize::new(WAKE_NONE),
         }
     }
 
+    /// Eagerly initializes every slot in the queue, by calling the
+    /// [recycling policy]'s [`new_element`] method for each slot that has not
+    /// been pushed to yet.
+    ///
+    /// Slots are normally initialized lazily, the first time an element is
+    /// pushed to them, so the first lap around the ring pays the cost of
+    /// creating each element. Once the slots have been pre-filled, every push
+    /// [recycles] an existing element instead. With a policy such as
+    /// [`WithCapacity`], which creates `String`s and `Vec`s with a minimum
+    /// capacity, this moves all of those allocations to when the queue is
+    /// created, rather than when it is first used.
+    ///
+    /// Calling this more than once has no effect. A `ThingBuf` may also be
+    /// pre-filled when it is built, with [`ThingBufBuilder::pre_fill`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{recycling::WithCapacity, ThingBuf};
+    ///
+    /// let mut q = ThingBuf::<String, _>::with_recycle(
+    ///     64,
+    ///     WithCapacity::new().with_min_capacity(256),
+    /// );
+    /// // Allocate all 64 strings now, rather than on the hot path.
+    /// q.pre_fill();
+    ///
+    /// let mut slot = q.push_ref().unwrap();
+    /// assert!(slot.capacity() >= 256);
+    /// slot.push_str("hello");
+    /// drop(slot);
+    ///
+    /// assert_eq!(q.pop_ref().unwrap().as_str(), "hello");
+    /// ```
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    /// [`new_element`]: crate::recycling::Recycle::new_element
+    /// [recycles]: crate::recycling::Recycle::recycle
+    /// [`WithCapacity`]: crate::recycling::WithCapacity
+    pub fn pre_fill(&mut self) {
+        self.core.pre_fill(&mut self.slots, &self.recycle);
+    }
+
     /// Reserves a slot to push an element into the queue, returning a [`Ref`] that
     /// can be used to write to that slot.
     ///
@@ -390,12 +811,18 @@ where
     /// [`pop_ref`]: Self::pop_ref
     /// [`push`]: Self::push_ref
     pub fn push_ref(&self) -> Result<Ref<'_, T>, Full> {
-        self.core
+        let slot = self
+            .core
             .push_ref(&self.slots, &self.recycle)
             .map_err(|e| match e {
                 crate::mpsc::errors::TrySendError::Full(()) => Full(()),
                 _ => unreachable!(),
-            })
+            })?;
+        #[cfg(feature = "high-integrity")]
+        let slot = slot.with_checksum(self.checksum);
+        #[cfg(all(feature = "std", not(all(loom, test))))]
+        let slot = self.with_wake(slot, &self.pop_wait);
+        Ok(slot)
     }
 
     /// Attempt to enqueue an element by value.
@@ -463,7 +890,11 @@ where
     /// [`push`]: Self::push
     /// [`pop`]: Self::pop
     pub fn pop_ref(&self) -> Option<Ref<'_, T>> {
-        self.core.pop_ref(&self.slots).ok()
+        let slot = self.core.pop_ref(&self.slots).ok()?;
+        self.verify(&slot);
+        #[cfg(all(feature = "std", not(all(loom, test))))]
+        let slot = self.with_wake(slot, &self.push_wait);
+        Some(slot)
     }
 
     /// Dequeue the first element in the queue *by value*, moving it out of the
@@ -495,11 +926,475 @@ where
     pub fn pop_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Option<U> {
         self.pop_ref().map(|mut r| r.with_mut(f))
     }
+
+    /// Dequeues as many elements as fit into `out`, copying them into it in
+    /// order, and returns the number of elements dequeued.
+    ///
+    /// All of the elements at the head of the queue that are ready to be read
+    /// are claimed at once, so this is cheaper than calling [`pop`]
+    /// repeatedly. Elements that are still being written are left in the
+    /// queue, so this may dequeue fewer elements than [`len`] reports.
+    ///
+    /// # Returns
+    ///
+    /// - The number of elements copied into `out`, which is 0 if the queue is
+    ///   empty or `out` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    ///
+    /// let q = ThingBuf::new(8);
+    /// for i in 0..5 {
+    ///     q.push(i).unwrap();
+    /// }
+    ///
+    /// let mut samples = [0; 4];
+    /// assert_eq!(q.pop_into(&mut samples), 4);
+    /// assert_eq!(samples, [0, 1, 2, 3]);
+    ///
+    /// assert_eq!(q.pop_into(&mut samples), 1);
+    /// assert_eq!(samples[0], 4);
+    /// assert_eq!(q.pop_into(&mut samples), 0);
+    /// ```
+    ///
+    /// [`pop`]: Self::pop
+    /// [`len`]: Self::len
+    pub fn pop_into(&self, out: &mut [T]) -> usize
+    where
+        T: Copy,
+    {
+        #[cfg(feature = "high-integrity")]
+        if self.checksum.is_some() && !out.is_empty() {
+            // Verify each element as it is copied out of its slot.
+            let mut dst = out.iter_mut();
+            let n = self
+                .core
+                .pop_n_with(&self.slots, dst.len(), |slot| {
+                    self.verify(&slot);
+                    if let Some(dst) = dst.next() {
+                        *dst = *slot;
+                    }
+                })
+                .unwrap_or(0);
+            self.notify_popped(n);
+            return n;
+        }
+        let n = self.core.pop_into(&self.slots, out).unwrap_or(0);
+        self.notify_popped(n);
+        n
+    }
+
+    /// Dequeues every element that is ready to be read, moving them into
+    /// `out` in order, and returns the number of elements dequeued.
+    ///
+    /// Like [`pop_into`], this claims all of the elements at the head of the
+    /// queue with a single update to the head index, so it is much cheaper
+    /// than calling [`pop`] in a loop when flushing the queue. Elements that
+    /// are still being written are left in the queue. Each element is taken
+    /// out of its slot according to the queue's [recycling policy], so that
+    /// the slot can be reused.
+    ///
+    /// # Returns
+    ///
+    /// - The number of elements appended to `out`, which is 0 if the queue is
+    ///   empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    ///
+    /// let q = ThingBuf::new(8);
+    /// for i in 0..5 {
+    ///     q.push(format!("line {}", i)).unwrap();
+    /// }
+    ///
+    /// let mut batch = Vec::new();
+    /// assert_eq!(q.pop_all(&mut batch), 5);
+    /// assert_eq!(batch[0], "line 0");
+    /// assert_eq!(batch[4], "line 4");
+    /// assert!(q.is_empty());
+    ///
+    /// // Flushing an empty queue does nothing.
+    /// assert_eq!(q.pop_all(&mut batch), 0);
+    /// assert_eq!(batch.len(), 5);
+    /// ```
+    ///
+    /// [`pop_into`]: Self::pop_into
+    /// [`pop`]: Self::pop
+    /// [recycling policy]: crate::recycling::Recycle
+    pub fn pop_all(&self, out: &mut Vec<T>) -> usize {
+        // Reserve space up front, so that `out` isn't reallocated while the
+        // slots are claimed.
+        out.reserve(self.len());
+        let recycle = &self.recycle;
+        let n = self
+            .core
+            .pop_n_with(&self.slots, self.capacity(), |mut slot| {
+                self.verify(&slot);
+                out.push(recycling::take(&mut *slot, recycle))
+            })
+            .unwrap_or(0);
+        self.notify_popped(n);
+        n
+    }
+
+    /// Retains only the elements for which the predicate `f` returns `true`.
+    ///
+    /// Every element in the queue is visited exactly once, in first-in,
+    /// first-out order, and the retained elements keep their relative order.
+    /// Elements for which `f` returns `false` are removed from the queue; their
+    /// slots will be [recycled] by a subsequent [`push_ref`].
+    ///
+    /// Because this method takes `&mut self`, it can only be called by the
+    /// owner of the `ThingBuf`, when no other thread can be pushing or popping
+    /// concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    ///
+    /// let mut q = ThingBuf::new(8);
+    /// for i in 0..8 {
+    ///     q.push(i).unwrap();
+    /// }
+    ///
+    /// q.retain(|i| *i % 2 == 0);
+    ///
+    /// assert_eq!(q.len(), 4);
+    /// assert_eq!(q.into_iter().collect::<Vec<_>>(), vec![0, 2, 4, 6]);
+    /// ```
+    ///
+    /// [recycled]: crate::recycling::Recycle
+    /// [`push_ref`]: Self::push_ref
+    pub fn retain(&mut self, f: impl FnMut(&mut T) -> bool) {
+        self.core.retain(&mut self.slots, f);
+        // `f` may have modified the retained elements.
+        #[cfg(feature = "high-integrity")]
+        if let Some(checksum) = self.checksum {
+            self.core.stamp_checksums(&self.slots, checksum);
+        }
+    }
+
+    /// Installs `replacement` in place of this `ThingBuf`, returning the
+    /// `ThingBuf` it replaced, along with all of its elements.
+    ///
+    /// This is exactly [`mem::replace(self, replacement)`][replace]: the
+    /// whole queue is replaced, including its capacity, [recycling policy],
+    /// and any other settings it was built with, and no elements are moved
+    /// between the two queues.
+    ///
+    /// This is the double-buffered flush pattern: an aggregator that owns
+    /// the queue swaps in an empty buffer, and then drains the full one at
+    /// its leisure, while new elements are pushed into the empty buffer. As
+    /// this takes `&mut self`, the swap is atomic with respect to every
+    /// other operation on the queue: a queue shared behind a lock, such as a
+    /// [`RwLock`] whose read lock is held while pushing, can be swapped
+    /// under the write lock. To flip buffers while other threads are pushing
+    /// without a lock, use a [`FrameBuffered`] queue instead.
+    ///
+    /// The returned `ThingBuf` may be consumed by iterating over it, or
+    /// drained by [popping](Self::pop) its elements. A drained `ThingBuf`
+    /// may be passed to the next call to `swap` as the replacement, so that
+    /// its slots, and any allocations owned by its elements, are reused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    /// use std::sync::RwLock;
+    ///
+    /// let metrics = RwLock::new(ThingBuf::new(64));
+    /// metrics.read().unwrap().push(("requests", 1)).unwrap();
+    /// metrics.read().unwrap().push(("errors", 1)).unwrap();
+    ///
+    /// // Flush the metrics recorded so far.
+    /// let mut spare = ThingBuf::new(64);
+    /// spare = metrics.write().unwrap().swap(spare);
+    /// metrics.read().unwrap().push(("requests", 2)).unwrap();
+    ///
+    /// assert_eq!(spare.pop(), Some(("requests", 1)));
+    /// assert_eq!(spare.pop(), Some(("errors", 1)));
+    /// assert_eq!(spare.pop(), None);
+    ///
+    /// // Flush again, reusing the drained buffer.
+    /// spare = metrics.write().unwrap().swap(spare);
+    /// assert_eq!(spare.pop(), Some(("requests", 2)));
+    /// ```
+    ///
+    /// [replace]: core::mem::replace
+    /// [recycling policy]: crate::recycling::Recycle
+    /// [`RwLock`]: std::sync::RwLock
+    /// [`FrameBuffered`]: crate::FrameBuffered
+    #[must_use = "the replaced `ThingBuf` holds the elements that were in the queue"]
+    pub fn swap(&mut self, replacement: Self) -> Self {
+        core::mem::replace(self, replacement)
+    }
+
+    /// Splits this queue into a [`Producer`] and a [`Consumer`], turning it
+    /// into a single-producer, single-consumer (SPSC) queue.
+    ///
+    /// The `Producer` is the only handle that can push to the queue, and the
+    /// `Consumer` is the only handle that can pop from it. Neither half can
+    /// be cloned or shared between threads, although each may be moved to
+    /// another thread. As each index of the queue is then only ever advanced
+    /// by one thread, that thread can cache the index, rather than loading
+    /// it on every operation, and advance it with a store rather than a
+    /// compare-and-swap loop. Elements already in the queue remain in it,
+    /// and are popped by the `Consumer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    /// use std::thread;
+    ///
+    /// let (producer, consumer) = ThingBuf::new(4).split();
+    ///
+    /// let t = thread::spawn(move || {
+    ///     for i in 0..10 {
+    ///         while producer.push(i).is_err() {
+    ///             thread::yield_now();
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// let mut received = Vec::new();
+    /// while received.len() < 10 {
+    ///     match consumer.pop() {
+    ///         Some(i) => received.push(i),
+    ///         None => thread::yield_now(),
+    ///     }
+    /// }
+    ///
+    /// t.join().unwrap();
+    /// assert_eq!(received, (0..10).collect::<Vec<_>>());
+    /// ```
+    #[must_use]
+    pub fn split(self) -> (Producer<T, R>, Consumer<T, R>) {
+        split::split(self)
+    }
+}
+
+#[cfg(not(all(loom, test)))]
+feature! {
+    #![feature = "std"]
+
+    use crate::wait;
+    use std::time::Duration;
+
+    impl<T, R> ThingBuf<T, R>
+    where
+        R: Recycle<T>,
+    {
+        /// Reserves a slot to push an element into the queue, blocking the
+        /// current thread until the queue has capacity.
+        ///
+        /// This allows a `ThingBuf` to be used as a bounded blocking queue
+        /// between threads. Like a [blocking channel][channel], the thread is
+        /// parked until another thread frees a slot (when a popped [`Ref`] is
+        /// dropped) and wakes it. Unlike a channel, a `ThingBuf` cannot be
+        /// closed, so this waits for as long as the queue stays full.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::ThingBuf;
+        /// use std::{sync::Arc, thread};
+        ///
+        /// let q = Arc::new(ThingBuf::new(2));
+        ///
+        /// let producer = {
+        ///     let q = q.clone();
+        ///     thread::spawn(move || {
+        ///         for i in 0..10 {
+        ///             // Blocks while the queue is full.
+        ///             *q.push_ref_blocking() = i;
+        ///         }
+        ///     })
+        /// };
+        ///
+        /// for i in 0..10 {
+        ///     assert_eq!(q.pop_blocking(), i);
+        /// }
+        /// producer.join().unwrap();
+        /// ```
+        ///
+        /// [channel]: crate::mpsc::blocking
+        pub fn push_ref_blocking(&self) -> Ref<'_, T> {
+            self.block_on(&self.push_wait, None, || self.push_ref().ok())
+                .expect("waiting without a deadline never times out")
+        }
+
+        /// Reserves a slot to push an element into the queue, blocking the
+        /// current thread for at most `timeout` until the queue has capacity.
+        ///
+        /// See [`push_ref_blocking`] for how the thread waits.
+        ///
+        /// # Returns
+        ///
+        /// - `Ok(`[`Ref<T>`](Ref)`)` if a slot was reserved
+        /// - `Err(`[`Full`]`)` if the queue was still full once `timeout` had
+        ///   elapsed
+        ///
+        /// [`push_ref_blocking`]: Self::push_ref_blocking
+        pub fn push_ref_timeout(&self, timeout: Duration) -> Result<Ref<'_, T>, Full> {
+            self.block_on(&self.push_wait, Some(timeout), || self.push_ref().ok()).ok_or(Full(()))
+        }
+
+        /// Enqueues an element by value, blocking the current thread until the
+        /// queue has capacity.
+        ///
+        /// See [`push_ref_blocking`] for how the thread waits.
+        ///
+        /// [`push_ref_blocking`]: Self::push_ref_blocking
+        pub fn push_blocking(&self, val: T) {
+            *self.push_ref_blocking() = val;
+        }
+
+        /// Dequeues the first element in the queue by reference, blocking the
+        /// current thread until an element is available.
+        ///
+        /// See [`push_ref_blocking`] for how the thread waits.
+        ///
+        /// [`push_ref_blocking`]: Self::push_ref_blocking
+        pub fn pop_ref_blocking(&self) -> Ref<'_, T> {
+            self.block_on(&self.pop_wait, None, || self.pop_ref())
+                .expect("waiting without a deadline never times out")
+        }
+
+        /// Dequeues the first element in the queue by reference, blocking the
+        /// current thread for at most `timeout` until an element is available.
+        ///
+        /// See [`push_ref_blocking`] for how the thread waits.
+        ///
+        /// # Returns
+        ///
+        /// - `Some(`[`Ref<T>`](Ref)`)` if an element was dequeued
+        /// - `None` if the queue was still empty once `timeout` had elapsed
+        ///
+        /// [`push_ref_blocking`]: Self::push_ref_blocking
+        pub fn pop_ref_timeout(&self, timeout: Duration) -> Option<Ref<'_, T>> {
+            self.block_on(&self.pop_wait, Some(timeout), || self.pop_ref())
+        }
+
+        /// Dequeues the first element in the queue *by value*, blocking the
+        /// current thread until an element is available.
+        ///
+        /// See [`push_ref_blocking`] for how the thread waits.
+        ///
+        /// [`push_ref_blocking`]: Self::push_ref_blocking
+        pub fn pop_blocking(&self) -> T {
+            let mut slot = self.pop_ref_blocking();
+            recycling::take(&mut *slot, &self.recycle)
+        }
+
+        /// Blocks the current thread on `queue` until `f` succeeds, or
+        /// `timeout` elapses.
+        fn block_on<U>(
+            &self,
+            queue: &WaitQueue<Unparker>,
+            timeout: Option<Duration>,
+            mut f: impl FnMut() -> Option<U>,
+        ) -> Option<U> {
+            if let Some(val) = f() {
+                return Some(val);
+            }
+            self.arm_wake();
+            wait::block_on(queue, timeout, f)
+        }
+    }
+}
+
+feature! {
+    #![all(feature = "std", feature = "rayon")]
+
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    impl<T, R> ThingBuf<T, R>
+    where
+        T: Send,
+        R: Recycle<T>,
+        Self: Sync,
+    {
+        /// Pushes every element produced by a [parallel iterator] into the
+        /// queue, blocking the [`rayon`] worker threads that produce them
+        /// whenever the queue is full.
+        ///
+        /// This feeds a bounded queue from a parallel computation, while
+        /// consumers pop elements from it concurrently. The queue applies
+        /// backpressure to the computation: once it fills up, each worker
+        /// waits as described in [`push_ref_blocking`] until a consumer frees
+        /// a slot. Elements may be pushed in any order.
+        ///
+        /// This returns once every element has been pushed, so the queue must
+        /// be drained by consumers that are *not* running on the same
+        /// [`rayon`] thread pool, or else they may never get to run while
+        /// the workers are blocked.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use rayon::prelude::*;
+        /// use thingbuf::ThingBuf;
+        /// use std::{sync::Arc, thread};
+        ///
+        /// let q = Arc::new(ThingBuf::new(16));
+        ///
+        /// let consumer = {
+        ///     let q = q.clone();
+        ///     thread::spawn(move || (0..1000).map(|_| q.pop_blocking()).sum::<u64>())
+        /// };
+        ///
+        /// q.par_extend((0..1000u64).into_par_iter().map(|i| i * 2));
+        /// assert_eq!(consumer.join().unwrap(), 999 * 1000);
+        /// ```
+        ///
+        /// [parallel iterator]: rayon::iter::IntoParallelIterator
+        /// [`push_ref_blocking`]: Self::push_ref_blocking
+        pub fn par_extend<I>(&self, iter: I)
+        where
+            I: IntoParallelIterator<Item = T>,
+        {
+            iter.into_par_iter().for_each(|val| self.push_blocking(val));
+        }
+    }
+}
+
+impl<T, R> IntoIterator for ThingBuf<T, R>
+where
+    R: Recycle<T>,
+{
+    type Item = T;
+    type IntoIter = IntoIter<T, R>;
+
+    /// Consumes the `ThingBuf`, returning an iterator over the elements that
+    /// were still enqueued in it, in first-in, first-out order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingBuf;
+    ///
+    /// let q = ThingBuf::new(4);
+    /// q.push(1).unwrap();
+    /// q.push(2).unwrap();
+    /// q.push(3).unwrap();
+    ///
+    /// let doubled = q.into_iter().map(|x| x * 2).collect::<Vec<_>>();
+    /// assert_eq!(doubled, vec![2, 4, 6]);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { buf: self }
+    }
 }
 
 impl<T, R> Drop for ThingBuf<T, R> {
     fn drop(&mut self) {
-        self.core.drop_slots(&mut self.slots[..]);
+        self.core.drop_slots(&mut self.slots);
     }
 }
 
@@ -513,3 +1408,69 @@ impl<T, R: fmt::Debug> fmt::Debug for ThingBuf<T, R> {
             .finish()
     }
 }
+
+// === impl SnapshotIter ===
+
+impl<T: Copy> Iterator for SnapshotIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos != self.end {
+            let pos = self.pos;
+            self.pos = self.core.next_pos(pos);
+            if let Some(val) = self.core.read_at(self.slots, pos) {
+                // A pop may have seen the element while it was pinned.
+                #[cfg(all(feature = "std", not(all(loom, test))))]
+                self.pop_wait.notify_waiting();
+                return Some(val);
+            }
+        }
+        None
+    }
+}
+
+impl<T> fmt::Debug for SnapshotIter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapshotIter")
+            .field("pos", &self.pos)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+// === impl IntoIter ===
+
+impl<T, R> Iterator for IntoIter<T, R>
+where
+    R: Recycle<T>,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buf.pop()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<T, R> ExactSizeIterator for IntoIter<T, R>
+where
+    R: Recycle<T>,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        // The iterator owns the queue, so no other thread can push or pop.
+        self.buf.len()
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for IntoIter<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.buf).finish()
+    }
+}
@@ -0,0 +1,519 @@
+use super::ThingBuf;
+use crate::{
+    loom::{atomic::AtomicUsize, cell::UnsafeCell},
+    recycling::{self, Recycle},
+    Core, Slot, SlotRef, Slots, MAX_CAPACITY,
+};
+use alloc::{
+    alloc::{alloc, dealloc, handle_alloc_error, Layout},
+    boxed::Box,
+};
+use core::{
+    fmt,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+    slice,
+};
+
+/// A builder for a [`ThingBuf`] with custom allocation options.
+///
+/// This type is returned by [`ThingBuf::builder`].
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::ThingBuf;
+///
+/// // A large ring used as a telemetry buffer: align the slot array to a page
+/// // boundary, and ask the OS to back it with huge pages if it can.
+/// let q = ThingBuf::<u64>::builder(1 << 16)
+///     .align(4096)
+///     .huge_pages(true)
+///     .build();
+///
+/// q.push(1).unwrap();
+/// assert_eq!(q.pop(), Some(1));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct ThingBufBuilder<T, R = recycling::DefaultRecycle> {
+    capacity: usize,
+    align: usize,
+    huge_pages: bool,
+    layout: SlotLayout,
+    pre_fill: bool,
+    recycle: R,
+    #[cfg(feature = "high-integrity")]
+    checksum: Option<fn(&T) -> u64>,
+    _t: PhantomData<fn(T)>,
+}
+
+/// How the slots of a [`ThingBuf`] are laid out in memory.
+///
+/// Each slot in a `ThingBuf` has a *state* word, which pushing and popping
+/// threads read and update to claim the slot, next to the *element* that is
+/// stored in it. This controls whether the states and elements are
+/// interleaved in a single array, or stored in two separate arrays.
+///
+/// This is set with [`ThingBufBuilder::layout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SlotLayout {
+    /// Each slot's state is stored next to its element, in a single array
+    /// (an "array of structures").
+    ///
+    /// This is the default. Claiming a slot and accessing its element touch
+    /// the same cache line, which is best when elements are small, or when
+    /// each claimed element is always read or written.
+    Interleaved,
+    /// The slots' states are stored in one array, and their elements in
+    /// another (a "structure of arrays").
+    ///
+    /// The states are packed together, so checking whether the next slots
+    /// can be claimed doesn't pull the elements' cache lines into the
+    /// checking thread's cache. This is best when elements are large, and
+    /// when queues are often polled while they are empty or full. Accessing
+    /// a claimed element touches a second cache line.
+    Split,
+}
+
+/// A heap-allocated array, which (unlike a `Box<[E]>`) may be allocated with
+/// a larger alignment than `E`'s.
+pub(crate) struct AlignedArray<E> {
+    ptr: NonNull<E>,
+    len: usize,
+    layout: Layout,
+}
+
+/// The slots of a `ThingBuf` with the [`SlotLayout::Split`] layout.
+pub(crate) struct SplitSlots<T> {
+    states: AlignedArray<AtomicUsize>,
+    values: AlignedArray<UnsafeCell<MaybeUninit<T>>>,
+    #[cfg(feature = "timestamps")]
+    enqueued_at: AlignedArray<UnsafeCell<Option<std::time::Instant>>>,
+    #[cfg(feature = "high-integrity")]
+    checksums: AlignedArray<UnsafeCell<u64>>,
+}
+
+/// The slots of a `ThingBuf`, in either [`SlotLayout`].
+pub(crate) enum SlotStorage<T> {
+    Interleaved(AlignedArray<Slot<T>>),
+    Split(SplitSlots<T>),
+}
+
+/// The size of a transparent huge page.
+///
+/// This is 2 MiB on x86_64, and on aarch64 with 4 KiB base pages.
+#[cfg(all(target_os = "linux", feature = "huge-pages"))]
+const HUGE_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+// === impl ThingBufBuilder ===
+
+impl<T> ThingBufBuilder<T> {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            align: 1,
+            huge_pages: false,
+            layout: SlotLayout::Interleaved,
+            pre_fill: false,
+            recycle: recycling::DefaultRecycle::new(),
+            #[cfg(feature = "high-integrity")]
+            checksum: None,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T, R> ThingBufBuilder<T, R> {
+    /// Sets the [recycling policy] for the `ThingBuf`.
+    ///
+    /// By default, the [`DefaultRecycle`] policy is used.
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    /// [`DefaultRecycle`]: crate::recycling::DefaultRecycle
+    #[must_use]
+    pub fn recycle<R2: Recycle<T>>(self, recycle: R2) -> ThingBufBuilder<T, R2> {
+        ThingBufBuilder {
+            capacity: self.capacity,
+            align: self.align,
+            huge_pages: self.huge_pages,
+            layout: self.layout,
+            pre_fill: self.pre_fill,
+            recycle,
+            #[cfg(feature = "high-integrity")]
+            checksum: self.checksum,
+            _t: PhantomData,
+        }
+    }
+
+    /// Sets the minimum alignment, in bytes, of the `ThingBuf`'s slot array.
+    ///
+    /// The slot array is always aligned to at least the alignment of its
+    /// elements; larger values may be used to align it to a cache line or to a
+    /// page boundary.
+    ///
+    /// # Panics
+    ///
+    /// If `align` is not a power of two.
+    #[must_use]
+    pub fn align(self, align: usize) -> Self {
+        assert!(
+            align.is_power_of_two(),
+            "slot array alignment must be a power of two"
+        );
+        Self { align, ..self }
+    }
+
+    /// Sets whether the OS should be asked to back the `ThingBuf`'s slot
+    /// array with huge pages.
+    ///
+    /// Very large rings may touch many pages, so backing them with huge pages
+    /// can reduce TLB pressure. On Linux, if the slot array is at least one
+    /// huge page in size, it is aligned to a huge page boundary, and
+    /// transparent huge pages are requested for it with `madvise(2)`. This is
+    /// only a hint: if transparent huge pages are disabled or unavailable, the
+    /// array is backed by regular pages. On other platforms, or if the
+    /// "huge-pages" feature flag is disabled, this option has no effect.
+    #[must_use]
+    pub fn huge_pages(self, huge_pages: bool) -> Self {
+        Self { huge_pages, ..self }
+    }
+
+    /// Sets the [`SlotLayout`] of the `ThingBuf`'s slots.
+    ///
+    /// By default, slots are [interleaved](SlotLayout::Interleaved). With
+    /// the [split](SlotLayout::Split) layout, the [alignment](Self::align)
+    /// and [huge pages](Self::huge_pages) options apply to both the array of
+    /// states and the array of elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{SlotLayout, ThingBuf};
+    ///
+    /// // Large elements, which consumers often poll for while the queue is
+    /// // empty: keep the slot states apart from the elements.
+    /// let q = ThingBuf::<[u64; 32]>::builder(64)
+    ///     .layout(SlotLayout::Split)
+    ///     .build();
+    ///
+    /// q.push([1; 32]).unwrap();
+    /// assert_eq!(q.pop(), Some([1; 32]));
+    /// ```
+    #[must_use]
+    pub fn layout(self, layout: SlotLayout) -> Self {
+        Self { layout, ..self }
+    }
+
+    /// Sets whether every slot of the `ThingBuf` is initialized when it is
+    /// built, rather than the first time an element is pushed to it.
+    ///
+    /// See [`ThingBuf::pre_fill`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{recycling::WithCapacity, ThingBuf};
+    ///
+    /// let q = ThingBuf::<Vec<u8>>::builder(16)
+    ///     .recycle(WithCapacity::new().with_min_capacity(1024))
+    ///     .pre_fill(true)
+    ///     .build();
+    ///
+    /// q.push_with(|buf| {
+    ///     assert!(buf.capacity() >= 1024);
+    ///     buf.extend_from_slice(b"hello");
+    /// })
+    /// .unwrap();
+    /// ```
+    #[must_use]
+    pub fn pre_fill(self, pre_fill: bool) -> Self {
+        Self { pre_fill, ..self }
+    }
+
+    /// Checks the integrity of the `ThingBuf`'s elements with the provided
+    /// `checksum` function.
+    ///
+    /// The checksum of each element is computed when it is pushed, and
+    /// stored next to it in its slot. When the element is popped, its
+    /// checksum is computed again, and if it has changed, the pop panics,
+    /// rather than returning an element that was corrupted while it was in
+    /// the queue. For elements that are byte buffers, [`integrity::bytes`]
+    /// may be used as the checksum function.
+    ///
+    /// See the [`integrity`] module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::{integrity, ThingBuf};
+    ///
+    /// let q = ThingBuf::<Vec<u8>>::builder(8)
+    ///     .checksum(integrity::bytes)
+    ///     .build();
+    ///
+    /// q.push(b"hello".to_vec()).unwrap();
+    /// assert_eq!(q.pop().as_deref(), Some(&b"hello"[..]));
+    /// ```
+    ///
+    /// [`integrity`]: crate::integrity
+    /// [`integrity::bytes`]: crate::integrity::bytes
+    #[cfg(feature = "high-integrity")]
+    #[must_use]
+    pub fn checksum(self, checksum: fn(&T) -> u64) -> Self {
+        Self {
+            checksum: Some(checksum),
+            ..self
+        }
+    }
+
+    /// Returns a new `ThingBuf` with the configured options.
+    ///
+    /// # Panics
+    ///
+    /// If the capacity is 0, or exceeds `usize::MAX & !(1 << (usize::BITS - 1))`.
+    #[must_use]
+    pub fn build(self) -> ThingBuf<T, R>
+    where
+        R: Recycle<T>,
+    {
+        assert!(self.capacity > 0);
+        assert!(self.capacity <= MAX_CAPACITY);
+        let slots = match self.layout {
+            SlotLayout::Interleaved => SlotStorage::Interleaved(AlignedArray::new(
+                self.capacity,
+                self.align,
+                self.huge_pages,
+                Slot::new,
+            )),
+            SlotLayout::Split => {
+                SlotStorage::Split(SplitSlots::new(self.capacity, self.align, self.huge_pages))
+            }
+        };
+        let mut q = ThingBuf {
+            core: Core::new(self.capacity),
+            slots,
+            recycle: self.recycle,
+            #[cfg(feature = "high-integrity")]
+            checksum: self.checksum,
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            push_wait: crate::wait::WaitQueue::new(),
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            pop_wait: crate::wait::WaitQueue::new(),
+            #[cfg(all(feature = "std", not(all(loom, test))))]
+            wake: crate::loom::atomic::AtomicUsize::new(super::WAKE_NONE),
+        };
+        if self.pre_fill {
+            q.pre_fill();
+        }
+        q
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for ThingBufBuilder<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThingBufBuilder")
+            .field("capacity", &self.capacity)
+            .field("align", &self.align)
+            .field("huge_pages", &self.huge_pages)
+            .field("layout", &self.layout)
+            .field("pre_fill", &self.pre_fill)
+            .field("recycle", &self.recycle)
+            .finish()
+    }
+}
+
+// === impl SlotLayout ===
+
+impl Default for SlotLayout {
+    fn default() -> Self {
+        Self::Interleaved
+    }
+}
+
+// === impl AlignedArray ===
+
+impl<E> AlignedArray<E> {
+    fn new(len: usize, align: usize, huge_pages: bool, mut init: impl FnMut(usize) -> E) -> Self {
+        let layout = Layout::array::<E>(len)
+            .and_then(|layout| layout.align_to(align))
+            .expect("slot array layout overflowed");
+
+        #[cfg(all(target_os = "linux", feature = "huge-pages"))]
+        let layout = if huge_pages && layout.size() >= HUGE_PAGE_SIZE {
+            // `madvise` only applies to whole pages, so the array must start
+            // on a huge page boundary, and be padded out to a whole number of
+            // huge pages.
+            layout
+                .align_to(HUGE_PAGE_SIZE)
+                .map(|layout| layout.pad_to_align())
+                .expect("slot array layout overflowed")
+        } else {
+            layout
+        };
+        #[cfg(not(all(target_os = "linux", feature = "huge-pages")))]
+        let _ = huge_pages;
+
+        if layout.size() == 0 {
+            // Zero-sized arrays (such as the elements of a split slot array
+            // of `()`s) don't need to be allocated.
+            return Self {
+                ptr: NonNull::dangling(),
+                len,
+                layout,
+            };
+        }
+
+        // Safety: `layout` is not zero-sized.
+        let ptr = match NonNull::new(unsafe { alloc(layout) } as *mut E) {
+            Some(ptr) => ptr,
+            None => handle_alloc_error(layout),
+        };
+
+        #[cfg(all(target_os = "linux", feature = "huge-pages"))]
+        if huge_pages && layout.align() >= HUGE_PAGE_SIZE {
+            // This must happen before the slots are initialized, as huge pages
+            // are only used for pages that have not yet been faulted in. The
+            // hint is best-effort, so failures are ignored.
+            let _res = unsafe {
+                libc::madvise(
+                    ptr.as_ptr() as *mut libc::c_void,
+                    layout.size(),
+                    libc::MADV_HUGEPAGE,
+                )
+            };
+            test_println!("madvise(MADV_HUGEPAGE) -> {}", _res);
+        }
+
+        for idx in 0..len {
+            // Safety: `idx` is in bounds of the allocation.
+            unsafe { ptr.as_ptr().add(idx).write(init(idx)) };
+        }
+
+        Self { ptr, len, layout }
+    }
+}
+
+impl<E> From<Box<[E]>> for AlignedArray<E> {
+    fn from(elems: Box<[E]>) -> Self {
+        let len = elems.len();
+        let layout = Layout::for_value(&*elems);
+        // Safety: `Box::into_raw` never returns a null pointer.
+        let ptr = unsafe { NonNull::new_unchecked(Box::into_raw(elems) as *mut E) };
+        Self { ptr, len, layout }
+    }
+}
+
+impl<E> Deref for AlignedArray<E> {
+    type Target = [E];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<E> DerefMut for AlignedArray<E> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<E> Drop for AlignedArray<E> {
+    fn drop(&mut self) {
+        unsafe {
+            // For slots, this only drops the slots themselves; the values in
+            // them are dropped by `Core::drop_slots`.
+            ptr::drop_in_place(&mut **self as *mut [E]);
+            // A `Box<[E]>` is deallocated with the same layout as the one
+            // recorded when converting it, so this is correct for both kinds
+            // of allocation.
+            if self.layout.size() != 0 {
+                dealloc(self.ptr.as_ptr() as *mut u8, self.layout);
+            }
+        }
+    }
+}
+
+// `AlignedArray` owns its elements, like a `Box<[E]>` does.
+unsafe impl<E: Send> Send for AlignedArray<E> {}
+unsafe impl<E: Sync> Sync for AlignedArray<E> {}
+
+// === impl SplitSlots ===
+
+impl<T> SplitSlots<T> {
+    fn new(capacity: usize, align: usize, huge_pages: bool) -> Self {
+        Self {
+            states: AlignedArray::new(capacity, align, huge_pages, AtomicUsize::new),
+            values: AlignedArray::new(capacity, align, huge_pages, |_| {
+                UnsafeCell::new(MaybeUninit::uninit())
+            }),
+            #[cfg(feature = "timestamps")]
+            enqueued_at: AlignedArray::new(capacity, 1, false, |_| UnsafeCell::new(None)),
+            #[cfg(feature = "high-integrity")]
+            checksums: AlignedArray::new(capacity, 1, false, |_| UnsafeCell::new(0)),
+        }
+    }
+}
+
+impl<T> Slots<T> for SplitSlots<T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    #[inline]
+    unsafe fn get_unchecked(&self, idx: usize) -> SlotRef<'_, T> {
+        SlotRef {
+            value: self.values.get_unchecked(idx),
+            state: self.states.get_unchecked(idx),
+            #[cfg(feature = "timestamps")]
+            enqueued_at: self.enqueued_at.get_unchecked(idx),
+            #[cfg(feature = "high-integrity")]
+            checksum: self.checksums.get_unchecked(idx),
+        }
+    }
+}
+
+// Like a `Slot<T>`, split slots may be shared between threads if `T` may be.
+unsafe impl<T: Send> Send for SplitSlots<T> {}
+unsafe impl<T: Sync> Sync for SplitSlots<T> {}
+
+// === impl SlotStorage ===
+
+impl<T> SlotStorage<T> {
+    /// Returns a pointer to the start of the slot states.
+    #[cfg(all(test, not(loom)))]
+    pub(crate) fn as_ptr(&self) -> *const u8 {
+        match self {
+            Self::Interleaved(slots) => slots.as_ptr() as *const u8,
+            Self::Split(slots) => slots.states.as_ptr() as *const u8,
+        }
+    }
+}
+
+impl<T> Slots<T> for SlotStorage<T> {
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Self::Interleaved(slots) => slots.len(),
+            Self::Split(slots) => slots.len(),
+        }
+    }
+
+    #[inline]
+    unsafe fn get_unchecked(&self, idx: usize) -> SlotRef<'_, T> {
+        match self {
+            Self::Interleaved(slots) => Slots::get_unchecked(&slots[..], idx),
+            Self::Split(slots) => slots.get_unchecked(idx),
+        }
+    }
+}
+
+impl<T> From<Box<[Slot<T>]>> for SlotStorage<T> {
+    fn from(slots: Box<[Slot<T>]>) -> Self {
+        Self::Interleaved(slots.into())
+    }
+}
@@ -0,0 +1,230 @@
+use super::ThingBuf;
+use crate::{
+    loom::{atomic::Ordering::Acquire, sync::Arc},
+    mpsc::errors::TrySendError,
+    recycling::{self, Recycle},
+    Full, Ref,
+};
+use core::{cell::Cell, fmt};
+
+/// The producer half of a [`ThingBuf`] that has been [split] into a
+/// single-producer, single-consumer (SPSC) queue.
+///
+/// A `Producer` is the only handle that can push elements into the queue.
+/// It can be moved to another thread, but not shared between threads, or
+/// cloned.
+///
+/// This type is returned by the [`ThingBuf::split`] method.
+///
+/// [split]: ThingBuf::split
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct Producer<T, R = recycling::DefaultRecycle> {
+    buf: Arc<ThingBuf<T, R>>,
+    /// The position of the next slot to push to. Only the producer advances
+    /// the queue's tail index, so it never has to load it.
+    tail: Cell<usize>,
+}
+
+/// The consumer half of a [`ThingBuf`] that has been [split] into a
+/// single-producer, single-consumer (SPSC) queue.
+///
+/// A `Consumer` is the only handle that can pop elements from the queue.
+/// It can be moved to another thread, but not shared between threads, or
+/// cloned.
+///
+/// This type is returned by the [`ThingBuf::split`] method.
+///
+/// [split]: ThingBuf::split
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct Consumer<T, R = recycling::DefaultRecycle> {
+    buf: Arc<ThingBuf<T, R>>,
+    /// The position of the next slot to pop from. Only the consumer
+    /// advances the queue's head index, so it never has to load it.
+    head: Cell<usize>,
+}
+
+pub(super) fn split<T, R>(buf: ThingBuf<T, R>) -> (Producer<T, R>, Consumer<T, R>) {
+    let tail = buf.core.tail.load(Acquire);
+    let head = buf.core.head.load(Acquire);
+    let buf = Arc::new(buf);
+    let producer = Producer {
+        buf: buf.clone(),
+        tail: Cell::new(tail),
+    };
+    let consumer = Consumer {
+        buf,
+        head: Cell::new(head),
+    };
+    (producer, consumer)
+}
+
+// === impl Producer ===
+
+impl<T, R: Recycle<T>> Producer<T, R> {
+    /// Reserves a slot to push an element into the queue, returning a [`Ref`]
+    /// that can be used to write to that slot.
+    ///
+    /// This is equivalent to [`ThingBuf::push_ref`], but as no other thread
+    /// can push to the queue, it never contends with another producer.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(`[`Ref`]`)` if a slot was reserved
+    /// - `Err(`[`Full`]`)` if there is no capacity remaining in the queue
+    pub fn push_ref(&self) -> Result<Ref<'_, T>, Full> {
+        let mut tail = self.tail.get();
+        let res = self
+            .buf
+            .core
+            .push_ref_spsc(&self.buf.slots, &self.buf.recycle, &mut tail);
+        self.tail.set(tail);
+        let slot = res.map_err(|e| match e {
+            TrySendError::Full(()) => Full(()),
+            _ => unreachable!(),
+        })?;
+        #[cfg(feature = "high-integrity")]
+        let slot = slot.with_checksum(self.buf.checksum);
+        Ok(slot)
+    }
+
+    /// Attempts to enqueue an element by value.
+    ///
+    /// If the queue is full, the element is returned in the [`Full`] error.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the element was enqueued
+    /// - `Err(`[`Full`]`)`, containing the value, if there is no capacity
+    ///   remaining in the queue
+    #[inline]
+    pub fn push(&self, val: T) -> Result<(), Full<T>> {
+        match self.push_ref() {
+            Err(_) => Err(Full(val)),
+            Ok(mut slot) => {
+                *slot = val;
+                Ok(())
+            }
+        }
+    }
+
+    /// Reserves a slot to push an element into the queue, and invokes the
+    /// provided function `f` with a mutable reference to that element.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(U)` containing the return value of the provided function, if the
+    ///   element was enqueued
+    /// - `Err(`[`Full`]`)`, if there is no capacity remaining in the queue
+    #[inline]
+    pub fn push_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Result<U, Full> {
+        self.push_ref().map(|mut r| r.with_mut(f))
+    }
+}
+
+impl<T, R> Producer<T, R> {
+    /// Returns the total capacity of the queue.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Returns the number of elements in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if there are currently no elements in the queue.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for Producer<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Producer")
+            .field("tail", &self.tail.get())
+            .field("buf", &self.buf)
+            .finish()
+    }
+}
+
+// === impl Consumer ===
+
+impl<T, R> Consumer<T, R> {
+    /// Dequeues the first element in the queue, returning a [`Ref`] that can
+    /// be used to read from (or mutate) the element.
+    ///
+    /// This is equivalent to [`ThingBuf::pop_ref`], but as no other thread
+    /// can pop from the queue, it never contends with another consumer.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(`[`Ref<T>`](Ref)`)` if an element was dequeued
+    /// - `None` if there are no elements in the queue
+    pub fn pop_ref(&self) -> Option<Ref<'_, T>> {
+        let mut head = self.head.get();
+        let res = self.buf.core.pop_ref_spsc(&self.buf.slots, &mut head);
+        self.head.set(head);
+        let slot = res.ok()?;
+        self.buf.verify(&slot);
+        Some(slot)
+    }
+
+    /// Dequeues the first element in the queue by reference, and invokes the
+    /// provided function `f` with a mutable reference to the dequeued
+    /// element.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(U)` containing the return value of the provided function, if
+    ///   the element was dequeued
+    /// - `None` if the queue is empty
+    #[inline]
+    pub fn pop_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Option<U> {
+        self.pop_ref().map(|mut r| r.with_mut(f))
+    }
+
+    /// Returns the total capacity of the queue.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Returns the number of elements in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns `true` if there are currently no elements in the queue.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+impl<T, R: Recycle<T>> Consumer<T, R> {
+    /// Dequeues the first element in the queue *by value*, moving it out of
+    /// the queue.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(T)` if an element was dequeued
+    /// - `None` if there are no elements in the queue
+    #[inline]
+    pub fn pop(&self) -> Option<T> {
+        let mut slot = self.pop_ref()?;
+        Some(recycling::take(&mut *slot, &self.buf.recycle))
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for Consumer<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Consumer")
+            .field("head", &self.head.get())
+            .field("buf", &self.buf)
+            .finish()
+    }
+}
@@ -1,4 +1,4 @@
-use super::ThingBuf;
+use super::{SlotLayout, ThingBuf};
 use crate::loom::{self, alloc, thread};
 use std::sync::Arc;
 
@@ -89,6 +89,35 @@ fn spsc() {
     });
 }
 
+#[test]
+fn spsc_split_halves() {
+    const COUNT: usize = 7;
+    loom::model(|| {
+        let (producer, consumer) = ThingBuf::new(2).split();
+
+        let producer = thread::spawn(move || {
+            for i in 0..COUNT {
+                while producer.push(i).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        for i in 0..COUNT {
+            loop {
+                if let Some(val) = consumer.pop_ref() {
+                    assert_eq!(*val, i);
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+
+        producer.join().unwrap();
+        assert!(consumer.is_empty());
+    });
+}
+
 #[test]
 #[ignore] // this takes about a million years to run
 fn linearizable() {
@@ -123,3 +152,74 @@ fn linearizable() {
         t3.join().expect("thread 3 panicked!");
     })
 }
+
+#[test]
+fn steal_batch_no_duplicates() {
+    use crate::Injector;
+    const COUNT: usize = 3;
+
+    loom::model(|| {
+        let q = Arc::new(Injector::new(4));
+        let producer = thread::spawn({
+            let q = q.clone();
+            move || {
+                for i in 0..COUNT {
+                    q.push(i).unwrap();
+                }
+            }
+        });
+        let stealer = thread::spawn({
+            let q = q.clone();
+            move || {
+                let mut local = Vec::new();
+                q.steal_batch_into(&mut local, 2);
+                local
+            }
+        });
+
+        let mut local = Vec::new();
+        q.steal_batch_into(&mut local, 2);
+
+        producer.join().unwrap();
+        local.extend(stealer.join().unwrap());
+        while q.steal_batch_into(&mut local, 2) > 0 {}
+
+        local.sort_unstable();
+        assert_eq!(local, (0..COUNT).collect::<Vec<_>>());
+    })
+}
+
+#[test]
+fn spsc_split_layout() {
+    const COUNT: usize = 5;
+    loom::model(|| {
+        let q = Arc::new(
+            ThingBuf::<alloc::Track<usize>>::builder(2)
+                .layout(SlotLayout::Split)
+                .build(),
+        );
+
+        let producer = {
+            let q = q.clone();
+            thread::spawn(move || {
+                for i in 0..COUNT {
+                    while q.push(alloc::Track::new(i)).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            })
+        };
+
+        for i in 0..COUNT {
+            loop {
+                if let Some(val) = q.pop_ref() {
+                    assert_eq!(*val.get_ref(), i);
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+
+        producer.join().unwrap();
+    });
+}
@@ -0,0 +1,899 @@
+#[cfg(feature = "std")]
+use crate::mpsc::blocking::park::Unparker;
+use crate::{
+    loom::{
+        atomic::{fence, AtomicUsize, Ordering::*},
+        cell::MutPtr,
+    },
+    recycling::{self, Recycle},
+    util::{Backoff, CachePadded},
+    wait::{queue, WaitQueue, WaitResult},
+    Full, Slot, MAX_CAPACITY,
+};
+use alloc::boxed::Box;
+use core::{
+    fmt,
+    future::Future,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops,
+    pin::Pin,
+    ptr,
+    task::{Context, Poll, Waker},
+};
+
+#[cfg(all(loom, test))]
+mod tests;
+
+/// A fixed-size, lock-free, multi-producer multi-consumer (MPMC) stack.
+///
+/// This is a fixed-capacity, last-in, first-out variant of [`ThingBuf`]:
+/// [`pop`] and [`pop_ref`] return the element that was pushed *most recently*,
+/// rather than the oldest one. Like a `ThingBuf`, a `ThingStack` stores its
+/// elements in a single array of slots, and reuses them in place, so it can be
+/// used as an [object pool] with [`push_ref`] and [`pop_ref`].
+///
+/// This makes a `ThingStack` a better fit than a `ThingBuf` for pool-like
+/// workloads, where *which* element is returned doesn't matter: the element
+/// that was returned to the pool most recently is the one most likely to still
+/// be in the CPU's cache.
+///
+/// The stack is implemented as two [Treiber stacks] of slot indices, one for
+/// occupied slots and one for free slots, whose heads are tagged to prevent
+/// the ABA problem.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::ThingStack;
+///
+/// let stack = ThingStack::new(4);
+///
+/// stack.push(1).unwrap();
+/// stack.push(2).unwrap();
+/// stack.push(3).unwrap();
+///
+/// // Elements are popped in last-in, first-out order.
+/// assert_eq!(stack.pop(), Some(3));
+/// assert_eq!(stack.pop(), Some(2));
+///
+/// stack.push(4).unwrap();
+/// assert_eq!(stack.pop(), Some(4));
+/// assert_eq!(stack.pop(), Some(1));
+/// assert_eq!(stack.pop(), None);
+/// ```
+///
+/// Reusing allocations in place:
+///
+/// ```
+/// use thingbuf::ThingStack;
+///
+/// let pool = ThingStack::<Vec<u8>>::new(8);
+///
+/// pool.push_with(|buf| buf.extend_from_slice(b"hello")).unwrap();
+///
+/// // The most recently returned buffer is handed out first.
+/// let len = pool.pop_with(|buf| buf.len());
+/// assert_eq!(len, Some(5));
+/// ```
+///
+/// A pool with a hard cap on the number of objects in use can check objects
+/// out with [`get`], which waits for an object to be returned if they are all
+/// in use, rather than polling. The returned [`PoolRef`] returns its object to
+/// the pool when it is dropped. With the "std" feature, [`get_blocking`] and
+/// [`get_timeout`] do the same for threads.
+///
+/// [`pop`]: Self::pop
+/// [`pop_ref`]: Self::pop_ref
+/// [`push_ref`]: Self::push_ref
+/// [`get`]: Self::get
+/// [`get_blocking`]: Self::get_blocking
+/// [`get_timeout`]: Self::get_timeout
+/// [`ThingBuf`]: crate::ThingBuf
+/// [object pool]: https://en.wikipedia.org/wiki/Object_pool_pattern
+/// [Treiber stacks]: https://en.wikipedia.org/wiki/Treiber_stack
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct ThingStack<T, R = recycling::DefaultRecycle> {
+    links: Links,
+    /// Every slot's value is initialized when the stack is constructed. Each
+    /// slot's state is the index of the next slot in the list it is on.
+    slots: Box<[Slot<T>]>,
+    recycle: R,
+}
+
+/// A reference to an element in a [`ThingStack`].
+///
+/// A `StackRef` represents exclusive access to an element which is being
+/// pushed or popped. When it is dropped, a pushed element becomes available to
+/// be popped, and a popped element's slot becomes available to be pushed to
+/// again.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct StackRef<'stack, T> {
+    /// This is dropped when the `StackRef` is, *before* the slot is released,
+    /// so that (under loom) access to the element ends before another thread
+    /// can claim the slot.
+    ptr: ManuallyDrop<MutPtr<MaybeUninit<T>>>,
+    links: &'stack Links,
+    slots: &'stack [Slot<T>],
+    idx: usize,
+    is_pop: bool,
+}
+
+/// An object checked out of a [`ThingStack`] that is used as an object pool.
+///
+/// This is returned by [`ThingStack::get`] and [`ThingStack::try_get`]. Like a
+/// popped [`StackRef`], a `PoolRef` can be used to read from (or mutate) the
+/// object. Unlike a `StackRef`, dropping a `PoolRef` [recycles] the object and
+/// pushes it back onto the stack, waking a task or thread waiting in
+/// [`get`](ThingStack::get), so that every object that is not checked out
+/// stays in the pool.
+///
+/// [recycles]: crate::recycling::Recycle::recycle
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct PoolRef<'stack, T, R = recycling::DefaultRecycle>
+where
+    R: Recycle<T>,
+{
+    slot: StackRef<'stack, T>,
+    recycle: &'stack R,
+}
+
+/// The heads of the occupied and free lists.
+///
+/// Each head stores the index of the first slot on the list in its low bits
+/// (or `nil` if the list is empty), and a tag in its high bits, which is
+/// advanced by every update so that a stale head can never be swapped in.
+struct Links {
+    full: CachePadded<AtomicUsize>,
+    free: CachePadded<AtomicUsize>,
+    len: AtomicUsize,
+    /// The index of the end of a list. This is the stack's capacity.
+    nil: usize,
+    idx_mask: usize,
+    /// Tasks waiting for an element to be pushed.
+    pop_wait: WaitQueue<Waker>,
+    /// Threads waiting for an element to be pushed.
+    #[cfg(feature = "std")]
+    pop_wait_blocking: WaitQueue<Unparker>,
+}
+
+/// Future returned by [`ThingStack::pop_ref_async`].
+#[pin_project::pin_project(PinnedDrop)]
+struct PopRefFuture<'stack, T, R> {
+    stack: &'stack ThingStack<T, R>,
+    /// Whether `waiter` has been added to the wait queue since it was last
+    /// notified.
+    queued: bool,
+    #[pin]
+    waiter: queue::Waiter<Waker>,
+}
+
+// === impl ThingStack ===
+
+impl<T: Default + Clone> ThingStack<T> {
+    /// Returns a new `ThingStack` with space for `capacity` elements.
+    ///
+    /// # Panics
+    ///
+    /// - If `capacity` is 0.
+    /// - If the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self::with_recycle(capacity, recycling::DefaultRecycle::new())
+    }
+}
+
+impl<T, R> ThingStack<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Returns a new `ThingStack` with space for `capacity` elements and the
+    /// provided [recycling policy].
+    ///
+    /// Unlike a [`ThingBuf`](crate::ThingBuf), a `ThingStack` creates every
+    /// element with the recycling policy up front.
+    ///
+    /// # Panics
+    ///
+    /// - If `capacity` is 0.
+    /// - If the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`.
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    #[must_use]
+    pub fn with_recycle(capacity: usize, recycle: R) -> Self {
+        assert!(capacity > 0);
+        assert!(capacity <= MAX_CAPACITY);
+        let slots = Slot::make_boxed_array(capacity);
+        for (idx, slot) in slots.iter().enumerate() {
+            slot.value.with_mut(|value: *mut MaybeUninit<T>| unsafe {
+                (*value).as_mut_ptr().write(recycle.new_element())
+            });
+            // Initially, every slot is on the free list.
+            slot.state.store(idx + 1, Relaxed);
+        }
+
+        let links = Links {
+            full: CachePadded(AtomicUsize::new(capacity)),
+            free: CachePadded(AtomicUsize::new(0)),
+            len: AtomicUsize::new(0),
+            nil: capacity,
+            idx_mask: (capacity + 1).next_power_of_two() - 1,
+            pop_wait: WaitQueue::new(),
+            #[cfg(feature = "std")]
+            pop_wait_blocking: WaitQueue::new(),
+        };
+        Self {
+            links,
+            slots,
+            recycle,
+        }
+    }
+
+    /// Reserves a slot to push an element onto the stack, and returns a
+    /// [`StackRef`] that can be used to write to that slot.
+    ///
+    /// The slot's previous element is [recycled] before it is returned. Once
+    /// the `StackRef` is dropped, the element can be popped.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(`[`StackRef`]`)` if a slot was reserved
+    /// - `Err(`[`Full`]`)` if there is no capacity remaining in the stack
+    ///
+    /// [recycled]: crate::recycling::Recycle::recycle
+    pub fn push_ref(&self) -> Result<StackRef<'_, T>, Full> {
+        let idx = self
+            .links
+            .pop(&self.links.free, &self.slots)
+            .ok_or(Full(()))?;
+        let mut slot = self.make_ref(idx, false);
+        self.recycle.recycle(&mut slot);
+        Ok(slot)
+    }
+
+    /// Pushes an element onto the stack by value.
+    ///
+    /// Like [`ThingBuf::push`](crate::ThingBuf::push), this replaces the
+    /// element in the reserved slot, rather than reusing it in place.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the element was pushed
+    /// - `Err(`[`Full`]`)`, containing the value, if there is no capacity
+    ///   remaining in the stack
+    #[inline]
+    pub fn push(&self, val: T) -> Result<(), Full<T>> {
+        match self.links.pop(&self.links.free, &self.slots) {
+            Some(idx) => {
+                *self.make_ref(idx, false) = val;
+                Ok(())
+            }
+            None => Err(Full(val)),
+        }
+    }
+
+    /// Reserves a slot to push an element onto the stack, and invokes the
+    /// provided function `f` with a mutable reference to that element.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(U)` containing the return value of the provided function, if the
+    ///   element was pushed
+    /// - `Err(`[`Full`]`)`, if there is no capacity remaining in the stack
+    #[inline]
+    pub fn push_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Result<U, Full> {
+        self.push_ref().map(|mut slot| f(&mut slot))
+    }
+
+    /// Pops the most recently pushed element from the stack, returning a
+    /// [`StackRef`] that can be used to read from (or mutate) the element.
+    ///
+    /// Once the `StackRef` is dropped, the element's slot can be pushed to
+    /// again, reusing the element in place.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(`[`StackRef<T>`](StackRef)`)` if an element was popped
+    /// - `None` if the stack is empty
+    pub fn pop_ref(&self) -> Option<StackRef<'_, T>> {
+        let idx = self.links.pop(&self.links.full, &self.slots)?;
+        self.links.len.fetch_sub(1, Release);
+        Some(self.make_ref(idx, true))
+    }
+
+    /// Pops the most recently pushed element from the stack *by value*,
+    /// moving it out of the stack.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(T)` if an element was popped
+    /// - `None` if the stack is empty
+    #[inline]
+    pub fn pop(&self) -> Option<T> {
+        let mut slot = self.pop_ref()?;
+        Some(recycling::take(&mut *slot, &self.recycle))
+    }
+
+    /// Pops the most recently pushed element from the stack by reference,
+    /// and invokes the provided function `f` with a mutable reference to it.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(U)` containing the return value of the provided function, if
+    ///   an element was popped
+    /// - `None` if the stack is empty
+    #[inline]
+    pub fn pop_with<U>(&self, f: impl FnOnce(&mut T) -> U) -> Option<U> {
+        self.pop_ref().map(|mut slot| f(&mut slot))
+    }
+
+    /// Pops the most recently pushed element from the stack, waiting until
+    /// an element is pushed if the stack is empty.
+    ///
+    /// A task that pops from an empty stack waits until another task or
+    /// thread pushes an element, rather than polling in a loop. Waiting tasks
+    /// are woken in the order in which they started waiting.
+    ///
+    /// Dropping the returned [`StackRef`] frees the element's slot, like
+    /// [`pop_ref`] does; it does *not* push the element back onto the stack.
+    /// To check out an object from a pool, and return it when it is dropped,
+    /// use [`get`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingStack;
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let stack = Arc::new(ThingStack::<usize>::new(2));
+    ///
+    ///     let consumer = {
+    ///         let stack = stack.clone();
+    ///         // Waits until an element is pushed.
+    ///         tokio::spawn(async move { *stack.pop_ref_async().await })
+    ///     };
+    ///
+    ///     stack.push(1).unwrap();
+    ///     assert_eq!(consumer.await.unwrap(), 1);
+    /// }
+    /// ```
+    ///
+    /// [`pop_ref`]: Self::pop_ref
+    /// [`get`]: Self::get
+    pub async fn pop_ref_async(&self) -> StackRef<'_, T> {
+        PopRefFuture {
+            stack: self,
+            queued: false,
+            waiter: queue::Waiter::new(),
+        }
+        .await
+    }
+
+    /// Checks out the most recently returned object from the pool, waiting
+    /// until an object is returned if every object is in use.
+    ///
+    /// This allows a `ThingStack` to be used as an object pool with a hard
+    /// cap on the number of objects. The objects are the elements pushed onto
+    /// the stack. The returned [`PoolRef`] pushes its object back onto the
+    /// stack when it is dropped, after [recycling] it, so that a task waiting
+    /// in `get` can check it out. Waiting tasks are woken in the order in
+    /// which they started waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::ThingStack;
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // A pool of at most 2 buffers.
+    ///     let pool = Arc::new(ThingStack::<Vec<u8>>::new(2));
+    ///     pool.push(Vec::with_capacity(1024)).unwrap();
+    ///     pool.push(Vec::with_capacity(1024)).unwrap();
+    ///
+    ///     let tasks = (0..8).map(|i| {
+    ///         let pool = pool.clone();
+    ///         tokio::spawn(async move {
+    ///             // Waits while both buffers are in use.
+    ///             let mut buf = pool.get().await;
+    ///             buf.push(i);
+    ///             // Dropping `buf` returns it to the pool.
+    ///         })
+    ///     });
+    ///     for task in tasks.collect::<Vec<_>>() {
+    ///         task.await.unwrap();
+    ///     }
+    ///
+    ///     assert_eq!(pool.len(), 2);
+    /// }
+    /// ```
+    ///
+    /// [recycling]: crate::recycling::Recycle::recycle
+    pub async fn get(&self) -> PoolRef<'_, T, R> {
+        self.checkout(self.pop_ref_async().await)
+    }
+
+    /// Checks out the most recently returned object from the pool, without
+    /// waiting.
+    ///
+    /// See [`get`] for details.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(`[`PoolRef`]`)` if an object was checked out
+    /// - `None` if every object is in use
+    ///
+    /// [`get`]: Self::get
+    pub fn try_get(&self) -> Option<PoolRef<'_, T, R>> {
+        self.pop_ref().map(|slot| self.checkout(slot))
+    }
+
+    fn checkout<'stack>(&'stack self, slot: StackRef<'stack, T>) -> PoolRef<'stack, T, R> {
+        PoolRef {
+            slot,
+            recycle: &self.recycle,
+        }
+    }
+
+    fn make_ref(&self, idx: usize, is_pop: bool) -> StackRef<'_, T> {
+        StackRef {
+            ptr: ManuallyDrop::new(self.slots[idx].value.get_mut()),
+            links: &self.links,
+            slots: &self.slots,
+            idx,
+            is_pop,
+        }
+    }
+}
+
+impl<T, R> ThingStack<T, R> {
+    /// Returns the *total* capacity of this stack. This includes both
+    /// occupied and unoccupied entries.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of elements in the stack.
+    ///
+    /// Elements which are still being pushed are not counted.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.links.len.load(Acquire)
+    }
+
+    /// Returns the unoccupied capacity of the stack.
+    ///
+    /// This is equivalent to subtracting the stack's [`len`] from its
+    /// [`capacity`].
+    ///
+    /// [`len`]: Self::len
+    /// [`capacity`]: Self::capacity
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.len()
+    }
+
+    /// Returns `true` if there are currently no elements in this
+    /// `ThingStack`.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T, R> Drop for ThingStack<T, R> {
+    fn drop(&mut self) {
+        for slot in &mut self.slots[..] {
+            unsafe {
+                // Safety: every slot was initialized when the stack was
+                // constructed, and we have exclusive access to the slots.
+                slot.value
+                    .with_mut(|value| ptr::drop_in_place((*value).as_mut_ptr()));
+            }
+        }
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for ThingStack<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThingStack")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .field("recycle", &self.recycle)
+            .finish()
+    }
+}
+
+#[cfg(not(all(loom, test)))]
+feature! {
+    #![feature = "std"]
+
+    use crate::wait;
+    use std::time::Duration;
+
+    impl<T, R> ThingStack<T, R>
+    where
+        R: Recycle<T>,
+    {
+        /// Pops the most recently pushed element from the stack, blocking the
+        /// current thread until an element is pushed if the stack is empty.
+        ///
+        /// The thread is parked until another thread pushes an element, as
+        /// with a [blocking channel]. Like [`pop_ref_async`], dropping the
+        /// returned [`StackRef`] does not push the element back onto the
+        /// stack; see [`get_blocking`] for that.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::ThingStack;
+        /// use std::{sync::Arc, thread};
+        ///
+        /// let stack = Arc::new(ThingStack::<String>::new(1));
+        ///
+        /// let t = {
+        ///     let stack = stack.clone();
+        ///     thread::spawn(move || {
+        ///         // Blocks until an element is pushed.
+        ///         stack.pop_ref_blocking().clone()
+        ///     })
+        /// };
+        ///
+        /// stack.push("hello".to_string()).unwrap();
+        /// assert_eq!(t.join().unwrap(), "hello");
+        /// ```
+        ///
+        /// [blocking channel]: crate::mpsc::blocking
+        /// [`pop_ref_async`]: Self::pop_ref_async
+        /// [`get_blocking`]: Self::get_blocking
+        pub fn pop_ref_blocking(&self) -> StackRef<'_, T> {
+            wait::block_on(&self.links.pop_wait_blocking, None, || self.pop_ref())
+                .expect("waiting without a deadline never times out")
+        }
+
+        /// Pops the most recently pushed element from the stack, blocking the
+        /// current thread for at most `timeout` until an element is pushed if
+        /// the stack is empty.
+        ///
+        /// See [`pop_ref_blocking`] for how the thread waits.
+        ///
+        /// # Returns
+        ///
+        /// - `Some(`[`StackRef<T>`](StackRef)`)` if an element was popped
+        /// - `None` if the stack was still empty once `timeout` had elapsed
+        ///
+        /// [`pop_ref_blocking`]: Self::pop_ref_blocking
+        pub fn pop_ref_timeout(&self, timeout: Duration) -> Option<StackRef<'_, T>> {
+            wait::block_on(&self.links.pop_wait_blocking, Some(timeout), || self.pop_ref())
+        }
+
+        /// Checks out the most recently returned object from the pool,
+        /// blocking the current thread until an object is returned if every
+        /// object is in use.
+        ///
+        /// This is the blocking equivalent of [`get`]: the returned
+        /// [`PoolRef`] returns its object to the pool when it is dropped.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::ThingStack;
+        /// use std::{sync::Arc, thread};
+        ///
+        /// // A pool of a single buffer, shared by several threads.
+        /// let pool = Arc::new(ThingStack::<Vec<u8>>::new(1));
+        /// pool.push(Vec::with_capacity(1024)).unwrap();
+        ///
+        /// let threads = (0..4).map(|i| {
+        ///     let pool = pool.clone();
+        ///     thread::spawn(move || {
+        ///         // Blocks while another thread is using the buffer.
+        ///         let mut buf = pool.get_blocking();
+        ///         buf.push(i);
+        ///     })
+        /// });
+        /// for thread in threads.collect::<Vec<_>>() {
+        ///     thread.join().unwrap();
+        /// }
+        ///
+        /// assert_eq!(pool.len(), 1);
+        /// ```
+        ///
+        /// [`get`]: Self::get
+        pub fn get_blocking(&self) -> PoolRef<'_, T, R> {
+            self.checkout(self.pop_ref_blocking())
+        }
+
+        /// Checks out the most recently returned object from the pool,
+        /// blocking the current thread for at most `timeout` until an object
+        /// is returned if every object is in use.
+        ///
+        /// See [`get_blocking`] for details.
+        ///
+        /// # Returns
+        ///
+        /// - `Some(`[`PoolRef`]`)` if an object was checked out
+        /// - `None` if every object was still in use once `timeout` had
+        ///   elapsed
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use thingbuf::ThingStack;
+        /// use std::time::Duration;
+        ///
+        /// let pool = ThingStack::<Vec<u8>>::new(1);
+        /// pool.push(Vec::new()).unwrap();
+        ///
+        /// let buf = pool.get_timeout(Duration::from_millis(10));
+        /// assert!(buf.is_some());
+        ///
+        /// // The pool's only object is still in use.
+        /// assert!(pool.get_timeout(Duration::from_millis(10)).is_none());
+        ///
+        /// // Once it is returned, it can be checked out again.
+        /// drop(buf);
+        /// assert!(pool.get_timeout(Duration::from_millis(10)).is_some());
+        /// ```
+        ///
+        /// [`get_blocking`]: Self::get_blocking
+        pub fn get_timeout(&self, timeout: Duration) -> Option<PoolRef<'_, T, R>> {
+            self.pop_ref_timeout(timeout).map(|slot| self.checkout(slot))
+        }
+    }
+}
+
+// === impl Links ===
+
+impl Links {
+    /// Removes the first slot from `list`, returning its index.
+    fn pop<T>(&self, list: &AtomicUsize, slots: &[Slot<T>]) -> Option<usize> {
+        let mut backoff = Backoff::new();
+        let mut head = list.load(Acquire);
+        loop {
+            let idx = head & self.idx_mask;
+            if test_dbg!(idx) == self.nil {
+                return None;
+            }
+            // This may read the link of a slot which has since been popped
+            // by another thread, but then the head has changed, and the
+            // compare-exchange fails.
+            let next = slots[idx].state.load(Relaxed);
+            match list.compare_exchange_weak(head, self.retag(head, next), AcqRel, Acquire) {
+                Ok(_) => return Some(idx),
+                Err(actual) => {
+                    head = actual;
+                    backoff.spin();
+                }
+            }
+        }
+    }
+
+    /// Adds the slot at `idx` to the front of `list`.
+    fn push<T>(&self, list: &AtomicUsize, slots: &[Slot<T>], idx: usize) {
+        let mut backoff = Backoff::new();
+        let mut head = list.load(Relaxed);
+        loop {
+            slots[idx].state.store(head & self.idx_mask, Relaxed);
+            match list.compare_exchange_weak(head, self.retag(head, idx), Release, Relaxed) {
+                Ok(_) => return,
+                Err(actual) => {
+                    head = actual;
+                    backoff.spin();
+                }
+            }
+        }
+    }
+
+    /// Wakes a task and a thread waiting to pop, if there are any.
+    ///
+    /// If nothing is waiting, this doesn't store a notification, so pushing
+    /// to a stack that nothing waits on only loads the wait queues' states.
+    /// Instead, waiters retry their pop once more after they are queued.
+    #[inline]
+    fn notify_pushed(&self) {
+        self.pop_wait.notify_waiting();
+        #[cfg(feature = "std")]
+        self.pop_wait_blocking.notify_waiting();
+    }
+
+    /// Returns a new head pointing at `idx`, with the next tag after `head`'s.
+    #[inline]
+    fn retag(&self, head: usize, idx: usize) -> usize {
+        (head & !self.idx_mask).wrapping_add(self.idx_mask + 1) | idx
+    }
+}
+
+// === impl PopRefFuture ===
+
+impl<'stack, T, R> Future for PopRefFuture<'stack, T, R>
+where
+    R: Recycle<T>,
+{
+    type Output = StackRef<'stack, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        test_println!("PopRefFuture::poll({:p})", self);
+        let this = self.project();
+        let stack: &'stack ThingStack<T, R> = this.stack;
+        let mut node = this.waiter;
+        loop {
+            if let Some(slot) = stack.pop_ref() {
+                if *this.queued && node.is_linked() {
+                    node.as_mut().remove(&stack.links.pop_wait);
+                }
+                return Poll::Ready(slot);
+            }
+
+            let wait = if *this.queued {
+                stack
+                    .links
+                    .pop_wait
+                    .continue_wait(node.as_mut(), cx.waker())
+            } else {
+                stack.links.pop_wait.start_wait(node.as_mut(), cx.waker())
+            };
+            match test_dbg!(wait) {
+                // We were just added to the queue, so try again before
+                // returning `Pending`, in case an element was pushed before
+                // the pusher could see us in the queue. This fence pairs with
+                // the one in `notify_waiting`.
+                WaitResult::Wait if !*this.queued => {
+                    *this.queued = true;
+                    fence(SeqCst);
+                }
+                WaitResult::Wait => return Poll::Pending,
+                // An element was pushed; try to pop it.
+                WaitResult::Notified => *this.queued = false,
+                WaitResult::Closed => unreachable!("a `ThingStack`'s wait queue is never closed"),
+            }
+        }
+    }
+}
+
+#[pin_project::pinned_drop]
+impl<T, R> PinnedDrop for PopRefFuture<'_, T, R> {
+    fn drop(self: Pin<&mut Self>) {
+        test_println!("PopRefFuture::drop({:p})", self);
+        let this = self.project();
+        if *this.queued && this.waiter.is_linked() {
+            this.waiter.remove(&this.stack.links.pop_wait)
+        }
+    }
+}
+
+// === impl StackRef ===
+
+impl<T> ops::Deref for StackRef<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            // Safety: if a `StackRef` exists, we have exclusive ownership of
+            // the slot, and every slot is initialized.
+            &*MutPtr::deref(&self.ptr).as_ptr()
+        }
+    }
+}
+
+impl<T> ops::DerefMut for StackRef<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe {
+            // Safety: if a `StackRef` exists, we have exclusive ownership of
+            // the slot, and every slot is initialized.
+            &mut *MutPtr::deref(&self.ptr).as_mut_ptr()
+        }
+    }
+}
+
+impl<T> Drop for StackRef<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        test_println!(
+            "drop StackRef<{}> [{}] (pop: {})",
+            core::any::type_name::<T>(),
+            self.idx,
+            self.is_pop
+        );
+        unsafe {
+            // Safety: `ptr` is not used again.
+            ManuallyDrop::drop(&mut self.ptr);
+        }
+        if self.is_pop {
+            self.links.push(&self.links.free, self.slots, self.idx);
+        } else {
+            // Count the element before it can be popped, so that the length
+            // never underflows.
+            self.links.len.fetch_add(1, Release);
+            self.links.push(&self.links.full, self.slots, self.idx);
+            self.links.notify_pushed();
+        }
+    }
+}
+
+impl<T> AsRef<T> for StackRef<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T> AsMut<T> for StackRef<'_, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for StackRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for StackRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+unsafe impl<T: Send> Send for StackRef<'_, T> {}
+unsafe impl<T: Send> Sync for StackRef<'_, T> {}
+
+// === impl PoolRef ===
+
+impl<T, R: Recycle<T>> ops::Deref for PoolRef<'_, T, R> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.slot
+    }
+}
+
+impl<T, R: Recycle<T>> ops::DerefMut for PoolRef<'_, T, R> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.slot
+    }
+}
+
+impl<T, R: Recycle<T>> Drop for PoolRef<'_, T, R> {
+    #[inline]
+    fn drop(&mut self) {
+        self.recycle.recycle(&mut self.slot);
+        // Turn the popped slot into a pushed one, so that dropping it pushes
+        // the object back onto the stack, rather than freeing the slot.
+        self.slot.is_pop = false;
+    }
+}
+
+impl<T, R: Recycle<T>> AsRef<T> for PoolRef<'_, T, R> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T, R: Recycle<T>> AsMut<T> for PoolRef<'_, T, R> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: fmt::Debug, R: Recycle<T>> fmt::Debug for PoolRef<'_, T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: fmt::Display, R: Recycle<T>> fmt::Display for PoolRef<'_, T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
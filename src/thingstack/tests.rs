@@ -0,0 +1,75 @@
+use super::ThingStack;
+use crate::loom::{self, future, thread};
+use std::sync::Arc;
+
+#[test]
+fn push_pop_concurrent() {
+    loom::model(|| {
+        let stack = Arc::new(ThingStack::new(2));
+
+        let t1 = {
+            let stack = stack.clone();
+            thread::spawn(move || {
+                stack.push(1).unwrap();
+                stack.pop()
+            })
+        };
+
+        stack.push(2).unwrap();
+        let popped = stack.pop();
+
+        let t1_popped = t1.join().unwrap();
+        // each thread pushed one element before popping, so neither stack
+        // was empty, and no element was popped twice.
+        let mut vals = vec![test_dbg!(popped).unwrap(), test_dbg!(t1_popped).unwrap()];
+        vals.sort_unstable();
+        assert_eq!(vals, vec![1, 2]);
+        assert!(stack.is_empty());
+    })
+}
+
+#[test]
+fn reuses_slots() {
+    loom::model(|| {
+        let stack = Arc::new(ThingStack::<usize>::new(1));
+
+        let t1 = {
+            let stack = stack.clone();
+            thread::spawn(move || {
+                for i in 0..2 {
+                    while stack.push(i).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            })
+        };
+
+        for i in 0..2 {
+            loop {
+                if let Some(val) = stack.pop() {
+                    assert_eq!(val, i);
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+        t1.join().unwrap();
+    })
+}
+
+#[test]
+fn pop_ref_async_woken_by_push() {
+    loom::model(|| {
+        let stack = Arc::new(ThingStack::<usize>::new(1));
+
+        let t1 = {
+            let stack = stack.clone();
+            thread::spawn(move || stack.push(1).unwrap())
+        };
+
+        let popped = future::block_on(async { *stack.pop_ref_async().await });
+        assert_eq!(popped, 1);
+        t1.join().unwrap();
+        assert!(stack.is_empty());
+    })
+}
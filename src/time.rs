@@ -0,0 +1,219 @@
+//! Runtime-agnostic timers for the timeout and delay APIs.
+//!
+//! The channel APIs that wait for a timeout or a delay, such as
+//! [`Receiver::timeout_items_with`] and [`Sender::batch_sink_with`], accept
+//! any [`Timer`], rather than depending on a particular async runtime. A
+//! `Timer` creates [`Sleep`] futures, which complete once a duration has
+//! elapsed and can be reset to wait again.
+//!
+//! Implementations are provided for the following runtimes, each behind a
+//! feature flag of the same name:
+//!
+//! | Feature flag        | Timer              | Runtime                                         |
+//! |---------------------|--------------------|-------------------------------------------------|
+//! | `"tokio"`           | [`TokioTimer`]     | [Tokio]                                         |
+//! | `"async-io"`        | [`AsyncIoTimer`]   | [`async-io`], used by `async-std` and `smol`    |
+//! | `"futures-timer"`   | [`FuturesTimer`]   | [`futures-timer`], which works with any runtime |
+//! | `"embassy-time"`    | [`EmbassyTimer`]   | [`embassy-time`], for embedded targets          |
+//!
+//! Timers for other runtimes can be added by implementing [`Timer`] and
+//! [`Sleep`].
+//!
+//! # Examples
+//!
+//! Implementing a timer for a custom runtime:
+//!
+//! ```
+//! use core::{future::Future, pin::Pin, task::{Context, Poll}, time::Duration};
+//! use thingbuf::time::{Sleep, Timer};
+//!
+//! /// A timer for our runtime, which happens to be Tokio.
+//! struct MyTimer;
+//!
+//! struct MySleep(Pin<Box<tokio::time::Sleep>>);
+//!
+//! impl Timer for MyTimer {
+//!     type Sleep = MySleep;
+//!
+//!     fn sleep(&self, duration: Duration) -> MySleep {
+//!         MySleep(Box::pin(tokio::time::sleep(duration)))
+//!     }
+//! }
+//!
+//! impl Future for MySleep {
+//!     type Output = ();
+//!
+//!     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+//!         self.0.as_mut().poll(cx)
+//!     }
+//! }
+//!
+//! impl Sleep for MySleep {
+//!     fn reset(mut self: Pin<&mut Self>, duration: Duration) {
+//!         let deadline = tokio::time::Instant::now() + duration;
+//!         self.0.as_mut().reset(deadline);
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let (tx, rx) = thingbuf::mpsc::channel(8);
+//!     let mut items = rx.timeout_items_with(Duration::from_millis(10), MyTimer);
+//!
+//!     tx.send(1).await.unwrap();
+//!     assert_eq!(items.next().await, Some(Ok(1)));
+//!     assert!(items.next().await.unwrap().is_err());
+//! }
+//! ```
+//!
+//! [`Receiver::timeout_items_with`]: crate::mpsc::Receiver::timeout_items_with
+//! [`Sender::batch_sink_with`]: crate::mpsc::Sender::batch_sink_with
+//! [Tokio]: https://tokio.rs
+//! [`async-io`]: https://crates.io/crates/async-io
+//! [`futures-timer`]: https://crates.io/crates/futures-timer
+//! [`embassy-time`]: https://crates.io/crates/embassy-time
+use core::{future::Future, pin::Pin, time::Duration};
+
+/// Creates [`Sleep`] futures.
+pub trait Timer {
+    /// The future returned by [`sleep`](Self::sleep).
+    type Sleep: Sleep;
+
+    /// Returns a future that completes once `duration` has elapsed.
+    fn sleep(&self, duration: Duration) -> Self::Sleep;
+}
+
+/// A future that completes once a duration has elapsed, and which may be
+/// reset to wait again.
+pub trait Sleep: Future<Output = ()> {
+    /// Resets this future to complete once `duration` has elapsed from now,
+    /// whether or not it has already completed.
+    fn reset(self: Pin<&mut Self>, duration: Duration);
+}
+
+feature! {
+    #![feature = "tokio"]
+
+    /// A [`Timer`] that uses [Tokio's timer](tokio::time::Sleep).
+    ///
+    /// Its futures must be polled within a Tokio runtime with the time driver
+    /// enabled.
+    #[derive(Copy, Clone, Debug, Default)]
+    pub struct TokioTimer;
+
+    impl Timer for TokioTimer {
+        type Sleep = tokio::time::Sleep;
+
+        #[inline]
+        fn sleep(&self, duration: Duration) -> Self::Sleep {
+            tokio::time::sleep(duration)
+        }
+    }
+
+    impl Sleep for tokio::time::Sleep {
+        #[inline]
+        fn reset(self: Pin<&mut Self>, duration: Duration) {
+            tokio::time::Sleep::reset(self, tokio::time::Instant::now() + duration)
+        }
+    }
+}
+
+feature! {
+    #![feature = "async-io"]
+    use core::task::{Context, Poll};
+
+    /// A [`Timer`] that uses [`async_io::Timer`], the timer used by the
+    /// `async-std` and `smol` runtimes.
+    ///
+    /// Its futures may be polled from any executor.
+    #[derive(Copy, Clone, Debug, Default)]
+    pub struct AsyncIoTimer;
+
+    /// The [`Sleep`] future returned by [`AsyncIoTimer`].
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct AsyncIoSleep(async_io::Timer);
+
+    impl Timer for AsyncIoTimer {
+        type Sleep = AsyncIoSleep;
+
+        #[inline]
+        fn sleep(&self, duration: Duration) -> Self::Sleep {
+            AsyncIoSleep(async_io::Timer::after(duration))
+        }
+    }
+
+    impl Future for AsyncIoSleep {
+        type Output = ();
+
+        #[inline]
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            Pin::new(&mut self.0).poll(cx).map(|_| ())
+        }
+    }
+
+    impl Sleep for AsyncIoSleep {
+        #[inline]
+        fn reset(mut self: Pin<&mut Self>, duration: Duration) {
+            self.0.set_after(duration)
+        }
+    }
+}
+
+feature! {
+    #![feature = "futures-timer"]
+
+    /// A [`Timer`] that uses [`futures_timer::Delay`].
+    ///
+    /// Its futures may be polled from any executor.
+    #[derive(Copy, Clone, Debug, Default)]
+    pub struct FuturesTimer;
+
+    impl Timer for FuturesTimer {
+        type Sleep = futures_timer::Delay;
+
+        #[inline]
+        fn sleep(&self, duration: Duration) -> Self::Sleep {
+            futures_timer::Delay::new(duration)
+        }
+    }
+
+    impl Sleep for futures_timer::Delay {
+        #[inline]
+        fn reset(self: Pin<&mut Self>, duration: Duration) {
+            futures_timer::Delay::reset(self.get_mut(), duration)
+        }
+    }
+}
+
+feature! {
+    #![feature = "embassy-time"]
+
+    /// A [`Timer`] that uses [`embassy_time::Timer`].
+    ///
+    /// Durations are rounded to the resolution of the `embassy-time` tick
+    /// rate. A time driver must be linked into the application.
+    #[derive(Copy, Clone, Debug, Default)]
+    pub struct EmbassyTimer;
+
+    impl Timer for EmbassyTimer {
+        type Sleep = embassy_time::Timer;
+
+        #[inline]
+        fn sleep(&self, duration: Duration) -> Self::Sleep {
+            embassy_time::Timer::after(embassy_duration(duration))
+        }
+    }
+
+    impl Sleep for embassy_time::Timer {
+        #[inline]
+        fn reset(mut self: Pin<&mut Self>, duration: Duration) {
+            self.set(embassy_time::Timer::after(embassy_duration(duration)))
+        }
+    }
+
+    fn embassy_duration(duration: Duration) -> embassy_time::Duration {
+        let micros = core::cmp::min(duration.as_micros(), u64::MAX as u128) as u64;
+        embassy_time::Duration::from_micros(micros)
+    }
+}
@@ -0,0 +1,331 @@
+//! A hashed timer wheel built on statically-allocated buffers.
+//!
+//! A [`Wheel`] schedules items to become *expired* after a delay, without
+//! allocating. Time is measured in *ticks* of a fixed duration, chosen when
+//! the wheel is constructed; the application drives the wheel by calling
+//! [`Wheel::tick`] once per tick period, for example from a hardware timer
+//! interrupt or a periodic task. Expired items can then be taken from the
+//! wheel with [`Wheel::pop_expired`], or awaited with [`Wheel::expired`].
+//!
+//! The wheel is divided into `SLOTS` buckets, each of which is a
+//! [`StaticThingBuf`] with space for `CAP` timers. A timer that expires `n`
+//! ticks from now is placed in bucket `(now + n) % SLOTS`, and each call to
+//! `tick` only visits a single bucket. Timers that expire more than `SLOTS`
+//! ticks in the future stay in their bucket for additional rotations of the
+//! wheel.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::timer::Wheel;
+//! use std::time::Duration;
+//!
+//! // A wheel with 8 buckets of 4 timers each, which ticks every 10ms.
+//! static WHEEL: Wheel<&'static str, 8, 4> = Wheel::new(Duration::from_millis(10));
+//!
+//! WHEEL.insert_after(Duration::from_millis(20), "second").unwrap();
+//! WHEEL.insert_after(Duration::from_millis(10), "first").unwrap();
+//!
+//! WHEEL.tick();
+//! assert_eq!(WHEEL.pop_expired(), Some("first"));
+//! assert_eq!(WHEEL.pop_expired(), None);
+//!
+//! WHEEL.tick();
+//! assert_eq!(WHEEL.pop_expired(), Some("second"));
+//! ```
+use crate::{
+    loom::atomic::{AtomicUsize, Ordering::*},
+    wait::{WaitCell, WaitResult},
+    Full, StaticThingBuf,
+};
+use core::{
+    convert::TryFrom,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+/// A hashed timer wheel with `SLOTS` buckets of `CAP` timers each, whose
+/// expired items are returned in a queue of `CAP` items.
+///
+/// See the [module-level documentation](self) for details.
+#[cfg_attr(docsrs, doc(cfg(feature = "static")))]
+pub struct Wheel<T, const SLOTS: usize, const CAP: usize> {
+    buckets: [StaticThingBuf<Entry<T>, CAP>; SLOTS],
+    expired: StaticThingBuf<T, CAP>,
+    now: AtomicUsize,
+    tick: Duration,
+    expired_wait: WaitCell<Waker>,
+}
+
+/// Future returned by [`Wheel::expired`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[cfg_attr(docsrs, doc(cfg(feature = "static")))]
+pub struct Expired<'a, T, const SLOTS: usize, const CAP: usize> {
+    wheel: &'a Wheel<T, SLOTS, CAP>,
+}
+
+#[derive(Clone, Default)]
+struct Entry<T> {
+    /// The tick at which this timer expires.
+    deadline: usize,
+    item: T,
+}
+
+// === impl Wheel ===
+
+impl<T, const SLOTS: usize, const CAP: usize> Wheel<T, SLOTS, CAP> {
+    // Only used to initialize the bucket array, which requires a constant
+    // since `StaticThingBuf` is not `Copy`.
+    #[allow(clippy::declare_interior_mutable_const)]
+    const BUCKET: StaticThingBuf<Entry<T>, CAP> = StaticThingBuf::new();
+
+    /// Returns a new `Wheel` which advances by `tick` every time
+    /// [`tick`](Self::tick) is called.
+    ///
+    /// # Panics
+    ///
+    /// - If `tick` is zero.
+    /// - If `SLOTS` is zero.
+    #[must_use]
+    pub const fn new(tick: Duration) -> Self {
+        assert!(!tick.is_zero(), "tick duration must be non-zero");
+        assert!(SLOTS > 0, "a timer wheel must have at least one slot");
+        Self {
+            buckets: [Self::BUCKET; SLOTS],
+            expired: StaticThingBuf::new(),
+            now: AtomicUsize::new(0),
+            tick,
+            expired_wait: WaitCell::new(),
+        }
+    }
+
+    /// Returns the duration of a single tick of this wheel.
+    #[inline]
+    #[must_use]
+    pub fn tick_duration(&self) -> Duration {
+        self.tick
+    }
+
+    /// Returns the number of times this wheel has [ticked](Self::tick).
+    ///
+    /// This wraps around on overflow.
+    #[inline]
+    #[must_use]
+    pub fn elapsed_ticks(&self) -> usize {
+        self.now.load(Acquire)
+    }
+
+    /// Returns the number of timers that have been inserted and have not yet
+    /// expired.
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.buckets.iter().map(StaticThingBuf::len).sum()
+    }
+
+    /// Returns the number of expired items waiting to be taken from the
+    /// wheel.
+    #[must_use]
+    pub fn expired_len(&self) -> usize {
+        self.expired.len()
+    }
+
+    /// Returns the number of ticks until a timer inserted with the given
+    /// delay expires: the delay rounded up to a whole number of ticks, and
+    /// at least one tick.
+    fn ticks_for(&self, dur: Duration) -> usize {
+        let tick = self.tick.as_nanos();
+        let ticks = (dur.as_nanos() + tick - 1) / tick;
+        usize::try_from(ticks)
+            .ok()
+            .filter(|&ticks| ticks <= isize::MAX as usize)
+            .expect("timer delay is too long for this wheel's tick duration")
+            .max(1)
+    }
+}
+
+impl<T, const SLOTS: usize, const CAP: usize> Wheel<T, SLOTS, CAP>
+where
+    T: Default + Clone,
+{
+    /// Inserts a timer which expires `item` once at least `dur` has elapsed.
+    ///
+    /// The delay is rounded up to a whole number of ticks, and a timer always
+    /// waits for at least one tick. Timers inserted concurrently with a call
+    /// to [`tick`](Self::tick) may expire up to one full rotation of the wheel
+    /// later than requested, but never earlier.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the timer was inserted
+    /// - `Err(`[`Full`]`)` containing the item if the bucket the timer hashes
+    ///   to is at capacity
+    ///
+    /// # Panics
+    ///
+    /// If `dur` is more than `isize::MAX` ticks.
+    pub fn insert_after(&self, dur: Duration, item: T) -> Result<(), Full<T>> {
+        let ticks = self.ticks_for(dur);
+        let deadline = self.now.load(Acquire).wrapping_add(ticks);
+        test_println!("insert_after({:?}) -> deadline {}", dur, deadline);
+        self.buckets[deadline % SLOTS]
+            .push(Entry { deadline, item })
+            .map_err(|full| Full(full.into_inner().item))
+    }
+
+    /// Advances the wheel by one tick, moving every timer in the current
+    /// bucket whose deadline has passed to the expired queue.
+    ///
+    /// This should be called once per [tick duration](Self::tick_duration),
+    /// by a single thread or interrupt handler. Calling it late delays every
+    /// pending timer by the same amount.
+    ///
+    /// If the expired queue is full, a timer that has expired stays in its
+    /// bucket, and is retried the next time the wheel comes around to it.
+    /// If a timer cannot be returned to any bucket because the whole wheel is
+    /// full, it is expired early if there is room in the expired queue, and
+    /// dropped otherwise.
+    ///
+    /// Returns the number of timers that expired.
+    pub fn tick(&self) -> usize {
+        let now = self.now.fetch_add(1, AcqRel).wrapping_add(1);
+        let idx = now % SLOTS;
+        let bucket = &self.buckets[idx];
+        test_println!("tick -> {} (bucket {})", now, idx);
+
+        let mut expired = 0;
+        // Only visit the timers that were in the bucket when we started, so
+        // that timers we put back are not visited again.
+        for _ in 0..bucket.len() {
+            let entry = match bucket.pop() {
+                Some(entry) => entry,
+                None => break,
+            };
+
+            if now.wrapping_sub(entry.deadline) as isize >= 0 {
+                match self.expired.push(entry.item) {
+                    Ok(()) => expired += 1,
+                    Err(full) => self.reschedule(
+                        idx,
+                        Entry {
+                            deadline: entry.deadline,
+                            item: full.into_inner(),
+                        },
+                        &mut expired,
+                    ),
+                }
+            } else {
+                self.reschedule(idx, entry, &mut expired);
+            }
+        }
+
+        if expired > 0 {
+            self.expired_wait.notify();
+        }
+        expired
+    }
+
+    /// Dequeues the next expired item, if there is one.
+    pub fn pop_expired(&self) -> Option<T> {
+        self.expired.pop()
+    }
+
+    /// Waits for the next expired item.
+    ///
+    /// Only a single task should wait for expired items at a time; if several
+    /// tasks wait concurrently, only the most recent one is woken.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::timer::Wheel;
+    /// use std::time::Duration;
+    ///
+    /// static WHEEL: Wheel<u32, 16, 8> = Wheel::new(Duration::from_millis(1));
+    ///
+    /// #[tokio::main(flavor = "current_thread")]
+    /// async fn main() {
+    ///     tokio::spawn(async {
+    ///         loop {
+    ///             tokio::time::sleep(WHEEL.tick_duration()).await;
+    ///             WHEEL.tick();
+    ///         }
+    ///     });
+    ///
+    ///     WHEEL.insert_after(Duration::from_millis(5), 42).unwrap();
+    ///     assert_eq!(WHEEL.expired().await, 42);
+    /// }
+    /// ```
+    pub fn expired(&self) -> Expired<'_, T, SLOTS, CAP> {
+        Expired { wheel: self }
+    }
+
+    /// Returns a timer to the wheel, starting at its own bucket and probing
+    /// the following buckets if that one was filled concurrently.
+    fn reschedule(&self, idx: usize, entry: Entry<T>, expired: &mut usize) {
+        let mut entry = entry;
+        for i in 0..SLOTS {
+            match self.buckets[(idx + i) % SLOTS].push(entry) {
+                Ok(()) => return,
+                Err(full) => entry = full.into_inner(),
+            }
+        }
+
+        test_println!("wheel is full, expiring timer early");
+        if self.expired.push(entry.item).is_ok() {
+            *expired += 1;
+        }
+    }
+}
+
+impl<T, const SLOTS: usize, const CAP: usize> fmt::Debug for Wheel<T, SLOTS, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Wheel")
+            .field("tick", &self.tick)
+            .field("now", &self.now.load(Acquire))
+            .field("pending", &self.pending())
+            .field("expired", &self.expired.len())
+            .finish()
+    }
+}
+
+// === impl Expired ===
+
+impl<T, const SLOTS: usize, const CAP: usize> Future for Expired<'_, T, SLOTS, CAP>
+where
+    T: Default + Clone,
+{
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let wheel = self.wheel;
+        loop {
+            if let Some(item) = wheel.pop_expired() {
+                return Poll::Ready(item);
+            }
+
+            match test_dbg!(wheel.expired_wait.wait_with(|| cx.waker().clone())) {
+                WaitResult::Wait => {
+                    // an item may have expired while we were registering.
+                    if let Some(item) = wheel.pop_expired() {
+                        return Poll::Ready(item);
+                    }
+                    return Poll::Pending;
+                }
+                WaitResult::Notified => core::hint::spin_loop(),
+                // the expired queue's wait cell is never closed.
+                WaitResult::Closed => unreachable!("timer wheel wait cell closed"),
+            }
+        }
+    }
+}
+
+impl<T, const SLOTS: usize, const CAP: usize> fmt::Debug for Expired<'_, T, SLOTS, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Expired")
+            .field("wheel", &self.wheel)
+            .finish()
+    }
+}
@@ -6,17 +6,66 @@ use core::{
 
 pub(crate) mod mutex;
 pub(crate) mod panic;
+#[cfg(all(feature = "prefetch", feature = "alloc"))]
+pub(crate) mod prefetch;
+pub(crate) mod ref_audit;
+pub(crate) mod ref_token;
+pub(crate) mod ref_watch;
 
 #[derive(Debug)]
 pub(crate) struct Backoff(u8);
 
+/// Pads and aligns a value to the length of a cache line.
+///
+/// Using `CachePadded<T>` as the element type of a [`ThingBuf`] or a channel
+/// gives every slot a cache line of its own, so that producers writing small
+/// messages to neighbouring slots don't contend on the same cache line
+/// ("false sharing"). This trades memory for latency: each slot takes up at
+/// least one cache line, and, as a slot also stores its state next to its
+/// element, typically two.
+///
+/// The alignment is 128 bytes on x86_64 and aarch64, where the hardware
+/// prefetches cache lines in pairs, and 64 bytes on other targets.
+///
+/// `CachePadded<T>` implements [`Default`] and [`Clone`] when `T` does, so it
+/// may be used with the [`DefaultRecycle`] policy. Other [recycling
+/// policies] for `T` may be wrapped in a `CachePadded` to recycle
+/// `CachePadded<T>` elements.
+///
+/// # Examples
+///
+/// ```
+/// use thingbuf::{CachePadded, ThingBuf};
+///
+/// let q = ThingBuf::<CachePadded<u64>>::new(8);
+///
+/// q.push(CachePadded::new(1)).unwrap();
+/// assert_eq!(q.pop().map(CachePadded::into_inner), Some(1));
+/// ```
+///
+/// Padding elements that own allocations, while reusing them:
+///
+/// ```
+/// use thingbuf::{recycling::WithCapacity, CachePadded, ThingBuf};
+/// use std::fmt::Write;
+///
+/// let recycle = CachePadded::new(WithCapacity::new());
+/// let q = ThingBuf::<CachePadded<String>, _>::with_recycle(8, recycle);
+///
+/// q.push_with(|s| write!(s, "hello")).unwrap().unwrap();
+/// assert_eq!(q.pop_ref().unwrap().as_str(), "hello");
+/// ```
+///
+/// [`ThingBuf`]: crate::ThingBuf
+/// [`DefaultRecycle`]: crate::recycling::DefaultRecycle
+/// [recycling policies]: crate::recycling::Recycle
 #[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64"), repr(align(128)))]
 #[cfg_attr(
     not(any(target_arch = "x86_64", target_arch = "aarch64")),
     repr(align(64))
 )]
 #[derive(Clone, Copy, Default, Hash, PartialEq, Eq)]
-pub(crate) struct CachePadded<T>(pub(crate) T);
+pub struct CachePadded<T>(pub(crate) T);
 
 // === impl Backoff ===
 
@@ -73,6 +122,24 @@ impl Backoff {
 
 // === impl CachePadded ===
 
+impl<T> CachePadded<T> {
+    /// Pads and aligns `value` to the length of a cache line.
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns the inner value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for CachePadded<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
 impl<T> Deref for CachePadded<T> {
     type Target = T;
 
@@ -8,6 +8,14 @@ use crate::{
 };
 use core::{fmt, ops};
 
+/// The number of spinlocks currently held inside critical sections.
+///
+/// This is only used to check that guards are dropped in the reverse order in
+/// which they were locked; it is only ever modified while a critical section
+/// is held, so it cannot be raced.
+#[cfg(all(feature = "embassy", debug_assertions))]
+static DEPTH: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+
 #[derive(Debug)]
 pub(crate) struct Mutex<T> {
     locked: AtomicBool,
@@ -17,9 +25,17 @@ pub(crate) struct Mutex<T> {
 pub(crate) struct MutexGuard<'lock, T> {
     locked: &'lock AtomicBool,
     data: MutPtr<T>,
+    /// With the "embassy" feature, the lock is held inside a critical section,
+    /// so that an interrupt handler can never preempt the code holding the
+    /// lock and then spin on it forever.
+    #[cfg(feature = "embassy")]
+    restore: critical_section::RestoreState,
+    /// The value of `DEPTH` after this guard was locked.
+    #[cfg(all(feature = "embassy", debug_assertions))]
+    depth: usize,
 }
 
-#[cfg(not(loom))]
+#[cfg(not(all(loom, test)))]
 pub(crate) const fn const_mutex<T>(data: T) -> Mutex<T> {
     Mutex {
         locked: AtomicBool::new(false),
@@ -28,7 +44,7 @@ pub(crate) const fn const_mutex<T>(data: T) -> Mutex<T> {
 }
 
 impl<T> Mutex<T> {
-    #[cfg(loom)]
+    #[cfg(all(loom, test))]
     pub(crate) fn new(data: T) -> Self {
         Self {
             locked: AtomicBool::new(false),
@@ -36,9 +52,28 @@ impl<T> Mutex<T> {
         }
     }
 
+    /// Locks the mutex, spinning until it is available.
+    ///
+    /// With the "embassy" feature, the lock is held inside a critical section
+    /// that is released when the returned guard is dropped. Because
+    /// `critical_section::release` must be called in the reverse order of
+    /// `critical_section::acquire`, guards must never be leaked, and if more
+    /// than one guard is held at a time, they must be dropped in the reverse
+    /// order in which they were locked. Every guard in this crate is dropped at
+    /// the end of the scope that locked it, which satisfies this; in debug
+    /// builds, dropping guards out of order panics.
     #[inline]
     pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
         test_println!("locking {}...", core::any::type_name::<T>());
+        #[cfg(feature = "embassy")]
+        let restore = unsafe {
+            // Safety: the critical section is released when the guard is
+            // dropped, and guards are never leaked, and are dropped in the
+            // reverse order in which they were locked (see above).
+            critical_section::acquire()
+        };
+        #[cfg(all(feature = "embassy", debug_assertions))]
+        let depth = DEPTH.fetch_add(1, Relaxed) + 1;
         let mut backoff = Backoff::new();
         while test_dbg!(self.locked.compare_exchange(false, true, AcqRel, Acquire)).is_err() {
             while self.locked.load(Relaxed) {
@@ -50,6 +85,10 @@ impl<T> Mutex<T> {
         MutexGuard {
             locked: &self.locked,
             data: self.data.get_mut(),
+            #[cfg(feature = "embassy")]
+            restore,
+            #[cfg(all(feature = "embassy", debug_assertions))]
+            depth,
         }
     }
 }
@@ -92,6 +131,19 @@ impl<T> Drop for MutexGuard<'_, T> {
     fn drop(&mut self) {
         test_dbg!(self.locked.store(false, Release));
         test_println!("unlocked!");
+        #[cfg(all(feature = "embassy", debug_assertions))]
+        assert_eq!(
+            DEPTH.fetch_sub(1, Relaxed),
+            self.depth,
+            "spinlock guards must be dropped in the reverse order in which they were locked",
+        );
+        #[cfg(feature = "embassy")]
+        unsafe {
+            // Safety: this is the state returned when the critical section
+            // was acquired in `Mutex::lock`, and critical sections are
+            // released in the reverse order in which they were acquired.
+            critical_section::release(self.restore);
+        }
     }
 }
 
@@ -0,0 +1,68 @@
+//! Prefetch hints for the slots that a batch receive is about to read.
+//!
+//! This module is only compiled when the "prefetch" and "alloc" feature flags
+//! are enabled. Only batch receives are prefetched: `recv_exact`,
+//! `recv_into`, `recv_chunk`, `ThingBuf::pop_into` and `ThingBuf::pop_all`.
+//! Receiving a single message is not.
+//! `prefetch` asks the CPU to start loading the first cache line of a slot's
+//! element into the cache, so that the load overlaps with the processing of
+//! the slots before it. This uses the `_mm_prefetch` intrinsic on x86 and
+//! x86_64 (with SSE), and the `prfm` instruction on aarch64. On other
+//! targets, it does nothing.
+use crate::SlotRef;
+
+/// The number of slots ahead of the one being read that batch receives
+/// prefetch.
+pub(crate) const DISTANCE: usize = 4;
+
+/// Hints that the element in `slot` will be read soon.
+#[inline(always)]
+pub(crate) fn prefetch<T>(slot: SlotRef<'_, T>) {
+    slot.value.with(|ptr| inner::prefetch(ptr.cast::<u8>()));
+}
+
+#[cfg(any(
+    target_arch = "x86_64",
+    all(target_arch = "x86", target_feature = "sse")
+))]
+mod inner {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::{_mm_prefetch, _MM_HINT_T0};
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+
+    #[inline(always)]
+    pub(super) fn prefetch(ptr: *const u8) {
+        unsafe {
+            // Safety: prefetching is only a hint, and never faults, even if
+            // `ptr` does not point to mapped memory.
+            _mm_prefetch(ptr as *const i8, _MM_HINT_T0);
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod inner {
+    #[inline(always)]
+    pub(super) fn prefetch(ptr: *const u8) {
+        unsafe {
+            // Safety: prefetching is only a hint, and never faults, even if
+            // `ptr` does not point to mapped memory.
+            core::arch::asm!(
+                "prfm pldl1keep, [{ptr}]",
+                ptr = in(reg) ptr,
+                options(nostack, readonly, preserves_flags)
+            );
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_arch = "x86_64",
+    all(target_arch = "x86", target_feature = "sse"),
+    target_arch = "aarch64"
+)))]
+mod inner {
+    #[inline(always)]
+    pub(super) fn prefetch(_: *const u8) {}
+}
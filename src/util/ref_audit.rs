@@ -0,0 +1,119 @@
+//! Auditing of a channel's outstanding [`SendRef`]s and [`RecvRef`]s.
+//!
+//! When the "audit-refs" feature flag is enabled, each channel counts the
+//! `SendRef` and `RecvRef` guards that currently exist for it, and panics
+//! when the channel is dropped if any of them are still outstanding. As a
+//! guard borrows the channel, it can only outlive it if it was leaked (for
+//! instance, with [`core::mem::forget`]), which wedges its slot forever: a
+//! leaked `SendRef` is never published, and a leaked `RecvRef` is never
+//! released for reuse.
+//!
+//! Otherwise, a `RefAudit` is zero-sized, and counting guards does nothing.
+//!
+//! [`SendRef`]: crate::mpsc::SendRef
+//! [`RecvRef`]: crate::mpsc::RecvRef
+pub(crate) use self::inner::{AuditGuard, RefAudit};
+
+#[cfg(feature = "audit-refs")]
+mod inner {
+    use crate::{
+        loom::atomic::{AtomicUsize, Ordering::Relaxed},
+        util::panic,
+    };
+    use core::fmt;
+
+    pub(crate) struct RefAudit {
+        sends: AtomicUsize,
+        recvs: AtomicUsize,
+    }
+
+    /// Decrements the number of outstanding guards when dropped.
+    pub(crate) struct AuditGuard<'a>(&'a AtomicUsize);
+
+    impl RefAudit {
+        loom_const_fn! {
+            pub(crate) fn new() -> Self {
+                Self {
+                    sends: AtomicUsize::new(0),
+                    recvs: AtomicUsize::new(0),
+                }
+            }
+        }
+
+        /// Records a new `SendRef`, until the returned guard is dropped.
+        #[inline]
+        pub(crate) fn send(&self) -> AuditGuard<'_> {
+            self.sends.fetch_add(1, Relaxed);
+            AuditGuard(&self.sends)
+        }
+
+        /// Records a new `RecvRef`, until the returned guard is dropped.
+        #[inline]
+        pub(crate) fn recv(&self) -> AuditGuard<'_> {
+            self.recvs.fetch_add(1, Relaxed);
+            AuditGuard(&self.recvs)
+        }
+    }
+
+    impl Drop for RefAudit {
+        fn drop(&mut self) {
+            // Don't panic while already unwinding.
+            if panic::panicking() {
+                return;
+            }
+
+            let sends = self.sends.load(Relaxed);
+            let recvs = self.recvs.load(Relaxed);
+            assert!(
+                sends == 0 && recvs == 0,
+                "a channel was dropped while {} `SendRef`s and {} `RecvRef`s \
+                were still outstanding; they must have been leaked (e.g. with \
+                `mem::forget`), wedging their slots",
+                sends,
+                recvs,
+            );
+        }
+    }
+
+    impl fmt::Debug for RefAudit {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RefAudit")
+                .field("sends", &self.sends.load(Relaxed))
+                .field("recvs", &self.recvs.load(Relaxed))
+                .finish()
+        }
+    }
+
+    impl Drop for AuditGuard<'_> {
+        #[inline]
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Relaxed);
+        }
+    }
+}
+
+#[cfg(not(feature = "audit-refs"))]
+mod inner {
+    use core::marker::PhantomData;
+
+    #[derive(Debug)]
+    pub(crate) struct RefAudit;
+
+    pub(crate) struct AuditGuard<'a>(PhantomData<&'a ()>);
+
+    impl RefAudit {
+        pub(crate) const fn new() -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn send(&self) -> AuditGuard<'_> {
+            AuditGuard(PhantomData)
+        }
+
+        #[inline(always)]
+        pub(crate) fn recv(&self) -> AuditGuard<'_> {
+            AuditGuard(PhantomData)
+        }
+    }
+}
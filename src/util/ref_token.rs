@@ -0,0 +1,107 @@
+//! Generation tokens for [`Ref`](crate::Ref) guards.
+//!
+//! When the "debug-refs" feature flag is enabled, or in debug builds, each
+//! `Ref` remembers the state its slot was in when the `Ref` claimed it, and
+//! checks that the slot is still in that state every time the `Ref` is
+//! accessed. If a bug in the ring buffer's state machine ever hands out a
+//! `Ref` to a slot that has since been claimed by someone else, this turns
+//! what would be silent data corruption into an immediate panic.
+//!
+//! Otherwise, a `RefToken` is zero-sized, and checking it does nothing.
+pub(crate) use self::inner::RefToken;
+
+#[cfg(any(feature = "debug-refs", all(debug_assertions, not(loom))))]
+mod inner {
+    use crate::{
+        loom::atomic::{AtomicUsize, Ordering::Relaxed},
+        HAS_READER,
+    };
+
+    #[derive(Copy, Clone, Debug)]
+    pub(crate) struct RefToken {
+        /// The slot's state when it was claimed.
+        claimed: usize,
+        idx_mask: usize,
+    }
+
+    impl RefToken {
+        /// Returns a token for a slot claimed by a push at position `pos`.
+        #[inline]
+        pub(crate) fn push(pos: usize, idx_mask: usize) -> Self {
+            Self {
+                claimed: pos,
+                idx_mask,
+            }
+        }
+
+        /// Returns a token for a slot claimed by a pop, which set the slot's
+        /// state to `state`.
+        #[inline]
+        pub(crate) fn pop(state: usize, idx_mask: usize) -> Self {
+            Self {
+                claimed: state,
+                idx_mask,
+            }
+        }
+
+        /// Panics if `state` is no longer the state of the slot this token
+        /// was issued for.
+        #[inline]
+        pub(crate) fn check(&self, state: &AtomicUsize, is_pop: bool) {
+            // Only the thread holding the `Ref` may change the state of a
+            // slot that's being written to, so a relaxed load sees the state
+            // it claimed.
+            let actual = state.load(Relaxed);
+            let valid = if is_pop {
+                // A push that skips a slot while it's being read advances
+                // the slot's generation, but leaves its index and the reader
+                // flag untouched.
+                actual & HAS_READER == HAS_READER
+                    && actual & self.idx_mask == self.claimed & self.idx_mask
+            } else {
+                actual == self.claimed
+            };
+
+            if !valid {
+                self.stale(actual, is_pop);
+            }
+        }
+
+        #[cold]
+        #[inline(never)]
+        fn stale(&self, actual: usize, is_pop: bool) -> ! {
+            panic!(
+                "accessed a stale {} `Ref` to slot [{}]: the slot's state was {:#x} \
+                when it was claimed, but is now {:#x}\n\nthis is a bug in \
+                `thingbuf`! please report an issue immediately!",
+                if is_pop { "pop" } else { "push" },
+                self.claimed & self.idx_mask,
+                self.claimed,
+                actual,
+            )
+        }
+    }
+}
+
+#[cfg(not(any(feature = "debug-refs", all(debug_assertions, not(loom)))))]
+mod inner {
+    use crate::loom::atomic::AtomicUsize;
+
+    #[derive(Copy, Clone, Debug)]
+    pub(crate) struct RefToken;
+
+    impl RefToken {
+        #[inline(always)]
+        pub(crate) fn push(_: usize, _: usize) -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn pop(_: usize, _: usize) -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn check(&self, _: &AtomicUsize, _: bool) {}
+    }
+}
@@ -0,0 +1,75 @@
+//! Tracking of outstanding [`SendRef`]s and [`RecvRef`]s by the
+//! [watchdog](crate::watchdog).
+//!
+//! When the "watchdog" feature flag is enabled, each guard holds a
+//! `RefWatch`, which registers the guard with the watchdog when it is
+//! created, and releases it when the guard is dropped.
+//!
+//! Otherwise, a `RefWatch` is zero-sized, and creating it does nothing.
+//!
+//! [`SendRef`]: crate::mpsc::SendRef
+//! [`RecvRef`]: crate::mpsc::RecvRef
+pub(crate) use self::inner::RefWatch;
+
+#[cfg(all(feature = "watchdog", not(all(loom, test))))]
+mod inner {
+    use crate::watchdog::{self, RefKind};
+
+    /// Releases the guard's registration when dropped.
+    pub(crate) struct RefWatch(Option<usize>);
+
+    impl RefWatch {
+        #[inline]
+        pub(crate) fn send() -> Self {
+            Self(Some(watchdog::register(RefKind::Send)))
+        }
+
+        #[inline]
+        pub(crate) fn recv() -> Self {
+            Self(Some(watchdog::register(RefKind::Recv)))
+        }
+
+        /// Returns a `RefWatch` that is not registered with the watchdog.
+        ///
+        /// Registering a guard takes the watchdog's lock, so guards that are
+        /// created where locking is not allowed, such as in a signal handler
+        /// or on a real-time thread, are not tracked.
+        #[cfg(feature = "rt-safe")]
+        #[inline]
+        pub(crate) fn untracked() -> Self {
+            Self(None)
+        }
+    }
+
+    impl Drop for RefWatch {
+        #[inline]
+        fn drop(&mut self) {
+            if let Some(id) = self.0 {
+                watchdog::release(id);
+            }
+        }
+    }
+}
+
+#[cfg(not(all(feature = "watchdog", not(all(loom, test)))))]
+mod inner {
+    pub(crate) struct RefWatch;
+
+    impl RefWatch {
+        #[inline(always)]
+        pub(crate) fn send() -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        pub(crate) fn recv() -> Self {
+            Self
+        }
+
+        #[cfg(feature = "rt-safe")]
+        #[inline(always)]
+        pub(crate) fn untracked() -> Self {
+            Self
+        }
+    }
+}
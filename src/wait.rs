@@ -27,6 +27,8 @@ pub(crate) use self::{cell::WaitCell, queue::WaitQueue};
 
 #[cfg(feature = "std")]
 use crate::loom::thread;
+#[cfg(all(feature = "std", not(all(loom, test))))]
+use crate::mpsc::blocking::park::{self, Unparker};
 
 /// What happened while trying to register to wait.
 #[derive(Debug, Eq, PartialEq)]
@@ -54,6 +56,11 @@ pub(crate) enum WaitResult {
 }
 
 pub(crate) trait Notify: fmt::Debug + Clone {
+    /// Whether a waiter of this type is a thread, which may yield to the OS
+    /// scheduler while spinning before it waits. Async tasks never do, since
+    /// yielding would block the executor's worker thread.
+    const IS_THREAD: bool = false;
+
     fn notify(self);
 
     fn same(&self, other: &Self) -> bool;
@@ -61,6 +68,8 @@ pub(crate) trait Notify: fmt::Debug + Clone {
 
 #[cfg(feature = "std")]
 impl Notify for thread::Thread {
+    const IS_THREAD: bool = true;
+
     #[inline]
     fn notify(self) {
         test_println!("NOTIFYING {:?} (from {:?})", self, thread::current());
@@ -85,3 +94,84 @@ impl Notify for Waker {
         other.will_wake(self)
     }
 }
+
+/// Calls `f` until it returns `Some`, parking the current thread on `queue`
+/// between attempts, and giving up once `timeout` has elapsed.
+///
+/// This is how the blocking methods of the lock-free queues, such as
+/// `ThingBuf::push_ref_blocking`, wait. Those queues only notify their wait
+/// queues with [`WaitQueue::notify_waiting`], so `f` is retried once more
+/// after the thread is added to the queue, before it parks.
+#[cfg(all(feature = "std", not(all(loom, test))))]
+pub(crate) fn block_on<U>(
+    queue: &WaitQueue<Unparker>,
+    timeout: Option<std::time::Duration>,
+    mut f: impl FnMut() -> Option<U>,
+) -> Option<U> {
+    use crate::loom::atomic::{fence, Ordering::SeqCst};
+    use core::pin::Pin;
+    use std::time::Instant;
+
+    if let Some(val) = f() {
+        return Some(val);
+    }
+
+    // A timeout too long to represent never elapses.
+    let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+    let mut waiter = queue::Waiter::new();
+    let mut queued = false;
+    let unparker = park::current();
+    loop {
+        let node = unsafe {
+            // Safety: in this case, it's totally safe to pin the waiter, as it
+            // is owned uniquely by this function, and it cannot possibly be
+            // moved while this thread is parked.
+            Pin::new_unchecked(&mut waiter)
+        };
+
+        let wait = if queued {
+            queue.continue_wait(node, &unparker)
+        } else {
+            queue.start_wait(node, &unparker)
+        };
+        let notified = match test_dbg!(wait) {
+            // We were just added to the queue, so retry before parking, in
+            // case the operation became possible before a notifier could see
+            // us in the queue. This fence pairs with the one in
+            // `notify_waiting`.
+            WaitResult::Wait if !queued => {
+                queued = true;
+                fence(SeqCst);
+                false
+            }
+            WaitResult::Wait => {
+                match deadline {
+                    Some(deadline) => {
+                        park::park_timeout(deadline.saturating_duration_since(Instant::now()))
+                    }
+                    None => park::park(),
+                }
+                false
+            }
+            WaitResult::Notified => {
+                queued = false;
+                true
+            }
+            WaitResult::Closed => unreachable!("lock-free queues' wait queues are never closed"),
+        };
+
+        let val = f();
+        if val.is_some() || deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+            // don't leave a dangling pointer to the waiter in the queue.
+            let node = unsafe { Pin::new_unchecked(&mut waiter) };
+            if queued && node.is_linked() {
+                node.remove(queue);
+            } else if notified && val.is_none() {
+                // We consumed a wakeup without using it, so pass it on to the
+                // next waiting thread.
+                queue.notify_waiting();
+            }
+            return val;
+        }
+    }
+}
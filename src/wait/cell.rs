@@ -44,19 +44,12 @@ struct State(usize);
 // === impl WaitCell ===
 
 impl<T> WaitCell<T> {
-    #[cfg(not(all(loom, test)))]
-    pub(crate) const fn new() -> Self {
-        Self {
-            lock: CachePadded(AtomicUsize::new(State::WAITING.0)),
-            waiter: UnsafeCell::new(None),
-        }
-    }
-
-    #[cfg(all(loom, test))]
-    pub(crate) fn new() -> Self {
-        Self {
-            lock: CachePadded(AtomicUsize::new(State::WAITING.0)),
-            waiter: UnsafeCell::new(None),
+    loom_const_fn! {
+        pub(crate) fn new() -> Self {
+            Self {
+                lock: CachePadded(AtomicUsize::new(State::WAITING.0)),
+                waiter: UnsafeCell::new(None),
+            }
         }
     }
 }
@@ -150,14 +143,21 @@ impl<T: Notify> WaitCell<T> {
     }
 
     pub(crate) fn notify(&self) -> bool {
-        self.notify2(State::WAITING)
+        self.notify2(State::WAITING, T::notify)
+    }
+
+    /// Like `notify`, but wakes the waiter by passing it to `wake`, rather
+    /// than by calling [`Notify::notify`].
+    #[cfg(feature = "std")]
+    pub(crate) fn notify_with(&self, wake: impl FnOnce(T)) -> bool {
+        self.notify2(State::WAITING, wake)
     }
 
     pub(crate) fn close_tx(&self) {
-        self.notify2(State::TX_CLOSED);
+        self.notify2(State::TX_CLOSED, T::notify);
     }
 
-    fn notify2(&self, close: State) -> bool {
+    fn notify2(&self, close: State, wake: impl FnOnce(T)) -> bool {
         test_println!("notifying; close={:?};", close);
         let bits = State::NOTIFYING | close;
         test_dbg!(bits);
@@ -168,7 +168,7 @@ impl<T: Notify> WaitCell<T> {
             test_dbg!(self.fetch_and(!State::NOTIFYING, AcqRel));
 
             if let Some(waiter) = test_dbg!(waiter) {
-                waiter.notify();
+                wake(waiter);
                 return true;
             }
         }
@@ -159,7 +159,7 @@ const WAKING: usize = 2;
 const CLOSED: usize = 3;
 
 impl<T> WaitQueue<T> {
-    #[cfg(loom)]
+    #[cfg(all(loom, test))]
     pub(crate) fn new() -> Self {
         Self {
             state: CachePadded(AtomicUsize::new(EMPTY)),
@@ -167,7 +167,7 @@ impl<T> WaitQueue<T> {
         }
     }
 
-    #[cfg(not(loom))]
+    #[cfg(not(all(loom, test)))]
     pub(crate) const fn new() -> Self {
         Self {
             state: CachePadded(AtomicUsize::new(EMPTY)),
@@ -391,6 +391,74 @@ impl<T: Notify + Unpin> WaitQueue<T> {
         self.notify_slow(state)
     }
 
+    /// Notify one waiter from the queue, if there are any waiters in the linked
+    /// list.
+    ///
+    /// Unlike [`notify`](Self::notify), this never stores a notification in
+    /// the queue, so if nothing is waiting, it only loads the queue's state,
+    /// rather than writing to it. This makes it cheap enough to call after
+    /// every operation on a queue that is rarely waited on. In exchange, a
+    /// waiter that is notified this way must retry its operation once more
+    /// after it is added to the queue, and after a `SeqCst` fence, before it
+    /// waits: the fence here pairs with that one, so that either the waiter
+    /// sees the caller's change, or this sees the waiter.
+    ///
+    /// Returns `true` if a waiter was popped from the queue.
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    pub(crate) fn notify_waiting(&self) -> bool {
+        test_println!("WaitQueue::notify_waiting()");
+        crate::loom::atomic::fence(SeqCst);
+        let state = self.state.load(Relaxed);
+        if test_dbg!(state) != WAITING {
+            return false;
+        }
+        self.notify_slow(state)
+    }
+
+    /// Notify every waiter in the queue, if there are any.
+    ///
+    /// Like [`notify_waiting`](Self::notify_waiting), this never stores a
+    /// notification in the queue, and only loads the queue's state if nothing
+    /// is waiting, so waiters notified this way must also retry once more
+    /// after they are added to the queue, and after a `SeqCst` fence.
+    ///
+    /// Returns the number of waiters that were popped from the queue.
+    #[cfg(feature = "seq")]
+    #[inline(always)]
+    pub(crate) fn notify_all_waiting(&self) -> usize {
+        test_println!("WaitQueue::notify_all_waiting()");
+        crate::loom::atomic::fence(SeqCst);
+        if test_dbg!(self.state.load(Relaxed)) != WAITING {
+            return 0;
+        }
+        self.notify_all_slow()
+    }
+
+    /// Slow path for `notify_all_waiting`: acquire the lock on the linked
+    /// list, and dequeue and notify every waiter.
+    #[cfg(feature = "seq")]
+    #[cold]
+    #[inline(never)]
+    fn notify_all_slow(&self) -> usize {
+        let mut list = self.list.lock();
+        // Reload the queue's state, as the waiters may have been woken while
+        // we were waiting to lock the linked list.
+        if test_dbg!(self.state.load(Acquire)) != WAITING {
+            return 0;
+        }
+
+        let mut notified = 0;
+        while !list.is_empty() {
+            if let Some(waiter) = list.dequeue(WAKING) {
+                waiter.notify();
+                notified += 1;
+            }
+        }
+        let _ = test_dbg!(self.state.compare_exchange(WAITING, EMPTY, SeqCst, SeqCst));
+        notified
+    }
+
     /// Slow path for `notify`: acquire the lock on the linked list, dequeue a
     /// waiter, and notify it.
     #[cold]
@@ -429,9 +497,12 @@ impl<T: Notify + Unpin> WaitQueue<T> {
                     return true;
                 }
             }
+            // The queue was closed, either before `notify` was called, or
+            // after it saw a waiter but before we acquired the lock. Closing
+            // the queue wakes every waiter, so there's no one left to notify.
+            CLOSED => {}
             _weird => {
-                // huh, if `notify_slow` was called, we *probably* shouldn't be
-                // in the `closed` state...
+                // huh, there are no other states...
                 #[cfg(debug_assertions)]
                 unreachable!("notify_slow: unexpected state value {:?}", _weird);
             }
@@ -439,6 +510,65 @@ impl<T: Notify + Unpin> WaitQueue<T> {
         false
     }
 
+    /// Notify up to `n` waiters from the queue, while holding the lock on the
+    /// linked list only once. If there are fewer than `n` waiters, the
+    /// remaining notification is assigned to the queue itself, like
+    /// [`notify`](Self::notify) does.
+    ///
+    /// Returns the number of waiters that were popped from the queue.
+    pub(crate) fn notify_n(&self, n: usize) -> usize {
+        test_println!("WaitQueue::notify_n({})", n);
+        if n == 0 {
+            return 0;
+        }
+
+        // Fast path: If the queue is empty, we can simply assign the
+        // notification to the queue.
+        let mut state = self.state.load(Acquire);
+        while test_dbg!(state) == WAKING || state == EMPTY {
+            match test_dbg!(self
+                .state
+                .compare_exchange_weak(state, WAKING, SeqCst, SeqCst))
+            {
+                Ok(_) => return 0,
+                Err(actual) => state = actual,
+            }
+        }
+
+        if state == CLOSED {
+            return 0;
+        }
+
+        let mut list = self.list.lock();
+        // Reload the queue's state, as the waiters may have been woken while
+        // we were waiting to lock the linked list.
+        if test_dbg!(self.state.load(Acquire)) != WAITING {
+            drop(list);
+            return usize::from(self.notify());
+        }
+
+        let mut notified = 0;
+        while notified < n {
+            match list.dequeue(WAKING) {
+                Some(waiter) => {
+                    // As in `close`, the waiters are woken while the lock is
+                    // held, so that they don't have to be buffered.
+                    waiter.notify();
+                    notified += 1;
+                }
+                None => break,
+            }
+        }
+
+        // If we popped the last node, transition back to the empty state, or
+        // store a notification if there were fewer waiters than `n`.
+        if test_dbg!(list.is_empty()) {
+            let state = if notified < n { WAKING } else { EMPTY };
+            self.state.store(state, SeqCst);
+        }
+        notified
+    }
+
     /// Close the queue, notifying all waiting tasks.
     pub(crate) fn close(&self) {
         test_println!("WaitQueue::close()");
@@ -451,6 +581,13 @@ impl<T: Notify + Unpin> WaitQueue<T> {
             }
         }
     }
+
+    /// Returns `true` if nothing is waiting on the queue, and no notification
+    /// is stored in it.
+    #[cfg(all(test, feature = "seq"))]
+    pub(crate) fn is_idle(&self) -> bool {
+        test_dbg!(self.state.load(SeqCst)) == EMPTY
+    }
 }
 
 // === impl Waiter ===
@@ -711,6 +848,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn notify_n() {
+        let q = WaitQueue::new();
+
+        let notifies = [MockNotify::new(), MockNotify::new(), MockNotify::new()];
+        let mut waiters = [
+            Box::pin(Waiter::new()),
+            Box::pin(Waiter::new()),
+            Box::pin(Waiter::new()),
+        ];
+        for (waiter, notify) in waiters.iter_mut().zip(&notifies) {
+            assert_eq_dbg!(q.start_wait(waiter.as_mut(), notify), WaitResult::Wait);
+        }
+
+        // Waiters are woken in the order they started waiting.
+        assert_eq_dbg!(q.notify_n(2), 2);
+        assert_dbg!(notifies[0].was_notified());
+        assert_dbg!(notifies[1].was_notified());
+        assert_dbg!(!notifies[2].was_notified());
+        assert_dbg!(waiters[2].is_linked());
+
+        // Waking more waiters than are waiting stores a notification for the
+        // next one.
+        assert_eq_dbg!(q.notify_n(5), 1);
+        assert_dbg!(notifies[2].was_notified());
+        assert_dbg!(!waiters[2].is_linked());
+
+        let notify4 = MockNotify::new();
+        let mut waiter4 = Box::pin(Waiter::new());
+        assert_eq_dbg!(
+            q.start_wait(waiter4.as_mut(), &notify4),
+            WaitResult::Notified
+        );
+    }
+
     #[test]
     fn close() {
         let q = WaitQueue::new();
@@ -749,6 +921,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn notify_after_close() {
+        let q = WaitQueue::new();
+
+        let notify1 = MockNotify::new();
+        let mut waiter1 = Box::pin(Waiter::new());
+
+        assert_eq_dbg!(q.start_wait(waiter1.as_mut(), &notify1), WaitResult::Wait);
+        q.close();
+        assert_dbg!(notify1.was_notified());
+
+        // notifying a closed queue is a no-op.
+        assert_dbg!(!q.notify());
+        assert_eq_dbg!(
+            q.continue_wait(waiter1.as_mut(), &notify1),
+            WaitResult::Closed
+        );
+    }
+
     #[test]
     fn remove_from_middle() {
         let q = WaitQueue::new();
@@ -0,0 +1,277 @@
+//! Reporting of [`SendRef`]s and [`RecvRef`]s that are held for too long.
+//!
+//! A [`SendRef`] or [`RecvRef`] claims its slot until it is dropped. If the
+//! code holding one gets stuck, the slot is never published or released, and
+//! once the ring wraps around to it, the whole channel stalls, with no
+//! indication of which guard is responsible.
+//!
+//! When the `watchdog` feature is enabled, the time at which each `SendRef`
+//! and `RecvRef` is created is recorded, along with the thread that created
+//! it. A guard that has been held for longer than the watchdog's
+//! [threshold](set_threshold) is reported to the watchdog's
+//! [handler](set_handler), which prints a message to standard error by
+//! default. Each guard is reported at most once: either when it is dropped,
+//! or, for guards that are still held, by the next call to [`check`]. As a
+//! guard that is stuck may never be dropped, a program that uses the
+//! watchdog should call `check` periodically, such as from a monitoring
+//! thread.
+//!
+//! The watchdog's settings, and the guards it tracks, are shared by every
+//! channel in the process. Tracking a guard takes a lock, so this feature
+//! is meant for debugging and operations, rather than for
+//! performance-sensitive production builds. For the same reason, messages
+//! sent and received through the `mpsc::rt` handles, or sent with
+//! `try_send_from_signal`, are never tracked, so that those operations stay
+//! lock-free.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::{mpsc::blocking, watchdog};
+//! use std::{sync::mpsc, thread, time::Duration};
+//!
+//! let (reports_tx, reports) = mpsc::channel();
+//! let reports_tx = std::sync::Mutex::new(reports_tx);
+//! watchdog::set_threshold(Duration::from_millis(10));
+//! watchdog::set_handler(move |held| {
+//!     reports_tx.lock().unwrap().send(held.to_string()).unwrap();
+//! });
+//!
+//! let (tx, rx) = blocking::channel::<u32>(4);
+//! tx.send(1).unwrap();
+//!
+//! // A consumer that holds on to a message for too long...
+//! let msg = rx.recv_ref().unwrap();
+//! thread::sleep(Duration::from_millis(20));
+//!
+//! // ...is reported by the next check.
+//! assert_eq!(watchdog::check(), 1);
+//! println!("{}", reports.recv().unwrap());
+//! drop(msg);
+//! ```
+//!
+//! [`SendRef`]: crate::mpsc::SendRef
+//! [`RecvRef`]: crate::mpsc::RecvRef
+use crate::util::mutex::{const_mutex, Mutex};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+};
+use std::{
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+/// The threshold used until [`set_threshold`] is called.
+pub const DEFAULT_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// The kind of a guard reported by the watchdog.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum RefKind {
+    /// A [`SendRef`](crate::mpsc::SendRef), which has not yet been sent.
+    Send,
+    /// A [`RecvRef`](crate::mpsc::RecvRef), whose slot has not yet been
+    /// released.
+    Recv,
+}
+
+/// A guard that was held for longer than the watchdog's threshold.
+///
+/// This is passed to the watchdog's [handler](set_handler). Its
+/// [`Display`](fmt::Display) implementation describes the guard in a
+/// message suitable for logging.
+#[derive(Clone, Debug)]
+pub struct HeldRef {
+    kind: RefKind,
+    held_for: Duration,
+    thread: Thread,
+    released: bool,
+}
+
+type Handler = Box<dyn Fn(&HeldRef) + Send + Sync>;
+
+struct Registry {
+    entries: Vec<Option<Entry>>,
+    free: Vec<usize>,
+}
+
+struct Entry {
+    kind: RefKind,
+    created: Instant,
+    thread: Thread,
+    reported: bool,
+}
+
+static THRESHOLD_MICROS: AtomicU64 = AtomicU64::new(DEFAULT_THRESHOLD.as_micros() as u64);
+static REGISTRY: Mutex<Registry> = const_mutex(Registry {
+    entries: Vec::new(),
+    free: Vec::new(),
+});
+static HANDLER: Mutex<Option<Handler>> = const_mutex(None);
+
+/// Sets how long a guard may be held before it is reported.
+///
+/// By default, this is [`DEFAULT_THRESHOLD`].
+pub fn set_threshold(threshold: Duration) {
+    let micros = core::cmp::min(threshold.as_micros(), u64::MAX as u128) as u64;
+    THRESHOLD_MICROS.store(micros, Relaxed);
+}
+
+/// Returns how long a guard may be held before it is reported.
+#[must_use]
+pub fn threshold() -> Duration {
+    Duration::from_micros(THRESHOLD_MICROS.load(Relaxed))
+}
+
+/// Sets the function that guards held for longer than the
+/// [threshold](set_threshold) are reported to, replacing the previous one.
+///
+/// By default, each report is printed to standard error. The handler is
+/// called on the thread that dropped the guard, or that called [`check`].
+/// It must not call `set_handler` itself, as that would deadlock.
+pub fn set_handler(handler: impl Fn(&HeldRef) + Send + Sync + 'static) {
+    *HANDLER.lock() = Some(Box::new(handler));
+}
+
+/// Reports every guard that is still held, and has been held for longer than
+/// the [threshold](set_threshold), which has not already been reported.
+///
+/// Returns the number of guards that are currently held for longer than the
+/// threshold, including those that were reported by previous calls.
+pub fn check() -> usize {
+    let threshold = threshold();
+    let now = Instant::now();
+    let mut overdue = 0;
+    let mut reports = Vec::new();
+    {
+        let mut registry = REGISTRY.lock();
+        for entry in registry.entries.iter_mut().flatten() {
+            let held_for = now.saturating_duration_since(entry.created);
+            if held_for <= threshold {
+                continue;
+            }
+            overdue += 1;
+            if !entry.reported {
+                entry.reported = true;
+                reports.push(entry.report(held_for, false));
+            }
+        }
+    }
+
+    // Don't hold the registry's lock while calling the handler, which may
+    // create or drop guards.
+    for held in &reports {
+        report(held);
+    }
+    overdue
+}
+
+/// Starts tracking a guard, returning its ID.
+pub(crate) fn register(kind: RefKind) -> usize {
+    let entry = Entry {
+        kind,
+        created: Instant::now(),
+        thread: thread::current(),
+        reported: false,
+    };
+    let mut registry = REGISTRY.lock();
+    match registry.free.pop() {
+        Some(id) => {
+            registry.entries[id] = Some(entry);
+            id
+        }
+        None => {
+            registry.entries.push(Some(entry));
+            registry.entries.len() - 1
+        }
+    }
+}
+
+/// Stops tracking the guard with the given ID, reporting it if it was held
+/// for longer than the threshold.
+pub(crate) fn release(id: usize) {
+    let entry = {
+        let mut registry = REGISTRY.lock();
+        let entry = registry.entries[id].take();
+        registry.free.push(id);
+        entry
+    };
+
+    if let Some(entry) = entry.filter(|entry| !entry.reported) {
+        let held_for = entry.created.elapsed();
+        if held_for > threshold() {
+            report(&entry.report(held_for, true));
+        }
+    }
+}
+
+fn report(held: &HeldRef) {
+    match &*HANDLER.lock() {
+        Some(handler) => handler(held),
+        None => std::eprintln!("{}", held),
+    }
+}
+
+// === impl Entry ===
+
+impl Entry {
+    fn report(&self, held_for: Duration, released: bool) -> HeldRef {
+        HeldRef {
+            kind: self.kind,
+            held_for,
+            thread: self.thread.clone(),
+            released,
+        }
+    }
+}
+
+// === impl HeldRef ===
+
+impl HeldRef {
+    /// Returns whether the guard is a `SendRef` or a `RecvRef`.
+    #[must_use]
+    pub fn kind(&self) -> RefKind {
+        self.kind
+    }
+
+    /// Returns how long the guard had been held when it was reported.
+    #[must_use]
+    pub fn held_for(&self) -> Duration {
+        self.held_for
+    }
+
+    /// Returns the thread that created the guard.
+    #[must_use]
+    pub fn thread(&self) -> &Thread {
+        &self.thread
+    }
+
+    /// Returns `true` if the guard was reported when it was dropped, or
+    /// `false` if it was still held when it was reported by [`check`].
+    #[must_use]
+    pub fn is_released(&self) -> bool {
+        self.released
+    }
+}
+
+impl fmt::Display for HeldRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            RefKind::Send => "SendRef",
+            RefKind::Recv => "RecvRef",
+        };
+        write!(
+            f,
+            "thingbuf watchdog: a `{}` created by thread {:?} ",
+            kind,
+            self.thread.name().unwrap_or("<unnamed>"),
+        )?;
+        if self.released {
+            write!(f, "was held for {:?}", self.held_for)?;
+        } else {
+            write!(f, "has been held for {:?}", self.held_for)?;
+        }
+        write!(f, ", longer than the threshold of {:?}", threshold())
+    }
+}
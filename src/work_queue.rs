@@ -0,0 +1,301 @@
+//! A bounded work queue with multiple priority levels.
+//!
+//! A [`WorkQueue`] combines several fixed-capacity rings, one per priority
+//! level, behind a single [`pop`] method. Workers always receive the
+//! highest-priority item available, but a lower priority level that has been
+//! passed over too many times in a row is *aged*: its next item is promoted
+//! ahead of higher-priority items, so that a steady stream of urgent work
+//! cannot starve background work forever.
+//!
+//! # Examples
+//!
+//! ```
+//! use thingbuf::work_queue::WorkQueue;
+//!
+//! // Two priority levels, each with room for 16 items.
+//! let q = WorkQueue::new(2, 16);
+//! q.push(1, "background").unwrap();
+//! q.push(0, "urgent").unwrap();
+//!
+//! assert_eq!(q.pop(), Some("urgent"));
+//! assert_eq!(q.pop(), Some("background"));
+//! assert_eq!(q.pop(), None);
+//! ```
+//!
+//! [`pop`]: WorkQueue::pop
+use crate::{
+    loom::atomic::{AtomicUsize, Ordering::*},
+    recycling::{self, Recycle},
+    Full, Ref, ThingBuf,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+
+/// The number of times a non-empty priority level may be passed over before
+/// its next item is promoted, unless configured otherwise with
+/// [`WorkQueue::set_aging`].
+pub const DEFAULT_AGING: usize = 16;
+
+/// A bounded, multi-producer, multi-consumer work queue with priority levels
+/// and aging.
+///
+/// See the [module-level documentation](self) for details.
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub struct WorkQueue<T, R = recycling::DefaultRecycle> {
+    levels: Box<[Level<T, R>]>,
+    /// The number of pops a non-empty level may be passed over before it is
+    /// promoted. 0 disables aging.
+    aging: AtomicUsize,
+}
+
+struct Level<T, R> {
+    buf: ThingBuf<T, R>,
+    /// How many items were popped from higher-priority levels while this
+    /// level was non-empty, since an item was last popped from it.
+    passed: AtomicUsize,
+}
+
+// === impl WorkQueue ===
+
+impl<T: Default + Clone> WorkQueue<T> {
+    /// Returns a new `WorkQueue` with `levels` priority levels, each with
+    /// space for `capacity` items.
+    ///
+    /// Aging is enabled with a threshold of [`DEFAULT_AGING`].
+    ///
+    /// # Panics
+    ///
+    /// - If `levels` is 0.
+    /// - If the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`.
+    pub fn new(levels: usize, capacity: usize) -> Self {
+        Self::with_recycle(levels, capacity, recycling::DefaultRecycle::new())
+    }
+}
+
+impl<T, R> WorkQueue<T, R>
+where
+    R: Recycle<T> + Clone,
+{
+    /// Returns a new `WorkQueue` with `levels` priority levels, each with
+    /// space for `capacity` items, and the provided [recycling policy].
+    ///
+    /// Aging is enabled with a threshold of [`DEFAULT_AGING`].
+    ///
+    /// # Panics
+    ///
+    /// - If `levels` is 0.
+    /// - If the capacity exceeds `usize::MAX & !(1 << (usize::BITS - 1))`.
+    ///
+    /// [recycling policy]: crate::recycling::Recycle
+    pub fn with_recycle(levels: usize, capacity: usize, recycle: R) -> Self {
+        assert!(levels > 0, "a work queue must have at least one level");
+        let levels = (0..levels)
+            .map(|_| Level {
+                buf: ThingBuf::with_recycle(capacity, recycle.clone()),
+                passed: AtomicUsize::new(0),
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        Self {
+            levels,
+            aging: AtomicUsize::new(DEFAULT_AGING),
+        }
+    }
+}
+
+impl<T, R> WorkQueue<T, R>
+where
+    R: Recycle<T>,
+{
+    /// Reserves a slot at the given `priority` level, returning a [`Ref`]
+    /// to write the item into.
+    ///
+    /// Priority 0 is the highest priority.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(`[`Ref`]`)` if a slot was reserved
+    /// - `Err(`[`Full`]`)` if that priority level is at capacity
+    ///
+    /// # Panics
+    ///
+    /// If `priority` is not less than the number of [levels].
+    ///
+    /// [levels]: Self::levels
+    pub fn push_ref(&self, priority: usize) -> Result<Ref<'_, T>, Full> {
+        self.level(priority).buf.push_ref()
+    }
+
+    /// Pushes an item at the given `priority` level.
+    ///
+    /// Priority 0 is the highest priority.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the item was enqueued
+    /// - `Err(`[`Full`]`)` containing the item if that priority level is at
+    ///   capacity
+    ///
+    /// # Panics
+    ///
+    /// If `priority` is not less than the number of [levels].
+    ///
+    /// [levels]: Self::levels
+    pub fn push(&self, priority: usize, val: T) -> Result<(), Full<T>> {
+        self.level(priority).buf.push(val)
+    }
+
+    /// Dequeues the next item by reference.
+    ///
+    /// This returns the item at the head of the highest-priority non-empty
+    /// level, unless a lower-priority level has been passed over at least
+    /// as many times as the [aging threshold], in which case the item at the
+    /// head of that level is returned instead.
+    ///
+    /// Returns `None` if every level is empty.
+    ///
+    /// [aging threshold]: Self::set_aging
+    pub fn pop_ref(&self) -> Option<Ref<'_, T>> {
+        self.pop_level().map(|(_, slot)| slot)
+    }
+
+    /// Dequeues the next item *by value*.
+    ///
+    /// See [`pop_ref`](Self::pop_ref) for how the next item is chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use thingbuf::work_queue::WorkQueue;
+    ///
+    /// let q = WorkQueue::new(2, 64);
+    /// q.set_aging(Some(2));
+    ///
+    /// q.push(1, 100).unwrap();
+    /// for i in 0..4 {
+    ///     q.push(0, i).unwrap();
+    /// }
+    ///
+    /// // After the low-priority item is passed over twice, it is promoted.
+    /// assert_eq!(q.pop(), Some(0));
+    /// assert_eq!(q.pop(), Some(1));
+    /// assert_eq!(q.pop(), Some(100));
+    /// assert_eq!(q.pop(), Some(2));
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        let (level, mut slot) = self.pop_level()?;
+        Some(recycling::take(&mut *slot, &level.buf.recycle))
+    }
+
+    /// Pops the next item, returning it along with the level it came from.
+    fn pop_level(&self) -> Option<(&Level<T, R>, Ref<'_, T>)> {
+        let aging = self.aging.load(Relaxed);
+        if aging > 0 {
+            // Serve the highest-priority starved level first.
+            for level in self.levels.iter().skip(1) {
+                if level.passed.load(Relaxed) >= aging {
+                    level.passed.store(0, Relaxed);
+                    if let Some(slot) = level.buf.pop_ref() {
+                        return Some((level, slot));
+                    }
+                }
+            }
+        }
+
+        for (i, level) in self.levels.iter().enumerate() {
+            if let Some(slot) = level.buf.pop_ref() {
+                level.passed.store(0, Relaxed);
+                if aging > 0 {
+                    for lower in &self.levels[i + 1..] {
+                        if !lower.buf.is_empty() {
+                            lower.passed.fetch_add(1, Relaxed);
+                        }
+                    }
+                }
+                return Some((level, slot));
+            }
+        }
+
+        None
+    }
+}
+
+impl<T, R> WorkQueue<T, R> {
+    /// Sets the aging threshold: the number of items a non-empty priority
+    /// level may be passed over for before its next item is promoted ahead
+    /// of higher-priority items.
+    ///
+    /// `None` disables aging, so that items are always returned strictly by
+    /// priority. Aging is enabled with a threshold of [`DEFAULT_AGING`] by
+    /// default.
+    ///
+    /// # Panics
+    ///
+    /// If the threshold is `Some(0)`.
+    #[inline]
+    pub fn set_aging(&self, threshold: Option<usize>) {
+        let threshold = match threshold {
+            Some(threshold) => {
+                assert!(threshold > 0, "aging threshold must be non-zero");
+                threshold
+            }
+            None => 0,
+        };
+        self.aging.store(threshold, Relaxed);
+    }
+
+    /// Returns the number of priority levels in this queue.
+    #[inline]
+    pub fn levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Returns the capacity of each priority level.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.levels[0].buf.capacity()
+    }
+
+    /// Returns the total number of items in the queue, across all priority
+    /// levels.
+    pub fn len(&self) -> usize {
+        self.levels.iter().map(|level| level.buf.len()).sum()
+    }
+
+    /// Returns the number of items at the given `priority` level.
+    ///
+    /// # Panics
+    ///
+    /// If `priority` is not less than the number of [levels].
+    ///
+    /// [levels]: Self::levels
+    pub fn len_at(&self, priority: usize) -> usize {
+        self.level(priority).buf.len()
+    }
+
+    /// Returns `true` if every priority level is empty.
+    pub fn is_empty(&self) -> bool {
+        self.levels.iter().all(|level| level.buf.is_empty())
+    }
+
+    fn level(&self, priority: usize) -> &Level<T, R> {
+        assert!(
+            priority < self.levels.len(),
+            "priority {} out of range for a work queue with {} levels",
+            priority,
+            self.levels.len()
+        );
+        &self.levels[priority]
+    }
+}
+
+impl<T, R: fmt::Debug> fmt::Debug for WorkQueue<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkQueue")
+            .field("levels", &self.levels.len())
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .field("aging", &self.aging.load(Relaxed))
+            .finish()
+    }
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "ffi")]
+use std::ptr;
+use thingbuf::ffi::*;
+
+#[test]
+fn round_trip() {
+    let mut tx = ptr::null_mut();
+    let mut rx = ptr::null_mut();
+    unsafe {
+        assert_eq!(
+            thingbuf_channel_new(2, &mut tx, &mut rx),
+            ThingbufStatus::Ok
+        );
+
+        let tx2 = thingbuf_sender_clone(tx);
+        assert_eq!(
+            thingbuf_try_send(tx, b"hello".as_ptr(), 5),
+            ThingbufStatus::Ok
+        );
+        assert_eq!(
+            thingbuf_try_send(tx2, b"world!".as_ptr(), 6),
+            ThingbufStatus::Ok
+        );
+        assert_eq!(
+            thingbuf_try_send(tx, b"nope".as_ptr(), 4),
+            ThingbufStatus::Full
+        );
+
+        let mut buf = [0u8; 5];
+        let mut len = 0;
+        assert_eq!(
+            thingbuf_try_recv(rx, buf.as_mut_ptr(), buf.len(), &mut len),
+            ThingbufStatus::Ok
+        );
+        assert_eq!(&buf[..len], b"hello");
+
+        // the second frame doesn't fit, so it's kept until a larger buffer is
+        // provided.
+        assert_eq!(
+            thingbuf_try_recv(rx, buf.as_mut_ptr(), buf.len(), &mut len),
+            ThingbufStatus::BufferTooSmall
+        );
+        assert_eq!(len, 6);
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            thingbuf_recv(rx, buf.as_mut_ptr(), buf.len(), &mut len),
+            ThingbufStatus::Ok
+        );
+        assert_eq!(&buf[..len], b"world!");
+
+        assert_eq!(
+            thingbuf_try_recv(rx, buf.as_mut_ptr(), buf.len(), &mut len),
+            ThingbufStatus::Empty
+        );
+
+        thingbuf_sender_free(tx);
+        thingbuf_sender_free(tx2);
+        assert_eq!(
+            thingbuf_recv(rx, buf.as_mut_ptr(), buf.len(), &mut len),
+            ThingbufStatus::Closed
+        );
+        thingbuf_receiver_free(rx);
+    }
+}
+
+#[test]
+fn invalid_args() {
+    let mut tx = ptr::null_mut();
+    let mut rx = ptr::null_mut();
+    unsafe {
+        assert_eq!(
+            thingbuf_channel_new(0, &mut tx, &mut rx),
+            ThingbufStatus::InvalidCapacity
+        );
+        assert_eq!(
+            thingbuf_channel_new(1, ptr::null_mut(), &mut rx),
+            ThingbufStatus::NullPointer
+        );
+        assert_eq!(
+            thingbuf_send(ptr::null(), ptr::null(), 0),
+            ThingbufStatus::NullPointer
+        );
+        assert!(thingbuf_sender_clone(ptr::null()).is_null());
+    }
+}
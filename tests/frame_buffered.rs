@@ -0,0 +1,75 @@
+use std::{sync::Arc, thread};
+use thingbuf::FrameBuffered;
+
+#[test]
+fn frames_do_not_overlap() {
+    const PRODUCERS: usize = 4;
+    const COMMANDS: usize = 1000;
+
+    let commands = Arc::new(FrameBuffered::<usize>::new(COMMANDS * PRODUCERS));
+    let producers = (0..PRODUCERS)
+        .map(|p| {
+            let commands = commands.clone();
+            thread::spawn(move || {
+                for i in 0..COMMANDS {
+                    commands.push(p * COMMANDS + i).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut received = Vec::new();
+    let mut frames = 0;
+    while received.len() < PRODUCERS * COMMANDS {
+        let frame = commands.swap();
+        assert_eq!(frame.number(), frames);
+        frames += 1;
+        let len = frame.len();
+        let applied = frame.collect::<Vec<_>>();
+        // Nothing is pushed into a frame once it has been swapped out.
+        assert_eq!(applied.len(), len);
+        received.extend(applied);
+        thread::yield_now();
+    }
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    assert_eq!(commands.frames(), frames);
+
+    // Each producer's commands arrive in order.
+    for p in 0..PRODUCERS {
+        let mine = received
+            .iter()
+            .copied()
+            .filter(|&c| c / COMMANDS == p)
+            .collect::<Vec<_>>();
+        assert_eq!(mine, (p * COMMANDS..(p + 1) * COMMANDS).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn dropped_frame_discards_its_commands() {
+    let commands = FrameBuffered::<Vec<u8>>::new(2);
+    commands.push(vec![1]).unwrap();
+    commands.push(vec![2]).unwrap();
+    assert!(commands.push(vec![3]).is_err());
+
+    let mut frame = commands.swap();
+    assert_eq!(frame.pop_ref().as_deref(), Some(&vec![1]));
+    drop(frame);
+
+    // The next frame fills the other buffer, and the one after reuses the
+    // drained buffer.
+    commands.push(vec![4]).unwrap();
+    assert_eq!(commands.swap().collect::<Vec<_>>(), vec![vec![4]]);
+    assert!(commands.swap().next().is_none());
+    assert_eq!(commands.frames(), 3);
+}
+
+#[test]
+#[should_panic]
+fn swap_while_reading_panics() {
+    let commands = FrameBuffered::<usize>::new(2);
+    let _frame = commands.swap();
+    let _next = commands.swap();
+}
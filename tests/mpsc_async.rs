@@ -43,3 +43,902 @@ async fn basically_works() {
         }
     }
 }
+
+#[cfg(feature = "futures-core")]
+#[tokio::test]
+async fn send_all_stream_returns_rest_on_close() {
+    use futures_util::stream::{self, StreamExt};
+
+    let (tx, rx) = mpsc::channel(2);
+    let consumer = tokio::spawn(async move {
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(rx.recv().await, Some(3));
+    });
+
+    let err = tx.send_all_stream(stream::iter(1..=10)).await.unwrap_err();
+    consumer.await.unwrap();
+
+    // the consumer only received 3 items, but up to 2 more may have been
+    // buffered before it was dropped.
+    let sent = err.sent();
+    assert!((3..=5).contains(&sent));
+    let (item, rest) = err.into_inner();
+    assert_eq!(item, sent + 1);
+    assert_eq!(rest.count().await, 10 - item);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn peek_waits_without_consuming() {
+    const N_SENDS: usize = 1000;
+
+    let (tx, mut rx) = mpsc::channel(2);
+    let producer = tokio::spawn(async move {
+        for i in 0..N_SENDS {
+            tx.send(i).await.unwrap();
+        }
+    });
+
+    for i in 0..N_SENDS {
+        assert_eq!(rx.peek().await, Some(&i));
+        assert_eq!(rx.peek().await, Some(&i));
+        assert_eq!(rx.recv().await, Some(i));
+    }
+
+    producer.await.unwrap();
+    assert_eq!(rx.peek().await, None);
+}
+
+#[tokio::test]
+async fn recv_exact_is_cancel_safe() {
+    use std::time::Duration;
+
+    let (tx, rx) = mpsc::channel(4);
+    tx.send(1).await.unwrap();
+
+    // Only one of the two messages arrives before the timeout, so the call is
+    // cancelled after receiving it.
+    let mut batch = Vec::new();
+    let res = tokio::time::timeout(Duration::from_millis(10), rx.recv_exact(&mut batch, 2)).await;
+    assert!(res.is_err());
+    assert_eq!(batch, vec![1]);
+
+    tx.send(2).await.unwrap();
+    assert_eq!(rx.recv_exact(&mut batch, 2).await, 1);
+    assert_eq!(batch, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn poll_recv_if_leaves_rejected_messages() {
+    use futures_util::future::poll_fn;
+    use std::task::Poll;
+
+    let (tx, mut rx) = mpsc::channel(4);
+
+    // No message has been sent yet, so the task is registered for a wakeup.
+    poll_fn(|cx| {
+        assert_eq!(rx.poll_recv_if(cx, |_| true), Poll::Pending);
+        Poll::Ready(())
+    })
+    .await;
+
+    tx.send(1).await.unwrap();
+    tx.send(2).await.unwrap();
+
+    // The first message is rejected, and stays at the front of the channel.
+    let msg = poll_fn(|cx| rx.poll_recv_if(cx, |&msg| msg == 2)).await;
+    assert_eq!(msg, Ok(None));
+    assert_eq!(rx.recv().await, Some(1));
+
+    let msg = poll_fn(|cx| rx.poll_recv_if(cx, |&msg| msg == 2)).await;
+    assert_eq!(msg, Ok(Some(2)));
+
+    drop(tx);
+    assert!(poll_fn(|cx| rx.poll_recv_if(cx, |_| true)).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recv_if_waits_for_a_message() {
+    let (tx, mut rx) = mpsc::channel(4);
+    let producer = tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        tx.send(1).await.unwrap();
+        tx.send(2).await.unwrap();
+    });
+
+    assert_eq!(rx.recv_if(|&msg| msg == 2).await, Ok(None));
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(rx.recv_if(|&msg| msg == 2).await, Ok(Some(2)));
+
+    producer.await.unwrap();
+    assert!(rx.recv_if(|_| true).await.is_err());
+}
+
+#[cfg(feature = "futures-sink")]
+#[tokio::test(flavor = "multi_thread")]
+async fn sink_with_fills_slots_in_place() {
+    use futures_util::SinkExt;
+
+    const N_SENDS: usize = 32;
+
+    // a small capacity, so that the sink has to wait for the receiver.
+    let (tx, rx) = mpsc::channel::<Vec<usize>>(2);
+    let producer = tokio::spawn(async move {
+        let mut sink = Box::pin(tx.into_sink_with());
+        for i in 0..N_SENDS {
+            sink.send(move |slot: &mut Vec<usize>| {
+                slot.clear();
+                slot.push(i);
+            })
+            .await
+            .unwrap();
+        }
+    });
+
+    for i in 0..N_SENDS {
+        let msg = rx.recv().await.unwrap();
+        assert_eq!(msg, vec![i]);
+    }
+    producer.await.unwrap();
+    assert_eq!(rx.recv().await, None);
+
+    let (tx, rx) = mpsc::channel::<usize>(2);
+    let mut sink = Box::pin(tx.into_sink_with());
+    drop(rx);
+    sink.feed(|slot: &mut usize| *slot = 1).await.unwrap();
+    assert!(sink.flush().await.is_err());
+}
+
+#[cfg(all(feature = "futures-sink", feature = "tokio"))]
+#[tokio::test(start_paused = true)]
+async fn batch_sink_publishes_batches() {
+    use futures_util::SinkExt;
+    use std::time::Duration;
+    use thingbuf::mpsc::errors::TryRecvError;
+
+    let (tx, rx) = mpsc::channel::<usize>(8);
+    let mut sink = Box::pin(tx.batch_sink(3, Duration::from_secs(1)));
+
+    // a full batch is published as soon as its last message is written.
+    for i in 0..3 {
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        sink.feed(i).await.unwrap();
+    }
+    for i in 0..3 {
+        assert_eq!(rx.try_recv(), Ok(i));
+    }
+
+    // a partial batch is published once the delay elapses.
+    sink.feed(3).await.unwrap();
+    assert_eq!(sink.pending(), 1);
+    let start = tokio::time::Instant::now();
+    sink.flush().await.unwrap();
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(rx.try_recv(), Ok(3));
+
+    // when the channel fills up, pending messages are published rather than
+    // deadlocking with the receiver.
+    let consumer = tokio::spawn(async move {
+        let mut received = Vec::new();
+        while let Some(msg) = rx.recv().await {
+            received.push(msg);
+        }
+        received
+    });
+    for i in 4..100 {
+        sink.feed(i).await.unwrap();
+    }
+    sink.close().await.unwrap();
+    drop(sink);
+    drop(tx);
+    assert_eq!(consumer.await.unwrap(), (4..100).collect::<Vec<_>>());
+}
+
+#[tokio::test(start_paused = true)]
+async fn send_from_keeps_message_when_cancelled() {
+    use std::time::Duration;
+
+    let (tx, rx) = mpsc::channel::<String>(1);
+    tx.send("first".to_string()).await.unwrap();
+
+    // The channel is full, so every attempt is cancelled by the timeout and
+    // the message is handed back.
+    let mut msg = Some("second".to_string());
+    for _ in 0..3 {
+        tokio::select! {
+            res = tx.send_from(&mut msg) => panic!("send completed: {:?}", res),
+            _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+        }
+        assert_eq!(msg.as_deref(), Some("second"));
+    }
+
+    assert_eq!(rx.recv().await.as_deref(), Some("first"));
+    tx.send_from(&mut msg).await.unwrap();
+    assert_eq!(msg, None);
+    assert_eq!(rx.recv().await.as_deref(), Some("second"));
+
+    // If the channel closes, the message is left in place.
+    drop(rx);
+    let mut msg = Some("third".to_string());
+    assert!(tx.send_from(&mut msg).await.is_err());
+    assert_eq!(msg.as_deref(), Some("third"));
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn tower_buffer_applies_backpressure() {
+    use futures_util::future::poll_fn;
+    use std::{
+        convert::Infallible,
+        future::{ready, Ready},
+        task::{Context, Poll},
+    };
+    use thingbuf::mpsc::tower::ThingbufBuffer;
+    use tower_service::Service;
+
+    struct Echo;
+
+    impl Service<String> for Echo {
+        type Response = String;
+        type Error = Infallible;
+        type Future = Ready<Result<String, Infallible>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: String) -> Self::Future {
+            ready(Ok(req))
+        }
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    let (mut svc, worker) = ThingbufBuffer::pair(Echo, 1);
+    assert_send(&svc);
+    assert_send(&worker);
+    poll_fn(|cx| svc.poll_ready(cx)).await.unwrap();
+    let first = svc.call("hello".to_string());
+    assert_send(&first);
+
+    // The only slot holds the first request, and the worker isn't running, so
+    // the buffer is not ready for another request.
+    let mut second = svc.clone();
+    assert!(poll_fn(|cx| Poll::Ready(second.poll_ready(cx)))
+        .await
+        .is_pending());
+
+    tokio::spawn(worker);
+    assert_eq!(first.await.unwrap(), "hello");
+    poll_fn(|cx| second.poll_ready(cx)).await.unwrap();
+    assert_eq!(second.call("world".to_string()).await.unwrap(), "world");
+
+    // Once every buffer is dropped, the worker shuts down.
+    drop((svc, second));
+}
+
+#[cfg(feature = "futures-timer")]
+#[tokio::test]
+async fn timeout_items_with_futures_timer() {
+    use std::time::Duration;
+    use thingbuf::time::FuturesTimer;
+
+    let (tx, rx) = mpsc::channel::<usize>(4);
+    let mut items = rx.timeout_items_with(Duration::from_millis(20), FuturesTimer);
+
+    tx.send(1).await.unwrap();
+    assert_eq!(items.next().await, Some(Ok(1)));
+    // nothing was sent, so the timer fires.
+    assert!(items.next().await.unwrap().is_err());
+
+    // the timer is reset after elapsing, and after each message.
+    tx.send(2).await.unwrap();
+    assert_eq!(items.next().await, Some(Ok(2)));
+    drop(tx);
+    assert_eq!(items.next().await, None);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn watermark_items_batches_wakeups() {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Wake, Waker},
+        time::Duration,
+    };
+
+    struct Flag(AtomicBool);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let (tx, rx) = mpsc::channel::<usize>(8);
+    let mut items = rx.watermark_items(4, Duration::from_millis(10));
+
+    let woken = Arc::new(Flag(AtomicBool::new(false)));
+    let waker = Waker::from(woken.clone());
+    let mut cx = Context::from_waker(&waker);
+    assert_eq!(items.poll_next(&mut cx), Poll::Pending);
+
+    // Below the watermark, a send does not wake the waiting task...
+    for i in 0..3 {
+        tx.send(i).await.unwrap();
+    }
+    assert!(!woken.0.load(Ordering::SeqCst));
+    // ...but reaching it does.
+    tx.send(3).await.unwrap();
+    assert!(woken.0.load(Ordering::SeqCst));
+    for i in 0..4 {
+        assert_eq!(items.next().await, Some(i));
+    }
+
+    // A partial batch is received once the maximum delay elapses.
+    tx.send(4).await.unwrap();
+    assert_eq!(items.next().await, Some(4));
+
+    // Dropping the stream restores waking the receiver on every send.
+    drop(items);
+    woken.0.store(false, Ordering::SeqCst);
+    assert_eq!(rx.poll_recv(&mut cx), Poll::Pending);
+    tx.send(5).await.unwrap();
+    assert!(woken.0.load(Ordering::SeqCst));
+    assert_eq!(rx.recv().await, Some(5));
+}
+
+#[tokio::test]
+async fn recv_chunk_takes_ready_messages() {
+    use futures_util::future::poll_fn;
+
+    let (tx, rx) = mpsc::channel::<usize>(8);
+    for i in 0..6 {
+        tx.send(i).await.unwrap();
+    }
+    assert_eq!(rx.recv_chunk(4).await, Some(vec![0, 1, 2, 3]));
+    assert_eq!(
+        poll_fn(|cx| rx.poll_recv_chunk(cx, 4)).await,
+        Some(vec![4, 5])
+    );
+
+    // with nothing ready, the chunk waits for the next message.
+    let producer = tokio::spawn(async move {
+        tokio::task::yield_now().await;
+        tx.send(6).await.unwrap();
+    });
+    assert_eq!(rx.recv_chunk(4).await, Some(vec![6]));
+    producer.await.unwrap();
+    assert_eq!(rx.recv_chunk(4).await, None);
+}
+
+#[tokio::test]
+async fn send_batch_returns_unsent_messages() {
+    let (tx, rx) = mpsc::channel::<usize>(2);
+    let consumer = tokio::spawn(async move {
+        assert_eq!(rx.recv().await, Some(0));
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, Some(2));
+        // dropping the receiver closes the channel while the batch is
+        // waiting for capacity.
+    });
+
+    let err = tx.send_batch((0..8).collect()).await.unwrap_err();
+    consumer.await.unwrap();
+    let unsent = err.into_inner();
+    // the messages sent after the receiver's last `recv` are lost with the
+    // receiver, and the rest are returned in order.
+    assert!(!unsent.is_empty());
+    assert_eq!(*unsent.last().unwrap(), 7);
+    assert!(unsent.windows(2).all(|w| w[0] + 1 == w[1]));
+}
+
+#[tokio::test]
+#[cfg(feature = "tokio-io")]
+async fn channel_io_round_trip() {
+    use mpsc::io::{ChannelReader, ChannelWriter};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (tx, rx) = mpsc::channel::<Vec<u8>>(2);
+    let mut writer = ChannelWriter::new(tx);
+    let mut reader = ChannelReader::new(rx);
+
+    let data = (0..=255u8).cycle().take(4096).collect::<Vec<_>>();
+    let expected = data.clone();
+    let write = tokio::spawn(async move {
+        // more writes than the channel can hold, so the writer must wait.
+        for chunk in data.chunks(100) {
+            writer.write_all(chunk).await.unwrap();
+        }
+        writer.shutdown().await.unwrap();
+        assert!(writer.write(b"late").await.is_err());
+    });
+
+    // reads smaller than the writes split each message across reads.
+    let mut received = Vec::new();
+    let mut buf = [0; 64];
+    loop {
+        let n = reader.read(&mut buf).await.unwrap();
+        if n == 0 {
+            break;
+        }
+        received.extend_from_slice(&buf[..n]);
+    }
+    write.await.unwrap();
+    assert_eq!(received, expected);
+}
+
+#[tokio::test]
+#[cfg(feature = "tokio-io")]
+async fn channel_writer_broken_pipe() {
+    use tokio::io::AsyncWriteExt;
+
+    let (tx, rx) = mpsc::channel::<Vec<u8>>(2);
+    let mut writer = mpsc::io::ChannelWriter::new(tx);
+    drop(rx);
+    let err = writer.write_all(b"hello").await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+}
+
+#[tokio::test]
+async fn channel_group_wakes_receivers() {
+    use mpsc::group::{ChannelGroup, ShutdownReason};
+
+    let (tx, rx) = mpsc::channel::<usize>(2);
+    let (other_tx, other_rx) = mpsc::channel::<usize>(2);
+    let group = ChannelGroup::<&str>::new();
+    group.add(&rx);
+    group.add(&other_tx);
+
+    let recv = tokio::spawn(async move { other_rx.recv().await });
+    tokio::task::yield_now().await;
+    drop(rx);
+
+    assert_eq!(recv.await.unwrap(), None);
+    assert_eq!(group.reason(), Some(ShutdownReason::Closed(0)));
+    assert!(tx.send(1).await.is_err());
+    assert!(other_tx.send(1).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_delivers_every_message() {
+    const N_SENDS: usize = 100;
+    const N_PRODUCERS: usize = 4;
+
+    let (tx, rx) = mpsc::mailbox::<usize>();
+    for n in 0..N_PRODUCERS {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            for i in 0..N_SENDS {
+                tx.send(n * N_SENDS + i).await.unwrap();
+            }
+        });
+    }
+    drop(tx);
+
+    let mut results = Vec::new();
+    while let Some(val) = rx.recv().await {
+        results.push(val);
+    }
+    results.sort_unstable();
+    assert_eq!(results, (0..N_SENDS * N_PRODUCERS).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn mailbox_closes() {
+    use thingbuf::mpsc::errors::{TryRecvError, TrySendError};
+
+    let (tx, rx) = mpsc::mailbox::<String>();
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    tx.send_with(|msg| msg.push_str("hello")).await.unwrap();
+    assert!(rx.is_full());
+    assert_eq!(rx.try_recv_with(|msg| msg.len()), Ok(5));
+
+    // The last message is still received after the sender is dropped.
+    tx.try_send("world".to_string()).unwrap();
+    drop(tx);
+    assert!(rx.is_closed());
+    assert_eq!(rx.recv().await.as_deref(), Some("world"));
+    assert_eq!(rx.recv().await, None);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+
+    let (tx, rx) = mpsc::mailbox::<String>();
+    drop(rx);
+    assert!(tx.is_closed());
+    assert!(matches!(
+        tx.try_send("hello".to_string()),
+        Err(TrySendError::Closed(_))
+    ));
+    assert!(tx.send("hello".to_string()).await.is_err());
+}
+
+#[tokio::test]
+async fn mailbox_wakes_waiting_sender() {
+    let (tx, rx) = mpsc::mailbox::<u32>();
+    tx.send(1).await.unwrap();
+    let sender = tokio::spawn(async move {
+        tx.send(2).await.unwrap();
+    });
+    tokio::task::yield_now().await;
+    assert_eq!(rx.recv().await, Some(1));
+    sender.await.unwrap();
+    assert_eq!(rx.recv().await, Some(2));
+    assert_eq!(rx.recv().await, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inline_channel_delivers_every_message() {
+    const N_SENDS: usize = 100;
+    const N_PRODUCERS: usize = 4;
+
+    let (tx, rx) = mpsc::Channel::<usize, 8>::new().split();
+    assert_eq!(tx.capacity(), 8);
+    for n in 0..N_PRODUCERS {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            for i in 0..N_SENDS {
+                tx.send(n * N_SENDS + i).await.unwrap();
+            }
+        });
+    }
+    drop(tx);
+
+    let mut results = Vec::new();
+    while let Some(val) = rx.recv().await {
+        results.push(val);
+    }
+    results.sort_unstable();
+    assert_eq!(results, (0..N_SENDS * N_PRODUCERS).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn inline_channel_reuses_slots() {
+    use std::fmt::Write;
+    use thingbuf::mpsc::errors::TrySendError;
+
+    let (tx, rx) = mpsc::Channel::<String, 2>::new().split();
+    write!(tx.send_ref().await.unwrap(), "hello").unwrap();
+    write!(tx.try_send_ref().unwrap(), "world").unwrap();
+    assert!(matches!(tx.try_send_ref(), Err(TrySendError::Full(()))));
+    assert_eq!(rx.len(), 2);
+
+    assert_eq!(rx.recv_ref().await.unwrap().as_str(), "hello");
+    assert_eq!(rx.try_recv_ref().unwrap().as_str(), "world");
+    assert!(rx.is_empty());
+
+    drop(rx);
+    assert!(!tx.receiver_alive());
+    assert!(tx.send(String::from("closed")).await.is_err());
+}
+
+#[test]
+fn inline_channel_with_large_capacity_is_built_on_the_heap() {
+    const CAPACITY: usize = 262_144;
+
+    // The slots alone are several megabytes, far more than this thread's
+    // stack, so this only works if the channel is never stored on it.
+    let handle = std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(|| {
+            let (tx, rx) = mpsc::Channel::<u64, CAPACITY>::new_split();
+            assert_eq!(tx.capacity(), CAPACITY);
+            for i in 0..CAPACITY as u64 {
+                tx.try_send(i).unwrap();
+            }
+            assert!(tx.try_send(0).is_err());
+            for i in 0..CAPACITY as u64 {
+                assert_eq!(rx.try_recv(), Ok(i));
+            }
+        })
+        .unwrap();
+    handle.join().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn recv_for_each_runs_until_closed() {
+    use std::fmt::Write;
+
+    const N_SENDS: usize = 20;
+    const N_PRODUCERS: usize = 4;
+
+    let (tx, rx) = mpsc::channel::<String>(2);
+    for n in 0..N_PRODUCERS {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            for i in 0..N_SENDS {
+                write!(tx.send_ref().await.unwrap(), "{}-{}", n, i).unwrap();
+            }
+        });
+    }
+    drop(tx);
+
+    let mut next = vec![0; N_PRODUCERS];
+    rx.recv_for_each(|msg| {
+        let (n, i) = msg.split_once('-').unwrap();
+        let (n, i): (usize, usize) = (n.parse().unwrap(), i.parse().unwrap());
+        // Messages from each producer arrive in order.
+        assert_eq!(i, next[n]);
+        next[n] += 1;
+    })
+    .await;
+    assert_eq!(next, vec![N_SENDS; N_PRODUCERS]);
+}
+
+#[tokio::test]
+#[cfg(feature = "audit-refs")]
+#[should_panic(expected = "1 `SendRef`s and 0 `RecvRef`s were still outstanding")]
+async fn audit_refs_leaked_send_ref() {
+    let (tx, rx) = mpsc::channel::<usize>(4);
+    std::mem::forget(tx.send_ref().await.unwrap());
+    drop(tx);
+    drop(rx);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn lines_reassembles_split_lines() {
+    use std::fmt::Write;
+
+    const N_LINES: usize = 100;
+
+    // The channel is smaller than the number of chunks each line is split
+    // into, so slots must be released as lines are returned.
+    let (tx, rx) = mpsc::channel::<String>(2);
+    let producer = tokio::spawn(async move {
+        let mut text = String::new();
+        for i in 0..N_LINES {
+            writeln!(text, "line {} {}", i, "x".repeat(i % 7)).unwrap();
+        }
+        // Send the text in chunks of varying sizes, which split lines (and
+        // line endings) at arbitrary points.
+        let mut rest = &text[..];
+        let mut size = 1;
+        while !rest.is_empty() {
+            let (chunk, next) = rest.split_at(size.min(rest.len()));
+            tx.send_ref().await.unwrap().push_str(chunk);
+            rest = next;
+            size = size % 13 + 1;
+        }
+        tx.send("no newline".to_string()).await.unwrap();
+    });
+
+    let mut lines = rx.lines();
+    for i in 0..N_LINES {
+        let expected = format!("line {} {}", i, "x".repeat(i % 7));
+        assert_eq!(lines.next_line().await, Some(&expected[..]));
+    }
+    assert_eq!(lines.next_line().await, Some("no newline"));
+    assert_eq!(lines.next_line().await, None);
+    assert_eq!(lines.next_line().await, None);
+    producer.await.unwrap();
+}
+
+#[tokio::test]
+async fn lines_strips_crlf_across_messages() {
+    let (tx, rx) = mpsc::channel::<String>(8);
+    for chunk in ["a\r", "\nb\r\n", "\n", "\r\nc"] {
+        tx.send(chunk.to_string()).await.unwrap();
+    }
+    drop(tx);
+
+    let mut lines = rx.lines();
+    let mut all = Vec::new();
+    while let Some(line) = lines.next_line().await {
+        all.push(line.to_string());
+    }
+    assert_eq!(all, ["a", "b", "", "", "c"]);
+}
+
+#[cfg(feature = "futures-core")]
+#[tokio::test]
+async fn lines_stream() {
+    use futures_util::stream::StreamExt;
+
+    let (tx, rx) = mpsc::channel::<String>(4);
+    tokio::spawn(async move {
+        for chunk in ["hello\nwor", "ld\n", "!"] {
+            tx.send(chunk.to_string()).await.unwrap();
+        }
+    });
+
+    let lines = rx.lines().collect::<Vec<_>>().await;
+    assert_eq!(lines, ["hello", "world", "!"]);
+}
+
+#[cfg(feature = "seq")]
+#[tokio::test]
+async fn flush_waits_for_every_earlier_message() {
+    const N: usize = 100;
+    let (tx, rx) = mpsc::channel::<usize>(4);
+
+    let consumer = tokio::spawn(async move {
+        let mut received = 0;
+        while rx.recv().await.is_some() {
+            received += 1;
+            // Give the flushing task a chance to return too early.
+            tokio::task::yield_now().await;
+        }
+        received
+    });
+
+    for i in 0..N {
+        tx.send(i).await.unwrap();
+    }
+    tx.flush().await.unwrap();
+    assert_eq!(tx.published_seq(), N);
+    drop(tx);
+    assert_eq!(consumer.await.unwrap(), N);
+}
+
+#[cfg(feature = "seq")]
+#[tokio::test]
+async fn flush_fails_if_receiver_dropped_first() {
+    let (tx, rx) = mpsc::channel::<usize>(4);
+    tx.send(1).await.unwrap();
+    tx.send(2).await.unwrap();
+
+    let consumer = tokio::spawn(async move {
+        assert_eq!(rx.recv().await, Some(1));
+        tokio::task::yield_now().await;
+        drop(rx);
+    });
+
+    assert!(tx.flush().await.is_err());
+    consumer.await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn send_or_modify_wakes_parked_receiver() {
+    use std::time::Duration;
+
+    const N_SENDS: u64 = 100_000;
+    let (tx, rx) = mpsc::channel::<u64>(1);
+    let (done_tx, done_rx) = tokio::sync::oneshot::channel::<()>();
+
+    // The sender stays alive until everything has been received, so only a
+    // notification from `send_or_modify` can wake the receiver.
+    let sender = tokio::spawn(async move {
+        for _ in 0..N_SENDS {
+            // With a single slot, there is nothing to merge into while the
+            // receiver is reading the queued message.
+            while let Err(mpsc::errors::TrySendError::Full(_)) =
+                tx.send_or_modify(1, |queued, delta| *queued += delta)
+            {
+                tokio::task::yield_now().await;
+            }
+        }
+        done_rx.await.unwrap();
+    });
+
+    let mut total = 0;
+    while total < N_SENDS {
+        // Check the timer first, so that a receiver which is only woken by
+        // the timer is caught even though a message is ready by then.
+        tokio::select! {
+            biased;
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {
+                panic!("receiver was not woken after {} of {}", total, N_SENDS)
+            }
+            msg = rx.recv() => total += msg.unwrap(),
+        }
+    }
+    assert_eq!(total, N_SENDS);
+    done_tx.send(()).unwrap();
+    sender.await.unwrap();
+}
+
+#[tokio::test]
+async fn budget_sender_waits_for_bytes_to_be_received() {
+    use mpsc::budget;
+    use std::time::Duration;
+
+    let (tx, rx) = budget::channel::<Vec<u8>>(16, 100);
+    tx.send(vec![0; 60]).await.unwrap();
+
+    let sender = {
+        let tx = tx.clone();
+        tokio::spawn(async move { tx.send(vec![1; 60]).await.unwrap() })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    // The second message does not fit until the first is received.
+    assert_eq!(rx.len(), 1);
+    assert_eq!(tx.used_bytes(), 60);
+
+    assert_eq!(rx.recv().await, Some(vec![0; 60]));
+    sender.await.unwrap();
+    assert_eq!(rx.recv().await, Some(vec![1; 60]));
+    assert_eq!(tx.used_bytes(), 0);
+}
+
+#[tokio::test]
+async fn budget_sends_oversized_message_alone() {
+    use mpsc::{budget, errors::TrySendError};
+
+    let (tx, rx) = budget::channel::<String>(16, 10);
+    tx.send("hello".to_string()).await.unwrap();
+    let big = "a".repeat(50);
+    assert_eq!(
+        tx.try_send(big.clone()),
+        Err(TrySendError::Full(big.clone()))
+    );
+
+    assert_eq!(rx.try_recv().as_deref(), Ok("hello"));
+    tx.try_send(big.clone()).unwrap();
+    assert_eq!(rx.used_bytes(), 50);
+    assert_eq!(rx.recv().await, Some(big));
+}
+
+#[tokio::test]
+async fn budget_sender_wakes_when_receiver_dropped() {
+    use mpsc::budget;
+    use std::time::Duration;
+
+    let (tx, rx) = budget::channel::<Vec<u8>>(16, 10);
+    tx.send(vec![0; 10]).await.unwrap();
+
+    let sender = tokio::spawn(async move { tx.send(vec![1; 10]).await });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    drop(rx);
+    assert!(sender.await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn budget_send_ref_charges_written_size() {
+    use mpsc::{budget, errors::TrySendError};
+
+    let (tx, rx) = budget::channel::<Vec<u8>>(16, 10);
+
+    // The reservation is charged until the message is sent...
+    let mut msg = tx.send_ref(8).await.unwrap();
+    assert_eq!(tx.used_bytes(), 8);
+    assert!(matches!(tx.try_send_ref(4), Err(TrySendError::Full(()))));
+    msg.extend_from_slice(b"abc");
+    // ...and then corrected to the size that was actually written.
+    drop(msg);
+    assert_eq!(tx.used_bytes(), 3);
+
+    let mut msg = tx.try_send_ref(4).unwrap();
+    msg.extend_from_slice(b"defghij");
+    drop(msg);
+    assert_eq!(tx.used_bytes(), 10);
+
+    assert_eq!(rx.recv().await.as_deref(), Some(&b"abc"[..]));
+    assert_eq!(rx.recv().await.as_deref(), Some(&b"defghij"[..]));
+    assert_eq!(tx.used_bytes(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn budget_many_senders() {
+    use mpsc::budget;
+
+    const SENDERS: usize = 4;
+    const N: usize = 500;
+
+    let (tx, rx) = budget::channel::<Vec<u8>>(64, 64);
+    let senders = (0..SENDERS)
+        .map(|s| {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                for i in 0..N {
+                    let mut msg = tx.send_ref((i % 17) + 1).await.unwrap();
+                    msg.resize((i % 17) + 1, s as u8);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    let mut received = 0;
+    while let Some(msg) = rx.recv().await {
+        assert!(rx.used_bytes() <= 64);
+        assert!(!msg.is_empty());
+        received += 1;
+    }
+    for sender in senders {
+        sender.await.unwrap();
+    }
+    assert_eq!(received, SENDERS * N);
+    assert_eq!(rx.used_bytes(), 0);
+}
@@ -1,6 +1,6 @@
-use std::thread;
+use std::{thread, time::Duration};
 use thingbuf::mpsc::blocking;
-use thingbuf::mpsc::errors::{TryRecvError, TrySendError};
+use thingbuf::mpsc::errors::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
 
 #[test]
 fn basically_works() {
@@ -122,3 +122,1693 @@ fn spsc_empty_after_skipped() {
     assert_eq!(rx.recv(), Some(1));
     assert!(matches!(rx.try_recv_ref(), Err(TryRecvError::Empty)));
 }
+
+#[test]
+fn is_closed_with_multiple_senders() {
+    let (tx, rx) = blocking::channel::<usize>(2);
+    let tx2 = tx.clone();
+    assert_eq!(tx.sender_count(), 2);
+
+    drop(tx);
+    assert!(!rx.is_closed());
+    assert_eq!(tx2.sender_count(), 1);
+    assert!(tx2.receiver_alive());
+
+    drop(tx2);
+    assert!(rx.is_closed());
+}
+
+#[test]
+fn spin_budget() {
+    const N: usize = 100;
+    let (tx, rx) = blocking::channel::<usize>(2);
+    rx.set_spin_budget(1024);
+
+    let producer = thread::spawn(move || {
+        for i in 0..N {
+            tx.send(i).unwrap();
+        }
+    });
+
+    for i in 0..N {
+        assert_eq!(rx.recv(), Some(i));
+    }
+    assert_eq!(rx.recv(), None);
+    producer.join().unwrap();
+}
+
+#[test]
+fn adaptive_spinning() {
+    let (tx, rx) = blocking::channel::<usize>(2);
+    rx.set_spin_budget(8);
+    rx.set_adaptive_spinning(true);
+
+    // spinning on an empty channel fails, so the budget shrinks...
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(1)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    let budget = rx.spin_budget();
+    assert!((1..8).contains(&budget), "budget: {}", budget);
+
+    // ...but never below 1, so that the channel can observe spinning succeed.
+    rx.set_spin_budget(0);
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(1)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert_eq!(rx.spin_budget(), 1);
+
+    // disabling adaptive spinning stops the budget from changing.
+    rx.set_adaptive_spinning(false);
+    rx.set_spin_budget(8);
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(1)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    assert_eq!(rx.spin_budget(), 8);
+
+    rx.set_adaptive_spinning(true);
+    let producer = thread::spawn(move || {
+        for i in 0..100 {
+            tx.send(i).unwrap();
+        }
+    });
+    for i in 0..100 {
+        assert_eq!(rx.recv(), Some(i));
+    }
+    assert_eq!(rx.recv(), None);
+    producer.join().unwrap();
+    assert!(rx.spin_budget() >= 1);
+}
+
+#[test]
+fn send_timeout_unlinks_timed_out_waiter() {
+    use std::sync::mpsc;
+    use thingbuf::mpsc::errors::SendTimeoutError;
+
+    let (tx, rx) = blocking::channel::<usize>(1);
+    tx.send(1).unwrap();
+    assert_eq!(
+        tx.send_timeout(2, Duration::from_millis(10)),
+        Err(SendTimeoutError::Timeout(2))
+    );
+
+    // a sender that starts waiting after the timed out one must still be
+    // woken when capacity is released.
+    let (done_tx, done_rx) = mpsc::channel();
+    let tx2 = tx.clone();
+    thread::spawn(move || {
+        tx2.send(3).unwrap();
+        done_tx.send(()).unwrap();
+    });
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(rx.recv(), Some(1));
+    done_rx
+        .recv_timeout(Duration::from_secs(5))
+        .expect("sender was not woken");
+    assert_eq!(rx.recv(), Some(3));
+}
+
+#[test]
+fn recv_if_waits_for_a_message() {
+    let (tx, mut rx) = blocking::channel(4);
+    let producer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+    });
+
+    // Blocks until the first message is sent, and leaves it in the channel.
+    assert_eq!(rx.recv_if(|&msg| msg == 2), Ok(None));
+    assert_eq!(rx.recv(), Some(1));
+    assert_eq!(rx.recv_if(|&msg| msg == 2), Ok(Some(2)));
+
+    producer.join().unwrap();
+    assert!(rx.recv_if(|_| true).is_err());
+}
+
+#[test]
+fn elastic_grows_and_shrinks() {
+    use blocking::elastic::{self, Config};
+
+    let config = Config::new(4)
+        .with_max_capacity(16)
+        .with_grow_after(Duration::from_millis(1));
+    let (tx, rx) = elastic::channel::<usize>(config);
+
+    // without a receiver, the producer can only make progress by growing.
+    let producer = thread::spawn(move || {
+        for i in 0..16 {
+            tx.send(i).unwrap();
+        }
+        tx
+    });
+    let tx = producer.join().unwrap();
+    assert_eq!(tx.capacity(), 16);
+
+    let consumer = thread::spawn(move || {
+        for i in 0..16 {
+            assert_eq!(rx.recv(), Some(i));
+        }
+        rx
+    });
+    let rx = consumer.join().unwrap();
+    assert_eq!(rx.capacity(), 4);
+
+    drop(tx);
+    assert_eq!(rx.recv(), None);
+}
+
+#[test]
+fn elastic_multi_producer() {
+    use blocking::elastic::{self, Config};
+    const PRODUCERS: usize = 4;
+    const N: usize = 500;
+
+    let config = Config::new(2)
+        .with_max_capacity(8)
+        .with_grow_after(Duration::from_micros(100));
+    let (tx, rx) = elastic::channel::<(usize, usize)>(config);
+    let producers = (0..PRODUCERS)
+        .map(|p| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..N {
+                    tx.send((p, i)).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    let mut next = [0; PRODUCERS];
+    for (p, i) in &rx {
+        assert_eq!(next[p], i, "messages from producer {} out of order", p);
+        next[p] += 1;
+        assert!(rx.capacity() <= 8);
+    }
+    assert_eq!(next, [N; PRODUCERS]);
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+}
+
+#[test]
+fn elastic_rx_closed() {
+    use blocking::elastic::{self, Config};
+
+    let config = Config::new(1).with_grow_after(Duration::from_millis(1));
+    let (tx, rx) = elastic::channel::<usize>(config);
+    tx.send(1).unwrap();
+    drop(rx);
+    assert_eq!(tx.send(2).unwrap_err().into_inner(), 2);
+}
+
+#[test]
+fn sharded_multi_producer() {
+    use blocking::sharded;
+    const PRODUCERS: usize = 4;
+    const N: usize = 500;
+
+    let (tx, rx) = sharded::channel::<(usize, usize)>(PRODUCERS, 4);
+    let producers = (0..PRODUCERS)
+        .map(|p| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..N {
+                    tx.send((p, i)).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    let mut next = [0; PRODUCERS];
+    for (p, i) in &rx {
+        assert_eq!(next[p], i, "messages from producer {} out of order", p);
+        next[p] += 1;
+    }
+    assert_eq!(next, [N; PRODUCERS]);
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+}
+
+#[test]
+fn sharded_round_robin() {
+    use blocking::sharded;
+
+    let (tx0, rx) = sharded::channel::<usize>(2, 4);
+    let tx1 = tx0.clone();
+    assert_eq!((tx0.shard(), tx1.shard()), (0, 1));
+    assert_eq!(rx.capacity(), 8);
+
+    // each shard is bounded separately.
+    for i in 0..4 {
+        tx0.try_send(i).unwrap();
+    }
+    assert!(matches!(tx0.try_send(4), Err(TrySendError::Full(4))));
+    tx1.try_send(10).unwrap();
+    tx1.try_send(11).unwrap();
+    assert_eq!(rx.len(), 6);
+
+    // the receiver alternates between shards with messages.
+    let received = (0..6).map(|_| rx.try_recv().unwrap()).collect::<Vec<_>>();
+    assert_eq!(received, vec![0, 10, 1, 11, 2, 3]);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+    drop((tx0, tx1));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+    assert_eq!(rx.recv(), None);
+}
+
+#[test]
+fn sharded_producer_tokens() {
+    use blocking::sharded;
+    const N: usize = 500;
+
+    let (tx, rx) = sharded::channel::<usize>(4, 4);
+    let token = tx.token();
+    let shard = tx.shard();
+    // without the token, each clone would send to a different shard.
+    let senders = (0..4)
+        .map(|_| {
+            let mut tx = tx.clone();
+            tx.attach(&token);
+            tx
+        })
+        .collect::<Vec<_>>();
+    assert!(senders.iter().all(|tx| tx.shard() == shard));
+    drop(tx);
+
+    let producer = thread::spawn(move || {
+        for i in 0..N {
+            senders[i % senders.len()].send(i).unwrap();
+        }
+    });
+
+    for i in 0..N {
+        assert_eq!(rx.recv(), Some(i));
+    }
+    assert_eq!(rx.recv(), None);
+    producer.join().unwrap();
+}
+
+#[test]
+fn transactions_are_never_interleaved() {
+    const PRODUCERS: usize = 3;
+    const N: usize = 200;
+
+    let (tx, rx) = blocking::channel::<(usize, usize)>(4);
+    let producers = (0..PRODUCERS)
+        .map(|p| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..N {
+                    let mut t = tx.transaction(3).unwrap();
+                    for part in 0..3 {
+                        t[part] = (p, i * 3 + part);
+                    }
+                    t.commit();
+                    // interleave with single messages, which may wrap the
+                    // transactions around the end of the ring.
+                    tx.send((PRODUCERS, i)).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    let mut fragments = 0;
+    while let Some((p, n)) = rx.recv() {
+        if p == PRODUCERS {
+            continue;
+        }
+        assert_eq!(
+            n % 3,
+            0,
+            "transaction from producer {} received partially",
+            p
+        );
+        for part in 1..3 {
+            assert_eq!(rx.try_recv(), Ok((p, n + part)));
+        }
+        fragments += 3;
+    }
+    assert_eq!(fragments, PRODUCERS * N * 3);
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+}
+
+#[test]
+fn try_transaction_full() {
+    let (tx, rx) = blocking::channel::<usize>(4);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(tx.try_transaction(3).unwrap_err(), TrySendError::Full(()));
+
+    assert_eq!(rx.recv(), Some(1));
+    let mut t = tx.try_transaction(3).unwrap();
+    assert_eq!(t.len(), 3);
+    t[0] = 3;
+    t[1] = 4;
+    t[2] = 5;
+    drop(t);
+
+    for i in 2..=5 {
+        assert_eq!(rx.try_recv(), Ok(i));
+    }
+}
+
+#[cfg(feature = "seq")]
+#[test]
+fn seq_does_not_count_skipped_slots() {
+    let (tx, rx) = blocking::channel::<usize>(2);
+
+    tx.send(0).unwrap();
+    // Hold on to the first message while the sender wraps around the ring,
+    // forcing it to skip that message's slot.
+    let held = rx.recv_ref().unwrap();
+    assert_eq!(held.seq(), 0);
+    tx.send(1).unwrap();
+    assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+    drop(held);
+
+    assert_eq!(rx.recv(), Some(1));
+    tx.send(2).unwrap();
+    let msg = rx.recv_ref().unwrap();
+    assert_eq!(*msg, 2);
+    assert_eq!(msg.seq(), 2);
+    drop(msg);
+
+    assert_eq!(tx.published_seq(), 3);
+    assert_eq!(rx.consumed_seq(), 3);
+}
+
+#[cfg(feature = "seq")]
+#[test]
+fn flush_waits_for_every_earlier_message() {
+    const N: usize = 100;
+    let (tx, rx) = blocking::channel::<usize>(4);
+    let tx2 = tx.clone();
+
+    let consumer = thread::spawn(move || {
+        let mut received = 0;
+        while rx.recv().is_some() {
+            received += 1;
+            // Give the flushing thread a chance to return too early.
+            if received % 10 == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+        received
+    });
+
+    for i in 0..N {
+        tx.send(i).unwrap();
+    }
+    tx2.flush().unwrap();
+    assert_eq!(tx.published_seq(), N);
+    drop((tx, tx2));
+    assert_eq!(consumer.join().unwrap(), N);
+}
+
+#[cfg(feature = "seq")]
+#[test]
+fn flush_fails_if_receiver_dropped_first() {
+    let (tx, rx) = blocking::channel::<usize>(4);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+
+    let consumer = thread::spawn(move || {
+        assert_eq!(rx.recv(), Some(1));
+        thread::sleep(Duration::from_millis(10));
+        drop(rx);
+    });
+
+    assert!(tx.flush().is_err());
+    consumer.join().unwrap();
+
+    // Flushing a channel with nothing left to receive succeeds, even though
+    // it has closed.
+    let (tx, rx) = blocking::channel::<usize>(4);
+    tx.send(1).unwrap();
+    assert_eq!(rx.recv(), Some(1));
+    drop(rx);
+    assert!(tx.flush().is_ok());
+}
+
+#[test]
+fn load_shedding() {
+    let (tx, rx) = blocking::channel::<usize>(8);
+    rx.set_load_shedding(Some(0.5));
+
+    // Below the threshold, nothing is shed.
+    for i in 0..4 {
+        tx.try_send(i).unwrap();
+    }
+    assert_eq!(rx.shed_count(), 0);
+
+    // Once the channel is full, every message is shed and handed back, rather
+    // than reported as full.
+    let mut shed = 0;
+    for i in 4..1000 {
+        match tx.try_send(i) {
+            Ok(()) => {}
+            Err(TrySendError::Shed(val)) => {
+                assert_eq!(val, i);
+                shed += 1;
+            }
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+    assert_eq!(rx.len(), 8);
+    assert_eq!(shed, 1000 - 8);
+    assert_eq!(rx.shed_count(), shed);
+    assert_eq!(
+        tx.send_timeout(1000, Duration::from_millis(1)),
+        Err(SendTimeoutError::Shed(1000))
+    );
+
+    // `send` waits for capacity rather than shedding.
+    assert_eq!(rx.recv(), Some(0));
+    tx.send(1000).unwrap();
+    assert_eq!(rx.len(), 8);
+
+    rx.set_load_shedding(None);
+    assert_eq!(tx.try_send(1001), Err(TrySendError::Full(1001)));
+
+    // Messages are not shed once the channel has closed.
+    rx.set_load_shedding(Some(0.0));
+    drop(rx);
+    assert_eq!(tx.try_send(1002), Err(TrySendError::Closed(1002)));
+}
+
+#[test]
+fn load_shedding_at_capacity_reports_full() {
+    let (tx, rx) = blocking::channel::<usize>(4);
+    rx.set_load_shedding(Some(1.0));
+    for i in 0..4 {
+        tx.try_send(i).unwrap();
+    }
+
+    // Shedding only once the channel is full would shed every message that
+    // doesn't fit, so a full channel is reported instead.
+    assert_eq!(tx.try_send(4), Err(TrySendError::Full(4)));
+    assert_eq!(rx.shed_count(), 0);
+}
+
+#[test]
+fn send_or_modify_merges_when_full() {
+    let (tx, rx) = blocking::channel::<u64>(3);
+    let add = |queued: &mut u64, delta: u64| *queued += delta;
+
+    // Move the tail index around the ring, so that the most recently queued
+    // message is at the end of the previous lap.
+    for i in 0..5 {
+        tx.send(i).unwrap();
+        assert_eq!(rx.recv(), Some(i));
+    }
+    for delta in [1, 2, 3, 4, 5] {
+        tx.send_or_modify(delta, add).unwrap();
+    }
+    assert_eq!(rx.recv(), Some(1));
+    assert_eq!(rx.recv(), Some(2));
+    assert_eq!(rx.recv(), Some(3 + 4 + 5));
+
+    // A message that is being received is never modified.
+    tx.send(10).unwrap();
+    tx.send(20).unwrap();
+    tx.send(30).unwrap();
+    {
+        let first = rx.recv_ref().unwrap();
+        assert_eq!(*first, 10);
+        tx.send_or_modify(1, add).unwrap();
+        assert_eq!(*first, 10);
+    }
+    assert_eq!(rx.recv(), Some(20));
+    tx.send_or_modify(1, add).unwrap();
+    assert_eq!(rx.recv(), Some(31));
+    assert_eq!(rx.recv(), Some(1));
+
+    // The message at the front of the queue is never modified, since the
+    // receiver may already be receiving it.
+    let (tx, rx) = blocking::channel::<u64>(1);
+    tx.send(1).unwrap();
+    assert_eq!(tx.send_or_modify(2, add), Err(TrySendError::Full(2)));
+    assert_eq!(rx.recv(), Some(1));
+
+    // With no message to merge into, the value is returned.
+    let (tx, rx) = blocking::channel::<u64>(2);
+    let sending = (tx.send_ref().unwrap(), tx.send_ref().unwrap());
+    assert_eq!(
+        tx.send_or_modify(1, |_, _| unreachable!()),
+        Err(TrySendError::Full(1))
+    );
+    drop(sending);
+    drop(rx);
+    assert_eq!(
+        tx.send_or_modify(2, |_, _| unreachable!()),
+        Err(TrySendError::Closed(2))
+    );
+}
+
+#[test]
+fn send_or_modify_wakes_parked_receiver() {
+    const N_SENDS: u64 = 100_000;
+    let (tx, rx) = blocking::channel::<u64>(1);
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+
+    // The sender stays alive until everything has been received, so only a
+    // notification from `send_or_modify` can wake the receiver.
+    let sender = thread::spawn(move || {
+        for _ in 0..N_SENDS {
+            // With a single slot, there is nothing to merge into while the
+            // receiver is reading the queued message.
+            while let Err(TrySendError::Full(_)) =
+                tx.send_or_modify(1, |queued, delta| *queued += delta)
+            {
+                thread::yield_now();
+            }
+        }
+        done_rx.recv().unwrap();
+    });
+
+    let mut total = 0;
+    while total < N_SENDS {
+        match rx.recv_timeout(Duration::from_secs(1)) {
+            Ok(n) => total += n,
+            Err(e) => panic!(
+                "receiver was not woken after {} of {}: {:?}",
+                total, N_SENDS, e
+            ),
+        }
+    }
+    assert_eq!(total, N_SENDS);
+    done_tx.send(()).unwrap();
+    sender.join().unwrap();
+}
+
+#[test]
+fn recv_for_each_runs_until_closed() {
+    const N_SENDS: usize = 50;
+    const N_PRODUCERS: usize = 4;
+
+    let (tx, rx) = blocking::channel::<(usize, usize)>(2);
+    let producers = (0..N_PRODUCERS)
+        .map(|n| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..N_SENDS {
+                    tx.send((n, i)).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    let mut next = vec![0; N_PRODUCERS];
+    rx.recv_for_each(|msg| {
+        let (n, i) = *msg;
+        // Messages from each producer arrive in order.
+        assert_eq!(i, next[n]);
+        next[n] += 1;
+    });
+    assert_eq!(next, vec![N_SENDS; N_PRODUCERS]);
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    // Once the channel has closed, this returns immediately.
+    rx.recv_for_each(|_| unreachable!());
+}
+
+#[test]
+fn notify_policies_wake_blocked_senders() {
+    use thingbuf::mpsc::NotifyPolicy;
+
+    const SENDERS: usize = 8;
+    const MSGS: usize = 200;
+
+    for policy in [
+        NotifyPolicy::PerSlot,
+        NotifyPolicy::Batched,
+        NotifyPolicy::WakeAll { threshold: 2 },
+    ] {
+        let (tx, rx) = blocking::channel::<usize>(4);
+        rx.set_notify_policy(policy);
+        assert_eq!(rx.notify_policy(), policy);
+
+        let senders = (0..SENDERS)
+            .map(|_| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..MSGS {
+                        tx.send(i).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(tx);
+
+        // Drain the channel in batches, so that several slots are freed at
+        // once while many senders are blocked.
+        let mut buf = [0; 4];
+        let mut received = 0;
+        loop {
+            let n = rx.recv_into(&mut buf);
+            if n == 0 {
+                break;
+            }
+            received += n;
+            // Single-message receives free one slot at a time.
+            if rx.recv().is_some() {
+                received += 1;
+            }
+        }
+        assert_eq!(received, SENDERS * MSGS, "policy: {:?}", policy);
+
+        for sender in senders {
+            sender.join().unwrap();
+        }
+    }
+
+    // The wake-all threshold is clamped to the channel's capacity.
+    let (_tx, rx) = blocking::channel::<usize>(4);
+    assert_eq!(rx.notify_policy(), NotifyPolicy::PerSlot);
+    rx.set_notify_policy(NotifyPolicy::WakeAll { threshold: 100 });
+    assert_eq!(rx.notify_policy(), NotifyPolicy::WakeAll { threshold: 4 });
+    rx.set_notify_policy(NotifyPolicy::WakeAll { threshold: 0 });
+    assert_eq!(rx.notify_policy(), NotifyPolicy::WakeAll { threshold: 1 });
+}
+
+#[test]
+fn errors_convert_to_io_errors() {
+    use std::io;
+
+    fn send(tx: &blocking::Sender<usize>, val: usize) -> io::Result<()> {
+        tx.try_send(val)?;
+        Ok(())
+    }
+
+    fn recv(rx: &blocking::Receiver<usize>) -> io::Result<usize> {
+        Ok(rx.try_recv()?)
+    }
+
+    let (tx, rx) = blocking::channel(1);
+    assert_eq!(recv(&rx).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    send(&tx, 1).unwrap();
+    assert_eq!(send(&tx, 2).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    assert_eq!(recv(&rx).unwrap(), 1);
+
+    drop(tx);
+    let err = recv(&rx).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(
+        err.into_inner()
+            .unwrap()
+            .downcast::<TryRecvError>()
+            .unwrap(),
+        Box::new(TryRecvError::Closed),
+    );
+}
+
+#[test]
+fn ref_comparisons() {
+    let (tx, rx) = blocking::channel::<usize>(4);
+
+    let mut slot = tx.send_ref().unwrap();
+    *slot.as_mut() = 1;
+    assert_eq!(slot, 1);
+    drop(slot);
+
+    let slot = rx.recv_ref().unwrap();
+    assert_eq!(slot, 1);
+    assert_eq!(*slot.as_ref(), 1);
+}
+
+#[test]
+fn try_send_or_else() {
+    let (tx, rx) = blocking::channel::<usize>(2);
+
+    tx.try_send_with(|slot| *slot = 1).unwrap();
+    assert!(tx
+        .try_send_or_else(2, |_| panic!("channel not full"))
+        .is_ok());
+
+    let mut spilled = Vec::new();
+    tx.try_send_or_else(3, |val| spilled.push(val)).unwrap();
+    assert_eq!(spilled, vec![3]);
+    assert!(matches!(
+        tx.try_send_with(|_| panic!("channel full")),
+        Err(TrySendError::Full(()))
+    ));
+
+    assert_eq!(rx.recv(), Some(1));
+    assert_eq!(rx.recv(), Some(2));
+
+    drop(rx);
+    let err = tx
+        .try_send_or_else(4, |_| panic!("channel closed"))
+        .unwrap_err();
+    assert_eq!(err.into_inner(), 4);
+}
+
+#[test]
+fn receiver_mints_senders() {
+    let (tx, rx) = blocking::channel::<usize>(4);
+    drop(tx);
+    assert!(rx.sender().is_none());
+    assert_eq!(rx.recv(), None);
+
+    let (tx, rx) = blocking::channel::<usize>(4);
+    let producers = (0..2)
+        .map(|i| {
+            let tx = rx.sender().unwrap();
+            thread::spawn(move || tx.send(i).unwrap())
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    let mut received = vec![rx.recv().unwrap(), rx.recv().unwrap()];
+    received.sort_unstable();
+    assert_eq!(received, vec![0, 1]);
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    assert_eq!(rx.recv(), None);
+    assert!(rx.sender().is_none());
+}
+
+#[test]
+fn per_producer_fifo() {
+    const N_SENDS: usize = 1000;
+    const N_PRODUCERS: usize = 4;
+
+    let (tx, rx) = blocking::channel::<(usize, usize)>(8);
+    for producer in 0..N_PRODUCERS {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            for i in 0..N_SENDS {
+                tx.send((producer, i)).unwrap();
+            }
+        });
+    }
+    drop(tx);
+
+    let mut next = [0; N_PRODUCERS];
+    while let Some((producer, i)) = rx.recv() {
+        assert_eq!(i, next[producer], "producer {} out of order", producer);
+        next[producer] += 1;
+    }
+    assert_eq!(next, [N_SENDS; N_PRODUCERS]);
+}
+
+#[test]
+fn dedicated_skips_unused_slots() {
+    /// An iterator that promises more items than it produces.
+    struct Short(Option<usize>);
+    impl Iterator for Short {
+        type Item = usize;
+        fn next(&mut self) -> Option<usize> {
+            self.0.take()
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (4, None)
+        }
+    }
+
+    let (tx, rx) = blocking::channel::<usize>(8);
+
+    let mut dedicated = tx.dedicated(4);
+    // a single send doesn't hold on to a block of slots...
+    dedicated.send(1).unwrap();
+    tx.send(2).unwrap();
+    assert_eq!(rx.recv(), Some(1));
+    assert_eq!(rx.recv(), Some(2));
+
+    // ...and neither does `send_all`, even if it claimed more slots than
+    // the iterator produced.
+    assert_eq!(dedicated.send_all(Short(Some(3))).unwrap(), 1);
+    tx.send(4).unwrap();
+    assert_eq!(rx.recv(), Some(3));
+    assert_eq!(rx.recv(), Some(4));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+    // the abandoned slots can be reused.
+    for i in 0..8 {
+        tx.send(i).unwrap();
+    }
+    for i in 0..8 {
+        assert_eq!(rx.recv(), Some(i));
+    }
+
+    drop(rx);
+    let err = dedicated.send_all(vec![5, 6, 7]).unwrap_err();
+    assert_eq!(err.sent(), 0);
+    let (item, rest) = err.into_inner();
+    assert_eq!(item, 5);
+    assert_eq!(rest.collect::<Vec<_>>(), vec![6, 7]);
+}
+
+#[test]
+fn dedicated_producers() {
+    const N_SENDS: usize = 1000;
+    const N_PRODUCERS: usize = 4;
+
+    let (tx, rx) = blocking::channel::<(usize, usize)>(16);
+    for producer in 0..N_PRODUCERS {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let mut tx = tx.dedicated(4);
+            let sent = tx.send_all((0..N_SENDS).map(|i| (producer, i))).unwrap();
+            assert_eq!(sent, N_SENDS);
+        });
+    }
+    drop(tx);
+
+    let mut next = [0; N_PRODUCERS];
+    while let Some((producer, i)) = rx.recv() {
+        assert_eq!(i, next[producer], "producer {} out of order", producer);
+        next[producer] += 1;
+    }
+    assert_eq!(next, [N_SENDS; N_PRODUCERS]);
+}
+
+#[test]
+fn rewind_replays_history() {
+    use thingbuf::mpsc::blocking::rewind;
+
+    let (tx, rx) = rewind::channel(4, 3);
+    let producer = thread::spawn(move || {
+        for i in 0..10 {
+            tx.send(i).unwrap();
+        }
+    });
+
+    for i in 0..5 {
+        assert_eq!(rx.recv(), Some(i));
+    }
+    assert_eq!(rx.retained(), 3);
+
+    // rewinding twice accumulates, up to the retained history.
+    assert_eq!(rx.rewind(2), 2);
+    assert_eq!(rx.rewind(2), 1);
+    assert_eq!(rx.rewind(1), 0);
+    assert_eq!(rx.recv(), Some(2));
+    assert_eq!(rx.recv(), Some(3));
+
+    // rewinding part way through a replay does not duplicate history.
+    assert_eq!(rx.rewind(1), 1);
+    assert_eq!(rx.recv(), Some(3));
+    assert_eq!(rx.recv(), Some(4));
+
+    assert_eq!((&rx).collect::<Vec<_>>(), vec![5, 6, 7, 8, 9]);
+    producer.join().unwrap();
+    assert_eq!(rx.rewind(10), 3);
+    assert_eq!(rx.try_recv(), Ok(7));
+}
+
+#[test]
+fn pipeline_stages() {
+    let (tx, rx, stages) = blocking::pipeline()
+        .stage(4, |n: u64| n * 2)
+        .stage(4, |n| n.to_string())
+        .stage(4, |s: String| s.len())
+        .output(4);
+    assert_eq!(stages.len(), 3);
+
+    let producer = thread::spawn(move || {
+        for n in 0..1000 {
+            tx.send(n).unwrap();
+        }
+    });
+    let expected = (0..1000u64).map(|n| (n * 2).to_string().len());
+    assert!((&rx).eq(expected));
+
+    producer.join().unwrap();
+    stages.join().unwrap();
+}
+
+#[test]
+fn pipeline_shutdown_from_output() {
+    let (tx, rx, stages) = blocking::pipeline()
+        .stage(2, |n: usize| n + 1)
+        .stage(2, |n| n + 1)
+        .output(2);
+    tx.send(0).unwrap();
+    assert_eq!(rx.recv(), Some(2));
+
+    // dropping the output shuts down every stage, until sends to the input
+    // fail.
+    drop(rx);
+    while tx.send(0).is_ok() {}
+    stages.join().unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn try_send_from_signal_handler() {
+    use std::sync::atomic::{AtomicPtr, Ordering};
+
+    static TX: AtomicPtr<blocking::Sender<i32>> = AtomicPtr::new(std::ptr::null_mut());
+
+    extern "C" fn handler(signum: libc::c_int) {
+        let tx = TX.load(Ordering::Acquire);
+        if let Some(tx) = unsafe { tx.as_ref() } {
+            let _ = tx.try_send_from_signal(signum);
+        }
+    }
+
+    let (tx, rx) = blocking::channel(4);
+    TX.store(Box::into_raw(Box::new(tx)), Ordering::Release);
+    unsafe {
+        let handler: extern "C" fn(libc::c_int) = handler;
+        libc::signal(libc::SIGUSR1, handler as libc::sighandler_t);
+    }
+
+    // The receiver blocks until the signal handler wakes it.
+    let consumer = thread::spawn(move || rx.recv());
+    thread::sleep(Duration::from_millis(50));
+    unsafe {
+        libc::raise(libc::SIGUSR1);
+        libc::signal(libc::SIGUSR1, libc::SIG_DFL);
+    }
+    assert_eq!(consumer.join().unwrap(), Some(libc::SIGUSR1));
+
+    let tx = TX.swap(std::ptr::null_mut(), Ordering::AcqRel);
+    drop(unsafe { Box::from_raw(tx) });
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "not async-signal-safe")]
+fn try_send_from_signal_rejects_custom_parker() {
+    use blocking::park::{self, CondvarParker};
+
+    let (tx, rx) = blocking::channel::<i32>(4);
+
+    // The receiver blocks with a parker whose `Unpark` implementation locks a
+    // mutex, so it must not be woken from a signal handler.
+    thread::spawn(move || {
+        park::set_thread_parker(CondvarParker::new());
+        rx.recv_timeout(Duration::from_secs(1))
+    });
+    thread::sleep(Duration::from_millis(50));
+    let _ = tx.try_send_from_signal(1);
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn bytes_mut_frames() {
+    use bytes::{Buf, BufMut, BytesMut};
+    use thingbuf::recycling::WithCapacity;
+
+    let (tx, rx) =
+        blocking::with_recycle::<BytesMut, _>(2, WithCapacity::new().with_min_capacity(64));
+
+    for round in 0..4u8 {
+        let mut frame = tx.send_ref().unwrap();
+        frame.put_u8(round);
+        frame.put_slice(b"hello");
+        drop(frame);
+
+        let mut frame = rx.recv_ref().unwrap();
+        assert_eq!(frame.remaining(), 6);
+        assert_eq!(frame.get_u8(), round);
+        // Split the payload off as a `Bytes`, which is dropped before the slot
+        // is reused, so that its capacity is reclaimed.
+        let payload = frame.copy_to_bytes(5);
+        assert_eq!(&payload[..], b"hello");
+        assert!(!frame.has_remaining());
+        drop(frame);
+        drop(payload);
+    }
+
+    let frame = tx.send_ref().unwrap();
+    assert!(frame.is_empty());
+    assert!(frame.capacity() >= 64);
+}
+
+#[test]
+fn ttl_skips_expired_messages() {
+    use thingbuf::mpsc::blocking::ttl;
+
+    let (tx, rx) = ttl::channel::<usize>(8, Some(Duration::from_millis(10)));
+    tx.send(1).unwrap();
+    tx.try_send(2).unwrap();
+    tx.send_with_ttl(3, Duration::from_secs(60)).unwrap();
+    thread::sleep(Duration::from_millis(20));
+    tx.send(4).unwrap();
+
+    assert_eq!(rx.try_recv(), Ok(3));
+    assert_eq!(rx.expired_count(), 2);
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)), Ok(4));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+    // expired slots are reused by later messages.
+    for i in 0..8 {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+    assert_eq!(
+        rx.into_iter().collect::<Vec<_>>(),
+        (0..8).collect::<Vec<_>>()
+    );
+    assert_eq!(rx.expired_count(), 2);
+}
+
+#[test]
+fn ttl_dead_letters_lost_messages() {
+    use thingbuf::mpsc::blocking::ttl;
+
+    let (dead_tx, dead_rx) = blocking::channel::<usize>(16);
+    let (tx, rx) = ttl::with_dead_letter(4, Some(Duration::from_millis(10)), dead_tx);
+    tx.send(1).unwrap();
+    thread::sleep(Duration::from_millis(20));
+
+    // with load shedding enabled, a full channel sheds every message rather
+    // than reporting that it is full, so some of these are handed back.
+    rx.set_load_shedding(Some(0.0));
+    let mut shed = Vec::new();
+    for i in 2..6 {
+        match tx.try_send_with_ttl(i, Duration::from_secs(60)) {
+            Ok(()) => {}
+            Err(TrySendError::Shed(i)) => shed.push(i),
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+    rx.set_load_shedding(None);
+    assert!(!shed.is_empty());
+    assert_eq!(rx.shed_count(), shed.len());
+
+    let mut received = Vec::new();
+    while let Ok(msg) = rx.try_recv() {
+        received.push(msg);
+    }
+    assert_eq!(rx.expired_count(), 1);
+    tx.try_send(6).unwrap();
+    drop(rx);
+
+    // every message was either received, shed, or dead-lettered.
+    let mut dead = Vec::new();
+    while let Ok(msg) = dead_rx.try_recv() {
+        dead.push(msg);
+    }
+    assert!(dead.contains(&1));
+    assert_eq!(dead.last(), Some(&6));
+    let mut all = received;
+    all.extend(shed);
+    all.extend(dead);
+    all.sort_unstable();
+    assert_eq!(all, (1..7).collect::<Vec<_>>());
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn recv_ref_enqueued_at() {
+    use std::time::Instant;
+
+    let (tx, rx) = blocking::channel::<usize>(4);
+    let start = Instant::now();
+    tx.send(1).unwrap();
+    thread::sleep(Duration::from_millis(10));
+    let sent = Instant::now();
+    tx.send(2).unwrap();
+
+    let first = rx.recv_ref().unwrap();
+    let first_at = first.enqueued_at();
+    assert!(first_at >= start && first_at <= sent);
+    drop(first);
+
+    let second = rx.recv_ref().unwrap();
+    assert!(second.enqueued_at() >= sent);
+    assert!(second.enqueued_at() - first_at >= Duration::from_millis(10));
+}
+
+#[test]
+#[cfg(all(feature = "stats", feature = "timestamps"))]
+fn latency_histogram() {
+    let (tx, rx) = blocking::channel::<usize>(4);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    thread::sleep(Duration::from_millis(5));
+    assert_eq!(rx.recv(), Some(1));
+    assert_eq!(rx.recv(), Some(2));
+
+    let stats = tx.stats();
+    let latency = stats.latency();
+    assert_eq!(latency.samples(), 2);
+    assert_eq!(rx.stats().latency().counts(), latency.counts());
+    // both messages waited at least 5ms, so they are in buckets starting at
+    // 4096µs or above.
+    let slow = latency
+        .buckets()
+        .filter(|(latencies, _)| latencies.start >= Duration::from_micros(4096))
+        .map(|(_, count)| count)
+        .sum::<usize>();
+    assert_eq!(slow, 2);
+}
+
+#[test]
+fn wake_watermark() {
+    use std::time::Instant;
+
+    let (tx, rx) = blocking::channel::<usize>(8);
+    // The maximum delay is long enough that the receiver must be woken by
+    // the watermark.
+    rx.set_wake_watermark(4, Duration::from_secs(30));
+    assert_eq!(rx.wake_watermark(), 4);
+
+    let start = Instant::now();
+    let sender = thread::spawn(move || {
+        for i in 0..4 {
+            thread::sleep(Duration::from_millis(1));
+            tx.send(i).unwrap();
+        }
+        tx
+    });
+    assert_eq!(rx.recv(), Some(0));
+    assert!(start.elapsed() < Duration::from_secs(30));
+    let tx = sender.join().unwrap();
+    for i in 1..4 {
+        assert_eq!(rx.recv(), Some(i));
+    }
+
+    // A partial batch is received once the maximum delay elapses.
+    rx.set_wake_watermark(4, Duration::from_millis(10));
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(5));
+        tx.send(4).unwrap();
+        tx
+    });
+    assert_eq!(rx.recv(), Some(4));
+    drop(sender.join().unwrap());
+    assert_eq!(rx.recv(), None);
+
+    // Watermarks are capped at the channel's capacity.
+    rx.set_wake_watermark(100, Duration::from_millis(10));
+    assert_eq!(rx.wake_watermark(), 8);
+}
+
+#[test]
+fn custom_thread_parkers() {
+    use blocking::park::{self, CondvarParker, Park, Unpark};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Counts how many times its thread is unparked.
+    struct CountingParker {
+        inner: CondvarParker,
+        unparks: Arc<AtomicUsize>,
+    }
+
+    struct CountingUnparker {
+        inner: park::CondvarUnparker,
+        unparks: Arc<AtomicUsize>,
+    }
+
+    impl Park for CountingParker {
+        type Unpark = CountingUnparker;
+
+        fn park(&self) {
+            self.inner.park()
+        }
+
+        fn park_timeout(&self, timeout: Duration) {
+            self.inner.park_timeout(timeout)
+        }
+
+        fn unparker(&self) -> CountingUnparker {
+            CountingUnparker {
+                inner: self.inner.unparker(),
+                unparks: self.unparks.clone(),
+            }
+        }
+    }
+
+    impl Unpark for CountingUnparker {
+        fn unpark(&self) {
+            self.unparks.fetch_add(1, Ordering::SeqCst);
+            self.inner.unpark()
+        }
+    }
+
+    let (tx, rx) = blocking::channel::<usize>(1);
+    let unparks = Arc::new(AtomicUsize::new(0));
+
+    // A sender waiting for capacity blocks with its custom parker.
+    let producer = thread::spawn({
+        let unparks = unparks.clone();
+        move || {
+            park::set_thread_parker(CountingParker {
+                inner: CondvarParker::new(),
+                unparks,
+            });
+            for i in 0..10 {
+                tx.send(i).unwrap();
+            }
+        }
+    });
+
+    // The receiver blocks on a condition variable.
+    park::set_thread_parker(CondvarParker::new());
+    for i in 0..10 {
+        assert_eq!(rx.recv(), Some(i));
+        thread::sleep(Duration::from_millis(1));
+    }
+    producer.join().unwrap();
+    assert_eq!(rx.recv(), None);
+    assert!(unparks.load(Ordering::SeqCst) > 0);
+
+    park::reset_thread_parker();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_millis(1)),
+        Err(RecvTimeoutError::Closed)
+    );
+}
+
+#[test]
+fn recv_into_wakes_senders() {
+    let (tx, rx) = blocking::channel::<u32>(4);
+    let producers = (0..2)
+        .map(|p| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..100 {
+                    tx.send(p * 1000 + i).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    let mut buf = [0; 3];
+    let mut received = Vec::new();
+    loop {
+        let n = rx.recv_into(&mut buf);
+        if n == 0 {
+            break;
+        }
+        assert!(n <= buf.len());
+        received.extend_from_slice(&buf[..n]);
+    }
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    assert_eq!(received.len(), 200);
+    for p in 0..2 {
+        let from_p = received
+            .iter()
+            .filter(|&&i| i / 1000 == p)
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(from_p, (0..100).map(|i| p * 1000 + i).collect::<Vec<_>>());
+    }
+    assert_eq!(rx.recv_into(&mut []), 0);
+}
+
+#[test]
+fn interceptor_vetoes_and_mutates() {
+    use blocking::intercept::{self, Verdict};
+
+    let (tx, rx) = blocking::channel::<u32>(2);
+    let tx = intercept::Sender::new(tx, |msg: &mut u32| {
+        if *msg % 2 == 1 {
+            return Verdict::Veto;
+        }
+        *msg *= 10;
+        Verdict::Publish
+    });
+
+    // Vetoed messages give their slots back, so the sender never fills the
+    // channel with them.
+    let producer = thread::spawn(move || {
+        for i in 0..20 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(tx.send(21), Ok(Verdict::Veto));
+        tx.vetoed_count()
+    });
+
+    let received = std::iter::from_fn(|| rx.recv()).collect::<Vec<_>>();
+    assert_eq!(producer.join().unwrap(), 11);
+    assert_eq!(
+        received,
+        (0..20).step_by(2).map(|i| i * 10).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn sender_map_converts_in_place() {
+    let (tx, rx) = blocking::channel::<String>(2);
+    let numbers = tx.clone().with_map(|n: usize, slot: &mut String| {
+        slot.push_str(&n.to_string());
+    });
+    let chars = tx.with_map(|c: char, slot: &mut String| slot.push(c));
+
+    numbers.send(1).unwrap();
+    chars.try_send('a').unwrap();
+    assert_eq!(numbers.try_send(2), Err(TrySendError::Full(2)));
+
+    let producer = thread::spawn(move || {
+        for i in 2..10 {
+            numbers.send(i).unwrap();
+        }
+    });
+    assert_eq!(rx.recv().as_deref(), Some("1"));
+    assert_eq!(rx.recv().as_deref(), Some("a"));
+    for i in 2..10 {
+        // Each slot's previous contents were cleared before conversion.
+        assert_eq!(rx.recv(), Some(i.to_string()));
+    }
+    producer.join().unwrap();
+
+    drop(rx);
+    assert_eq!(chars.send('b').unwrap_err().into_inner(), 'b');
+}
+
+#[test]
+fn channel_group_shuts_down_members() {
+    use thingbuf::mpsc::group::{ChannelGroup, ShutdownReason};
+
+    let (a_tx, a_rx) = blocking::channel::<usize>(2);
+    let (b_tx, b_rx) = blocking::channel::<usize>(2);
+    let group = ChannelGroup::new();
+    assert_eq!(group.add(&a_tx), 0);
+    assert_eq!(group.add(&b_rx), 1);
+
+    a_tx.send(1).unwrap();
+    a_tx.send(2).unwrap();
+    // Both the blocked sender and the blocked receiver are woken.
+    let sender = thread::spawn(move || a_tx.send(3));
+    let receiver = thread::spawn(move || b_rx.recv());
+
+    assert!(group.shutdown("stopping"));
+    assert!(!group.shutdown("again"));
+    assert_eq!(group.reason(), Some(ShutdownReason::Shutdown("stopping")));
+
+    assert_eq!(sender.join().unwrap().unwrap_err().into_inner(), 3);
+    assert_eq!(receiver.join().unwrap(), None);
+    assert!(b_tx.send(1).is_err());
+
+    // Messages sent before the shutdown are still received.
+    assert!(a_rx.is_closed());
+    assert_eq!(a_rx.recv(), Some(1));
+    assert_eq!(a_rx.recv(), Some(2));
+    assert_eq!(a_rx.recv(), None);
+
+    // A channel added after the group shut down is closed immediately.
+    let (c_tx, c_rx) = blocking::channel::<usize>(2);
+    group.add(&c_rx);
+    assert!(c_tx.send(1).is_err());
+}
+
+#[test]
+fn channel_group_member_close_propagates() {
+    use thingbuf::mpsc::group::{ChannelGroup, ShutdownReason};
+
+    let (a_tx, a_rx) = blocking::channel::<usize>(2);
+    let (b_tx, b_rx) = blocking::channel::<usize>(2);
+    let (c_tx, c_rx) = blocking::channel::<usize>(2);
+    let first = ChannelGroup::<()>::new();
+    let second = ChannelGroup::<()>::new();
+    first.add(&a_tx);
+    first.add(&b_tx);
+    // `b` links the two groups.
+    second.add(&b_rx);
+    second.add(&c_tx);
+
+    drop(a_tx);
+    assert_eq!(a_rx.recv(), None);
+    assert_eq!(first.reason(), Some(ShutdownReason::Closed(0)));
+    assert!(b_tx.send(1).is_err());
+    assert_eq!(b_rx.recv(), None);
+    assert_eq!(second.reason(), Some(ShutdownReason::Closed(0)));
+    assert!(c_tx.send(1).is_err());
+    assert_eq!(c_rx.recv(), None);
+
+    // Adding a channel that has already closed shuts the group down.
+    let (d_tx, d_rx) = blocking::channel::<usize>(2);
+    drop(d_rx);
+    let (e_tx, _e_rx) = blocking::channel::<usize>(2);
+    let third = ChannelGroup::<()>::new();
+    third.add(&e_tx);
+    let d = third.add(&d_tx);
+    assert_eq!(third.reason(), Some(ShutdownReason::Closed(d)));
+    assert!(e_tx.send(1).is_err());
+}
+
+#[test]
+#[cfg(feature = "rt-safe")]
+fn rt_handles_only_try() {
+    use thingbuf::mpsc::{errors::TryRecvError, rt};
+
+    let (tx, rx) = blocking::channel::<Vec<u8>>(2);
+    let tx = rt::Sender::new(tx);
+    let rx = rt::Receiver::new(rx);
+
+    tx.try_send(vec![1, 2]).unwrap();
+    tx.try_send_with(|buf| buf.extend_from_slice(&[3, 4]))
+        .unwrap();
+    assert_eq!(tx.try_send(vec![5]), Err(TrySendError::Full(vec![5])));
+
+    assert_eq!(rx.try_recv(), Ok(vec![1, 2]));
+    assert_eq!(rx.try_recv_with(|buf| buf.len()), Ok(2));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+    // Unwrapping the real-time sender allows it to wait again.
+    let tx = tx.into_inner();
+    let producer = thread::spawn(move || {
+        for i in 0..4 {
+            tx.send(vec![i]).unwrap();
+        }
+    });
+    let mut received = Vec::new();
+    while received.len() < 4 {
+        match rx.try_recv() {
+            Ok(msg) => received.push(msg[0]),
+            Err(TryRecvError::Empty) => thread::yield_now(),
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+    producer.join().unwrap();
+    assert_eq!(received, vec![0, 1, 2, 3]);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+}
+
+#[test]
+#[cfg(feature = "audit-refs")]
+#[should_panic(expected = "1 `SendRef`s and 0 `RecvRef`s were still outstanding")]
+fn audit_refs_leaked_send_ref() {
+    let (tx, rx) = blocking::channel::<usize>(4);
+    std::mem::forget(tx.send_ref().unwrap());
+    drop(tx);
+    drop(rx);
+}
+
+#[test]
+#[cfg(feature = "audit-refs")]
+#[should_panic(expected = "0 `SendRef`s and 1 `RecvRef`s were still outstanding")]
+fn audit_refs_leaked_recv_ref() {
+    let (tx, rx) = blocking::channel::<usize>(4);
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+    // Guards that are dropped normally are not reported.
+    assert_eq!(*rx.recv_ref().unwrap(), 1);
+    std::mem::forget(rx.recv_ref().unwrap());
+    drop(tx);
+    drop(rx);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn par_send_all_stops_when_closed() {
+    use rayon::prelude::*;
+
+    let (tx, rx) = blocking::channel::<usize>(4);
+    let consumer = thread::spawn(move || {
+        for _ in 0..100 {
+            rx.recv().unwrap();
+        }
+    });
+
+    assert!(tx.par_send_all((0..10_000).into_par_iter()).is_err());
+    consumer.join().unwrap();
+}
+
+#[test]
+#[cfg(feature = "crossbeam-channel")]
+fn select_receiver_in_crossbeam_select() {
+    use blocking::select;
+    use crossbeam_channel::select;
+
+    const N: usize = 1000;
+    let (tx, rx) = blocking::channel::<usize>(4);
+    let rx = select::Receiver::new(rx);
+    let (ticks_tx, ticks_rx) = crossbeam_channel::bounded::<()>(1);
+
+    let producer = thread::spawn(move || {
+        for i in 0..N {
+            tx.send(i).unwrap();
+            if i % 100 == 0 {
+                ticks_tx.send(()).unwrap();
+            }
+        }
+    });
+
+    let mut received = Vec::new();
+    let mut ticks = 0;
+    loop {
+        select! {
+            recv(rx.ready()) -> token => {
+                token.unwrap();
+                match rx.try_recv() {
+                    Ok(i) => received.push(i),
+                    Err(TryRecvError::Empty) => {}
+                    Err(TryRecvError::Closed) => break,
+                    Err(e) => panic!("unexpected error: {}", e),
+                }
+            }
+            recv(ticks_rx) -> tick => {
+                if tick.is_ok() {
+                    ticks += 1;
+                }
+            }
+        }
+    }
+
+    producer.join().unwrap();
+    assert_eq!(received, (0..N).collect::<Vec<_>>());
+    ticks += ticks_rx.try_iter().count();
+    assert_eq!(ticks, N / 100);
+}
+
+#[test]
+#[cfg(all(feature = "eventfd", target_os = "linux"))]
+fn eventfd_receiver_in_poll_loop() {
+    use blocking::eventfd;
+    use std::os::unix::io::AsRawFd;
+
+    const N: usize = 1000;
+    let (tx, rx) = blocking::channel::<usize>(4);
+    let rx = eventfd::Receiver::new(rx).unwrap();
+
+    let producer = thread::spawn(move || {
+        for i in 0..N {
+            tx.send(i).unwrap();
+        }
+    });
+
+    let mut received = Vec::new();
+    let mut pollfd = libc::pollfd {
+        fd: rx.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    'poll: loop {
+        let ready = unsafe { libc::poll(&mut pollfd, 1, 5_000) };
+        assert_eq!(ready, 1, "eventfd did not become readable");
+        loop {
+            match rx.try_recv() {
+                Ok(i) => received.push(i),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Closed) => break 'poll,
+                Err(e) => panic!("unexpected error: {}", e),
+            }
+        }
+    }
+
+    producer.join().unwrap();
+    assert_eq!(received, (0..N).collect::<Vec<_>>());
+}
+
+#[test]
+fn budget_sender_waits_for_bytes_to_be_received() {
+    use blocking::budget;
+
+    let (tx, rx) = budget::channel::<Vec<u8>>(16, 100);
+    tx.send(vec![0; 60]).unwrap();
+
+    let sender = {
+        let tx = tx.clone();
+        thread::spawn(move || tx.send(vec![1; 60]).unwrap())
+    };
+    thread::sleep(Duration::from_millis(10));
+    // The second message does not fit until the first is received.
+    assert_eq!(rx.len(), 1);
+    assert_eq!(tx.used_bytes(), 60);
+
+    assert_eq!(rx.recv(), Some(vec![0; 60]));
+    sender.join().unwrap();
+    assert_eq!(rx.recv(), Some(vec![1; 60]));
+    assert_eq!(tx.used_bytes(), 0);
+}
+
+#[test]
+fn budget_sends_oversized_message_alone() {
+    use blocking::budget;
+
+    let (tx, rx) = budget::channel::<String>(16, 10);
+    tx.send("hello".to_string()).unwrap();
+    let big = "a".repeat(50);
+    assert_eq!(
+        tx.try_send(big.clone()),
+        Err(TrySendError::Full(big.clone()))
+    );
+
+    assert_eq!(rx.try_recv().as_deref(), Ok("hello"));
+    tx.try_send(big.clone()).unwrap();
+    assert_eq!(rx.used_bytes(), 50);
+    assert_eq!(rx.recv(), Some(big));
+}
+
+#[test]
+fn budget_sender_wakes_when_receiver_dropped() {
+    use blocking::budget;
+
+    let (tx, rx) = budget::channel::<Vec<u8>>(16, 10);
+    tx.send(vec![0; 10]).unwrap();
+
+    let sender = thread::spawn(move || tx.send(vec![1; 10]));
+    thread::sleep(Duration::from_millis(10));
+    drop(rx);
+    assert!(sender.join().unwrap().is_err());
+}
+
+#[test]
+fn budget_send_ref_waits_for_reserved_bytes() {
+    use blocking::budget;
+
+    let (tx, rx) = budget::channel::<Vec<u8>>(16, 10);
+    let mut msg = tx.send_ref(8).unwrap();
+    msg.extend_from_slice(b"abcdefgh");
+    drop(msg);
+
+    let sender = {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let mut msg = tx.send_ref(8).unwrap();
+            msg.extend_from_slice(b"ij");
+        })
+    };
+    thread::sleep(Duration::from_millis(10));
+    // The second reservation does not fit until the first is received.
+    assert_eq!(rx.len(), 1);
+
+    assert_eq!(rx.recv().as_deref(), Some(&b"abcdefgh"[..]));
+    sender.join().unwrap();
+    // The second message is charged for what was written, not reserved.
+    assert_eq!(tx.used_bytes(), 2);
+    assert_eq!(rx.recv().as_deref(), Some(&b"ij"[..]));
+    assert_eq!(tx.used_bytes(), 0);
+}
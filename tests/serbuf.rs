@@ -0,0 +1,105 @@
+#![cfg(feature = "serbuf")]
+use thingbuf::mpsc::{self, blocking, serbuf::SendError};
+
+fn encode(msg: &str, buf: &mut Vec<u8>) -> Result<(), &'static str> {
+    if msg.is_empty() {
+        return Err("empty message");
+    }
+    buf.extend_from_slice(msg.as_bytes());
+    Ok(())
+}
+
+fn decode(buf: &[u8]) -> Result<String, std::str::Utf8Error> {
+    std::str::from_utf8(buf).map(str::to_owned)
+}
+
+#[test]
+fn blocking_round_trip() {
+    let (tx, rx) = blocking::channel::<Vec<u8>>(3);
+    tx.send_serialized("hello", encode).unwrap();
+
+    // A message that fails to serialize is never received, although its slot
+    // is only reused once the receiver has skipped it.
+    assert_eq!(
+        tx.send_serialized("", encode),
+        Err(SendError::Encode("empty message"))
+    );
+    tx.try_send_serialized("world", encode).unwrap();
+    assert_eq!(tx.try_send_serialized("full", encode), Err(SendError::Full));
+
+    assert_eq!(rx.recv_deserialized(decode), Some(Ok("hello".to_owned())));
+    assert_eq!(rx.recv_deserialized(decode), Some(Ok("world".to_owned())));
+
+    // Invalid messages are still received.
+    tx.send_serialized(&[0xff][..], |msg: &[u8], buf: &mut Vec<u8>| {
+        buf.extend_from_slice(msg);
+        Ok::<_, ()>(())
+    })
+    .unwrap();
+    assert!(matches!(rx.recv_deserialized(decode), Some(Err(_))));
+
+    drop(rx);
+    assert_eq!(tx.send_serialized("closed", encode), Err(SendError::Closed));
+}
+
+#[test]
+fn blocking_reuses_buffers() {
+    let (tx, rx) = blocking::channel::<Vec<u8>>(1);
+    let long = "a".repeat(1024);
+    tx.send_serialized(long.as_str(), encode).unwrap();
+    assert_eq!(rx.recv_deserialized(decode), Some(Ok(long)));
+
+    // The next message is encoded into the same buffer, which is cleared
+    // first but keeps its capacity.
+    tx.send_serialized("short", encode).unwrap();
+    let frame = rx.recv_ref().unwrap();
+    assert_eq!(&frame[..], b"short");
+    assert!(frame.capacity() >= 1024);
+}
+
+#[test]
+fn blocking_abandoned_slot_wakes_receiver() {
+    use std::thread;
+
+    let (tx, rx) = blocking::channel::<Vec<u8>>(4);
+    let producer = thread::spawn(move || {
+        for i in 0..100 {
+            let msg = if i % 3 == 0 {
+                String::new()
+            } else {
+                i.to_string()
+            };
+            let _ = tx.send_serialized(msg.as_str(), encode);
+        }
+    });
+
+    let mut received = Vec::new();
+    while let Some(msg) = rx.recv_deserialized(decode) {
+        received.push(msg.unwrap().parse::<usize>().unwrap());
+    }
+    producer.join().unwrap();
+    let expected = (0..100).filter(|i| i % 3 != 0).collect::<Vec<_>>();
+    assert_eq!(received, expected);
+}
+
+#[tokio::test]
+async fn async_round_trip() {
+    let (tx, rx) = mpsc::channel::<Vec<u8>>(2);
+    let producer = tokio::spawn(async move {
+        for msg in ["hello", "", "world"] {
+            let res = tx.send_serialized(msg, encode).await;
+            assert_eq!(res.is_err(), msg.is_empty());
+        }
+    });
+
+    assert_eq!(
+        rx.recv_deserialized(decode).await,
+        Some(Ok("hello".to_owned()))
+    );
+    assert_eq!(
+        rx.recv_deserialized(decode).await,
+        Some(Ok("world".to_owned()))
+    );
+    assert_eq!(rx.recv_deserialized(decode).await, None);
+    producer.await.unwrap();
+}
@@ -0,0 +1,220 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use thingbuf::telemetry::{FlushGuard, FlushPolicy, Telemetry};
+
+#[test]
+fn collects_from_each_thread_in_order() {
+    const THREADS: usize = 4;
+    const EVENTS: usize = 8;
+
+    let (telemetry, mut collector) = Telemetry::<usize>::new(EVENTS);
+    let threads = (0..THREADS)
+        .map(|t| {
+            let telemetry = telemetry.clone();
+            thread::spawn(move || {
+                for i in 0..EVENTS {
+                    assert!(telemetry.record(t * EVENTS + i));
+                }
+                // This thread's buffer is full.
+                assert!(!telemetry.record(0));
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(collector.threads(), THREADS);
+    assert_eq!(collector.missed(), THREADS);
+    assert_eq!(collector.missed(), 0);
+
+    let mut events = Vec::new();
+    // Batches take events from every thread in turn.
+    assert_eq!(collector.collect_into(&mut events, THREADS), THREADS);
+    let mut firsts = events.iter().map(|e| e / EVENTS).collect::<Vec<_>>();
+    firsts.sort_unstable();
+    assert_eq!(firsts, (0..THREADS).collect::<Vec<_>>());
+
+    while collector.collect_into(&mut events, 5) > 0 {}
+    assert_eq!(events.len(), THREADS * EVENTS);
+    for t in 0..THREADS {
+        let mine = events
+            .iter()
+            .copied()
+            .filter(|e| e / EVENTS == t)
+            .collect::<Vec<_>>();
+        assert_eq!(mine, (t * EVENTS..(t + 1) * EVENTS).collect::<Vec<_>>());
+    }
+
+    // The exited threads' buffers are removed once they have been drained.
+    collector.collect_into(&mut events, 1);
+    assert_eq!(collector.threads(), 0);
+}
+
+#[test]
+fn flush_guard_drains_remaining_events() {
+    let (telemetry, collector) = Telemetry::<usize>::new(4);
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let guard = {
+        let batches = batches.clone();
+        // The interval is long enough that only dropping the guard drains the
+        // buffer.
+        collector.spawn(3, Duration::from_secs(60), move |events, missed| {
+            batches.lock().unwrap().push((events.to_vec(), missed));
+        })
+    };
+
+    for i in 0..5 {
+        telemetry.record(i);
+    }
+    drop(guard);
+
+    let batches = batches.lock().unwrap();
+    let events = batches
+        .iter()
+        .flat_map(|(events, _)| events.iter().copied())
+        .collect::<Vec<_>>();
+    let missed = batches.iter().map(|&(_, missed)| missed).sum::<usize>();
+    assert!(batches.iter().all(|(events, _)| events.len() <= 3));
+    // Either the collector drained the buffer before the last event was
+    // recorded, or it was full and the last event was missed.
+    assert_eq!(events.len() + missed, 5);
+    assert_eq!(events, (0..events.len()).collect::<Vec<_>>());
+}
+
+#[test]
+fn flush_guard_flushes_without_stopping() {
+    let (telemetry, collector) = Telemetry::<usize>::new(8);
+    let written = Arc::new(Mutex::new(Vec::new()));
+    let guard = {
+        let written = written.clone();
+        collector.spawn(2, Duration::from_secs(60), move |events, _| {
+            written.lock().unwrap().extend_from_slice(events);
+        })
+    };
+
+    for i in 0..5 {
+        telemetry.record(i);
+    }
+    assert!(guard.flush());
+    assert_eq!(*written.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+
+    // The collector thread is still running after a flush.
+    telemetry.record(5);
+    assert!(guard.flush());
+    assert_eq!(written.lock().unwrap().len(), 6);
+    assert!(guard.shutdown().is_ok());
+}
+
+#[test]
+fn flush_guard_reports_sink_panic() {
+    let (telemetry, collector) = Telemetry::<usize>::new(8);
+    let guard = collector.spawn(8, Duration::from_secs(60), |_, _| panic!("sink failed"));
+
+    telemetry.record(1);
+    assert!(!guard.flush());
+    assert!(!guard.flush());
+    assert!(guard.shutdown().is_err());
+}
+
+/// The batches of events passed to a sink.
+type Batches = Arc<Mutex<Vec<Vec<String>>>>;
+
+/// Spawns a collector for `String` events with the given flush policy, which
+/// checks for events every millisecond, and records each batch passed to its
+/// sink.
+fn spawn_with_policy(policy: FlushPolicy<String>) -> (Telemetry<String>, FlushGuard, Batches) {
+    let (telemetry, collector) = Telemetry::<String>::new(16);
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let guard = {
+        let batches = batches.clone();
+        collector
+            .flush_policy(policy)
+            .spawn(8, Duration::from_millis(1), move |events, _| {
+                batches.lock().unwrap().push(events.to_vec());
+            })
+    };
+    (telemetry, guard, batches)
+}
+
+/// Waits until the sink has been passed `n` events in total.
+fn wait_for_events(batches: &Mutex<Vec<Vec<String>>>, n: usize) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while batches.lock().unwrap().iter().map(Vec::len).sum::<usize>() < n {
+        assert!(
+            Instant::now() < deadline,
+            "timed out waiting for {} events",
+            n
+        );
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn flush_policy_per_record() {
+    let (telemetry, guard, batches) = spawn_with_policy(FlushPolicy::per_record());
+    for i in 0..6 {
+        telemetry.record(i.to_string());
+    }
+    guard.shutdown().unwrap();
+
+    let batches = batches.lock().unwrap();
+    assert_eq!(batches.len(), 6);
+    assert!(batches.iter().all(|batch| batch.len() == 1));
+    assert_eq!(batches.concat(), ["0", "1", "2", "3", "4", "5"]);
+}
+
+#[test]
+fn flush_policy_on_newline() {
+    let (telemetry, guard, batches) = spawn_with_policy(FlushPolicy::on_newline());
+    telemetry.record("hello ".into());
+    telemetry.record("world".into());
+    // Partial lines are held back.
+    thread::sleep(Duration::from_millis(50));
+    assert!(batches.lock().unwrap().is_empty());
+
+    telemetry.record("!\n".into());
+    telemetry.record("next ".into());
+    wait_for_events(&batches, 3);
+    assert_eq!(batches.lock().unwrap().concat(), ["hello ", "world", "!\n"]);
+
+    // Explicitly flushing passes on the partial line, too.
+    assert!(guard.flush());
+    assert_eq!(batches.lock().unwrap().concat().len(), 4);
+    guard.shutdown().unwrap();
+}
+
+#[test]
+fn flush_policy_bytes() {
+    let (telemetry, guard, batches) = spawn_with_policy(FlushPolicy::bytes(8));
+    telemetry.record("abc".into());
+    thread::sleep(Duration::from_millis(50));
+    assert!(batches.lock().unwrap().is_empty());
+
+    telemetry.record("defgh".into());
+    wait_for_events(&batches, 2);
+    assert_eq!(batches.lock().unwrap().concat(), ["abc", "defgh"]);
+
+    // Shutting down passes on the events below the threshold.
+    telemetry.record("i".into());
+    guard.shutdown().unwrap();
+    assert_eq!(batches.lock().unwrap().concat(), ["abc", "defgh", "i"]);
+}
+
+#[test]
+fn flush_policy_interval() {
+    const INTERVAL: Duration = Duration::from_millis(200);
+
+    let start = Instant::now();
+    let (telemetry, guard, batches) = spawn_with_policy(FlushPolicy::interval(INTERVAL));
+    telemetry.record("a".into());
+    telemetry.record("b".into());
+    wait_for_events(&batches, 2);
+    // Both events were held back until the interval elapsed, and then
+    // flushed together.
+    assert!(start.elapsed() >= INTERVAL);
+    assert_eq!(*batches.lock().unwrap(), [vec!["a", "b"]]);
+    guard.shutdown().unwrap();
+}
@@ -0,0 +1,281 @@
+#![cfg(feature = "test-util")]
+use std::thread;
+use thingbuf::{
+    mpsc::{self, blocking},
+    recycling::WithCapacity,
+    test_util::{
+        Action, ChannelConfig, Differential, ModelQueue, Op, Outcome, Recorder, ReplayError,
+    },
+};
+
+#[test]
+fn replays_recorded_interleaving() {
+    const PRODUCERS: usize = 3;
+    const MSGS: usize = 50;
+
+    let (tx, rx) = blocking::channel::<usize>(4);
+    let recorder = Recorder::new();
+    let consumer = {
+        let rx = recorder.receiver(rx);
+        thread::spawn(move || {
+            let mut received = Vec::new();
+            while received.len() < PRODUCERS * MSGS {
+                if let Ok(msg) = rx.try_recv() {
+                    received.push(msg);
+                }
+            }
+            received
+        })
+    };
+    let producers = (0..PRODUCERS)
+        .map(|i| {
+            let tx = recorder.sender(tx.clone());
+            thread::spawn(move || {
+                let mut sent = 0;
+                while sent < MSGS {
+                    if tx.try_send(i * MSGS + sent).is_ok() {
+                        sent += 1;
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    let received = consumer.join().unwrap();
+
+    let schedule = recorder.schedule();
+    assert_eq!(schedule.len(), PRODUCERS * MSGS * 2);
+    let recorded_recvs = schedule
+        .ops()
+        .iter()
+        .filter_map(|op| match op {
+            Op::Recv { msg } => Some(*msg),
+            Op::Send { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(recorded_recvs, received);
+
+    // Replaying the schedule reproduces it exactly, every time. All of the
+    // messages are sent by a single sender when replaying.
+    let single_sender = schedule
+        .clone()
+        .into_iter()
+        .map(|op| match op {
+            Op::Send { msg, .. } => Op::Send { sender: 0, msg },
+            op => op,
+        })
+        .collect::<Vec<_>>();
+    for _ in 0..3 {
+        let (tx, rx) = mpsc::channel::<usize>(4);
+        let replay = Recorder::new();
+        let (tx, rx) = (replay.sender(tx), replay.receiver(rx));
+        schedule.replay(&tx, &rx).unwrap();
+        assert_eq!(replay.schedule().ops(), &single_sender[..]);
+    }
+}
+
+#[test]
+fn hashed_schedules_match_cloned_schedules() {
+    let cloned = Recorder::new();
+    let hashed = Recorder::hashing();
+    for recorder in 0..2 {
+        let (tx, rx) = mpsc::channel::<String>(2);
+        if recorder == 0 {
+            let (tx, rx) = (cloned.sender(tx), cloned.receiver(rx));
+            tx.try_send_with(|msg| msg.push_str("hello")).unwrap();
+            tx.try_send("world".to_string()).unwrap();
+            assert_eq!(rx.try_recv_with(|msg| msg.len()), Ok(5));
+        } else {
+            let (tx, rx) = (hashed.sender(tx), hashed.receiver(rx));
+            tx.try_send("hello".to_string()).unwrap();
+            tx.try_send_with(|msg| msg.push_str("world")).unwrap();
+            assert_eq!(rx.try_recv().as_deref(), Ok("hello"));
+        }
+    }
+
+    assert_eq!(
+        cloned.schedule().ops(),
+        &[
+            Op::Send {
+                sender: 0,
+                msg: "hello".to_string()
+            },
+            Op::Send {
+                sender: 0,
+                msg: "world".to_string()
+            },
+            Op::Recv {
+                msg: "hello".to_string()
+            },
+        ]
+    );
+    assert_eq!(cloned.schedule().hashed(), hashed.schedule());
+}
+
+#[test]
+fn replay_reports_divergence() {
+    let schedule = thingbuf::test_util::Schedule::from(vec![
+        Op::Send { sender: 0, msg: 1 },
+        Op::Send { sender: 0, msg: 2 },
+        Op::Recv { msg: 1 },
+        Op::Recv { msg: 2 },
+        Op::Recv { msg: 3 },
+    ]);
+
+    // The replayed channel is smaller than the recorded one.
+    let (tx, rx) = blocking::channel::<u32>(1);
+    match schedule.replay(&tx, &rx) {
+        Err(ReplayError::Send { op: 1, .. }) => {}
+        res => panic!("unexpected replay result: {:?}", res),
+    }
+
+    let (tx, rx) = blocking::channel::<u32>(2);
+    match schedule.replay(&tx, &rx) {
+        Err(ReplayError::Recv { op: 4, .. }) => {}
+        res => panic!("unexpected replay result: {:?}", res),
+    }
+}
+
+#[test]
+fn model_queue_is_bounded_fifo() {
+    let mut q = ModelQueue::new(2);
+    assert_eq!(q.pop(), None);
+    q.push(1).unwrap();
+    assert_eq!(q.push_with(|val| *val = 2), Ok(()));
+    assert!(q.is_full());
+    assert!(q.push(3).is_err());
+    assert_eq!(q.iter().copied().collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(q.pop_with(|val| *val * 10), Some(10));
+    assert_eq!(q.pop(), Some(2));
+    assert!(q.is_empty());
+}
+
+#[test]
+fn differential_wraps_around() {
+    let mut harness = Differential::new(ChannelConfig::new(3));
+    for round in 0..10 {
+        for i in 0..4 {
+            let outcome = harness
+                .apply(Action::Send {
+                    sender: 0,
+                    msg: round * 10 + i,
+                })
+                .unwrap();
+            let expected = if i < 3 {
+                Outcome::Pushed
+            } else {
+                Outcome::Full
+            };
+            assert_eq!(outcome, expected);
+        }
+        for i in 0..3 {
+            assert_eq!(
+                harness.apply(Action::Recv).unwrap(),
+                Outcome::Popped(round * 10 + i)
+            );
+        }
+        assert_eq!(harness.apply(Action::Recv).unwrap(), Outcome::Empty);
+    }
+    assert_eq!(harness.steps(), 80);
+    assert!(harness.real().is_empty());
+}
+
+#[test]
+fn differential_checks_recycling_in_place() {
+    let mut harness = Differential::with_recycle(
+        ChannelConfig::new(2),
+        WithCapacity::new().with_max_capacity(8),
+    );
+    let actions = (0..32).map(|i| {
+        if i % 3 == 2 {
+            Action::Recv
+        } else {
+            Action::Send { sender: 0, msg: i }
+        }
+    });
+    harness
+        .run_with(actions, |slot: &mut Vec<usize>, msg| slot.extend(0..msg))
+        .unwrap();
+}
+
+#[cfg(feature = "proptest")]
+mod strategies {
+    use proptest::prelude::*;
+    use std::collections::VecDeque;
+    use thingbuf::{
+        mpsc::blocking,
+        recycling::WithCapacity,
+        test_util::{strategy, Action, Differential},
+    };
+
+    proptest! {
+        #[test]
+        fn channel_preserves_each_senders_order(
+            (config, actions) in strategy::config_and_actions(any::<u16>(), 0..256),
+        ) {
+            let (tx, rx) = blocking::channel::<(usize, u16)>(config.capacity());
+            let mut in_flight = VecDeque::new();
+            for action in &actions {
+                let sender = config.sender_of(action);
+                prop_assert!(sender.map_or(true, |sender| sender < config.senders()));
+                match *action {
+                    Action::Send { sender, msg } => {
+                        let full = in_flight.len() == config.capacity();
+                        prop_assert_eq!(tx.try_send((sender, msg)).is_err(), full);
+                        if !full {
+                            in_flight.push_back((sender, msg));
+                        }
+                    }
+                    Action::Recv => prop_assert_eq!(rx.try_recv().ok(), in_flight.pop_front()),
+                }
+            }
+        }
+
+        #[test]
+        fn thingbuf_matches_model(
+            (config, actions) in strategy::config_and_actions(any::<u32>(), 0..512),
+        ) {
+            let mut harness = Differential::<u32>::new(config);
+            let res = harness.run(actions);
+            prop_assert!(res.is_ok(), "{}", res.unwrap_err());
+        }
+
+        #[test]
+        fn recycled_strings_match_model(
+            (config, actions) in strategy::config_and_actions("[a-z]{0,8}", 0..512),
+        ) {
+            let mut harness = Differential::with_recycle(config, WithCapacity::new());
+            let res = harness.run_with(actions, |slot: &mut String, msg| slot.push_str(&msg));
+            prop_assert!(res.is_ok(), "{}", res.unwrap_err());
+        }
+
+        #[test]
+        fn arbitrary_actions_use_configured_senders(action in any::<Action<u8>>()) {
+            if let Action::Send { sender, .. } = action {
+                prop_assert!(sender < thingbuf::test_util::ChannelConfig::MAX_GENERATED_SENDERS);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn arbitrary_configs_are_in_range() {
+    use arbitrary::{Arbitrary, Unstructured};
+    use thingbuf::test_util::{Action, ChannelConfig};
+
+    let bytes = (0..=255u8).cycle().take(4096).collect::<Vec<_>>();
+    let mut u = Unstructured::new(&bytes);
+    while !u.is_empty() {
+        let config = ChannelConfig::arbitrary(&mut u).unwrap();
+        assert!((1..=ChannelConfig::MAX_GENERATED_CAPACITY).contains(&config.capacity()));
+        assert!((1..=ChannelConfig::MAX_GENERATED_SENDERS).contains(&config.senders()));
+        if let Action::Send { sender, .. } = Action::<u32>::arbitrary(&mut u).unwrap() {
+            assert!(
+                config.sender_of(&Action::Send { sender, msg: () }).unwrap() < config.senders()
+            );
+        }
+    }
+}
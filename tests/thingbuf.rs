@@ -0,0 +1,1021 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+use thingbuf::{
+    raw,
+    recycling::{self, DefaultRecycle},
+    Injector, Slot, SlotLayout, ThingBuf,
+};
+
+#[test]
+fn into_iter_wraps_around() {
+    let q = ThingBuf::new(4);
+    for i in 0..4 {
+        q.push(i).unwrap();
+    }
+    assert_eq!(q.pop(), Some(0));
+    assert_eq!(q.pop(), Some(1));
+    q.push(4).unwrap();
+    q.push(5).unwrap();
+
+    assert_eq!(q.into_iter().collect::<Vec<_>>(), vec![2, 3, 4, 5]);
+}
+
+#[test]
+fn into_iter_len() {
+    let q = ThingBuf::new(4);
+    for i in 0..3 {
+        q.push(i).unwrap();
+    }
+
+    let mut iter = q.into_iter();
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.size_hint(), (2, Some(2)));
+    iter.by_ref().for_each(drop);
+    assert_eq!(iter.len(), 0);
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn retain_across_laps() {
+    let mut q = ThingBuf::new(4);
+    for i in 0..4 {
+        q.push(i).unwrap();
+    }
+    assert_eq!(q.pop(), Some(0));
+    assert_eq!(q.pop(), Some(1));
+    q.push(4).unwrap();
+    q.push(5).unwrap();
+
+    q.retain(|i| *i != 3 && *i != 4);
+    assert_eq!(q.len(), 2);
+
+    // the freed slots are usable again
+    q.push(6).unwrap();
+    q.push(7).unwrap();
+    assert!(q.push(8).is_err());
+
+    assert_eq!(q.into_iter().collect::<Vec<_>>(), vec![2, 5, 6, 7]);
+}
+
+#[test]
+fn retain_drops_removed_strings() {
+    let mut q = ThingBuf::<String>::new(4);
+    for s in ["a", "b", "c"] {
+        q.push(s.to_string()).unwrap();
+    }
+    q.retain(|s| s != "a");
+    q.push("d".to_string()).unwrap();
+    q.push("e".to_string()).unwrap();
+
+    assert_eq!(q.into_iter().collect::<Vec<_>>(), vec!["b", "c", "d", "e"]);
+}
+
+#[test]
+fn swap_double_buffered_flush() {
+    use std::sync::RwLock;
+
+    const PRODUCERS: usize = 4;
+    const PER_PRODUCER: usize = 1000;
+
+    let q = Arc::new(RwLock::new(ThingBuf::new(64)));
+    let producers = (0..PRODUCERS)
+        .map(|p| {
+            let q = q.clone();
+            thread::spawn(move || {
+                for i in 0..PER_PRODUCER {
+                    let mut msg = p * PER_PRODUCER + i;
+                    // Retry outside the lock, so the flusher can swap in an
+                    // empty buffer while the queue is full.
+                    while let Err(full) = q.read().unwrap().push(msg) {
+                        msg = full.into_inner();
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut flushed = Vec::new();
+    let mut spare = ThingBuf::new(64);
+    while flushed.len() < PRODUCERS * PER_PRODUCER {
+        spare = q.write().unwrap().swap(spare);
+        while let Some(msg) = spare.pop() {
+            flushed.push(msg);
+        }
+        thread::yield_now();
+    }
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    flushed.sort_unstable();
+    assert_eq!(flushed, (0..PRODUCERS * PER_PRODUCER).collect::<Vec<_>>());
+}
+
+#[test]
+fn swap_replaces_contents_and_capacity() {
+    let mut q = ThingBuf::new(4);
+    q.push(1).unwrap();
+    q.push(2).unwrap();
+    let replacement = ThingBuf::new(8);
+    replacement.push(3).unwrap();
+
+    let old = q.swap(replacement);
+    assert_eq!(q.capacity(), 8);
+    assert_eq!(old.capacity(), 4);
+
+    // the replacement keeps its elements, and has room for 7 more.
+    for i in 4..11 {
+        q.push(i).unwrap();
+    }
+    assert!(q.push(11).is_err());
+    assert_eq!(
+        q.into_iter().collect::<Vec<_>>(),
+        vec![3, 4, 5, 6, 7, 8, 9, 10]
+    );
+    assert_eq!(old.into_iter().collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[test]
+#[should_panic]
+fn with_placement_wrong_length() {
+    let _q =
+        ThingBuf::<usize>::with_placement(4, DefaultRecycle::new(), |_| Slot::make_boxed_array(2));
+}
+
+#[test]
+#[should_panic]
+fn with_placement_used_slots() {
+    let mut core = raw::Core::new(4);
+    let mut slots = Slot::<usize>::make_boxed_array(4);
+    unsafe {
+        *core.push_ref(&slots, &DefaultRecycle::new()).unwrap() = 1;
+        core.drop_slots(&mut slots);
+    }
+
+    let _q = ThingBuf::<usize>::with_placement(4, DefaultRecycle::new(), move |_| slots);
+}
+
+#[test]
+fn builder_with_recycle() {
+    let q = ThingBuf::builder(4)
+        .recycle(recycling::WithCapacity::new().with_max_capacity(8))
+        .align(64)
+        .huge_pages(true)
+        .build();
+    q.push(String::from("hello")).unwrap();
+    q.push(String::from("world")).unwrap();
+    assert_eq!(q.pop().as_deref(), Some("hello"));
+    assert_eq!(q.into_iter().collect::<Vec<_>>(), vec!["world"]);
+}
+
+#[test]
+fn pre_fill_initializes_every_slot() {
+    /// Hands out clones of the `Arc` it holds, so that the `Arc`'s strong
+    /// count tracks the number of elements that are alive.
+    struct CountingRecycle(Arc<()>);
+
+    impl recycling::Recycle<Arc<()>> for CountingRecycle {
+        fn new_element(&self) -> Arc<()> {
+            self.0.clone()
+        }
+
+        fn recycle(&self, _: &mut Arc<()>) {}
+    }
+
+    let live = Arc::new(());
+    let mut q = ThingBuf::with_recycle(4, CountingRecycle(live.clone()));
+    q.push_ref().unwrap();
+    assert_eq!(Arc::strong_count(&live), 3);
+
+    q.pre_fill();
+    assert_eq!(Arc::strong_count(&live), 6);
+    // pre-filling again does nothing
+    q.pre_fill();
+    assert_eq!(Arc::strong_count(&live), 6);
+
+    // no more elements are created on the first lap, or the ones after it
+    for _ in 0..10 {
+        drop(q.push_ref().unwrap());
+        drop(q.pop_ref().unwrap());
+    }
+    q.retain(|_| false);
+    assert_eq!(Arc::strong_count(&live), 6);
+
+    drop(q);
+    assert_eq!(Arc::strong_count(&live), 1);
+
+    // an empty queue built with pre-filling drops every slot
+    let q = ThingBuf::builder(8)
+        .recycle(CountingRecycle(live.clone()))
+        .layout(SlotLayout::Split)
+        .pre_fill(true)
+        .build();
+    assert_eq!(Arc::strong_count(&live), 10);
+    drop(q);
+    assert_eq!(Arc::strong_count(&live), 1);
+}
+
+#[test]
+fn split_layout_wraps_around() {
+    let q = ThingBuf::<String>::builder(3)
+        .layout(SlotLayout::Split)
+        .align(64)
+        .build();
+    for i in 0..10 {
+        q.push(i.to_string()).unwrap();
+        q.push_with(|s| s.push('!')).unwrap();
+        assert_eq!(q.pop().as_deref(), Some(i.to_string().as_str()));
+        assert_eq!(q.pop().as_deref(), Some("!"));
+    }
+    assert!(q.is_empty());
+}
+
+#[test]
+fn split_layout_retains_and_drops_elements() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    #[derive(Clone)]
+    struct Counted(usize, Arc<AtomicUsize>);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.1.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let mut q = ThingBuf::builder(4).layout(SlotLayout::Split).build();
+    for i in 0..4 {
+        q.push(Some(Counted(i, drops.clone()))).unwrap();
+    }
+    q.retain(|elem| elem.as_ref().map_or(false, |elem| elem.0 % 2 == 0));
+    assert_eq!(q.len(), 2);
+    let mut kept = Vec::new();
+    while let Some(elem) = q.pop_ref() {
+        kept.push((*elem).as_ref().unwrap().0);
+    }
+    assert_eq!(kept, vec![0, 2]);
+
+    drop(q);
+    assert_eq!(drops.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn split_layout_zero_sized() {
+    let q = ThingBuf::<()>::builder(2).layout(SlotLayout::Split).build();
+    q.push(()).unwrap();
+    q.push(()).unwrap();
+    assert!(q.push(()).is_err());
+    assert_eq!(q.pop(), Some(()));
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn occupancy_histogram() {
+    let q = ThingBuf::new(16);
+    // fill the queue, then drain it.
+    for i in 0..16 {
+        q.push(i).unwrap();
+    }
+    while q.pop().is_some() {}
+
+    let stats = q.stats();
+    let occupancy = stats.occupancy();
+    assert_eq!(occupancy.capacity(), 16);
+    assert_eq!(occupancy.samples(), 32);
+    // depths 1..=16 were each sampled once while pushing, and 15..=0 while
+    // popping, so there are 4 samples of each pair of depths, except for the
+    // lowest and highest buckets.
+    assert_eq!(occupancy.counts(), &[3, 4, 4, 4, 4, 4, 4, 5]);
+
+    let buckets = occupancy.buckets().collect::<Vec<_>>();
+    assert_eq!(buckets[0], (0..=1, 3));
+    assert_eq!(buckets[7], (14..=16, 5));
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn slot_states_track_claims() {
+    use thingbuf::stats::SlotState;
+
+    let q = ThingBuf::new(4);
+    assert_eq!(q.slot_states().to_string(), "....");
+
+    for i in 0..4 {
+        q.push(i).unwrap();
+    }
+    let reading = q.pop_ref().unwrap();
+    assert_eq!(q.pop(), Some(1));
+    // The push skips the slot that's still being read, and claims the next.
+    let writing = q.push_ref().unwrap();
+
+    let states = q.slot_states();
+    assert_eq!(states.to_string(), "~~##");
+    assert_eq!(states.count(SlotState::Claimed), 2);
+    assert_eq!(states.count(SlotState::Occupied), 2);
+    assert_eq!(states.count(SlotState::Empty), 0);
+    assert_eq!(states.get(4), None);
+
+    drop(reading);
+    drop(writing);
+    let states = q.slot_states();
+    assert_eq!(states.to_string(), ".###");
+    assert_eq!(
+        states.iter().collect::<Vec<_>>(),
+        [
+            SlotState::Empty,
+            SlotState::Occupied,
+            SlotState::Occupied,
+            SlotState::Occupied,
+        ]
+    );
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn recycling_stats_count_fresh_and_reused() {
+    let recycle = recycling::WithCapacity::new()
+        .with_min_capacity(4)
+        .with_max_capacity(16);
+    let mut q = ThingBuf::<Vec<u8>, _>::with_recycle(4, recycle.clone());
+    q.push_ref().unwrap().extend_from_slice(&[0; 64]);
+    q.push_ref().unwrap();
+    drop(q.pop_ref());
+    drop(q.pop_ref());
+
+    let recycling = q.stats().recycling().clone();
+    assert_eq!(recycling.fresh(), 2);
+    assert_eq!(recycling.reused(), 0);
+    assert_eq!(recycling.shed_capacity(), 0);
+
+    // the remaining slots are pre-filled without being counted, and every
+    // push after that reuses an element.
+    q.pre_fill();
+    for _ in 0..4 {
+        drop(q.push_ref().unwrap());
+        drop(q.pop_ref().unwrap());
+    }
+    let recycling = q.stats().recycling().clone();
+    assert_eq!(recycling.fresh(), 2);
+    assert_eq!(recycling.reused(), 4);
+    // the first element's capacity was shrunk to the maximum
+    assert!(recycling.shed_capacity() >= 64 - 16);
+
+    // popping by value takes the element, which leaves a new one behind to be
+    // recycled.
+    let q = ThingBuf::<String, _>::with_recycle(1, recycle);
+    for _ in 0..3 {
+        q.push("hello".to_string()).unwrap();
+        assert_eq!(q.pop().as_deref(), Some("hello"));
+    }
+    let recycling = q.stats().recycling().clone();
+    assert_eq!(recycling.fresh(), 1);
+    assert_eq!(recycling.reused(), 2);
+}
+
+#[test]
+fn ref_comparisons() {
+    let q = ThingBuf::<String>::new(4);
+
+    let mut slot = q.push_ref().unwrap();
+    slot.push_str("hello");
+    assert_eq!(slot, String::from("hello"));
+    slot.as_mut().push_str(" world");
+    drop(slot);
+
+    let slot = q.pop_ref().unwrap();
+    assert!(slot == String::from("hello world"));
+    assert_eq!(slot.as_ref().len(), 11);
+    assert_eq!(slot.to_string(), "hello world");
+}
+
+#[test]
+fn injector_steal_batches() {
+    const PRODUCERS: usize = 4;
+    const PER_PRODUCER: usize = 1000;
+
+    let injector = Arc::new(Injector::new(64));
+    let producers = (0..PRODUCERS)
+        .map(|p| {
+            let injector = injector.clone();
+            thread::spawn(move || {
+                for i in 0..PER_PRODUCER {
+                    let mut task = p * PER_PRODUCER + i;
+                    while let Err(full) = injector.push(task) {
+                        task = full.into_inner();
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let workers = (0..2)
+        .map(|_| {
+            let injector = injector.clone();
+            thread::spawn(move || {
+                let mut local = Vec::new();
+                let mut stolen = Vec::new();
+                while Arc::strong_count(&injector) > 3 || !injector.is_empty() {
+                    let n = injector.steal_batch_into(&mut local, 8);
+                    assert!(n <= 8);
+                    // each batch preserves the order of each producer's tasks
+                    for pair in local.windows(2) {
+                        if pair[0] / PER_PRODUCER == pair[1] / PER_PRODUCER {
+                            assert!(pair[0] < pair[1], "batch out of order: {:?}", local);
+                        }
+                    }
+                    stolen.append(&mut local);
+                    if n == 0 {
+                        thread::yield_now();
+                    }
+                }
+                stolen
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    drop(injector);
+    let mut all = workers
+        .into_iter()
+        .flat_map(|worker| worker.join().unwrap())
+        .collect::<Vec<_>>();
+    all.sort_unstable();
+    assert_eq!(all, (0..PRODUCERS * PER_PRODUCER).collect::<Vec<_>>());
+}
+
+#[test]
+fn work_queue_aging_prevents_starvation() {
+    use thingbuf::work_queue::WorkQueue;
+
+    let q = WorkQueue::new(3, 64);
+    q.set_aging(Some(4));
+    q.push(2, 200).unwrap();
+    q.push(1, 100).unwrap();
+    for i in 0..32 {
+        q.push(0, i).unwrap();
+    }
+
+    let order = std::iter::from_fn(|| q.pop()).collect::<Vec<_>>();
+    assert_eq!(order.len(), 34);
+    let pos = |val| order.iter().position(|&v| v == val).unwrap();
+    assert!(pos(100) <= 4, "level 1 starved: {:?}", order);
+    assert!(pos(200) <= 8, "level 2 starved: {:?}", order);
+
+    // without aging, items come out strictly by priority
+    q.set_aging(None);
+    q.push(1, 100).unwrap();
+    for i in 0..8 {
+        q.push(0, i).unwrap();
+    }
+    assert_eq!(
+        std::iter::from_fn(|| q.pop()).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4, 5, 6, 7, 100]
+    );
+}
+
+#[test]
+fn iter_snapshot_live() {
+    let q = Arc::new(ThingBuf::new(32));
+    let done = Arc::new(AtomicUsize::new(0));
+    let producer = thread::spawn({
+        let done = done.clone();
+        let q = q.clone();
+        move || {
+            for i in 0..10_000usize {
+                while q.push(i).is_err() {
+                    thread::yield_now();
+                }
+            }
+            done.fetch_add(1, Ordering::Release);
+        }
+    });
+    let consumer = thread::spawn({
+        let done = done.clone();
+        let q = q.clone();
+        move || {
+            let mut next = 0;
+            while next < 10_000 {
+                if let Some(val) = q.pop() {
+                    assert_eq!(val, next);
+                    next += 1;
+                }
+            }
+            done.fetch_add(1, Ordering::Release);
+        }
+    });
+
+    while done.load(Ordering::Acquire) < 2 {
+        let snapshot = q.iter_snapshot().collect::<Vec<usize>>();
+        assert!(snapshot.len() <= 32);
+        // a snapshot never yields an element twice or out of order
+        for pair in snapshot.windows(2) {
+            assert!(pair[0] < pair[1], "snapshot out of order: {:?}", snapshot);
+        }
+    }
+
+    producer.join().unwrap();
+    consumer.join().unwrap();
+}
+
+#[test]
+fn iter_snapshot_skips_head() {
+    let q = ThingBuf::new(4);
+    q.push(1).unwrap();
+    assert_eq!(q.iter_snapshot().count(), 0);
+    q.push(2).unwrap();
+    q.push(3).unwrap();
+    assert_eq!(q.iter_snapshot().collect::<Vec<_>>(), vec![2, 3]);
+
+    // Copied elements are still in the queue.
+    for i in 1..=3 {
+        assert_eq!(q.pop(), Some(i));
+    }
+}
+
+#[test]
+fn blocking_push_and_pop() {
+    use std::time::Duration;
+
+    const N: usize = 100;
+    let q = Arc::new(ThingBuf::<usize>::new(4));
+
+    // nothing has been pushed, so popping times out.
+    assert!(q.pop_ref_timeout(Duration::from_millis(5)).is_none());
+
+    let producers = (0..2)
+        .map(|p| {
+            let q = q.clone();
+            thread::spawn(move || {
+                for i in 0..N {
+                    q.push_blocking(p * N + i);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut received = (0..N * 2).map(|_| q.pop_blocking()).collect::<Vec<_>>();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    received.sort_unstable();
+    assert_eq!(received, (0..N * 2).collect::<Vec<_>>());
+
+    // once the queue is full, pushing times out.
+    for i in 0..4 {
+        *q.push_ref_timeout(Duration::from_millis(5)).unwrap() = i;
+    }
+    assert!(q.push_ref_timeout(Duration::from_millis(5)).is_err());
+    assert_eq!(*q.pop_ref_blocking(), 0);
+}
+
+#[test]
+fn blocking_pop_sees_push_started_before_it_blocked() {
+    use std::time::Duration;
+
+    let q = Arc::new(ThingBuf::<usize>::new(4));
+
+    // this push starts before any thread has blocked on the queue.
+    let mut slot = q.push_ref().unwrap();
+    let popper = {
+        let q = q.clone();
+        thread::spawn(move || q.pop_blocking())
+    };
+    thread::sleep(Duration::from_millis(50));
+    *slot = 1;
+    drop(slot);
+    assert_eq!(popper.join().unwrap(), 1);
+
+    // once a thread has blocked, non-blocking pushes wake blocked threads.
+    let popper = {
+        let q = q.clone();
+        thread::spawn(move || q.pop_blocking())
+    };
+    thread::sleep(Duration::from_millis(50));
+    q.push(2).unwrap();
+    assert_eq!(popper.join().unwrap(), 2);
+}
+
+#[test]
+fn batch_pops_wake_blocked_pushers() {
+    use std::time::Duration;
+
+    let q = Arc::new(ThingBuf::<usize>::new(2));
+    q.push(0).unwrap();
+    q.push(1).unwrap();
+
+    let pushers = (2..4)
+        .map(|i| {
+            let q = q.clone();
+            thread::spawn(move || q.push_blocking(i))
+        })
+        .collect::<Vec<_>>();
+
+    // let both pushers park before freeing their slots.
+    thread::sleep(Duration::from_millis(50));
+    let mut out = [0; 2];
+    assert_eq!(q.pop_into(&mut out), 2);
+    assert_eq!(out, [0, 1]);
+    for pusher in pushers {
+        pusher.join().unwrap();
+    }
+
+    let blocked = {
+        let q = q.clone();
+        thread::spawn(move || {
+            q.push_ref_timeout(Duration::from_secs(10))
+                .map(|mut slot| *slot = 4)
+                .is_ok()
+        })
+    };
+    thread::sleep(Duration::from_millis(50));
+    let mut all = Vec::new();
+    assert_eq!(q.pop_all(&mut all), 2);
+    all.sort_unstable();
+    assert_eq!(all, [2, 3]);
+    assert!(blocked.join().unwrap());
+    assert_eq!(
+        q.pop_ref_timeout(Duration::from_secs(10)).map(|slot| *slot),
+        Some(4)
+    );
+}
+
+#[test]
+fn pop_into_wraps_around() {
+    let q = ThingBuf::new(4);
+    let mut out = [0; 8];
+    for round in 0..10 {
+        for i in 0..3 {
+            q.push(round * 10 + i).unwrap();
+        }
+        assert_eq!(q.pop_into(&mut out), 3);
+        assert_eq!(&out[..3], &[round * 10, round * 10 + 1, round * 10 + 2]);
+        assert!(q.is_empty());
+    }
+    assert_eq!(q.pop_into(&mut out), 0);
+}
+
+#[test]
+fn pop_all_wraps_around() {
+    let q = ThingBuf::new(4);
+    let mut out = Vec::new();
+    for round in 0..10 {
+        for i in 0..3 {
+            q.push(round * 10 + i).unwrap();
+        }
+        assert_eq!(q.pop_all(&mut out), 3);
+        assert!(q.is_empty());
+    }
+    assert_eq!(q.pop_all(&mut out), 0);
+    let expected = (0..10)
+        .flat_map(|round| (0..3).map(move |i| round * 10 + i))
+        .collect::<Vec<_>>();
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn pop_all_stops_at_slot_being_written() {
+    let q = ThingBuf::new(4);
+    q.push(1).unwrap();
+    let writing = q.push_ref().unwrap();
+    q.push(3).unwrap();
+
+    let mut out = Vec::new();
+    assert_eq!(q.pop_all(&mut out), 1);
+    assert_eq!(out, [1]);
+
+    drop(writing);
+    assert_eq!(q.pop_all(&mut out), 2);
+    assert_eq!(out, [1, 0, 3]);
+}
+
+#[test]
+fn pop_all_releases_slots_on_panic() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    /// Panics the `n`th time it creates an element.
+    struct PanicOn(AtomicUsize);
+
+    impl recycling::Recycle<usize> for PanicOn {
+        fn new_element(&self) -> usize {
+            let n = self.0.fetch_sub(1, Ordering::SeqCst);
+            assert_ne!(n, 1, "new_element panicked");
+            0
+        }
+
+        fn recycle(&self, _: &mut usize) {}
+    }
+
+    // pushing 3 elements creates 3 elements, and taking the second one out
+    // of its slot creates the 5th.
+    let q = ThingBuf::with_recycle(4, PanicOn(AtomicUsize::new(5)));
+    for i in 1..=3 {
+        q.push(i).unwrap();
+    }
+    let mut out = Vec::new();
+    let res = panic::catch_unwind(AssertUnwindSafe(|| q.pop_all(&mut out)));
+    assert!(res.is_err());
+    assert_eq!(out, [1]);
+
+    // the rest of the batch was discarded, and every slot can be reused.
+    assert!(q.is_empty());
+    for i in 4..8 {
+        q.push(i).unwrap();
+    }
+    assert_eq!(q.pop_all(&mut out), 4);
+    assert_eq!(out, [1, 4, 5, 6, 7]);
+}
+
+#[test]
+fn freeze_newest_wraps_around() {
+    let q = ThingBuf::new(4);
+    for round in 0..10 {
+        for i in 0..3 {
+            q.push(round * 10 + i).unwrap();
+        }
+        let frozen = q.freeze();
+        assert_eq!(frozen.size_hint(), (0, Some(3)));
+        let newest = frozen.newest(2).collect::<Vec<_>>();
+        assert_eq!(newest, vec![round * 10 + 1, round * 10 + 2]);
+        // Asking for more elements than there are returns all of them, but
+        // the one at the head of the queue.
+        assert_eq!(q.freeze().newest(8).count(), 2);
+
+        // Frozen elements are still in the queue.
+        for i in 0..3 {
+            assert_eq!(q.pop(), Some(round * 10 + i));
+        }
+    }
+}
+
+#[test]
+fn freeze_does_not_block_pops() {
+    let q = ThingBuf::new(4);
+    q.push(1).unwrap();
+    q.push(2).unwrap();
+    q.push(3).unwrap();
+
+    // The element at the head of the queue is skipped.
+    let mut frozen = q.freeze();
+    assert_eq!(frozen.next(), Some(2));
+    // Reading an element leaves it in the queue, and doesn't stop it from
+    // being popped.
+    assert_eq!(q.pop(), Some(1));
+    assert_eq!(q.pop(), Some(2));
+
+    // An element popped before the view reaches it is skipped.
+    assert_eq!(q.pop(), Some(3));
+    assert!(frozen.next().is_none());
+}
+
+#[test]
+fn freeze_while_popping() {
+    let q = Arc::new(ThingBuf::new(8));
+    let done = Arc::new(AtomicUsize::new(0));
+    let producer = thread::spawn({
+        let done = done.clone();
+        let q = q.clone();
+        move || {
+            for i in 0..10_000usize {
+                while q.push((i, i * 2)).is_err() {
+                    thread::yield_now();
+                }
+            }
+            done.fetch_add(1, Ordering::Release);
+        }
+    });
+    let consumer = thread::spawn({
+        let done = done.clone();
+        let q = q.clone();
+        move || {
+            let mut next = 0;
+            while next < 10_000 {
+                if let Some(val) = q.pop() {
+                    assert_eq!(val, (next, next * 2));
+                    next += 1;
+                }
+            }
+            done.fetch_add(1, Ordering::Release);
+        }
+    });
+
+    while done.load(Ordering::Acquire) < 2 {
+        let frozen = q.freeze().collect::<Vec<_>>();
+        assert!(frozen.len() <= 8);
+        for pair in frozen.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "frozen out of order: {:?}", frozen);
+        }
+        // a copy is never torn.
+        for &(i, double) in &frozen {
+            assert_eq!(i * 2, double, "torn element in {:?}", frozen);
+        }
+    }
+
+    producer.join().unwrap();
+    consumer.join().unwrap();
+}
+
+#[test]
+fn cache_padded_slots_do_not_share_lines() {
+    use std::mem;
+    use thingbuf::CachePadded;
+
+    assert!(mem::align_of::<Slot<CachePadded<u8>>>() >= 64);
+    assert_eq!(mem::size_of::<Slot<CachePadded<u8>>>() % 64, 0);
+
+    let q = ThingBuf::<CachePadded<usize>>::new(4);
+    for i in 0..10 {
+        q.push(CachePadded::new(i)).unwrap();
+        assert_eq!(*q.pop().unwrap(), i);
+    }
+}
+
+#[test]
+fn cache_padded_recycles_inner_policy() {
+    use std::fmt::Write;
+    use thingbuf::{recycling::WithCapacity, CachePadded};
+
+    let recycle = CachePadded::new(WithCapacity::new().with_min_capacity(16));
+    let q = ThingBuf::<CachePadded<String>, _>::with_recycle(2, recycle);
+    q.push_with(|s| {
+        assert!(s.capacity() >= 16);
+        write!(s, "hello").unwrap();
+    })
+    .unwrap();
+    let popped = q.pop_ref().unwrap();
+    assert_eq!(popped.as_str(), "hello");
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn par_extend_applies_backpressure() {
+    use rayon::prelude::*;
+
+    const N: usize = 10_000;
+    let q = Arc::new(ThingBuf::new(8));
+    let popped = Arc::new(AtomicUsize::new(0));
+    let consumers = (0..2)
+        .map(|_| {
+            let q = q.clone();
+            let popped = popped.clone();
+            thread::spawn(move || {
+                let mut received = Vec::new();
+                while popped.load(Ordering::SeqCst) < N {
+                    if let Some(i) = q.pop_ref_timeout(std::time::Duration::from_millis(1)) {
+                        assert!(q.len() <= q.capacity());
+                        received.push(*i);
+                        popped.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+                received
+            })
+        })
+        .collect::<Vec<_>>();
+
+    q.par_extend((0..N).into_par_iter());
+
+    let mut received = consumers
+        .into_iter()
+        .flat_map(|consumer| consumer.join().unwrap())
+        .collect::<Vec<_>>();
+    received.sort_unstable();
+    assert_eq!(received, (0..N).collect::<Vec<_>>());
+}
+
+#[test]
+fn split_halves_wrap_around() {
+    const N: usize = 10_000;
+    let q = ThingBuf::new(4);
+    // Elements pushed before splitting are popped by the consumer.
+    q.push(0).unwrap();
+    let (producer, consumer) = q.split();
+    assert_eq!(producer.len(), 1);
+
+    let t = thread::spawn(move || {
+        for i in 1..N {
+            while producer.push(i).is_err() {
+                thread::yield_now();
+            }
+        }
+    });
+
+    let mut received = Vec::with_capacity(N);
+    while received.len() < N {
+        match consumer.pop() {
+            Some(i) => received.push(i),
+            None => thread::yield_now(),
+        }
+    }
+
+    t.join().unwrap();
+    assert_eq!(received, (0..N).collect::<Vec<_>>());
+    assert!(consumer.is_empty());
+}
+
+#[test]
+fn split_producer_skips_slot_being_read() {
+    let (producer, consumer) = ThingBuf::new(3).split();
+    for i in 1..=3 {
+        producer.push(i).unwrap();
+    }
+
+    // While the first slot is still being read, the producer skips over it,
+    // and pushes to the next free slot instead.
+    let first = consumer.pop_ref().unwrap();
+    assert_eq!(*first, 1);
+    assert_eq!(consumer.pop(), Some(2));
+    producer.push(4).unwrap();
+    drop(first);
+
+    assert_eq!(consumer.pop(), Some(3));
+    assert_eq!(consumer.pop(), Some(4));
+    assert_eq!(consumer.pop(), None);
+
+    producer.push(5).unwrap();
+    assert_eq!(consumer.pop(), Some(5));
+}
+
+#[cfg(feature = "high-integrity")]
+mod integrity {
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    };
+    use thingbuf::{integrity, ThingBuf};
+
+    // Elements whose contents can be changed while they are in the queue,
+    // standing in for memory that is corrupted.
+    type Cell = Arc<AtomicU64>;
+
+    fn checksum(cell: &Cell) -> u64 {
+        cell.load(Ordering::SeqCst)
+    }
+
+    fn corrupted_queue() -> ThingBuf<Cell> {
+        let q = ThingBuf::<Cell>::builder(4).checksum(checksum).build();
+        let cell = Arc::new(AtomicU64::new(1));
+        q.push(cell.clone()).unwrap();
+        cell.store(2, Ordering::SeqCst);
+        q
+    }
+
+    #[test]
+    #[should_panic(expected = "failed its integrity check")]
+    fn pop_detects_corruption() {
+        let _ = corrupted_queue().pop();
+    }
+
+    #[test]
+    #[should_panic(expected = "failed its integrity check")]
+    fn pop_all_detects_corruption() {
+        let _ = corrupted_queue().pop_all(&mut Vec::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "failed its integrity check")]
+    fn split_consumer_detects_corruption() {
+        let (_producer, consumer) = corrupted_queue().split();
+        let _ = consumer.pop();
+    }
+
+    #[test]
+    fn bytes_checksum_round_trips() {
+        let q = ThingBuf::<Vec<u8>>::builder(4)
+            .checksum(integrity::bytes)
+            .build();
+        for round in 0..3u8 {
+            q.push_with(|buf| buf.extend_from_slice(&[round; 16]))
+                .unwrap();
+            // Mutating a popped element doesn't trip the check.
+            let mut buf = q.pop_ref().unwrap();
+            assert_eq!(*buf, [round; 16]);
+            buf.push(0);
+        }
+    }
+
+    #[test]
+    fn retain_restamps_checksums() {
+        let mut q = ThingBuf::<Vec<u8>>::builder(4)
+            .checksum(integrity::bytes)
+            .build();
+        for i in 0..4 {
+            q.push(vec![i]).unwrap();
+        }
+        q.retain(|buf| {
+            buf[0] *= 10;
+            buf[0] != 10
+        });
+        assert_eq!(q.pop(), Some(vec![0]));
+        assert_eq!(q.pop(), Some(vec![20]));
+        assert_eq!(q.pop(), Some(vec![30]));
+        assert_eq!(q.pop(), None);
+    }
+}
@@ -0,0 +1,212 @@
+use std::{sync::Arc, thread, time::Duration};
+use thingbuf::ThingStack;
+
+#[test]
+fn lifo_order() {
+    let stack = ThingStack::new(4);
+    for i in 0..4 {
+        stack.push(i).unwrap();
+    }
+    assert_eq!(stack.push(4).unwrap_err().into_inner(), 4);
+    assert_eq!(stack.len(), 4);
+
+    assert_eq!(stack.pop(), Some(3));
+    stack.push(5).unwrap();
+    assert_eq!(stack.pop(), Some(5));
+    assert_eq!(stack.pop(), Some(2));
+    assert_eq!(stack.pop(), Some(1));
+    assert_eq!(stack.pop(), Some(0));
+    assert_eq!(stack.pop(), None);
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn reuses_most_recent_allocation() {
+    let stack = ThingStack::<Vec<u8>>::new(4);
+    stack.push(Vec::with_capacity(16)).unwrap();
+    stack.push(Vec::with_capacity(64)).unwrap();
+    drop(stack.pop_ref());
+    drop(stack.pop_ref());
+
+    // the slot that was freed last is reused first, and keeps its
+    // allocation.
+    let slot = stack.push_ref().unwrap();
+    assert!(slot.is_empty());
+    assert_eq!(slot.capacity(), 16);
+}
+
+#[test]
+fn concurrent_push_pop() {
+    const THREADS: usize = 4;
+    const N: usize = 1000;
+
+    let stack = Arc::new(ThingStack::<usize>::new(8));
+    let threads = (0..THREADS)
+        .map(|t| {
+            let stack = stack.clone();
+            thread::spawn(move || {
+                let mut popped = Vec::new();
+                for i in 0..N {
+                    while stack.push(t * N + i).is_err() {
+                        if let Some(val) = stack.pop() {
+                            popped.push(val);
+                        }
+                    }
+                }
+                popped
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut all = Vec::new();
+    for thread in threads {
+        all.extend(thread.join().unwrap());
+    }
+    while let Some(val) = stack.pop() {
+        all.push(val);
+    }
+    all.sort_unstable();
+    assert_eq!(all, (0..THREADS * N).collect::<Vec<_>>());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pop_ref_async_waits_for_push() {
+    const TASKS: usize = 16;
+    const ROUNDS: usize = 100;
+
+    // A pool of 2 objects shared by more tasks than that.
+    let pool = Arc::new(ThingStack::<Vec<usize>>::new(2));
+    pool.push(Vec::new()).unwrap();
+    pool.push(Vec::new()).unwrap();
+
+    let tasks = (0..TASKS)
+        .map(|t| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                for i in 0..ROUNDS {
+                    let mut buf = std::mem::take(&mut *pool.pop_ref_async().await);
+                    buf.push(t * ROUNDS + i);
+                    tokio::task::yield_now().await;
+                    pool.push(buf).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let mut all = Vec::new();
+    while let Some(buf) = pool.pop() {
+        all.extend(buf);
+    }
+    all.sort_unstable();
+    assert_eq!(all, (0..TASKS * ROUNDS).collect::<Vec<_>>());
+}
+
+#[test]
+fn pop_ref_blocking_waits_for_push() {
+    const THREADS: usize = 4;
+    const ROUNDS: usize = 100;
+
+    let pool = Arc::new(ThingStack::<usize>::new(1));
+    pool.push(0).unwrap();
+
+    let threads = (0..THREADS)
+        .map(|_| {
+            let pool = pool.clone();
+            thread::spawn(move || {
+                for _ in 0..ROUNDS {
+                    let val = *pool.pop_ref_blocking();
+                    pool.push(val + 1).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(pool.pop(), Some(THREADS * ROUNDS));
+}
+
+#[test]
+fn pop_ref_timeout() {
+    let pool = Arc::new(ThingStack::<usize>::new(1));
+    assert!(pool.pop_ref_timeout(Duration::from_millis(10)).is_none());
+
+    let t = {
+        let pool = pool.clone();
+        thread::spawn(move || {
+            pool.pop_ref_timeout(Duration::from_secs(10))
+                .map(|val| *val)
+        })
+    };
+    thread::sleep(Duration::from_millis(10));
+    pool.push(1).unwrap();
+    assert_eq!(t.join().unwrap(), Some(1));
+
+    // the timed out waiters left nothing behind in the wait queue
+    pool.push(2).unwrap();
+    assert_eq!(
+        pool.pop_ref_timeout(Duration::from_millis(10))
+            .map(|val| *val),
+        Some(2)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_returns_object_on_drop() {
+    const TASKS: usize = 16;
+    const ROUNDS: usize = 100;
+
+    // A pool of 2 counters shared by more tasks than that.
+    let pool = Arc::new(ThingStack::<usize>::new(2));
+    pool.push(0).unwrap();
+    pool.push(0).unwrap();
+
+    let tasks = (0..TASKS)
+        .map(|_| {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                for _ in 0..ROUNDS {
+                    let mut counter = pool.get().await;
+                    // While this task holds one of the objects, the pool holds
+                    // at most the other one.
+                    assert!(pool.len() <= 1);
+                    *counter += 1;
+                    tokio::task::yield_now().await;
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    // Both objects were returned to the pool.
+    assert_eq!(pool.len(), 2);
+}
+
+#[test]
+fn get_timeout() {
+    let pool = Arc::new(ThingStack::<Vec<u8>>::new(1));
+    pool.push(b"hello".to_vec()).unwrap();
+
+    let buf = pool.get_timeout(Duration::from_millis(10)).unwrap();
+    assert_eq!(&buf[..], b"hello");
+    assert!(pool.get_timeout(Duration::from_millis(10)).is_none());
+
+    let t = {
+        let pool = pool.clone();
+        thread::spawn(move || {
+            pool.get_timeout(Duration::from_secs(10))
+                .map(|buf| buf.len())
+        })
+    };
+    thread::sleep(Duration::from_millis(10));
+    drop(buf);
+    // The returned buffer was recycled.
+    assert_eq!(t.join().unwrap(), Some(0));
+    assert_eq!(pool.len(), 1);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "static")]
+use std::time::Duration;
+use thingbuf::timer::Wheel;
+
+#[test]
+fn timers_span_rotations() {
+    static WHEEL: Wheel<usize, 4, 8> = Wheel::new(Duration::from_millis(1));
+
+    // every delay from 1 to 12 ticks, so some timers wrap around the wheel
+    // several times before expiring.
+    for ticks in 1..=12 {
+        WHEEL
+            .insert_after(Duration::from_millis(ticks as u64), ticks)
+            .unwrap();
+    }
+    assert_eq!(WHEEL.pending(), 12);
+
+    for now in 1..=12 {
+        assert_eq!(WHEEL.tick(), 1, "tick {}", now);
+        assert_eq!(WHEEL.pop_expired(), Some(now));
+        assert_eq!(WHEEL.pop_expired(), None);
+    }
+    assert_eq!(WHEEL.pending(), 0);
+    assert_eq!(WHEEL.elapsed_ticks(), 12);
+}
+
+#[test]
+fn delays_round_up_to_ticks() {
+    static WHEEL: Wheel<&'static str, 8, 4> = Wheel::new(Duration::from_millis(10));
+
+    WHEEL.insert_after(Duration::ZERO, "zero").unwrap();
+    WHEEL
+        .insert_after(Duration::from_millis(21), "twenty-one")
+        .unwrap();
+
+    WHEEL.tick();
+    assert_eq!(WHEEL.pop_expired(), Some("zero"));
+    WHEEL.tick();
+    assert_eq!(WHEEL.pop_expired(), None);
+    WHEEL.tick();
+    assert_eq!(WHEEL.pop_expired(), Some("twenty-one"));
+}
+
+#[test]
+fn full_bucket() {
+    static WHEEL: Wheel<usize, 2, 2> = Wheel::new(Duration::from_millis(1));
+
+    WHEEL.insert_after(Duration::from_millis(1), 1).unwrap();
+    WHEEL.insert_after(Duration::from_millis(3), 3).unwrap();
+    let err = WHEEL.insert_after(Duration::from_millis(5), 5).unwrap_err();
+    assert_eq!(err.into_inner(), 5);
+}
+
+#[tokio::test]
+async fn expired_wakes_waiter() {
+    static WHEEL: Wheel<usize, 8, 8> = Wheel::new(Duration::from_millis(1));
+
+    let waiter = tokio::spawn(async {
+        let mut items = Vec::new();
+        for _ in 0..3 {
+            items.push(WHEEL.expired().await);
+        }
+        items
+    });
+
+    for i in 0..3 {
+        WHEEL
+            .insert_after(Duration::from_millis(i as u64 + 1), i)
+            .unwrap();
+    }
+    for _ in 0..3 {
+        tokio::task::yield_now().await;
+        WHEEL.tick();
+    }
+
+    assert_eq!(waiter.await.unwrap(), vec![0, 1, 2]);
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "watchdog")]
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use thingbuf::{
+    mpsc::blocking,
+    watchdog::{self, HeldRef, RefKind},
+};
+
+#[test]
+fn reports_long_held_refs() {
+    let reports = Arc::new(Mutex::new(Vec::<HeldRef>::new()));
+    let test_thread = thread::current().id();
+    watchdog::set_threshold(Duration::from_millis(20));
+    watchdog::set_handler({
+        let reports = reports.clone();
+        move |held| {
+            if held.thread().id() == test_thread {
+                reports.lock().unwrap().push(held.clone());
+            }
+        }
+    });
+
+    let (tx, rx) = blocking::channel::<usize>(4);
+
+    // Guards released before the threshold aren't reported.
+    tx.send(1).unwrap();
+    assert_eq!(rx.recv(), Some(1));
+    assert_eq!(watchdog::check(), 0);
+    assert!(reports.lock().unwrap().is_empty());
+
+    // A guard that is still held is reported by `check`, but only once.
+    let send = tx.send_ref().unwrap();
+    thread::sleep(Duration::from_millis(40));
+    assert_eq!(watchdog::check(), 1);
+    assert_eq!(watchdog::check(), 1);
+    {
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].kind(), RefKind::Send);
+        assert!(!reports[0].is_released());
+        assert!(reports[0].held_for() >= Duration::from_millis(20));
+    }
+    drop(send);
+    assert_eq!(watchdog::check(), 0);
+    assert_eq!(reports.lock().unwrap().len(), 1);
+
+    // A guard that was never checked is reported when it's dropped.
+    let recv = rx.recv_ref().unwrap();
+    thread::sleep(Duration::from_millis(40));
+    drop(recv);
+    {
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[1].kind(), RefKind::Recv);
+        assert!(reports[1].is_released());
+    }
+
+    // Slots used by the `rt` handles are never tracked, as tracking them
+    // would take the watchdog's lock.
+    #[cfg(feature = "rt-safe")]
+    {
+        use thingbuf::mpsc::rt;
+
+        let tx = rt::Sender::new(tx);
+        let rx = rt::Receiver::new(rx);
+        tx.try_send_with(|msg| {
+            *msg = 2;
+            thread::sleep(Duration::from_millis(40));
+        })
+        .unwrap();
+        rx.try_recv_with(|msg| {
+            assert_eq!(*msg, 2);
+            thread::sleep(Duration::from_millis(40));
+        })
+        .unwrap();
+        assert_eq!(watchdog::check(), 0);
+        assert_eq!(reports.lock().unwrap().len(), 2);
+    }
+}